@@ -0,0 +1,250 @@
+use serde::Serialize;
+
+use super::report::describe_action;
+use super::runs::AgentRun;
+
+/// Outcome of one of the compared runs
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub run_id: String,
+    pub task: String,
+    pub task_completed: bool,
+    pub steps_taken: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl RunSummary {
+    fn of(run: &AgentRun) -> Self {
+        Self {
+            run_id: run.run_id.clone(),
+            task: run.task.clone(),
+            task_completed: run.result.task_completed,
+            steps_taken: run.result.steps.len(),
+            error: run.result.error.clone(),
+        }
+    }
+}
+
+/// A stretch where the runs took different actions, between two shared steps
+///
+/// Step numbers are where the stretch starts in each run; an empty action list
+/// means that run skipped the stretch (or ended before it).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepDivergence {
+    pub step_a: usize,
+    pub step_b: usize,
+    pub actions_a: Vec<String>,
+    pub actions_b: Vec<String>,
+}
+
+/// Two runs of a task aligned by their actions
+#[derive(Debug, Clone, Serialize)]
+pub struct RunComparison {
+    pub a: RunSummary,
+    pub b: RunSummary,
+
+    /// Both runs were given the same task text
+    pub same_task: bool,
+
+    /// Actions both runs took, in the same order
+    pub common_steps: usize,
+
+    /// Where the runs took different actions, in order
+    pub divergences: Vec<StepDivergence>,
+
+    /// Both runs completed, or both failed with the same error
+    pub same_outcome: bool,
+}
+
+impl RunComparison {
+    /// Align the runs on their longest common sequence of actions
+    pub fn between(a: &AgentRun, b: &AgentRun) -> Self {
+        // Compared as JSON so that every action field counts
+        let actions = |run: &AgentRun| -> Vec<serde_json::Value> {
+            run.result
+                .steps
+                .iter()
+                .map(|step| serde_json::to_value(&step.action_decided).unwrap_or_default())
+                .collect()
+        };
+        let (actions_a, actions_b) = (actions(a), actions(b));
+        let (n, m) = (actions_a.len(), actions_b.len());
+
+        // common[i][j]: longest common subsequence of actions_a[i..] and actions_b[j..]
+        let mut common = vec![vec![0usize; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                common[i][j] = if actions_a[i] == actions_b[j] {
+                    common[i + 1][j + 1] + 1
+                } else {
+                    common[i + 1][j].max(common[i][j + 1])
+                };
+            }
+        }
+
+        let describe = |run: &AgentRun, range: std::ops::Range<usize>| -> Vec<String> {
+            run.result.steps[range]
+                .iter()
+                .map(|step| describe_action(&step.action_decided))
+                .collect()
+        };
+
+        let mut divergences = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            let (start_a, start_b) = (i, j);
+            while i < n && j < m && actions_a[i] != actions_b[j] {
+                if common[i + 1][j] >= common[i][j + 1] {
+                    i += 1;
+                } else {
+                    j += 1;
+                }
+            }
+            if i == n || j == m {
+                // No shared action left: the rest of both runs diverges
+                (i, j) = (n, m);
+            }
+
+            if (i, j) != (start_a, start_b) {
+                divergences.push(StepDivergence {
+                    step_a: start_a + 1,
+                    step_b: start_b + 1,
+                    actions_a: describe(a, start_a..i),
+                    actions_b: describe(b, start_b..j),
+                });
+            }
+
+            // Skip the matching action
+            if i < n && j < m {
+                i += 1;
+                j += 1;
+            }
+        }
+
+        let same_outcome = a.result.task_completed == b.result.task_completed
+            && failure(a) == failure(b);
+
+        Self {
+            a: RunSummary::of(a),
+            b: RunSummary::of(b),
+            same_task: a.task == b.task,
+            common_steps: common[0][0],
+            divergences,
+            same_outcome,
+        }
+    }
+}
+
+/// Error of a failed run without the step prefix, which shifts when earlier steps differ
+fn failure(run: &AgentRun) -> Option<&str> {
+    let error = run.result.error.as_deref()?;
+    Some(match error.split_once(": ") {
+        Some((prefix, rest)) if prefix.starts_with("Failed at step") => rest,
+        _ => error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::executor::{ConversationStep, MultiStepExecutionResult};
+    use crate::models::{ActionRequest, ActionResponse, UIContext, Viewport};
+    use chrono::Utc;
+
+    fn run(urls: &[&str], error: Option<&str>) -> AgentRun {
+        let context = UIContext {
+            url: "http://localhost:3000".to_string(),
+            title: "Shop".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements: vec![],
+            navigation_history: vec![],
+            closed_overlays: vec![],
+            tabs: vec![],
+            focused: None,
+        };
+
+        let steps = urls
+            .iter()
+            .enumerate()
+            .map(|(i, url)| ConversationStep {
+                step_number: i + 1,
+                action_decided: ActionRequest::Navigate { url: url.to_string() },
+                action_result: ActionResponse::success(),
+                context_after: context.clone(),
+                llm_response: "{}".to_string(),
+                transition: None,
+                normalized: Vec::new(),
+                trace: None,
+                reflection: None,
+            })
+            .collect::<Vec<_>>();
+
+        let result = MultiStepExecutionResult {
+            task_completed: error.is_none(),
+            steps_taken: steps.len(),
+            max_steps: 20,
+            steps,
+            final_context: None,
+            error: error.map(str::to_string),
+            retries_count: 0,
+            consent_dismissed: vec![],
+            cancelled: false,
+            summary: None,
+            run_id: None,
+            downloads: Vec::new(),
+            plan: Vec::new(),
+            usage: Default::default(),
+        };
+
+        AgentRun::new("session-1", "Buy a lamp", Utc::now(), result)
+    }
+
+    #[test]
+    fn test_identical_runs_have_no_divergence() {
+        let comparison = RunComparison::between(&run(&["/a", "/b"], None), &run(&["/a", "/b"], None));
+        assert!(comparison.same_task);
+        assert!(comparison.same_outcome);
+        assert_eq!(comparison.common_steps, 2);
+        assert!(comparison.divergences.is_empty());
+    }
+
+    #[test]
+    fn test_divergence_between_shared_steps() {
+        let a = run(&["/a", "/b", "/c", "/d"], None);
+        let b = run(&["/a", "/x", "/y", "/c", "/d"], None);
+
+        let comparison = RunComparison::between(&a, &b);
+        assert_eq!(comparison.common_steps, 3);
+        assert_eq!(
+            comparison.divergences,
+            vec![StepDivergence {
+                step_a: 2,
+                step_b: 2,
+                actions_a: vec!["Navigate to /b".to_string()],
+                actions_b: vec!["Navigate to /x".to_string(), "Navigate to /y".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_different_failures_and_tails() {
+        let a = run(&["/a", "/b"], Some("Failed at step 3 after retries: element not found"));
+        let b = run(&["/a"], Some("Failed at step 2 after retries: element not found"));
+
+        let comparison = RunComparison::between(&a, &b);
+        // Same error, only the step differs
+        assert!(comparison.same_outcome);
+        assert_eq!(comparison.divergences.len(), 1);
+        assert_eq!(comparison.divergences[0].step_b, 2);
+        assert!(comparison.divergences[0].actions_b.is_empty());
+
+        let c = run(&["/a"], Some("Failed at step 2 after retries: timeout"));
+        assert!(!RunComparison::between(&b, &c).same_outcome);
+    }
+}
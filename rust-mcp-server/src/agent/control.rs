@@ -0,0 +1,233 @@
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::{watch, Mutex, OwnedMutexGuard};
+
+/// What an operator asked of a multi-step run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    Running,
+    /// Waits before its next step until resumed or cancelled
+    Paused,
+    /// Stops before its next step
+    Cancelled,
+}
+
+/// Cooperative pause and cancel switch of one multi-step run
+///
+/// The executor checks it between steps, so the step in progress always
+/// finishes and the page is never left halfway through an action.
+#[derive(Clone)]
+pub struct RunControl {
+    state: Arc<watch::Sender<RunState>>,
+
+    /// Held by the run during each step, and by whoever keeps it between steps
+    step: Arc<Mutex<()>>,
+}
+
+impl RunControl {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(watch::Sender::new(RunState::Running)),
+            step: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn state(&self) -> RunState {
+        *self.state.borrow()
+    }
+
+    /// Pause a running run; returns the state afterwards
+    pub fn pause(&self) -> RunState {
+        self.transition(RunState::Running, RunState::Paused)
+    }
+
+    /// Resume a paused run; returns the state afterwards
+    pub fn resume(&self) -> RunState {
+        self.transition(RunState::Paused, RunState::Running)
+    }
+
+    /// Stop the run before its next step, also when paused
+    pub fn cancel(&self) -> RunState {
+        self.state.send_replace(RunState::Cancelled);
+        RunState::Cancelled
+    }
+
+    fn transition(&self, from: RunState, to: RunState) -> RunState {
+        self.state.send_if_modified(|state| {
+            let change = *state == from;
+            if change {
+                *state = to;
+            }
+            change
+        });
+        self.state()
+    }
+
+    /// Wait while paused; `false` once the run is cancelled
+    pub async fn proceed(&self) -> bool {
+        let mut state = self.state.subscribe();
+        // The sender lives as long as `self`, so waiting can't fail
+        let running = match state.wait_for(|state| *state != RunState::Paused).await {
+            Ok(state) => *state == RunState::Running,
+            Err(_) => false,
+        };
+        running
+    }
+
+    /// Wait while paused or held, then keep the step lock for the coming step;
+    /// `None` once the run is cancelled
+    pub async fn step(&self) -> Option<OwnedMutexGuard<()>> {
+        loop {
+            if !self.proceed().await {
+                return None;
+            }
+            let step = Arc::clone(&self.step).lock_owned().await;
+            // Paused again while waiting for the lock
+            if self.state() == RunState::Running {
+                return Some(step);
+            }
+        }
+    }
+
+    /// Keep the run from starting another step until the guard drops
+    ///
+    /// Waits for the step in progress to finish.
+    pub async fn hold(&self) -> OwnedMutexGuard<()> {
+        Arc::clone(&self.step).lock_owned().await
+    }
+}
+
+impl Default for RunControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Controls of the multi-step runs in progress, keyed by session_id
+pub struct RunControls {
+    controls: DashMap<String, RunControl>,
+}
+
+impl RunControls {
+    pub fn new() -> Self {
+        Self {
+            controls: DashMap::new(),
+        }
+    }
+
+    /// Make `control` the one of the run starting on the session; replaces the one of an earlier run
+    ///
+    /// The control is forgotten when the returned guard drops.
+    pub fn register(&self, session_id: &str, control: RunControl) -> ActiveRun<'_> {
+        self.controls.insert(session_id.to_string(), control.clone());
+        ActiveRun {
+            controls: self,
+            session_id: session_id.to_string(),
+            control,
+        }
+    }
+
+    pub fn get(&self, session_id: &str) -> Option<RunControl> {
+        self.controls.get(session_id).map(|control| control.clone())
+    }
+
+    /// Forget the control once its run ended, unless a newer run took its place
+    fn remove(&self, session_id: &str, control: &RunControl) {
+        self.controls
+            .remove_if(session_id, |_, current| Arc::ptr_eq(&current.state, &control.state));
+    }
+}
+
+impl Default for RunControls {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A registered run; dropping it (also when the run is aborted) unregisters the control
+pub struct ActiveRun<'a> {
+    controls: &'a RunControls,
+    session_id: String,
+    control: RunControl,
+}
+
+impl ActiveRun<'_> {
+    pub fn control(&self) -> RunControl {
+        self.control.clone()
+    }
+}
+
+impl Drop for ActiveRun<'_> {
+    fn drop(&mut self) {
+        self.controls.remove(&self.session_id, &self.control);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_pause_resume_cancel() {
+        let control = RunControl::new();
+        assert!(control.proceed().await);
+
+        assert_eq!(control.pause(), RunState::Paused);
+        let waiting = tokio::time::timeout(Duration::from_millis(50), control.proceed()).await;
+        assert!(waiting.is_err());
+
+        let waiter = {
+            let control = control.clone();
+            tokio::spawn(async move { control.proceed().await })
+        };
+        assert_eq!(control.resume(), RunState::Running);
+        assert!(waiter.await.unwrap());
+
+        // Cancelling wakes a paused run, which then stops
+        control.pause();
+        let waiter = {
+            let control = control.clone();
+            tokio::spawn(async move { control.proceed().await })
+        };
+        assert_eq!(control.cancel(), RunState::Cancelled);
+        assert!(!waiter.await.unwrap());
+
+        // A cancelled run stays cancelled
+        assert_eq!(control.resume(), RunState::Cancelled);
+        assert_eq!(control.pause(), RunState::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_held_run_waits_before_its_next_step() {
+        let control = RunControl::new();
+        let hold = control.hold().await;
+
+        let waiting = tokio::time::timeout(Duration::from_millis(50), control.step()).await;
+        assert!(waiting.is_err());
+
+        drop(hold);
+        let step = control.step().await;
+        assert!(step.is_some());
+        // A step in progress keeps others from holding the run
+        assert!(tokio::time::timeout(Duration::from_millis(50), control.hold()).await.is_err());
+        drop(step);
+
+        control.cancel();
+        assert!(control.step().await.is_none());
+    }
+
+    #[test]
+    fn test_newer_run_keeps_its_control() {
+        let controls = RunControls::new();
+        let first = controls.register("session-1", RunControl::new());
+        let second = controls.register("session-1", RunControl::new());
+
+        drop(first);
+        assert!(controls.get("session-1").is_some());
+        drop(second);
+        assert!(controls.get("session-1").is_none());
+    }
+}
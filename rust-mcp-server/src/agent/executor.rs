@@ -1,16 +1,89 @@
 use anyhow::Result;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+use ui_core::prompt::{
+    build_keyboard_only_system_prompt, build_neighborhood_hint, build_reflection_prompt, build_retry_prompt, build_system_prompt,
+    build_user_prompt, diff_prompt_instructions, element_line, evaluate_prompt_instructions, success_criteria_instructions,
+    vision_prompt_instructions,
+};
+use ui_core::lenient::{parse_action, LenientAction};
+use ui_core::transition::PageTransition;
+
+use crate::browser::{
+    overlay_auto_close_from_env, BrowserAutomation, ConsentDismissal, ConsentPolicy, ContextExtractor,
+};
+use crate::features::Feature;
+use crate::models::{
+    observed_action, ActionRequest, ActionResponse, Assertion, DownloadArtifact, ErrorCode, ScreenshotFormat, SessionEventKind,
+    UIContext,
+};
+use crate::privacy::PiiScrubber;
+
+use super::control::RunControl;
+use super::llm_client::{ChatTurn, LLMClient, TokenUsage};
+use super::prompt_diff::{PromptDiffPolicy, PromptDiffer};
+use super::trace::{StepTrace, Tracer};
+
+/// Step limit of multi-step runs when the request sets none
+pub const DEFAULT_MAX_STEPS: usize = 20;
+
+/// Retries per failed step when the request sets none
+pub const DEFAULT_MAX_RETRIES_PER_STEP: usize = 3;
+
+/// JPEG quality of the screenshots sent to vision models; keeps the image small
+const VISION_SCREENSHOT_QUALITY: u8 = 70;
+
+/// Elements of the final page shown to the LLM when summarizing a run
+const SUMMARY_MAX_ELEMENTS: usize = 80;
+
+/// Whether multi-step runs are summarized by the LLM when the request doesn't say (RUN_SUMMARY, default: false)
+pub fn run_summary_from_env() -> bool {
+    std::env::var("RUN_SUMMARY").is_ok_and(|v| v.eq_ignore_ascii_case("true") || v == "1")
+}
 
-use crate::browser::{BrowserAutomation, ContextExtractor};
-use crate::models::{ActionRequest, ActionResponse, UIContext};
+/// Whether multi-step runs start with an LLM-written plan when the request doesn't say (RUN_PLAN, default: false)
+pub fn run_plan_from_env() -> bool {
+    std::env::var("RUN_PLAN").is_ok_and(|v| v.eq_ignore_ascii_case("true") || v == "1")
+}
 
-use super::llm_client::LLMClient;
-use super::prompt::{build_retry_prompt, build_system_prompt, build_user_prompt};
+/// Whether the agent reflects on repeated failures of a step (AGENT_REFLECTION, default: true)
+fn reflection_from_env() -> bool {
+    std::env::var("AGENT_REFLECTION").map_or(true, |v| !(v.eq_ignore_ascii_case("false") || v == "0"))
+}
 
 /// Agent executor for single-step autonomous execution (Step 2)
 pub struct AgentExecutor {
     llm_client: LLMClient,
+    consent_policy: ConsentPolicy,
+    auto_close_overlays: bool,
+    prompt_diff: PromptDiffPolicy,
+    /// Receives each completed step of multi-step runs
+    step_listener: Option<UnboundedSender<ConversationStep>>,
+    /// Pause and cancel switch checked between the steps of multi-step runs
+    control: Option<RunControl>,
+    /// Attach a viewport screenshot to each page state (needs a multimodal model)
+    use_vision: bool,
+    /// Removes personal data from page content before the LLM or a stored run sees it
+    pii_scrubber: Option<PiiScrubber>,
+    tracer: Option<Tracer>,
+    /// Ask the LLM what went wrong when a step fails more than once, and retry with its answer
+    reflection: bool,
+    /// Gated features the server has on; actions needing any other are turned away
+    features: Vec<Feature>,
+    /// Success criteria of multi-step runs, checked after each step instead of asking the LLM
+    assertions: Vec<Assertion>,
+}
+
+/// Prompts for the first attempt of a step
+struct StepPrompt<'a> {
+    system: &'a str,
+    /// Earlier turns the user prompt builds on
+    history: &'a [ChatTurn],
+    user: &'a str,
+    /// Viewport the user prompt describes, as a data URL
+    screenshot: Option<&'a str>,
 }
 
 /// Response from agent execution
@@ -33,6 +106,10 @@ pub struct AgentExecutionResult {
 
     /// Raw LLM response for debugging
     pub llm_response: Option<String>,
+
+    /// Consent banners handled before the step
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub consent_dismissed: Vec<ConsentDismissal>,
 }
 
 /// Step in conversation history for multi-step execution
@@ -43,6 +120,55 @@ pub struct ConversationStep {
     pub action_result: ActionResponse,
     pub context_after: UIContext,
     pub llm_response: String,
+
+    /// How the page changed from before the action to `context_after`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transition: Option<PageTransition>,
+
+    /// Rewrites that made the LLM's answer a valid action, e.g. "renamed `value` to `text`"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub normalized: Vec<String>,
+
+    /// Screenshots and DOM snapshot of the step, when the run is traced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<StepTrace>,
+
+    /// The agent's reflection on the failed attempts that preceded this action, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reflection: Option<String>,
+}
+
+/// The LLM's account of why a step keeps failing (see `build_reflection_prompt`)
+#[derive(Debug, Clone, Deserialize)]
+struct Reflection {
+    wrong_assumption: String,
+    alternative: String,
+}
+
+impl Reflection {
+    /// Suggestion for the next retry prompt
+    fn suggestion(&self) -> String {
+        format!(
+            "Your attempts relied on a wrong assumption: {} Try instead: {}",
+            self.wrong_assumption.trim(),
+            self.alternative.trim()
+        )
+    }
+}
+
+/// Plain-language account of a finished run, written by the LLM for end users
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSummary {
+    /// What was done and how it ended, in a few sentences
+    pub summary: String,
+
+    /// Data the run found on the pages, e.g. "Order #1042 total: $120.00"
+    #[serde(default)]
+    pub findings: Vec<String>,
+
+    /// Anything unexpected: errors, retries, surprising page states
+    #[serde(default)]
+    pub anomalies: Vec<String>,
 }
 
 /// Result from multi-step execution (Step 3: Feedback Loop)
@@ -68,6 +194,34 @@ pub struct MultiStepExecutionResult {
 
     /// Number of retries performed
     pub retries_count: usize,
+
+    /// Consent banners handled before the first step
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub consent_dismissed: Vec<ConsentDismissal>,
+
+    /// Whether an operator cancelled the run
+    #[serde(default)]
+    pub cancelled: bool,
+
+    /// LLM-written summary, when requested (see `AgentExecutor::summarize`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<RunSummary>,
+
+    /// ID under which the run was stored (set by the API layer)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+
+    /// Files downloaded during the run, kept with it (set by the API layer)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub downloads: Vec<DownloadArtifact>,
+
+    /// Steps the LLM expected the task to take, when requested (see `AgentExecutor::plan`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plan: Vec<String>,
+
+    /// Tokens the run's LLM calls used, planning and summary included
+    #[serde(default, skip_serializing_if = "TokenUsage::is_empty")]
+    pub usage: TokenUsage,
 }
 
 impl AgentExecutor {
@@ -75,6 +229,187 @@ impl AgentExecutor {
     pub fn new() -> Self {
         Self {
             llm_client: LLMClient::new(),
+            consent_policy: ConsentPolicy::from_env(),
+            auto_close_overlays: overlay_auto_close_from_env(),
+            prompt_diff: PromptDiffPolicy::from_env(),
+            step_listener: None,
+            control: None,
+            use_vision: false,
+            pii_scrubber: None,
+            tracer: None,
+            reflection: reflection_from_env(),
+            features: Vec::new(),
+            assertions: Vec::new(),
+        }
+    }
+
+    /// Override the consent banner policy (defaults to CONSENT_POLICY)
+    pub fn with_consent_policy(mut self, policy: ConsentPolicy) -> Self {
+        self.consent_policy = policy;
+        self
+    }
+
+    /// Enable or disable automatic closing of newsletter/chat overlays (defaults to OVERLAY_AUTO_CLOSE)
+    pub fn with_overlay_auto_close(mut self, enabled: bool) -> Self {
+        self.auto_close_overlays = enabled;
+        self
+    }
+
+    /// Send only changed elements after the first step of multi-step runs (defaults to PROMPT_DIFF)
+    pub fn with_prompt_diff(mut self, enabled: bool) -> Self {
+        self.prompt_diff = self.prompt_diff.with_enabled(enabled);
+        self
+    }
+
+    /// Send each step of multi-step runs to `listener` as soon as it is recorded
+    pub fn with_step_listener(mut self, listener: UnboundedSender<ConversationStep>) -> Self {
+        self.step_listener = Some(listener);
+        self
+    }
+
+    /// Show the LLM a screenshot of the viewport next to the element list
+    pub fn with_vision(mut self, enabled: bool) -> Self {
+        self.use_vision = enabled;
+        self
+    }
+
+    /// Allow the actions of these gated features, e.g. `evaluate`
+    pub fn with_features(mut self, features: Vec<Feature>) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Finish multi-step runs once all of these hold, rather than when the LLM judges the task done
+    pub fn with_assertions(mut self, assertions: Vec<Assertion>) -> Self {
+        self.assertions = assertions;
+        self
+    }
+
+    /// Let operators pause, resume or cancel multi-step runs through `control`
+    pub fn with_run_control(mut self, control: RunControl) -> Self {
+        self.control = Some(control);
+        self
+    }
+
+    /// Scrub personal data from every context and action result of the run
+    ///
+    /// Screenshots can't be scrubbed, so vision is off while a scrubber is set.
+    pub fn with_pii_scrubber(mut self, scrubber: Option<PiiScrubber>) -> Self {
+        self.pii_scrubber = scrubber;
+        self
+    }
+
+    /// Capture screenshots (and DOM snapshots, if the tracer takes them) around each step of multi-step runs
+    pub fn with_tracer(mut self, tracer: Option<Tracer>) -> Self {
+        self.tracer = tracer;
+        self
+    }
+
+    /// Reflect on repeated failures of a step before retrying it (defaults to AGENT_REFLECTION)
+    pub fn with_reflection(mut self, enabled: bool) -> Self {
+        self.reflection = enabled;
+        self
+    }
+
+    /// Current page state, scrubbed if PII scrubbing is on
+    async fn read_context(&self, browser: &BrowserAutomation) -> Result<UIContext> {
+        let mut context = browser.extract_context().await?;
+        if let Some(scrubber) = &self.pii_scrubber {
+            scrubber.scrub_context(&mut context);
+        }
+        Ok(context)
+    }
+
+    /// Execute `action`, scrubbing the page text its feedback may quote
+    async fn run_action(&self, browser: &BrowserAutomation, action: &ActionRequest) -> Result<ActionResponse> {
+        if let Some(feature) = Feature::for_action(action).filter(|feature| !self.features.contains(feature)) {
+            return Ok(feature.disabled_feedback());
+        }
+
+        let mut response = browser.execute_action(action).await?;
+        if let Some(scrubber) = &self.pii_scrubber {
+            scrubber.scrub_response(&mut response);
+        }
+        Ok(response)
+    }
+
+    /// JavaScript errors the page logged since `mark`, scrubbed if PII scrubbing is on
+    async fn page_errors(&self, browser: &BrowserAutomation, mark: u64) -> Vec<String> {
+        browser
+            .console()
+            .errors_since(mark)
+            .await
+            .iter()
+            .map(|entry| {
+                let summary = entry.summary();
+                match &self.pii_scrubber {
+                    Some(scrubber) => scrubber.scrub(&summary),
+                    None => summary,
+                }
+            })
+            .collect()
+    }
+
+    /// Close obstructing overlays before a step (they can appear at any time)
+    async fn close_overlays(&self, browser: &Arc<BrowserAutomation>) {
+        if !self.auto_close_overlays {
+            return;
+        }
+
+        if let Err(e) = browser.close_overlays().await {
+            tracing::warn!("Agent: Overlay auto-close failed: {}", e);
+        }
+    }
+
+    /// Keyboard-only sessions get a prompt without the mouse actions
+    fn system_prompt(&self, browser: &BrowserAutomation) -> String {
+        let mut prompt = if browser.keyboard_only() {
+            build_keyboard_only_system_prompt()
+        } else {
+            build_system_prompt()
+        };
+        if self.use_vision {
+            prompt.push_str(vision_prompt_instructions());
+        }
+        if self.features.contains(&Feature::Evaluate) {
+            prompt.push_str(evaluate_prompt_instructions());
+        }
+        prompt
+    }
+
+    /// Viewport as a JPEG data URL when vision is on; `None` if off or the capture failed
+    ///
+    /// Interactive elements carry Set-of-Mark boxes labeled with their context IDs.
+    async fn viewport_screenshot(&self, browser: &BrowserAutomation, context: &UIContext) -> Option<String> {
+        if !self.use_vision || self.pii_scrubber.is_some() {
+            return None;
+        }
+
+        let ids = ContextExtractor::set_of_mark_ids(context);
+        match browser
+            .set_of_mark_screenshot(&ids, ScreenshotFormat::Jpeg, Some(VISION_SCREENSHOT_QUALITY))
+            .await
+        {
+            Ok(image) => Some(format!(
+                "data:image/jpeg;base64,{}",
+                base64::engine::general_purpose::STANDARD.encode(image)
+            )),
+            Err(e) => {
+                // The element list alone still lets the step run
+                tracing::warn!("Agent: Screenshot for the LLM failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Pre-processing pass before the first step: dismiss consent banners
+    async fn prepare_page(&self, browser: &Arc<BrowserAutomation>) -> Vec<ConsentDismissal> {
+        match browser.dismiss_consent_banners(self.consent_policy).await {
+            Ok(dismissed) => dismissed,
+            Err(e) => {
+                tracing::warn!("Agent: Consent banner pass failed: {}", e);
+                Vec::new()
+            }
         }
     }
 
@@ -91,11 +426,13 @@ impl AgentExecutor {
         browser: &Arc<BrowserAutomation>,
         task: &str,
     ) -> Result<AgentExecutionResult> {
+        let consent_dismissed = self.prepare_page(browser).await;
+        self.close_overlays(browser).await;
+
         // Step 1: Get current UI context
         tracing::info!("Agent: Extracting UI context for task: {}", task);
 
-        let page = browser.get_page().await;
-        let context = match ContextExtractor::extract(page).await {
+        let context = match self.read_context(browser).await {
             Ok(ctx) => ctx,
             Err(e) => {
                 return Ok(AgentExecutionResult {
@@ -105,6 +442,7 @@ impl AgentExecutor {
                     current_context: None,
                     error: Some(format!("Failed to extract context: {}", e)),
                     llm_response: None,
+                    consent_dismissed,
                 });
             }
         };
@@ -116,14 +454,15 @@ impl AgentExecutor {
         );
 
         // Step 2: Build prompts and get LLM decision
-        let system_prompt = build_system_prompt();
-        let user_prompt = build_user_prompt(&context, task);
+        let system_prompt = self.system_prompt(browser);
+        let user_prompt = build_user_prompt(&context, task, self.llm_client.budget().max_prompt_tokens);
+        let screenshot = self.viewport_screenshot(browser, &context).await;
 
         tracing::debug!("Agent: Sending prompt to LLM");
 
         let llm_response = match self
             .llm_client
-            .generate_json(&system_prompt, &user_prompt)
+            .generate_json_with_screenshot(&system_prompt, &[], &user_prompt, screenshot.as_deref())
             .await
         {
             Ok(response) => response,
@@ -135,6 +474,7 @@ impl AgentExecutor {
                     current_context: Some(context),
                     error: Some(format!("LLM generation failed: {}", e)),
                     llm_response: None,
+                    consent_dismissed,
                 });
             }
         };
@@ -142,8 +482,13 @@ impl AgentExecutor {
         tracing::info!("Agent: LLM response: {}", llm_response);
 
         // Step 3: Parse LLM response into action
-        let action: ActionRequest = match serde_json::from_str(&llm_response) {
-            Ok(action) => action,
+        let action = match parse_action(&llm_response) {
+            Ok(LenientAction { action, normalized }) => {
+                if !normalized.is_empty() {
+                    tracing::info!("Agent: Normalized LLM action: {}", normalized.join("; "));
+                }
+                action
+            }
             Err(e) => {
                 return Ok(AgentExecutionResult {
                     success: false,
@@ -152,6 +497,7 @@ impl AgentExecutor {
                     current_context: Some(context),
                     error: Some(format!("Failed to parse LLM response as action: {}", e)),
                     llm_response: Some(llm_response),
+                    consent_dismissed,
                 });
             }
         };
@@ -159,7 +505,7 @@ impl AgentExecutor {
         tracing::info!("Agent: Decided action: {:?}", action);
 
         // Step 4: Execute the action
-        let action_result = match browser.execute_action(&action).await {
+        let action_result = match self.run_action(browser, &action).await {
             Ok(result) => result,
             Err(e) => {
                 return Ok(AgentExecutionResult {
@@ -169,6 +515,7 @@ impl AgentExecutor {
                     current_context: Some(context),
                     error: Some(format!("Action execution failed: {}", e)),
                     llm_response: Some(llm_response),
+                    consent_dismissed,
                 });
             }
         };
@@ -176,8 +523,7 @@ impl AgentExecutor {
         tracing::info!("Agent: Action result: {:?}", action_result);
 
         // Step 5: Get updated context
-        let page = browser.get_page().await;
-        let updated_context = ContextExtractor::extract(page).await.ok();
+        let updated_context = self.read_context(browser).await.ok();
 
         Ok(AgentExecutionResult {
             success: action_result.success,
@@ -186,6 +532,7 @@ impl AgentExecutor {
             current_context: updated_context,
             error: None,
             llm_response: Some(llm_response),
+            consent_dismissed,
         })
     }
 
@@ -211,8 +558,8 @@ impl AgentExecutor {
         max_steps: Option<usize>,
         max_retries_per_step: Option<usize>,
     ) -> Result<MultiStepExecutionResult> {
-        let max_steps = max_steps.unwrap_or(20);
-        let max_retries_per_step = max_retries_per_step.unwrap_or(3);
+        let max_steps = max_steps.unwrap_or(DEFAULT_MAX_STEPS);
+        let max_retries_per_step = max_retries_per_step.unwrap_or(DEFAULT_MAX_RETRIES_PER_STEP);
         let mut steps: Vec<ConversationStep> = Vec::new();
         let mut total_retries = 0;
 
@@ -223,12 +570,53 @@ impl AgentExecutor {
             max_retries_per_step
         );
 
+        let consent_dismissed = self.prepare_page(browser).await;
+
+        let mut system_prompt = self.system_prompt(browser);
+        if self.prompt_diff.enabled {
+            system_prompt.push_str(diff_prompt_instructions());
+        }
+        if !self.assertions.is_empty() {
+            system_prompt.push_str(&success_criteria_instructions(&self.assertions));
+        }
+        let mut differ =
+            PromptDiffer::new(self.prompt_diff).with_max_tokens(self.llm_client.budget().max_prompt_tokens);
+        let mut last_transition: Option<PageTransition> = None;
+
         for step_num in 1..=max_steps {
+            // Waits here while paused or held by a task that took the session over
+            let _step = match &self.control {
+                Some(control) => match control.step().await {
+                    Some(step) => Some(step),
+                    None => {
+                        tracing::info!("Agent: Run cancelled before step {}", step_num);
+                        return Ok(MultiStepExecutionResult {
+                            task_completed: false,
+                            steps_taken: steps.len(),
+                            max_steps,
+                            steps,
+                            final_context: self.read_context(browser).await.ok(),
+                            error: Some(format!("Cancelled by operator before step {}", step_num)),
+                            retries_count: total_retries,
+                            consent_dismissed,
+                            cancelled: true,
+                            summary: None,
+                            run_id: None,
+                            downloads: Vec::new(),
+                            plan: Vec::new(),
+                            usage: self.llm_client.usage(),
+                        });
+                    }
+                },
+                None => None,
+            };
+
             tracing::info!("Agent: Step {}/{}", step_num, max_steps);
 
+            self.close_overlays(browser).await;
+
             // Extract current UI context
-            let page = browser.get_page().await;
-            let context = match ContextExtractor::extract(page).await {
+            let context = match self.read_context(browser).await {
                 Ok(ctx) => ctx,
                 Err(e) => {
                     return Ok(MultiStepExecutionResult {
@@ -239,25 +627,60 @@ impl AgentExecutor {
                         final_context: None,
                         error: Some(format!("Failed to extract context at step {}: {}", step_num, e)),
                         retries_count: total_retries,
+                        consent_dismissed,
+                        cancelled: false,
+                        summary: None,
+                        run_id: None,
+                        downloads: Vec::new(),
+                        plan: Vec::new(),
+                        usage: self.llm_client.usage(),
                     });
                 }
             };
 
             // Build prompt and get LLM decision
-            let system_prompt = build_system_prompt();
-            let user_prompt = build_user_prompt(&context, task);
+            let mut user_prompt = differ.user_prompt(&context, task);
+            if let Some(transition) = &last_transition {
+                user_prompt = format!("Outcome of your last action: {}\n\n{}", transition.describe(), user_prompt);
+            }
+            let screenshot = self.viewport_screenshot(browser, &context).await;
+            let before_screenshot = match &self.tracer {
+                Some(tracer) => tracer.screenshot(browser, self.pii_scrubber.as_ref(), step_num, "before").await,
+                None => None,
+            };
 
-            let (action, llm_response) = match self.try_action_with_retry(
+            let (LenientAction { action, normalized }, llm_response, action_result, reflection) = match self.try_action_with_retry(
                 browser,
                 &context,
                 task,
-                &system_prompt,
-                &user_prompt,
+                StepPrompt {
+                    system: &system_prompt,
+                    history: differ.history(),
+                    user: &user_prompt,
+                    screenshot: screenshot.as_deref(),
+                },
                 max_retries_per_step,
             ).await {
-                Ok((act, resp, retries)) => {
+                Ok((act, resp, retries, result, reflection)) => {
                     total_retries += retries;
-                    (act, resp)
+                    // Full page states that crowd the context window degrade answers silently
+                    let prompt_tokens = self.llm_client.last_prompt_tokens();
+                    if !differ.enabled() && self.llm_client.budget().is_near_limit(prompt_tokens) {
+                        tracing::warn!(
+                            "Agent: Prompt of step {} used {} tokens, switching to diff prompts",
+                            step_num,
+                            prompt_tokens
+                        );
+                        differ.enable();
+                        system_prompt.push_str(diff_prompt_instructions());
+                    }
+                    // Needing retries suggests the model lost track of the page: start over with a full state
+                    if retries == 0 {
+                        differ.record(user_prompt, resp.clone());
+                    } else {
+                        differ.reset();
+                    }
+                    (act, resp, result, reflection)
                 }
                 Err(e) => {
                     return Ok(MultiStepExecutionResult {
@@ -268,13 +691,19 @@ impl AgentExecutor {
                         final_context: Some(context),
                         error: Some(format!("Failed at step {} after retries: {}", step_num, e)),
                         retries_count: total_retries,
+                        consent_dismissed,
+                        cancelled: false,
+                        summary: None,
+                        run_id: None,
+                        downloads: Vec::new(),
+                        plan: Vec::new(),
+                        usage: self.llm_client.usage(),
                     });
                 }
             };
 
             // Get updated context after action
-            let page = browser.get_page().await;
-            let context_after = match ContextExtractor::extract(page).await {
+            let context_after = match self.read_context(browser).await {
                 Ok(ctx) => ctx,
                 Err(e) => {
                     tracing::warn!("Failed to extract context after action: {}", e);
@@ -282,23 +711,53 @@ impl AgentExecutor {
                 }
             };
 
+            let transition = PageTransition::between(&context, &context_after);
+            tracing::info!("Agent: Step {} outcome: {}", step_num, transition.describe());
+
+            let trace = match &self.tracer {
+                Some(tracer) => Some(StepTrace {
+                    before_screenshot,
+                    after_screenshot: tracer.screenshot(browser, self.pii_scrubber.as_ref(), step_num, "after").await,
+                    dom_snapshot: tracer.dom_snapshot(browser, self.pii_scrubber.as_ref(), step_num).await,
+                }),
+                None => None,
+            };
+
             // Record this step
             steps.push(ConversationStep {
                 step_number: step_num,
                 action_decided: action.clone(),
                 action_result: ActionResponse {
-                    success: true,
-                    error: None,
                     reason: Some("Action executed successfully".to_string()),
-                    suggestion: None,
-                    details: None,
+                    ..action_result
                 },
                 context_after: context_after.clone(),
                 llm_response: llm_response.clone(),
+                transition: Some(transition.clone()),
+                normalized,
+                trace,
+                reflection,
             });
+            let events = browser.events();
+            if let (true, Some(step)) = (events.has_subscribers(), steps.last()) {
+                events.broadcast(SessionEventKind::AgentStep {
+                    step_number: step_num,
+                    action: observed_action(&action, step.action_result.is_secret()),
+                    context: context_after.compact(),
+                });
+            }
+            if let (Some(listener), Some(step)) = (&self.step_listener, steps.last()) {
+                // The listener going away (e.g. a closed stream) doesn't stop the run
+                let _ = listener.send(step.clone());
+            }
 
             // Check if task is complete
-            if self.is_task_complete(&context_after, task, &steps).await? {
+            let completed = if self.assertions.is_empty() {
+                self.is_task_complete(&context_after, task, &steps).await?
+            } else {
+                self.failed_assertions(browser).await?.is_empty()
+            };
+            if completed {
                 tracing::info!("Agent: Task completed successfully at step {}", step_num);
                 return Ok(MultiStepExecutionResult {
                     task_completed: true,
@@ -308,15 +767,28 @@ impl AgentExecutor {
                     final_context: Some(context_after),
                     error: None,
                     retries_count: total_retries,
+                    consent_dismissed,
+                    cancelled: false,
+                    summary: None,
+                    run_id: None,
+                    downloads: Vec::new(),
+                    plan: Vec::new(),
+                    usage: self.llm_client.usage(),
                 });
             }
 
             tracing::info!("Agent: Task not yet complete, continuing...");
+            last_transition = Some(transition);
         }
 
         // Reached max steps without completion
-        let final_page = browser.get_page().await;
-        let final_context = ContextExtractor::extract(final_page).await.ok();
+        let final_context = self.read_context(browser).await.ok();
+        let mut error = format!("Reached maximum steps ({}) without completing task", max_steps);
+        if let Ok(failed) = self.failed_assertions(browser).await {
+            if !failed.is_empty() {
+                error.push_str(&format!("; still failing: {}", failed.join("; ")));
+            }
+        }
 
         Ok(MultiStepExecutionResult {
             task_completed: false,
@@ -324,26 +796,32 @@ impl AgentExecutor {
             max_steps,
             steps,
             final_context,
-            error: Some(format!(
-                "Reached maximum steps ({}) without completing task",
-                max_steps
-            )),
+            error: Some(error),
             retries_count: total_retries,
+            consent_dismissed,
+            cancelled: false,
+            summary: None,
+            run_id: None,
+            downloads: Vec::new(),
+            plan: Vec::new(),
+            usage: self.llm_client.usage(),
         })
     }
 
     /// Try to execute an action with retry mechanism
-    /// Returns (ActionRequest, LLM response, retry_count)
+    /// Returns (parsed action, LLM response, retry_count, action result, reflection behind the action)
     async fn try_action_with_retry(
         &self,
         browser: &Arc<BrowserAutomation>,
         context: &UIContext,
         task: &str,
-        system_prompt: &str,
-        initial_user_prompt: &str,
+        prompt: StepPrompt<'_>,
         max_retries: usize,
-    ) -> Result<(ActionRequest, String, usize)> {
-        let mut current_prompt = initial_user_prompt.to_string();
+    ) -> Result<(LenientAction, String, usize, ActionResponse, Option<String>)> {
+        let mut current_prompt = prompt.user.to_string();
+        // Failed actions of this step with their errors, for reflection
+        let mut attempts: Vec<(String, String)> = Vec::new();
+        let mut reflection: Option<String> = None;
 
         for retry in 0..=max_retries {
             if retry > 0 {
@@ -353,12 +831,12 @@ impl AgentExecutor {
             // Get LLM decision
             let llm_response = self
                 .llm_client
-                .generate_json(system_prompt, &current_prompt)
+                .generate_json_with_screenshot(prompt.system, prompt.history, &current_prompt, prompt.screenshot)
                 .await?;
 
-            // Parse action
-            let action: ActionRequest = match serde_json::from_str(&llm_response) {
-                Ok(act) => act,
+            // Parse action, forgiving near misses of the schema
+            let parsed = match parse_action(&llm_response) {
+                Ok(parsed) => parsed,
                 Err(e) => {
                     if retry < max_retries {
                         tracing::warn!("Failed to parse LLM response, will retry: {}", e);
@@ -367,30 +845,39 @@ impl AgentExecutor {
                     return Err(anyhow::anyhow!("Failed to parse LLM response: {}", e));
                 }
             };
+            if !parsed.normalized.is_empty() {
+                tracing::info!("Agent: Normalized LLM action: {}", parsed.normalized.join("; "));
+            }
+            let action = &parsed.action;
 
             tracing::info!("Agent: Attempting action: {:?}", action);
 
             // Execute action
-            match browser.execute_action(&action).await {
+            let console_mark = browser.console().mark().await;
+            match self.run_action(browser, action).await {
                 Ok(result) => {
                     if result.success {
                         tracing::info!("Agent: Action succeeded");
-                        return Ok((action, llm_response, retry));
+                        return Ok((parsed, llm_response, retry, result, reflection));
                     } else {
                         // Action executed but returned failure
-                        let error_msg = result.error.as_ref()
-                            .or(result.reason.as_ref())
-                            .map(|s| s.as_str())
+                        let error_msg = result.error.map(ErrorCode::name)
+                            .or(result.reason.as_deref())
                             .unwrap_or("Action failed");
                         tracing::warn!("Agent: Action failed: {}", error_msg);
                         if retry < max_retries {
                             // Build retry prompt with feedback
-                            let action_str = serde_json::to_string(&action)
+                            let action_str = serde_json::to_string(action)
                                 .unwrap_or_else(|_| format!("{:?}", action));
-                            let suggestion = result
+                            attempts.push((action_str.clone(), error_msg.to_string()));
+                            let mut suggestion = result
                                 .suggestion
                                 .clone()
                                 .unwrap_or_else(|| "Try a different approach".to_string());
+                            if let Some(reflected) = self.reflect(context, task, &attempts, &suggestion).await {
+                                suggestion = reflected.clone();
+                                reflection = Some(reflected);
+                            }
 
                             current_prompt = build_retry_prompt(
                                 context,
@@ -398,8 +885,16 @@ impl AgentExecutor {
                                 &action_str,
                                 error_msg,
                                 &suggestion,
+                                &self.page_errors(browser, console_mark).await,
+                                self.llm_client.budget().max_prompt_tokens,
                             );
 
+                            // Repeated failures on a target: show candidate surroundings
+                            if retry >= 1 {
+                                current_prompt
+                                    .push_str(&self.neighborhood_hint(browser, context, action).await);
+                            }
+
                             continue;
                         } else {
                             return Err(anyhow::anyhow!(
@@ -413,14 +908,22 @@ impl AgentExecutor {
                 Err(e) => {
                     tracing::error!("Agent: Action execution error: {}", e);
                     if retry < max_retries {
-                        let action_str = serde_json::to_string(&action)
+                        let action_str = serde_json::to_string(action)
                             .unwrap_or_else(|_| format!("{:?}", action));
+                        attempts.push((action_str.clone(), e.to_string()));
+                        let mut suggestion = "Check if the element exists and is interactable".to_string();
+                        if let Some(reflected) = self.reflect(context, task, &attempts, &suggestion).await {
+                            suggestion = reflected.clone();
+                            reflection = Some(reflected);
+                        }
                         current_prompt = build_retry_prompt(
                             context,
                             task,
                             &action_str,
                             &e.to_string(),
-                            "Check if the element exists and is interactable",
+                            &suggestion,
+                            &self.page_errors(browser, console_mark).await,
+                            self.llm_client.budget().max_prompt_tokens,
                         );
                         continue;
                     }
@@ -435,6 +938,228 @@ impl AgentExecutor {
         ))
     }
 
+    /// After the second failed attempt at a step, the LLM's reflection on it as a retry suggestion
+    ///
+    /// A single failure is often a flaky page; repeated ones mean the agent's
+    /// picture of the page is off, which a generic "try something else" does
+    /// not correct. `None` when reflection is off, not due yet, or failed.
+    async fn reflect(
+        &self,
+        context: &UIContext,
+        task: &str,
+        attempts: &[(String, String)],
+        feedback: &str,
+    ) -> Option<String> {
+        if !self.reflection || attempts.len() < 2 {
+            return None;
+        }
+
+        let prompt = build_reflection_prompt(context, task, attempts, feedback, self.llm_client.budget().max_prompt_tokens);
+        let reflected = async {
+            let response = self
+                .llm_client
+                .generate_json("You review why a browser automation agent's actions keep failing.", &prompt)
+                .await?;
+            anyhow::Ok(serde_json::from_str::<Reflection>(&response)?)
+        };
+        match reflected.await {
+            Ok(reflection) => {
+                tracing::info!(
+                    "Agent: Reflected after {} failed attempts: wrong assumption: {}",
+                    attempts.len(),
+                    reflection.wrong_assumption
+                );
+                Some(reflection.suggestion())
+            }
+            Err(e) => {
+                tracing::warn!("Agent: Reflection failed, retrying with the plain suggestion: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Neighborhoods of context elements that resemble the action's target
+    async fn neighborhood_hint(
+        &self,
+        browser: &Arc<BrowserAutomation>,
+        context: &UIContext,
+        action: &ActionRequest,
+    ) -> String {
+        let Some(selector) = action.selector() else {
+            return String::new();
+        };
+
+        let wanted = selector.name.as_deref().unwrap_or("").to_lowercase();
+        let candidates = context
+            .elements
+            .iter()
+            .filter(|el| el.selector.role == selector.role)
+            .filter(|el| {
+                let name = el.selector.name.as_deref().unwrap_or("").to_lowercase();
+                wanted.is_empty() || name.contains(&wanted) || wanted.contains(&name)
+            })
+            .take(3);
+
+        let mut neighborhoods = Vec::new();
+        for candidate in candidates {
+            match browser.element_neighborhood(candidate.id).await {
+                Ok(Some(mut n)) => {
+                    if let Some(scrubber) = &self.pii_scrubber {
+                        scrubber.scrub_neighborhood(&mut n);
+                    }
+                    neighborhoods.push(n)
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to get neighborhood of [{}]: {}", candidate.id, e),
+            }
+        }
+
+        build_neighborhood_hint(&neighborhoods)
+    }
+
+    /// Pre-run step: ask the LLM for the steps it expects the task to take on the current page
+    ///
+    /// The plan is kept with the run for reports; the agent still decides each
+    /// step on the page as it finds it.
+    pub async fn plan(&self, browser: &Arc<BrowserAutomation>, task: &str) -> Result<Vec<String>> {
+        let context = self.read_context(browser).await?;
+        let elements: Vec<String> = context
+            .elements
+            .iter()
+            .take(SUMMARY_MAX_ELEMENTS)
+            .map(element_line)
+            .collect();
+
+        let prompt = format!(
+            r#"Plan this browser automation task before it starts.
+
+Task: {}
+
+Current page:
+URL: {}
+Title: {}
+Elements:
+{}
+
+Respond with a JSON object:
+{{
+  "steps": ["3-8 short steps in plain words, e.g. 'Open the account menu', 'Sign in with the demo account'"]
+}}
+
+IMPORTANT: Respond ONLY with valid JSON."#,
+            task,
+            context.url,
+            context.title,
+            elements.join("\n")
+        );
+
+        #[derive(Deserialize)]
+        struct Plan {
+            steps: Vec<String>,
+        }
+
+        let response = self
+            .llm_client
+            .generate_json("You plan browser automation runs.", &prompt)
+            .await?;
+
+        Ok(serde_json::from_str::<Plan>(&response)?.steps)
+    }
+
+    /// Tokens this executor's LLM calls used so far
+    pub fn usage(&self) -> TokenUsage {
+        self.llm_client.usage()
+    }
+
+    /// Post-run step: ask the LLM for a short account of the run for end users
+    ///
+    /// Covers what was done, data visible on the final page and anything that
+    /// went wrong along the way.
+    pub async fn summarize(&self, task: &str, result: &MultiStepExecutionResult) -> Result<RunSummary> {
+        let mut steps = String::new();
+        for step in &result.steps {
+            let outcome = step
+                .action_result
+                .error
+                .map(ErrorCode::name)
+                .or(step.action_result.reason.as_deref())
+                .unwrap_or("completed");
+            steps.push_str(&format!(
+                "Step {}: {} - {} (then on '{}', {})\n",
+                step.step_number,
+                serde_json::to_string(&step.action_decided).unwrap_or_default(),
+                outcome,
+                step.context_after.title,
+                step.context_after.url
+            ));
+        }
+        if steps.is_empty() {
+            steps.push_str("No steps were executed.\n");
+        }
+
+        let outcome = match (&result.error, result.task_completed) {
+            (_, true) => "Completed".to_string(),
+            (Some(error), false) => format!("Not completed: {}", error),
+            (None, false) => "Not completed".to_string(),
+        };
+
+        let final_page = match &result.final_context {
+            Some(context) => {
+                let elements: Vec<String> = context
+                    .elements
+                    .iter()
+                    .take(SUMMARY_MAX_ELEMENTS)
+                    .map(element_line)
+                    .collect();
+                format!("URL: {}\nTitle: {}\nElements:\n{}", context.url, context.title, elements.join("\n"))
+            }
+            None => "Unknown".to_string(),
+        };
+
+        let prompt = format!(
+            r#"Summarize this browser automation run for the person who asked for it.
+
+Task: {}
+Outcome: {}
+Retries: {}
+
+Steps:
+{}
+Final page:
+{}
+
+Respond with a JSON object:
+{{
+  "summary": "2-3 plain sentences: what was done and how it ended",
+  "findings": ["data the user would care about, copied from the pages, e.g. totals, order numbers, statuses"],
+  "anomalies": ["errors, retries or unexpected page states worth a look"]
+}}
+
+Leave a list empty when there is nothing to report. IMPORTANT: Respond ONLY with valid JSON."#,
+            task, outcome, result.retries_count, steps, final_page
+        );
+
+        let response = self
+            .llm_client
+            .generate_json("You summarize browser automation runs for end users.", &prompt)
+            .await?;
+
+        Ok(serde_json::from_str(&response)?)
+    }
+
+    /// The run's assertions that don't hold on the current page, with what was found instead
+    async fn failed_assertions(&self, browser: &Arc<BrowserAutomation>) -> Result<Vec<String>> {
+        let mut failed = Vec::new();
+        for assertion in &self.assertions {
+            let response = browser.check_assertion(assertion).await?;
+            if !response.success {
+                failed.push(response.reason.unwrap_or_else(|| assertion.describe()));
+            }
+        }
+        tracing::info!("Success criteria: {} of {} hold", self.assertions.len() - failed.len(), self.assertions.len());
+        Ok(failed)
+    }
+
     /// Check if the task is complete by asking the LLM
     async fn is_task_complete(
         &self,
@@ -445,14 +1170,16 @@ impl AgentExecutor {
         // Build a summary of what we've done
         let mut steps_summary = String::new();
         for step in steps {
-            let result_desc = step.action_result.reason.as_ref()
-                .or(step.action_result.error.as_ref())
-                .map(|s| s.as_str())
+            let result_desc = step.action_result.reason.as_deref()
+                .or(step.action_result.error.map(ErrorCode::name))
                 .unwrap_or("completed");
             steps_summary.push_str(&format!(
                 "Step {}: {:?} - {}\n",
                 step.step_number, step.action_decided, result_desc
             ));
+            if let Some(transition) = &step.transition {
+                steps_summary.push_str(&format!("  Page: {}\n", transition.describe()));
+            }
         }
 
         let completion_prompt = format!(
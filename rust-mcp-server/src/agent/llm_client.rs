@@ -1,16 +1,118 @@
 use anyhow::Result;
 use async_openai::{
     types::{
-        ChatCompletionRequestMessage, ChatCompletionRequestSystemMessageArgs,
-        ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequestArgs,
+        ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestMessage, ChatCompletionRequestMessageContentPart,
+        ChatCompletionRequestMessageContentPartImageArgs, ChatCompletionRequestMessageContentPartTextArgs,
+        ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs,
+        CreateChatCompletionRequestArgs, ImageUrlArgs,
     },
     Client,
 };
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use ui_core::prompt::estimate_tokens;
+
+/// An earlier prompt and the model's answer, replayed so a prompt can build on it
+#[derive(Debug, Clone)]
+pub struct ChatTurn {
+    pub user: String,
+    pub assistant: String,
+}
+
+/// The configured LLM provider, as reported to clients (without secrets)
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmProvider {
+    pub name: &'static str,
+    pub model: String,
+    /// An API key is set, so agent endpoints can run
+    pub configured: bool,
+}
+
+/// Context window of the model and when prompts count as too close to it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PromptBudget {
+    pub context_tokens: usize,
+
+    /// Fraction of the context window above which prompts are reported
+    pub warn_ratio: f64,
+
+    /// Page state prompts are trimmed to about this many tokens; no limit when `None`
+    pub max_prompt_tokens: Option<usize>,
+}
+
+impl PromptBudget {
+    /// Reads LLM_CONTEXT_TOKENS (default: known window of `model`, else 8192)
+    /// LLM_PROMPT_WARN_RATIO (default: 0.8) and LLM_MAX_PROMPT_TOKENS (default: no limit)
+    pub fn from_env(model: &str) -> Self {
+        let context_tokens = std::env::var("LLM_CONTEXT_TOKENS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|tokens| *tokens > 0)
+            .unwrap_or_else(|| Self::default_context_tokens(model));
+        let warn_ratio = std::env::var("LLM_PROMPT_WARN_RATIO")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|ratio: &f64| *ratio > 0.0 && *ratio <= 1.0)
+            .unwrap_or(0.8);
+        let max_prompt_tokens = std::env::var("LLM_MAX_PROMPT_TOKENS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|tokens| *tokens > 0);
+
+        Self {
+            context_tokens,
+            warn_ratio,
+            max_prompt_tokens,
+        }
+    }
+
+    fn default_context_tokens(model: &str) -> usize {
+        let model = model.to_lowercase();
+        if ["gpt-4o", "gpt-4-turbo", "gpt-4.1"].iter().any(|prefix| model.starts_with(prefix)) {
+            128_000
+        } else if model.starts_with("gpt-4-32k") {
+            32_768
+        } else if model.starts_with("gpt-3.5-turbo") {
+            16_385
+        } else {
+            8_192
+        }
+    }
+
+    pub fn is_near_limit(&self, prompt_tokens: usize) -> bool {
+        prompt_tokens as f64 >= self.context_tokens as f64 * self.warn_ratio
+    }
+}
+
+/// Tokens an LLM client used, as reported by the API (estimated when it doesn't say)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+}
+
+impl TokenUsage {
+    pub fn is_empty(&self) -> bool {
+        self.prompt_tokens == 0 && self.completion_tokens == 0
+    }
+
+    pub fn total(&self) -> usize {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
 
 /// LLM Client for agent decision-making (Step 2)
 pub struct LLMClient {
     client: Client<async_openai::config::OpenAIConfig>,
     model: String,
+    budget: PromptBudget,
+    /// Completion limit per call
+    max_tokens: u32,
+    /// Prompt size of the latest JSON-mode call
+    last_prompt_tokens: AtomicUsize,
+    /// Totals over all JSON-mode calls
+    prompt_tokens: AtomicUsize,
+    completion_tokens: AtomicUsize,
 }
 
 impl LLMClient {
@@ -24,10 +126,53 @@ impl LLMClient {
 
         let client = Client::with_config(config);
 
-        let model = std::env::var("OPENAI_MODEL")
-            .unwrap_or_else(|_| "gpt-4".to_string());
+        let model = Self::model_from_env();
+        Self {
+            client,
+            budget: PromptBudget::from_env(&model),
+            model,
+            max_tokens: 500,
+            last_prompt_tokens: AtomicUsize::new(0),
+            prompt_tokens: AtomicUsize::new(0),
+            completion_tokens: AtomicUsize::new(0),
+        }
+    }
+
+    /// Builder method to raise the completion limit, for answers longer than one action
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Model from OPENAI_MODEL (default: gpt-4)
+    fn model_from_env() -> String {
+        std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4".to_string())
+    }
+
+    /// Provider the agent would use, without creating a client
+    pub fn provider_from_env() -> LlmProvider {
+        LlmProvider {
+            name: "openai",
+            model: Self::model_from_env(),
+            configured: std::env::var("OPENAI_API_KEY").is_ok_and(|key| !key.is_empty()),
+        }
+    }
+
+    pub fn budget(&self) -> PromptBudget {
+        self.budget
+    }
 
-        Self { client, model }
+    /// Prompt tokens of the latest JSON-mode call (0 before the first one)
+    pub fn last_prompt_tokens(&self) -> usize {
+        self.last_prompt_tokens.load(Ordering::Relaxed)
+    }
+
+    /// Tokens used by all JSON-mode calls of this client so far
+    pub fn usage(&self) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: self.prompt_tokens.load(Ordering::Relaxed),
+            completion_tokens: self.completion_tokens.load(Ordering::Relaxed),
+        }
     }
 
     /// Generate a completion from the LLM
@@ -55,7 +200,7 @@ impl LLMClient {
             .model(&self.model)
             .messages(messages)
             .temperature(0.1) // Low temperature for consistent actions
-            .max_tokens(500u32)
+            .max_tokens(self.max_tokens)
             .build()?;
 
         let response = self.client.chat().create(request).await?;
@@ -75,24 +220,75 @@ impl LLMClient {
         system_prompt: &str,
         user_message: &str,
     ) -> Result<String> {
-        let messages = vec![
-            ChatCompletionRequestMessage::System(
-                ChatCompletionRequestSystemMessageArgs::default()
-                    .content(system_prompt)
-                    .build()?,
-            ),
-            ChatCompletionRequestMessage::User(
+        self.generate_json_with_history(system_prompt, &[], user_message).await
+    }
+
+    /// Generate with JSON mode after replaying earlier turns of the conversation
+    pub async fn generate_json_with_history(
+        &self,
+        system_prompt: &str,
+        history: &[ChatTurn],
+        user_message: &str,
+    ) -> Result<String> {
+        self.generate_json_with_screenshot(system_prompt, history, user_message, None)
+            .await
+    }
+
+    /// Generate with JSON mode, attaching an image to the user message for multimodal models
+    ///
+    /// `screenshot` is a data URL (`data:image/jpeg;base64,...`); models without
+    /// vision support reject such requests.
+    pub async fn generate_json_with_screenshot(
+        &self,
+        system_prompt: &str,
+        history: &[ChatTurn],
+        user_message: &str,
+        screenshot: Option<&str>,
+    ) -> Result<String> {
+        let mut messages = vec![ChatCompletionRequestMessage::System(
+            ChatCompletionRequestSystemMessageArgs::default()
+                .content(system_prompt)
+                .build()?,
+        )];
+        for turn in history {
+            messages.push(ChatCompletionRequestMessage::User(
                 ChatCompletionRequestUserMessageArgs::default()
-                    .content(user_message)
+                    .content(turn.user.as_str())
                     .build()?,
-            ),
-        ];
+            ));
+            messages.push(ChatCompletionRequestMessage::Assistant(
+                ChatCompletionRequestAssistantMessageArgs::default()
+                    .content(turn.assistant.as_str())
+                    .build()?,
+            ));
+        }
+        let user = match screenshot {
+            None => ChatCompletionRequestUserMessageArgs::default()
+                .content(user_message)
+                .build()?,
+            Some(image_url) => {
+                let parts: Vec<ChatCompletionRequestMessageContentPart> = vec![
+                    ChatCompletionRequestMessageContentPartTextArgs::default()
+                        .text(user_message)
+                        .build()?
+                        .into(),
+                    ChatCompletionRequestMessageContentPartImageArgs::default()
+                        .image_url(ImageUrlArgs::default().url(image_url).build()?)
+                        .build()?
+                        .into(),
+                ];
+                ChatCompletionRequestUserMessageArgs::default()
+                    .content(parts)
+                    .build()?
+            }
+        };
+        messages.push(ChatCompletionRequestMessage::User(user));
 
         let request = CreateChatCompletionRequestArgs::default()
             .model(&self.model)
             .messages(messages)
             .temperature(0.1)
-            .max_tokens(500u32)
+            .max_tokens(self.max_tokens)
             .response_format(async_openai::types::ChatCompletionResponseFormat {
                 r#type: async_openai::types::ChatCompletionResponseFormatType::JsonObject,
             })
@@ -106,6 +302,40 @@ impl LLMClient {
             .and_then(|choice| choice.message.content.clone())
             .ok_or_else(|| anyhow::anyhow!("No response from LLM"))?;
 
+        let (prompt_tokens, completion_tokens) = match &response.usage {
+            Some(usage) => (usage.prompt_tokens as usize, usage.completion_tokens as usize),
+            None => {
+                let prompt_tokens = std::iter::once(system_prompt)
+                    .chain(history.iter().flat_map(|turn| [turn.user.as_str(), turn.assistant.as_str()]))
+                    .chain(std::iter::once(user_message))
+                    .map(estimate_tokens)
+                    .sum();
+                (prompt_tokens, estimate_tokens(&content))
+            }
+        };
+        self.last_prompt_tokens.store(prompt_tokens, Ordering::Relaxed);
+        self.prompt_tokens.fetch_add(prompt_tokens, Ordering::Relaxed);
+        self.completion_tokens.fetch_add(completion_tokens, Ordering::Relaxed);
+
+        tracing::info!(
+            model = %self.model,
+            prompt_tokens,
+            completion_tokens,
+            history_turns = history.len(),
+            screenshot = screenshot.is_some(),
+            context_tokens = self.budget.context_tokens,
+            "LLM call"
+        );
+        if self.budget.is_near_limit(prompt_tokens) {
+            tracing::warn!(
+                model = %self.model,
+                prompt_tokens,
+                context_tokens = self.budget.context_tokens,
+                "Prompt used {:.0}% of the model's context window",
+                prompt_tokens as f64 * 100.0 / self.budget.context_tokens as f64
+            );
+        }
+
         Ok(content)
     }
 }
@@ -115,3 +345,24 @@ impl Default for LLMClient {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_budget_defaults_by_model() {
+        assert_eq!(PromptBudget::default_context_tokens("gpt-4"), 8_192);
+        assert_eq!(PromptBudget::default_context_tokens("gpt-4o-mini"), 128_000);
+        assert_eq!(PromptBudget::default_context_tokens("gpt-3.5-turbo"), 16_385);
+        assert_eq!(PromptBudget::default_context_tokens("some-local-model"), 8_192);
+
+        let budget = PromptBudget {
+            context_tokens: 8_000,
+            warn_ratio: 0.8,
+            max_prompt_tokens: None,
+        };
+        assert!(!budget.is_near_limit(6_399));
+        assert!(budget.is_near_limit(6_400));
+    }
+}
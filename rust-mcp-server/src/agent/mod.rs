@@ -1,6 +1,24 @@
+pub mod compare;
+pub mod control;
 pub mod llm_client;
-pub mod prompt;
+pub mod prompt_diff;
 pub mod executor;
+pub mod report;
+pub mod runs;
+pub mod scrape;
+pub mod tasks;
+pub mod trace;
 
 // Re-export main types
-pub use executor::{AgentExecutor, AgentExecutionResult, MultiStepExecutionResult};
+pub use compare::RunComparison;
+pub use control::{RunControl, RunControls, RunState};
+pub use executor::{
+    run_plan_from_env, run_summary_from_env, AgentExecutor, AgentExecutionResult, ConversationStep, MultiStepExecutionResult, DEFAULT_MAX_RETRIES_PER_STEP,
+    DEFAULT_MAX_STEPS,
+};
+pub use llm_client::{LLMClient, LlmProvider};
+pub use report::{render_report, ReportFormat};
+pub use runs::{AgentRun, RunOverview, RunStore};
+pub use scrape::{SchemaExtraction, SchemaExtractor};
+pub use tasks::{AgentTask, SessionSchedule, TaskPriority, TaskStatus, TaskStore};
+pub use trace::{is_trace_file_name, link_trace, run_trace_dir, TraceBundle, Tracer};
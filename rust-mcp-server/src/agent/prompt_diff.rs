@@ -0,0 +1,213 @@
+use ui_core::diff::ContextDiff;
+use ui_core::prompt::{build_diff_prompt, build_user_prompt};
+
+use crate::models::UIContext;
+
+use super::llm_client::ChatTurn;
+
+/// Send only the elements that changed on later steps of a multi-step run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PromptDiffPolicy {
+    pub enabled: bool,
+
+    /// Steps after which the full page state is sent again
+    pub full_refresh_every: usize,
+
+    /// Fraction of the element list that may change before a full state is cheaper
+    pub max_changed_ratio: f64,
+}
+
+impl PromptDiffPolicy {
+    /// Reads PROMPT_DIFF (default: false) and PROMPT_DIFF_REFRESH_STEPS (default: 5)
+    pub fn from_env() -> Self {
+        let enabled = std::env::var("PROMPT_DIFF")
+            .map(|v| matches!(v.to_lowercase().as_str(), "true" | "1" | "on"))
+            .unwrap_or(false);
+        let full_refresh_every = std::env::var("PROMPT_DIFF_REFRESH_STEPS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|steps| *steps > 0)
+            .unwrap_or(5);
+
+        Self {
+            enabled,
+            full_refresh_every,
+            max_changed_ratio: 0.5,
+        }
+    }
+
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+}
+
+/// Prompts of one run: a full page state, then diffs against the previous step
+///
+/// The diffs only make sense next to the prompts they build on, so the turns
+/// since the last full state are replayed with every request.
+#[derive(Debug)]
+pub struct PromptDiffer {
+    policy: PromptDiffPolicy,
+    previous: Option<UIContext>,
+    history: Vec<ChatTurn>,
+    /// Token limit of full page state prompts
+    max_tokens: Option<usize>,
+}
+
+impl PromptDiffer {
+    pub fn new(policy: PromptDiffPolicy) -> Self {
+        Self {
+            policy,
+            previous: None,
+            history: Vec::new(),
+            max_tokens: None,
+        }
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: Option<usize>) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Prompt for the next step, a diff when the previous one allows it
+    pub fn user_prompt(&mut self, context: &UIContext, task: &str) -> String {
+        let diff = self.diff(context);
+        self.previous = Some(context.clone());
+
+        match diff {
+            Some(diff) => build_diff_prompt(context, &diff, task),
+            None => {
+                self.history.clear();
+                build_user_prompt(context, task, self.max_tokens)
+            }
+        }
+    }
+
+    fn diff(&self, context: &UIContext) -> Option<ContextDiff> {
+        if !self.policy.enabled || self.history.len() >= self.policy.full_refresh_every {
+            return None;
+        }
+        // Nothing to build on, or a different page
+        let previous = self.previous.as_ref().filter(|_| !self.history.is_empty())?;
+        if previous.url != context.url {
+            return None;
+        }
+
+        let diff = ContextDiff::between(previous, context);
+        let limit = context.elements.len().max(previous.elements.len()) as f64 * self.policy.max_changed_ratio;
+        (diff.len() as f64 <= limit).then_some(diff)
+    }
+
+    /// Turns to replay before the prompt returned by `user_prompt`
+    pub fn history(&self) -> &[ChatTurn] {
+        &self.history
+    }
+
+    /// The model answered `prompt` and its action worked
+    pub fn record(&mut self, prompt: String, response: String) {
+        if self.policy.enabled {
+            self.history.push(ChatTurn {
+                user: prompt,
+                assistant: response,
+            });
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.policy.enabled
+    }
+
+    /// Switch to diff prompts for the rest of the run, e.g. when full prompts grow too large
+    pub fn enable(&mut self) {
+        self.policy.enabled = true;
+    }
+
+    /// Send the full page state next time, e.g. after the model lost track
+    pub fn reset(&mut self) {
+        self.previous = None;
+        self.history.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{SimplifiedElement, Viewport};
+
+    fn context(url: &str, names: &[&str]) -> UIContext {
+        UIContext {
+            url: url.to_string(),
+            title: "Shop".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements: names
+                .iter()
+                .enumerate()
+                .map(|(id, name)| SimplifiedElement::new(id, "button", Some(*name), true))
+                .collect(),
+            navigation_history: vec![],
+            closed_overlays: vec![],
+            tabs: vec![],
+            focused: None,
+        }
+    }
+
+    fn policy(full_refresh_every: usize) -> PromptDiffPolicy {
+        PromptDiffPolicy {
+            enabled: true,
+            full_refresh_every,
+            max_changed_ratio: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_differ_sends_diffs_after_first_step() {
+        let mut differ = PromptDiffer::new(policy(5));
+        let names = ["Home", "Cart", "Checkout", "Help"];
+
+        let first = differ.user_prompt(&context("http://shop", &names), "Buy");
+        assert!(first.contains("Available Elements"));
+        assert!(differ.history().is_empty());
+        differ.record(first, "{}".to_string());
+
+        let second = differ.user_prompt(&context("http://shop", &["Home", "Cart (1)", "Checkout", "Help"]), "Buy");
+        assert!(second.contains("Page Update Since Your Last Action"));
+        assert!(second.contains("Cart (1)"));
+        assert!(!second.contains("Checkout"));
+        assert_eq!(differ.history().len(), 1);
+    }
+
+    #[test]
+    fn test_differ_falls_back_to_full_state() {
+        let names = ["Home", "Cart", "Checkout", "Help"];
+
+        // Periodic refresh
+        let mut differ = PromptDiffer::new(policy(1));
+        let prompt = differ.user_prompt(&context("http://shop", &names), "Buy");
+        differ.record(prompt, "{}".to_string());
+        assert!(differ.user_prompt(&context("http://shop", &names), "Buy").contains("Available Elements"));
+        assert!(differ.history().is_empty());
+
+        // Navigation and large changes
+        let mut differ = PromptDiffer::new(policy(5));
+        let prompt = differ.user_prompt(&context("http://shop", &names), "Buy");
+        differ.record(prompt, "{}".to_string());
+        let prompt = differ.user_prompt(&context("http://shop/cart", &names), "Buy");
+        assert!(prompt.contains("Available Elements"));
+        differ.record(prompt, "{}".to_string());
+        let rebuilt = context("http://shop/cart", &["Pay", "Back", "Coupon", "Help"]);
+        assert!(differ.user_prompt(&rebuilt, "Buy").contains("Available Elements"));
+
+        // The model lost track
+        let mut differ = PromptDiffer::new(policy(5));
+        let prompt = differ.user_prompt(&context("http://shop", &names), "Buy");
+        differ.record(prompt, "{}".to_string());
+        differ.reset();
+        assert!(differ.user_prompt(&context("http://shop", &names), "Buy").contains("Available Elements"));
+    }
+}
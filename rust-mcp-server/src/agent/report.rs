@@ -0,0 +1,480 @@
+use serde::Deserialize;
+
+use crate::models::{ActionRequest, ErrorCode, ScrollDirection};
+
+use super::executor::ConversationStep;
+use super::llm_client::TokenUsage;
+use super::runs::AgentRun;
+
+/// Output format for run reports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+/// Human-readable one-line description of an action, e.g. "Click button 'Login'"
+pub fn describe_action(action: &ActionRequest) -> String {
+    let target = |role: &str, name: Option<&str>| match name {
+        Some(name) => format!("{} '{}'", role, name),
+        None => role.to_string(),
+    };
+
+    match action {
+        ActionRequest::Click { selector } => {
+            format!("Click {}", target(&selector.role, selector.name.as_deref()))
+        }
+        ActionRequest::Type { selector, text } => format!(
+            "Type \"{}\" into {}",
+            text,
+            target(&selector.role, selector.name.as_deref())
+        ),
+        ActionRequest::SelectOption { selector, value, label } => format!(
+            "Select '{}' in {}",
+            label.as_deref().or(value.as_deref()).unwrap_or(""),
+            target(&selector.role, selector.name.as_deref())
+        ),
+        ActionRequest::SetChecked { selector, checked } => format!(
+            "{} {}",
+            if *checked { "Check" } else { "Uncheck" },
+            target(&selector.role, selector.name.as_deref())
+        ),
+        ActionRequest::Focus { selector } => {
+            format!("Focus {}", target(&selector.role, selector.name.as_deref()))
+        }
+        ActionRequest::PressKey { key, modifiers } => {
+            let mut combination: Vec<&str> = modifiers.iter().map(|m| m.name()).collect();
+            combination.push(key);
+            format!("Press {}", combination.join("+"))
+        }
+        ActionRequest::Scroll { direction, amount, selector } => {
+            let direction = match direction {
+                ScrollDirection::Up => "up",
+                ScrollDirection::Down => "down",
+                ScrollDirection::Left => "left",
+                ScrollDirection::Right => "right",
+            };
+            match selector {
+                Some(selector) => format!(
+                    "Scroll {} {} by {}px",
+                    target(&selector.role, selector.name.as_deref()),
+                    direction,
+                    amount.unwrap_or(300)
+                ),
+                None => format!("Scroll {} by {}px", direction, amount.unwrap_or(300)),
+            }
+        }
+        ActionRequest::WaitForElement { selector, .. } => format!(
+            "Wait for {}",
+            target(&selector.role, selector.name.as_deref())
+        ),
+        ActionRequest::WaitForNavigation { .. } => "Wait for the page to load".to_string(),
+        ActionRequest::WaitForNetworkIdle { .. } => "Wait for the network to go idle".to_string(),
+        ActionRequest::WaitForUrl { pattern, .. } => format!("Wait for a URL matching {}", pattern),
+        ActionRequest::WaitForText { text, .. } => format!("Wait for the text '{}'", text),
+        ActionRequest::Assert { assertion } => format!("Check that {}", assertion.describe()),
+        ActionRequest::Navigate { url } => format!("Navigate to {}", url),
+        ActionRequest::NewTab { url: Some(url) } => format!("Open {} in a new tab", url),
+        ActionRequest::NewTab { url: None } => "Open a new tab".to_string(),
+        ActionRequest::SwitchTab { tab_id } => format!("Switch to tab {}", tab_id),
+        ActionRequest::CloseTab { tab_id: Some(tab_id) } => format!("Close tab {}", tab_id),
+        ActionRequest::CloseTab { tab_id: None } => "Close the tab".to_string(),
+        ActionRequest::GoBack => "Go back".to_string(),
+        ActionRequest::GoForward => "Go forward".to_string(),
+        ActionRequest::Reload => "Reload the page".to_string(),
+        ActionRequest::FindText { query, .. } => format!("Find text \"{}\"", query),
+        ActionRequest::SetZoom { factor } => format!("Set zoom to {}", factor),
+        ActionRequest::RestoreOverlay { overlay_id } => format!("Restore overlay {}", overlay_id),
+        ActionRequest::WaitForDownload { filename_pattern, .. } => match filename_pattern {
+            Some(pattern) => format!("Wait for download \"{}\"", pattern),
+            None => "Wait for download".to_string(),
+        },
+        ActionRequest::FillForm { fields, submit } => format!(
+            "Fill {} field(s): {}{}",
+            fields.len(),
+            fields.keys().cloned().collect::<Vec<_>>().join(", "),
+            if *submit { ", then submit" } else { "" }
+        ),
+        ActionRequest::Login { credential_name } => format!("Log in as '{}'", credential_name),
+        ActionRequest::GetOtp { provider, .. } => format!("Get a one-time code from '{}'", provider),
+        ActionRequest::InvokeHook { name, .. } => format!("Invoke test hook '{}'", name),
+        ActionRequest::HandleDialog { accept: true, .. } => "Accept the next dialog".to_string(),
+        ActionRequest::HandleDialog { .. } => "Dismiss the next dialog".to_string(),
+        ActionRequest::GetStorage { area, key: Some(key) } => format!("Read {} '{}'", area.global(), key),
+        ActionRequest::GetStorage { area, key: None } => format!("Read {}", area.global()),
+        ActionRequest::SetStorage { area, key, value: Some(_) } => format!("Set {} '{}'", area.global(), key),
+        ActionRequest::SetStorage { area, key, value: None } => format!("Remove {} '{}'", area.global(), key),
+        ActionRequest::Evaluate { .. } => "Run a script".to_string(),
+        ActionRequest::Screenshot { full_page: true, .. } => "Take a full-page screenshot".to_string(),
+        ActionRequest::Screenshot { .. } => "Take a screenshot".to_string(),
+    }
+}
+
+/// Render a completed run as a shareable report
+pub fn render_report(run: &AgentRun, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Markdown => render_markdown(run),
+        ReportFormat::Html => render_html(run),
+    }
+}
+
+fn outcome(run: &AgentRun) -> &'static str {
+    if run.result.task_completed {
+        "Completed"
+    } else if run.result.cancelled {
+        "Cancelled"
+    } else {
+        "Not completed"
+    }
+}
+
+fn duration_seconds(run: &AgentRun) -> f64 {
+    (run.finished_at - run.started_at).num_milliseconds() as f64 / 1000.0
+}
+
+fn token_usage(usage: &TokenUsage) -> String {
+    format!(
+        "{} ({} prompt, {} completion)",
+        usage.total(),
+        usage.prompt_tokens,
+        usage.completion_tokens
+    )
+}
+
+/// Link to the page as the step left it, for traced runs
+fn step_screenshot(step: &ConversationStep) -> Option<&str> {
+    let trace = step.trace.as_ref()?;
+    trace.after_screenshot.as_deref().or(trace.before_screenshot.as_deref())
+}
+
+/// Render a run as Markdown
+pub fn render_markdown(run: &AgentRun) -> String {
+    let result = &run.result;
+    let mut md = String::new();
+
+    md.push_str("# Agent Run Report\n\n");
+    md.push_str(&format!("**Task:** {}\n\n", run.task));
+    md.push_str(&format!("**Outcome:** {}\n\n", outcome(run)));
+    if let Some(summary) = &result.summary {
+        md.push_str(&format!("{}\n\n", summary.summary));
+        for finding in &summary.findings {
+            md.push_str(&format!("- {}\n", finding));
+        }
+        for anomaly in &summary.anomalies {
+            md.push_str(&format!("- ⚠️ {}\n", anomaly));
+        }
+        if !summary.findings.is_empty() || !summary.anomalies.is_empty() {
+            md.push('\n');
+        }
+    }
+    if !result.plan.is_empty() {
+        md.push_str("## Plan\n\n");
+        for (i, step) in result.plan.iter().enumerate() {
+            md.push_str(&format!("{}. {}\n", i + 1, step));
+        }
+        md.push('\n');
+    }
+    md.push_str("| | |\n|---|---|\n");
+    md.push_str(&format!("| Run | `{}` |\n", run.run_id));
+    md.push_str(&format!("| Session | `{}` |\n", run.session_id));
+    md.push_str(&format!("| Started | {} |\n", run.started_at.to_rfc3339()));
+    md.push_str(&format!("| Duration | {:.1}s |\n", duration_seconds(run)));
+    md.push_str(&format!("| Steps | {} / {} |\n", result.steps_taken, result.max_steps));
+    md.push_str(&format!("| Retries | {} |\n", result.retries_count));
+    if !result.usage.is_empty() {
+        md.push_str(&format!("| LLM tokens | {} |\n", token_usage(&result.usage)));
+    }
+
+    md.push_str("\n## Steps\n\n");
+    if result.steps.is_empty() {
+        md.push_str("_No steps were executed._\n");
+    }
+    for step in &result.steps {
+        md.push_str(&format!(
+            "### Step {}: {}\n\n",
+            step.step_number,
+            describe_action(&step.action_decided)
+        ));
+        let status = if step.action_result.success {
+            "succeeded".to_string()
+        } else {
+            format!(
+                "failed ({})",
+                step.action_result.error.map(ErrorCode::name).unwrap_or("unknown error")
+            )
+        };
+        md.push_str(&format!("- Result: {}\n", status));
+        if let Some(transition) = &step.transition {
+            md.push_str(&format!("- Outcome: {}\n", transition.describe()));
+        }
+        md.push_str(&format!(
+            "- Page after: {} ({})\n\n",
+            step.context_after.title, step.context_after.url
+        ));
+        if let Some(screenshot) = step_screenshot(step) {
+            md.push_str(&format!("![Step {}]({})\n\n", step.step_number, screenshot));
+        }
+    }
+
+    if let Some(context) = &result.final_context {
+        md.push_str("## Final Page\n\n");
+        md.push_str(&format!("{} ({})\n", context.title, context.url));
+    }
+
+    if let Some(error) = &result.error {
+        md.push_str("\n## Error\n\n");
+        md.push_str(&format!("{}\n", error));
+    }
+
+    md
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Render a run as a standalone, print-friendly HTML page
+pub fn render_html(run: &AgentRun) -> String {
+    let result = &run.result;
+    let mut body = String::new();
+
+    body.push_str(&format!(
+        "<h1>Agent Run Report</h1>\n<p><strong>Task:</strong> {}</p>\n",
+        escape_html(&run.task)
+    ));
+    body.push_str(&format!(
+        "<p class=\"outcome {}\"><strong>Outcome:</strong> {}</p>\n",
+        if result.task_completed { "ok" } else { "fail" },
+        outcome(run)
+    ));
+    if let Some(summary) = &result.summary {
+        body.push_str(&format!("<p>{}</p>\n", escape_html(&summary.summary)));
+        if !summary.findings.is_empty() || !summary.anomalies.is_empty() {
+            body.push_str("<ul>\n");
+            for finding in &summary.findings {
+                body.push_str(&format!("<li>{}</li>\n", escape_html(finding)));
+            }
+            for anomaly in &summary.anomalies {
+                body.push_str(&format!("<li>⚠️ {}</li>\n", escape_html(anomaly)));
+            }
+            body.push_str("</ul>\n");
+        }
+    }
+    if !result.plan.is_empty() {
+        body.push_str("<h2>Plan</h2>\n<ol>\n");
+        for step in &result.plan {
+            body.push_str(&format!("<li>{}</li>\n", escape_html(step)));
+        }
+        body.push_str("</ol>\n");
+    }
+    body.push_str("<table>\n");
+    let mut rows = vec![
+        ("Run", run.run_id.clone()),
+        ("Session", run.session_id.clone()),
+        ("Started", run.started_at.to_rfc3339()),
+        ("Duration", format!("{:.1}s", duration_seconds(run))),
+        ("Steps", format!("{} / {}", result.steps_taken, result.max_steps)),
+        ("Retries", result.retries_count.to_string()),
+    ];
+    if !result.usage.is_empty() {
+        rows.push(("LLM tokens", token_usage(&result.usage)));
+    }
+    for (label, value) in rows {
+        body.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", label, escape_html(&value)));
+    }
+    body.push_str("</table>\n<h2>Steps</h2>\n");
+
+    if result.steps.is_empty() {
+        body.push_str("<p><em>No steps were executed.</em></p>\n");
+    } else {
+        body.push_str("<ol>\n");
+        for step in &result.steps {
+            let status = if step.action_result.success {
+                "succeeded".to_string()
+            } else {
+                format!(
+                    "failed ({})",
+                    step.action_result.error.map(ErrorCode::name).unwrap_or("unknown error")
+                )
+            };
+            let outcome = step
+                .transition
+                .as_ref()
+                .map(|t| format!("<br>Outcome: {}", escape_html(&t.describe())))
+                .unwrap_or_default();
+            let screenshot = step_screenshot(step)
+                .map(|src| format!("<br><img src=\"{}\" alt=\"Step {}\">", escape_html(src), step.step_number))
+                .unwrap_or_default();
+            body.push_str(&format!(
+                "<li><strong>{}</strong><br>Result: {}{}<br>Page after: {} ({}){}</li>\n",
+                escape_html(&describe_action(&step.action_decided)),
+                escape_html(&status),
+                outcome,
+                escape_html(&step.context_after.title),
+                escape_html(&step.context_after.url),
+                screenshot
+            ));
+        }
+        body.push_str("</ol>\n");
+    }
+
+    if let Some(context) = &result.final_context {
+        body.push_str(&format!(
+            "<h2>Final Page</h2>\n<p>{} ({})</p>\n",
+            escape_html(&context.title),
+            escape_html(&context.url)
+        ));
+    }
+
+    if let Some(error) = &result.error {
+        body.push_str(&format!("<h2>Error</h2>\n<p>{}</p>\n", escape_html(error)));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Agent Run Report - {}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 860px; margin: 2em auto; color: #222; }}
+table {{ border-collapse: collapse; }}
+th, td {{ text-align: left; padding: 4px 12px; border-bottom: 1px solid #ddd; }}
+li {{ margin-bottom: 0.8em; }}
+img {{ max-width: 100%; margin-top: 0.4em; border: 1px solid #ddd; }}
+.outcome.ok {{ color: #1b7f3b; }}
+.outcome.fail {{ color: #b3261e; }}
+</style>
+</head>
+<body>
+{}</body>
+</html>
+"#,
+        escape_html(&run.task),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::executor::MultiStepExecutionResult;
+    use crate::agent::trace::StepTrace;
+    use crate::models::{ActionResponse, SemanticSelector, UIContext, Viewport};
+    use chrono::Utc;
+
+    fn sample_run() -> AgentRun {
+        let context = UIContext {
+            url: "http://localhost:3000/dashboard".to_string(),
+            title: "Dashboard".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements: vec![],
+            navigation_history: vec![],
+            closed_overlays: vec![],
+            tabs: vec![],
+            focused: None,
+        };
+
+        let result = MultiStepExecutionResult {
+            task_completed: true,
+            steps_taken: 1,
+            max_steps: 20,
+            steps: vec![ConversationStep {
+                step_number: 1,
+                action_decided: ActionRequest::Click {
+                    selector: SemanticSelector {
+                        role: "button".to_string(),
+                        name: Some("Login".to_string()),
+                        description: None,
+                        css_fallback: None,
+                        frame: Vec::new(),
+                        element_id: None,
+                    },
+                },
+                action_result: ActionResponse::success(),
+                context_after: context.clone(),
+                llm_response: "{}".to_string(),
+                transition: None,
+                normalized: Vec::new(),
+                trace: None,
+                reflection: None,
+            }],
+            final_context: Some(context),
+            error: None,
+            retries_count: 0,
+            consent_dismissed: vec![],
+            cancelled: false,
+            summary: None,
+            run_id: None,
+            downloads: Vec::new(),
+            plan: Vec::new(),
+            usage: Default::default(),
+        };
+
+        AgentRun::new("session-1", "Log in <as admin>", Utc::now(), result)
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let report = render_markdown(&sample_run());
+        assert!(report.contains("**Task:** Log in <as admin>"));
+        assert!(report.contains("**Outcome:** Completed"));
+        assert!(report.contains("### Step 1: Click button 'Login'"));
+        assert!(report.contains("Dashboard (http://localhost:3000/dashboard)"));
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let mut run = sample_run();
+        run.result.summary = Some(serde_json::from_str(
+            r#"{"summary": "Logged in as admin.", "findings": ["3 unread messages"]}"#,
+        ).unwrap());
+
+        let report = render_markdown(&run);
+        assert!(report.contains("Logged in as admin.\n\n- 3 unread messages\n"));
+        assert!(render_html(&run).contains("<li>3 unread messages</li>"));
+    }
+
+    #[test]
+    fn test_render_plan_screenshots_and_tokens() {
+        let mut run = sample_run();
+        run.result.plan = vec!["Open the login form".to_string(), "Sign in".to_string()];
+        run.result.usage = TokenUsage { prompt_tokens: 1200, completion_tokens: 80 };
+        run.result.steps[0].trace = Some(StepTrace {
+            before_screenshot: None,
+            after_screenshot: Some("/session-1/agent/trace/run-1/files/step-1-after.jpg".to_string()),
+            dom_snapshot: None,
+        });
+
+        let report = render_markdown(&run);
+        assert!(report.contains("## Plan\n\n1. Open the login form\n2. Sign in\n"));
+        assert!(report.contains("| LLM tokens | 1280 (1200 prompt, 80 completion) |"));
+        assert!(report.contains("![Step 1](/session-1/agent/trace/run-1/files/step-1-after.jpg)"));
+
+        let html = render_html(&run);
+        assert!(html.contains("<li>Sign in</li>"));
+        assert!(html.contains("<img src=\"/session-1/agent/trace/run-1/files/step-1-after.jpg\" alt=\"Step 1\">"));
+
+        // Untraced runs without LLM usage have neither
+        let report = render_markdown(&sample_run());
+        assert!(!report.contains("LLM tokens") && !report.contains("!["));
+    }
+
+    #[test]
+    fn test_render_html_escapes_content() {
+        let report = render_html(&sample_run());
+        assert!(report.contains("Log in &lt;as admin&gt;"));
+        assert!(!report.contains("<as admin>"));
+        assert!(report.contains("Click button 'Login'"));
+    }
+}
@@ -0,0 +1,405 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+use crate::browser::{prune_run_downloads, remove_run_downloads};
+
+use super::executor::MultiStepExecutionResult;
+use super::trace::{prune_traces, remove_trace};
+
+/// A completed multi-step agent run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentRun {
+    pub run_id: String,
+    pub session_id: String,
+    /// User who started the run; `None` when it was started without authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub task: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub result: MultiStepExecutionResult,
+}
+
+impl AgentRun {
+    pub fn new(
+        session_id: &str,
+        task: &str,
+        started_at: DateTime<Utc>,
+        result: MultiStepExecutionResult,
+    ) -> Self {
+        Self {
+            run_id: Uuid::new_v4().to_string(),
+            session_id: session_id.to_string(),
+            owner: None,
+            task: task.to_string(),
+            started_at,
+            finished_at: Utc::now(),
+            result,
+        }
+    }
+
+    /// Whether `user` (`None` without authentication) may see the run: only its owner may
+    pub fn visible_to(&self, user: Option<&str>) -> bool {
+        self.owner.as_deref() == user
+    }
+
+    pub fn overview(&self) -> RunOverview {
+        RunOverview {
+            run_id: self.run_id.clone(),
+            session_id: self.session_id.clone(),
+            owner: self.owner.clone(),
+            task: self.task.clone(),
+            started_at: self.started_at,
+            finished_at: self.finished_at,
+            task_completed: self.result.task_completed,
+            cancelled: self.result.cancelled,
+            steps_taken: self.result.steps_taken,
+            error: self.result.error.clone(),
+        }
+    }
+}
+
+/// A run without its steps and page contexts, for listings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunOverview {
+    pub run_id: String,
+    pub session_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub task: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub task_completed: bool,
+    pub cancelled: bool,
+    pub steps_taken: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Redis sorted set of the stored run IDs, scored by start time (ms); bounds what is kept
+const REDIS_INDEX_KEY: &str = "agent_runs:index";
+
+/// Redis key of a stored run (the run as JSON), expiring with the retention
+fn redis_run_key(run_id: &str) -> String {
+    format!("agent_runs:run:{}", run_id)
+}
+
+/// Redis key of a stored run's overview, so listings don't read whole runs
+fn redis_overview_key(run_id: &str) -> String {
+    format!("agent_runs:overview:{}", run_id)
+}
+
+/// Runs kept at most, and for how long, unless configured otherwise
+const DEFAULT_MAX_RUNS: usize = 1000;
+const DEFAULT_RETENTION_HOURS: i64 = 168;
+
+/// Store of completed agent runs, keyed by run_id
+///
+/// Runs are kept in memory, and also in Redis once connected, so the history
+/// survives restarts. Both keep at most `max_runs` runs, each for at most the
+/// retention period; the oldest go first. Runs stored in Redis are read back
+/// when asked for, not at startup.
+pub struct RunStore {
+    /// Recent runs, and runs read back from Redis
+    runs: Arc<DashMap<String, AgentRun>>,
+    redis: OnceCell<ConnectionManager>,
+    max_runs: usize,
+    retention: Duration,
+}
+
+impl RunStore {
+    pub fn new() -> Self {
+        Self::with_limits(DEFAULT_MAX_RUNS, Duration::hours(DEFAULT_RETENTION_HOURS))
+    }
+
+    /// RUN_HISTORY_MAX (default: 1000) and RUN_RETENTION_HOURS (default: 168)
+    pub fn from_env() -> Self {
+        let max_runs = std::env::var("RUN_HISTORY_MAX")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_MAX_RUNS);
+        let retention_hours = std::env::var("RUN_RETENTION_HOURS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_RETENTION_HOURS);
+
+        Self::with_limits(max_runs, Duration::hours(retention_hours))
+    }
+
+    pub fn with_limits(max_runs: usize, retention: Duration) -> Self {
+        Self {
+            runs: Arc::new(DashMap::new()),
+            redis: OnceCell::new(),
+            max_runs,
+            retention,
+        }
+    }
+
+    /// Persist runs in Redis when USE_REDIS=true
+    pub async fn connect_redis_from_env(&self) -> Result<()> {
+        let use_redis = std::env::var("USE_REDIS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false);
+        if !use_redis {
+            return Ok(());
+        }
+
+        let redis_url =
+            std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".to_string());
+        let client = redis::Client::open(redis_url)?;
+        let connection = ConnectionManager::new(client).await?;
+
+        let _ = self.redis.set(connection);
+        tracing::info!("Agent runs persisted in Redis");
+        Ok(())
+    }
+
+    /// Keep a run; a failure to persist it is logged, the run stays available from memory
+    pub async fn insert(&self, run: AgentRun) {
+        if let Some(connection) = self.redis.get() {
+            if let Err(e) = self.persist(&mut connection.clone(), &run).await {
+                tracing::warn!("Failed to persist run {}: {}", run.run_id, e);
+            }
+        }
+
+        self.runs.insert(run.run_id.clone(), run);
+        self.evict().await;
+    }
+
+    /// Drop runs from memory; without Redis that is the end of them, so their files go too
+    async fn evict(&self) {
+        let evicted = evict(&self.runs, self.max_runs, self.retention);
+        if self.redis.get().is_none() {
+            for run_id in &evicted {
+                remove_run_downloads(run_id).await;
+                remove_trace(run_id).await;
+            }
+        }
+    }
+
+    /// Store the run under its own expiring keys, then drop the runs past the cap from the index
+    async fn persist(&self, connection: &mut ConnectionManager, run: &AgentRun) -> Result<()> {
+        let ttl = self.retention.num_seconds().max(1) as u64;
+        let cutoff = (Utc::now() - self.retention).timestamp_millis();
+        redis::pipe()
+            .atomic()
+            .set_ex(redis_run_key(&run.run_id), serde_json::to_string(run)?, ttl)
+            .ignore()
+            .set_ex(redis_overview_key(&run.run_id), serde_json::to_string(&run.overview())?, ttl)
+            .ignore()
+            .zadd(REDIS_INDEX_KEY, &run.run_id, run.started_at.timestamp_millis())
+            .ignore()
+            .zrembyscore(REDIS_INDEX_KEY, "-inf", cutoff)
+            .ignore()
+            .query_async::<()>(connection)
+            .await?;
+
+        let surplus: Vec<String> = connection
+            .zrange(REDIS_INDEX_KEY, 0, -(self.max_runs as isize) - 1)
+            .await?;
+        if !surplus.is_empty() {
+            let keys: Vec<String> = surplus
+                .iter()
+                .flat_map(|run_id| [redis_run_key(run_id), redis_overview_key(run_id)])
+                .collect();
+            redis::pipe()
+                .atomic()
+                .zrem(REDIS_INDEX_KEY, &surplus)
+                .ignore()
+                .del(keys)
+                .ignore()
+                .query_async::<()>(connection)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// A stored run, read back from Redis if this instance doesn't have it
+    pub async fn get(&self, run_id: &str) -> Option<AgentRun> {
+        let cutoff = Utc::now() - self.retention;
+        if let Some(run) = self.runs.get(run_id).filter(|run| run.finished_at >= cutoff) {
+            return Some(run.clone());
+        }
+
+        let mut connection = self.redis.get()?.clone();
+        let json: Option<String> = match connection.get(redis_run_key(run_id)).await {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::warn!("Failed to read run {} from Redis: {}", run_id, e);
+                return None;
+            }
+        };
+        let run: AgentRun = match serde_json::from_str(&json?) {
+            Ok(run) => run,
+            Err(e) => {
+                tracing::warn!("Skipping unreadable stored run {}: {}", run_id, e);
+                return None;
+            }
+        };
+
+        self.runs.insert(run.run_id.clone(), run.clone());
+        self.evict().await;
+        Some(run)
+    }
+
+    /// Overviews of the stored runs, oldest first
+    pub async fn overviews(&self) -> Vec<RunOverview> {
+        let mut overviews = match self.redis.get() {
+            Some(connection) => match self.stored_overviews(&mut connection.clone()).await {
+                Ok(overviews) => overviews,
+                Err(e) => {
+                    tracing::warn!("Failed to list runs from Redis, listing this instance's: {}", e);
+                    self.local_overviews()
+                }
+            },
+            None => self.local_overviews(),
+        };
+        overviews.sort_by(|a, b| (a.started_at, &a.run_id).cmp(&(b.started_at, &b.run_id)));
+        overviews
+    }
+
+    fn local_overviews(&self) -> Vec<RunOverview> {
+        let cutoff = Utc::now() - self.retention;
+        self.runs
+            .iter()
+            .filter(|entry| entry.finished_at >= cutoff)
+            .map(|entry| entry.overview())
+            .collect()
+    }
+
+    async fn stored_overviews(&self, connection: &mut ConnectionManager) -> Result<Vec<RunOverview>> {
+        let cutoff = (Utc::now() - self.retention).timestamp_millis();
+        let run_ids: Vec<String> = connection.zrangebyscore(REDIS_INDEX_KEY, cutoff, "+inf").await?;
+        if run_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys: Vec<String> = run_ids.iter().map(|run_id| redis_overview_key(run_id)).collect();
+        let stored: Vec<Option<String>> = redis::cmd("MGET").arg(keys).query_async(connection).await?;
+        Ok(stored
+            .into_iter()
+            .flatten()
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect())
+    }
+
+    /// Periodically drop runs past the retention from memory, and the files they downloaded and traced
+    pub fn start_reaper(&self, interval: std::time::Duration) {
+        let runs = Arc::clone(&self.runs);
+        let (max_runs, retention) = (self.max_runs, self.retention);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                evict(&runs, max_runs, retention);
+                match prune_run_downloads(retention.to_std().unwrap_or_default()).await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::info!("Removed the downloads of {} expired run(s)", removed),
+                    Err(e) => tracing::warn!("Failed to prune run downloads: {}", e),
+                }
+                match prune_traces(retention.to_std().unwrap_or_default()).await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::info!("Removed {} expired trace(s)", removed),
+                    Err(e) => tracing::warn!("Failed to prune traces: {}", e),
+                }
+            }
+        });
+    }
+}
+
+/// Drop runs past the retention, then the oldest beyond `max_runs`; the IDs dropped
+fn evict(runs: &DashMap<String, AgentRun>, max_runs: usize, retention: Duration) -> Vec<String> {
+    let cutoff = Utc::now() - retention;
+    let mut evicted: Vec<String> = runs
+        .iter()
+        .filter(|entry| entry.finished_at < cutoff)
+        .map(|entry| entry.key().clone())
+        .collect();
+    for run_id in &evicted {
+        runs.remove(run_id);
+    }
+
+    if runs.len() > max_runs {
+        let mut by_age: Vec<(DateTime<Utc>, String)> = runs
+            .iter()
+            .map(|entry| (entry.started_at, entry.key().clone()))
+            .collect();
+        by_age.sort();
+        for (_, run_id) in by_age.into_iter().take(runs.len() - max_runs) {
+            runs.remove(&run_id);
+            evicted.push(run_id);
+        }
+    }
+    evicted
+}
+
+impl Default for RunStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(started_minutes_ago: i64) -> AgentRun {
+        let result = MultiStepExecutionResult {
+            task_completed: true,
+            steps_taken: 0,
+            max_steps: 20,
+            steps: Vec::new(),
+            final_context: None,
+            error: None,
+            retries_count: 0,
+            consent_dismissed: Vec::new(),
+            cancelled: false,
+            summary: None,
+            run_id: None,
+            downloads: Vec::new(),
+            plan: Vec::new(),
+            usage: Default::default(),
+        };
+        let started_at = Utc::now() - Duration::minutes(started_minutes_ago);
+        let mut run = AgentRun::new("session-1", "Buy a lamp", started_at, result);
+        run.finished_at = started_at;
+        run
+    }
+
+    #[test]
+    fn test_visible_to_owner_only() {
+        let mut owned = run(1);
+        owned.owner = Some("alice".to_string());
+        assert!(owned.visible_to(Some("alice")));
+        assert!(!owned.visible_to(Some("bob")));
+        assert!(!owned.visible_to(None));
+
+        // Runs started without authentication stay out of users' histories
+        let anonymous = run(1);
+        assert!(anonymous.visible_to(None));
+        assert!(!anonymous.visible_to(Some("alice")));
+    }
+
+    #[tokio::test]
+    async fn test_oldest_and_expired_runs_are_evicted() {
+        let store = RunStore::with_limits(2, Duration::hours(1));
+        let (expired, oldest, older, newest) = (run(120), run(30), run(20), run(10));
+        for run in [&expired, &oldest, &older, &newest] {
+            store.insert(run.clone()).await;
+        }
+
+        assert!(store.get(&expired.run_id).await.is_none());
+        assert!(store.get(&oldest.run_id).await.is_none());
+        let kept: Vec<String> = store.overviews().await.into_iter().map(|run| run.run_id).collect();
+        assert_eq!(kept, vec![older.run_id, newest.run_id]);
+    }
+}
@@ -0,0 +1,269 @@
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::browser::BrowserAutomation;
+use crate::models::PageCandidates;
+use crate::privacy::PiiScrubber;
+
+use super::llm_client::{ChatTurn, LLMClient};
+
+/// Completion limit for extracted data, which can list many records
+const SCRAPE_MAX_TOKENS: u32 = 4000;
+
+/// Answers asked for: the first plus one correction when it doesn't fit the schema
+const MAX_ATTEMPTS: usize = 2;
+
+/// Schema mismatches reported back to the model and the caller
+const MAX_MISMATCHES: usize = 20;
+
+const SYSTEM_PROMPT: &str = r#"You extract structured data from web pages.
+
+You get content gathered from one page and a JSON Schema. Fill the schema from the content:
+- Use only facts on the page; never invent values.
+- Use null for a field the page doesn't give, and an empty array when there are no records.
+- Prefer the JSON-LD and meta values, which are exact, over text.
+- Keep numbers as numbers: "$1,299.00" is 1299.0 for a number field.
+- For lists, take every matching record from the groups, in page order.
+
+Respond ONLY with a JSON object of the form {"data": <value matching the schema>}."#;
+
+/// Data extracted from a page into a caller's schema
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaExtraction {
+    pub url: String,
+    pub data: Value,
+
+    /// Where `data` still doesn't fit the schema after the correction, e.g. "$.price: expected number"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mismatches: Vec<String>,
+
+    /// LLM answers it took
+    pub attempts: usize,
+}
+
+/// Maps page content into a JSON Schema with the LLM
+pub struct SchemaExtractor {
+    llm_client: LLMClient,
+    pii_scrubber: Option<PiiScrubber>,
+}
+
+impl SchemaExtractor {
+    pub fn new() -> Self {
+        Self {
+            llm_client: LLMClient::new().with_max_tokens(SCRAPE_MAX_TOKENS),
+            pii_scrubber: None,
+        }
+    }
+
+    /// Builder method to scrub page text before it goes to the LLM
+    pub fn with_pii_scrubber(mut self, scrubber: Option<PiiScrubber>) -> Self {
+        self.pii_scrubber = scrubber;
+        self
+    }
+
+    /// Extract data matching `schema` from the current page
+    ///
+    /// An answer that doesn't fit the schema is sent back once with the
+    /// mismatches; whatever remains after that is reported, not fixed up.
+    pub async fn extract(
+        &self,
+        browser: &BrowserAutomation,
+        schema: &Value,
+        instructions: Option<&str>,
+    ) -> Result<SchemaExtraction> {
+        let mut candidates = browser.scrape_candidates().await?;
+        if let Some(scrubber) = &self.pii_scrubber {
+            candidates.map_text(|text| scrubber.scrub(text));
+        }
+
+        let mut prompt = build_scrape_prompt(&candidates, schema, instructions);
+        let mut history = Vec::new();
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let response = self
+                .llm_client
+                .generate_json_with_history(SYSTEM_PROMPT, &history, &prompt)
+                .await?;
+
+            let (data, mismatches) = match serde_json::from_str::<Value>(&response) {
+                Ok(mut answer) => {
+                    let data = answer.get_mut("data").map(Value::take).unwrap_or(Value::Null);
+                    let mismatches = schema_mismatches(schema, &data);
+                    (data, mismatches)
+                }
+                Err(e) => (Value::Null, vec![format!("the answer is not valid JSON: {}", e)]),
+            };
+
+            if mismatches.is_empty() || attempts >= MAX_ATTEMPTS {
+                return Ok(SchemaExtraction {
+                    url: candidates.url,
+                    data,
+                    mismatches,
+                    attempts,
+                });
+            }
+
+            tracing::debug!("Extracted data doesn't fit the schema: {:?}", mismatches);
+            let correction = format!(
+                "Your answer doesn't match the schema:\n- {}\n\nAnswer again with the corrected {{\"data\": ...}} object.",
+                mismatches.join("\n- ")
+            );
+            history.push(ChatTurn { user: prompt, assistant: response });
+            prompt = correction;
+        }
+    }
+}
+
+fn build_scrape_prompt(candidates: &PageCandidates, schema: &Value, instructions: Option<&str>) -> String {
+    let content = serde_json::to_string_pretty(candidates).unwrap_or_default();
+    let schema = serde_json::to_string_pretty(schema).unwrap_or_default();
+    let instructions = instructions
+        .map(|text| format!("\n\nInstructions: {}", text))
+        .unwrap_or_default();
+
+    format!(
+        "Page content:\n{}\n\nJSON Schema:\n{}{}\n\nRespond with {{\"data\": ...}}.",
+        content, schema, instructions
+    )
+}
+
+/// Where `value` breaks `schema`, as "$.path: problem" lines
+///
+/// Covers the parts of JSON Schema that describe data shapes: `type`,
+/// `enum`, `properties`, `required` and `items`. Optional properties may be
+/// null, as the model is told to use null for values the page lacks.
+pub fn schema_mismatches(schema: &Value, value: &Value) -> Vec<String> {
+    let mut mismatches = Vec::new();
+    check(schema, value, "$", &mut mismatches);
+    mismatches.truncate(MAX_MISMATCHES);
+    mismatches
+}
+
+fn check(schema: &Value, value: &Value, path: &str, mismatches: &mut Vec<String>) {
+    if mismatches.len() >= MAX_MISMATCHES {
+        return;
+    }
+
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::String(kind)) => vec![kind.as_str()],
+        Some(Value::Array(kinds)) => kinds.iter().filter_map(Value::as_str).collect(),
+        _ if schema.get("properties").is_some() => vec!["object"],
+        _ if schema.get("items").is_some() => vec!["array"],
+        _ => vec![],
+    };
+    if !types.is_empty() && !types.iter().any(|kind| has_type(value, kind)) {
+        mismatches.push(format!("{}: expected {}, got {}", path, types.join(" or "), type_name(value)));
+        return;
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            mismatches.push(format!("{}: {} is not one of {}", path, value, Value::Array(options.clone())));
+        }
+    }
+
+    match value {
+        Value::Object(fields) => {
+            let required: Vec<&str> = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|names| names.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            for name in &required {
+                if !fields.contains_key(*name) {
+                    mismatches.push(format!("{}.{}: missing", path, name));
+                }
+            }
+            if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                for (name, property) in properties {
+                    match fields.get(name) {
+                        Some(Value::Null) if !required.contains(&name.as_str()) => {}
+                        Some(field) => check(property, field, &format!("{}.{}", path, name), mismatches),
+                        None => {}
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check(item_schema, item, &format!("{}[{}]", path, index), mismatches);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn has_type(value: &Value, kind: &str) -> bool {
+    match kind {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_mismatches() {
+        let schema = json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "price": { "type": "number" },
+                    "stock": { "enum": ["in_stock", "sold_out"] },
+                    "rating": { "type": ["number", "null"] }
+                },
+                "required": ["name", "price"]
+            }
+        });
+
+        let products = json!([
+            { "name": "Desk lamp", "price": 39.5, "stock": "in_stock", "rating": null },
+            { "name": "Chair", "price": 120, "stock": null }
+        ]);
+        assert!(schema_mismatches(&schema, &products).is_empty());
+
+        let broken = json!([
+            { "name": "Desk lamp", "price": "$39.50" },
+            { "price": 12, "stock": "backorder" }
+        ]);
+        assert_eq!(
+            schema_mismatches(&schema, &broken),
+            vec![
+                "$[0].price: expected number, got string",
+                "$[1].name: missing",
+                "$[1].stock: \"backorder\" is not one of [\"in_stock\",\"sold_out\"]",
+            ]
+        );
+
+        // Required fields can't be null
+        let nameless = json!([{ "name": null, "price": 5 }]);
+        assert_eq!(schema_mismatches(&schema, &nameless), vec!["$[0].name: expected string, got null"]);
+        assert_eq!(schema_mismatches(&schema, &json!({})), vec!["$: expected array, got object"]);
+    }
+}
@@ -0,0 +1,800 @@
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{Notify, OwnedMutexGuard};
+use uuid::Uuid;
+
+use super::control::{RunControl, RunState};
+use super::executor::MultiStepExecutionResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    /// Waiting for earlier tasks on the same session
+    Queued,
+    Running,
+    /// Running, but waits before its next step until resumed
+    Paused,
+    /// The run ended; `result.task_completed` tells whether the task was achieved
+    Completed,
+    /// The run stopped with an error, e.g. the LLM was unreachable
+    Failed,
+    Cancelled,
+}
+
+impl TaskStatus {
+    pub fn is_active(self) -> bool {
+        matches!(self, Self::Queued | Self::Running | Self::Paused)
+    }
+}
+
+/// Scheduling class of a task among the other tasks of its session
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskPriority {
+    /// Runs after the queued interactive tasks, and yields to new ones between two steps
+    Batch,
+    /// Someone waits for the result
+    #[default]
+    Interactive,
+}
+
+/// A multi-step agent run submitted to run in the background
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentTask {
+    pub task_id: String,
+    pub session_id: String,
+    /// User who submitted the task; `None` when it was submitted without authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub task: String,
+    pub status: TaskStatus,
+    #[serde(default)]
+    pub priority: TaskPriority,
+
+    /// Interactive task this batch task yielded the session to; it waits between two steps meanwhile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preempted_by: Option<String>,
+
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+
+    /// Steps finished so far
+    pub steps_completed: usize,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<MultiStepExecutionResult>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AgentTask {
+    pub fn new(session_id: &str, task: &str) -> Self {
+        Self {
+            task_id: Uuid::new_v4().to_string(),
+            session_id: session_id.to_string(),
+            owner: None,
+            task: task.to_string(),
+            status: TaskStatus::Queued,
+            priority: TaskPriority::default(),
+            preempted_by: None,
+            created_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+            steps_completed: 0,
+            result: None,
+            error: None,
+        }
+    }
+
+    /// Whether `user` (`None` without authentication) may see the task: only its owner may
+    pub fn visible_to(&self, user: Option<&str>) -> bool {
+        self.owner.as_deref() == user
+    }
+}
+
+/// Turn order of a session's active tasks, for operators
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSchedule {
+    pub session_id: String,
+
+    /// Task that has the session's browser
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub running: Option<AgentTask>,
+
+    /// Batch task waiting between two steps until no interactive task is queued
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preempted: Option<AgentTask>,
+
+    /// Tasks waiting for their turn, in the order they will get it
+    pub queued: Vec<AgentTask>,
+}
+
+/// A run that yielded its session to a task of higher priority, kept between two steps
+struct Suspended {
+    task_id: String,
+    priority: TaskPriority,
+    control: RunControl,
+    /// Whether the run was paused to yield, so it is resumed once it continues
+    paused: bool,
+    /// Keeps the run from starting its next step
+    _hold: OwnedMutexGuard<()>,
+}
+
+impl Drop for Suspended {
+    fn drop(&mut self) {
+        if self.paused {
+            self.control.resume();
+        }
+    }
+}
+
+/// Turns of one session's tasks
+#[derive(Default)]
+struct SessionQueue {
+    /// Task that has the session's browser
+    running: Option<(String, TaskPriority)>,
+    suspended: Option<Suspended>,
+    /// Tasks waiting for their turn, with their place in submission order
+    waiting: Vec<(String, TaskPriority, u64)>,
+}
+
+impl SessionQueue {
+    /// Waiting task that gets the next turn: the first one of the highest priority
+    fn next(&self) -> Option<&(String, TaskPriority, u64)> {
+        self.waiting
+            .iter()
+            .max_by_key(|(_, priority, submitted)| (*priority, Reverse(*submitted)))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.running.is_none() && self.suspended.is_none() && self.waiting.is_empty()
+    }
+}
+
+/// What a task waiting for its turn does next
+enum Next {
+    Taken,
+    /// The task is no longer queued
+    Gone,
+    Wait,
+    /// Hold the running task (ID and control) between two steps, then take the session
+    Preempt(String, RunControl),
+}
+
+/// A task's turn on its session; ends when dropped
+pub struct SessionTurn {
+    store: Arc<TaskStore>,
+    session_id: String,
+    task_id: String,
+}
+
+impl Drop for SessionTurn {
+    fn drop(&mut self) {
+        self.store.end_turn(&self.session_id, &self.task_id);
+    }
+}
+
+/// How long finished tasks can be polled, unless TASK_RETENTION_SECONDS says otherwise
+const DEFAULT_RETENTION_SECONDS: i64 = 3600;
+
+/// In-memory store of background agent tasks, keyed by task_id
+///
+/// Tasks on the same session run one at a time: interactive tasks before
+/// batch tasks, and tasks of the same priority in submission order.
+pub struct TaskStore {
+    tasks: DashMap<String, AgentTask>,
+
+    /// Turns of the sessions with active tasks
+    queues: DashMap<String, SessionQueue>,
+
+    /// Tasks submitted so far, for their place in the queue
+    submitted: AtomicU64,
+
+    /// Woken whenever a turn may have become free
+    turn_changed: Notify,
+
+    /// Pause and cancel switches of active tasks, handed to their run when it starts
+    controls: DashMap<String, RunControl>,
+
+    /// How long finished tasks are kept
+    retention: Duration,
+}
+
+impl TaskStore {
+    pub fn new() -> Self {
+        Self::with_retention(Duration::seconds(DEFAULT_RETENTION_SECONDS))
+    }
+
+    pub fn from_env() -> Self {
+        let retention_seconds = std::env::var("TASK_RETENTION_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_RETENTION_SECONDS);
+
+        Self::with_retention(Duration::seconds(retention_seconds))
+    }
+
+    pub fn with_retention(retention: Duration) -> Self {
+        Self {
+            tasks: DashMap::new(),
+            queues: DashMap::new(),
+            submitted: AtomicU64::new(0),
+            turn_changed: Notify::new(),
+            controls: DashMap::new(),
+            retention,
+        }
+    }
+
+    /// Queue a task on its session
+    pub fn insert(&self, task: AgentTask) {
+        let submitted = self.submitted.fetch_add(1, Ordering::Relaxed);
+        self.controls.insert(task.task_id.clone(), RunControl::new());
+        self.queues
+            .entry(task.session_id.clone())
+            .or_default()
+            .waiting
+            .push((task.task_id.clone(), task.priority, submitted));
+        self.tasks.insert(task.task_id.clone(), task);
+    }
+
+    /// The task as it is now; a running task whose run is paused, or that
+    /// yielded to an interactive task, shows as paused
+    ///
+    /// A running task that was cancelled shows as cancelled while it finishes
+    /// its current step.
+    pub fn get(&self, task_id: &str) -> Option<AgentTask> {
+        let mut task = self.tasks.get(task_id).map(|entry| entry.clone())?;
+        if task.status == TaskStatus::Running {
+            match self.control(task_id).map(|control| control.state()) {
+                Some(RunState::Cancelled) => task.status = TaskStatus::Cancelled,
+                Some(RunState::Paused) => task.status = TaskStatus::Paused,
+                _ if task.preempted_by.is_some() => task.status = TaskStatus::Paused,
+                _ => {}
+            }
+        }
+        Some(task)
+    }
+
+    /// Pause, resume and cancel switch of an active task, for its run to check between steps
+    pub fn control(&self, task_id: &str) -> Option<RunControl> {
+        self.controls.get(task_id).map(|control| control.clone())
+    }
+
+    /// Hold the task before its next step; a queued task holds before its first
+    ///
+    /// Returns the task as it is afterwards. The step in progress finishes, and
+    /// the run keeps its steps and page state while it waits.
+    pub fn pause(&self, task_id: &str) -> Option<AgentTask> {
+        if let Some(control) = self.control(task_id) {
+            control.pause();
+        }
+        self.get(task_id)
+    }
+
+    /// Let a paused task continue with its next step; returns the task as it is afterwards
+    pub fn resume(&self, task_id: &str) -> Option<AgentTask> {
+        if let Some(control) = self.control(task_id) {
+            control.resume();
+        }
+        self.get(task_id)
+    }
+
+    /// Wait for the task's turn on its session; the turn ends when the guard drops
+    ///
+    /// A running batch task yields to a queued interactive one: it is paused,
+    /// and the interactive task starts once the batch task's step in progress
+    /// has finished. The batch task continues where it stopped when no
+    /// interactive task is left in the queue. `None` if the task was cancelled
+    /// while queued.
+    pub async fn turn(self: &Arc<Self>, task_id: &str) -> Option<SessionTurn> {
+        let session_id = self.tasks.get(task_id)?.session_id.clone();
+        loop {
+            // Registered before looking, so a turn freed meanwhile isn't missed
+            let changed = self.turn_changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            match self.try_turn(&session_id, task_id) {
+                Next::Taken => {
+                    return Some(SessionTurn {
+                        store: Arc::clone(self),
+                        session_id,
+                        task_id: task_id.to_string(),
+                    })
+                }
+                Next::Gone => return None,
+                Next::Wait => changed.await,
+                Next::Preempt(running, control) => {
+                    let paused = control.pause() == RunState::Paused;
+                    let hold = control.hold().await;
+                    tracing::info!("Agent task {} yields session {} to task {}", running, session_id, task_id);
+                    self.suspend(
+                        &session_id,
+                        Suspended {
+                            task_id: running,
+                            priority: TaskPriority::Batch,
+                            control,
+                            paused,
+                            _hold: hold,
+                        },
+                        task_id,
+                    );
+                }
+            }
+        }
+    }
+
+    fn try_turn(&self, session_id: &str, task_id: &str) -> Next {
+        let Some(mut queue) = self.queues.get_mut(session_id) else {
+            return Next::Gone;
+        };
+        if !queue.waiting.iter().any(|(id, _, _)| id == task_id) {
+            return Next::Gone;
+        }
+        let Some((next, priority, _)) = queue.next().cloned() else {
+            return Next::Gone;
+        };
+        if next != task_id {
+            return Next::Wait;
+        }
+
+        match &queue.running {
+            None if queue.suspended.as_ref().is_some_and(|suspended| suspended.priority >= priority) => Next::Wait,
+            None => {
+                queue.waiting.retain(|(id, _, _)| id != task_id);
+                queue.running = Some((task_id.to_string(), priority));
+                Next::Taken
+            }
+            // Only a run that is going on yields; one paused by an operator keeps the session
+            Some((running, running_priority)) if *running_priority < priority && queue.suspended.is_none() => {
+                match self.control(running) {
+                    Some(control) if control.state() == RunState::Running => Next::Preempt(running.clone(), control),
+                    _ => Next::Wait,
+                }
+            }
+            Some(_) => Next::Wait,
+        }
+    }
+
+    /// Keep the running task between two steps, so `preempted_by` can take the session
+    fn suspend(&self, session_id: &str, suspended: Suspended, preempted_by: &str) {
+        let task_id = suspended.task_id.clone();
+        let kept = match self.queues.get_mut(session_id) {
+            // The run may have ended while its step in progress finished
+            Some(mut queue) if queue.running.as_ref().is_some_and(|(running, _)| *running == task_id) => {
+                queue.running = None;
+                queue.suspended = Some(suspended);
+                true
+            }
+            _ => false,
+        };
+        if kept {
+            if let Some(mut task) = self.tasks.get_mut(&task_id) {
+                task.preempted_by = Some(preempted_by.to_string());
+            }
+        }
+        self.settle(session_id);
+    }
+
+    fn end_turn(&self, session_id: &str, task_id: &str) {
+        let suspended = self.queues.get_mut(session_id).and_then(|mut queue| {
+            if queue.running.as_ref().is_some_and(|(running, _)| running == task_id) {
+                queue.running = None;
+            }
+            queue.waiting.retain(|(id, _, _)| id != task_id);
+            queue
+                .suspended
+                .take_if(|suspended| suspended.task_id == task_id)
+        });
+        drop(suspended);
+        self.settle(session_id);
+    }
+
+    /// Let a suspended task continue once nothing of higher priority waits, and wake the waiters
+    fn settle(&self, session_id: &str) {
+        let resumed = self.queues.get_mut(session_id).and_then(|mut queue| {
+            let priority = queue.suspended.as_ref()?.priority;
+            if queue.running.is_some() || queue.waiting.iter().any(|(_, waiting, _)| *waiting > priority) {
+                return None;
+            }
+            let suspended = queue.suspended.take()?;
+            queue.running = Some((suspended.task_id.clone(), suspended.priority));
+            Some(suspended)
+        });
+        if let Some(suspended) = resumed {
+            if let Some(mut task) = self.tasks.get_mut(&suspended.task_id) {
+                task.preempted_by = None;
+            }
+            tracing::info!("Agent task {} continues on session {}", suspended.task_id, session_id);
+            // Dropping it resumes the run
+        }
+
+        self.queues.remove_if(session_id, |_, queue| queue.is_empty());
+        self.turn_changed.notify_waiters();
+    }
+
+    /// Turn order of every session with active tasks
+    pub fn schedules(&self) -> Vec<SessionSchedule> {
+        let mut schedules: Vec<SessionSchedule> = self
+            .queues
+            .iter()
+            .map(|queue| {
+                let mut waiting = queue.waiting.clone();
+                waiting.sort_by_key(|(_, priority, submitted)| (Reverse(*priority), *submitted));
+                SessionSchedule {
+                    session_id: queue.key().clone(),
+                    running: queue.running.as_ref().and_then(|(task_id, _)| self.get(task_id)),
+                    preempted: queue.suspended.as_ref().and_then(|suspended| self.get(&suspended.task_id)),
+                    queued: waiting.iter().filter_map(|(task_id, _, _)| self.get(task_id)).collect(),
+                }
+            })
+            .collect();
+        schedules.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        schedules
+    }
+
+    /// The task got its turn; `false` if it was cancelled while queued
+    pub fn start(&self, task_id: &str) -> bool {
+        let Some(mut task) = self.tasks.get_mut(task_id) else {
+            return false;
+        };
+        if task.status != TaskStatus::Queued {
+            return false;
+        }
+        task.status = TaskStatus::Running;
+        task.started_at = Some(Utc::now());
+        true
+    }
+
+    pub fn record_step(&self, task_id: &str, step_number: usize) {
+        if let Some(mut task) = self.tasks.get_mut(task_id) {
+            task.steps_completed = task.steps_completed.max(step_number);
+        }
+    }
+
+    /// Store the outcome of the run; a cancelled task stays cancelled
+    ///
+    /// A run stopped through its run control ends up cancelled as well.
+    pub fn finish(&self, task_id: &str, outcome: Result<MultiStepExecutionResult, String>) {
+        self.controls.remove(task_id);
+        let Some(mut task) = self.tasks.get_mut(task_id) else {
+            return;
+        };
+        if !task.status.is_active() {
+            return;
+        }
+
+        task.finished_at = Some(Utc::now());
+        match outcome {
+            Ok(result) if result.cancelled => {
+                task.status = TaskStatus::Cancelled;
+                task.steps_completed = result.steps_taken;
+                task.result = Some(result);
+            }
+            Ok(result) => {
+                task.status = TaskStatus::Completed;
+                task.steps_completed = result.steps_taken;
+                task.result = Some(result);
+            }
+            Err(error) => {
+                task.status = TaskStatus::Failed;
+                task.error = Some(error);
+            }
+        }
+    }
+
+    /// Stop a queued or running task; returns the task as it is afterwards
+    ///
+    /// A queued task never starts. A running task stops before its next step,
+    /// through its run control, so the step in progress finishes and the run
+    /// keeps the steps it took.
+    pub fn cancel(&self, task_id: &str) -> Option<AgentTask> {
+        let session_id = {
+            let mut task = self.tasks.get_mut(task_id)?;
+            match task.status {
+                TaskStatus::Queued => {
+                    self.controls.remove(task_id);
+                    task.status = TaskStatus::Cancelled;
+                    task.finished_at = Some(Utc::now());
+                }
+                TaskStatus::Running | TaskStatus::Paused => {
+                    if let Some(control) = self.control(task_id) {
+                        control.cancel();
+                    }
+                }
+                _ => {}
+            }
+            task.session_id.clone()
+        };
+
+        // A queued task leaves the queue; a suspended one is let go, so its run can stop
+        let suspended = self.queues.get_mut(&session_id).and_then(|mut queue| {
+            queue.waiting.retain(|(id, _, _)| id != task_id);
+            queue
+                .suspended
+                .take_if(|suspended| suspended.task_id == task_id)
+        });
+        drop(suspended);
+        self.settle(&session_id);
+
+        self.get(task_id)
+    }
+
+    /// Cancel the session's active tasks and drop its finished ones and its turn,
+    /// once the session is closed
+    ///
+    /// Cancelled tasks that are still running are dropped after they finish.
+    pub fn forget_session(&self, session_id: &str) {
+        let task_ids: Vec<String> = self
+            .tasks
+            .iter()
+            .filter(|task| task.session_id == session_id)
+            .map(|task| task.key().clone())
+            .collect();
+        for task_id in &task_ids {
+            self.cancel(task_id);
+        }
+
+        self.tasks
+            .retain(|_, task| task.session_id != session_id || task.status.is_active());
+        self.queues.remove(session_id);
+        self.turn_changed.notify_waiters();
+    }
+
+    /// Drop finished tasks past the retention, and forget sessions for which
+    /// `is_open` is false
+    fn sweep(&self, is_open: impl Fn(&str) -> bool) {
+        let cutoff = Utc::now() - self.retention;
+        self.tasks.retain(|_, task| {
+            task.status.is_active() || task.finished_at.is_some_and(|finished_at| finished_at >= cutoff)
+        });
+
+        let closed: HashSet<String> = self
+            .tasks
+            .iter()
+            .map(|task| task.session_id.clone())
+            .chain(self.queues.iter().map(|queue| queue.key().clone()))
+            .filter(|session_id| !is_open(session_id))
+            .collect();
+        for session_id in closed {
+            self.forget_session(&session_id);
+        }
+    }
+
+    /// Periodically drop finished tasks past TASK_RETENTION_SECONDS and the
+    /// tasks of sessions for which `is_open` is false
+    pub fn start_reaper(
+        self: &Arc<Self>,
+        interval: std::time::Duration,
+        is_open: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) {
+        let store = Arc::clone(self);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                store.sweep(&is_open);
+            }
+        });
+    }
+}
+
+impl Default for TaskStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_lifecycle() {
+        let store = TaskStore::new();
+        let task = AgentTask::new("session-1", "Buy a lamp");
+        let id = task.task_id.clone();
+        store.insert(task);
+
+        assert!(store.start(&id));
+        assert!(!store.start(&id));
+        store.record_step(&id, 2);
+        assert_eq!(store.get(&id).unwrap().steps_completed, 2);
+
+        store.finish(&id, Err("Multi-step agent execution failed: LLM timeout".to_string()));
+        let task = store.get(&id).unwrap();
+        assert_eq!(task.status, TaskStatus::Failed);
+        assert!(task.finished_at.is_some());
+
+        // Finished tasks can't be cancelled
+        assert_eq!(store.cancel(&id).unwrap().status, TaskStatus::Failed);
+        assert!(store.cancel("missing").is_none());
+    }
+
+    #[test]
+    fn test_only_the_owner_sees_a_task() {
+        let mut task = AgentTask::new("session-1", "Buy a lamp");
+        assert!(task.visible_to(None));
+
+        task.owner = Some("alice".to_string());
+        assert!(task.visible_to(Some("alice")));
+        assert!(!task.visible_to(Some("bob")));
+        assert!(!task.visible_to(None));
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let store = TaskStore::new();
+        let task = AgentTask::new("session-1", "Buy a lamp");
+        let id = task.task_id.clone();
+        store.insert(task);
+
+        // A queued task holds before its first step once it starts
+        assert_eq!(store.pause(&id).unwrap().status, TaskStatus::Queued);
+        assert!(store.start(&id));
+        assert_eq!(store.get(&id).unwrap().status, TaskStatus::Paused);
+        assert_eq!(store.control(&id).unwrap().state(), RunState::Paused);
+
+        assert_eq!(store.resume(&id).unwrap().status, TaskStatus::Running);
+        assert_eq!(store.pause(&id).unwrap().status, TaskStatus::Paused);
+
+        store.finish(&id, Err("aborted".to_string()));
+        assert!(store.control(&id).is_none());
+        assert_eq!(store.resume(&id).unwrap().status, TaskStatus::Failed);
+    }
+
+    #[test]
+    fn test_cancelled_task_stays_cancelled() {
+        let store = TaskStore::new();
+        let task = AgentTask::new("session-1", "Buy a lamp");
+        let id = task.task_id.clone();
+        store.insert(task);
+
+        assert_eq!(store.cancel(&id).unwrap().status, TaskStatus::Cancelled);
+        assert!(!store.start(&id));
+        store.finish(&id, Err("aborted".to_string()));
+        assert_eq!(store.get(&id).unwrap().status, TaskStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_running_task_cancels_before_its_next_step() {
+        let store = TaskStore::new();
+        let task = AgentTask::new("session-1", "Buy a lamp");
+        let id = task.task_id.clone();
+        store.insert(task);
+        assert!(store.start(&id));
+
+        let control = store.control(&id).unwrap();
+        assert_eq!(store.cancel(&id).unwrap().status, TaskStatus::Cancelled);
+        // The run stops through its control; the task is recorded once it does
+        assert_eq!(control.state(), RunState::Cancelled);
+        assert!(store.get(&id).unwrap().finished_at.is_none());
+    }
+
+    #[test]
+    fn test_sweep_drops_expired_tasks_and_closed_sessions() {
+        let store = TaskStore::with_retention(Duration::seconds(60));
+        let finished = AgentTask::new("session-1", "Buy a lamp");
+        let old = AgentTask::new("session-1", "Buy a chair");
+        let running = AgentTask::new("session-2", "Buy a desk");
+        let (finished_id, old_id, running_id) =
+            (finished.task_id.clone(), old.task_id.clone(), running.task_id.clone());
+        for task in [finished, old, running] {
+            store.insert(task);
+        }
+
+        store.finish(&finished_id, Err("LLM timeout".to_string()));
+        store.finish(&old_id, Err("LLM timeout".to_string()));
+        store.tasks.get_mut(&old_id).unwrap().finished_at = Some(Utc::now() - Duration::seconds(120));
+        assert!(store.start(&running_id));
+
+        store.sweep(|_| true);
+        assert!(store.get(&finished_id).is_some());
+        assert!(store.get(&old_id).is_none());
+
+        // Closing session-2 cancels its task, which is dropped once it finishes
+        store.sweep(|session_id| session_id == "session-1");
+        assert_eq!(store.get(&running_id).unwrap().status, TaskStatus::Cancelled);
+        store.finish(&running_id, Err("aborted".to_string()));
+        store.sweep(|session_id| session_id == "session-1");
+        assert!(store.get(&running_id).is_none());
+        assert!(store.get(&finished_id).is_some());
+    }
+
+    fn queue(store: &TaskStore, session_id: &str, priority: TaskPriority) -> String {
+        let mut task = AgentTask::new(session_id, "Buy a lamp");
+        task.priority = priority;
+        let id = task.task_id.clone();
+        store.insert(task);
+        id
+    }
+
+    fn pending<T>(future: impl std::future::Future<Output = T>) -> bool {
+        futures::FutureExt::now_or_never(future).is_none()
+    }
+
+    #[tokio::test]
+    async fn test_session_turns_are_exclusive() {
+        let store = Arc::new(TaskStore::new());
+        let first = queue(&store, "session-1", TaskPriority::Interactive);
+        let second = queue(&store, "session-1", TaskPriority::Interactive);
+        let other = queue(&store, "session-2", TaskPriority::Interactive);
+
+        let turn = store.turn(&first).await.unwrap();
+        // Other sessions don't wait
+        let _other = store.turn(&other).await.unwrap();
+        assert!(pending(store.turn(&second)));
+
+        drop(turn);
+        assert!(store.turn(&second).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_interactive_tasks_go_before_batch_tasks() {
+        let store = Arc::new(TaskStore::new());
+        let first = queue(&store, "session-1", TaskPriority::Interactive);
+        let batch = queue(&store, "session-1", TaskPriority::Batch);
+        let interactive = queue(&store, "session-1", TaskPriority::Interactive);
+
+        let turn = store.turn(&first).await.unwrap();
+        let schedule = &store.schedules()[0];
+        assert_eq!(schedule.running.as_ref().unwrap().task_id, first);
+        let queued: Vec<&str> = schedule.queued.iter().map(|task| task.task_id.as_str()).collect();
+        assert_eq!(queued, [interactive.as_str(), batch.as_str()]);
+
+        drop(turn);
+        assert!(pending(store.turn(&batch)));
+        let turn = store.turn(&interactive).await.unwrap();
+        drop(turn);
+        assert!(store.turn(&batch).await.is_some());
+
+        // A cancelled task gives up its place
+        let cancelled = queue(&store, "session-2", TaskPriority::Interactive);
+        store.cancel(&cancelled);
+        assert!(store.turn(&cancelled).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_batch_task_yields_between_steps() {
+        let store = Arc::new(TaskStore::new());
+        let batch = queue(&store, "session-1", TaskPriority::Batch);
+        let batch_turn = store.turn(&batch).await.unwrap();
+        assert!(store.start(&batch));
+        let control = store.control(&batch).unwrap();
+        let step = control.step().await.unwrap();
+
+        let interactive = queue(&store, "session-1", TaskPriority::Interactive);
+        let waiting = {
+            let store = Arc::clone(&store);
+            let interactive = interactive.clone();
+            tokio::spawn(async move { store.turn(&interactive).await })
+        };
+        // The step in progress finishes first
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+        assert_eq!(control.state(), RunState::Paused);
+
+        drop(step);
+        let interactive_turn = waiting.await.unwrap().unwrap();
+        let task = store.get(&batch).unwrap();
+        assert_eq!(task.status, TaskStatus::Paused);
+        assert_eq!(task.preempted_by.as_deref(), Some(interactive.as_str()));
+        assert_eq!(store.schedules()[0].preempted.as_ref().unwrap().task_id, batch);
+        assert!(pending(control.step()));
+
+        // The batch run continues once the interactive task is done
+        drop(interactive_turn);
+        assert!(control.step().await.is_some());
+        assert_eq!(store.get(&batch).unwrap().status, TaskStatus::Running);
+        drop(batch_turn);
+        assert!(store.schedules().is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use ui_core::transition::PageTransition;
+
+use crate::browser::downloads::remove_dirs_older_than;
+use crate::browser::BrowserAutomation;
+use crate::models::{ActionRequest, ActionResponse, ScreenshotFormat, SemanticSelector};
+use crate::privacy::PiiScrubber;
+
+use super::executor::ConversationStep;
+use super::runs::AgentRun;
+
+/// JPEG quality of trace screenshots
+const TRACE_SCREENSHOT_QUALITY: u8 = 70;
+
+/// Files captured for one step of a traced run
+///
+/// While the run is going these are file names in its trace directory; the
+/// stored run links them as URLs (see `link_trace`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StepTrace {
+    /// Viewport before the action was decided
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before_screenshot: Option<String>,
+
+    /// Viewport once the action ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after_screenshot: Option<String>,
+
+    /// HTML of the page once the action ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dom_snapshot: Option<String>,
+}
+
+/// TRACE_DIR, or `mcp-traces` in the system temp dir
+fn trace_root() -> PathBuf {
+    std::env::var("TRACE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| std::env::temp_dir().join("mcp-traces"))
+}
+
+/// Where the trace files of a run are kept
+pub fn run_trace_dir(run_id: &str) -> PathBuf {
+    trace_root().join(run_id)
+}
+
+/// Remove the traces of runs that finished more than `max_age` ago, and those of runs never stored
+pub async fn prune_traces(max_age: std::time::Duration) -> Result<usize> {
+    remove_dirs_older_than(&trace_root(), max_age).await
+}
+
+/// Remove the trace of a run, if it has one
+pub async fn remove_trace(run_id: &str) {
+    if let Err(e) = tokio::fs::remove_dir_all(run_trace_dir(run_id)).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("Failed to remove the trace of run {}: {}", run_id, e);
+        }
+    }
+}
+
+/// Whether `name` can be a trace file: no separators or parent references
+pub fn is_trace_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Writes the screenshots and DOM snapshots of a run's steps
+///
+/// Screenshots can't be scrubbed, so none are taken while PII scrubbing is
+/// on; DOM snapshots are scrubbed like any other page text.
+#[derive(Debug, Clone)]
+pub struct Tracer {
+    dir: PathBuf,
+    dom: bool,
+}
+
+impl Tracer {
+    /// Trace into a directory of its own, to be moved to the run's once the run is stored
+    pub fn new(dom: bool) -> Self {
+        Self {
+            dir: run_trace_dir(&format!("pending-{}", uuid::Uuid::new_v4())),
+            dom,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Screenshot of the viewport as `step-<n>-<phase>.jpg`; `None` if it failed or scrubbing is on
+    pub async fn screenshot(
+        &self,
+        browser: &BrowserAutomation,
+        scrubber: Option<&PiiScrubber>,
+        step: usize,
+        phase: &str,
+    ) -> Option<String> {
+        if scrubber.is_some() {
+            return None;
+        }
+
+        let name = format!("step-{}-{}.jpg", step, phase);
+        let captured = async {
+            let image = browser
+                .screenshot(ScreenshotFormat::Jpeg, false, Some(TRACE_SCREENSHOT_QUALITY))
+                .await?;
+            self.write(&name, &image).await
+        };
+        match captured.await {
+            Ok(()) => Some(name),
+            Err(e) => {
+                tracing::warn!("Agent: Failed to capture the {} screenshot of step {}: {}", phase, step, e);
+                None
+            }
+        }
+    }
+
+    /// The page's HTML as `step-<n>.html`, when DOM snapshots are on
+    pub async fn dom_snapshot(
+        &self,
+        browser: &BrowserAutomation,
+        scrubber: Option<&PiiScrubber>,
+        step: usize,
+    ) -> Option<String> {
+        if !self.dom {
+            return None;
+        }
+
+        let name = format!("step-{}.html", step);
+        let captured = async {
+            let mut html = browser.html().await?;
+            if let Some(scrubber) = scrubber {
+                html = scrubber.scrub(&html);
+            }
+            self.write(&name, html.as_bytes()).await
+        };
+        match captured.await {
+            Ok(()) => Some(name),
+            Err(e) => {
+                tracing::warn!("Agent: Failed to snapshot the DOM of step {}: {}", step, e);
+                None
+            }
+        }
+    }
+
+    async fn write(&self, name: &str, contents: &[u8]) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.dir.join(name), contents).await?;
+        Ok(())
+    }
+}
+
+/// Turn the trace file names of a run's steps into URLs under `base_url`
+pub fn link_trace(steps: &mut [ConversationStep], base_url: &str) {
+    let link = |file: &mut Option<String>| {
+        if let Some(name) = file {
+            *name = format!("{}/{}", base_url, name);
+        }
+    };
+    for trace in steps.iter_mut().filter_map(|step| step.trace.as_mut()) {
+        link(&mut trace.before_screenshot);
+        link(&mut trace.after_screenshot);
+        link(&mut trace.dom_snapshot);
+    }
+}
+
+/// The action of a step as it can be replayed outside the run
+///
+/// An element picked by ID is given the role and name it had in the page
+/// state the action was decided on, since the ID means nothing elsewhere.
+pub fn replayable_action(steps: &[ConversationStep], index: usize) -> ActionRequest {
+    let mut action = steps[index].action_decided.clone();
+    let decided_on = index.checked_sub(1).map(|previous| &steps[previous].context_after);
+    if let (Some(selector), Some(context)) = (action.selector_mut(), decided_on) {
+        let element = selector
+            .element_id
+            .and_then(|id| context.elements.iter().find(|element| element.id == id));
+        if let Some(element) = element {
+            *selector = SemanticSelector {
+                element_id: None,
+                ..element.selector.clone()
+            };
+        }
+    }
+    action
+}
+
+/// One step of a trace bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceStep {
+    pub step_number: usize,
+    /// The action, ready to send to `/execute`
+    pub action: ActionRequest,
+    pub result: ActionResponse,
+    /// Page after the action
+    pub url: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transition: Option<PageTransition>,
+    #[serde(flatten)]
+    pub trace: StepTrace,
+}
+
+/// A run step by step, with the captured screenshots and snapshots
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceBundle {
+    pub run_id: String,
+    pub session_id: String,
+    pub task: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub task_completed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub steps: Vec<TraceStep>,
+}
+
+impl TraceBundle {
+    pub fn new(run: &AgentRun) -> Self {
+        let steps = &run.result.steps;
+        Self {
+            run_id: run.run_id.clone(),
+            session_id: run.session_id.clone(),
+            task: run.task.clone(),
+            started_at: run.started_at,
+            finished_at: run.finished_at,
+            task_completed: run.result.task_completed,
+            error: run.result.error.clone(),
+            steps: steps
+                .iter()
+                .enumerate()
+                .map(|(index, step)| TraceStep {
+                    step_number: step.step_number,
+                    action: replayable_action(steps, index),
+                    result: step.action_result.clone(),
+                    url: step.context_after.url.clone(),
+                    title: step.context_after.title.clone(),
+                    transition: step.transition.clone(),
+                    trace: step.trace.clone().unwrap_or_default(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_trace_file_name() {
+        assert!(is_trace_file_name("step-3-after.jpg"));
+        assert!(is_trace_file_name("step-3.html"));
+        assert!(!is_trace_file_name("../secrets.txt"));
+        assert!(!is_trace_file_name("step/3.jpg"));
+        assert!(!is_trace_file_name(".."));
+        assert!(!is_trace_file_name(""));
+    }
+}
@@ -0,0 +1,102 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use std::fmt;
+
+use crate::models::ErrorCode;
+
+/// Error response of the API: `{"error": <ErrorCode>, "message": "..."}`
+///
+/// Clients should switch on `error`; `message` is for people and may change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+struct ApiErrorBody<'a> {
+    error: ErrorCode,
+    message: &'a str,
+}
+
+impl ApiError {
+    /// An error with the generic code for its status
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code: code_for(status),
+            message: message.into(),
+        }
+    }
+
+    /// Report a more specific code than the status implies, e.g. why an action failed
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = code;
+        self
+    }
+}
+
+fn code_for(status: StatusCode) -> ErrorCode {
+    match status {
+        StatusCode::UNAUTHORIZED => ErrorCode::Unauthorized,
+        StatusCode::FORBIDDEN => ErrorCode::Forbidden,
+        StatusCode::NOT_FOUND => ErrorCode::NotFound,
+        StatusCode::CONFLICT => ErrorCode::Conflict,
+        StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+        StatusCode::TOO_MANY_REQUESTS => ErrorCode::RateLimited,
+        StatusCode::SERVICE_UNAVAILABLE => ErrorCode::Unavailable,
+        status if status.is_client_error() => ErrorCode::InvalidRequest,
+        _ => ErrorCode::InternalError,
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.status)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ApiErrorBody {
+            error: self.code,
+            message: &self.message,
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+
+    #[tokio::test]
+    async fn test_renders_code_and_message() {
+        let response = ApiError::new(StatusCode::NOT_FOUND, "Session abc not found").into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"error": "not_found", "message": "Session abc not found"})
+        );
+    }
+
+    #[test]
+    fn test_generic_codes_follow_the_status() {
+        assert_eq!(ApiError::new(StatusCode::BAD_REQUEST, "").code, ErrorCode::InvalidRequest);
+        assert_eq!(ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "").code, ErrorCode::InvalidRequest);
+        assert_eq!(ApiError::new(StatusCode::BAD_GATEWAY, "").code, ErrorCode::InternalError);
+        assert_eq!(
+            ApiError::new(StatusCode::FORBIDDEN, "").with_code(ErrorCode::ReadOnly).code,
+            ErrorCode::ReadOnly
+        );
+    }
+}
@@ -1,23 +1,135 @@
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        OriginalUri, Path, Query, State,
+    },
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     Extension,
+    Form,
     Json,
 };
+use base64::Engine;
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use ui_core::transition::PageTransition;
 
-use crate::agent::{AgentExecutor, AgentExecutionResult, MultiStepExecutionResult};
-use crate::auth::{AuthUser, Claims};
-use crate::browser::ContextExtractor;
-use crate::models::{ActionRequest, ActionResponse, TriggerEvent, UIContext};
+use crate::agent::{
+    is_trace_file_name, link_trace, render_report, run_plan_from_env, run_summary_from_env, run_trace_dir, AgentExecutor, AgentExecutionResult, AgentRun, AgentTask, ConversationStep, LLMClient, LlmProvider, MultiStepExecutionResult,
+    ReportFormat, RunComparison, RunControl, RunOverview, RunState, SchemaExtraction, SchemaExtractor, SessionSchedule, TaskPriority, TaskStatus, TaskStore, TraceBundle, Tracer, DEFAULT_MAX_RETRIES_PER_STEP, DEFAULT_MAX_STEPS,
+};
+use crate::auth::{
+    generate_csrf_token, AuthUser, Capability, CapabilityClaims, Claims, CAPABILITY_QUERY,
+};
+use crate::browser::{
+    run_download_dir, url_pattern, BrowserAutomation, ConsentPolicy, ContextExtractor, EventLog, HygieneStats, PageHeatmap,
+    PageSummary, TableTarget,
+};
+use crate::features::{Feature, FeatureState};
+use crate::hooks::TestHook;
+use crate::notifications::{validate_webhook_url, WebhookTarget};
+use crate::recorder::{export_script, run_actions, ExportFormat, Macro, MacroOverview};
+use crate::otp::{verify_twilio_signature, SmsMessage};
+use crate::tunnel::{TunnelRegistry, TunnelResponse};
+use crate::session::{
+    interpolate, interpolate_action, is_valid_var_name, SessionManager, SessionOptions, SessionVars, WarmPoolStatus,
+};
+use crate::models::{
+    AXElement, ActionRequest, ActionResponse, Assertion, AssistiveCheck, AssistiveSetting, CompactContext, ConsoleEntry, DialogPolicy, DownloadArtifact,
+    PlaywrightStorageState, DownloadState, ElementNeighborhood, ElementSubscription,
+    ErrorCode, ErrorCodeInfo, NetworkRule, PageForm, ParsedDate, ScreenshotFormat, SemanticSelector, Session, SessionEvent, TabInfo, TriggerEvent, UIContext,
+    WebStorageArea, WebStorageContents,
+};
 
+use super::error::ApiError;
+use super::pagination::PageQuery;
+use super::routes::rate_limit_per_minute;
 use super::state::AppState;
+use super::version::ApiVersion;
 
 /// Health check endpoint
 pub async fn health_check() -> &'static str {
     "OK"
 }
 
+/// Environment emulation a session or action can request: viewport size and
+/// fingerprint (create_session), keyboard_only sessions, page zoom (set_zoom), the
+/// assistive check's high contrast and text zoom, and the automation banner
+const EMULATION_FEATURES: &[&str] = &[
+    "viewport",
+    "fingerprint",
+    "keyboard_only",
+    "zoom",
+    "high_contrast",
+    "text_zoom",
+    "automation_banner",
+];
+
+/// What this server supports, so clients can adapt instead of hard-coding it
+#[derive(Debug, Serialize)]
+pub struct CapabilitiesResponse {
+    /// Versions served under `/vN`; unprefixed paths use `default_api_version`
+    pub api_versions: Vec<&'static str>,
+    pub default_api_version: &'static str,
+
+    /// `tool` values accepted by `/:session_id/execute`
+    pub actions: Vec<&'static str>,
+
+    pub emulation: Vec<&'static str>,
+    /// Session presets `create_session` accepts
+    pub presets: Vec<String>,
+    /// Fingerprint presets `create_session` accepts
+    pub fingerprints: Vec<String>,
+    pub llm_providers: Vec<LlmProvider>,
+    pub limits: CapabilityLimits,
+
+    /// Feature flags that are currently on
+    pub features: Vec<Feature>,
+
+    /// Every `error` code an action or a turned-away request can report
+    pub error_codes: Vec<ErrorCodeInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CapabilityLimits {
+    /// Concurrent sessions; `null` when not limited
+    pub max_sessions: Option<usize>,
+    /// Used by multi-step runs that don't set `max_steps`
+    pub default_max_steps: usize,
+    pub default_max_retries_per_step: usize,
+    pub rate_limit_per_minute: u64,
+    pub session_lease_seconds: i64,
+    pub session_max_lease_seconds: i64,
+}
+
+/// Describe supported actions, emulation, LLM providers and limits
+pub async fn get_capabilities(State(state): State<AppState>) -> Json<CapabilitiesResponse> {
+    Json(CapabilitiesResponse {
+        api_versions: ApiVersion::ALL.iter().map(|version| version.number()).collect(),
+        default_api_version: ApiVersion::V1.number(),
+        actions: ActionRequest::TOOLS.to_vec(),
+        emulation: EMULATION_FEATURES.to_vec(),
+        presets: state.session_manager.preset_names(),
+        fingerprints: state.session_manager.fingerprint_names(),
+        llm_providers: vec![LLMClient::provider_from_env()],
+        limits: CapabilityLimits {
+            max_sessions: None,
+            default_max_steps: DEFAULT_MAX_STEPS,
+            default_max_retries_per_step: DEFAULT_MAX_RETRIES_PER_STEP,
+            rate_limit_per_minute: rate_limit_per_minute(),
+            session_lease_seconds: state.session_manager.lease_seconds(),
+            session_max_lease_seconds: state.session_manager.max_lease_seconds(),
+        },
+        features: state.features.enabled(),
+        error_codes: ErrorCodeInfo::all(),
+    })
+}
+
 /// Create new session
 #[derive(Debug, Deserialize)]
 pub struct CreateSessionRequest {
@@ -26,6 +138,63 @@ pub struct CreateSessionRequest {
     pub viewport_width: u32,
     #[serde(default = "default_viewport_height")]
     pub viewport_height: u32,
+    /// Show the "automated session" banner on its pages (default: AUTOMATION_BANNER env)
+    #[serde(default)]
+    pub automation_banner: Option<bool>,
+    /// Reject mouse actions so flows must be completed with the keyboard
+    #[serde(default)]
+    pub keyboard_only: bool,
+    /// Session preset (SESSION_PRESETS) whose rules the session follows
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Fingerprint preset (user agent, languages, platform, screen) the browser presents
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+    /// How to answer `alert`/`confirm`/`prompt` dialogs; overrides the preset's
+    #[serde(default)]
+    pub dialogs: Option<DialogPolicy>,
+    /// Cookies and localStorage to start with, in Playwright's format (as `GET /:session_id/state` returns)
+    #[serde(default)]
+    pub storage_state: Option<PlaywrightStorageState>,
+}
+
+/// Reject an unknown fingerprint preset
+fn check_fingerprint(state: &AppState, fingerprint: Option<&str>) -> Result<(), ApiError> {
+    match fingerprint {
+        Some(name) if state.session_manager.fingerprint(name).is_none() => Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown fingerprint '{}'; available: {}",
+                name,
+                state.session_manager.fingerprint_names().join(", ")
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Reject an unknown preset, or an initial URL the preset doesn't allow
+fn check_preset(state: &AppState, preset: Option<&str>, initial_url: &str) -> Result<(), ApiError> {
+    let Some(name) = preset else {
+        return Ok(());
+    };
+    let preset = state.session_manager.preset(name).ok_or_else(|| {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unknown session preset '{}'; available: {}",
+                name,
+                state.session_manager.preset_names().join(", ")
+            ),
+        )
+    })?;
+    if !preset.policy().allows_url(initial_url) {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            format!("Preset '{}' does not allow opening {}", name, initial_url),
+        ));
+    }
+    Ok(())
 }
 
 fn default_viewport_width() -> u32 {
@@ -43,15 +212,33 @@ pub struct CreateSessionResponse {
 
 pub async fn create_session(
     State(state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
     Json(req): Json<CreateSessionRequest>,
-) -> Result<Json<CreateSessionResponse>, (StatusCode, String)> {
+) -> Result<Json<CreateSessionResponse>, ApiError> {
+    check_preset(&state, req.preset.as_deref(), &req.initial_url)?;
+    check_fingerprint(&state, req.fingerprint.as_deref())?;
+
+    let owner = auth_user.map(|Extension(user)| user.user_id);
     let session_id = state
         .session_manager
-        .create_session(req.initial_url, req.viewport_width, req.viewport_height)
+        .create_session(
+            req.initial_url,
+            req.viewport_width,
+            req.viewport_height,
+            owner,
+            SessionOptions {
+                automation_banner: req.automation_banner,
+                keyboard_only: req.keyboard_only,
+                preset: req.preset,
+                fingerprint: req.fingerprint,
+                dialogs: req.dialogs,
+                storage_state: req.storage_state,
+            },
+        )
         .await
         .map_err(|e| {
             tracing::error!("Failed to create session: {}", e);
-            (
+            ApiError::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Failed to create session: {}", e),
             )
@@ -60,18 +247,62 @@ pub async fn create_session(
     Ok(Json(CreateSessionResponse { session_id }))
 }
 
+/// What `get_context` returns besides the element list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextMode {
+    #[default]
+    Elements,
+    /// Also a viewport screenshot with numbered boxes over the interactive elements
+    SetOfMark,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextFormat {
+    #[default]
+    Flat,
+    /// Also the elements nested under their forms, lists, menus and landmarks
+    Tree,
+}
+
+/// Context options (`?mode=set_of_mark`, `?format=tree`)
+#[derive(Debug, Default, Deserialize)]
+pub struct ContextQuery {
+    #[serde(default)]
+    pub mode: ContextMode,
+
+    #[serde(default)]
+    pub format: ContextFormat,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContextResponse {
+    #[serde(flatten)]
+    pub context: UIContext,
+
+    /// PNG data URL; box labels are the element IDs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<String>,
+
+    /// Top-level nodes of the element tree
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tree: Option<Vec<AXElement>>,
+}
+
 /// Get UI context (Step 1 API: get_context)
 /// This implements Solution A: AXTree extraction
 pub async fn get_context(
     State(state): State<AppState>,
     Path(session_id): Path<String>,
-) -> Result<Json<UIContext>, (StatusCode, String)> {
+    Query(query): Query<ContextQuery>,
+) -> Result<Json<ContextResponse>, ApiError> {
     // Update activity
     state
         .session_manager
         .update_activity(&session_id)
         .map_err(|e| {
-            (
+            ApiError::new(
                 StatusCode::NOT_FOUND,
                 format!("Session not found: {}", e),
             )
@@ -82,23 +313,58 @@ pub async fn get_context(
         .session_manager
         .get_browser(&session_id)
         .map_err(|e| {
-            (
+            ApiError::new(
                 StatusCode::NOT_FOUND,
                 format!("Session not found: {}", e),
             )
         })?;
 
     // Extract context
-    let page = browser.get_page().await;
-    let context = ContextExtractor::extract(page).await.map_err(|e| {
+    let context = browser.extract_context().await.map_err(|e| {
         tracing::error!("Failed to extract context: {}", e);
-        (
+        ApiError::new(
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to extract context: {}", e),
         )
     })?;
 
-    Ok(Json(context))
+    let screenshot = match query.mode {
+        ContextMode::Elements => None,
+        ContextMode::SetOfMark => {
+            let ids = ContextExtractor::set_of_mark_ids(&context);
+            let image = browser
+                .set_of_mark_screenshot(&ids, ScreenshotFormat::Png, None)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to capture Set-of-Mark screenshot: {}", e);
+                    ApiError::new(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to capture screenshot: {}", e),
+                    )
+                })?;
+            Some(format!(
+                "data:image/png;base64,{}",
+                base64::engine::general_purpose::STANDARD.encode(image)
+            ))
+        }
+    };
+
+    let tree = match query.format {
+        ContextFormat::Flat => None,
+        ContextFormat::Tree => Some(browser.extract_context_tree().await.map_err(|e| {
+            tracing::error!("Failed to extract context tree: {}", e);
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to extract context tree: {}", e),
+            )
+        })?),
+    };
+
+    Ok(Json(ContextResponse {
+        context,
+        screenshot,
+        tree,
+    }))
 }
 
 /// Execute action (Step 1 API: execute)
@@ -107,13 +373,13 @@ pub async fn execute_action(
     State(state): State<AppState>,
     Path(session_id): Path<String>,
     Json(action): Json<ActionRequest>,
-) -> Result<Json<ActionResponse>, (StatusCode, String)> {
+) -> Result<Json<ActionResponse>, ApiError> {
     // Update activity
     state
         .session_manager
         .update_activity(&session_id)
         .map_err(|e| {
-            (
+            ApiError::new(
                 StatusCode::NOT_FOUND,
                 format!("Session not found: {}", e),
             )
@@ -124,16 +390,21 @@ pub async fn execute_action(
         .session_manager
         .get_browser(&session_id)
         .map_err(|e| {
-            (
+            ApiError::new(
                 StatusCode::NOT_FOUND,
                 format!("Session not found: {}", e),
             )
         })?;
 
+    let action = action_with_session_vars(&state, &session_id, &action)?;
+    if let Some(feedback) = state.features.check_action(&action) {
+        return Ok(Json(feedback));
+    }
+
     // Execute action
     let response = browser.execute_action(&action).await.map_err(|e| {
         tracing::error!("Failed to execute action: {}", e);
-        (
+        ApiError::new(
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to execute action: {}", e),
         )
@@ -142,188 +413,352 @@ pub async fn execute_action(
     Ok(Json(response))
 }
 
-/// Handle trigger event (Step 1.5 API: trigger)
-/// This is called by the SolidJS client when page changes
-pub async fn handle_trigger(
+/// Replace the session's variables used for `{{name}}` placeholders
+pub async fn set_session_vars(
     State(state): State<AppState>,
     Path(session_id): Path<String>,
-    Json(trigger): Json<TriggerEvent>,
-) -> Result<Json<TriggerResponse>, (StatusCode, String)> {
-    tracing::info!(
-        "Received trigger event: {:?} for path: {}",
-        trigger.event,
-        trigger.path
-    );
+    Json(vars): Json<SessionVars>,
+) -> Result<Json<SessionVars>, ApiError> {
+    if let Some(name) = vars.keys().find(|name| !is_valid_var_name(name)) {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("Invalid variable name '{}' (use letters, digits, '_', '-' and '.')", name),
+        ));
+    }
 
-    // Update activity
     state
         .session_manager
-        .update_activity(&session_id)
+        .set_vars(&session_id, vars.clone())
         .map_err(|e| {
-            (
+            ApiError::new(
                 StatusCode::NOT_FOUND,
                 format!("Session not found: {}", e),
             )
         })?;
 
-    // Get browser to potentially refresh context
+    tracing::info!("Session {} has {} variables", session_id, vars.len());
+    Ok(Json(vars))
+}
+
+pub async fn get_session_vars(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<SessionVars>, ApiError> {
+    session_vars(&state, &session_id).map(Json)
+}
+
+/// Session variables of a session that exists
+fn session_vars(state: &AppState, session_id: &str) -> Result<SessionVars, ApiError> {
+    state.session_manager.vars(session_id).map_err(|e| {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Session not found: {}", e),
+        )
+    })
+}
+
+/// Fill `{{name}}` placeholders of a task from the session's variables
+fn task_with_session_vars(state: &AppState, session_id: &str, task: &str) -> Result<String, ApiError> {
+    interpolate(task, &session_vars(state, session_id)?)
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+/// Fill `{{name}}` placeholders of an action from the session's variables
+fn action_with_session_vars(
+    state: &AppState,
+    session_id: &str,
+    action: &ActionRequest,
+) -> Result<ActionRequest, ApiError> {
+    interpolate_action(action, &session_vars(state, session_id)?)
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+/// Name of the macro a recording is saved as
+#[derive(Debug, Deserialize)]
+pub struct StartRecordingRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecordingStarted {
+    pub name: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Start recording the actions the session executes, from /execute as well as agent runs
+pub async fn start_recording(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    auth_user: Option<Extension<AuthUser>>,
+    Json(req): Json<StartRecordingRequest>,
+) -> Result<Json<RecordingStarted>, ApiError> {
+    if !is_valid_var_name(&req.name) {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("Invalid macro name '{}' (use letters, digits, '_', '-' and '.')", req.name),
+        ));
+    }
+
+    let caller = auth_user.as_ref().map(|Extension(user)| user.user_id.as_str());
+    if state.macro_store.taken_by_other(&req.name, caller) {
+        return Err(macro_name_taken(&req.name));
+    }
+
     let browser = state
         .session_manager
         .get_browser(&session_id)
         .map_err(|e| {
-            (
+            ApiError::new(
                 StatusCode::NOT_FOUND,
                 format!("Session not found: {}", e),
             )
         })?;
 
-    // Optionally, auto-refresh context on page change
-    let context = match trigger.event {
-        crate::models::TriggerEventType::PageChanged => {
-            let page = browser.get_page().await;
-            Some(ContextExtractor::extract(page).await.map_err(|e| {
-                tracing::error!("Failed to extract context after trigger: {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to extract context: {}", e),
-                )
-            })?)
-        }
-        _ => None,
-    };
+    if !browser.start_recording(&req.name) {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            format!("Session {} is already recording", session_id),
+        ));
+    }
 
-    Ok(Json(TriggerResponse {
-        acknowledged: true,
-        context_refreshed: context.is_some(),
-        context,
+    tracing::info!("Session {} is recording macro '{}'", session_id, req.name);
+    Ok(Json(RecordingStarted {
+        name: req.name,
+        started_at: chrono::Utc::now(),
     }))
 }
 
-#[derive(Debug, Serialize)]
-pub struct TriggerResponse {
-    pub acknowledged: bool,
-    pub context_refreshed: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub context: Option<UIContext>,
+/// Values of the recording to turn into `{{name}}` parameters
+#[derive(Debug, Default, Deserialize)]
+pub struct StopRecordingRequest {
+    #[serde(default)]
+    pub parameters: BTreeMap<String, String>,
 }
 
-/// Delete session
-pub async fn delete_session(
+/// Stop the session's recording and store it as a macro
+pub async fn stop_recording(
     State(state): State<AppState>,
     Path(session_id): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    state
+    auth_user: Option<Extension<AuthUser>>,
+    body: Option<Json<StopRecordingRequest>>,
+) -> Result<Json<Macro>, ApiError> {
+    let Json(req) = body.unwrap_or_default();
+
+    let browser = state
         .session_manager
-        .remove_session(&session_id)
+        .get_browser(&session_id)
         .map_err(|e| {
-            (
+            ApiError::new(
                 StatusCode::NOT_FOUND,
                 format!("Session not found: {}", e),
             )
         })?;
 
-    Ok(StatusCode::NO_CONTENT)
+    let recording = browser.stop_recording().ok_or_else(|| {
+        ApiError::new(
+            StatusCode::CONFLICT,
+            format!("Session {} is not recording", session_id),
+        )
+    })?;
+
+    let mut recorded = recording.finish(&session_id, &req.parameters);
+    recorded.owner = auth_user.map(|Extension(user)| user.user_id);
+    // Someone else may have taken the name while the session was recording
+    if !state.macro_store.insert(recorded.clone()) {
+        return Err(macro_name_taken(&recorded.name));
+    }
+    tracing::info!(
+        "Session {} recorded macro '{}' ({} actions)",
+        session_id,
+        recorded.name,
+        recorded.actions.len()
+    );
+    Ok(Json(recorded))
 }
 
-/// List sessions
 #[derive(Debug, Serialize)]
-pub struct ListSessionsResponse {
-    pub sessions: Vec<String>,
+pub struct MacrosResponse {
+    pub macros: Vec<MacroOverview>,
     pub count: usize,
 }
 
-pub async fn list_sessions(
+/// Stored macros; callers see the macros they recorded, admins see all of them
+pub async fn list_macros(
     State(state): State<AppState>,
-) -> Json<ListSessionsResponse> {
-    let sessions = state.session_manager.list_sessions();
-    let count = sessions.len();
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Json<MacrosResponse> {
+    let admin = is_admin(&headers);
+    let caller = auth_user.as_ref().map(|Extension(user)| user.user_id.as_str());
+    let macros: Vec<MacroOverview> = state
+        .macro_store
+        .overviews()
+        .into_iter()
+        .filter(|recorded| admin || recorded.owner.as_deref() == caller)
+        .collect();
+    let count = macros.len();
+    Json(MacrosResponse { macros, count })
+}
 
-    Json(ListSessionsResponse { sessions, count })
+fn macro_not_found(name: &str) -> ApiError {
+    ApiError::new(StatusCode::NOT_FOUND, format!("Macro not found: {}", name))
 }
 
-/// Execute task with AI agent (Step 2: Agent Logic)
-#[derive(Debug, Deserialize)]
-pub struct AgentTaskRequest {
-    pub task: String,
+fn macro_name_taken(name: &str) -> ApiError {
+    ApiError::new(
+        StatusCode::CONFLICT,
+        format!("Macro name '{}' is taken by another user", name),
+    )
 }
 
-pub async fn agent_execute_task(
+/// The macro, unless it belongs to someone other than the caller (admins see every macro)
+fn visible_macro(
+    state: &AppState,
+    name: &str,
+    auth_user: Option<&Extension<AuthUser>>,
+    headers: &HeaderMap,
+) -> Result<Macro, ApiError> {
+    let caller = auth_user.map(|Extension(user)| user.user_id.as_str());
+    state
+        .macro_store
+        .get(name)
+        .filter(|recorded| is_admin(headers) || recorded.visible_to(caller))
+        .ok_or_else(|| macro_not_found(name))
+}
+
+pub async fn get_macro(
     State(state): State<AppState>,
-    Path(session_id): Path<String>,
-    Json(req): Json<AgentTaskRequest>,
-) -> Result<Json<AgentExecutionResult>, (StatusCode, String)> {
-    tracing::info!("Agent execution requested for session: {}", session_id);
-    tracing::info!("Task: {}", req.task);
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<Json<Macro>, ApiError> {
+    visible_macro(&state, &name, auth_user.as_ref(), &headers).map(Json)
+}
+
+pub async fn delete_macro(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<Json<Macro>, ApiError> {
+    visible_macro(&state, &name, auth_user.as_ref(), &headers)?;
+    let removed = state.macro_store.remove(&name).ok_or_else(|| macro_not_found(&name))?;
+    tracing::info!("Macro '{}' deleted", name);
+    Ok(Json(removed))
+}
+
+/// Values for a macro's parameters
+#[derive(Debug, Default, Deserialize)]
+pub struct ReplayMacroRequest {
+    /// Parameter values; the session's variables fill the ones left out
+    #[serde(default)]
+    pub params: SessionVars,
+    /// Keep going after a step fails instead of stopping there
+    #[serde(default)]
+    pub continue_on_failure: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayStep {
+    pub action: ActionRequest,
+    pub response: ActionResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayResponse {
+    pub name: String,
+    pub steps: Vec<ReplayStep>,
+    /// Whether every action of the macro ran and succeeded
+    pub completed: bool,
+}
+
+/// Replay a stored macro against the session, filling its parameters
+pub async fn replay_macro(
+    State(state): State<AppState>,
+    Path((session_id, name)): Path<(String, String)>,
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+    body: Option<Json<ReplayMacroRequest>>,
+) -> Result<Json<ReplayResponse>, ApiError> {
+    let Json(req) = body.unwrap_or_default();
+    let recorded = visible_macro(&state, &name, auth_user.as_ref(), &headers)?;
 
-    // Update activity
     state
         .session_manager
         .update_activity(&session_id)
         .map_err(|e| {
-            (
+            ApiError::new(
                 StatusCode::NOT_FOUND,
                 format!("Session not found: {}", e),
             )
         })?;
 
-    // Get browser
     let browser = state
         .session_manager
         .get_browser(&session_id)
         .map_err(|e| {
-            (
+            ApiError::new(
                 StatusCode::NOT_FOUND,
                 format!("Session not found: {}", e),
             )
         })?;
 
-    // Create agent executor
-    let agent = AgentExecutor::new();
-
-    // Execute task
-    let result = agent
-        .execute_single_step(&browser, &req.task)
-        .await
-        .map_err(|e| {
-            tracing::error!("Agent execution error: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Agent execution failed: {}", e),
-            )
-        })?;
-
-    tracing::info!("Agent execution completed: success={}", result.success);
+    // Every placeholder is filled before the first action runs
+    let mut vars = session_vars(&state, &session_id)?;
+    vars.extend(req.params);
+    let actions = recorded
+        .actions
+        .iter()
+        .map(|action| interpolate_action(action, &vars))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e.to_string()))?;
 
-    Ok(Json(result))
-}
+    let mut steps = Vec::with_capacity(actions.len());
+    let mut completed = true;
+    for action in actions {
+        let response = match state.features.check_action(&action) {
+            Some(feedback) => feedback,
+            None => browser.execute_action(&action).await.map_err(|e| {
+                tracing::error!("Failed to replay macro '{}': {}", name, e);
+                ApiError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to execute action: {}", e),
+                )
+            })?,
+        };
+        let success = response.success;
+        steps.push(ReplayStep { action, response });
+        if !success {
+            completed = false;
+            if !req.continue_on_failure {
+                break;
+            }
+        }
+    }
 
-/// Execute multi-step task with feedback loop (Step 3: Feedback Loop)
-#[derive(Debug, Deserialize)]
-pub struct MultiStepTaskRequest {
-    pub task: String,
-    /// Maximum number of steps (default: 20)
-    #[serde(default)]
-    pub max_steps: Option<usize>,
-    /// Maximum retries per step (default: 3)
-    #[serde(default)]
-    pub max_retries_per_step: Option<usize>,
+    tracing::info!(
+        "Replayed macro '{}' on session {}: {} of {} steps, completed: {}",
+        name,
+        session_id,
+        steps.len(),
+        recorded.actions.len(),
+        completed
+    );
+    Ok(Json(ReplayResponse { name, steps, completed }))
 }
 
-pub async fn agent_execute_multi_step(
+/// Dry-run an action: check it against the current page without executing it
+pub async fn validate_action(
     State(state): State<AppState>,
     Path(session_id): Path<String>,
-    Json(req): Json<MultiStepTaskRequest>,
-) -> Result<Json<MultiStepExecutionResult>, (StatusCode, String)> {
-    tracing::info!("Multi-step agent execution requested for session: {}", session_id);
-    tracing::info!("Task: {}", req.task);
-    tracing::info!("Max steps: {:?}, Max retries per step: {:?}", req.max_steps, req.max_retries_per_step);
-
+    Json(action): Json<ActionRequest>,
+) -> Result<Json<ActionResponse>, ApiError> {
     // Update activity
     state
         .session_manager
         .update_activity(&session_id)
         .map_err(|e| {
-            (
+            ApiError::new(
                 StatusCode::NOT_FOUND,
                 format!("Session not found: {}", e),
             )
@@ -334,37 +769,3004 @@ pub async fn agent_execute_multi_step(
         .session_manager
         .get_browser(&session_id)
         .map_err(|e| {
-            (
+            ApiError::new(
                 StatusCode::NOT_FOUND,
                 format!("Session not found: {}", e),
             )
         })?;
 
-    // Create agent executor
-    let agent = AgentExecutor::new();
+    let action = action_with_session_vars(&state, &session_id, &action)?;
+    if let Some(feedback) = state.features.check_action(&action) {
+        return Ok(Json(feedback));
+    }
 
-    // Execute multi-step task
-    let result = agent
-        .execute_multi_step(&browser, &req.task, req.max_steps, req.max_retries_per_step)
-        .await
-        .map_err(|e| {
-            tracing::error!("Multi-step agent execution error: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Multi-step agent execution failed: {}", e),
-            )
-        })?;
+    let response = browser.validate_action(&action).await.map_err(|e| {
+        tracing::error!("Failed to validate action: {}", e);
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to validate action: {}", e),
+        )
+    })?;
 
+    Ok(Json(response))
+}
+
+/// Handle trigger event (Step 1.5 API: trigger)
+/// This is called by the SolidJS client when page changes
+pub async fn handle_trigger(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Json(trigger): Json<TriggerEvent>,
+) -> Result<Json<TriggerResponse>, ApiError> {
     tracing::info!(
-        "Multi-step agent execution completed: task_completed={}, steps_taken={}, retries={}",
-        result.task_completed,
-        result.steps_taken,
-        result.retries_count
+        "Received trigger event: {:?} for path: {}",
+        trigger.event,
+        trigger.path
     );
 
+    // Update activity
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    // Get browser to potentially refresh context
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    // Optionally, auto-refresh context on page change
+    let context = match trigger.event {
+        crate::models::TriggerEventType::PageChanged => {
+            Some(browser.extract_context().await.map_err(|e| {
+                tracing::error!("Failed to extract context after trigger: {}", e);
+                ApiError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to extract context: {}", e),
+                )
+            })?)
+        }
+        _ => None,
+    };
+
+    Ok(Json(TriggerResponse {
+        acknowledged: true,
+        context_refreshed: context.is_some(),
+        context,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct TriggerResponse {
+    pub acknowledged: bool,
+    pub context_refreshed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<UIContext>,
+}
+
+/// Get the neighborhood of a context element (ancestors, headings, siblings, nearby text)
+pub async fn get_element_neighborhood(
+    State(state): State<AppState>,
+    Path((session_id, element_id)): Path<(String, usize)>,
+) -> Result<Json<ElementNeighborhood>, ApiError> {
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let neighborhood = browser
+        .element_neighborhood(element_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to extract element neighborhood: {}", e);
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to extract element neighborhood: {}", e),
+            )
+        })?
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Element {} not found in current context", element_id),
+            )
+        })?;
+
+    Ok(Json(neighborhood))
+}
+
+/// Session event log (navigations, dismissed banners, closed overlays, watched elements)
+#[derive(Debug, Serialize)]
+pub struct SessionEventsResponse {
+    pub events: Vec<SessionEvent>,
+    pub count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Filters of the session event log
+#[derive(Debug, Deserialize)]
+pub struct SessionEventsFilter {
+    /// Only events of this `type`, e.g. "navigated"
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+}
+
+pub async fn get_session_events(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(page): Query<PageQuery>,
+    Query(filter): Query<SessionEventsFilter>,
+) -> Result<Json<SessionEventsResponse>, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let events = browser.events().recent().await.into_iter().filter(|(_, event)| {
+        filter.kind.as_deref().is_none_or(|kind| {
+            serde_json::to_value(&event.kind).is_ok_and(|value| value["type"] == kind)
+        })
+    });
+    let page = page.page(events, |(seq, _)| *seq)?;
+    let events: Vec<SessionEvent> = page.items.into_iter().map(|(_, event)| event).collect();
+    let count = events.len();
+
+    Ok(Json(SessionEventsResponse {
+        events,
+        count,
+        next_cursor: page.next_cursor,
+    }))
+}
+
+/// Console messages and uncaught exceptions of a session's pages
+#[derive(Debug, Serialize)]
+pub struct ConsoleResponse {
+    pub entries: Vec<ConsoleEntry>,
+    pub count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Filters of the console log
+#[derive(Debug, Deserialize)]
+pub struct ConsoleFilter {
+    /// Only entries of this level, e.g. "warning" or "exception"
+    #[serde(default)]
+    pub level: Option<String>,
+    /// Only errors, failed assertions and uncaught exceptions
+    #[serde(default)]
+    pub errors: bool,
+}
+
+pub async fn get_console(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(page): Query<PageQuery>,
+    Query(filter): Query<ConsoleFilter>,
+) -> Result<Json<ConsoleResponse>, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let entries = browser.console().recent().await.into_iter().filter(|(_, entry)| {
+        filter.level.as_deref().is_none_or(|level| entry.level == level) && (!filter.errors || entry.is_error())
+    });
+    let page = page.page(entries, |(seq, _)| *seq)?;
+    let entries: Vec<ConsoleEntry> = page.items.into_iter().map(|(_, entry)| entry).collect();
+    let count = entries.len();
+
+    Ok(Json(ConsoleResponse {
+        entries,
+        count,
+        next_cursor: page.next_cursor,
+    }))
+}
+
+/// Live session events over WebSocket
+///
+/// Every event is sent as one JSON text message, including the live-only ones
+/// (context pushes, console messages, action results, agent steps). The socket
+/// is closed once the session is gone; watching does not renew the lease.
+pub async fn session_websocket(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let events = browser.events();
+    Ok(ws.on_upgrade(move |socket| forward_session_events(socket, state, session_id, events)))
+}
+
+async fn forward_session_events(mut socket: WebSocket, state: AppState, session_id: String, events: Arc<EventLog>) {
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut receiver = events.subscribe();
+    // Let the channel close along with the session's own handles
+    drop(events);
+
+    let mut ping = tokio::time::interval(std::time::Duration::from_secs(30));
+    loop {
+        tokio::select! {
+            event = receiver.recv() => match event {
+                Ok(event) => {
+                    let Ok(text) = serde_json::to_string(&event) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("WebSocket client of session {} fell behind, skipped {} events", session_id, skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Clients have nothing to say on this channel
+                Some(Ok(_)) => {}
+            },
+            _ = ping.tick() => {
+                if state.session_manager.get_browser(&session_id).is_err() {
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                }
+                if socket.send(Message::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Open a tunnel to an app on the caller's machine over a WebSocket
+///
+/// The first frame names the tunnel's URL. Browser requests to it arrive as
+/// `request` frames; the client sends them to its local app and answers each
+/// with a `TunnelResponse` frame carrying the same `id`. The tunnel closes
+/// with the socket.
+pub async fn tunnel_websocket(State(state): State<AppState>, ws: WebSocketUpgrade) -> Response {
+    let tunnels = state.session_manager.tunnels();
+    ws.on_upgrade(move |socket| serve_tunnel(socket, tunnels))
+}
+
+async fn serve_tunnel(mut socket: WebSocket, tunnels: Arc<TunnelRegistry>) {
+    let (tunnel, mut frames) = tunnels.open();
+    tracing::info!("Tunnel {} opened at {}", tunnel.id, tunnel.url());
+
+    let mut ping = tokio::time::interval(std::time::Duration::from_secs(30));
+    loop {
+        tokio::select! {
+            frame = frames.recv() => {
+                let Some(Ok(text)) = frame.map(|frame| serde_json::to_string(&frame)) else {
+                    break;
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str::<TunnelResponse>(&text) {
+                    Ok(response) => {
+                        if !tunnel.resolve(response) {
+                            tracing::debug!("Tunnel {} answered a request nobody waits for", tunnel.id);
+                        }
+                    }
+                    Err(e) => tracing::warn!("Invalid frame on tunnel {}: {}", tunnel.id, e),
+                },
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            _ = ping.tick() => {
+                if socket.send(Message::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    tunnels.close(&tunnel.id);
+    tracing::info!("Tunnel {} closed", tunnel.id);
+}
+
+/// Extract an HTML table as JSON records or CSV
+#[derive(Debug, Deserialize)]
+pub struct ExtractTableRequest {
+    #[serde(flatten)]
+    pub target: TableTarget,
+
+    #[serde(default)]
+    pub format: TableFormat,
+
+    /// Pages to read, following the "next" control (default: 1, capped at 50)
+    #[serde(default)]
+    pub max_pages: Option<u32>,
+
+    /// Next-page control; detected from common labels ("Next", "›", rel="next") when absent
+    #[serde(default)]
+    pub next: Option<SemanticSelector>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExtractTableResponse {
+    pub headers: Vec<String>,
+    pub rows: Vec<serde_json::Map<String, serde_json::Value>>,
+    /// Dates/times per row, keyed by header (omitted when the table has none)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parsed: Vec<BTreeMap<String, ParsedDate>>,
+    pub row_count: usize,
+    pub pages: u32,
+}
+
+pub async fn extract_table(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Json(req): Json<ExtractTableRequest>,
+) -> Result<axum::response::Response, ApiError> {
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let max_pages = req.max_pages.unwrap_or(1).clamp(1, 50);
+    let table = browser
+        .extract_table(&req.target, max_pages, req.next.as_ref())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to extract table: {}", e);
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to extract table: {}", e),
+            )
+        })?
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                "No matching table found on the page".to_string(),
+            )
+        })?;
+
+    Ok(match req.format {
+        TableFormat::Csv => (
+            [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+            table.to_csv(),
+        )
+            .into_response(),
+        TableFormat::Json => Json(ExtractTableResponse {
+            row_count: table.rows.len(),
+            rows: table.records(),
+            headers: table.headers,
+            parsed: table.parsed,
+            pages: table.pages,
+        })
+        .into_response(),
+    })
+}
+
+/// Forms on the current page
+#[derive(Debug, Serialize)]
+pub struct FormsResponse {
+    pub url: String,
+    pub forms: Vec<PageForm>,
+}
+
+/// Get the forms of the current page with their fields' values and validation state
+pub async fn get_forms(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<FormsResponse>, ApiError> {
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let forms = browser.forms().await.map_err(|e| {
+        tracing::error!("Failed to extract forms: {}", e);
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to extract forms: {}", e),
+        )
+    })?;
+
+    Ok(Json(FormsResponse {
+        url: browser.get_url().await.unwrap_or_default(),
+        forms,
+    }))
+}
+
+/// How to render the main content
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentFormat {
+    #[default]
+    Text,
+    Markdown,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ContentQuery {
+    #[serde(default)]
+    pub format: ContentFormat,
+}
+
+/// Main content of the current page
+#[derive(Debug, Serialize)]
+pub struct ContentResponse {
+    pub url: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byline: Option<String>,
+    pub content: String,
+    pub word_count: usize,
+}
+
+/// Get the readable main content of the page as text or Markdown
+pub async fn get_content(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(query): Query<ContentQuery>,
+) -> Result<Json<ContentResponse>, ApiError> {
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let page = browser.readable_content().await.map_err(|e| {
+        tracing::error!("Failed to extract content: {}", e);
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to extract content: {}", e),
+        )
+    })?;
+
+    let content = match query.format {
+        ContentFormat::Text => page.to_text(),
+        ContentFormat::Markdown => page.to_markdown(),
+    };
+    Ok(Json(ContentResponse {
+        word_count: page.word_count(),
+        url: page.url,
+        title: page.title,
+        byline: page.byline,
+        content,
+    }))
+}
+
+/// Extract data from the current page into a JSON Schema
+#[derive(Debug, Deserialize)]
+pub struct ExtractRequest {
+    /// JSON Schema of the data, e.g. an array of `{name, price}` objects
+    pub schema: serde_json::Value,
+
+    /// Extra guidance for the LLM, e.g. "only products on sale"
+    #[serde(default)]
+    pub instructions: Option<String>,
+}
+
+pub async fn extract_with_schema(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    auth_user: Option<Extension<AuthUser>>,
+    Json(req): Json<ExtractRequest>,
+) -> Result<Json<SchemaExtraction>, ApiError> {
+    if !req.schema.is_object() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "schema must be a JSON Schema object".to_string()));
+    }
+    if !LLMClient::provider_from_env().configured {
+        return Err(ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Schema extraction needs an LLM; set OPENAI_API_KEY".to_string(),
+        ));
+    }
+
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let tenant = auth_user.map(|Extension(user)| user.user_id);
+    let extraction = SchemaExtractor::new()
+        .with_pii_scrubber(state.pii_scrubbing.for_tenant(tenant.as_deref()))
+        .extract(&browser, &req.schema, req.instructions.as_deref())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to extract data: {}", e);
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to extract data: {}", e),
+            )
+        })?;
+
+    Ok(Json(extraction))
+}
+
+/// Files downloaded in a session
+#[derive(Debug, Serialize)]
+pub struct ArtifactsResponse {
+    pub artifacts: Vec<DownloadArtifact>,
+    pub count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Filters of the artifact list
+#[derive(Debug, Deserialize)]
+pub struct ArtifactsFilter {
+    #[serde(default)]
+    pub state: Option<DownloadState>,
+}
+
+pub async fn list_artifacts(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(page): Query<PageQuery>,
+    Query(filter): Query<ArtifactsFilter>,
+) -> Result<Json<ArtifactsResponse>, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let mut artifacts = browser.downloads().await;
+    artifacts.retain(|artifact| filter.state.is_none_or(|state| artifact.state == state));
+    artifacts.sort_by(|a, b| (a.started_at, &a.id).cmp(&(b.started_at, &b.id)));
+
+    let page = page.page(artifacts, |artifact| (artifact.started_at, artifact.id.clone()))?;
+    let count = page.items.len();
+
+    Ok(Json(ArtifactsResponse {
+        artifacts: page.items,
+        count,
+        next_cursor: page.next_cursor,
+    }))
+}
+
+/// Download a completed artifact (e.g. the `artifact_id` returned by `wait_for_download`)
+pub async fn get_artifact(
+    State(state): State<AppState>,
+    Path((session_id, artifact_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let (artifact, path) = browser.download_artifact(&artifact_id).await.ok_or_else(|| {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Artifact not found: {}", artifact_id),
+        )
+    })?;
+
+    download_file(&artifact, &path).await
+}
+
+/// Download a file a run saved, after its session is gone
+pub async fn get_run_download(
+    State(state): State<AppState>,
+    Path((run_id, download_id)): Path<(String, String)>,
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let run = visible_run(&state, &run_id, auth_user.as_ref(), &headers).await?;
+
+    let artifact = run
+        .result
+        .downloads
+        .iter()
+        .find(|artifact| artifact.id == download_id)
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Download not found: {}", download_id),
+            )
+        })?;
+
+    download_file(artifact, &run_download_dir(&run_id).join(&artifact.id)).await
+}
+
+/// A downloaded file as an attachment under its original name
+async fn download_file(
+    artifact: &DownloadArtifact,
+    path: &std::path::Path,
+) -> Result<impl IntoResponse, ApiError> {
+    let bytes = tokio::fs::read(path).await.map_err(|e| {
+        tracing::error!("Failed to read artifact {}: {}", artifact.id, e);
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read artifact: {}", e),
+        )
+    })?;
+
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        artifact.filename.replace(['"', '\\', '\r', '\n'], "_")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        bytes,
+    ))
+}
+
+/// Screenshot options (`?format=jpeg&full_page=true&quality=80`)
+#[derive(Debug, Deserialize)]
+pub struct ScreenshotQuery {
+    #[serde(default)]
+    pub format: ScreenshotFormat,
+    #[serde(default)]
+    pub full_page: bool,
+    #[serde(default)]
+    pub quality: Option<u8>,
+}
+
+/// Capture the page as an image
+pub async fn get_screenshot(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(query): Query<ScreenshotQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    if query.quality.is_some() && query.format != ScreenshotFormat::Jpeg {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "quality only applies to format=jpeg".to_string(),
+        ));
+    }
+
+    let image = browser
+        .screenshot(query.format, query.full_page, query.quality)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to capture screenshot: {}", e);
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to capture screenshot: {}", e),
+            )
+        })?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, query.format.content_type()),
+            (header::CACHE_CONTROL, "no-store"),
+        ],
+        image,
+    ))
+}
+
+/// Assistive settings to check the page under
+#[derive(Debug, Deserialize)]
+pub struct AssistiveCheckRequest {
+    #[serde(default = "all_assistive_settings")]
+    pub settings: Vec<AssistiveSetting>,
+}
+
+fn all_assistive_settings() -> Vec<AssistiveSetting> {
+    vec![AssistiveSetting::HighContrast, AssistiveSetting::TextZoom]
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssistiveCheckResponse {
+    pub url: String,
+    pub checks: Vec<AssistiveCheck>,
+}
+
+/// Report interactive elements that disappear or overlap under high contrast or text zoom
+pub async fn assistive_check(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Json(req): Json<AssistiveCheckRequest>,
+) -> Result<Json<AssistiveCheckResponse>, ApiError> {
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let failed = |e: anyhow::Error| {
+        tracing::error!("Assistive check failed: {}", e);
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Assistive check failed: {}", e),
+        )
+    };
+    let checks = browser.assistive_check(&req.settings).await.map_err(failed)?;
+    let url = browser.get_url().await.map_err(failed)?;
+
+    Ok(Json(AssistiveCheckResponse { url, checks }))
+}
+
+/// Register interest in an element appearing or disappearing
+#[derive(Debug, Deserialize)]
+pub struct SubscribeElementRequest {
+    pub selector: SemanticSelector,
+    /// Also POST each change here (events are always recorded in the session log)
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubscribeElementResponse {
+    pub subscription_id: String,
+    /// Whether a matching element is on the page right now
+    pub present: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ElementSubscriptionsResponse {
+    pub subscriptions: Vec<ElementSubscription>,
+    pub count: usize,
+}
+
+pub async fn subscribe_element(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Json(payload): Json<SubscribeElementRequest>,
+) -> Result<Json<SubscribeElementResponse>, ApiError> {
+    if let Some(url) = &payload.webhook_url {
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Webhook URL must use http or https: {}", url),
+            ));
+        }
+    }
+
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let subscription = browser
+        .subscribe_element(payload.selector, payload.webhook_url)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to subscribe to element: {}", e);
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to subscribe to element: {}", e),
+            )
+        })?;
+
+    Ok(Json(SubscribeElementResponse {
+        subscription_id: subscription.id,
+        present: subscription.present,
+    }))
+}
+
+pub async fn list_element_subscriptions(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<ElementSubscriptionsResponse>, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let subscriptions = browser.element_subscriptions().await;
+    let count = subscriptions.len();
+
+    Ok(Json(ElementSubscriptionsResponse { subscriptions, count }))
+}
+
+pub async fn unsubscribe_element(
+    State(state): State<AppState>,
+    Path((session_id, subscription_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let removed = browser
+        .unsubscribe_element(&subscription_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to remove element subscription: {}", e);
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to remove element subscription: {}", e),
+            )
+        })?;
+
+    if !removed {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Subscription not found: {}", subscription_id),
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Add request interception rules to a session
+#[derive(Debug, Deserialize)]
+pub struct AddNetworkRulesRequest {
+    pub rules: Vec<NetworkRule>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworkRulesResponse {
+    pub rules: Vec<NetworkRule>,
+    pub count: usize,
+}
+
+fn network_rules_unavailable(session_id: &str) -> ApiError {
+    ApiError::new(
+        StatusCode::SERVICE_UNAVAILABLE,
+        format!("Request interception is not running in session {}", session_id),
+    )
+}
+
+pub async fn add_network_rules(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Json(payload): Json<AddNetworkRulesRequest>,
+) -> Result<Json<NetworkRulesResponse>, ApiError> {
+    if payload.rules.is_empty() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "No rules given".to_string()));
+    }
+    for (index, rule) in payload.rules.iter().enumerate() {
+        rule.validate()
+            .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, format!("Rule {}: {}", index, e)))?;
+    }
+
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let network_rules = browser
+        .network_rules()
+        .ok_or_else(|| network_rules_unavailable(&session_id))?;
+    let rules = network_rules.add(payload.rules).await;
+    browser.intercept_requests().await.map_err(|e| {
+        tracing::error!("Failed to pause requests for network rules: {}", e);
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to pause requests for network rules: {}", e),
+        )
+    })?;
+    tracing::info!("Added {} network rule(s) to session {}", rules.len(), session_id);
+    let count = rules.len();
+
+    Ok(Json(NetworkRulesResponse { rules, count }))
+}
+
+pub async fn list_network_rules(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<NetworkRulesResponse>, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let rules = match browser.network_rules() {
+        Some(network_rules) => network_rules.list().await,
+        None => Vec::new(),
+    };
+    let count = rules.len();
+
+    Ok(Json(NetworkRulesResponse { rules, count }))
+}
+
+pub async fn clear_network_rules(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    if let Some(network_rules) = browser.network_rules() {
+        let removed = network_rules.clear().await;
+        tracing::info!("Cleared {} network rule(s) from session {}", removed, session_id);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn delete_network_rule(
+    State(state): State<AppState>,
+    Path((session_id, rule_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let removed = match browser.network_rules() {
+        Some(network_rules) => network_rules.remove(&rule_id).await,
+        None => false,
+    };
+
+    if !removed {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Network rule not found: {}", rule_id),
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Cookies and localStorage of a session in Playwright's `storageState` format
+///
+/// Passing the result as `storage_state` to `POST /sessions` starts another
+/// session logged in the same way.
+pub async fn get_storage_state(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<PlaywrightStorageState>, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let storage_state = browser.export_storage_state().await.map_err(|e| {
+        tracing::error!("Failed to export storage state: {}", e);
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to export storage state: {}", e),
+        )
+    })?;
+
+    Ok(Json(storage_state))
+}
+
+/// Keys to set in one storage area of the current page's origin
+#[derive(Debug, Deserialize)]
+pub struct WebStorageUpdate {
+    #[serde(default)]
+    pub area: WebStorageArea,
+    /// Values to set; `null` removes the key
+    #[serde(default)]
+    pub items: BTreeMap<String, Option<String>>,
+    /// Empty the area before setting `items`
+    #[serde(default)]
+    pub clear: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TabsResponse {
+    pub tabs: Vec<TabInfo>,
+    /// The tab actions and context reads go to
+    pub active_tab_id: Option<u32>,
+}
+
+/// The session's open tabs, and which one is active
+pub async fn list_tabs(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<TabsResponse>, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let tabs = browser.tabs().await;
+    let active_tab_id = tabs.iter().find(|tab| tab.active).map(|tab| tab.tab_id);
+    Ok(Json(TabsResponse { tabs, active_tab_id }))
+}
+
+/// localStorage and sessionStorage of the session's current origin
+pub async fn get_web_storage(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<WebStorageContents>, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let storage = browser.web_storage().await.map_err(|e| {
+        tracing::error!("Failed to read web storage: {}", e);
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read web storage: {}", e),
+        )
+    })?;
+
+    Ok(Json(storage))
+}
+
+/// Set or remove keys of the current origin's storage, e.g. to inject an auth token
+pub async fn set_web_storage(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Json(update): Json<WebStorageUpdate>,
+) -> Result<Json<WebStorageContents>, ApiError> {
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let internal_error = |e: anyhow::Error| {
+        tracing::error!("Failed to write web storage: {}", e);
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to write web storage: {}", e),
+        )
+    };
+
+    browser
+        .write_web_storage(update.area, &update.items, update.clear)
+        .await
+        .map_err(internal_error)?
+        .map_err(|reason| {
+            ApiError::new(
+                StatusCode::CONFLICT,
+                format!("Cannot write {}: {}", update.area.global(), reason),
+            )
+        })?;
+    tracing::info!(
+        "Session {} wrote {} key(s) to {}",
+        session_id,
+        update.items.len(),
+        update.area.global()
+    );
+
+    browser.web_storage().await.map(Json).map_err(internal_error)
+}
+
+/// Script for `POST /:session_id/evaluate`
+#[derive(Debug, Deserialize)]
+pub struct EvaluateRequest {
+    pub script: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EvaluateResponse {
+    /// The script's return value as JSON; null for `undefined`
+    pub result: serde_json::Value,
+}
+
+/// Run JavaScript in the session's page (needs the `evaluate` feature)
+///
+/// Runs as an `evaluate` action, so the session's policy applies and live
+/// event subscribers see it.
+pub async fn evaluate_script(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Json(req): Json<EvaluateRequest>,
+) -> Result<Json<EvaluateResponse>, ApiError> {
+    require_feature(&state, Feature::Evaluate)?;
+
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let action = ActionRequest::Evaluate { script: req.script };
+    let response = browser.execute_action(&action).await.map_err(|e| {
+        tracing::error!("Failed to evaluate script: {}", e);
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to evaluate script: {}", e),
+        )
+    })?;
+
+    if !response.success {
+        let status = match response.error {
+            Some(ErrorCode::ScriptFailed) => StatusCode::UNPROCESSABLE_ENTITY,
+            _ => StatusCode::FORBIDDEN,
+        };
+        let mut error = ApiError::new(status, response.reason.unwrap_or_default());
+        if let Some(code) = response.error {
+            error = error.with_code(code);
+        }
+        return Err(error);
+    }
+
+    let result = response
+        .details
+        .and_then(|mut details| details.get_mut("result").map(serde_json::Value::take))
+        .unwrap_or_default();
+    Ok(Json(EvaluateResponse { result }))
+}
+
+/// Get session details, including its lease
+pub async fn get_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<Session>, ApiError> {
+    let session = state
+        .session_manager
+        .get_session(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    Ok(Json(session))
+}
+
+/// Extend a session's lease
+#[derive(Debug, Default, Deserialize)]
+pub struct KeepaliveRequest {
+    /// Seconds from now to hold the session (default: SESSION_LEASE_SECONDS)
+    #[serde(default)]
+    pub extend_seconds: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KeepaliveResponse {
+    pub session_id: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub async fn keepalive_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    body: Option<Json<KeepaliveRequest>>,
+) -> Result<Json<KeepaliveResponse>, ApiError> {
+    let Json(req) = body.unwrap_or_default();
+
+    let session = state
+        .session_manager
+        .keepalive(&session_id, req.extend_seconds)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    Ok(Json(KeepaliveResponse {
+        session_id: session.id,
+        expires_at: session.expires_at,
+    }))
+}
+
+/// Mint a capability token for a session
+#[derive(Debug, Deserialize)]
+pub struct ShareSessionRequest {
+    pub rights: Vec<Capability>,
+    /// Action types the token may execute (default: all)
+    #[serde(default)]
+    pub actions: Option<Vec<String>>,
+    /// Token lifetime (default: SHARE_DEFAULT_SECONDS, capped at SHARE_MAX_SECONDS)
+    #[serde(default)]
+    pub expires_in_seconds: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareSessionResponse {
+    pub session_id: String,
+    pub token: String,
+    pub rights: Vec<Capability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actions: Option<Vec<String>>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    /// Link to the session, set when PUBLIC_BASE_URL is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share_url: Option<String>,
+}
+
+pub async fn share_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    auth_user: Option<Extension<AuthUser>>,
+    Json(req): Json<ShareSessionRequest>,
+) -> Result<Json<ShareSessionResponse>, ApiError> {
+    let Some(Extension(user)) = auth_user else {
+        return Err(ApiError::new(StatusCode::UNAUTHORIZED, "Authentication required".to_string()));
+    };
+
+    let session = state
+        .session_manager
+        .get_session(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    // Sessions created without auth have no owner and can be shared by any user,
+    // who becomes their owner
+    if session.user_id.as_deref().is_some_and(|owner| owner != user.user_id) {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "Only the session owner can share it".to_string(),
+        ));
+    }
+
+    if req.rights.is_empty() {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "At least one right is required".to_string(),
+        ));
+    }
+
+    let default_seconds: u64 = std::env::var("SHARE_DEFAULT_SECONDS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(1800);
+    let max_seconds: u64 = std::env::var("SHARE_MAX_SECONDS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(86400);
+    let ttl = req.expires_in_seconds.unwrap_or(default_seconds).min(max_seconds);
+
+    // A shared session needs an owner, or token holders could drop the token
+    // and use it without limits; the sharer claims a session that has none
+    state.session_manager.claim(&session_id, &user.user_id).map_err(|e| {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Session not found: {}", e),
+        )
+    })?;
+
+    let mut claims = CapabilityClaims::new(session_id.clone(), user.user_id, req.rights, ttl);
+    if let Some(actions) = req.actions {
+        claims = claims.with_actions(actions);
+    }
+
+    let token = state.jwt_handler.encode_capability(&claims).map_err(|e| {
+        tracing::error!("Failed to mint capability token: {}", e);
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to mint capability token: {}", e),
+        )
+    })?;
+
+    let share_url = std::env::var("PUBLIC_BASE_URL").ok().map(|base| {
+        format!(
+            "{}/sessions/{}?{}={}",
+            base.trim_end_matches('/'),
+            session_id,
+            CAPABILITY_QUERY,
+            token
+        )
+    });
+
+    tracing::info!(
+        "Session {} shared by {} with {:?} until {}",
+        session_id,
+        claims.granted_by,
+        claims.rights,
+        claims.exp
+    );
+
+    Ok(Json(ShareSessionResponse {
+        session_id,
+        token,
+        rights: claims.rights,
+        actions: claims.actions,
+        expires_at: chrono::DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_default(),
+        share_url,
+    }))
+}
+
+/// Delete session (soft by default, `?permanent=true` skips the trash)
+#[derive(Debug, Default, Deserialize)]
+pub struct DeleteSessionQuery {
+    #[serde(default)]
+    pub permanent: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteSessionResponse {
+    pub session_id: String,
+    pub deleted: bool,
+    /// Until when `POST /sessions/:id/restore` can bring the session back
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restorable_until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+pub async fn delete_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(query): Query<DeleteSessionQuery>,
+) -> Result<Json<DeleteSessionResponse>, ApiError> {
+    let restorable_until = if query.permanent {
+        state
+            .session_manager
+            .remove_session(&session_id)
+            .map(|_| None)
+    } else {
+        state
+            .session_manager
+            .soft_delete_session(&session_id)
+            .await
+            .map(Some)
+    }
+    .map_err(|e| {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Session not found: {}", e),
+        )
+    })?;
+    state.task_store.forget_session(&session_id);
+
+    Ok(Json(DeleteSessionResponse {
+        session_id,
+        deleted: true,
+        restorable_until,
+    }))
+}
+
+/// Restore a soft-deleted session from its storage state
+pub async fn restore_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<Json<Session>, ApiError> {
+    let caller = auth_user.as_ref().map(|Extension(user)| user.user_id.as_str());
+    let session = state
+        .session_manager
+        .restore_session(&session_id, caller)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Failed to restore session {}: {}", session_id, e);
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session cannot be restored: {}", e),
+            )
+        })?;
+
+    Ok(Json(session))
+}
+
+/// List sessions
+#[derive(Debug, Serialize)]
+pub struct ListSessionsResponse {
+    pub sessions: Vec<String>,
+    pub count: usize,
+}
+
+pub async fn list_sessions(
+    State(state): State<AppState>,
+) -> Json<ListSessionsResponse> {
+    let sessions = state.session_manager.list_sessions();
+    let count = sessions.len();
+
+    Json(ListSessionsResponse { sessions, count })
+}
+
+/// List sessions with their metadata (v2)
+#[derive(Debug, Serialize)]
+pub struct ListSessionsV2Response {
+    pub sessions: Vec<Session>,
+    pub count: usize,
+}
+
+pub async fn list_sessions_v2(
+    State(state): State<AppState>,
+) -> Json<ListSessionsV2Response> {
+    let sessions = state.session_manager.list_session_details();
+    let count = sessions.len();
+
+    Json(ListSessionsV2Response { sessions, count })
+}
+
+/// Execute task with AI agent (Step 2: Agent Logic)
+#[derive(Debug, Deserialize)]
+pub struct AgentTaskRequest {
+    pub task: String,
+    /// Consent banner policy before the step (default: CONSENT_POLICY env)
+    #[serde(default)]
+    pub consent_policy: Option<ConsentPolicy>,
+    /// Close newsletter/chat overlays automatically (default: OVERLAY_AUTO_CLOSE env)
+    #[serde(default)]
+    pub auto_close_overlays: Option<bool>,
+    /// Send a viewport screenshot with the element list (needs a multimodal OPENAI_MODEL such as gpt-4o)
+    #[serde(default)]
+    pub use_vision: bool,
+}
+
+pub async fn agent_execute_task(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    auth_user: Option<Extension<AuthUser>>,
+    Json(req): Json<AgentTaskRequest>,
+) -> Result<Json<AgentExecutionResult>, ApiError> {
+    tracing::info!("Agent execution requested for session: {}", session_id);
+    tracing::info!("Task: {}", req.task);
+
+    // Update activity
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    // Get browser
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    // Create agent executor
+    let mut agent = AgentExecutor::new();
+    if let Some(policy) = req.consent_policy {
+        agent = agent.with_consent_policy(policy);
+    }
+    if let Some(enabled) = req.auto_close_overlays {
+        agent = agent.with_overlay_auto_close(enabled);
+    }
+    let tenant = auth_user.map(|Extension(user)| user.user_id);
+    agent = agent
+        .with_vision(req.use_vision)
+        .with_features(state.features.enabled())
+        .with_pii_scrubber(state.pii_scrubbing.for_tenant(tenant.as_deref()));
+
+    let task = task_with_session_vars(&state, &session_id, &req.task)?;
+
+    // Execute task
+    let result = agent
+        .execute_single_step(&browser, &task)
+        .await
+        .map_err(|e| {
+            tracing::error!("Agent execution error: {}", e);
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Agent execution failed: {}", e),
+            )
+        })?;
+
+    tracing::info!("Agent execution completed: success={}", result.success);
+
+    Ok(Json(result))
+}
+
+/// Execute multi-step task with feedback loop (Step 3: Feedback Loop)
+#[derive(Debug, Deserialize)]
+pub struct MultiStepTaskRequest {
+    pub task: String,
+    /// Maximum number of steps (default: 20)
+    #[serde(default)]
+    pub max_steps: Option<usize>,
+    /// Maximum retries per step (default: 3)
+    #[serde(default)]
+    pub max_retries_per_step: Option<usize>,
+    /// Consent banner policy before the first step (default: CONSENT_POLICY env)
+    #[serde(default)]
+    pub consent_policy: Option<ConsentPolicy>,
+    /// Close newsletter/chat overlays automatically (default: OVERLAY_AUTO_CLOSE env)
+    #[serde(default)]
+    pub auto_close_overlays: Option<bool>,
+    /// Send only changed elements after the first step (default: PROMPT_DIFF env)
+    #[serde(default)]
+    pub prompt_diff: Option<bool>,
+    /// Have the LLM reflect on a step that failed twice before retrying it (default: AGENT_REFLECTION env)
+    #[serde(default)]
+    pub reflect: Option<bool>,
+    /// Send a viewport screenshot with each page state (needs a multimodal OPENAI_MODEL such as gpt-4o)
+    #[serde(default)]
+    pub use_vision: bool,
+    /// Have the LLM summarize the finished run into `summary` (default: RUN_SUMMARY env)
+    #[serde(default)]
+    pub summarize: Option<bool>,
+    /// Have the LLM write a `plan` for the task before the first step (default: RUN_PLAN env)
+    #[serde(default)]
+    pub plan: Option<bool>,
+    /// The run is done once all of these hold; without them the LLM judges when the task is done
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    /// Keep before/after screenshots of each step, served with the run's trace
+    #[serde(default)]
+    pub trace: bool,
+    /// With `trace`, also keep the page's HTML after each step
+    #[serde(default)]
+    pub trace_dom: bool,
+}
+
+pub async fn agent_execute_multi_step(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    auth_user: Option<Extension<AuthUser>>,
+    Json(mut req): Json<MultiStepTaskRequest>,
+) -> Result<Json<MultiStepExecutionResult>, ApiError> {
+    tracing::info!("Multi-step agent execution requested for session: {}", session_id);
+    tracing::info!("Task: {}", req.task);
+    tracing::info!("Max steps: {:?}, Max retries per step: {:?}", req.max_steps, req.max_retries_per_step);
+
+    // Update activity
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    // Get browser
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    req.task = task_with_session_vars(&state, &session_id, &req.task)?;
+
+    let tenant = auth_user.map(|Extension(user)| user.user_id);
+    let result = run_multi_step_task(&state, &session_id, &browser, &req, tenant, RunControl::new(), None).await?;
+
     Ok(Json(result))
 }
 
+/// Run the multi-step agent on a session, store the run and notify webhooks
+async fn run_multi_step_task(
+    state: &AppState,
+    session_id: &str,
+    browser: &Arc<BrowserAutomation>,
+    req: &MultiStepTaskRequest,
+    tenant: Option<String>,
+    control: RunControl,
+    step_listener: Option<tokio::sync::mpsc::UnboundedSender<ConversationStep>>,
+) -> Result<MultiStepExecutionResult, ApiError> {
+    // Operators can pause or cancel the run while it is registered
+    let active_run = state.run_controls.register(session_id, control);
+
+    // Create agent executor
+    let mut agent = AgentExecutor::new().with_run_control(active_run.control());
+    if let Some(listener) = step_listener {
+        agent = agent.with_step_listener(listener);
+    }
+    if let Some(policy) = req.consent_policy {
+        agent = agent.with_consent_policy(policy);
+    }
+    if let Some(enabled) = req.auto_close_overlays {
+        agent = agent.with_overlay_auto_close(enabled);
+    }
+    if let Some(enabled) = req.prompt_diff {
+        agent = agent.with_prompt_diff(enabled);
+    }
+    if let Some(enabled) = req.reflect {
+        agent = agent.with_reflection(enabled);
+    }
+    agent = agent
+        .with_vision(req.use_vision)
+        .with_features(state.features.enabled())
+        .with_assertions(req.assertions.clone())
+        .with_pii_scrubber(state.pii_scrubbing.for_tenant(tenant.as_deref()));
+    let tracer = req.trace.then(|| Tracer::new(req.trace_dom));
+    agent = agent.with_tracer(tracer.clone());
+
+    let started_at = chrono::Utc::now();
+
+    let plan = if req.plan.unwrap_or_else(run_plan_from_env) {
+        agent.plan(browser, &req.task).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to plan the run: {}", e);
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
+
+    // Execute multi-step task
+    let mut result = agent
+        .execute_multi_step(browser, &req.task, req.max_steps, req.max_retries_per_step)
+        .await
+        .map_err(|e| {
+            tracing::error!("Multi-step agent execution error: {}", e);
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Multi-step agent execution failed: {}", e),
+            )
+        })?;
+    drop(active_run);
+
+    if req.summarize.unwrap_or_else(run_summary_from_env) {
+        match agent.summarize(&req.task, &result).await {
+            Ok(summary) => result.summary = Some(summary),
+            Err(e) => tracing::warn!("Failed to summarize the run: {}", e),
+        }
+    }
+    result.plan = plan;
+    result.usage = agent.usage();
+
+    tracing::info!(
+        "Multi-step agent execution completed: task_completed={}, steps_taken={}, retries={}",
+        result.task_completed,
+        result.steps_taken,
+        result.retries_count
+    );
+
+    // Keep the run for reports, with copies of its downloads that outlive the session
+    let mut run = AgentRun::new(session_id, &req.task, started_at, result);
+    run.owner = tenant.clone();
+    run.result.downloads = browser.keep_downloads(started_at, &run_download_dir(&run.run_id)).await;
+    if let Some(tracer) = &tracer {
+        if tracer.dir().exists() {
+            if let Err(e) = tokio::fs::rename(tracer.dir(), run_trace_dir(&run.run_id)).await {
+                tracing::warn!("Failed to keep the trace of run {}: {}", run.run_id, e);
+            }
+        }
+        link_trace(&mut run.result.steps, &format!("/{}/agent/trace/{}/files", session_id, run.run_id));
+    }
+    let mut result = run.result.clone();
+    result.run_id = Some(run.run_id.clone());
+    state.run_store.insert(run.clone()).await;
+
+    // Announce the outcome without delaying the response
+    let notifier = state.notifier.clone();
+    tokio::spawn(async move {
+        notifier.notify_run(tenant.as_deref(), &run).await;
+    });
+
+    Ok(result)
+}
+
+/// Progress of a streamed multi-step run, sent as each step completes
+#[derive(Debug, Serialize)]
+pub struct StepProgress {
+    pub step_number: usize,
+    pub action: ActionRequest,
+    pub result: ActionResponse,
+    /// Page after the step
+    pub context: CompactContext,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transition: Option<PageTransition>,
+}
+
+impl From<ConversationStep> for StepProgress {
+    fn from(step: ConversationStep) -> Self {
+        Self {
+            step_number: step.step_number,
+            context: step.context_after.compact(),
+            action: step.action_decided,
+            result: step.action_result,
+            transition: step.transition,
+        }
+    }
+}
+
+/// Multi-step agent execution streamed as Server-Sent Events
+///
+/// Sends a `step` event per completed step, then `done` with the same result
+/// `execute_multi_step` returns, or `error` with the failure message. The run
+/// continues (and is stored) if the client disconnects.
+pub async fn agent_execute_multi_step_stream(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    auth_user: Option<Extension<AuthUser>>,
+    Json(mut req): Json<MultiStepTaskRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, ApiError> {
+    tracing::info!("Streamed multi-step agent execution requested for session: {}", session_id);
+    tracing::info!("Task: {}", req.task);
+
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    req.task = task_with_session_vars(&state, &session_id, &req.task)?;
+
+    let tenant = auth_user.map(|Extension(user)| user.user_id);
+    let (step_tx, step_rx) = tokio::sync::mpsc::unbounded_channel();
+    let run = tokio::spawn(async move {
+        run_multi_step_task(&state, &session_id, &browser, &req, tenant, RunControl::new(), Some(step_tx)).await
+    });
+
+    // The step channel closes when the run's executor is dropped, before the run task ends
+    let steps = futures::stream::unfold(step_rx, |mut step_rx| async move {
+        let step = step_rx.recv().await?;
+        let event = Event::default().event("step").json_data(StepProgress::from(step));
+        Some((event, step_rx))
+    });
+    let outcome = futures::stream::once(async move {
+        match run.await {
+            Ok(Ok(result)) => Event::default().event("done").json_data(result),
+            Ok(Err(error)) => Ok(Event::default().event("error").data(error.message)),
+            Err(e) => Ok(Event::default().event("error").data(format!("Multi-step agent execution aborted: {}", e))),
+        }
+    });
+
+    Ok(Sse::new(steps.chain(outcome)).keep_alive(KeepAlive::default()))
+}
+
+/// A multi-step run to queue, with its scheduling class
+#[derive(Debug, Deserialize)]
+pub struct QueuedTaskRequest {
+    #[serde(flatten)]
+    pub run: MultiStepTaskRequest,
+    /// `interactive` (default) or `batch`
+    #[serde(default)]
+    pub priority: TaskPriority,
+}
+
+/// Queue a multi-step agent run on the session and return at once
+///
+/// Interactive tasks on one session run before batch tasks, each class in
+/// submission order; poll `GET /tasks/:task_id` for progress and the result.
+pub async fn enqueue_agent_task(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    auth_user: Option<Extension<AuthUser>>,
+    Json(QueuedTaskRequest { run: mut req, priority }): Json<QueuedTaskRequest>,
+) -> Result<(StatusCode, Json<AgentTask>), ApiError> {
+    state
+        .session_manager
+        .update_activity(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    // Filled in now, so a missing variable is reported before the task is queued
+    req.task = task_with_session_vars(&state, &session_id, &req.task)?;
+
+    let tenant = auth_user.map(|Extension(user)| user.user_id);
+    let mut task = AgentTask::new(&session_id, &req.task);
+    task.owner = tenant.clone();
+    task.priority = priority;
+    let task_id = task.task_id.clone();
+    state.task_store.insert(task.clone());
+    tracing::info!("Agent task {} ({:?}) queued on session {}: {}", task_id, priority, session_id, req.task);
+
+    {
+        let state = state.clone();
+        let task_id = task_id.clone();
+        tokio::spawn(async move {
+            let tasks = Arc::clone(&state.task_store);
+            let Some(_turn) = tasks.turn(&task_id).await else {
+                return;
+            };
+            if !tasks.start(&task_id) {
+                return;
+            }
+
+            let browser = match state.session_manager.get_browser(&session_id) {
+                Ok(browser) => browser,
+                Err(e) => {
+                    tasks.finish(&task_id, Err(format!("Session not found: {}", e)));
+                    return;
+                }
+            };
+
+            let (step_tx, mut step_rx) = tokio::sync::mpsc::unbounded_channel::<ConversationStep>();
+            let progress = {
+                let tasks = Arc::clone(&tasks);
+                let task_id = task_id.clone();
+                tokio::spawn(async move {
+                    while let Some(step) = step_rx.recv().await {
+                        tasks.record_step(&task_id, step.step_number);
+                    }
+                })
+            };
+
+            let control = tasks.control(&task_id).unwrap_or_default();
+            let result =
+                run_multi_step_task(&state, &session_id, &browser, &req, tenant, control, Some(step_tx)).await;
+            let _ = progress.await;
+            tasks.finish(&task_id, result.map_err(|error| error.message));
+        });
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(task)))
+}
+
+/// Status, progress and (once finished) result of a queued agent task
+pub async fn get_agent_task(
+    State(state): State<AppState>,
+    Path(task_id): Path<String>,
+    auth_user: Option<Extension<AuthUser>>,
+    headers: HeaderMap,
+) -> Result<Json<AgentTask>, ApiError> {
+    visible_task(&state, &task_id, auth_user.as_ref(), &headers).map(Json)
+}
+
+/// Cancel a queued or running agent task
+///
+/// A running task stops before its next step.
+pub async fn cancel_agent_task(
+    State(state): State<AppState>,
+    Path(task_id): Path<String>,
+    auth_user: Option<Extension<AuthUser>>,
+    headers: HeaderMap,
+) -> Result<Json<AgentTask>, ApiError> {
+    visible_task(&state, &task_id, auth_user.as_ref(), &headers)?;
+    let task = state
+        .task_store
+        .cancel(&task_id)
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("Task not found: {}", task_id)))?;
+
+    if task.status != TaskStatus::Cancelled {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            format!("Task {} already finished", task_id),
+        ));
+    }
+
+    tracing::info!("Agent task {} cancelled", task_id);
+    Ok(Json(task))
+}
+
+/// The task, unless it belongs to someone other than the caller (admins see every task)
+///
+/// Someone else's task is reported as missing, so task IDs can't be probed.
+fn visible_task(
+    state: &AppState,
+    task_id: &str,
+    auth_user: Option<&Extension<AuthUser>>,
+    headers: &HeaderMap,
+) -> Result<AgentTask, ApiError> {
+    let caller = auth_user.map(|Extension(user)| user.user_id.as_str());
+    state
+        .task_store
+        .get(task_id)
+        .filter(|task| is_admin(headers) || task.visible_to(caller))
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, format!("Task not found: {}", task_id)))
+}
+
+/// Hold a queued or running agent task before its next step
+pub async fn pause_agent_task(
+    State(state): State<AppState>,
+    Path((session_id, task_id)): Path<(String, String)>,
+) -> Result<Json<AgentTask>, ApiError> {
+    control_agent_task(&state, &session_id, &task_id, TaskStore::pause)
+}
+
+/// Let a paused agent task continue
+pub async fn resume_agent_task(
+    State(state): State<AppState>,
+    Path((session_id, task_id)): Path<(String, String)>,
+) -> Result<Json<AgentTask>, ApiError> {
+    control_agent_task(&state, &session_id, &task_id, TaskStore::resume)
+}
+
+/// Apply an operator request to an agent task of the session
+fn control_agent_task(
+    state: &AppState,
+    session_id: &str,
+    task_id: &str,
+    apply: impl FnOnce(&TaskStore, &str) -> Option<AgentTask>,
+) -> Result<Json<AgentTask>, ApiError> {
+    state
+        .session_manager
+        .update_activity(session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let not_found = || ApiError::new(StatusCode::NOT_FOUND, format!("Task not found: {}", task_id));
+    let task = state
+        .task_store
+        .get(task_id)
+        .filter(|task| task.session_id == session_id)
+        .ok_or_else(not_found)?;
+    if !task.status.is_active() {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            format!("Task {} already finished", task_id),
+        ));
+    }
+
+    let task = apply(&state.task_store, task_id).ok_or_else(not_found)?;
+    tracing::info!("Agent task {} is now {:?}", task_id, task.status);
+    Ok(Json(task))
+}
+
+/// State of a session's multi-step run after a pause, resume or cancel request
+#[derive(Debug, Serialize)]
+pub struct RunControlResponse {
+    pub session_id: String,
+    pub state: RunState,
+}
+
+/// Apply an operator request to the multi-step run in progress on the session
+fn control_agent_run(
+    state: &AppState,
+    session_id: &str,
+    apply: impl FnOnce(&RunControl) -> RunState,
+) -> Result<Json<RunControlResponse>, ApiError> {
+    state
+        .session_manager
+        .update_activity(session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let control = state.run_controls.get(session_id).ok_or_else(|| {
+        ApiError::new(
+            StatusCode::CONFLICT,
+            format!("No agent run in progress on session {}", session_id),
+        )
+    })?;
+
+    let run_state = apply(&control);
+    tracing::info!("Agent run on session {} is now {:?}", session_id, run_state);
+
+    Ok(Json(RunControlResponse {
+        session_id: session_id.to_string(),
+        state: run_state,
+    }))
+}
+
+/// Pause the session's multi-step run before its next step
+pub async fn pause_agent_run(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<RunControlResponse>, ApiError> {
+    control_agent_run(&state, &session_id, RunControl::pause)
+}
+
+/// Resume a paused multi-step run
+pub async fn resume_agent_run(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<RunControlResponse>, ApiError> {
+    control_agent_run(&state, &session_id, RunControl::resume)
+}
+
+/// Stop the session's multi-step run before its next step, also while paused
+pub async fn cancel_agent_run(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<RunControlResponse>, ApiError> {
+    control_agent_run(&state, &session_id, RunControl::cancel)
+}
+
+/// One-shot run: open a session, execute the task, tear the session down
+#[derive(Debug, Deserialize)]
+pub struct OneShotRunRequest {
+    pub url: String,
+    pub task: String,
+    #[serde(default)]
+    pub options: OneShotRunOptions,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct OneShotRunOptions {
+    #[serde(default)]
+    pub viewport_width: Option<u32>,
+    #[serde(default)]
+    pub viewport_height: Option<u32>,
+    #[serde(default)]
+    pub automation_banner: Option<bool>,
+    #[serde(default)]
+    pub keyboard_only: bool,
+    #[serde(default)]
+    pub preset: Option<String>,
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+    #[serde(default)]
+    pub dialogs: Option<DialogPolicy>,
+    #[serde(default)]
+    pub storage_state: Option<PlaywrightStorageState>,
+    #[serde(default)]
+    pub max_steps: Option<usize>,
+    #[serde(default)]
+    pub max_retries_per_step: Option<usize>,
+    #[serde(default)]
+    pub consent_policy: Option<ConsentPolicy>,
+    #[serde(default)]
+    pub auto_close_overlays: Option<bool>,
+    #[serde(default)]
+    pub prompt_diff: Option<bool>,
+    #[serde(default)]
+    pub reflect: Option<bool>,
+    #[serde(default)]
+    pub use_vision: bool,
+    #[serde(default)]
+    pub summarize: Option<bool>,
+    #[serde(default)]
+    pub plan: Option<bool>,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    #[serde(default)]
+    pub trace: bool,
+    #[serde(default)]
+    pub trace_dom: bool,
+}
+
+/// Links to artifacts produced by a run
+#[derive(Debug, Serialize)]
+pub struct RunArtifacts {
+    pub report_markdown: String,
+    pub report_html: String,
+    /// The files the run downloaded, by download ID
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub downloads: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OneShotRunResponse {
+    #[serde(flatten)]
+    pub result: MultiStepExecutionResult,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifacts: Option<RunArtifacts>,
+}
+
+/// The session of a one-shot run; dropping it (also when the client disconnects
+/// and the request is aborted) closes the session and its browser
+struct OneShotSession {
+    sessions: Arc<SessionManager>,
+    session_id: String,
+}
+
+impl Drop for OneShotSession {
+    fn drop(&mut self) {
+        if let Err(e) = self.sessions.remove_session(&self.session_id) {
+            tracing::warn!("Failed to remove one-shot session {}: {}", self.session_id, e);
+        }
+    }
+}
+
+pub async fn run_one_shot(
+    State(state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
+    Json(req): Json<OneShotRunRequest>,
+) -> Result<Json<OneShotRunResponse>, ApiError> {
+    tracing::info!("One-shot run requested for {}: {}", req.url, req.task);
+
+    let options = req.options;
+    check_preset(&state, options.preset.as_deref(), &req.url)?;
+    check_fingerprint(&state, options.fingerprint.as_deref())?;
+
+    let session_id = state
+        .session_manager
+        .create_session(
+            req.url.clone(),
+            options.viewport_width.unwrap_or_else(default_viewport_width),
+            options.viewport_height.unwrap_or_else(default_viewport_height),
+            auth_user.as_ref().map(|Extension(user)| user.user_id.clone()),
+            SessionOptions {
+                automation_banner: options.automation_banner,
+                keyboard_only: options.keyboard_only,
+                preset: options.preset.clone(),
+                fingerprint: options.fingerprint.clone(),
+                dialogs: options.dialogs.clone(),
+                storage_state: options.storage_state.clone(),
+            },
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create session for one-shot run: {}", e);
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to create session: {}", e),
+            )
+        })?;
+    let session = OneShotSession {
+        sessions: Arc::clone(&state.session_manager),
+        session_id: session_id.clone(),
+    };
+
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Session disappeared: {}", e),
+            )
+        })?;
+
+    let task_req = MultiStepTaskRequest {
+        task: req.task,
+        max_steps: options.max_steps,
+        max_retries_per_step: options.max_retries_per_step,
+        consent_policy: options.consent_policy,
+        auto_close_overlays: options.auto_close_overlays,
+        prompt_diff: options.prompt_diff,
+        reflect: options.reflect,
+        use_vision: options.use_vision,
+        summarize: options.summarize,
+        plan: options.plan,
+        assertions: options.assertions,
+        trace: options.trace,
+        trace_dom: options.trace_dom,
+    };
+    let tenant = auth_user.map(|Extension(user)| user.user_id);
+    let result = run_multi_step_task(&state, &session_id, &browser, &task_req, tenant, RunControl::new(), None).await;
+
+    // Tear down whether or not the run succeeded
+    drop(browser);
+    drop(session);
+
+    let result = result?;
+    let artifacts = result.run_id.as_ref().map(|run_id| RunArtifacts {
+        report_markdown: format!("/runs/{}/report?format=markdown", run_id),
+        report_html: format!("/runs/{}/report?format=html", run_id),
+        downloads: result
+            .downloads
+            .iter()
+            .map(|download| format!("/runs/{}/downloads/{}", run_id, download.id))
+            .collect(),
+    });
+
+    Ok(Json(OneShotRunResponse { result, artifacts }))
+}
+
+/// Report format query for run reports
+#[derive(Debug, Deserialize)]
+pub struct ReportQuery {
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+/// Render a completed run as Markdown (default) or HTML
+pub async fn get_run_report(
+    State(state): State<AppState>,
+    Path(run_id): Path<String>,
+    Query(query): Query<ReportQuery>,
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let run = visible_run(&state, &run_id, auth_user.as_ref(), &headers).await?;
+
+    let content_type = match query.format {
+        ReportFormat::Markdown => "text/markdown; charset=utf-8",
+        ReportFormat::Html => "text/html; charset=utf-8",
+    };
+
+    Ok((
+        [(header::CONTENT_TYPE, content_type)],
+        render_report(&run, query.format),
+    ))
+}
+
+/// A stored run of the session with its steps' screenshots and DOM snapshots, as linked in `trace`
+pub async fn get_agent_trace(
+    State(state): State<AppState>,
+    Path((session_id, run_id)): Path<(String, String)>,
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<Json<TraceBundle>, ApiError> {
+    let run = visible_run(&state, &run_id, auth_user.as_ref(), &headers).await?;
+    if run.session_id != session_id {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Run not found: {}", run_id),
+        ));
+    }
+
+    Ok(Json(TraceBundle::new(&run)))
+}
+
+/// A screenshot or DOM snapshot of a traced run
+pub async fn get_agent_trace_file(
+    State(state): State<AppState>,
+    Path((session_id, run_id, file)): Path<(String, String, String)>,
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let not_found = || {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Trace file not found: {}", file),
+        )
+    };
+    if !is_trace_file_name(&file) {
+        return Err(not_found());
+    }
+    let run = visible_run(&state, &run_id, auth_user.as_ref(), &headers).await?;
+    if run.session_id != session_id {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Run not found: {}", run_id),
+        ));
+    }
+
+    let bytes = tokio::fs::read(run_trace_dir(&run_id).join(&file))
+        .await
+        .map_err(|_| not_found())?;
+
+    // Captured pages are shown, never run: their scripts would run on this origin
+    let headers = if file.ends_with(".html") {
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+            (header::CONTENT_SECURITY_POLICY, "sandbox"),
+        ]
+    } else {
+        [
+            (header::CONTENT_TYPE, "image/jpeg"),
+            (header::CONTENT_SECURITY_POLICY, "default-src 'none'"),
+        ]
+    };
+    Ok((headers, bytes))
+}
+
+/// Script language for recording exports
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// Download a macro (by name) or a stored run (by ID) as a runnable test script
+pub async fn export_recording(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<ExportQuery>,
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (title, actions) = match visible_macro(&state, &id, auth_user.as_ref(), &headers) {
+        Ok(recorded) => (recorded.name, recorded.actions),
+        Err(_) => {
+            let run = visible_run(&state, &id, auth_user.as_ref(), &headers)
+                .await
+                .map_err(|_| {
+                    ApiError::new(
+                        StatusCode::NOT_FOUND,
+                        format!("No macro or run named {}", id),
+                    )
+                })?;
+            (run.task, run_actions(&run.result))
+        }
+    };
+
+    let disposition = format!(
+        "attachment; filename=\"{}.{}\"",
+        id.replace(['"', '\\', '\r', '\n', '/'], "_"),
+        query.format.extension()
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, query.format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        export_script(query.format, &title, &actions),
+    ))
+}
+
+/// Stored multi-step runs, oldest first
+#[derive(Debug, Serialize)]
+pub struct RunsResponse {
+    pub runs: Vec<RunOverview>,
+    pub count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Filters of the run history
+#[derive(Debug, Deserialize)]
+pub struct RunsFilter {
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Only runs that completed their task (`true`) or didn't (`false`)
+    #[serde(default)]
+    pub completed: Option<bool>,
+    /// Only runs started at or after this time (RFC 3339)
+    #[serde(default)]
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Stored runs, filtered; callers see the runs they started, admins see all of them
+pub async fn list_runs(
+    State(state): State<AppState>,
+    Query(page): Query<PageQuery>,
+    Query(filter): Query<RunsFilter>,
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<Json<RunsResponse>, ApiError> {
+    let admin = is_admin(&headers);
+    let caller = auth_user.as_ref().map(|Extension(user)| user.user_id.as_str());
+    let runs = state.run_store.overviews().await.into_iter().filter(|run| {
+        (admin || run.owner.as_deref() == caller)
+            && filter.session_id.as_ref().is_none_or(|id| run.session_id == *id)
+            && filter.completed.is_none_or(|completed| run.task_completed == completed)
+            && filter.since.is_none_or(|since| run.started_at >= since)
+    });
+
+    let page = page.page(runs, |run| (run.started_at, run.run_id.clone()))?;
+    let count = page.items.len();
+
+    Ok(Json(RunsResponse {
+        runs: page.items,
+        count,
+        next_cursor: page.next_cursor,
+    }))
+}
+
+/// Stored multi-step runs of one session, oldest first; takes the filters of `/runs`
+pub async fn list_session_runs(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(page): Query<PageQuery>,
+    Query(mut filter): Query<RunsFilter>,
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<Json<RunsResponse>, ApiError> {
+    filter.session_id = Some(session_id);
+    list_runs(State(state), Query(page), Query(filter), headers, auth_user).await
+}
+
+/// A stored multi-step run with its full result
+pub async fn get_agent_run(
+    State(state): State<AppState>,
+    Path(run_id): Path<String>,
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<Json<AgentRun>, ApiError> {
+    visible_run(&state, &run_id, auth_user.as_ref(), &headers).await.map(Json)
+}
+
+/// A stored run the caller started, or any run for an admin; others are reported as not found
+async fn visible_run(
+    state: &AppState,
+    run_id: &str,
+    auth_user: Option<&Extension<AuthUser>>,
+    headers: &HeaderMap,
+) -> Result<AgentRun, ApiError> {
+    let caller = auth_user.map(|Extension(user)| user.user_id.as_str());
+    state
+        .run_store
+        .get(run_id)
+        .await
+        .filter(|run| is_admin(headers) || run.visible_to(caller))
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Run not found: {}", run_id),
+            )
+        })
+}
+
+/// The two runs to compare
+#[derive(Debug, Deserialize)]
+pub struct CompareRunsQuery {
+    pub a: String,
+    pub b: String,
+}
+
+/// Align two runs of a task and report where their actions and outcomes diverge
+pub async fn compare_runs(
+    State(state): State<AppState>,
+    Query(query): Query<CompareRunsQuery>,
+    headers: HeaderMap,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<Json<RunComparison>, ApiError> {
+    let a = visible_run(&state, &query.a, auth_user.as_ref(), &headers).await?;
+    let b = visible_run(&state, &query.b, auth_user.as_ref(), &headers).await?;
+
+    Ok(Json(RunComparison::between(&a, &b)))
+}
+
+// ===== Notification Handlers =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhooksBody {
+    pub webhooks: Vec<WebhookTarget>,
+}
+
+/// List the authenticated user's notification webhooks
+pub async fn get_webhooks(
+    State(state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
+) -> Result<Json<WebhooksBody>, ApiError> {
+    let Some(Extension(user)) = auth_user else {
+        return Err(ApiError::new(StatusCode::UNAUTHORIZED, "Authentication required".to_string()));
+    };
+
+    Ok(Json(WebhooksBody {
+        webhooks: state.notifier.webhooks_for(&user.user_id),
+    }))
+}
+
+/// Replace the authenticated user's notification webhooks
+pub async fn set_webhooks(
+    State(state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
+    Json(body): Json<WebhooksBody>,
+) -> Result<Json<WebhooksBody>, ApiError> {
+    let Some(Extension(user)) = auth_user else {
+        return Err(ApiError::new(StatusCode::UNAUTHORIZED, "Authentication required".to_string()));
+    };
+
+    for webhook in &body.webhooks {
+        if let Err(e) = validate_webhook_url(&webhook.url).await {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Webhook URL {} is not allowed: {}", webhook.url, e),
+            ));
+        }
+    }
+
+    state.notifier.set_webhooks(&user.user_id, body.webhooks.clone());
+
+    Ok(Json(body))
+}
+
+// ===== Feature Flag Handlers =====
+
+/// Reject the request unless the feature is enabled
+pub fn require_feature(state: &AppState, feature: Feature) -> Result<(), ApiError> {
+    if state.features.is_enabled(feature) {
+        Ok(())
+    } else {
+        Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            format!("Feature '{}' is disabled on this server", feature.name()),
+        ))
+    }
+}
+
+/// Whether the request carries a valid admin token (see `require_admin`)
+fn is_admin(headers: &HeaderMap) -> bool {
+    require_admin(headers).is_ok()
+}
+
+/// Admin endpoints require X-Admin-Token to match ADMIN_TOKEN (disabled when unset)
+fn require_admin(headers: &HeaderMap) -> Result<(), ApiError> {
+    let expected = std::env::var("ADMIN_TOKEN").unwrap_or_default();
+    if expected.is_empty() {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "Admin API is disabled (ADMIN_TOKEN not set)".to_string(),
+        ));
+    }
+
+    let provided = headers
+        .get("x-admin-token")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if provided != expected {
+        return Err(ApiError::new(StatusCode::UNAUTHORIZED, "Invalid admin token".to_string()));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeaturesResponse {
+    pub features: Vec<FeatureState>,
+}
+
+/// List feature flags with their defaults and overrides
+pub async fn list_features(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<FeaturesResponse>, ApiError> {
+    require_admin(&headers)?;
+
+    Ok(Json(FeaturesResponse {
+        features: state.features.list(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetFeatureRequest {
+    /// `true`/`false` to override, `null` to return to the configured default
+    pub enabled: Option<bool>,
+}
+
+/// Flip a feature flag at runtime
+pub async fn set_feature(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<SetFeatureRequest>,
+) -> Result<Json<FeatureState>, ApiError> {
+    require_admin(&headers)?;
+
+    let feature = Feature::parse(&name).ok_or_else(|| {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("Unknown feature: {}", name),
+        )
+    })?;
+
+    state
+        .features
+        .set_override(feature, req.enabled)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to set feature flag {}: {}", name, e);
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to set feature flag: {}", e),
+            )
+        })?;
+
+    Ok(Json(state.features.state(feature)))
+}
+
+// ===== Metrics =====
+
+#[derive(Debug, Serialize)]
+pub struct MetricsResponse {
+    pub sessions: usize,
+    pub page_hygiene: HygieneStats,
+    pub warm_pool: Vec<WarmPoolStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskSchedulesResponse {
+    pub sessions: Vec<SessionSchedule>,
+    pub count: usize,
+}
+
+/// Turn order of the background agent tasks of every session
+pub async fn list_task_schedules(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<TaskSchedulesResponse>, ApiError> {
+    require_admin(&headers)?;
+
+    let sessions = state.task_store.schedules();
+    let count = sessions.len();
+    Ok(Json(TaskSchedulesResponse { sessions, count }))
+}
+
+/// Server-wide counters for monitoring
+pub async fn get_metrics(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<MetricsResponse>, ApiError> {
+    require_admin(&headers)?;
+
+    Ok(Json(MetricsResponse {
+        sessions: state.session_manager.session_count(),
+        page_hygiene: state.session_manager.hygiene_stats(),
+        warm_pool: state.session_manager.warm_pool_status(),
+    }))
+}
+
+// ===== Page Analytics =====
+
+/// Page analytics options (`?url=https://shop.example.com/orders/1042`)
+#[derive(Debug, Deserialize)]
+pub struct PageAnalyticsQuery {
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum PageAnalyticsResponse {
+    Page(PageHeatmap),
+    Pages { pages: Vec<PageSummary> },
+}
+
+/// Element interaction heatmap of the URL pattern `url` belongs to, or totals of every page
+pub async fn get_page_analytics(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<PageAnalyticsQuery>,
+) -> Result<Json<PageAnalyticsResponse>, ApiError> {
+    require_admin(&headers)?;
+
+    let heatmap = state.session_manager.interaction_heatmap();
+    let Some(url) = query.url else {
+        return Ok(Json(PageAnalyticsResponse::Pages { pages: heatmap.pages() }));
+    };
+
+    let page = heatmap.page(&url).ok_or_else(|| {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("No interactions recorded on {}", url_pattern(&url)),
+        )
+    })?;
+    Ok(Json(PageAnalyticsResponse::Page(page)))
+}
+
+// ===== Test Hooks =====
+
+#[derive(Debug, Serialize)]
+pub struct HookEntry {
+    pub name: String,
+    #[serde(flatten)]
+    pub hook: TestHook,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HooksResponse {
+    pub hooks: Vec<HookEntry>,
+}
+
+fn hook_entries(hooks: Vec<(String, TestHook)>) -> Json<HooksResponse> {
+    Json(HooksResponse {
+        hooks: hooks
+            .into_iter()
+            .map(|(name, hook)| HookEntry { name, hook })
+            .collect(),
+    })
+}
+
+/// List all registered test hooks
+pub async fn list_hooks(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<HooksResponse>, ApiError> {
+    require_admin(&headers)?;
+
+    Ok(hook_entries(state.session_manager.hooks().list()))
+}
+
+/// Register or replace a test hook
+pub async fn set_hook(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(hook): Json<TestHook>,
+) -> Result<Json<HookEntry>, ApiError> {
+    require_admin(&headers)?;
+
+    state
+        .session_manager
+        .hooks()
+        .set(&name, hook.clone())
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, format!("Invalid hook: {}", e)))?;
+
+    tracing::info!("Test hook '{}' registered for {}", name, hook.origins.join(", "));
+    Ok(Json(HookEntry { name, hook }))
+}
+
+/// Unregister a test hook
+pub async fn delete_hook(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&headers)?;
+
+    match state.session_manager.hooks().remove(&name) {
+        Some(_) => Ok(StatusCode::NO_CONTENT),
+        None => Err(ApiError::new(StatusCode::NOT_FOUND, format!("Unknown hook: {}", name))),
+    }
+}
+
+/// Hooks the `invoke_hook` action may call on the session's current page
+pub async fn list_session_hooks(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<HooksResponse>, ApiError> {
+    let browser = state
+        .session_manager
+        .get_browser(&session_id)
+        .map_err(|e| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Session not found: {}", e),
+            )
+        })?;
+
+    let hooks = browser.available_hooks().await.map_err(|e| {
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read the page origin: {}", e),
+        )
+    })?;
+
+    Ok(hook_entries(hooks))
+}
+
+// ===== One-time Code Webhooks =====
+
+/// Twilio incoming-SMS webhook feeding `twilio` OTP providers
+///
+/// Disabled unless TWILIO_AUTH_TOKEN is set; every request must carry a valid
+/// X-Twilio-Signature. The signed URL is TWILIO_WEBHOOK_URL (exactly as entered
+/// in the Twilio console), or PUBLIC_BASE_URL plus the request path.
+pub async fn twilio_sms_webhook(
+    State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Form(params): Form<BTreeMap<String, String>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let auth_token = std::env::var("TWILIO_AUTH_TOKEN")
+        .map_err(|_| ApiError::new(StatusCode::NOT_FOUND, "Twilio webhook is not configured".to_string()))?;
+
+    let url = std::env::var("TWILIO_WEBHOOK_URL").unwrap_or_else(|_| {
+        let base = std::env::var("PUBLIC_BASE_URL").unwrap_or_default();
+        format!("{}{}", base.trim_end_matches('/'), uri)
+    });
+
+    let signature = headers
+        .get("x-twilio-signature")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !verify_twilio_signature(&auth_token, &url, &params, signature) {
+        tracing::warn!("Rejected Twilio webhook with invalid signature (signed URL: {})", url);
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Invalid Twilio signature".to_string()));
+    }
+
+    let field = |name: &str| params.get(name).cloned().unwrap_or_default();
+    state
+        .session_manager
+        .otp_providers()
+        .sms_inbox()
+        .push(SmsMessage {
+            from: field("From"),
+            to: field("To"),
+            body: field("Body"),
+            received_at: chrono::Utc::now(),
+        })
+        .await;
+
+    // Empty TwiML: no auto-reply
+    Ok(([(header::CONTENT_TYPE, "text/xml")], "<Response></Response>"))
+}
+
 // ===== Authentication Handlers (Step 4) =====
 
 /// Login request for JWT authentication
@@ -374,14 +3776,22 @@ pub struct LoginRequest {
     /// In production, this would be a hashed password
     #[allow(dead_code)]
     pub password: String,
+    /// Store the JWT in an HttpOnly cookie instead of returning it (default: AUTH_COOKIE_ENABLED)
+    #[serde(default)]
+    pub use_cookie: Option<bool>,
 }
 
 /// Login response with JWT token
 #[derive(Debug, Serialize)]
 pub struct LoginResponse {
-    pub token: String,
+    /// Omitted in cookie mode, where the token lives in an HttpOnly cookie
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
     pub user_id: String,
     pub username: String,
+    /// Cookie mode only: send back in the X-CSRF-Token header on mutating requests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub csrf_token: Option<String>,
 }
 
 /// Simple login endpoint (Step 4)
@@ -389,13 +3799,13 @@ pub struct LoginResponse {
 pub async fn login(
     State(state): State<AppState>,
     Json(req): Json<LoginRequest>,
-) -> Result<Json<LoginResponse>, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
     tracing::info!("Login attempt for user: {}", req.username);
 
     // TODO: In production, validate against database with hashed passwords
     // For now, simple username-based authentication for demo
     if req.username.is_empty() {
-        return Err((
+        return Err(ApiError::new(
             StatusCode::BAD_REQUEST,
             "Username cannot be empty".to_string(),
         ));
@@ -410,12 +3820,18 @@ pub async fn login(
         .and_then(|s| s.parse().ok())
         .unwrap_or(86400); // 24 hours
 
-    // Create JWT claims
-    let claims = Claims::new(
+    let use_cookie = req.use_cookie.unwrap_or(state.cookie_auth.enabled);
+
+    // Create JWT claims (cookie sessions bind a CSRF token into the JWT)
+    let mut claims = Claims::new(
         user_id.clone(),
         Some(req.username.clone()),
         expiration_seconds,
     );
+    let csrf_token = use_cookie.then(generate_csrf_token);
+    if let Some(csrf) = &csrf_token {
+        claims = claims.with_csrf(csrf.clone());
+    }
 
     // Encode token
     let token = state
@@ -423,7 +3839,7 @@ pub async fn login(
         .encode(&claims)
         .map_err(|e| {
             tracing::error!("Failed to encode JWT: {}", e);
-            (
+            ApiError::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Failed to create token".to_string(),
             )
@@ -431,11 +3847,40 @@ pub async fn login(
 
     tracing::info!("User {} logged in successfully (user_id: {})", req.username, user_id);
 
-    Ok(Json(LoginResponse {
-        token,
-        user_id,
-        username: req.username,
-    }))
+    let mut headers = HeaderMap::new();
+    if let Some(csrf) = &csrf_token {
+        for cookie in [
+            state.cookie_auth.auth_cookie(&token, expiration_seconds),
+            state.cookie_auth.csrf_cookie(csrf, expiration_seconds),
+        ] {
+            let value = HeaderValue::from_str(&cookie).map_err(|e| {
+                ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Invalid cookie: {}", e))
+            })?;
+            headers.append(header::SET_COOKIE, value);
+        }
+    }
+
+    Ok((
+        headers,
+        Json(LoginResponse {
+            token: (!use_cookie).then_some(token),
+            user_id,
+            username: req.username,
+            csrf_token,
+        }),
+    ))
+}
+
+/// Clear the auth and CSRF cookies
+pub async fn logout(State(state): State<AppState>) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    for cookie in state.cookie_auth.clear_cookies() {
+        if let Ok(value) = HeaderValue::from_str(&cookie) {
+            headers.append(header::SET_COOKIE, value);
+        }
+    }
+
+    (headers, StatusCode::NO_CONTENT)
 }
 
 /// Get current user info from JWT
@@ -0,0 +1,269 @@
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use dashmap::DashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::auth::AuthUser;
+use crate::models::ErrorCode;
+
+use super::error::ApiError;
+
+/// Request header carrying the client's key for one logical request
+pub const IDEMPOTENCY_HEADER: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// Set on responses served from the cache instead of running the request again
+pub const IDEMPOTENT_REPLAYED_HEADER: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// Longest accepted key
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Request bodies read for fingerprinting, same as axum's default body limit
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Response stored for a key
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl CachedResponse {
+    fn replay(&self) -> Response {
+        let mut response = Response::new(Body::from(self.body.clone()));
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers.clone();
+        response
+            .headers_mut()
+            .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+/// The first request's response; empty while it is still running
+pub type ResponseSlot = Arc<Mutex<Option<CachedResponse>>>;
+
+struct Entry {
+    /// Hash of the request body the key was first used with
+    fingerprint: u64,
+    created_at: Instant,
+    response: ResponseSlot,
+}
+
+/// Responses of requests sent with an `Idempotency-Key`, kept for IDEMPOTENCY_TTL_SECONDS
+///
+/// Keys are scoped to the caller and the route, so two tenants can't see each
+/// other's responses by guessing keys.
+pub struct IdempotencyCache {
+    ttl: Duration,
+    entries: Arc<DashMap<String, Entry>>,
+}
+
+impl IdempotencyCache {
+    /// IDEMPOTENCY_TTL_SECONDS (default: 3600)
+    pub fn from_env() -> Self {
+        let ttl = std::env::var("IDEMPOTENCY_TTL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(3600);
+
+        Self::new(Duration::from_secs(ttl))
+    }
+
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Slot holding the response for `scope`; `None` if the key was used with another body
+    ///
+    /// A new or expired key gets an empty slot, which the caller fills. The
+    /// lookup and the insert happen under the key's lock, so concurrent first
+    /// requests share one slot and only one of them runs.
+    pub fn slot(&self, scope: &str, fingerprint: u64) -> Option<ResponseSlot> {
+        let fresh = || Entry {
+            fingerprint,
+            created_at: Instant::now(),
+            response: ResponseSlot::default(),
+        };
+
+        let mut entry = self.entries.entry(scope.to_string()).or_insert_with(fresh);
+        if entry.created_at.elapsed() >= self.ttl {
+            *entry = fresh();
+        }
+        (entry.fingerprint == fingerprint).then(|| Arc::clone(&entry.response))
+    }
+
+    /// Periodically drop expired keys
+    pub fn start_reaper(&self, interval: Duration) {
+        let entries = Arc::clone(&self.entries);
+        let ttl = self.ttl;
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                entries.retain(|_, entry| entry.created_at.elapsed() < ttl);
+            }
+        });
+    }
+}
+
+fn fingerprint(body: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Run a request once per `Idempotency-Key` and replay its response to retries
+///
+/// Requests without the header pass through. A retry that arrives while the
+/// first request is still running waits for its response. The request runs to
+/// completion even if the client disconnects, so a retry after a timeout gets
+/// the outcome instead of repeating the action.
+pub async fn idempotency_middleware(
+    State(cache): State<Arc<IdempotencyCache>>,
+    auth_user: Option<Extension<AuthUser>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let key = match request.headers().get(&IDEMPOTENCY_HEADER) {
+        None => return next.run(request).await,
+        Some(value) => match value.to_str() {
+            Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => key.to_string(),
+            _ => {
+                return ApiError::new(
+                    StatusCode::BAD_REQUEST,
+                    format!("Idempotency-Key must be 1-{} visible ASCII characters", MAX_KEY_LENGTH),
+                )
+                .into_response();
+            }
+        },
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => {
+            return ApiError::new(StatusCode::BAD_REQUEST, format!("Failed to read request body: {}", e))
+                .into_response();
+        }
+    };
+
+    let caller = auth_user.map(|Extension(user)| user.user_id).unwrap_or_default();
+    let scope = format!("{}|{} {}|{}", caller, parts.method, parts.uri.path(), key);
+    let Some(slot) = cache.slot(&scope, fingerprint(&body)) else {
+        return ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("Idempotency-Key '{}' was already used with a different request body", key),
+        )
+        .with_code(ErrorCode::IdempotencyKeyReused)
+        .into_response();
+    };
+
+    let mut cached = slot.lock_owned().await;
+    if let Some(response) = cached.as_ref() {
+        return response.replay();
+    }
+
+    let request = Request::from_parts(parts, Body::from(body));
+    let run = tokio::spawn(async move {
+        let (parts, body) = next.run(request).await.into_parts();
+        let body = match to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(e) => {
+                return ApiError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to read response body: {}", e),
+                )
+                .into_response();
+            }
+        };
+
+        *cached = Some(CachedResponse {
+            status: parts.status,
+            headers: parts.headers.clone(),
+            body: body.clone(),
+        });
+        Response::from_parts(parts, Body::from(body))
+    });
+
+    match run.await {
+        Ok(response) => response,
+        Err(e) => {
+            ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, format!("Request failed: {}", e)).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_retry_gets_the_first_response() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60));
+        let body = fingerprint(br#"{"tool":"click","selector":"button"}"#);
+
+        let slot = cache.slot("user-1|POST /s1/execute|key-1", body).unwrap();
+        *slot.lock().await = Some(CachedResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(b"{\"success\":true}"),
+        });
+
+        let retry = cache.slot("user-1|POST /s1/execute|key-1", body).unwrap();
+        let replayed = retry.lock().await.as_ref().unwrap().replay();
+        assert_eq!(replayed.status(), StatusCode::OK);
+        assert_eq!(replayed.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+
+        // Another caller with the same key runs its own request
+        assert!(cache.slot("user-2|POST /s1/execute|key-1", body).unwrap().lock().await.is_none());
+        // Same key, different body
+        assert!(cache
+            .slot("user-1|POST /s1/execute|key-1", fingerprint(b"{}"))
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_first_requests_share_a_slot() {
+        let cache = Arc::new(IdempotencyCache::new(Duration::from_secs(60)));
+        let claims: Vec<_> = (0..16)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                tokio::spawn(async move { cache.slot("user-1|POST /run|key-1", 1).unwrap() })
+            })
+            .collect();
+
+        let mut slots = Vec::new();
+        for claim in claims {
+            slots.push(claim.await.unwrap());
+        }
+        assert!(slots.iter().all(|slot| Arc::ptr_eq(slot, &slots[0])));
+    }
+
+    #[tokio::test]
+    async fn test_expired_keys_start_over() {
+        let cache = IdempotencyCache::new(Duration::ZERO);
+        let slot = cache.slot("user-1|POST /run|key-1", 1).unwrap();
+        *slot.lock().await = Some(CachedResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: Bytes::new(),
+        });
+
+        // Expired keys may be reused with any body
+        assert!(cache.slot("user-1|POST /run|key-1", 2).unwrap().lock().await.is_none());
+    }
+}
@@ -1,6 +1,11 @@
+pub mod error;
 pub mod handlers;
+pub mod idempotency;
+pub mod pagination;
 pub mod routes;
 pub mod state;
+pub mod throttle;
+pub mod version;
 
 pub use routes::create_router;
 pub use state::AppState;
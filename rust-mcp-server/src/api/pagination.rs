@@ -0,0 +1,112 @@
+use axum::http::StatusCode;
+use base64::Engine;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::error::ApiError;
+
+/// Items per page when the request sets no `limit`
+pub const DEFAULT_PAGE_LIMIT: usize = 50;
+
+/// Largest `limit` honored; higher values are capped
+pub const MAX_PAGE_LIMIT: usize = 500;
+
+/// `?limit=&cursor=` of list endpoints
+///
+/// Pages are cut by key, not by offset: the cursor holds the key of the last
+/// item returned, so items added or dropped meanwhile don't shift the next page.
+#[derive(Debug, Default, Deserialize)]
+pub struct PageQuery {
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// `next_cursor` of the previous page; the first page when absent
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+/// One page of a list, and the cursor of the next one if more items follow
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+impl PageQuery {
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT)
+    }
+
+    /// The page of `items` after the cursor
+    ///
+    /// `items` must be sorted by `key`, ascending, with no two items sharing a key.
+    pub fn page<T, K>(
+        &self,
+        items: impl IntoIterator<Item = T>,
+        key: impl Fn(&T) -> K,
+    ) -> Result<Page<T>, ApiError>
+    where
+        K: Ord + Serialize + DeserializeOwned,
+    {
+        let after: Option<K> = self.cursor.as_deref().map(decode_cursor).transpose()?;
+        let limit = self.limit();
+
+        let mut remaining = items
+            .into_iter()
+            .skip_while(|item| after.as_ref().is_some_and(|after| key(item) <= *after))
+            .peekable();
+        let items: Vec<T> = remaining.by_ref().take(limit).collect();
+        let next_cursor = match (remaining.peek(), items.last()) {
+            (Some(_), Some(last)) => Some(encode_cursor(&key(last))),
+            _ => None,
+        };
+
+        Ok(Page { items, next_cursor })
+    }
+}
+
+/// Opaque to clients: URL-safe base64 of the key as JSON
+fn encode_cursor<K: Serialize>(key: &K) -> String {
+    let json = serde_json::to_vec(key).unwrap_or_default();
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json)
+}
+
+fn decode_cursor<K: DeserializeOwned>(cursor: &str) -> Result<K, ApiError> {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid cursor: {}", cursor),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page() {
+        let items: Vec<u64> = (1..=5).collect();
+        let query = |limit, cursor: Option<String>| PageQuery { limit: Some(limit), cursor };
+
+        let first = query(2, None).page(items.clone(), |n| *n).unwrap();
+        assert_eq!(first.items, vec![1, 2]);
+
+        let second = query(2, first.next_cursor).page(items.clone(), |n| *n).unwrap();
+        assert_eq!(second.items, vec![3, 4]);
+
+        // Items before the cursor dropped out meanwhile
+        let third = query(2, second.next_cursor).page(vec![4, 5], |n| *n).unwrap();
+        assert_eq!(third.items, vec![5]);
+        assert_eq!(third.next_cursor, None);
+
+        // An exactly full last page has no next cursor
+        assert_eq!(query(5, None).page(items.clone(), |n| *n).unwrap().next_cursor, None);
+        assert_eq!(query(0, None).limit(), 1);
+
+        let error = query(2, Some("not a cursor".to_string())).page(items, |n| *n).unwrap_err();
+        assert_eq!(error.status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,6 +1,7 @@
 use axum::{
+    http::{header, HeaderName, HeaderValue, Method},
     middleware,
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use tower_http::cors::{Any, CorsLayer};
@@ -8,28 +9,71 @@ use tower_governor::{governor::GovernorConfigBuilder, GovernorLayer};
 
 #[allow(unused_imports)] // Used when protected_routes is enabled
 use crate::auth::{auth_middleware, optional_auth_middleware};
+use crate::auth::{capability_middleware, CAPABILITY_HEADER, CSRF_HEADER};
 use super::handlers::*;
+use super::idempotency::{idempotency_middleware, IDEMPOTENCY_HEADER, IDEMPOTENT_REPLAYED_HEADER};
 use super::state::AppState;
+use super::throttle::{rate_limit_error_response, RATE_LIMIT_LIMIT_HEADER, RATE_LIMIT_USED_HEADER, RETRY_AFTER_MS_HEADER};
+use super::version::{negotiate_version, ApiVersion, API_VERSION_HEADER};
 
 /// Build application router
 pub fn create_router(state: AppState) -> Router {
+    // Backoff hints of 429/503 responses, readable by browser clients
+    let exposed_headers = [
+        API_VERSION_HEADER,
+        IDEMPOTENT_REPLAYED_HEADER,
+        header::RETRY_AFTER,
+        RETRY_AFTER_MS_HEADER,
+        RATE_LIMIT_LIMIT_HEADER,
+        RATE_LIMIT_USED_HEADER,
+    ];
+
     // CORS layer for frontend
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    // Cookie auth needs credentialed requests, which browsers only allow for explicit origins
+    let cors = match std::env::var("CORS_ALLOWED_ORIGINS") {
+        Ok(origins) if !origins.trim().is_empty() => {
+            let origins: Vec<HeaderValue> = origins
+                .split(',')
+                .filter_map(|origin| HeaderValue::from_str(origin.trim()).ok())
+                .collect();
+
+            CorsLayer::new()
+                .allow_origin(origins)
+                .allow_methods([
+                    Method::GET,
+                    Method::POST,
+                    Method::PUT,
+                    Method::PATCH,
+                    Method::DELETE,
+                    Method::OPTIONS,
+                ])
+                .allow_headers([
+                    header::AUTHORIZATION,
+                    header::CONTENT_TYPE,
+                    HeaderName::from_static(CSRF_HEADER),
+                    HeaderName::from_static(CAPABILITY_HEADER),
+                    API_VERSION_HEADER,
+                    IDEMPOTENCY_HEADER,
+                ])
+                .expose_headers(exposed_headers)
+                .allow_credentials(true)
+        }
+        _ => CorsLayer::new()
+            .allow_origin(Any)
+            .allow_methods(Any)
+            .allow_headers(Any)
+            .expose_headers(exposed_headers),
+    };
 
     // Rate limiting (Step 4)
-    // Get rate limit from environment or use default (60 requests per minute)
-    let rate_limit_per_minute: u64 = std::env::var("RATE_LIMIT_PER_MINUTE")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(60);
+    let rate_limit_per_minute = rate_limit_per_minute();
+    let burst_size = ((rate_limit_per_minute / 60) * 10) as u32;
 
     let governor_conf = std::sync::Arc::new(
         GovernorConfigBuilder::default()
-            .per_second((rate_limit_per_minute / 60) as u64) // Convert to per-second
-            .burst_size(((rate_limit_per_minute / 60) * 10) as u32) // Allow bursts
+            .per_second(rate_limit_per_minute / 60) // Convert to per-second
+            .burst_size(burst_size) // Allow bursts
+            .error_handler(move |error| rate_limit_error_response(error, burst_size))
             .finish()
             .expect("Failed to create rate limiter config"),
     );
@@ -38,15 +82,64 @@ pub fn create_router(state: AppState) -> Router {
         config: governor_conf,
     };
 
+    // Every version is nested under its prefix; unprefixed paths are rewritten
+    // by the negotiation middleware, which has to run before routing
+    let mut versioned = Router::new();
+    for version in ApiVersion::ALL {
+        versioned = versioned.nest(version.prefix(), api_routes(&state, version));
+    }
+
+    let api = versioned
+        .layer(rate_limit_layer)
+        .layer(cors)
+        .with_state(state);
+
+    Router::new()
+        .fallback_service(api)
+        .layer(middleware::from_fn(negotiate_version))
+}
+
+/// Requests per user per minute, from RATE_LIMIT_PER_MINUTE (default: 60)
+pub fn rate_limit_per_minute() -> u64 {
+    std::env::var("RATE_LIMIT_PER_MINUTE")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(60)
+}
+
+/// Routes served under one API version
+///
+/// Versions share handlers; a route only differs where v2 made a breaking change.
+fn api_routes(state: &AppState, version: ApiVersion) -> Router<AppState> {
+    let list_sessions_route = match version {
+        ApiVersion::V1 => get(list_sessions),
+        ApiVersion::V2 => get(list_sessions_v2),
+    };
+
+    // Requests that act on the page run once per Idempotency-Key
+    let idempotent = middleware::from_fn_with_state(state.idempotency.clone(), idempotency_middleware);
+
     // Public routes (no auth required)
     let public_routes = Router::new()
         .route("/health", get(health_check))
-        .route("/auth/login", post(login));
+        .route("/capabilities", get(get_capabilities))
+        .route("/auth/login", post(login))
+        .route("/auth/logout", post(logout))
+        .route("/otp/twilio/sms", post(twilio_sms_webhook))
+        .route("/admin/features", get(list_features))
+        .route("/admin/features/:feature", put(set_feature))
+        .route("/admin/metrics", get(get_metrics))
+        .route("/admin/tasks", get(list_task_schedules))
+        .route("/analytics/pages", get(get_page_analytics))
+        .route("/admin/hooks", get(list_hooks))
+        .route("/admin/hooks/:name", put(set_hook))
+        .route("/admin/hooks/:name", delete(delete_hook));
 
     // Optional auth routes (work with or without auth)
     let optional_auth_routes = Router::new()
         .route("/sessions", post(create_session))
-        .route("/sessions", get(list_sessions))
+        .route("/sessions", list_sessions_route)
+        .route("/run", post(run_one_shot).layer(idempotent.clone()))
         .layer(middleware::from_fn_with_state(
             state.jwt_handler.clone(),
             optional_auth_middleware,
@@ -55,9 +148,13 @@ pub fn create_router(state: AppState) -> Router {
     // Protected routes (require authentication) - commented out for now since we want backward compatibility
     // Can be enabled in production
     /* let protected_routes = Router::new()
+        .route("/sessions/:session_id", get(get_session))
         .route("/sessions/:session_id", delete(delete_session))
+        .route("/sessions/:session_id/keepalive", post(keepalive_session))
+        .route("/sessions/:session_id/restore", post(restore_session))
         .route("/:session_id/get_context", get(get_context))
         .route("/:session_id/execute", post(execute_action))
+        .route("/:session_id/validate_action", post(validate_action))
         .route("/:session_id/trigger", post(handle_trigger))
         .route("/:session_id/agent/execute", post(agent_execute_task))
         .route("/:session_id/agent/execute_multi_step", post(agent_execute_multi_step))
@@ -69,14 +166,80 @@ pub fn create_router(state: AppState) -> Router {
     */
 
     // For now, use optional auth for backward compatibility
+    // Capability tokens (POST /sessions/:id/share) are checked per matched route
     let main_routes = Router::new()
+        .route("/sessions/:session_id", get(get_session))
         .route("/sessions/:session_id", delete(delete_session))
+        .route("/sessions/:session_id/keepalive", post(keepalive_session))
+        .route("/sessions/:session_id/share", post(share_session))
+        .route("/sessions/:session_id/restore", post(restore_session))
+        .route("/:session_id/vars", get(get_session_vars))
+        .route("/:session_id/vars", put(set_session_vars))
         .route("/:session_id/get_context", get(get_context))
-        .route("/:session_id/execute", post(execute_action))
+        .route("/:session_id/execute", post(execute_action).layer(idempotent.clone()))
+        .route("/:session_id/validate_action", post(validate_action))
         .route("/:session_id/trigger", post(handle_trigger))
-        .route("/:session_id/agent/execute", post(agent_execute_task))
-        .route("/:session_id/agent/execute_multi_step", post(agent_execute_multi_step))
+        .route("/:session_id/events", get(get_session_events))
+        .route("/:session_id/console", get(get_console))
+        .route("/:session_id/tabs", get(list_tabs))
+        .route("/:session_id/ws", get(session_websocket))
+        .route("/:session_id/extract_table", post(extract_table))
+        .route("/:session_id/forms", get(get_forms))
+        .route("/:session_id/content", get(get_content))
+        .route("/:session_id/extract", post(extract_with_schema))
+        .route("/:session_id/screenshot", get(get_screenshot))
+        .route("/:session_id/a11y/assistive_check", post(assistive_check))
+        .route("/:session_id/downloads", get(list_artifacts))
+        .route("/:session_id/downloads/:artifact_id", get(get_artifact))
+        .route("/:session_id/artifacts", get(list_artifacts))
+        .route("/:session_id/artifacts/:artifact_id", get(get_artifact))
+        .route("/:session_id/subscriptions", post(subscribe_element))
+        .route("/:session_id/subscriptions", get(list_element_subscriptions))
+        .route("/:session_id/subscriptions/:subscription_id", delete(unsubscribe_element))
+        .route("/:session_id/network/rules", post(add_network_rules))
+        .route("/:session_id/network/rules", get(list_network_rules))
+        .route("/:session_id/network/rules", delete(clear_network_rules))
+        .route("/:session_id/network/rules/:rule_id", delete(delete_network_rule))
+        .route("/:session_id/storage", get(get_web_storage))
+        .route("/:session_id/state", get(get_storage_state))
+        .route("/:session_id/storage", put(set_web_storage))
+        .route("/:session_id/evaluate", post(evaluate_script))
+        .route("/:session_id/elements/:element_id/neighborhood", get(get_element_neighborhood))
+        .route("/:session_id/hooks", get(list_session_hooks))
+        .route("/:session_id/recordings/start", post(start_recording))
+        .route("/:session_id/recordings/stop", post(stop_recording))
+        .route("/:session_id/macros/:name/replay", post(replay_macro))
+        .route("/:session_id/agent/execute", post(agent_execute_task).layer(idempotent.clone()))
+        .route("/:session_id/agent/execute_multi_step", post(agent_execute_multi_step).layer(idempotent.clone()))
+        .route("/:session_id/agent/execute_multi_step/stream", post(agent_execute_multi_step_stream))
+        .route("/:session_id/agent/tasks", post(enqueue_agent_task).layer(idempotent.clone()))
+        .route("/:session_id/agent/jobs/:task_id/pause", post(pause_agent_task))
+        .route("/:session_id/agent/jobs/:task_id/resume", post(resume_agent_task))
+        .route("/:session_id/agent/pause", post(pause_agent_run))
+        .route("/:session_id/agent/resume", post(resume_agent_run))
+        .route("/:session_id/agent/cancel", post(cancel_agent_run))
+        .route("/:session_id/agent/runs", get(list_session_runs))
+        .route("/:session_id/agent/trace/:run_id", get(get_agent_trace))
+        .route("/:session_id/agent/trace/:run_id/files/:file", get(get_agent_trace_file))
+        .route("/tasks/:task_id", get(get_agent_task))
+        .route("/tasks/:task_id", delete(cancel_agent_task))
+        .route("/runs", get(list_runs))
+        .route("/agent/runs/:run_id", get(get_agent_run))
+        .route("/runs/compare", get(compare_runs))
+        .route("/runs/:run_id/report", get(get_run_report))
+        .route("/runs/:run_id/downloads/:download_id", get(get_run_download))
+        .route("/macros", get(list_macros))
+        .route("/macros/:name", get(get_macro))
+        .route("/macros/:name", delete(delete_macro))
+        .route("/recordings/:id/export", get(export_recording))
+        .route("/tunnels/connect", get(tunnel_websocket))
+        .route("/notifications/webhooks", get(get_webhooks))
+        .route("/notifications/webhooks", put(set_webhooks))
         .route("/auth/me", get(get_current_user))
+        .route_layer(middleware::from_fn_with_state(
+            (state.jwt_handler.clone(), state.session_manager.clone()),
+            capability_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             state.jwt_handler.clone(),
             optional_auth_middleware,
@@ -87,7 +250,4 @@ pub fn create_router(state: AppState) -> Router {
         .merge(public_routes)
         .merge(optional_auth_routes)
         .merge(main_routes)
-        .layer(rate_limit_layer)
-        .layer(cors)
-        .with_state(state)
 }
@@ -1,12 +1,30 @@
 use std::sync::Arc;
-use crate::auth::JwtHandler;
+use crate::agent::{RunControls, RunStore, TaskStore};
+use crate::auth::{CookieAuthConfig, JwtHandler};
+use crate::features::FeatureFlags;
+use crate::notifications::Notifier;
+use crate::privacy::PiiScrubbing;
+use crate::recorder::MacroStore;
 use crate::session::SessionManager;
 
+use super::idempotency::IdempotencyCache;
+
 /// Application state shared across handlers
 #[derive(Clone)]
 pub struct AppState {
     pub session_manager: Arc<SessionManager>,
     pub jwt_handler: Arc<JwtHandler>,
+    pub cookie_auth: Arc<CookieAuthConfig>,
+    pub run_store: Arc<RunStore>,
+    pub task_store: Arc<TaskStore>,
+    /// Action sequences recorded from sessions, for replay
+    pub macro_store: Arc<MacroStore>,
+    /// Pause and cancel switches of the multi-step runs in progress
+    pub run_controls: Arc<RunControls>,
+    pub notifier: Arc<Notifier>,
+    pub features: Arc<FeatureFlags>,
+    pub idempotency: Arc<IdempotencyCache>,
+    pub pii_scrubbing: Arc<PiiScrubbing>,
 }
 
 impl AppState {
@@ -21,6 +39,15 @@ impl AppState {
         Self {
             session_manager: Arc::new(SessionManager::new()),
             jwt_handler: Arc::new(JwtHandler::new(&jwt_secret)),
+            cookie_auth: Arc::new(CookieAuthConfig::from_env()),
+            run_store: Arc::new(RunStore::from_env()),
+            task_store: Arc::new(TaskStore::from_env()),
+            macro_store: Arc::new(MacroStore::new()),
+            run_controls: Arc::new(RunControls::new()),
+            notifier: Arc::new(Notifier::from_env()),
+            features: Arc::new(FeatureFlags::from_env()),
+            idempotency: Arc::new(IdempotencyCache::from_env()),
+            pii_scrubbing: Arc::new(PiiScrubbing::from_env()),
         }
     }
 }
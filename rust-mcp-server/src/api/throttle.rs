@@ -0,0 +1,142 @@
+use axum::{
+    body::Body,
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use std::time::Duration;
+use tower_governor::GovernorError;
+
+use crate::models::ErrorCode;
+
+use super::error::ApiError;
+
+/// Allowed requests (or units) in the window that rejected the request
+pub const RATE_LIMIT_LIMIT_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+
+/// How many of them are in use
+pub const RATE_LIMIT_USED_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-used");
+
+/// Same as `Retry-After`, in milliseconds
+pub const RETRY_AFTER_MS_HEADER: HeaderName = HeaderName::from_static("retry-after-ms");
+
+/// A request turned away for now, with when to try again
+///
+/// Answered with `status` (`429 Too Many Requests` for rate limits, `503
+/// Service Unavailable` when capacity runs out), a `Retry-After` header and a
+/// JSON body, so clients can back off instead of retrying right away.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryAfter {
+    pub status: StatusCode,
+    /// Machine-readable cause, e.g. `rate_limited`
+    pub error: ErrorCode,
+    pub message: String,
+    pub retry_after: Duration,
+    pub current_usage: u64,
+    pub limit: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct RetryAfterBody<'a> {
+    error: ErrorCode,
+    message: &'a str,
+    retry_after_ms: u128,
+    current_usage: u64,
+    limit: u64,
+}
+
+impl RetryAfter {
+    /// The caller used up its request burst
+    pub fn rate_limited(retry_after: Duration, limit: u64) -> Self {
+        Self {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            error: ErrorCode::RateLimited,
+            message: format!(
+                "Rate limit of {} requests exceeded, retry in {} ms",
+                limit,
+                retry_after.as_millis()
+            ),
+            retry_after,
+            current_usage: limit,
+            limit,
+        }
+    }
+}
+
+impl IntoResponse for RetryAfter {
+    fn into_response(self) -> Response {
+        let body = RetryAfterBody {
+            error: self.error,
+            message: &self.message,
+            retry_after_ms: self.retry_after.as_millis(),
+            current_usage: self.current_usage,
+            limit: self.limit,
+        };
+        let mut response = (self.status, Json(body)).into_response();
+
+        // Retry-After only takes whole seconds; round up so waiting that long is enough
+        let seconds = self.retry_after.as_secs() + u64::from(self.retry_after.subsec_nanos() > 0);
+        let headers = response.headers_mut();
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        headers.insert(RETRY_AFTER_MS_HEADER, HeaderValue::from(self.retry_after.as_millis() as u64));
+        headers.insert(RATE_LIMIT_LIMIT_HEADER, HeaderValue::from(self.limit));
+        headers.insert(RATE_LIMIT_USED_HEADER, HeaderValue::from(self.current_usage));
+        response
+    }
+}
+
+/// Error response of the rate limiter; `burst_size` is the number of requests it allows at once
+pub fn rate_limit_error_response(error: GovernorError, burst_size: u32) -> Response<Body> {
+    match error {
+        // The limiter reports whole seconds, rounded down
+        GovernorError::TooManyRequests { wait_time, .. } => {
+            RetryAfter::rate_limited(Duration::from_secs(wait_time + 1), u64::from(burst_size)).into_response()
+        }
+        GovernorError::UnableToExtractKey => {
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Unable to identify the client for rate limiting",
+            )
+            .into_response()
+        }
+        GovernorError::Other { code, msg, headers } => {
+            let mut response = ApiError::new(code, msg.unwrap_or_default()).into_response();
+            if let Some(headers) = headers {
+                response.headers_mut().extend(headers);
+            }
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_retry_after_response() {
+        let response = rate_limit_error_response(
+            GovernorError::TooManyRequests {
+                wait_time: 2,
+                headers: None,
+            },
+            10,
+        );
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "3");
+        assert_eq!(response.headers()[RETRY_AFTER_MS_HEADER], "3000");
+        assert_eq!(response.headers()[RATE_LIMIT_LIMIT_HEADER], "10");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "rate_limited");
+        assert_eq!(body["retry_after_ms"], 3000);
+        assert_eq!(body["current_usage"], 10);
+
+        // Partial seconds round up in Retry-After
+        let response = RetryAfter::rate_limited(Duration::from_millis(1500), 10).into_response();
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+        assert_eq!(response.headers()[RETRY_AFTER_MS_HEADER], "1500");
+    }
+}
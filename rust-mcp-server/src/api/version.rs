@@ -0,0 +1,135 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue, StatusCode, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::error::ApiError;
+
+/// Request header selecting the API version for unprefixed paths; echoed on every response
+pub const API_VERSION_HEADER: HeaderName = HeaderName::from_static("api-version");
+
+/// Supported API versions
+///
+/// v1 is the original API and stays the default for unprefixed paths, so existing
+/// clients keep working. Breaking changes ship under v2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    /// Parse "1", "v1", "2", "v2"
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().trim_start_matches(['v', 'V']) {
+            "1" => Some(Self::V1),
+            "2" => Some(Self::V2),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self) -> &'static str {
+        match self {
+            Self::V1 => "1",
+            Self::V2 => "2",
+        }
+    }
+
+    /// Path prefix the version's routes are nested under
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Self::V1 => "/v1",
+            Self::V2 => "/v2",
+        }
+    }
+
+    /// Version named by a `/vN` path prefix, if any
+    fn from_path(path: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|version| {
+            path.strip_prefix(version.prefix())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+/// Map an unprefixed path (and query) onto the negotiated version's routes
+fn versioned_uri(uri: &Uri, version: ApiVersion) -> Option<Uri> {
+    let path_and_query = uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+    format!("{}{}", version.prefix(), path_and_query).parse().ok()
+}
+
+/// Version negotiation middleware
+///
+/// Must wrap the router from the outside (it rewrites the URI before routing).
+/// `/v1/...` and `/v2/...` are served as-is; other paths are routed to the version
+/// named by the `API-Version` header, defaulting to v1.
+pub async fn negotiate_version(mut request: Request, next: Next) -> Response {
+    let version = match ApiVersion::from_path(request.uri().path()) {
+        Some(version) => version,
+        None => {
+            let requested = request
+                .headers()
+                .get(&API_VERSION_HEADER)
+                .and_then(|value| value.to_str().ok());
+
+            let version = match requested {
+                None => ApiVersion::V1,
+                Some(value) => match ApiVersion::parse(value) {
+                    Some(version) => version,
+                    None => {
+                        return ApiError::new(
+                            StatusCode::BAD_REQUEST,
+                            format!("Unsupported API version '{}' (supported: 1, 2)", value),
+                        )
+                        .into_response();
+                    }
+                },
+            };
+
+            if let Some(uri) = versioned_uri(request.uri(), version) {
+                *request.uri_mut() = uri;
+            }
+            version
+        }
+    };
+
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .insert(API_VERSION_HEADER, HeaderValue::from_static(version.number()));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(ApiVersion::parse("2"), Some(ApiVersion::V2));
+        assert_eq!(ApiVersion::parse("v1"), Some(ApiVersion::V1));
+        assert_eq!(ApiVersion::parse("3"), None);
+    }
+
+    #[test]
+    fn test_version_from_path() {
+        assert_eq!(ApiVersion::from_path("/v2/sessions"), Some(ApiVersion::V2));
+        assert_eq!(ApiVersion::from_path("/v1"), Some(ApiVersion::V1));
+        // Session IDs are top-level path segments, so only exact prefixes count
+        assert_eq!(ApiVersion::from_path("/v1abc/get_context"), None);
+        assert_eq!(ApiVersion::from_path("/sessions"), None);
+    }
+
+    #[test]
+    fn test_versioned_uri_keeps_query() {
+        let uri: Uri = "/runs/abc/report?format=html".parse().unwrap();
+        assert_eq!(
+            versioned_uri(&uri, ApiVersion::V2).unwrap(),
+            "/v2/runs/abc/report?format=html"
+        );
+    }
+}
@@ -0,0 +1,276 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{MatchedPath, RawPathParams, Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::jwt::JwtHandler;
+use super::middleware::AuthUser;
+use crate::api::error::ApiError;
+use crate::session::SessionManager;
+
+/// Header carrying a capability token
+pub const CAPABILITY_HEADER: &str = "x-capability-token";
+
+/// Query parameter carrying a capability token (for shareable links)
+pub const CAPABILITY_QUERY: &str = "capability";
+
+/// Largest request body inspected when checking allowed action types
+const MAX_ACTION_BODY_BYTES: usize = 1024 * 1024;
+
+/// Rights a capability token can grant on its session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Read-only access: session details, context, events, reports
+    Observe,
+    /// Run (and dry-run) actions through `/execute` and `/validate_action`
+    Execute,
+}
+
+/// Claims of a capability token, scoped to a single session
+///
+/// Deliberately shaped differently from user `Claims` (no `sub`), so neither
+/// token type decodes as the other.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityClaims {
+    /// Session the token is valid for
+    pub sid: String,
+
+    /// User who minted the token
+    pub granted_by: String,
+
+    pub rights: Vec<Capability>,
+
+    /// Action types (`tool` names) allowed with `execute`; all when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actions: Option<Vec<String>>,
+
+    pub iat: u64,
+    pub exp: u64,
+}
+
+impl CapabilityClaims {
+    pub fn new(session_id: String, granted_by: String, rights: Vec<Capability>, ttl_seconds: u64) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs();
+
+        Self {
+            sid: session_id,
+            granted_by,
+            rights,
+            actions: None,
+            iat: now,
+            exp: now + ttl_seconds,
+        }
+    }
+
+    /// Builder method to restrict `execute` to some action types
+    pub fn with_actions(mut self, actions: Vec<String>) -> Self {
+        self.actions = Some(actions);
+        self
+    }
+
+    pub fn grants(&self, capability: Capability) -> bool {
+        self.rights.contains(&capability)
+    }
+
+    pub fn allows_action(&self, tool: &str) -> bool {
+        self.actions
+            .as_ref()
+            .is_none_or(|actions| actions.iter().any(|a| a == tool))
+    }
+}
+
+/// Capability token from the header or the `capability` query parameter
+fn extract_capability(request: &Request) -> Option<String> {
+    if let Some(token) = request
+        .headers()
+        .get(CAPABILITY_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        return Some(token.to_string());
+    }
+
+    request.uri().query()?.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == CAPABILITY_QUERY).then(|| value.to_string())
+    })
+}
+
+/// Right needed for a route, or `None` if capability tokens can't be used there
+fn required_capability(method: &Method, route: &str) -> Option<Capability> {
+    if route.ends_with("/:session_id/execute") || route.ends_with("/:session_id/validate_action") {
+        return (method == Method::POST).then_some(Capability::Execute);
+    }
+
+    // Variables, web storage and cookies may hold account details and tokens, so observers can't read them
+    if ["/:session_id/vars", "/:session_id/storage", "/:session_id/state"]
+        .iter()
+        .any(|private| route.ends_with(private))
+    {
+        return None;
+    }
+
+    // Agent runs and anything else that changes the session need the owner
+    (method == Method::GET).then_some(Capability::Observe)
+}
+
+/// Capability token middleware for session routes
+///
+/// Without a capability token, a session with an owner (every shared session
+/// has one) is only open to its owner. With a token, the token must be valid,
+/// issued for the session in the path, and grant the right the route needs;
+/// `execute` tokens may also be limited to some action types. Must be applied
+/// with `route_layer` so path parameters are available, inside the auth
+/// middleware so the caller is known.
+pub async fn capability_middleware(
+    State((jwt_handler, sessions)): State<(Arc<JwtHandler>, Arc<SessionManager>)>,
+    matched_path: Option<MatchedPath>,
+    params: RawPathParams,
+    request: Request,
+    next: Next,
+) -> Response {
+    let session_id = params
+        .iter()
+        .find(|(key, _)| *key == "session_id")
+        .map(|(_, value)| value);
+
+    let Some(token) = extract_capability(&request) else {
+        let caller = request.extensions().get::<AuthUser>().map(|user| user.user_id.as_str());
+        let session = session_id.and_then(|session_id| sessions.get_session(session_id).ok());
+        if session.is_some_and(|session| !session.usable_by(caller)) {
+            return ApiError::new(
+                StatusCode::FORBIDDEN,
+                "Only the session owner can use it without a capability token",
+            )
+            .into_response();
+        }
+        return next.run(request).await;
+    };
+
+    let claims = match jwt_handler.validate_capability(&token) {
+        Ok(claims) => claims,
+        Err(e) => {
+            tracing::warn!("Capability token rejected: {}", e);
+            return ApiError::new(StatusCode::UNAUTHORIZED, "Invalid or expired capability token")
+                .into_response();
+        }
+    };
+
+    if session_id != Some(claims.sid.as_str()) {
+        return ApiError::new(StatusCode::FORBIDDEN, "Capability token is not valid for this resource")
+            .into_response();
+    }
+
+    let route = matched_path.as_ref().map(|p| p.as_str()).unwrap_or_default();
+    let Some(required) = required_capability(request.method(), route) else {
+        return ApiError::new(StatusCode::FORBIDDEN, "Capability tokens cannot be used for this operation")
+            .into_response();
+    };
+    if !claims.grants(required) {
+        return ApiError::new(StatusCode::FORBIDDEN, "Capability token does not grant this right")
+            .into_response();
+    }
+
+    if required == Capability::Execute && claims.actions.is_some() {
+        // Buffer the body to read the action type, then hand it on unchanged
+        let (parts, body) = request.into_parts();
+        let bytes = match to_bytes(body, MAX_ACTION_BODY_BYTES).await {
+            Ok(bytes) => bytes,
+            Err(_) => {
+                return ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large").into_response()
+            }
+        };
+
+        let tool = serde_json::from_slice::<serde_json::Value>(&bytes)
+            .ok()
+            .and_then(|v| v.get("tool")?.as_str().map(str::to_string))
+            .unwrap_or_default();
+        if !claims.allows_action(&tool) {
+            return ApiError::new(
+                StatusCode::FORBIDDEN,
+                format!("Capability token does not allow '{}' actions", tool),
+            )
+            .into_response();
+        }
+
+        let mut request = Request::from_parts(parts, Body::from(bytes));
+        request.extensions_mut().insert(claims);
+        return next.run(request).await;
+    }
+
+    let mut request = request;
+    request.extensions_mut().insert(claims);
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Claims;
+    use crate::models::Session;
+
+    #[test]
+    fn test_capability_token_roundtrip() {
+        let handler = JwtHandler::new("test_secret");
+        let claims = CapabilityClaims::new("s1".to_string(), "user123".to_string(), vec![Capability::Execute], 60)
+            .with_actions(vec!["click".to_string()]);
+
+        let token = handler.encode_capability(&claims).unwrap();
+        let decoded = handler.validate_capability(&token).unwrap();
+
+        assert_eq!(decoded.sid, "s1");
+        assert!(decoded.grants(Capability::Execute));
+        assert!(!decoded.grants(Capability::Observe));
+        assert!(decoded.allows_action("click"));
+        assert!(!decoded.allows_action("navigate"));
+
+        // Capability tokens are not user tokens and vice versa
+        assert!(handler.validate(&token).is_err());
+        let user_token = handler
+            .encode(&Claims::new("user123".to_string(), None, 60))
+            .unwrap();
+        assert!(handler.validate_capability(&user_token).is_err());
+    }
+
+    #[test]
+    fn test_shared_session_rejects_tokenless_non_owners() {
+        let mut session = Session::new("https://example.com".to_string(), 1280, 720);
+        assert!(session.usable_by(None));
+
+        // Sharing claims the session for the sharer
+        session.claim("alice");
+        assert!(session.usable_by(Some("alice")));
+        assert!(!session.usable_by(Some("bob")));
+        assert!(!session.usable_by(None));
+
+        // An owner is never replaced
+        session.claim("bob");
+        assert_eq!(session.user_id.as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn test_required_capability() {
+        assert_eq!(
+            required_capability(&Method::GET, "/v1/:session_id/get_context"),
+            Some(Capability::Observe)
+        );
+        assert_eq!(
+            required_capability(&Method::POST, "/v1/:session_id/execute"),
+            Some(Capability::Execute)
+        );
+        assert_eq!(required_capability(&Method::DELETE, "/v1/sessions/:session_id"), None);
+        assert_eq!(required_capability(&Method::POST, "/v1/:session_id/agent/execute"), None);
+        assert_eq!(required_capability(&Method::GET, "/v1/:session_id/vars"), None);
+        assert_eq!(required_capability(&Method::GET, "/v1/:session_id/storage"), None);
+        assert_eq!(required_capability(&Method::GET, "/v1/:session_id/state"), None);
+    }
+}
@@ -0,0 +1,145 @@
+use axum::http::{header, HeaderMap, Method};
+
+/// HttpOnly cookie carrying the JWT
+pub const AUTH_COOKIE: &str = "mcp_token";
+
+/// Script-readable cookie carrying the CSRF token (double-submit)
+pub const CSRF_COOKIE: &str = "mcp_csrf";
+
+/// Header mutating requests must echo the CSRF token in
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Settings for cookie-based authentication
+#[derive(Debug, Clone)]
+pub struct CookieAuthConfig {
+    /// Whether login sets cookies by default (AUTH_COOKIE_ENABLED)
+    pub enabled: bool,
+
+    /// Mark cookies `Secure` (AUTH_COOKIE_SECURE, default true; disable for plain-http dev)
+    pub secure: bool,
+
+    /// SameSite attribute (AUTH_COOKIE_SAMESITE: Strict | Lax | None, default Lax)
+    pub same_site: String,
+}
+
+impl CookieAuthConfig {
+    pub fn from_env() -> Self {
+        let flag = |name: &str, default: bool| {
+            std::env::var(name)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(default)
+        };
+
+        let same_site = match std::env::var("AUTH_COOKIE_SAMESITE")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "strict" => "Strict",
+            "none" => "None",
+            _ => "Lax",
+        };
+
+        Self {
+            enabled: flag("AUTH_COOKIE_ENABLED", false),
+            secure: flag("AUTH_COOKIE_SECURE", true),
+            same_site: same_site.to_string(),
+        }
+    }
+
+    fn attributes(&self, max_age: u64) -> String {
+        let mut attrs = format!("Path=/; Max-Age={}; SameSite={}", max_age, self.same_site);
+        // Browsers reject SameSite=None without Secure
+        if self.secure || self.same_site == "None" {
+            attrs.push_str("; Secure");
+        }
+        attrs
+    }
+
+    /// `Set-Cookie` value for the JWT
+    pub fn auth_cookie(&self, token: &str, max_age: u64) -> String {
+        format!("{}={}; HttpOnly; {}", AUTH_COOKIE, token, self.attributes(max_age))
+    }
+
+    /// `Set-Cookie` value for the CSRF token (readable by the client's JavaScript)
+    pub fn csrf_cookie(&self, csrf: &str, max_age: u64) -> String {
+        format!("{}={}; {}", CSRF_COOKIE, csrf, self.attributes(max_age))
+    }
+
+    /// `Set-Cookie` values that remove both cookies
+    pub fn clear_cookies(&self) -> [String; 2] {
+        [
+            format!("{}=; HttpOnly; {}", AUTH_COOKIE, self.attributes(0)),
+            format!("{}=; {}", CSRF_COOKIE, self.attributes(0)),
+        ]
+    }
+}
+
+/// New random CSRF token
+pub fn generate_csrf_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Read a cookie value from the request's `Cookie` headers
+pub fn read_cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Whether a request can change state and therefore needs a CSRF check
+pub fn is_mutating(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Check the CSRF header against the token bound to the JWT
+pub fn csrf_matches(headers: &HeaderMap, expected: Option<&str>) -> bool {
+    let provided = headers.get(CSRF_HEADER).and_then(|v| v.to_str().ok());
+    matches!((provided, expected), (Some(p), Some(e)) if !e.is_empty() && p == e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_read_cookie_and_csrf() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; mcp_token=abc.def.ghi; mcp_csrf=123"),
+        );
+
+        assert_eq!(read_cookie(&headers, AUTH_COOKIE), Some("abc.def.ghi"));
+        assert_eq!(read_cookie(&headers, CSRF_COOKIE), Some("123"));
+        assert_eq!(read_cookie(&headers, "missing"), None);
+
+        assert!(!csrf_matches(&headers, Some("123")));
+        headers.insert(CSRF_HEADER, HeaderValue::from_static("123"));
+        assert!(csrf_matches(&headers, Some("123")));
+        assert!(!csrf_matches(&headers, Some("456")));
+        assert!(!csrf_matches(&headers, None));
+    }
+
+    #[test]
+    fn test_cookie_attributes() {
+        let config = CookieAuthConfig {
+            enabled: true,
+            secure: false,
+            same_site: "Lax".to_string(),
+        };
+
+        assert_eq!(
+            config.auth_cookie("jwt", 60),
+            "mcp_token=jwt; HttpOnly; Path=/; Max-Age=60; SameSite=Lax"
+        );
+        assert!(!config.csrf_cookie("c", 60).contains("HttpOnly"));
+    }
+}
@@ -3,6 +3,8 @@ use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation}
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use super::capability::CapabilityClaims;
+
 /// JWT Claims for user authentication
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
@@ -17,6 +19,10 @@ pub struct Claims {
 
     /// Expiration time (unix timestamp)
     pub exp: u64,
+
+    /// CSRF token bound to this JWT (cookie-based auth only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub csrf: Option<String>,
 }
 
 impl Claims {
@@ -32,9 +38,16 @@ impl Claims {
             username,
             iat: now,
             exp: now + expiration_seconds,
+            csrf: None,
         }
     }
 
+    /// Builder method to bind a CSRF token to the claims
+    pub fn with_csrf(mut self, csrf: String) -> Self {
+        self.csrf = Some(csrf);
+        self
+    }
+
     /// Check if token is expired
     pub fn is_expired(&self) -> bool {
         let now = SystemTime::now()
@@ -88,6 +101,23 @@ impl JwtHandler {
 
         Ok(claims)
     }
+
+    /// Encode a session capability token
+    pub fn encode_capability(&self, claims: &CapabilityClaims) -> Result<String> {
+        let token = encode(&Header::default(), claims, &self.encoding_key)?;
+        Ok(token)
+    }
+
+    /// Decode a capability token; expiry is checked by the decoder
+    pub fn validate_capability(&self, token: &str) -> Result<CapabilityClaims> {
+        let token_data = decode::<CapabilityClaims>(
+            token,
+            &self.decoding_key,
+            &Validation::default(),
+        )?;
+
+        Ok(token_data.claims)
+    }
 }
 
 #[cfg(test)]
@@ -1,12 +1,14 @@
 use axum::{
     extract::{Request, State},
-    http::{header, StatusCode},
+    http::{header, HeaderMap, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use std::sync::Arc;
 
-use super::jwt::JwtHandler;
+use super::cookie::{csrf_matches, is_mutating, read_cookie, AUTH_COOKIE};
+use super::jwt::{Claims, JwtHandler};
+use crate::api::error::ApiError;
 
 /// Authenticated user information extracted from JWT
 #[derive(Debug, Clone)]
@@ -15,39 +17,56 @@ pub struct AuthUser {
     pub username: Option<String>,
 }
 
+/// Extract the JWT from the Authorization header, falling back to the auth cookie
+///
+/// Returns the token and whether it came from the cookie.
+fn extract_token(headers: &HeaderMap) -> Option<(&str, bool)> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match bearer {
+        Some(token) => Some((token, false)),
+        None => read_cookie(headers, AUTH_COOKIE).map(|token| (token, true)),
+    }
+}
+
+/// Cookie-authenticated requests that change state must echo the CSRF token
+fn csrf_ok(request: &Request, claims: &Claims, from_cookie: bool) -> bool {
+    !from_cookie
+        || !is_mutating(request.method())
+        || csrf_matches(request.headers(), claims.csrf.as_deref())
+}
+
 /// Authentication middleware that validates JWT tokens
 ///
-/// Extracts the JWT token from the Authorization header (Bearer token)
-/// and validates it. If valid, adds the user info to request extensions.
+/// Extracts the JWT token from the Authorization header (Bearer token) or the
+/// HttpOnly auth cookie and validates it. If valid, adds the user info to
+/// request extensions.
 #[allow(dead_code)] // Used when protected_routes is enabled in routes.rs
 pub async fn auth_middleware(
     State(jwt_handler): State<Arc<JwtHandler>>,
     mut request: Request,
     next: Next,
-) -> Result<Response, StatusCode> {
-    // Extract authorization header
-    let auth_header = request
-        .headers()
-        .get(header::AUTHORIZATION)
-        .and_then(|value| value.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-
-    // Check if it's a Bearer token
-    if !auth_header.starts_with("Bearer ") {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
-
-    // Extract token
-    let token = &auth_header[7..]; // Remove "Bearer " prefix
+) -> Result<Response, ApiError> {
+    // Extract token (Bearer header or auth cookie)
+    let (token, from_cookie) = extract_token(request.headers())
+        .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "Missing bearer token or auth cookie"))?;
 
     // Validate token
     let claims = jwt_handler
         .validate(token)
         .map_err(|e| {
             tracing::warn!("JWT validation failed: {}", e);
-            StatusCode::UNAUTHORIZED
+            ApiError::new(StatusCode::UNAUTHORIZED, "Invalid or expired token")
         })?;
 
+    if !csrf_ok(&request, &claims, from_cookie) {
+        tracing::warn!("Rejected cookie-authenticated request without a valid CSRF token");
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "CSRF token missing or invalid"));
+    }
+
     // Add user info to request extensions
     request.extensions_mut().insert(AuthUser {
         user_id: claims.sub,
@@ -61,29 +80,26 @@ pub async fn auth_middleware(
 ///
 /// Tries to extract and validate JWT token, but doesn't fail the request
 /// if the token is missing or invalid. Useful for endpoints that work
-/// with or without authentication.
+/// with or without authentication. A valid auth cookie on a mutating request
+/// without the matching CSRF token is still rejected.
 pub async fn optional_auth_middleware(
     State(jwt_handler): State<Arc<JwtHandler>>,
     mut request: Request,
     next: Next,
 ) -> Response {
-    // Try to extract authorization header
-    if let Some(auth_header) = request
-        .headers()
-        .get(header::AUTHORIZATION)
-        .and_then(|value| value.to_str().ok())
-    {
-        if auth_header.starts_with("Bearer ") {
-            let token = &auth_header[7..];
+    // Try to extract a token (Bearer header or auth cookie)
+    let validated = extract_token(request.headers())
+        .and_then(|(token, from_cookie)| Some((jwt_handler.validate(token).ok()?, from_cookie)));
 
-            // Try to validate token
-            if let Ok(claims) = jwt_handler.validate(token) {
-                request.extensions_mut().insert(AuthUser {
-                    user_id: claims.sub,
-                    username: claims.username,
-                });
-            }
+    if let Some((claims, from_cookie)) = validated {
+        if !csrf_ok(&request, &claims, from_cookie) {
+            return ApiError::new(StatusCode::FORBIDDEN, "CSRF token missing or invalid").into_response();
         }
+
+        request.extensions_mut().insert(AuthUser {
+            user_id: claims.sub,
+            username: claims.username,
+        });
     }
 
     next.run(request).await
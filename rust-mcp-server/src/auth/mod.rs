@@ -1,5 +1,9 @@
+pub mod capability;
+pub mod cookie;
 pub mod jwt;
 pub mod middleware;
 
+pub use capability::*;
+pub use cookie::*;
 pub use jwt::*;
 pub use middleware::*;
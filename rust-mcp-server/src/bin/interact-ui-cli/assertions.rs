@@ -0,0 +1,161 @@
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+/// Field of the final page an assertion checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subject {
+    Url,
+    Title,
+    /// Accessible name of any element on the final page
+    Text,
+}
+
+/// How the expected value is compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// `=`
+    Equals,
+    /// `~=`
+    Contains,
+    /// `!~=`
+    NotContains,
+}
+
+/// A post-run check such as `url~=/done` or `title=Dashboard`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assertion {
+    pub raw: String,
+    pub subject: Subject,
+    pub comparison: Comparison,
+    pub expected: String,
+}
+
+impl Assertion {
+    /// Parse `<subject><op><value>` where op is `=`, `~=` or `!~=`
+    pub fn parse(raw: &str) -> Result<Self> {
+        // Longest operators first so `~=` is not read as `=`
+        let (subject, comparison, expected) = if let Some((s, v)) = raw.split_once("!~=") {
+            (s, Comparison::NotContains, v)
+        } else if let Some((s, v)) = raw.split_once("~=") {
+            (s, Comparison::Contains, v)
+        } else if let Some((s, v)) = raw.split_once('=') {
+            (s, Comparison::Equals, v)
+        } else {
+            bail!("Invalid assertion '{}': expected <subject>=<value>, ~= or !~=", raw);
+        };
+
+        let subject = match subject.trim() {
+            "url" => Subject::Url,
+            "title" => Subject::Title,
+            "text" => Subject::Text,
+            other => bail!(
+                "Invalid assertion '{}': unknown subject '{}' (use url, title or text)",
+                raw,
+                other
+            ),
+        };
+
+        Ok(Self {
+            raw: raw.to_string(),
+            subject,
+            comparison,
+            expected: expected.to_string(),
+        })
+    }
+
+    /// Check the assertion against the run's final UI context
+    ///
+    /// Returns a failure message when the assertion does not hold.
+    pub fn check(&self, final_context: Option<&Value>) -> Option<String> {
+        let Some(context) = final_context else {
+            return Some("No final page context was returned by the run".to_string());
+        };
+
+        let actual: Vec<String> = match self.subject {
+            Subject::Url => vec![string_field(context, "url")],
+            Subject::Title => vec![string_field(context, "title")],
+            Subject::Text => context
+                .get("elements")
+                .and_then(|e| e.as_array())
+                .map(|elements| {
+                    elements
+                        .iter()
+                        .filter_map(|e| e.get("name").and_then(|n| n.as_str()))
+                        .map(|n| n.to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+
+        let holds = match self.comparison {
+            Comparison::Equals => actual.contains(&self.expected),
+            Comparison::Contains => actual.iter().any(|a| a.contains(&self.expected)),
+            Comparison::NotContains => !actual.iter().any(|a| a.contains(&self.expected)),
+        };
+
+        if holds {
+            return None;
+        }
+
+        Some(match self.subject {
+            Subject::Text => format!("Assertion '{}' failed on the final page", self.raw),
+            _ => format!(
+                "Assertion '{}' failed: actual value was '{}'",
+                self.raw,
+                actual.first().map(String::as_str).unwrap_or("")
+            ),
+        })
+    }
+}
+
+fn string_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_operators() {
+        let contains = Assertion::parse("url~=/done").unwrap();
+        assert_eq!(contains.subject, Subject::Url);
+        assert_eq!(contains.comparison, Comparison::Contains);
+        assert_eq!(contains.expected, "/done");
+
+        let equals = Assertion::parse("title=Dashboard").unwrap();
+        assert_eq!(equals.comparison, Comparison::Equals);
+
+        let negated = Assertion::parse("text!~=Error").unwrap();
+        assert_eq!(negated.subject, Subject::Text);
+        assert_eq!(negated.comparison, Comparison::NotContains);
+
+        assert!(Assertion::parse("status=200").is_err());
+        assert!(Assertion::parse("url").is_err());
+    }
+
+    #[test]
+    fn test_check_against_context() {
+        let context = json!({
+            "url": "https://staging.app/checkout/done",
+            "title": "Order placed",
+            "elements": [{ "name": "Thank you for your order" }]
+        });
+
+        assert!(Assertion::parse("url~=/done").unwrap().check(Some(&context)).is_none());
+        assert!(Assertion::parse("text~=Thank you").unwrap().check(Some(&context)).is_none());
+        assert!(Assertion::parse("text!~=Error").unwrap().check(Some(&context)).is_none());
+
+        let failure = Assertion::parse("title=Cart").unwrap().check(Some(&context));
+        assert_eq!(
+            failure.as_deref(),
+            Some("Assertion 'title=Cart' failed: actual value was 'Order placed'")
+        );
+        assert!(Assertion::parse("url~=/done").unwrap().check(None).is_some());
+    }
+}
@@ -0,0 +1,97 @@
+use super::report::TestSuite;
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Render a JUnit XML report with one `<testsuite>` per scenario
+pub fn render_junit(suites: &[TestSuite]) -> String {
+    let tests: usize = suites.iter().map(|s| s.cases.len()).sum();
+    let failures: usize = suites.iter().map(|s| s.failures()).sum();
+    let total_time: f64 = suites.iter().map(|s| s.time_seconds()).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        tests, failures, total_time
+    ));
+
+    for suite in suites {
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" time=\"{:.3}\">\n",
+            escape_xml(&suite.name),
+            suite.cases.len(),
+            suite.failures(),
+            suite.time_seconds()
+        ));
+
+        for case in &suite.cases {
+            xml.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+                escape_xml(&suite.name),
+                escape_xml(&case.name),
+                case.time_seconds
+            ));
+
+            if case.failure.is_none() && case.system_out.is_none() {
+                xml.push_str("/>\n");
+                continue;
+            }
+
+            xml.push_str(">\n");
+            if let Some(message) = &case.failure {
+                // The attribute holds the first line, the body the full feedback
+                let summary = message.lines().next().unwrap_or_default();
+                xml.push_str(&format!(
+                    "      <failure message=\"{}\">{}</failure>\n",
+                    escape_xml(summary),
+                    escape_xml(message)
+                ));
+            }
+            if let Some(output) = &case.system_out {
+                xml.push_str(&format!("      <system-out>{}</system-out>\n", escape_xml(output)));
+            }
+            xml.push_str("    </testcase>\n");
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::TestCase;
+
+    #[test]
+    fn test_render_junit() {
+        let suites = vec![
+            TestSuite {
+                name: "login".to_string(),
+                cases: vec![
+                    TestCase::passed("agent task: log in", 12.5)
+                        .with_system_out("1. Click button 'Login'"),
+                    TestCase::failed("assert url~=/done", 0.0, "actual value was '/login?err=<1>'"),
+                ],
+            },
+            TestSuite {
+                name: "checkout".to_string(),
+                cases: vec![TestCase::passed("agent task: checkout", 3.0)],
+            },
+        ];
+
+        let xml = render_junit(&suites);
+        assert!(xml.contains("<testsuites tests=\"3\" failures=\"1\""));
+        assert!(xml.contains("<testsuite name=\"login\" tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("<testsuite name=\"checkout\" tests=\"1\" failures=\"0\""));
+        assert!(xml.contains("<system-out>1. Click button &apos;Login&apos;</system-out>"));
+        assert!(xml.contains("<failure message=\"actual value was &apos;/login?err=&lt;1&gt;&apos;\">"));
+    }
+}
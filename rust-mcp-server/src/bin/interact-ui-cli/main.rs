@@ -0,0 +1,494 @@
+//! Command-line runner for agent-driven E2E checks
+//!
+//! ```text
+//! interact-ui-cli run --url https://staging.app --task "Log in as demo" \
+//!     --assert url~=/dashboard --junit out.xml
+//! ```
+//!
+//! Batches of scenarios can be evaluated from a JSON file with `eval --scenarios`,
+//! producing JUnit XML and/or TAP reports with one suite per scenario.
+//!
+//! Talks to a running server (`--server`, default http://localhost:8080) or starts
+//! the `mcp-server` binary next to this one with `--embedded`. Exits with 0 when the
+//! task completed and every assertion held, 1 on test failures, 2 on usage/infra errors.
+
+mod assertions;
+mod junit;
+mod report;
+mod scenario;
+mod tap;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tokio::process::{Child, Command};
+
+use assertions::Assertion;
+use junit::render_junit;
+use report::{TestCase, TestSuite};
+use scenario::{load_scenarios, Scenario};
+use tap::render_tap;
+
+const USAGE: &str = "Usage:
+  interact-ui-cli run --url <URL> --task <TASK> [--assert <CHECK>]... [options]
+  interact-ui-cli eval --scenarios <FILE> [options]
+
+Run options:
+  --assert <CHECK>      Check the final page, repeatable. Subjects: url, title, text;
+                        operators: = (equals), ~= (contains), !~= (does not contain)
+  --max-steps <N>       Maximum agent steps
+  --suite <NAME>        Test suite name in the report (default: interact-ui)
+
+Eval options:
+  --scenarios <FILE>    JSON array of {name, url, task, assert?, max_steps?}
+
+Common options:
+  --junit <PATH>        Write a JUnit XML report to PATH
+  --tap <PATH>          Write a TAP report to PATH
+  --server <URL>        Server to use (default: $INTERACT_UI_SERVER or http://localhost:8080)
+  --token <JWT>         Bearer token (default: $INTERACT_UI_TOKEN)
+  --embedded            Start a local mcp-server instead of using --server
+  --port <PORT>         Port for the embedded server (default: 8089)";
+
+/// Parsed command line, shared by `run` and `eval`
+#[derive(Debug, Default)]
+struct CliArgs {
+    scenarios: Vec<Scenario>,
+    junit: Option<String>,
+    tap: Option<String>,
+    server: Option<String>,
+    token: Option<String>,
+    embedded: bool,
+    port: Option<u16>,
+}
+
+fn parse_args(args: &[String]) -> Result<CliArgs> {
+    let Some((command, rest)) = args.split_first() else {
+        bail!("Missing command");
+    };
+    if command != "run" && command != "eval" {
+        bail!("Unknown command '{}'", command);
+    }
+
+    let mut parsed = CliArgs::default();
+    let mut url = None;
+    let mut task = None;
+    let mut suite = None;
+    let mut assertions = Vec::new();
+    let mut max_steps = None;
+    let mut scenarios_path = None;
+    let mut iter = rest.iter();
+
+    while let Some(flag) = iter.next() {
+        if flag == "--embedded" {
+            parsed.embedded = true;
+            continue;
+        }
+
+        let value = iter
+            .next()
+            .ok_or_else(|| anyhow!("Missing value for {}", flag))?
+            .clone();
+
+        match (command.as_str(), flag.as_str()) {
+            ("run", "--url") => url = Some(value),
+            ("run", "--task") => task = Some(value),
+            ("run", "--assert") => assertions.push(Assertion::parse(&value)?),
+            ("run", "--suite") => suite = Some(value),
+            ("run", "--max-steps") => {
+                max_steps = Some(value.parse().context("--max-steps must be a number")?)
+            }
+            ("eval", "--scenarios") => scenarios_path = Some(value),
+            (_, "--junit") => parsed.junit = Some(value),
+            (_, "--tap") => parsed.tap = Some(value),
+            (_, "--server") => parsed.server = Some(value),
+            (_, "--token") => parsed.token = Some(value),
+            (_, "--port") => parsed.port = Some(value.parse().context("--port must be a number")?),
+            (command, other) => bail!("Unknown option '{}' for {}", other, command),
+        }
+    }
+
+    parsed.scenarios = if command == "eval" {
+        let path = scenarios_path.ok_or_else(|| anyhow!("--scenarios is required"))?;
+        load_scenarios(&path)?
+    } else {
+        vec![Scenario {
+            name: suite.unwrap_or_else(|| "interact-ui".to_string()),
+            url: url.ok_or_else(|| anyhow!("--url is required"))?,
+            task: task.ok_or_else(|| anyhow!("--task is required"))?,
+            assertions,
+            max_steps,
+        }]
+    };
+
+    Ok(parsed)
+}
+
+/// Minimal HTTP client for the server API
+struct ServerClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl ServerClient {
+    fn new(base_url: &str, token: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let builder = self.http.request(method, format!("{}{}", self.base_url, path));
+        match &self.token {
+            Some(token) => builder.bearer_auth(token),
+            None => builder,
+        }
+    }
+
+    async fn send(&self, builder: reqwest::RequestBuilder) -> Result<Value> {
+        let response = builder.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("Server returned {}: {}", status, body);
+        }
+        Ok(response.json().await?)
+    }
+
+    async fn wait_until_healthy(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let healthy = self
+                .request(reqwest::Method::GET, "/health")
+                .send()
+                .await
+                .map(|r| r.status().is_success())
+                .unwrap_or(false);
+            if healthy {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                bail!("Server at {} did not become healthy", self.base_url);
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    async fn create_session(&self, url: &str) -> Result<String> {
+        let response = self
+            .send(self.request(reqwest::Method::POST, "/sessions").json(&json!({ "initial_url": url })))
+            .await?;
+        response
+            .get("session_id")
+            .and_then(|id| id.as_str())
+            .map(|id| id.to_string())
+            .ok_or_else(|| anyhow!("Server response is missing session_id"))
+    }
+
+    async fn run_task(&self, session_id: &str, task: &str, max_steps: Option<usize>) -> Result<Value> {
+        let mut body = json!({ "task": task });
+        if let Some(max_steps) = max_steps {
+            body["max_steps"] = json!(max_steps);
+        }
+        self.send(
+            self.request(
+                reqwest::Method::POST,
+                &format!("/{}/agent/execute_multi_step", session_id),
+            )
+            .json(&body),
+        )
+        .await
+    }
+
+    async fn delete_session(&self, session_id: &str) {
+        // CI runs have nothing to restore, so skip the trash
+        let path = format!("/sessions/{}?permanent=true", session_id);
+        if let Err(e) = self.request(reqwest::Method::DELETE, &path).send().await {
+            eprintln!("warning: failed to delete session {}: {}", session_id, e);
+        }
+    }
+}
+
+/// Start the `mcp-server` binary that was built alongside this CLI
+fn spawn_embedded_server(port: u16) -> Result<Child> {
+    let exe = std::env::current_exe()?
+        .with_file_name(format!("mcp-server{}", std::env::consts::EXE_SUFFIX));
+    if !exe.exists() {
+        bail!("Embedded server binary not found at {}", exe.display());
+    }
+
+    Command::new(&exe)
+        .env("PORT", port.to_string())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start {}", exe.display()))
+}
+
+/// One line per agent step for the console and the report's system-out
+fn describe_steps(result: &Value) -> String {
+    let steps = result
+        .get("steps")
+        .and_then(|s| s.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    steps
+        .iter()
+        .map(|step| {
+            let number = step.get("step_number").and_then(|n| n.as_u64()).unwrap_or(0);
+            let action = step
+                .get("action_decided")
+                .map(|a| a.to_string())
+                .unwrap_or_default();
+            let outcome = step.get("action_result").cloned().unwrap_or_default();
+            let status = if outcome.get("success").and_then(|s| s.as_bool()).unwrap_or(false) {
+                "ok".to_string()
+            } else {
+                format!(
+                    "failed ({})",
+                    outcome.get("error").and_then(|e| e.as_str()).unwrap_or("unknown error")
+                )
+            };
+            format!("{}. {} -> {}", number, action, status)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Explain why the task failed, using the server's feedback for the last failed step
+fn failure_feedback(result: &Value) -> String {
+    let mut lines = vec![result
+        .get("error")
+        .and_then(|e| e.as_str())
+        .unwrap_or("Task was not completed")
+        .to_string()];
+
+    let last_failure = result
+        .get("steps")
+        .and_then(|s| s.as_array())
+        .and_then(|steps| {
+            steps.iter().rev().find(|step| {
+                !step
+                    .pointer("/action_result/success")
+                    .and_then(|s| s.as_bool())
+                    .unwrap_or(true)
+            })
+        });
+
+    if let Some(step) = last_failure {
+        let number = step.get("step_number").and_then(|n| n.as_u64()).unwrap_or(0);
+        let outcome = &step["action_result"];
+        let field = |key: &str| outcome.get(key).and_then(|v| v.as_str());
+
+        lines.push(format!(
+            "Step {} failed: {}",
+            number,
+            field("error").unwrap_or("unknown error")
+        ));
+        if let Some(reason) = field("reason") {
+            lines.push(format!("Reason: {}", reason));
+        }
+        if let Some(suggestion) = field("suggestion") {
+            lines.push(format!("Suggestion: {}", suggestion));
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Run one scenario in a fresh session and turn the outcome into test cases
+async fn run_scenario(client: &ServerClient, scenario: &Scenario) -> TestSuite {
+    let started = Instant::now();
+    let task_name = format!("agent task: {}", scenario.task);
+
+    let result = async {
+        let session_id = client.create_session(&scenario.url).await?;
+        eprintln!("[{}] session {} opened at {}", scenario.name, session_id, scenario.url);
+
+        let result = client
+            .run_task(&session_id, &scenario.task, scenario.max_steps)
+            .await;
+        client.delete_session(&session_id).await;
+        result
+    }
+    .await;
+
+    let elapsed = started.elapsed().as_secs_f64();
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            // Skip assertions: there is no final page to check
+            return TestSuite {
+                name: scenario.name.clone(),
+                cases: vec![TestCase::failed(task_name, elapsed, format!("Error: {:#}", e))],
+            };
+        }
+    };
+
+    let steps = describe_steps(&result);
+    let completed = result
+        .get("task_completed")
+        .and_then(|c| c.as_bool())
+        .unwrap_or(false);
+
+    let task_case = if completed {
+        TestCase::passed(task_name, elapsed)
+    } else {
+        TestCase::failed(task_name, elapsed, failure_feedback(&result))
+    };
+
+    let mut cases = vec![task_case.with_system_out(steps)];
+    let final_context = result.get("final_context").filter(|c| !c.is_null());
+    for assertion in &scenario.assertions {
+        let name = format!("assert {}", assertion.raw);
+        cases.push(match assertion.check(final_context) {
+            None => TestCase::passed(name, 0.0),
+            Some(message) => TestCase::failed(name, 0.0, message),
+        });
+    }
+
+    TestSuite {
+        name: scenario.name.clone(),
+        cases,
+    }
+}
+
+async fn run(args: CliArgs) -> Result<bool> {
+    // Keep the embedded server alive until the run is over
+    let (base_url, _server) = if args.embedded {
+        let port = args.port.unwrap_or(8089);
+        let child = spawn_embedded_server(port)?;
+        (format!("http://127.0.0.1:{}", port), Some(child))
+    } else {
+        let base_url = args
+            .server
+            .clone()
+            .or_else(|| std::env::var("INTERACT_UI_SERVER").ok())
+            .unwrap_or_else(|| "http://localhost:8080".to_string());
+        (base_url, None)
+    };
+
+    let token = args
+        .token
+        .clone()
+        .or_else(|| std::env::var("INTERACT_UI_TOKEN").ok());
+    let client = ServerClient::new(&base_url, token);
+    client.wait_until_healthy(Duration::from_secs(30)).await?;
+
+    let mut suites = Vec::new();
+    for scenario in &args.scenarios {
+        let suite = run_scenario(&client, scenario).await;
+
+        println!("{}", suite.name);
+        for case in &suite.cases {
+            if let Some(output) = case.system_out.as_deref().filter(|o| !o.is_empty()) {
+                println!("{}", output);
+            }
+            match &case.failure {
+                None => println!("  PASS  {}", case.name),
+                Some(message) => println!("  FAIL  {}: {}", case.name, message),
+            }
+        }
+        suites.push(suite);
+    }
+
+    if let Some(path) = &args.junit {
+        std::fs::write(path, render_junit(&suites))
+            .with_context(|| format!("Failed to write JUnit report to {}", path))?;
+        eprintln!("JUnit report written to {}", path);
+    }
+    if let Some(path) = &args.tap {
+        std::fs::write(path, render_tap(&suites))
+            .with_context(|| format!("Failed to write TAP report to {}", path))?;
+        eprintln!("TAP report written to {}", path);
+    }
+
+    Ok(suites.iter().all(|s| s.failures() == 0))
+}
+
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args = match parse_args(&args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    match run(args).await {
+        Ok(true) => std::process::exit(0),
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_run_args() {
+        let parsed = parse_args(&args(&[
+            "run",
+            "--url",
+            "https://staging.app",
+            "--task",
+            "Log in",
+            "--assert",
+            "url~=/done",
+            "--junit",
+            "out.xml",
+            "--embedded",
+        ]))
+        .unwrap();
+
+        assert_eq!(parsed.scenarios.len(), 1);
+        assert_eq!(parsed.scenarios[0].url, "https://staging.app");
+        assert_eq!(parsed.scenarios[0].task, "Log in");
+        assert_eq!(parsed.scenarios[0].assertions.len(), 1);
+        assert_eq!(parsed.junit.as_deref(), Some("out.xml"));
+        assert!(parsed.embedded);
+
+        assert!(parse_args(&args(&["run", "--url", "https://staging.app"])).is_err());
+        assert!(parse_args(&args(&["eval", "--url", "https://staging.app"])).is_err());
+        assert!(parse_args(&args(&["deploy"])).is_err());
+    }
+
+    #[test]
+    fn test_failure_feedback_uses_last_failed_step() {
+        let result = json!({
+            "task_completed": false,
+            "error": "Max steps reached",
+            "steps": [
+                { "step_number": 1, "action_result": { "success": false, "error": "ELEMENT_NOT_FOUND" } },
+                { "step_number": 2, "action_result": {
+                    "success": false,
+                    "error": "ELEMENT_NOT_VISIBLE",
+                    "reason": "Button is covered by a modal",
+                    "suggestion": "Close the modal first"
+                } },
+                { "step_number": 3, "action_result": { "success": true } }
+            ]
+        });
+
+        assert_eq!(
+            failure_feedback(&result),
+            "Max steps reached\nStep 2 failed: ELEMENT_NOT_VISIBLE\n\
+             Reason: Button is covered by a modal\nSuggestion: Close the modal first"
+        );
+    }
+}
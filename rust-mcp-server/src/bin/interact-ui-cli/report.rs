@@ -0,0 +1,52 @@
+/// A single reported check
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    pub name: String,
+    pub time_seconds: f64,
+    /// Failure message, `None` when the check passed
+    pub failure: Option<String>,
+    /// Extra output attached to the case (e.g. the steps the agent took)
+    pub system_out: Option<String>,
+}
+
+impl TestCase {
+    pub fn passed(name: impl Into<String>, time_seconds: f64) -> Self {
+        Self {
+            name: name.into(),
+            time_seconds,
+            failure: None,
+            system_out: None,
+        }
+    }
+
+    pub fn failed(name: impl Into<String>, time_seconds: f64, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            time_seconds,
+            failure: Some(message.into()),
+            system_out: None,
+        }
+    }
+
+    pub fn with_system_out(mut self, output: impl Into<String>) -> Self {
+        self.system_out = Some(output.into());
+        self
+    }
+}
+
+/// The checks belonging to one scenario
+#[derive(Debug, Clone)]
+pub struct TestSuite {
+    pub name: String,
+    pub cases: Vec<TestCase>,
+}
+
+impl TestSuite {
+    pub fn failures(&self) -> usize {
+        self.cases.iter().filter(|c| c.failure.is_some()).count()
+    }
+
+    pub fn time_seconds(&self) -> f64 {
+        self.cases.iter().map(|c| c.time_seconds).sum()
+    }
+}
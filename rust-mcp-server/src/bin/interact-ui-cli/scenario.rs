@@ -0,0 +1,88 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::assertions::Assertion;
+
+/// One agent task to evaluate, as written in a scenarios file
+///
+/// ```json
+/// [{ "name": "login", "url": "https://staging.app", "task": "Log in as demo",
+///    "assert": ["url~=/dashboard"], "max_steps": 10 }]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScenarioSpec {
+    pub name: String,
+    pub url: String,
+    pub task: String,
+    #[serde(default)]
+    pub assert: Vec<String>,
+    #[serde(default)]
+    pub max_steps: Option<usize>,
+}
+
+/// A scenario with its assertions parsed
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub name: String,
+    pub url: String,
+    pub task: String,
+    pub assertions: Vec<Assertion>,
+    pub max_steps: Option<usize>,
+}
+
+impl Scenario {
+    fn from_spec(spec: ScenarioSpec) -> Result<Self> {
+        let assertions = spec
+            .assert
+            .iter()
+            .map(|a| Assertion::parse(a))
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("In scenario '{}'", spec.name))?;
+
+        Ok(Self {
+            name: spec.name,
+            url: spec.url,
+            task: spec.task,
+            assertions,
+            max_steps: spec.max_steps,
+        })
+    }
+}
+
+/// Parse a JSON array of scenarios
+pub fn parse_scenarios(json: &str) -> Result<Vec<Scenario>> {
+    let specs: Vec<ScenarioSpec> = serde_json::from_str(json)?;
+    specs.into_iter().map(Scenario::from_spec).collect()
+}
+
+/// Load scenarios from a JSON file
+pub fn load_scenarios(path: &str) -> Result<Vec<Scenario>> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read scenarios from {}", path))?;
+    parse_scenarios(&json).with_context(|| format!("Invalid scenarios file {}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scenarios() {
+        let scenarios = parse_scenarios(
+            r#"[
+                { "name": "login", "url": "https://staging.app", "task": "Log in", "assert": ["url~=/home"] },
+                { "name": "search", "url": "https://staging.app", "task": "Search shoes", "max_steps": 5 }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(scenarios.len(), 2);
+        assert_eq!(scenarios[0].assertions.len(), 1);
+        assert_eq!(scenarios[1].max_steps, Some(5));
+
+        let invalid = parse_scenarios(
+            r#"[{ "name": "bad", "url": "https://staging.app", "task": "x", "assert": ["status=200"] }]"#,
+        );
+        assert!(format!("{:#}", invalid.unwrap_err()).contains("In scenario 'bad'"));
+    }
+}
@@ -0,0 +1,63 @@
+use super::report::TestSuite;
+
+/// Render a TAP version 13 report, one test point per check
+///
+/// Points are named `<scenario> - <check>`; failures carry a YAML block with the message.
+pub fn render_tap(suites: &[TestSuite]) -> String {
+    let total: usize = suites.iter().map(|s| s.cases.len()).sum();
+    let mut tap = format!("TAP version 13\n1..{}\n", total);
+    let mut number = 0;
+
+    for suite in suites {
+        tap.push_str(&format!("# {}\n", suite.name));
+
+        for case in &suite.cases {
+            number += 1;
+            // '#' starts a directive in TAP, so keep it out of descriptions
+            let description = format!("{} - {}", suite.name, case.name).replace('#', "\\#");
+
+            match &case.failure {
+                None => tap.push_str(&format!("ok {} {}\n", number, description)),
+                Some(message) => {
+                    tap.push_str(&format!("not ok {} {}\n", number, description));
+                    tap.push_str("  ---\n  message: |\n");
+                    for line in message.lines() {
+                        tap.push_str(&format!("    {}\n", line));
+                    }
+                    tap.push_str(&format!("  duration_ms: {:.0}\n", case.time_seconds * 1000.0));
+                    tap.push_str("  ...\n");
+                }
+            }
+        }
+    }
+
+    tap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::TestCase;
+
+    #[test]
+    fn test_render_tap() {
+        let suites = vec![TestSuite {
+            name: "login".to_string(),
+            cases: vec![
+                TestCase::passed("agent task: log in", 1.0),
+                TestCase::failed(
+                    "assert url~=/done",
+                    0.5,
+                    "Task was not completed\nSuggestion: Try scrolling #2",
+                ),
+            ],
+        }];
+
+        let tap = render_tap(&suites);
+        assert!(tap.starts_with("TAP version 13\n1..2\n# login\n"));
+        assert!(tap.contains("ok 1 login - agent task: log in\n"));
+        assert!(tap.contains("not ok 2 login - assert url~=/done\n"));
+        assert!(tap.contains("    Suggestion: Try scrolling #2\n"));
+        assert!(tap.contains("  duration_ms: 500\n"));
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use chromiumoxide::cdp::browser_protocol::emulation::{MediaFeature, SetEmulatedMediaParams};
+use chromiumoxide::page::Page;
+
+use crate::models::{AssistiveSetting, ElementLayout, INTERACTIVE_ROLES};
+
+use super::COLLECT_ELEMENTS_JS;
+
+/// Text size multiplier for `AssistiveSetting::TextZoom`
+pub const TEXT_ZOOM_FACTOR: f64 = 2.0;
+
+/// Switches assistive display settings on a page and measures the interactive elements
+pub struct AssistiveEmulation;
+
+impl AssistiveEmulation {
+    /// Turn a setting on or off for the current document
+    pub async fn set(page: &Page, setting: AssistiveSetting, enabled: bool) -> Result<()> {
+        match setting {
+            AssistiveSetting::HighContrast => {
+                // An empty value clears the override
+                let (forced_colors, contrast) = if enabled { ("active", "more") } else { ("", "") };
+                page.execute(SetEmulatedMediaParams {
+                    media: None,
+                    features: Some(vec![
+                        MediaFeature::new("forced-colors", forced_colors),
+                        MediaFeature::new("prefers-contrast", contrast),
+                    ]),
+                })
+                .await?;
+            }
+            AssistiveSetting::TextZoom => {
+                page.evaluate(Self::text_zoom_script(enabled)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Scale every element's computed font size, keeping the inline values to restore
+    ///
+    /// Browser zoom would scale the whole layout; text zoom only enlarges text, which
+    /// is what breaks fixed-size boxes.
+    fn text_zoom_script(enabled: bool) -> String {
+        format!(
+            r#"
+            (() => {{
+                const key = Symbol.for('mcp-text-zoom');
+                if ({enabled}) {{
+                    if (window[key]) return;
+                    const elements = Array.from(document.querySelectorAll('body, body *'));
+                    // Read every size first: scaling a parent changes its children's computed size
+                    const sizes = elements.map(el => parseFloat(getComputedStyle(el).fontSize));
+                    window[key] = elements.map((el, i) => {{
+                        const saved = [el, el.style.getPropertyValue('font-size'), el.style.getPropertyPriority('font-size')];
+                        if (sizes[i]) el.style.setProperty('font-size', `${{sizes[i] * {factor}}}px`, 'important');
+                        return saved;
+                    }});
+                }} else {{
+                    (window[key] || []).forEach(([el, value, priority]) => {{
+                        if (value) el.style.setProperty('font-size', value, priority);
+                        else el.style.removeProperty('font-size');
+                    }});
+                    delete window[key];
+                }}
+            }})()
+            "#,
+            enabled = enabled,
+            factor = TEXT_ZOOM_FACTOR,
+        )
+    }
+
+    /// Layout of the interactive context elements, with context IDs
+    pub async fn snapshot(page: &Page) -> Result<Vec<ElementLayout>> {
+        let script = format!(
+            r#"
+            (() => {{
+                {}
+
+                const interactive = {};
+                return collectElements()
+                    .map(({{ el, role, name }}, id) => ({{ el, role, name, id }}))
+                    .filter(({{ role }}) => interactive.includes(role))
+                    .map(({{ el, role, name, id }}) => ({{
+                        id,
+                        role,
+                        name,
+                        visible: isVisible(el),
+                        rect: getRect(el),
+                    }}));
+            }})()
+            "#,
+            COLLECT_ELEMENTS_JS,
+            serde_json::to_string(INTERACTIVE_ROLES)?,
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(serde_json::from_value(result.into_value()?)?)
+    }
+}
@@ -1,24 +1,106 @@
 use anyhow::Result;
 use chromiumoxide::browser::{Browser, BrowserConfig};
-use chromiumoxide::cdp::browser_protocol::page::Viewport;
+use base64::Engine;
+use chromiumoxide::cdp::browser_protocol::page::{
+    CaptureScreenshotFormat, GetNavigationHistoryParams, NavigateToHistoryEntryParams,
+    RemoveScriptToEvaluateOnNewDocumentParams, ScriptIdentifier, StopLoadingParams, Viewport,
+};
+use chromiumoxide::cdp::js_protocol::runtime::EvaluateParams;
 use chromiumoxide::element::Element;
-use chromiumoxide::page::Page;
+use chromiumoxide::page::{Page, ScreenshotParams};
+use chrono::{DateTime, Utc};
 use futures::StreamExt;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use ui_core::selector::{css_selector, find_element_script, ELEMENT_ID_ATTRIBUTE};
+use ui_core::suggest;
 
-use crate::models::{ActionRequest, ActionResponse, ScrollDirection, SemanticSelector};
+use crate::credentials::CredentialVault;
+use crate::hooks::{HookRegistry, TestHook};
+use crate::otp::OtpProviders;
+use crate::recorder::Recording;
+use crate::models::{
+    AXElement, ActionRequest, ActionResponse, Assertion, AssistiveCheck, AssistiveSetting, ClosedOverlay, DialogPolicy, DialogRecord, DownloadArtifact, ElementNeighborhood, ErrorCode,
+    ElementSubscription, ExtractedTable, KeyModifier, PageCandidates, PageContent, PageForm,
+    NavigationEntry, ScreenshotFormat, ScrollDirection, SemanticSelector, SessionEventKind, StorageState, UIContext,
+    PlaywrightStorageState, TabInfo, WebStorageArea, WebStorageContents, text_assertion_holds,
+};
+
+use super::form_fill::FILL_ATTRIBUTE;
+use super::login::LOGIN_ATTRIBUTE;
+use super::waits::{self, NETWORK_IDLE_MS};
+use super::{
+    check_state, choose_option, client_certificate_feedback, filename_matches, is_client_certificate_error, AssistiveEmulation,
+    forget_overlays_on_navigation, forward_console, has_focus, start_context_push, track_navigation, value_matches, AutomationMarker, ConsentDismissal,
+    ConsentDismisser, ConsentPolicy, ConsoleLog, ContextExtractor, ContextPushConfig, DialogHandler, DownloadTracker, DownloadWait,
+    ElementInspector, ElementMap, ElementWatcher, EventLog, FieldFillResult, FieldKind, FieldValue, Fingerprint, FocusTrail, FormFiller, FormStateExtractor,
+    HttpAuthHandler, NetworkRules,
+    InteractionHeatmap,
+    KeyChord, LocalRetryPolicy, LoginHelper,
+    NavigationHistory, OverlayCloser, TabSetup, TabTracker, PageHygiene, ReadabilityExtractor, RequestRouting, Scraper, SelectOutcome, SessionPolicy, StorageStateManager, TableExtractor,
+    TableTarget, NAMED_KEYS,
+};
 
 /// Browser automation handler using Chromiumoxide
 pub struct BrowserAutomation {
-    #[allow(dead_code)] // Kept for future direct browser control
     browser: Arc<Browser>,
     page: Arc<RwLock<Page>>,
+    history: Arc<RwLock<NavigationHistory>>,
+    events: Arc<EventLog>,
+    console: Arc<ConsoleLog>,
+    closed_overlays: Arc<RwLock<Vec<ClosedOverlay>>>,
+    /// Script re-applying the zoom factor on every new document
+    zoom_script: RwLock<Option<ScriptIdentifier>>,
+    watcher: ElementWatcher,
+    /// Download tracking, if the browser accepted a download directory
+    downloads: Option<DownloadTracker>,
+    /// Answers JavaScript dialogs, unless dialog events could not be subscribed to
+    dialogs: Option<DialogHandler>,
+    /// HTTP authentication answers and unanswered challenges, if request interception is on
+    http_auth: Option<HttpAuthHandler>,
+    /// Block, rewrite and stub rules for the page's requests
+    network_rules: Arc<NetworkRules>,
+    /// Credentials available to the `login` action
+    credentials: Arc<CredentialVault>,
+    /// One-time code sources available to the `get_otp` action
+    otp: Arc<OtpProviders>,
+    /// App test hooks available to the `invoke_hook` action
+    hooks: Arc<HookRegistry>,
+    /// Removes attributes stamped on elements during actions
+    hygiene: Arc<PageHygiene>,
+    /// Which elements actions target and how often they fail, per page
+    heatmap: Arc<InteractionHeatmap>,
+    /// How the session's pages are marked as automated
+    marker: AutomationMarker,
+    /// Mouse actions are rejected; the page must be operated with the keyboard
+    keyboard_only: bool,
+    /// Origins, requests and actions the session's preset allows
+    policy: Arc<SessionPolicy>,
+    /// Elements reached by consecutive Tab presses
+    focus_trail: RwLock<FocusTrail>,
+    /// Re-runs of element actions after transient failures
+    local_retry: LocalRetryPolicy,
+    /// Elements of the latest context, for `element_id` targets
+    element_map: RwLock<ElementMap>,
+    /// Open tabs; `page` holds the active one's page
+    tabs: TabTracker,
+    /// Macro being recorded from the actions this session executes
+    recording: std::sync::Mutex<Option<Recording>>,
 }
 
 impl BrowserAutomation {
     /// Create new browser automation instance
-    pub async fn new(initial_url: &str, viewport_width: u32, viewport_height: u32) -> Result<Self> {
+    pub async fn new(
+        initial_url: &str,
+        viewport_width: u32,
+        viewport_height: u32,
+        marker: &AutomationMarker,
+        routing: RequestRouting,
+        policy: SessionPolicy,
+        fingerprint: Option<&Fingerprint>,
+    ) -> Result<Self> {
         // Configure browser
         let (browser, mut handler) = Browser::launch(
             BrowserConfig::builder()
@@ -43,55 +125,595 @@ impl BrowserAutomation {
         // Create new page
         let page = browser.new_page("about:blank").await?;
 
+        if let Some(fingerprint) = fingerprint {
+            if let Err(e) = fingerprint.apply(&page).await {
+                tracing::warn!("Failed to apply the browser fingerprint: {}", e);
+            }
+        }
+        let screen = fingerprint.and_then(|fingerprint| fingerprint.screen);
+
         // Set viewport using emulation
-        let _ = page
-            .execute(chromiumoxide::cdp::browser_protocol::emulation::SetDeviceMetricsOverrideParams {
-                width: viewport_width as i64,
-                height: viewport_height as i64,
-                device_scale_factor: 1.0,
-                mobile: false,
-                scale: None,
-                screen_width: None,
-                screen_height: None,
-                position_x: None,
-                position_y: None,
-                dont_set_visible_size: None,
-                screen_orientation: None,
-                viewport: Some(Viewport {
-                    x: 0.0,
-                    y: 0.0,
-                    scale: 1.0,
-                    width: viewport_width as f64,
-                    height: viewport_height as f64,
-                }),
-                display_feature: None,
-            })
-            .await;
+        let device_metrics = chromiumoxide::cdp::browser_protocol::emulation::SetDeviceMetricsOverrideParams {
+            width: viewport_width as i64,
+            height: viewport_height as i64,
+            device_scale_factor: fingerprint.map_or(1.0, |fingerprint| fingerprint.device_scale_factor),
+            mobile: false,
+            scale: None,
+            screen_width: screen.map(|screen| screen.width as i64),
+            screen_height: screen.map(|screen| screen.height as i64),
+            position_x: None,
+            position_y: None,
+            dont_set_visible_size: None,
+            screen_orientation: None,
+            viewport: Some(Viewport {
+                x: 0.0,
+                y: 0.0,
+                scale: 1.0,
+                width: viewport_width as f64,
+                height: viewport_height as f64,
+            }),
+            display_feature: None,
+        };
+        let _ = page.execute(device_metrics.clone()).await;
+
+        // Record navigation breadcrumb from CDP events
+        let history = Arc::new(RwLock::new(NavigationHistory::from_env()));
+        let events = Arc::new(EventLog::from_env());
+        if let Err(e) = track_navigation(page.clone(), Arc::clone(&history), Arc::clone(&events)).await {
+            tracing::warn!("Failed to subscribe to navigation events: {}", e);
+        }
+        let closed_overlays = Arc::new(RwLock::new(Vec::new()));
+        if let Err(e) = forget_overlays_on_navigation(&page, Arc::clone(&closed_overlays)).await {
+            tracing::warn!("Failed to subscribe to navigation events for closed overlays: {}", e);
+        }
 
-        // Navigate to initial URL
-        page.goto(initial_url).await?;
+        let console = Arc::new(ConsoleLog::from_env());
+        if let Err(e) = forward_console(&page, Arc::clone(&events), Arc::clone(&console)).await {
+            tracing::warn!("Failed to subscribe to console messages: {}", e);
+        }
 
-        // Wait for page to load
-        page.wait_for_navigation().await?;
+        // Element subscriptions report through a binding that must exist before navigation
+        let watcher = ElementWatcher::start(page.clone(), Arc::clone(&events)).await?;
+
+        let downloads = match DownloadTracker::start(&page, Arc::clone(&events)).await {
+            Ok(tracker) => Some(tracker),
+            Err(e) => {
+                tracing::warn!("Failed to set up download tracking: {}", e);
+                None
+            }
+        };
+
+        if let Err(e) = marker.install(&page).await {
+            tracing::warn!("Failed to mark the session as automated: {}", e);
+        }
+
+        let dialogs = match DialogHandler::start(&page, Arc::clone(&events), policy.dialogs.clone()).await {
+            Ok(handler) => Some(handler),
+            Err(e) => {
+                tracing::warn!("Failed to set up dialog handling: {}", e);
+                None
+            }
+        };
+
+        let policy = Arc::new(policy);
+        let network_rules = Arc::new(NetworkRules::default());
+        let http_auth = match HttpAuthHandler::start(&page, routing, Arc::clone(&policy), Arc::clone(&network_rules)).await {
+            Ok(handler) => Some(handler),
+            Err(e) => {
+                tracing::warn!("Failed to set up HTTP authentication handling: {}", e);
+                None
+            }
+        };
+
+        // Navigate to initial URL and wait for it to load; a login prompt leaves the session on its 401 page
+        let open = async {
+            page.goto(initial_url).await?;
+            page.wait_for_navigation().await?;
+            anyhow::Ok(())
+        };
+        match &http_auth {
+            Some(auth) => tokio::select! {
+                opened = open => opened?,
+                challenge = auth.challenged() => {
+                    tracing::warn!("Session opened without login: {}", challenge.feedback().reason.unwrap_or_default());
+                    let _ = page.execute(StopLoadingParams::default()).await;
+                }
+            },
+            None => open.await?,
+        }
 
         let page = Arc::new(RwLock::new(page));
+        let tab_setup = TabSetup {
+            device_metrics,
+            fingerprint: fingerprint.cloned(),
+            marker: marker.clone(),
+            events: Arc::clone(&events),
+            console: Arc::clone(&console),
+            history: Arc::clone(&history),
+            closed_overlays: Arc::clone(&closed_overlays),
+            dialogs: dialogs.clone(),
+            http_auth: http_auth.clone(),
+        };
+        let tabs = TabTracker::start(Arc::clone(&browser), Arc::clone(&page), tab_setup).await?;
+
+        let settle = policy.settle.clone();
+        if let Err(e) = start_context_push(Arc::clone(&page), Arc::clone(&events), ContextPushConfig::from_env(), settle).await {
+            tracing::warn!("Failed to start automatic context push: {}", e);
+        }
+
+        Ok(Self {
+            browser,
+            page,
+            history,
+            events,
+            console,
+            closed_overlays,
+            zoom_script: RwLock::new(None),
+            watcher,
+            downloads,
+            dialogs,
+            http_auth,
+            network_rules,
+            credentials: Arc::new(CredentialVault::default()),
+            otp: Arc::new(OtpProviders::default()),
+            hooks: Arc::new(HookRegistry::default()),
+            hygiene: Arc::new(PageHygiene::default()),
+            heatmap: Arc::new(InteractionHeatmap::default()),
+            marker: marker.clone(),
+            keyboard_only: false,
+            policy,
+            focus_trail: RwLock::new(FocusTrail::default()),
+            local_retry: LocalRetryPolicy::from_env(),
+            element_map: RwLock::new(ElementMap::default()),
+            tabs,
+            recording: std::sync::Mutex::new(None),
+        })
+    }
 
-        Ok(Self { browser, page })
+    /// Builder method to make vault credentials available to the `login` action
+    pub fn with_credentials(mut self, credentials: Arc<CredentialVault>) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Builder method to make OTP providers available to the `get_otp` action
+    pub fn with_otp_providers(mut self, otp: Arc<OtpProviders>) -> Self {
+        self.otp = otp;
+        self
+    }
+
+    /// Builder method to make test hooks available to the `invoke_hook` action
+    pub fn with_hooks(mut self, hooks: Arc<HookRegistry>) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Builder method to count page cleanups in server-wide metrics
+    pub fn with_page_hygiene(mut self, hygiene: Arc<PageHygiene>) -> Self {
+        self.hygiene = hygiene;
+        self
+    }
+
+    /// Builder method to count element interactions in the server-wide heatmap
+    pub fn with_interaction_heatmap(mut self, heatmap: Arc<InteractionHeatmap>) -> Self {
+        self.heatmap = heatmap;
+        self
+    }
+
+    /// Builder method to restrict the session to keyboard interaction (accessibility testing)
+    pub fn with_keyboard_only(mut self, enabled: bool) -> Self {
+        self.keyboard_only = enabled;
+        self
+    }
+
+    pub fn keyboard_only(&self) -> bool {
+        self.keyboard_only
+    }
+
+    /// Builder method to answer JavaScript dialogs by another policy than the preset's
+    pub fn with_dialog_policy(self, policy: DialogPolicy) -> Self {
+        if let Some(dialogs) = &self.dialogs {
+            dialogs.set_policy(policy);
+        }
+        self
     }
 
     /// Execute an action request (Solution B: Semantic Selectors)
     pub async fn execute_action(&self, action: &ActionRequest) -> Result<ActionResponse> {
+        let action = &self.element_map.read().await.complete(action);
+
+        // Waiting and checking aren't interactions, so they stay out of the heatmap
+        let target = match action {
+            ActionRequest::WaitForElement { .. } | ActionRequest::Assert { .. } => None,
+            _ => action.selector(),
+        };
+        let url = match target {
+            Some(_) => self.get_url().await.ok(),
+            None => None,
+        };
+
+        let dialog_mark = self.dialogs.as_ref().map(DialogHandler::mark);
+        let tab_mark = self.tabs.mark();
+        let mut response = self.perform_action_retrying(action).await;
+        self.hygiene.after_action(&*self.page.read().await).await;
+
+        if let Ok(result) = &mut response {
+            // Tabs the page opened; a new_tab action reports its own
+            let opened = self.tabs.since(tab_mark).await;
+            if opened.iter().any(|tab| tab.popup) {
+                // The popup is the active tab now; element IDs were the opener's
+                self.forget_elements().await;
+            }
+            if !opened.is_empty() && !matches!(action, ActionRequest::NewTab { .. }) {
+                let details = result.details.get_or_insert_with(|| serde_json::json!({}));
+                if let Some(details) = details.as_object_mut() {
+                    details.insert("opened_tabs".to_string(), serde_json::json!(opened));
+                }
+            }
+        }
+
+        if let (Ok(result), Some(dialogs), Some(mark)) = (&mut response, &self.dialogs, dialog_mark) {
+            let opened = dialogs.since(mark);
+            if !opened.is_empty() {
+                Self::report_dialogs(result, opened);
+            }
+        }
+
+        if let (Some(selector), Some(url)) = (target, &url) {
+            let error = match &response {
+                Ok(result) if result.success => None,
+                Ok(result) => Some(result.error.map(ErrorCode::name).unwrap_or("failed")),
+                Err(_) => Some("error"),
+            };
+            self.heatmap.record(url, selector, error);
+        }
+
+        if let Ok(result) = &response {
+            if let Some(recording) = self.recording.lock().unwrap().as_mut() {
+                recording.record(action, result);
+            }
+            if self.events.has_subscribers() {
+                self.events.broadcast(SessionEventKind::action_executed(action, result));
+            }
+        }
+        response
+    }
+
+    /// Perform an action, trying element actions again while they fail for transient reasons
+    async fn perform_action_retrying(&self, action: &ActionRequest) -> Result<ActionResponse> {
+        let mut retries = 0;
+        loop {
+            let response = self.perform_action_answering_auth(action).await;
+            if retries >= self.local_retry.max_retries || !LocalRetryPolicy::should_retry(action, &response) {
+                return response.map(|mut result| {
+                    result.local_retries = (retries > 0).then_some(retries);
+                    result
+                });
+            }
+
+            retries += 1;
+            tracing::debug!("Retrying {} locally ({}/{})", action.tool(), retries, self.local_retry.max_retries);
+            tokio::time::sleep(self.local_retry.delay).await;
+        }
+    }
+
+    /// Perform an action, reporting HTTP authentication the page ran into instead of hanging on it
+    async fn perform_action_answering_auth(&self, action: &ActionRequest) -> Result<ActionResponse> {
+        let Some(auth) = &self.http_auth else {
+            return self.perform_action(action).await;
+        };
+
+        auth.clear();
+        let response = tokio::select! {
+            response = self.perform_action(action) => response,
+            challenge = auth.challenged() => {
+                let _ = self.page.read().await.execute(StopLoadingParams::default()).await;
+                return Ok(challenge.feedback());
+            }
+        };
+
+        match (response, auth.challenge()) {
+            (Ok(result), Some(challenge)) if result.success => Ok(challenge.feedback()),
+            (response, _) => response,
+        }
+    }
+
+    async fn perform_action(&self, action: &ActionRequest) -> Result<ActionResponse> {
+        if self.keyboard_only && action.uses_pointer() {
+            return Ok(Self::pointer_disabled());
+        }
+        if let Some(blocked) = self.policy.check_action(action) {
+            return Ok(blocked);
+        }
+
         match action {
             ActionRequest::Click { selector } => self.click(selector).await,
             ActionRequest::Type { selector, text } => self.type_text(selector, text).await,
-            ActionRequest::Scroll { direction, amount } => {
-                self.scroll(direction, amount.unwrap_or(300)).await
+            ActionRequest::SelectOption { selector, value, label } => {
+                self.select_option(selector, value.as_deref(), label.as_deref()).await
             }
+            ActionRequest::SetChecked { selector, checked } => self.set_checked(selector, *checked).await,
+            ActionRequest::Focus { selector } => self.focus(selector).await,
+            ActionRequest::PressKey { key, modifiers } => self.press_key(key, modifiers).await,
+            ActionRequest::Scroll { direction, amount, selector } => match selector {
+                Some(selector) => self.scroll_container(selector, direction, amount.unwrap_or(300)).await,
+                None => self.scroll(direction, amount.unwrap_or(300)).await,
+            },
             ActionRequest::WaitForElement { selector, timeout_ms } => {
                 self.wait_for_element(selector, *timeout_ms).await
             }
+            ActionRequest::WaitForNavigation { timeout_ms } => {
+                self.wait_for_navigation(timeout_ms.unwrap_or(10000)).await
+            }
+            ActionRequest::WaitForNetworkIdle { timeout_ms } => {
+                self.wait_for_network_idle(timeout_ms.unwrap_or(10000)).await
+            }
+            ActionRequest::WaitForUrl { pattern, timeout_ms } => {
+                self.wait_for_url(pattern, timeout_ms.unwrap_or(5000)).await
+            }
+            ActionRequest::WaitForText { text, timeout_ms } => {
+                self.wait_for_text(text, timeout_ms.unwrap_or(5000)).await
+            }
             ActionRequest::Navigate { url } => self.navigate(url).await,
+            ActionRequest::GoBack => self.go_through_history(-1).await,
+            ActionRequest::GoForward => self.go_through_history(1).await,
+            ActionRequest::Reload => self.reload().await,
+            ActionRequest::NewTab { url } => self.new_tab(url.as_deref()).await,
+            ActionRequest::SwitchTab { tab_id } => self.switch_tab(*tab_id).await,
+            ActionRequest::CloseTab { tab_id } => self.close_tab(*tab_id).await,
+            ActionRequest::FindText { query, scroll_to_first } => {
+                self.find_text(query, scroll_to_first.unwrap_or(true)).await
+            }
+            ActionRequest::SetZoom { factor } => self.set_zoom(*factor).await,
+            ActionRequest::RestoreOverlay { overlay_id } => self.restore_overlay(*overlay_id).await,
+            ActionRequest::WaitForDownload { filename_pattern, timeout_ms } => {
+                self.wait_for_download(filename_pattern.as_deref(), timeout_ms.unwrap_or(30000)).await
+            }
+            ActionRequest::FillForm { fields, submit } => self.fill_form(fields, *submit).await,
+            ActionRequest::Login { credential_name } => self.login(credential_name).await,
+            ActionRequest::GetOtp { provider, timeout_ms, fill } => {
+                self.get_otp(provider, timeout_ms.unwrap_or(60000), fill.unwrap_or(true)).await
+            }
+            ActionRequest::InvokeHook { name, args } => self.invoke_hook(name, args).await,
+            ActionRequest::HandleDialog { accept, prompt_text } => Ok(self.handle_dialog(*accept, prompt_text.clone())),
+            ActionRequest::GetStorage { area, key } => self.get_storage(*area, key.as_deref()).await,
+            ActionRequest::SetStorage { area, key, value } => self.set_storage(*area, key, value.clone()).await,
+            ActionRequest::Evaluate { script } => self.evaluate_action(script).await,
+            ActionRequest::Assert { assertion } => self.check_assertion(assertion).await,
+            ActionRequest::Screenshot { full_page, format, quality } => {
+                self.take_screenshot(*format, *full_page, *quality).await
+            }
+        }
+    }
+
+    /// Check an action against the current page without executing it
+    ///
+    /// Returns the same feedback `execute_action` would give for a missing,
+    /// hidden or disabled target, or success with `"dry_run": true`.
+    pub async fn validate_action(&self, action: &ActionRequest) -> Result<ActionResponse> {
+        let action = &self.element_map.read().await.complete(action);
+        let response = self.dry_run_action(action).await;
+        // Lookups stamp elements just like real actions
+        self.hygiene.after_action(&*self.page.read().await).await;
+        response
+    }
+
+    async fn dry_run_action(&self, action: &ActionRequest) -> Result<ActionResponse> {
+        if self.keyboard_only && action.uses_pointer() {
+            return Ok(Self::pointer_disabled());
         }
+        if let Some(blocked) = self.policy.check_action(action) {
+            return Ok(blocked);
+        }
+
+        let feedback = match action {
+            ActionRequest::SelectOption { value: None, label: None, .. } => Some(Self::missing_option()),
+            ActionRequest::Click { selector }
+            | ActionRequest::Type { selector, .. }
+            | ActionRequest::SelectOption { selector, .. }
+            | ActionRequest::SetChecked { selector, .. }
+            | ActionRequest::Focus { selector }
+            | ActionRequest::Scroll { selector: Some(selector), .. } => {
+                let page = self.page.read().await;
+                match self.find_element(&page, selector).await {
+                    Ok(Some(element)) => match Self::check_interactable(&element, selector).await? {
+                        None if self.keyboard_only
+                            && matches!(action, ActionRequest::Type { .. })
+                            && !has_focus(&element).await? =>
+                        {
+                            Some(Self::not_focused(selector))
+                        }
+                        feedback => feedback,
+                    },
+                    Ok(None) => Some(Self::element_not_found(&page, action.tool(), selector).await),
+                    Err(e) => Some(ActionResponse::error_with_suggestion(
+                        ErrorCode::ExecutionError,
+                        &format!("Failed to look up element: {}", e),
+                        "try get_context() to verify element exists",
+                    )),
+                }
+            }
+            ActionRequest::WaitForElement { selector, .. } => {
+                // Absence is fine for a wait; report whether it would return immediately
+                let page = self.page.read().await;
+                let present = matches!(self.find_element(&page, selector).await, Ok(Some(_)));
+                return Ok(ActionResponse::success_with_details(serde_json::json!({
+                    "dry_run": true,
+                    "element_present": present,
+                })));
+            }
+            // Checking changes nothing, so the dry run is the check itself
+            ActionRequest::Assert { assertion } => return self.check_assertion(assertion).await,
+            ActionRequest::WaitForUrl { pattern, .. } => {
+                let page = self.page.read().await;
+                let matches = waits::wait_for_url(&page, pattern, tokio::time::Duration::ZERO).await?.is_some();
+                return Ok(ActionResponse::success_with_details(serde_json::json!({
+                    "dry_run": true,
+                    "url_matches": matches,
+                })));
+            }
+            ActionRequest::WaitForText { text, .. } => {
+                let page = self.page.read().await;
+                let present = waits::wait_for_text(&page, text, tokio::time::Duration::ZERO).await?;
+                return Ok(ActionResponse::success_with_details(serde_json::json!({
+                    "dry_run": true,
+                    "text_present": present,
+                })));
+            }
+            ActionRequest::Navigate { url } | ActionRequest::NewTab { url: Some(url) } => Self::invalid_url(url),
+            ActionRequest::SwitchTab { tab_id } => {
+                (!self.tabs.ids().contains(tab_id)).then(|| self.unknown_tab(*tab_id))
+            }
+            ActionRequest::CloseTab { tab_id } => match tab_id {
+                Some(tab_id) if !self.tabs.ids().contains(tab_id) => Some(self.unknown_tab(*tab_id)),
+                _ => (self.tabs.count() < 2).then(Self::last_tab),
+            },
+            ActionRequest::GoBack => self.history_entry(-1).await?.is_none().then(|| Self::no_history(-1)),
+            ActionRequest::GoForward => self.history_entry(1).await?.is_none().then(|| Self::no_history(1)),
+            ActionRequest::FindText { query, .. } => Self::invalid_query(query),
+            ActionRequest::SetZoom { factor } => Self::invalid_zoom(*factor),
+            ActionRequest::RestoreOverlay { overlay_id } => {
+                let closed_overlays = self.closed_overlays.read().await;
+                (!closed_overlays.iter().any(|o| o.id == *overlay_id))
+                    .then(|| Self::unknown_overlay(*overlay_id))
+            }
+            ActionRequest::WaitForDownload { .. } => {
+                self.downloads.is_none().then(Self::downloads_unavailable)
+            }
+            ActionRequest::HandleDialog { .. } => self.dialogs.is_none().then(Self::dialogs_unavailable),
+            ActionRequest::FillForm { fields, .. } => {
+                if fields.is_empty() {
+                    Some(Self::empty_form())
+                } else {
+                    // Report which keys would not find a field
+                    let page = self.page.read().await;
+                    let (_, unmatched) = FormFiller::resolve(&page, fields).await?;
+                    return Ok(ActionResponse::success_with_details(serde_json::json!({
+                        "dry_run": true,
+                        "unmatched_fields": unmatched,
+                    })));
+                }
+            }
+            ActionRequest::Login { credential_name } => match self.credentials.get(credential_name) {
+                None => Some(self.unknown_credential(credential_name)),
+                Some(_) => {
+                    let page = self.page.read().await;
+                    let fields = LoginHelper::locate(&page).await?;
+                    (!fields.username && !fields.password).then(Self::login_fields_not_found)
+                }
+            },
+            ActionRequest::GetOtp { provider, .. } => {
+                self.otp.get(provider).is_none().then(|| self.unknown_otp_provider(provider))
+            }
+            ActionRequest::InvokeHook { name, .. } => self.resolve_hook(name).await?.err(),
+            ActionRequest::Screenshot { format, quality, .. } => Self::invalid_quality(*format, *quality),
+            ActionRequest::PressKey { key, modifiers } => {
+                KeyChord::parse(key, modifiers).is_none().then(|| Self::unsupported_key(key))
+            }
+            ActionRequest::Scroll { selector: None, .. }
+            | ActionRequest::Reload
+            | ActionRequest::NewTab { url: None }
+            | ActionRequest::WaitForNavigation { .. }
+            | ActionRequest::WaitForNetworkIdle { .. }
+            | ActionRequest::GetStorage { .. }
+            | ActionRequest::SetStorage { .. }
+            | ActionRequest::Evaluate { .. } => None,
+        };
+
+        Ok(feedback.unwrap_or_else(|| {
+            ActionResponse::success_with_details(serde_json::json!({ "dry_run": true }))
+        }))
+    }
+
+    fn downloads_unavailable() -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::DownloadsUnavailable,
+            "Download tracking is not available in this session",
+            "create a new session; check the server logs for the download setup error",
+        )
+    }
+
+    fn dialogs_unavailable() -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::DialogsUnavailable,
+            "Dialog handling is not available in this session",
+            "create a new session; check the server logs for the dialog setup error",
+        )
+    }
+
+    fn invalid_query(query: &str) -> Option<ActionResponse> {
+        query.trim().is_empty().then(|| {
+            ActionResponse::error_with_suggestion(
+                ErrorCode::InvalidQuery,
+                "Search query is empty",
+                "provide the text to search for in 'query'",
+            )
+        })
+    }
+
+    fn invalid_zoom(factor: f64) -> Option<ActionResponse> {
+        (!(0.25..=3.0).contains(&factor)).then(|| {
+            ActionResponse::error_with_suggestion(
+                ErrorCode::InvalidZoom,
+                &format!("Zoom factor {} is out of range", factor),
+                "use a factor between 0.25 and 3.0 (1.0 resets the zoom)",
+            )
+        })
+    }
+
+    fn missing_option() -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::InvalidOption,
+            "No option given",
+            "set 'label' to the option's visible text (or 'value' to its value)",
+        )
+    }
+
+    fn pointer_disabled() -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::KeyboardOnly,
+            "Mouse actions are disabled in this keyboard-only session",
+            "move focus with press_key Tab / Shift+Tab and activate the focused element with Enter or Space",
+        )
+    }
+
+    fn not_focused(selector: &SemanticSelector) -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::NotFocused,
+            &format!(
+                "{} '{}' does not have keyboard focus",
+                selector.role,
+                selector.name.as_deref().unwrap_or("unknown")
+            ),
+            "press Tab or Shift+Tab until the field is the focused element, then type",
+        )
+    }
+
+    fn unsupported_key(key: &str) -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::UnsupportedKey,
+            &format!("'{}' is not a supported key", key),
+            &format!(
+                "use a single character or one of {}, optionally with modifiers (e.g. Shift+Tab, Control+a)",
+                NAMED_KEYS.join(", ")
+            ),
+        )
+    }
+
+    fn unknown_overlay(overlay_id: u32) -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::OverlayNotFound,
+            &format!("No closed overlay with id {}", overlay_id),
+            "check closed_overlays in get_context() for valid ids",
+        )
+    }
+
+    fn no_history(offset: i64) -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::NoHistory,
+            if offset < 0 { "There is no previous page in this tab" } else { "There is no next page in this tab" },
+            "use navigate with the URL of the page you want",
+        )
     }
 
     /// Click an element using semantic selector
@@ -101,21 +723,8 @@ impl BrowserAutomation {
         // Find element using semantic selector
         match self.find_element(&page, selector).await {
             Ok(Some(element)) => {
-                // Check if element is visible
-                let is_visible = self.is_element_visible(&page, &element).await?;
-                if !is_visible {
-                    return Ok(ActionResponse::element_not_visible(
-                        selector.name.as_deref().unwrap_or("unknown"),
-                        &selector.role,
-                    ));
-                }
-
-                // Check if element is enabled
-                let is_enabled = self.is_element_enabled(&page, &element).await?;
-                if !is_enabled {
-                    return Ok(ActionResponse::element_not_enabled(
-                        selector.name.as_deref().unwrap_or("unknown"),
-                    ));
+                if let Some(feedback) = Self::check_interactable(&element, selector).await? {
+                    return Ok(feedback);
                 }
 
                 // Perform click
@@ -126,9 +735,9 @@ impl BrowserAutomation {
 
                 Ok(ActionResponse::success())
             }
-            Ok(None) => Ok(ActionResponse::element_not_found(selector)),
+            Ok(None) => Ok(Self::element_not_found(&page, "click", selector).await),
             Err(e) => Ok(ActionResponse::error_with_suggestion(
-                "execution_error",
+                ErrorCode::ExecutionError,
                 &format!("Failed to click: {}", e),
                 "try get_context() to verify element exists",
             )),
@@ -141,161 +750,1336 @@ impl BrowserAutomation {
 
         match self.find_element(&page, selector).await {
             Ok(Some(element)) => {
-                // Check if element is visible
-                let is_visible = self.is_element_visible(&page, &element).await?;
-                if !is_visible {
-                    return Ok(ActionResponse::element_not_visible(
-                        selector.name.as_deref().unwrap_or("unknown"),
-                        &selector.role,
-                    ));
+                if let Some(feedback) = Self::check_interactable(&element, selector).await? {
+                    return Ok(feedback);
                 }
 
-                // Check if element is enabled
-                let is_enabled = self.is_element_enabled(&page, &element).await?;
-                if !is_enabled {
-                    return Ok(ActionResponse::element_not_enabled(
-                        selector.name.as_deref().unwrap_or("unknown"),
-                    ));
+                if self.keyboard_only {
+                    // Clicking into the field would skip the keyboard path being tested
+                    if !has_focus(&element).await? {
+                        return Ok(Self::not_focused(selector));
+                    }
+                    Self::clear_and_type(&element, text).await?;
+                } else {
+                    Self::replace_text(&element, text).await?;
                 }
 
-                // Focus and clear
-                element.click().await?;
-                element.press_key("End").await?;
-
-                // Select all and delete
-                #[cfg(target_os = "macos")]
-                element.press_key("Meta+a").await?;
-                #[cfg(not(target_os = "macos"))]
-                element.press_key("Control+a").await?;
-
-                element.press_key("Backspace").await?;
-
-                // Type new text
-                element.type_str(text).await?;
-
-                Ok(ActionResponse::success())
+                Ok(Self::verify_typed(&element, text).await)
             }
-            Ok(None) => Ok(ActionResponse::element_not_found(selector)),
+            Ok(None) => Ok(Self::element_not_found(&page, "type", selector).await),
             Err(e) => Ok(ActionResponse::error_with_suggestion(
-                "execution_error",
+                ErrorCode::ExecutionError,
                 &format!("Failed to type: {}", e),
                 "try get_context() to verify element exists and is a text input",
             )),
         }
     }
 
-    /// Scroll the page
-    async fn scroll(&self, direction: &ScrollDirection, amount: u32) -> Result<ActionResponse> {
-        let page = self.page.read().await;
+    /// Read the field back, catching controlled inputs that swallowed the keystrokes
+    async fn verify_typed(element: &Element, text: &str) -> ActionResponse {
+        // Let frameworks re-render the controlled value first
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
-        let (x, y) = match direction {
-            ScrollDirection::Down => (0, amount as i32),
-            ScrollDirection::Up => (0, -(amount as i32)),
-            ScrollDirection::Right => (amount as i32, 0),
-            ScrollDirection::Left => (-(amount as i32), 0),
+        let (actual, secret) = match FieldValue::read(element).await {
+            Ok(FieldValue { value: Some(value), secret }) => (value, secret),
+            // Nothing to compare against (e.g. a custom widget); typing itself succeeded
+            Ok(_) => return ActionResponse::success(),
+            Err(e) => {
+                tracing::debug!("Could not read back typed value: {}", e);
+                return ActionResponse::success();
+            }
         };
 
-        let script = format!("window.scrollBy({}, {})", x, y);
-        page.evaluate(script).await?;
+        let details = if secret {
+            serde_json::json!({
+                "expected_length": text.chars().count(),
+                "actual_length": actual.chars().count(),
+            })
+        } else {
+            serde_json::json!({ "expected": text, "actual": actual })
+        };
 
-        // Wait for scroll to complete
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        if value_matches(text, &actual) {
+            // Recordings leave secrets out
+            let details = if secret {
+                serde_json::json!({ "verified": true, "secret": true })
+            } else {
+                serde_json::json!({ "verified": true })
+            };
+            return ActionResponse::success_with_details(details);
+        }
 
-        Ok(ActionResponse::success())
+        let reason = if actual.is_empty() {
+            "The field is empty after typing".to_string()
+        } else if secret {
+            "The field holds a different value than was typed".to_string()
+        } else {
+            format!("The field contains '{}' instead of the typed text", actual)
+        };
+        let mut response = ActionResponse::error_with_suggestion(
+            ErrorCode::ValueMismatch,
+            &reason,
+            "the page may have rejected or reformatted the input (e.g. a length limit or a script-controlled field); \
+             check the field's format hints, then type again",
+        );
+        response.details = Some(details);
+        response
     }
 
-    /// Wait for element to appear
-    async fn wait_for_element(
+    /// Choose an option in a select or listbox
+    async fn select_option(
         &self,
         selector: &SemanticSelector,
-        timeout_ms: Option<u64>,
+        value: Option<&str>,
+        label: Option<&str>,
     ) -> Result<ActionResponse> {
-        let timeout = timeout_ms.unwrap_or(5000);
+        if value.is_none() && label.is_none() {
+            return Ok(Self::missing_option());
+        }
+
         let page = self.page.read().await;
+        let element = match self.find_element(&page, selector).await {
+            Ok(Some(element)) => element,
+            Ok(None) => return Ok(Self::element_not_found(&page, "select_option", selector).await),
+            Err(e) => {
+                return Ok(ActionResponse::error_with_suggestion(
+                    ErrorCode::ExecutionError,
+                    &format!("Failed to select option: {}", e),
+                    "try get_context() to verify element exists",
+                ))
+            }
+        };
+        if let Some(feedback) = Self::check_interactable(&element, selector).await? {
+            return Ok(feedback);
+        }
 
-        let css_selector = self.build_css_selector(selector);
+        let wanted = label.or(value).unwrap_or_default();
+        let response = match choose_option(&element, value, label).await? {
+            SelectOutcome::Selected { value, label } => {
+                // Let the page react to the change
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                ActionResponse::success_with_details(serde_json::json!({ "value": value, "label": label }))
+            }
+            SelectOutcome::NotFound { options } => {
+                let mut response = ActionResponse::error_with_suggestion(
+                    ErrorCode::OptionNotFound,
+                    &format!("No option matching '{}'", wanted),
+                    "use one of the options listed in details",
+                );
+                response.details = Some(serde_json::json!({ "options": options }));
+                response
+            }
+            SelectOutcome::Disabled { label } => ActionResponse::error_with_suggestion(
+                ErrorCode::OptionDisabled,
+                &format!("Option '{}' is disabled", label),
+                "choose another option, or complete the fields it depends on first",
+            ),
+            SelectOutcome::NotAList => ActionResponse::error_with_suggestion(
+                ErrorCode::NotAList,
+                &format!(
+                    "{} '{}' is not a select or listbox",
+                    selector.role,
+                    selector.name.as_deref().unwrap_or("unknown")
+                ),
+                "click the dropdown to open it, then click the option",
+            ),
+        };
+        Ok(response)
+    }
 
-        let wait_result = tokio::time::timeout(
-            tokio::time::Duration::from_millis(timeout),
-            page.find_element(&css_selector),
-        )
-        .await;
+    /// Bring a checkbox, radio button or switch into the wanted state
+    ///
+    /// Clicks only when the state differs, so repeating the action never toggles
+    /// it back; the state is read again afterwards to confirm the click took.
+    async fn set_checked(&self, selector: &SemanticSelector, checked: bool) -> Result<ActionResponse> {
+        let page = self.page.read().await;
+        let element = match self.find_element(&page, selector).await {
+            Ok(Some(element)) => element,
+            Ok(None) => return Ok(Self::element_not_found(&page, "set_checked", selector).await),
+            Err(e) => {
+                return Ok(ActionResponse::error_with_suggestion(
+                    ErrorCode::ExecutionError,
+                    &format!("Failed to set checked state: {}", e),
+                    "try get_context() to verify element exists",
+                ))
+            }
+        };
+        let target = format!("{} '{}'", selector.role, selector.name.as_deref().unwrap_or("unknown"));
 
-        match wait_result {
-            Ok(Ok(_)) => Ok(ActionResponse::success()),
-            Ok(Err(e)) => Ok(ActionResponse::error_with_suggestion(
-                "element_not_found",
-                &format!("Element did not appear: {}", e),
-                "verify the selector is correct or increase timeout",
-            )),
-            Err(_) => Ok(ActionResponse::error_with_suggestion(
-                "timeout",
-                &format!("Element did not appear within {}ms", timeout),
-                "try increasing timeout or verify element exists",
-            )),
+        let Some(before) = check_state(&element).await? else {
+            return Ok(ActionResponse::error_with_suggestion(
+                ErrorCode::NotCheckable,
+                &format!("{} is not a checkbox, radio button or switch", target),
+                "use click for other elements, or select_option for dropdowns",
+            ));
+        };
+        if before.checked == Some(checked) {
+            return Ok(ActionResponse::success_with_details(
+                serde_json::json!({ "checked": checked, "changed": false }),
+            ));
+        }
+        if before.radio && !checked {
+            return Ok(ActionResponse::error_with_suggestion(
+                ErrorCode::CannotUncheckRadio,
+                &format!("{} cannot be unchecked directly", target),
+                "check another radio button in the same group instead",
+            ));
+        }
+        if let Some(feedback) = Self::check_interactable(&element, selector).await? {
+            return Ok(feedback);
         }
-    }
 
-    /// Navigate to a URL
-    async fn navigate(&self, url: &str) -> Result<ActionResponse> {
-        let page = self.page.read().await;
+        element.click().await?;
+        // Let scripted widgets update their state
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
-        page.goto(url).await?;
-        page.wait_for_navigation().await?;
+        let after = check_state(&element).await?.and_then(|state| state.checked);
+        if after != Some(checked) {
+            let mut response = ActionResponse::error_with_suggestion(
+                ErrorCode::StateUnchanged,
+                &format!("{} is still not {} after clicking it", target, if checked { "checked" } else { "unchecked" }),
+                "the control may be read-only or driven by another element such as its label; \
+                 try clicking the label text instead",
+            );
+            response.details = Some(serde_json::json!({ "checked": after }));
+            return Ok(response);
+        }
 
-        Ok(ActionResponse::success())
+        Ok(ActionResponse::success_with_details(
+            serde_json::json!({ "checked": checked, "changed": true }),
+        ))
     }
 
-    /// Find element using semantic selector (Solution B)
-    async fn find_element(
-        &self,
-        page: &Page,
-        selector: &SemanticSelector,
-    ) -> Result<Option<Element>> {
-        // Try CSS selector approach with semantic attributes
-        let js_script = self.build_find_element_script(selector);
+    /// Move focus to an element, as a script or a skip link would
+    async fn focus(&self, selector: &SemanticSelector) -> Result<ActionResponse> {
+        let page = self.page.read().await;
 
-        let result = page.evaluate(js_script).await?;
+        match self.find_element(&page, selector).await {
+            Ok(Some(element)) => {
+                if let Some(feedback) = Self::check_interactable(&element, selector).await? {
+                    return Ok(feedback);
+                }
 
-        // Check if result is null
-        if let Some(value) = result.value() {
-            if value.is_null() {
-                // Try CSS fallback if available
-                if let Some(css) = &selector.css_fallback {
-                    match page.find_element(css).await {
-                        Ok(el) => return Ok(Some(el)),
-                        Err(_) => return Ok(None),
-                    }
+                element.call_js_fn("function() { this.focus(); }", false).await?;
+                if !has_focus(&element).await? {
+                    return Ok(ActionResponse::error_with_suggestion(
+                        ErrorCode::NotFocusable,
+                        &format!(
+                            "{} '{}' cannot receive keyboard focus",
+                            selector.role,
+                            selector.name.as_deref().unwrap_or("unknown")
+                        ),
+                        "focus a link, button or form field instead; other elements need a tabindex",
+                    ));
                 }
-                return Ok(None);
-            }
 
-            // Get the element ID from JavaScript and find it
-            if let Some(element_id) = value.as_str() {
-                // Use the element ID to find via CSS
-                let element = page.find_element(&format!("[data-element-id='{}']", element_id)).await.ok();
-                return Ok(element);
+                let focused = ContextExtractor::focused_element(&page).await?;
+                Ok(ActionResponse::success_with_details(serde_json::json!({ "focused": focused })))
             }
-        }
-
-        // Fallback: try direct CSS selector
-        let css_selector = self.build_css_selector(selector);
-        match page.find_element(&css_selector).await {
-            Ok(el) => Ok(Some(el)),
-            Err(_) => Ok(None),
+            Ok(None) => Ok(Self::element_not_found(&page, "focus", selector).await),
+            Err(e) => Ok(ActionResponse::error_with_suggestion(
+                ErrorCode::ExecutionError,
+                &format!("Failed to focus: {}", e),
+                "try get_context() to verify element exists",
+            )),
         }
     }
 
-    /// Build JavaScript to find element by semantic selector
-    fn build_find_element_script(&self, selector: &SemanticSelector) -> String {
-        let role = &selector.role;
-        let name = selector.name.as_deref().unwrap_or("");
+    /// Press a key and report where focus went
+    ///
+    /// Tab presses are tracked to detect focus traps. In keyboard-only sessions a
+    /// trap fails the action, since the flow cannot be completed without a mouse.
+    async fn press_key(&self, key: &str, modifiers: &[KeyModifier]) -> Result<ActionResponse> {
+        let Some(chord) = KeyChord::parse(key, modifiers) else {
+            return Ok(Self::unsupported_key(key));
+        };
 
-        // JavaScript to find element based on role and accessible name
-        format!(
-            r#"
-            (function() {{
-                function getAccessibleName(el) {{
+        let page = self.page.read().await;
+        let tabbing = chord.moves_focus();
+        let before = if tabbing {
+            ContextExtractor::focused_element(&page).await.ok().flatten()
+        } else {
+            None
+        };
+
+        chord.press(&page).await?;
+
+        // Let focus handlers and page changes run
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        // Enter may have started a navigation; the key press itself succeeded
+        let focused = ContextExtractor::focused_element(&page).await.ok().flatten();
+        drop(page);
+
+        let cycle = if tabbing {
+            self.focus_trail.write().await.record(before, focused.clone())
+        } else {
+            None
+        };
+        // Modal dialogs are supposed to keep focus inside
+        let trap = cycle.filter(|cycle| !cycle.iter().all(|element| element.in_modal));
+
+        let Some(cycle) = trap else {
+            return Ok(ActionResponse::success_with_details(serde_json::json!({ "focused": focused })));
+        };
+
+        self.events
+            .push(SessionEventKind::FocusTrapped { elements: cycle.clone() })
+            .await;
+
+        let details = serde_json::json!({ "focused": focused, "focus_trap": cycle });
+        if !self.keyboard_only {
+            return Ok(ActionResponse::success_with_details(details));
+        }
+
+        let mut response = ActionResponse::error_with_suggestion(
+            ErrorCode::FocusTrap,
+            &format!(
+                "Focus keeps cycling through {} element(s) without reaching the rest of the page",
+                cycle.len()
+            ),
+            "try Escape to leave the widget; if focus stays trapped, the page is not keyboard accessible",
+        );
+        response.details = Some(details);
+        Ok(response)
+    }
+
+    /// Clear an input and type `text` with real key events
+    async fn replace_text(element: &Element, text: &str) -> Result<()> {
+        // Focus
+        element.click().await?;
+        Self::clear_and_type(element, text).await
+    }
+
+    /// Clear the focused input and type `text`
+    async fn clear_and_type(element: &Element, text: &str) -> Result<()> {
+        element.press_key("End").await?;
+
+        // Select all and delete
+        #[cfg(target_os = "macos")]
+        element.press_key("Meta+a").await?;
+        #[cfg(not(target_os = "macos"))]
+        element.press_key("Control+a").await?;
+
+        element.press_key("Backspace").await?;
+
+        // Type new text
+        element.type_str(text).await?;
+
+        Ok(())
+    }
+
+    /// Fill several form fields by label and optionally submit the form
+    async fn fill_form(&self, fields: &BTreeMap<String, String>, submit: bool) -> Result<ActionResponse> {
+        if fields.is_empty() {
+            return Ok(Self::empty_form());
+        }
+
+        let page = self.page.read().await;
+        let (matched, unmatched) = FormFiller::resolve(&page, fields).await?;
+
+        let mut results: Vec<FieldFillResult> = unmatched
+            .iter()
+            .map(|key| FieldFillResult::failed(key, None, "no form field with this label, placeholder or name"))
+            .collect();
+
+        // Fill in document order, so dependent fields (country, then state) see their inputs
+        for field in &matched {
+            let value = &fields[&field.key];
+
+            if field.disabled {
+                results.push(FieldFillResult::failed(&field.key, Some(&field.label), "field is disabled or read-only"));
+                continue;
+            }
+
+            let error = match field.kind {
+                FieldKind::Text => {
+                    let selector = format!("[{}='{}']", FILL_ATTRIBUTE, field.fill_id);
+                    match page.find_element(&selector).await {
+                        Ok(element) => Self::replace_text(&element, value).await.err().map(|e| e.to_string()),
+                        Err(e) => Some(format!("field disappeared: {}", e)),
+                    }
+                }
+                _ => FormFiller::set_choice(&page, field, value).await?,
+            };
+
+            results.push(match error {
+                None => FieldFillResult::filled(field),
+                Some(error) => FieldFillResult::failed(&field.key, Some(&field.label), &error),
+            });
+        }
+
+        let all_filled = results.iter().all(|r| r.success);
+
+        // Never submit a half-filled form
+        let submitted = if submit && all_filled {
+            let submitted = FormFiller::submit(&page).await?;
+            // Give the submission a moment to start navigating
+            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+            submitted
+        } else {
+            false
+        };
+
+        let details = serde_json::json!({
+            "fields": results,
+            "submitted": submitted,
+        });
+
+        if !all_filled {
+            let failed: Vec<&str> = results.iter().filter(|r| !r.success).map(|r| r.field.as_str()).collect();
+            let mut response = ActionResponse::error_with_suggestion(
+                ErrorCode::FormFieldsFailed,
+                &format!("Could not fill: {}", failed.join(", ")),
+                "check the field labels with get_context() and retry the failed fields",
+            );
+            response.details = Some(details);
+            return Ok(response);
+        }
+
+        if submit && !submitted {
+            let mut response = ActionResponse::error_with_suggestion(
+                ErrorCode::FormNotFound,
+                "Fields were filled, but they are not inside a form that can be submitted",
+                "click the submit button instead",
+            );
+            response.details = Some(details);
+            return Ok(response);
+        }
+
+        Ok(ActionResponse::success_with_details(details))
+    }
+
+    /// Log in with a vault credential: locate fields, fill, submit, verify
+    ///
+    /// Handles identifier-first forms (username, submit, then password). The
+    /// password never appears in the response or in logs.
+    async fn login(&self, credential_name: &str) -> Result<ActionResponse> {
+        let Some(credential) = self.credentials.get(credential_name) else {
+            return Ok(self.unknown_credential(credential_name));
+        };
+
+        let page = self.page.read().await;
+        let start_url = page.url().await?.unwrap_or_default();
+        let mut phases: Vec<serde_json::Value> = Vec::new();
+        let phase = |name: &str, ok: bool| serde_json::json!({ "phase": name, "success": ok });
+
+        let mut fields = LoginHelper::locate(&page).await?;
+        if !fields.username && !fields.password {
+            return Ok(Self::login_fields_not_found());
+        }
+        phases.push(phase("locate", true));
+
+        if fields.username {
+            let selector = format!("[{}='username']", LOGIN_ATTRIBUTE);
+            Self::replace_text(&page.find_element(&selector).await?, &credential.username).await?;
+            phases.push(phase("fill_username", true));
+        }
+
+        if !fields.password {
+            // Identifier-first flow: submit the username and wait for the password step
+            if !LoginHelper::submit(&page, "username").await? {
+                let selector = format!("[{}='username']", LOGIN_ATTRIBUTE);
+                page.find_element(&selector).await?.press_key("Enter").await?;
+            }
+            phases.push(phase("submit_username", true));
+
+            let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_millis(5000);
+            while !fields.password && tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
+                // The page may be navigating; try again on the next tick
+                if let Ok(found) = LoginHelper::locate(&page).await {
+                    fields = found;
+                }
+            }
+
+            if !fields.password {
+                phases.push(phase("locate_password", false));
+                return Ok(Self::login_failure(
+                    ErrorCode::PasswordFieldNotFound,
+                    "The password field did not appear after submitting the username",
+                    "check the page with get_context(); the site may require a different step (captcha, SSO)",
+                    phases,
+                ));
+            }
+        }
+
+        let password_selector = format!("[{}='password']", LOGIN_ATTRIBUTE);
+        let password_field = page.find_element(&password_selector).await?;
+        Self::replace_text(&password_field, &credential.password).await?;
+        phases.push(phase("fill_password", true));
+
+        if !LoginHelper::submit(&page, "password").await? {
+            password_field.press_key("Enter").await?;
+        }
+        phases.push(phase("submit", true));
+
+        // Success once the password form is gone; an error banner means rejection
+        let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_millis(8000);
+        let mut last_state = None;
+        while tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
+
+            let Ok(state) = LoginHelper::state(&page).await else {
+                continue;
+            };
+
+            if let Some(error) = &state.error {
+                phases.push(phase("verify", false));
+                return Ok(Self::login_failure(
+                    ErrorCode::LoginFailed,
+                    &format!("The site reported an error after login: {}", error),
+                    "verify the credential in the vault is correct for this site",
+                    phases,
+                ));
+            }
+
+            if !state.password_visible {
+                phases.push(phase("verify", true));
+                return Ok(ActionResponse::success_with_details(serde_json::json!({
+                    "credential": credential_name,
+                    "phases": phases,
+                    "url": state.url,
+                    "url_changed": state.url != start_url,
+                })));
+            }
+
+            last_state = Some(state);
+        }
+
+        phases.push(phase("verify", false));
+        let still_on = last_state.map(|s| s.url).unwrap_or(start_url);
+        Ok(Self::login_failure(
+            ErrorCode::LoginUnverified,
+            &format!("Still on the login form at {} after submitting", still_on),
+            "check get_context() for captchas, 2FA prompts or messages the heuristics missed",
+            phases,
+        ))
+    }
+
+    fn login_failure(error: ErrorCode, reason: &str, suggestion: &str, phases: Vec<serde_json::Value>) -> ActionResponse {
+        let mut response = ActionResponse::error_with_suggestion(error, reason, suggestion);
+        response.details = Some(serde_json::json!({ "phases": phases }));
+        response
+    }
+
+    fn login_fields_not_found() -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::LoginFieldsNotFound,
+            "No username or password field on this page",
+            "navigate to the login page first, or click the 'Sign in' link",
+        )
+    }
+
+    fn unknown_credential(&self, name: &str) -> ActionResponse {
+        let names = self.credentials.names();
+        ActionResponse::error_with_suggestion(
+            ErrorCode::CredentialNotFound,
+            &format!("No credential named '{}' in the vault", name),
+            &if names.is_empty() {
+                "configure CREDENTIAL_VAULT or CREDENTIAL_VAULT_FILE on the server".to_string()
+            } else {
+                format!("use one of: {}", names.join(", "))
+            },
+        )
+    }
+
+    /// Wait for the next one-time code from a provider, optionally filling it in
+    ///
+    /// Codes are returned once: a second call waits for a newer code.
+    async fn get_otp(&self, provider: &str, timeout_ms: u64, fill: bool) -> Result<ActionResponse> {
+        let Some(kind) = self.otp.get(provider).map(|p| p.kind()) else {
+            return Ok(self.unknown_otp_provider(provider));
+        };
+
+        match self.otp.fetch(provider, tokio::time::Duration::from_millis(timeout_ms)).await {
+            Ok(Some(code)) => {
+                let filled = if fill {
+                    let page = self.page.read().await;
+                    LoginHelper::fill_otp(&page, &code).await?
+                } else {
+                    false
+                };
+
+                Ok(ActionResponse::success_with_details(serde_json::json!({
+                    "provider": provider,
+                    "kind": kind,
+                    "code": code,
+                    "filled": filled,
+                })))
+            }
+            Ok(None) => Ok(ActionResponse::error_with_suggestion(
+                ErrorCode::Timeout,
+                &format!("No new code from '{}' within {}ms", provider, timeout_ms),
+                "make sure the code was requested (e.g. submit the login form) and retry with a longer timeout_ms",
+            )),
+            Err(e) => {
+                tracing::warn!("OTP provider '{}' failed: {}", provider, e);
+                Ok(ActionResponse::error_with_suggestion(
+                    ErrorCode::OtpProviderError,
+                    &format!("Provider '{}' failed: {}", provider, e),
+                    "check the provider configuration in OTP_PROVIDERS",
+                ))
+            }
+        }
+    }
+
+    fn unknown_otp_provider(&self, name: &str) -> ActionResponse {
+        let names = self.otp.names();
+        ActionResponse::error_with_suggestion(
+            ErrorCode::OtpProviderNotFound,
+            &format!("No OTP provider named '{}'", name),
+            &if names.is_empty() {
+                "configure OTP_PROVIDERS on the server".to_string()
+            } else {
+                format!("use one of: {}", names.join(", "))
+            },
+        )
+    }
+
+    /// Hooks the operator allowed on the current page's origin
+    pub async fn available_hooks(&self) -> Result<Vec<(String, TestHook)>> {
+        Ok(self.hooks.available_on(&self.current_origin().await?))
+    }
+
+    async fn current_origin(&self) -> Result<String> {
+        let page = self.page.read().await;
+        let url = page.url().await?.unwrap_or_default();
+        Ok(reqwest::Url::parse(&url)
+            .map(|url| url.origin().ascii_serialization())
+            .unwrap_or_default())
+    }
+
+    /// The hook if it exists and may run on the current origin, else the feedback why not
+    async fn resolve_hook(&self, name: &str) -> Result<std::result::Result<TestHook, ActionResponse>> {
+        let origin = self.current_origin().await?;
+        let available = || {
+            let names: Vec<String> = self.hooks.available_on(&origin).into_iter().map(|(n, _)| n).collect();
+            if names.is_empty() {
+                format!("no hooks are registered for {}", origin)
+            } else {
+                format!("hooks available here: {}", names.join(", "))
+            }
+        };
+
+        let Some(hook) = self.hooks.get(name) else {
+            return Ok(Err(ActionResponse::error_with_suggestion(
+                ErrorCode::HookNotFound,
+                &format!("No test hook named '{}'", name),
+                &available(),
+            )));
+        };
+
+        if !hook.allows_origin(&origin) {
+            return Ok(Err(ActionResponse::error_with_suggestion(
+                ErrorCode::HookNotAllowed,
+                &format!("Hook '{}' may not run on {}", name, origin),
+                &format!("navigate to one of {} first; {}", hook.origins.join(", "), available()),
+            )));
+        }
+
+        Ok(Ok(hook))
+    }
+
+    /// Call a registered page function with JSON arguments and return its result
+    async fn invoke_hook(&self, name: &str, args: &[serde_json::Value]) -> Result<ActionResponse> {
+        let hook = match self.resolve_hook(name).await? {
+            Ok(hook) => hook,
+            Err(feedback) => return Ok(feedback),
+        };
+
+        // Only the operator-registered path is called; the agent controls nothing but the JSON arguments
+        let script = format!(
+            r#"
+            (async () => {{
+                const path = {path}.split('.');
+                let owner = window;
+                let target = window;
+                for (const key of path[0] === 'window' ? path.slice(1) : path) {{
+                    owner = target;
+                    target = target == null ? undefined : target[key];
+                }}
+                if (typeof target !== 'function') return {{ status: 'missing' }};
+
+                try {{
+                    const value = await target.apply(owner, {args});
+                    return {{ status: 'ok', value: value === undefined ? null : JSON.parse(JSON.stringify(value)) }};
+                }} catch (e) {{
+                    return {{ status: 'error', message: String((e && e.message) || e) }};
+                }}
+            }})()
+            "#,
+            path = serde_json::to_string(&hook.function)?,
+            args = serde_json::to_string(args)?,
+        );
+
+        let page = self.page.read().await;
+        let outcome: serde_json::Value = page.evaluate(script).await?.into_value()?;
+        drop(page);
+
+        self.events
+            .push(SessionEventKind::HookInvoked {
+                name: name.to_string(),
+                success: outcome["status"] == "ok",
+            })
+            .await;
+
+        Ok(match outcome["status"].as_str() {
+            Some("ok") => ActionResponse::success_with_details(serde_json::json!({
+                "hook": name,
+                "result": outcome["value"],
+            })),
+            Some("missing") => ActionResponse::error_with_suggestion(
+                ErrorCode::HookUnavailable,
+                &format!("The page does not define {} (hook '{}')", hook.function, name),
+                "the app's test build may not be loaded on this page; reload or check the deployment",
+            ),
+            _ => ActionResponse::error_with_suggestion(
+                ErrorCode::HookFailed,
+                &format!(
+                    "Hook '{}' threw: {}",
+                    name,
+                    outcome["message"].as_str().unwrap_or("unknown error")
+                ),
+                "check the arguments against the hook's description",
+            ),
+        })
+    }
+
+    fn invalid_quality(format: ScreenshotFormat, quality: Option<u8>) -> Option<ActionResponse> {
+        let reason = match (format, quality) {
+            (ScreenshotFormat::Png, Some(_)) => "quality only applies to JPEG screenshots",
+            (ScreenshotFormat::Jpeg, Some(quality)) if quality > 100 => "quality must be between 0 and 100",
+            _ => return None,
+        };
+        Some(ActionResponse::error_with_suggestion(
+            ErrorCode::InvalidQuality,
+            reason,
+            "use \"format\": \"jpeg\" with a quality from 0 to 100, or omit quality",
+        ))
+    }
+
+    /// Capture the page as PNG or JPEG
+    ///
+    /// The automation banner is hidden for the capture unless configured to stay.
+    pub async fn screenshot(
+        &self,
+        format: ScreenshotFormat,
+        full_page: bool,
+        quality: Option<u8>,
+    ) -> Result<Vec<u8>> {
+        let mut params = ScreenshotParams::builder()
+            .format(match format {
+                ScreenshotFormat::Png => CaptureScreenshotFormat::Png,
+                ScreenshotFormat::Jpeg => CaptureScreenshotFormat::Jpeg,
+            })
+            .full_page(full_page);
+        if let (ScreenshotFormat::Jpeg, Some(quality)) = (format, quality) {
+            params = params.quality(quality.min(100) as i64);
+        }
+
+        let page = self.page.read().await;
+        let hide_banner = self.marker.hides_banner_in_screenshots();
+        if hide_banner {
+            self.marker.set_banner_visible(&page, false).await?;
+        }
+
+        let image = page.screenshot(params.build()).await;
+
+        if hide_banner {
+            if let Err(e) = self.marker.set_banner_visible(&page, true).await {
+                tracing::debug!("Failed to show the automation banner again: {}", e);
+            }
+        }
+        Ok(image?)
+    }
+
+    /// HTML of the active page, as currently in the DOM
+    pub async fn html(&self) -> Result<String> {
+        Ok(self.page.read().await.content().await?)
+    }
+
+    /// Capture the viewport with numbered boxes over the context elements in `ids`
+    ///
+    /// The boxes are removed again after the capture, also when it fails.
+    pub async fn set_of_mark_screenshot(
+        &self,
+        ids: &[usize],
+        format: ScreenshotFormat,
+        quality: Option<u8>,
+    ) -> Result<Vec<u8>> {
+        ContextExtractor::draw_marks(&*self.page.read().await, ids).await?;
+
+        let image = self.screenshot(format, false, quality).await;
+
+        if let Err(e) = ContextExtractor::clear_marks(&*self.page.read().await).await {
+            tracing::debug!("Failed to remove the Set-of-Mark boxes: {}", e);
+        }
+        image
+    }
+
+    async fn take_screenshot(
+        &self,
+        format: ScreenshotFormat,
+        full_page: bool,
+        quality: Option<u8>,
+    ) -> Result<ActionResponse> {
+        if let Some(feedback) = Self::invalid_quality(format, quality) {
+            return Ok(feedback);
+        }
+
+        let image = self.screenshot(format, full_page, quality).await?;
+
+        Ok(ActionResponse::success_with_details(serde_json::json!({
+            "format": format,
+            "full_page": full_page,
+            "bytes": image.len(),
+            "data": base64::engine::general_purpose::STANDARD.encode(&image),
+        })))
+    }
+
+    fn empty_form() -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::InvalidFields,
+            "No fields given",
+            "pass a map of field label to value in 'fields'",
+        )
+    }
+
+    /// Scroll the page
+    async fn scroll(&self, direction: &ScrollDirection, amount: u32) -> Result<ActionResponse> {
+        let page = self.page.read().await;
+
+        let (x, y) = match direction {
+            ScrollDirection::Down => (0, amount as i32),
+            ScrollDirection::Up => (0, -(amount as i32)),
+            ScrollDirection::Right => (amount as i32, 0),
+            ScrollDirection::Left => (-(amount as i32), 0),
+        };
+
+        let script = format!("window.scrollBy({}, {})", x, y);
+        page.evaluate(script).await?;
+
+        // Wait for scroll to complete
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        Ok(ActionResponse::success())
+    }
+
+    /// Scroll an overflow container: the element itself, its closest scrollable
+    /// ancestor, or else its first scrollable descendant
+    async fn scroll_container(
+        &self,
+        selector: &SemanticSelector,
+        direction: &ScrollDirection,
+        amount: u32,
+    ) -> Result<ActionResponse> {
+        let page = self.page.read().await;
+        let element = match self.find_element(&page, selector).await {
+            Ok(Some(element)) => element,
+            Ok(None) => return Ok(Self::element_not_found(&page, "scroll", selector).await),
+            Err(e) => {
+                return Ok(ActionResponse::error_with_suggestion(
+                    ErrorCode::ExecutionError,
+                    &format!("Failed to scroll: {}", e),
+                    "try get_context() to verify element exists",
+                ))
+            }
+        };
+        if let Some(feedback) = Self::check_interactable(&element, selector).await? {
+            return Ok(feedback);
+        }
+
+        let (x, y) = match direction {
+            ScrollDirection::Down => (0, amount as i32),
+            ScrollDirection::Up => (0, -(amount as i32)),
+            ScrollDirection::Right => (amount as i32, 0),
+            ScrollDirection::Left => (-(amount as i32), 0),
+        };
+        let script = format!(
+            r#"async function() {{
+                const [x, y] = [{x}, {y}];
+                const scrollable = el => {{
+                    const style = getComputedStyle(el);
+                    const overflow = y !== 0 ? style.overflowY : style.overflowX;
+                    const room = y !== 0 ? el.scrollHeight - el.clientHeight : el.scrollWidth - el.clientWidth;
+                    return room > 0 && ['auto', 'scroll', 'overlay'].includes(overflow);
+                }};
+
+                let container = null;
+                for (let el = this; el && el !== document.body && el !== document.documentElement; el = el.parentElement) {{
+                    if (scrollable(el)) {{ container = el; break; }}
+                }}
+                container = container || Array.from(this.querySelectorAll('*')).find(scrollable);
+                if (!container) return JSON.stringify(null);
+
+                container.scrollBy(x, y);
+                // Virtualized lists render the new rows a frame later
+                await new Promise(resolve => requestAnimationFrame(() => resolve()));
+                return JSON.stringify({{
+                    scroll_top: container.scrollTop,
+                    scroll_left: container.scrollLeft,
+                    at_start: y !== 0 ? container.scrollTop <= 0 : container.scrollLeft <= 0,
+                    at_end: y !== 0
+                        ? container.scrollTop + container.clientHeight >= container.scrollHeight - 1
+                        : container.scrollLeft + container.clientWidth >= container.scrollWidth - 1,
+                }});
+            }}"#,
+            x = x,
+            y = y,
+        );
+
+        let result = element.call_js_fn(script, true).await?;
+        let position = result
+            .result
+            .value
+            .and_then(|value| value.as_str().and_then(|json| serde_json::from_str::<serde_json::Value>(json).ok()))
+            .filter(|position| !position.is_null());
+
+        // Wait for scroll to complete
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        match position {
+            Some(position) => Ok(ActionResponse::success_with_details(position)),
+            None => Ok(ActionResponse::error_with_suggestion(
+                ErrorCode::NotScrollable,
+                &format!(
+                    "{} '{}' has nothing to scroll in that direction",
+                    selector.role,
+                    selector.name.as_deref().unwrap_or("unknown")
+                ),
+                "scroll the page without a selector, or pick the list or pane that holds the content",
+            )),
+        }
+    }
+
+    /// Wait for element to appear
+    async fn wait_for_element(
+        &self,
+        selector: &SemanticSelector,
+        timeout_ms: Option<u64>,
+    ) -> Result<ActionResponse> {
+        let timeout = timeout_ms.unwrap_or(5000);
+        let page = self.page.read().await;
+
+        let css_selector = css_selector(selector);
+
+        let wait_result = tokio::time::timeout(
+            tokio::time::Duration::from_millis(timeout),
+            page.find_element(&css_selector),
+        )
+        .await;
+
+        match wait_result {
+            Ok(Ok(_)) => Ok(ActionResponse::success()),
+            Ok(Err(e)) => Ok(ActionResponse::error_with_suggestion(
+                ErrorCode::ElementNotFound,
+                &format!("Element did not appear: {}", e),
+                "verify the selector is correct or increase timeout",
+            )),
+            Err(_) => Ok(ActionResponse::error_with_suggestion(
+                ErrorCode::Timeout,
+                &format!("Element did not appear within {}ms", timeout),
+                "try increasing timeout or verify element exists",
+            )),
+        }
+    }
+
+    /// Wait for the current navigation, or the next one, to finish
+    async fn wait_for_navigation(&self, timeout_ms: u64) -> Result<ActionResponse> {
+        let page = self.page.read().await;
+        match waits::wait_for_navigation(&page, tokio::time::Duration::from_millis(timeout_ms)).await? {
+            Some(url) => Ok(ActionResponse::success_with_details(serde_json::json!({ "url": url }))),
+            None => Ok(ActionResponse::error_with_suggestion(
+                ErrorCode::Timeout,
+                &format!("The page did not navigate within {}ms", timeout_ms),
+                "start the navigation first (e.g. click the link), or wait_for_element for what the new page shows",
+            )),
+        }
+    }
+
+    async fn wait_for_network_idle(&self, timeout_ms: u64) -> Result<ActionResponse> {
+        let page = self.page.read().await;
+        let idle = tokio::time::Duration::from_millis(NETWORK_IDLE_MS);
+        if waits::wait_for_network_idle(&page, idle, tokio::time::Duration::from_millis(timeout_ms)).await? {
+            return Ok(ActionResponse::success());
+        }
+
+        Ok(ActionResponse::error_with_suggestion(
+            ErrorCode::Timeout,
+            &format!("Requests kept running for {}ms", timeout_ms),
+            "the page polls or streams continuously; wait_for_element or wait_for_text for the content you need instead",
+        ))
+    }
+
+    async fn wait_for_url(&self, pattern: &str, timeout_ms: u64) -> Result<ActionResponse> {
+        let page = self.page.read().await;
+        match waits::wait_for_url(&page, pattern, tokio::time::Duration::from_millis(timeout_ms)).await? {
+            Some(url) => Ok(ActionResponse::success_with_details(serde_json::json!({ "url": url }))),
+            None => Ok(ActionResponse::error_with_suggestion(
+                ErrorCode::Timeout,
+                &format!(
+                    "The URL did not match '{}' within {}ms (now {})",
+                    pattern,
+                    timeout_ms,
+                    page.url().await?.unwrap_or_default()
+                ),
+                "the pattern must match the whole URL; wrap the part you expect in * (e.g. */orders/*)",
+            )),
+        }
+    }
+
+    async fn wait_for_text(&self, text: &str, timeout_ms: u64) -> Result<ActionResponse> {
+        let page = self.page.read().await;
+        if waits::wait_for_text(&page, text, tokio::time::Duration::from_millis(timeout_ms)).await? {
+            return Ok(ActionResponse::success());
+        }
+
+        Ok(ActionResponse::error_with_suggestion(
+            ErrorCode::Timeout,
+            &format!("'{}' did not appear within {}ms", text, timeout_ms),
+            "try a shorter part of the text, increase timeout_ms, or find_text to check what the page shows",
+        ))
+    }
+
+    /// Check an assertion against the page; `details.actual` holds what was found
+    pub async fn check_assertion(&self, assertion: &Assertion) -> Result<ActionResponse> {
+        let page = self.page.read().await;
+        let actual = match assertion {
+            Assertion::UrlMatches { .. } => page.url().await?,
+            Assertion::TitleContains { .. } => page.get_title().await?,
+            _ => {
+                let selector = assertion.selector().expect("element assertions have a selector");
+                match self.find_element(&page, selector).await? {
+                    Some(element) => Some(element.inner_text().await?.unwrap_or_default()),
+                    None => None,
+                }
+            }
+        };
+
+        let passed = match (assertion, &actual) {
+            (_, None) => false,
+            (Assertion::ElementExists { .. }, Some(_)) => true,
+            (Assertion::UrlMatches { pattern }, Some(url)) => filename_matches(pattern, url),
+            (Assertion::TitleContains { text }, Some(title)) => title.contains(text.as_str()),
+            (_, Some(text)) => text_assertion_holds(assertion, text).unwrap_or(false),
+        };
+        // Whether the element exists is all element_exists reports
+        let actual = match assertion {
+            Assertion::ElementExists { .. } => None,
+            _ => actual,
+        };
+        let details = serde_json::json!({
+            "check": assertion.name(),
+            "passed": passed,
+            "actual": actual,
+        });
+
+        if passed {
+            return Ok(ActionResponse::success_with_details(details));
+        }
+        let found = match (assertion.selector(), &actual) {
+            (Some(_), None) => "the element was not found".to_string(),
+            (_, Some(actual)) => format!("found '{}'", actual),
+            (None, None) => "found nothing".to_string(),
+        };
+        let suggestion = match assertion {
+            Assertion::UrlMatches { .. } => "the pattern must match the whole URL; wrap the part you expect in * (e.g. */orders/*)",
+            _ if actual.is_none() => "wait_for_element first if the element is still appearing, or check the selector against get_context()",
+            _ => "wait_for_text first if the page is still updating",
+        };
+        Ok(ActionResponse {
+            details: Some(details),
+            ..ActionResponse::error_with_suggestion(
+                ErrorCode::AssertionFailed,
+                &format!("Expected {}, but {}", assertion.describe(), found),
+                suggestion,
+            )
+        })
+    }
+
+    /// Wait for a download to finish and return its artifact ID
+    async fn wait_for_download(
+        &self,
+        filename_pattern: Option<&str>,
+        timeout_ms: u64,
+    ) -> Result<ActionResponse> {
+        let Some(downloads) = &self.downloads else {
+            return Ok(Self::downloads_unavailable());
+        };
+
+        match downloads.wait_for_download(filename_pattern, timeout_ms).await {
+            DownloadWait::Completed(artifact) => Ok(ActionResponse::success_with_details(serde_json::json!({
+                "artifact_id": artifact.id,
+                "filename": artifact.filename,
+                "size_bytes": artifact.size_bytes,
+            }))),
+            DownloadWait::Canceled(artifact) => Ok(ActionResponse::error_with_suggestion(
+                ErrorCode::DownloadCanceled,
+                &format!("Download of '{}' was canceled", artifact.filename),
+                "trigger the download again",
+            )),
+            DownloadWait::TimedOut => Ok(ActionResponse::error_with_suggestion(
+                ErrorCode::Timeout,
+                &format!(
+                    "No download matching '{}' finished within {}ms",
+                    filename_pattern.unwrap_or("*"),
+                    timeout_ms
+                ),
+                "trigger the download first (e.g. click the export button) or increase timeout_ms",
+            )),
+        }
+    }
+
+    /// Set the answer for the next JavaScript dialog the page opens
+    fn handle_dialog(&self, accept: bool, prompt_text: Option<String>) -> ActionResponse {
+        let Some(dialogs) = &self.dialogs else {
+            return Self::dialogs_unavailable();
+        };
+
+        let answer = DialogPolicy { accept, prompt_text };
+        let details = serde_json::json!({ "next_dialog": &answer });
+        dialogs.answer_next(answer);
+        ActionResponse::success_with_details(details)
+    }
+
+    /// Read the origin's storage area, or one key of it
+    async fn get_storage(&self, area: WebStorageArea, key: Option<&str>) -> Result<ActionResponse> {
+        let storage = self.web_storage().await?;
+        let items = storage.area(area);
+
+        Ok(ActionResponse::success_with_details(match key {
+            Some(key) => serde_json::json!({
+                "origin": storage.origin,
+                "area": area,
+                "key": key,
+                "value": items.get(key),
+            }),
+            None => serde_json::json!({ "origin": storage.origin, "area": area, "items": items }),
+        }))
+    }
+
+    /// Set or remove one key of the origin's storage area
+    async fn set_storage(&self, area: WebStorageArea, key: &str, value: Option<String>) -> Result<ActionResponse> {
+        let items = BTreeMap::from([(key.to_string(), value)]);
+        if let Err(reason) = self.write_web_storage(area, &items, false).await? {
+            return Ok(Self::storage_unavailable(area, &reason));
+        }
+
+        Ok(ActionResponse::success())
+    }
+
+    fn storage_unavailable(area: WebStorageArea, reason: &str) -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::StorageUnavailable,
+            &format!("Cannot write {}: {}", area.global(), reason),
+            "navigate to a page of the site first; web storage belongs to the page's origin",
+        )
+    }
+
+    async fn evaluate_action(&self, script: &str) -> Result<ActionResponse> {
+        Ok(match self.evaluate(script).await? {
+            Ok(result) => ActionResponse::success_with_details(serde_json::json!({ "result": result })),
+            Err(message) => ActionResponse::error_with_suggestion(
+                ErrorCode::ScriptFailed,
+                &format!("Script threw: {}", message),
+                "check the script in the browser console; wrap statements in an arrow function and return the value",
+            ),
+        })
+    }
+
+    /// Run a script in the page and return its JSON-serialized result
+    ///
+    /// A returned promise is awaited. The inner error is the exception the
+    /// script threw; values JSON can't hold (DOM nodes, functions) come back
+    /// as `{}` or null.
+    pub async fn evaluate(&self, script: &str) -> Result<std::result::Result<serde_json::Value, String>> {
+        let mut params = EvaluateParams::new(script);
+        params.return_by_value = Some(true);
+        params.await_promise = Some(true);
+        params.user_gesture = Some(true);
+
+        let page = self.page.read().await;
+        let evaluated = page.execute(params).await?.result;
+        drop(page);
+
+        if let Some(details) = evaluated.exception_details {
+            // `text` is only "Uncaught"; the error's description carries the message
+            let message = details
+                .exception
+                .and_then(|exception| exception.description)
+                .and_then(|description| description.lines().next().map(str::to_string))
+                .unwrap_or(details.text);
+            return Ok(Err(message));
+        }
+
+        Ok(Ok(evaluated.result.value.unwrap_or(serde_json::Value::Null)))
+    }
+
+    /// Add the dialogs an action opened to its response details
+    fn report_dialogs(result: &mut ActionResponse, opened: Vec<DialogRecord>) {
+        let details = result.details.get_or_insert_with(|| serde_json::json!({}));
+        if let Some(details) = details.as_object_mut() {
+            details.insert("dialogs".to_string(), serde_json::json!(opened));
+        }
+    }
+
+    fn invalid_url(url: &str) -> Option<ActionResponse> {
+        match reqwest::Url::parse(url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https" | "file" | "about") => None,
+            _ => Some(ActionResponse::error_with_suggestion(
+                ErrorCode::InvalidUrl,
+                &format!("'{}' is not a valid absolute URL", url),
+                "use a full URL such as https://example.com/path",
+            )),
+        }
+    }
+
+    /// Open a tab and continue in it
+    async fn new_tab(&self, url: Option<&str>) -> Result<ActionResponse> {
+        if let Some(feedback) = url.and_then(Self::invalid_url) {
+            return Ok(feedback);
+        }
+
+        let tab = self.tabs.open(url).await?;
+        self.forget_elements().await;
+        Ok(ActionResponse::success_with_details(serde_json::json!({ "tab": tab })))
+    }
+
+    async fn switch_tab(&self, tab_id: u32) -> Result<ActionResponse> {
+        let Some(tab) = self.tabs.switch(tab_id).await else {
+            return Ok(self.unknown_tab(tab_id));
+        };
+        self.forget_elements().await;
+        Ok(ActionResponse::success_with_details(serde_json::json!({ "tab": tab })))
+    }
+
+    async fn close_tab(&self, tab_id: Option<u32>) -> Result<ActionResponse> {
+        if self.tabs.count() < 2 {
+            return Ok(Self::last_tab());
+        }
+
+        let tab_id = tab_id.unwrap_or_else(|| self.tabs.active());
+        let Some(active_tab_id) = self.tabs.close(tab_id).await? else {
+            return Ok(self.unknown_tab(tab_id));
+        };
+        self.forget_elements().await;
+        Ok(ActionResponse::success_with_details(serde_json::json!({
+            "closed_tab_id": tab_id,
+            "active_tab_id": active_tab_id,
+        })))
+    }
+
+    /// Element and overlay IDs of the previous tab's context must not reach into another tab
+    async fn forget_elements(&self) {
+        *self.element_map.write().await = ElementMap::default();
+        self.closed_overlays.write().await.clear();
+    }
+
+    fn unknown_tab(&self, tab_id: u32) -> ActionResponse {
+        let open: Vec<String> = self.tabs.ids().into_iter().map(|id| id.to_string()).collect();
+        ActionResponse::error_with_suggestion(
+            ErrorCode::TabNotFound,
+            &format!("No open tab has ID {}", tab_id),
+            &format!("use one of the open tabs: {}", open.join(", ")),
+        )
+    }
+
+    fn last_tab() -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::LastTab,
+            "The session's only tab cannot be closed",
+            "navigate elsewhere instead, or delete the session when done",
+        )
+    }
+
+    /// Navigate to a URL
+    async fn navigate(&self, url: &str) -> Result<ActionResponse> {
+        let page = self.page.read().await;
+
+        if let Err(e) = page.goto(url).await {
+            if is_client_certificate_error(&e.to_string()) {
+                return Ok(client_certificate_feedback(url));
+            }
+            return Err(e.into());
+        }
+        page.wait_for_navigation().await?;
+
+        Ok(ActionResponse::success())
+    }
+
+    /// Move `offset` entries through the tab's history (-1 = back, 1 = forward)
+    async fn go_through_history(&self, offset: i64) -> Result<ActionResponse> {
+        let Some((entry_id, url)) = self.history_entry(offset).await? else {
+            return Ok(Self::no_history(offset));
+        };
+
+        let page = self.page.read().await;
+        page.execute(NavigateToHistoryEntryParams::new(entry_id)).await?;
+        page.wait_for_navigation().await?;
+
+        Ok(ActionResponse::success_with_details(serde_json::json!({ "url": url })))
+    }
+
+    /// ID and URL of the history entry `offset` steps from the current one
+    async fn history_entry(&self, offset: i64) -> Result<Option<(i64, String)>> {
+        let page = self.page.read().await;
+        let history = page.execute(GetNavigationHistoryParams::default()).await?.result;
+
+        Ok(usize::try_from(history.current_index + offset)
+            .ok()
+            .and_then(|index| history.entries.get(index))
+            .map(|entry| (entry.id, entry.url.clone())))
+    }
+
+    /// Reload the current page
+    async fn reload(&self) -> Result<ActionResponse> {
+        let page = self.page.read().await;
+        page.reload().await?;
+
+        Ok(ActionResponse::success())
+    }
+
+    /// Search visible text, highlight matches and report nearby interactive elements
+    async fn find_text(&self, query: &str, scroll_to_first: bool) -> Result<ActionResponse> {
+        if let Some(feedback) = Self::invalid_query(query) {
+            return Ok(feedback);
+        }
+
+        let page = self.page.read().await;
+
+        let script = format!(
+            r#"
+            (() => {{
+                const query = {};
+                const scrollToFirst = {};
+                const maxMatches = 20;
+
+                // Hidden ancestors (display, visibility, opacity, the hidden
+                // attribute, a clipped zero-size box) hide the element too
+                function isVisible(el) {{
+                    if (!el || !el.isConnected || el.getClientRects().length === 0) return false;
+                    if (el.checkVisibility) {{
+                        const options = {{
+                            opacityProperty: true,
+                            visibilityProperty: true,
+                            checkOpacity: true,
+                            checkVisibilityCSS: true,
+                        }};
+                        if (!el.checkVisibility(options)) return false;
+                    }}
+                    // Visibility is inherited, so the element's own value is the one that counts
+                    if (window.getComputedStyle(el).visibility !== 'visible') return false;
+                    for (let node = el; node; node = node.parentElement) {{
+                        const style = window.getComputedStyle(node);
+                        if (style.display === 'none' || style.opacity === '0') return false;
+                        const rect = node.getBoundingClientRect();
+                        if ((rect.width === 0 || rect.height === 0) && style.overflow !== 'visible') return false;
+                    }}
+                    return true;
+                }}
+
+                function getAccessibleName(el) {{
                     if (el.getAttribute('aria-label')) return el.getAttribute('aria-label');
                     const labelledBy = el.getAttribute('aria-labelledby');
                     if (labelledBy) {{
@@ -307,16 +2091,12 @@ impl BrowserAutomation {
                         if (label) return label.textContent.trim();
                     }}
                     if (el.placeholder) return el.placeholder;
-                    if (el.tagName === 'BUTTON' || el.tagName === 'A') {{
-                        return el.textContent.trim();
-                    }}
-                    return '';
+                    return el.textContent.trim().slice(0, 80) || null;
                 }}
 
                 function getRole(el) {{
                     const ariaRole = el.getAttribute('role');
                     if (ariaRole) return ariaRole;
-
                     const tagRoles = {{
                         'BUTTON': 'button',
                         'A': 'link',
@@ -324,99 +2104,266 @@ impl BrowserAutomation {
                         'TEXTAREA': 'textbox',
                         'SELECT': 'combobox',
                     }};
-                    return tagRoles[el.tagName] || '';
+                    return tagRoles[el.tagName] || 'generic';
                 }}
 
-                const targetRole = '{}';
-                const targetName = '{}';
-
-                const allElements = document.querySelectorAll('*');
-                for (let el of allElements) {{
-                    const role = getRole(el);
-                    const name = getAccessibleName(el);
+                const needle = query.toLowerCase();
+                const ranges = [];
+                const walker = document.createTreeWalker(document.body, NodeFilter.SHOW_TEXT);
+                while (walker.nextNode() && ranges.length < maxMatches) {{
+                    const node = walker.currentNode;
+                    if (!isVisible(node.parentElement)) continue;
+                    const haystack = node.textContent.toLowerCase();
+                    let index = haystack.indexOf(needle);
+                    while (index !== -1 && ranges.length < maxMatches) {{
+                        const range = document.createRange();
+                        range.setStart(node, index);
+                        range.setEnd(node, index + query.length);
+                        ranges.push(range);
+                        index = haystack.indexOf(needle, index + needle.length);
+                    }}
+                }}
 
-                    if (role === targetRole && (!targetName || name.includes(targetName))) {{
-                        // Mark element for retrieval
-                        el.setAttribute('data-element-id', Math.random().toString(36));
-                        return el.getAttribute('data-element-id');
+                // Highlight without touching the DOM (CSS Custom Highlight API)
+                if (window.CSS && CSS.highlights && window.Highlight) {{
+                    CSS.highlights.set('mcp-find-text', new Highlight(...ranges));
+                    if (!document.getElementById('mcp-find-text-style')) {{
+                        const style = document.createElement('style');
+                        style.id = 'mcp-find-text-style';
+                        style.textContent = '::highlight(mcp-find-text) {{ background: #ffeb3b; color: #000; }}';
+                        document.head.appendChild(style);
                     }}
                 }}
 
-                return null;
+                const interactive = [...document.querySelectorAll(
+                    'button, a[href], input, textarea, select, [role="button"], [role="link"], [role="tab"], [role="menuitem"], [role="checkbox"], [role="radio"]'
+                )].filter(isVisible);
+
+                const matches = ranges.map(range => {{
+                    const rect = range.getBoundingClientRect();
+                    const cx = rect.x + rect.width / 2;
+                    const cy = rect.y + rect.height / 2;
+                    const text = range.startContainer.textContent;
+                    const start = Math.max(0, range.startOffset - 40);
+                    const snippet = text.slice(start, range.endOffset + 40).replace(/\s+/g, ' ').trim();
+
+                    const nearby = interactive
+                        .map(el => {{
+                            const r = el.getBoundingClientRect();
+                            const dx = Math.max(r.left - cx, 0, cx - r.right);
+                            const dy = Math.max(r.top - cy, 0, cy - r.bottom);
+                            return {{ el, distance: Math.sqrt(dx * dx + dy * dy) }};
+                        }})
+                        .sort((a, b) => a.distance - b.distance)
+                        .slice(0, 3)
+                        .map(({{ el, distance }}) => ({{
+                            role: getRole(el),
+                            name: getAccessibleName(el),
+                            distance: Math.round(distance),
+                        }}));
+
+                    return {{ snippet, y: Math.round(rect.y + window.scrollY), nearby }};
+                }});
+
+                if (scrollToFirst && ranges.length > 0) {{
+                    const target = ranges[0].startContainer.parentElement;
+                    if (target) target.scrollIntoView({{ block: 'center' }});
+                }}
+
+                return {{ match_count: matches.length, truncated: matches.length >= maxMatches, matches }};
             }})()
             "#,
-            role, name
-        )
+            serde_json::to_string(query)?,
+            scroll_to_first
+        );
+
+        let result = page.evaluate(script).await?;
+        let details: serde_json::Value = result.into_value()?;
+
+        let match_count = details
+            .get("match_count")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+
+        if match_count == 0 {
+            let mut response = ActionResponse::error_with_suggestion(
+                ErrorCode::TextNotFound,
+                &format!("No visible text matching '{}'", query),
+                "try a shorter or different query, or scroll to load more content",
+            );
+            response.details = Some(details);
+            return Ok(response);
+        }
+
+        if scroll_to_first {
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+
+        Ok(ActionResponse::success_with_details(details))
     }
 
-    /// Build CSS selector from semantic selector (fallback)
-    fn build_css_selector(&self, selector: &SemanticSelector) -> String {
-        // Try to build a reasonable CSS selector based on role
-        match selector.role.as_str() {
-            "button" => {
-                if let Some(name) = &selector.name {
-                    format!("button:contains('{}'), [role='button']:contains('{}')", name, name)
-                } else {
-                    "button, [role='button']".to_string()
+    /// Zoom the page via CSS zoom so more (or larger) elements fit in the viewport.
+    /// The factor persists across navigations until reset to 1.0.
+    async fn set_zoom(&self, factor: f64) -> Result<ActionResponse> {
+        if let Some(feedback) = Self::invalid_zoom(factor) {
+            return Ok(feedback);
+        }
+
+        let page = self.page.read().await;
+        let mut zoom_script = self.zoom_script.write().await;
+
+        // Replace any previous persistent zoom
+        if let Some(identifier) = zoom_script.take() {
+            page.execute(RemoveScriptToEvaluateOnNewDocumentParams::new(identifier))
+                .await?;
+        }
+
+        let apply = format!(
+            "(() => {{ const apply = () => {{ if (document.documentElement) document.documentElement.style.zoom = '{}'; }}; apply(); document.addEventListener('DOMContentLoaded', apply); }})()",
+            if factor == 1.0 { String::new() } else { factor.to_string() }
+        );
+
+        page.evaluate(apply.as_str()).await?;
+
+        if factor != 1.0 {
+            *zoom_script = Some(page.evaluate_on_new_document(apply).await?);
+        }
+
+        // Wait for layout to settle
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        Ok(ActionResponse::success_with_details(serde_json::json!({ "zoom": factor })))
+    }
+
+    /// Find element using semantic selector (Solution B)
+    /// Element stamped with `token` in ELEMENT_ID_ATTRIBUTE
+    ///
+    /// querySelector stays in the top document's light DOM. Elsewhere the DOM
+    /// search finds it: it walks every frame and open shadow root, and a quoted
+    /// query matches whole attribute values.
+    async fn find_stamped(page: &Page, token: &str) -> Option<Element> {
+        if let Ok(element) = page.find_element(&format!("[{}='{}']", ELEMENT_ID_ATTRIBUTE, token)).await {
+            return Some(element);
+        }
+        let elements = page.find_xpaths(format!("\"{}\"", token)).await.unwrap_or_default();
+        elements.into_iter().next()
+    }
+
+    async fn find_element(
+        &self,
+        page: &Page,
+        selector: &SemanticSelector,
+    ) -> Result<Option<Element>> {
+        if let Some(element_id) = selector.element_id {
+            return self.find_mapped(page, element_id).await;
+        }
+
+        // Try CSS selector approach with semantic attributes
+        let js_script = find_element_script(selector);
+
+        let result = page.evaluate(js_script).await?;
+
+        // Check if result is null
+        if let Some(value) = result.value() {
+            if value.is_null() {
+                // Accessible names from Chrome can differ from the finder's simpler computation
+                let token = uuid::Uuid::new_v4().simple().to_string();
+                match ContextExtractor::stamp_ax_match(page, selector, ELEMENT_ID_ATTRIBUTE, &token).await {
+                    Ok(true) => return Ok(Self::find_stamped(page, &token).await),
+                    Ok(false) => {}
+                    Err(e) => tracing::debug!("Accessibility tree lookup failed: {}", e),
                 }
-            }
-            "link" => {
-                if let Some(name) = &selector.name {
-                    format!("a:contains('{}')", name)
-                } else {
-                    "a".to_string()
+
+                // Try CSS fallback if available
+                if let Some(css) = &selector.css_fallback {
+                    match page.find_element(css).await {
+                        Ok(el) => return Ok(Some(el)),
+                        Err(_) => return Ok(None),
+                    }
                 }
+                return Ok(None);
             }
-            "textbox" => "input[type='text'], input:not([type]), textarea, [role='textbox']".to_string(),
-            _ => {
-                if let Some(name) = &selector.name {
-                    format!("[role='{}']:contains('{}')", selector.role, name)
-                } else {
-                    format!("[role='{}']", selector.role)
-                }
+
+            // Get the element ID from JavaScript and find it
+            if let Some(element_id) = value.as_str() {
+                return Ok(Self::find_stamped(page, element_id).await);
             }
         }
-    }
 
-    /// Check if element is visible
-    async fn is_element_visible(&self, _page: &Page, element: &Element) -> Result<bool> {
-        let script = r#"
-            (function(el) {
-                if (!el) return false;
-                const style = window.getComputedStyle(el);
-                return style.display !== 'none' &&
-                       style.visibility !== 'hidden' &&
-                       style.opacity !== '0';
-            })(arguments[0])
-        "#;
+        // Fallback: try direct CSS selector
+        let css_selector = css_selector(selector);
+        match page.find_element(&css_selector).await {
+            Ok(el) => Ok(Some(el)),
+            Err(_) => Ok(None),
+        }
+    }
 
-        let result = element.call_js_fn(script, false).await?;
-        let value = serde_json::from_value::<bool>(result.result.value.clone().unwrap_or(serde_json::Value::Bool(false)))?;
-        Ok(value)
+    /// The element a context listed under `element_id`, if it is still on the page
+    async fn find_mapped(&self, page: &Page, element_id: usize) -> Result<Option<Element>> {
+        let Some(node) = self.element_map.read().await.node(element_id) else {
+            return Ok(None);
+        };
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        match ContextExtractor::stamp_node(page, node, ELEMENT_ID_ATTRIBUTE, &token).await? {
+            true => Ok(Self::find_stamped(page, &token).await),
+            false => Ok(None),
+        }
     }
 
-    /// Check if element is enabled
-    async fn is_element_enabled(&self, _page: &Page, element: &Element) -> Result<bool> {
-        let script = r#"
-            (function(el) {
-                if (!el) return false;
-                return !el.disabled;
-            })(arguments[0])
-        "#;
+    /// Feedback explaining why an element cannot be interacted with, if any
+    async fn check_interactable(element: &Element, selector: &SemanticSelector) -> Result<Option<ActionResponse>> {
+        let facts = ElementInspector::facts(element).await?;
+
+        if facts.hidden {
+            return Ok(Some(suggest::not_visible(selector, &facts)));
+        }
+
+        if facts.disabled {
+            return Ok(Some(suggest::not_enabled(selector, &facts)));
+        }
+
+        Ok(suggest::obstructed(selector, &facts))
+    }
 
-        let result = element.call_js_fn(script, false).await?;
-        let value = serde_json::from_value::<bool>(result.result.value.clone().unwrap_or(serde_json::Value::Bool(true)))?;
-        Ok(value)
+    /// Feedback for a selector that matched nothing, pointing at similarly named elements
+    async fn element_not_found(page: &Page, tool: &str, selector: &SemanticSelector) -> ActionResponse {
+        let elements = match selector.element_id {
+            None if !selector.role.is_empty() => ElementInspector::named_elements(page).await.unwrap_or_else(|e| {
+                tracing::debug!("Listing elements for suggestions failed: {}", e);
+                Vec::new()
+            }),
+            _ => Vec::new(),
+        };
+        suggest::not_found(tool, selector, &elements)
     }
 
     /// Get current page URL
-    #[allow(dead_code)] // Utility method for future use
     pub async fn get_url(&self) -> Result<String> {
         let page = self.page.read().await;
         let url = page.url().await?;
         Ok(url.map(|u| u.to_string()).unwrap_or_default())
     }
 
+    /// Start recording executed actions into a macro; `false` if a recording is already running
+    pub fn start_recording(&self, name: &str) -> bool {
+        let mut recording = self.recording.lock().unwrap();
+        if recording.is_some() {
+            return false;
+        }
+        *recording = Some(Recording::new(name));
+        true
+    }
+
+    /// End the running recording, if any
+    pub fn stop_recording(&self) -> Option<Recording> {
+        self.recording.lock().unwrap().take()
+    }
+
+    /// The session's open tabs, in the order they opened
+    pub async fn tabs(&self) -> Vec<TabInfo> {
+        self.tabs.list().await
+    }
+
     /// Get current page title
     #[allow(dead_code)] // Utility method for future use
     pub async fn get_title(&self) -> Result<String> {
@@ -429,4 +2376,257 @@ impl BrowserAutomation {
     pub async fn get_page(&self) -> Arc<RwLock<Page>> {
         Arc::clone(&self.page)
     }
+
+    /// Snapshot cookies and web storage of the current page
+    pub async fn storage_state(&self) -> Result<StorageState> {
+        let page = self.page.read().await;
+        StorageStateManager::capture(&page).await
+    }
+
+    /// localStorage and sessionStorage of the current page's origin
+    pub async fn web_storage(&self) -> Result<WebStorageContents> {
+        let page = self.page.read().await;
+        StorageStateManager::web_storage(&page).await
+    }
+
+    /// Set or (for `None` values) remove keys of one storage area; the inner error is the page's
+    pub async fn write_web_storage(
+        &self,
+        area: WebStorageArea,
+        items: &BTreeMap<String, Option<String>>,
+        clear: bool,
+    ) -> Result<std::result::Result<(), String>> {
+        let page = self.page.read().await;
+        StorageStateManager::write_web_storage(&page, area, items, clear).await
+    }
+
+    /// Cookies of every site and the current origin's localStorage, in Playwright's format
+    pub async fn export_storage_state(&self) -> Result<PlaywrightStorageState> {
+        let page = self.page.read().await;
+        StorageStateManager::export_playwright(&self.browser, &page).await
+    }
+
+    /// Apply a Playwright storage state and open `url` with it
+    pub async fn import_storage_state(&self, state: &PlaywrightStorageState, url: &str) -> Result<()> {
+        let page = self.page.read().await;
+        StorageStateManager::import_playwright(&page, state, url).await
+    }
+
+    /// Re-apply a previously captured storage state and reload the page
+    pub async fn restore_storage_state(&self, state: &StorageState) -> Result<()> {
+        let page = self.page.read().await;
+        StorageStateManager::apply(&page, state).await
+    }
+
+    /// Recently visited pages, oldest first
+    pub async fn navigation_history(&self) -> Vec<NavigationEntry> {
+        self.history.read().await.entries()
+    }
+
+    /// Dismiss consent/cookie banners according to `policy`
+    pub async fn dismiss_consent_banners(&self, policy: ConsentPolicy) -> Result<Vec<ConsentDismissal>> {
+        let page = self.page.read().await;
+        let dismissals = ConsentDismisser::dismiss(&page, policy).await?;
+
+        for dismissal in dismissals.iter().filter(|d| d.dismissed) {
+            self.events
+                .push(SessionEventKind::ConsentDismissed {
+                    vendor: dismissal.vendor.clone(),
+                    button: dismissal.button.clone(),
+                })
+                .await;
+        }
+
+        Ok(dismissals)
+    }
+
+    /// Close newsletter modals and chat widgets obstructing the viewport
+    pub async fn close_overlays(&self) -> Result<Vec<ClosedOverlay>> {
+        let page = self.page.read().await;
+        let mut closed_overlays = self.closed_overlays.write().await;
+
+        let next_id = closed_overlays.iter().map(|o| o.id + 1).max().unwrap_or(1);
+        let closed = OverlayCloser::close(&page, next_id).await?;
+
+        for overlay in &closed {
+            tracing::info!("Closed {:?} overlay '{}' (id {})", overlay.kind, overlay.label, overlay.id);
+            self.events
+                .push(SessionEventKind::OverlayClosed {
+                    overlay: overlay.clone(),
+                })
+                .await;
+        }
+
+        closed_overlays.extend(closed.iter().cloned());
+        Ok(closed)
+    }
+
+    /// Re-open an overlay closed by `close_overlays`
+    async fn restore_overlay(&self, overlay_id: u32) -> Result<ActionResponse> {
+        let page = self.page.read().await;
+        let mut closed_overlays = self.closed_overlays.write().await;
+
+        if !closed_overlays.iter().any(|o| o.id == overlay_id) {
+            return Ok(Self::unknown_overlay(overlay_id));
+        }
+
+        let restored = OverlayCloser::restore(&page, overlay_id).await?;
+        closed_overlays.retain(|o| o.id != overlay_id);
+
+        if !restored {
+            return Ok(ActionResponse::error_with_suggestion(
+                ErrorCode::OverlayNotFound,
+                &format!("Overlay {} is no longer on the page", overlay_id),
+                "the page may have navigated; try get_context() to see the current page",
+            ));
+        }
+
+        self.events
+            .push(SessionEventKind::OverlayRestored { overlay_id })
+            .await;
+
+        Ok(ActionResponse::success())
+    }
+
+    /// Ancestors, headings, siblings and nearby text of a context element
+    pub async fn element_neighborhood(&self, element_id: usize) -> Result<Option<ElementNeighborhood>> {
+        let page = self.page.read().await;
+        ContextExtractor::extract_neighborhood(&page, element_id).await
+    }
+
+    /// Watch for an element matching `selector` to appear or disappear
+    pub async fn subscribe_element(
+        &self,
+        selector: SemanticSelector,
+        webhook_url: Option<String>,
+    ) -> Result<ElementSubscription> {
+        self.watcher.subscribe(selector, webhook_url).await
+    }
+
+    /// Returns false if the subscription does not exist
+    pub async fn unsubscribe_element(&self, subscription_id: &str) -> Result<bool> {
+        self.watcher.unsubscribe(subscription_id).await
+    }
+
+    pub async fn element_subscriptions(&self) -> Vec<ElementSubscription> {
+        self.watcher.list().await
+    }
+
+    /// Read a table as normalized rows, following up to `max_pages` pages
+    pub async fn extract_table(
+        &self,
+        target: &TableTarget,
+        max_pages: u32,
+        next: Option<&SemanticSelector>,
+    ) -> Result<Option<ExtractedTable>> {
+        let page = self.page.read().await;
+        TableExtractor::extract(&page, target, max_pages, next).await
+    }
+
+    /// Forms on the page with their fields' labels, values and validation state
+    pub async fn forms(&self) -> Result<Vec<PageForm>> {
+        let page = self.page.read().await;
+        FormStateExtractor::extract(&page).await
+    }
+
+    /// Main content of the page as readable blocks, without navigation and footers
+    pub async fn readable_content(&self) -> Result<PageContent> {
+        let page = self.page.read().await;
+        ReadabilityExtractor::extract(&page).await
+    }
+
+    /// Structured markup, record-like groups and main text of the page, for schema extraction
+    pub async fn scrape_candidates(&self) -> Result<PageCandidates> {
+        let page = self.page.read().await;
+        Scraper::candidates(&page).await
+    }
+
+    /// Measure the interactive elements under assistive display settings
+    ///
+    /// Each setting is switched on, the elements are measured again and the
+    /// setting is switched off, so the page is left as it was.
+    pub async fn assistive_check(&self, settings: &[AssistiveSetting]) -> Result<Vec<AssistiveCheck>> {
+        let page = self.page.read().await;
+        let baseline = AssistiveEmulation::snapshot(&page).await?;
+
+        let mut checks = Vec::new();
+        for &setting in settings {
+            AssistiveEmulation::set(&page, setting, true).await?;
+            // Let the page re-render with the setting
+            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+            let emulated = AssistiveEmulation::snapshot(&page).await;
+            AssistiveEmulation::set(&page, setting, false).await?;
+
+            checks.push(AssistiveCheck::compare(setting, &baseline, &emulated?));
+        }
+
+        Ok(checks)
+    }
+
+    /// Files downloaded in this session
+    pub async fn downloads(&self) -> Vec<DownloadArtifact> {
+        match &self.downloads {
+            Some(downloads) => downloads.list().await,
+            None => Vec::new(),
+        }
+    }
+
+    /// Completed download and the path of its file
+    pub async fn download_artifact(&self, artifact_id: &str) -> Option<(DownloadArtifact, PathBuf)> {
+        self.downloads.as_ref()?.artifact(artifact_id).await
+    }
+
+    /// Copy the downloads completed since `since` into `dir`; the ones copied
+    pub async fn keep_downloads(&self, since: DateTime<Utc>, dir: &Path) -> Vec<DownloadArtifact> {
+        let Some(downloads) = &self.downloads else {
+            return Vec::new();
+        };
+
+        downloads.keep(since, dir).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to keep downloads in {}: {}", dir.display(), e);
+            Vec::new()
+        })
+    }
+
+    /// Session event log
+    pub fn events(&self) -> Arc<EventLog> {
+        Arc::clone(&self.events)
+    }
+
+    /// Console messages and uncaught exceptions of the session's pages
+    pub fn console(&self) -> &ConsoleLog {
+        &self.console
+    }
+
+    /// Request interception rules; `None` when the browser refused request interception
+    pub fn network_rules(&self) -> Option<&NetworkRules> {
+        self.http_auth.as_ref().map(|_| &*self.network_rules)
+    }
+
+    /// Start pausing the page's requests if rules, tunnels or credentials now need it
+    pub async fn intercept_requests(&self) -> Result<()> {
+        match &self.http_auth {
+            Some(http_auth) => http_auth.intercept().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Extract UI context including session-level information (navigation breadcrumb)
+    pub async fn extract_context(&self) -> Result<UIContext> {
+        self.hygiene.sweep(&*self.page.read().await).await;
+
+        let (mut context, nodes) = ContextExtractor::extract_with_nodes(self.get_page().await).await?;
+        *self.element_map.write().await = ElementMap::new(&context.elements, nodes);
+        context.navigation_history = self.navigation_history().await;
+        context.closed_overlays = self.closed_overlays.read().await.clone();
+        if self.tabs.count() > 1 {
+            context.tabs = self.tabs.list().await;
+        }
+        Ok(context)
+    }
+
+    /// Context elements nested under the containers holding them
+    pub async fn extract_context_tree(&self) -> Result<Vec<AXElement>> {
+        ContextExtractor::extract_tree(&*self.page.read().await).await
+    }
 }
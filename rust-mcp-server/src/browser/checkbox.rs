@@ -0,0 +1,47 @@
+use anyhow::Result;
+use chromiumoxide::element::Element;
+use serde::Deserialize;
+
+/// Checked state of a checkbox, radio button or switch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct CheckState {
+    /// `None` for the mixed (indeterminate) state
+    pub checked: Option<bool>,
+
+    /// Radio buttons can only be unchecked by checking another one in their group
+    pub radio: bool,
+}
+
+/// Read the checked state of a native or ARIA checkbox, radio button or switch
+///
+/// `None` if the element is none of these.
+pub async fn check_state(element: &Element) -> Result<Option<CheckState>> {
+    let result = element
+        .call_js_fn(
+            r#"function() {
+                const type = (this.type || '').toLowerCase();
+                if (this.tagName === 'INPUT' && (type === 'checkbox' || type === 'radio')) {
+                    return JSON.stringify({ checked: this.indeterminate ? null : this.checked, radio: type === 'radio' });
+                }
+
+                const role = this.getAttribute('role');
+                if (['checkbox', 'radio', 'switch', 'menuitemcheckbox', 'menuitemradio'].includes(role)) {
+                    const checked = this.getAttribute('aria-checked');
+                    return JSON.stringify({
+                        checked: checked === 'mixed' ? null : checked === 'true',
+                        radio: role === 'radio' || role === 'menuitemradio',
+                    });
+                }
+                return JSON.stringify(null);
+            }"#,
+            false,
+        )
+        .await?;
+
+    let json = result
+        .result
+        .value
+        .and_then(|value| value.as_str().map(str::to_string))
+        .ok_or_else(|| anyhow::anyhow!("checked state lookup returned no result"))?;
+    Ok(serde_json::from_str(&json)?)
+}
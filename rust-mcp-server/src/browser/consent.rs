@@ -0,0 +1,194 @@
+use anyhow::Result;
+use chromiumoxide::page::Page;
+use serde::{Deserialize, Serialize};
+
+/// How to answer consent/cookie banners before the agent starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentPolicy {
+    /// Leave banners alone (the agent sees them like any other element)
+    Off,
+    /// Click "Accept all" / "Agree" style buttons
+    Accept,
+    /// Click "Reject all" / "Necessary only" style buttons, falling back to closing the banner
+    Reject,
+}
+
+impl ConsentPolicy {
+    /// Read CONSENT_POLICY (off | accept | reject), defaulting to off
+    pub fn from_env() -> Self {
+        match std::env::var("CONSENT_POLICY")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "accept" => Self::Accept,
+            "reject" => Self::Reject,
+            _ => Self::Off,
+        }
+    }
+}
+
+/// A consent banner that was detected (and possibly dismissed)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsentDismissal {
+    /// Detected vendor (e.g. "onetrust", "cookiebot") or "generic"
+    pub vendor: String,
+
+    /// Text of the button that was clicked
+    pub button: Option<String>,
+
+    /// Whether the banner was actually dismissed
+    pub dismissed: bool,
+
+    /// Why the banner could not be dismissed (e.g. cross-origin iframe)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Detects and dismisses common GDPR consent dialogs
+pub struct ConsentDismisser;
+
+impl ConsentDismisser {
+    /// Run a single dismissal pass over the page
+    pub async fn dismiss(page: &Page, policy: ConsentPolicy) -> Result<Vec<ConsentDismissal>> {
+        if policy == ConsentPolicy::Off {
+            return Ok(Vec::new());
+        }
+
+        let script = Self::build_dismiss_script(policy);
+        let result = page.evaluate(script).await?;
+        let dismissals: Vec<ConsentDismissal> = serde_json::from_value(result.into_value()?)?;
+
+        for dismissal in &dismissals {
+            if dismissal.dismissed {
+                tracing::info!(
+                    "Dismissed consent banner ({}) via '{}'",
+                    dismissal.vendor,
+                    dismissal.button.as_deref().unwrap_or("close")
+                );
+            } else {
+                tracing::info!(
+                    "Detected consent banner ({}) but could not dismiss it: {}",
+                    dismissal.vendor,
+                    dismissal.note.as_deref().unwrap_or("no matching button")
+                );
+            }
+        }
+
+        if dismissals.iter().any(|d| d.dismissed) {
+            // Give the page a moment to remove the banner and reflow
+            tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+        }
+
+        Ok(dismissals)
+    }
+
+    /// Build JavaScript that finds consent containers and clicks the policy's button
+    fn build_dismiss_script(policy: ConsentPolicy) -> String {
+        let mode = match policy {
+            ConsentPolicy::Reject => "reject",
+            _ => "accept",
+        };
+
+        format!(
+            r#"
+            (() => {{
+                const mode = '{}';
+
+                // Known consent-management vendors and their containers
+                const vendors = [
+                    {{ vendor: 'onetrust', selector: '#onetrust-banner-sdk, #onetrust-consent-sdk' }},
+                    {{ vendor: 'cookiebot', selector: '#CybotCookiebotDialog' }},
+                    {{ vendor: 'quantcast', selector: '.qc-cmp2-container' }},
+                    {{ vendor: 'didomi', selector: '#didomi-host, #didomi-popup' }},
+                    {{ vendor: 'usercentrics', selector: '#usercentrics-root' }},
+                    {{ vendor: 'trustarc', selector: '#truste-consent-track, .truste_box_overlay' }},
+                    {{ vendor: 'osano', selector: '.osano-cm-window' }},
+                    {{ vendor: 'cookieyes', selector: '.cky-consent-container' }},
+                ];
+                const vendorFrames = ['consent', 'cmp', 'sp_message_iframe', 'privacy-mgmt', 'trustarc', 'cookielaw'];
+
+                const acceptWords = ['accept all', 'accept', 'agree', 'allow all', 'allow', 'i agree', 'got it', 'ok', 'consent'];
+                const rejectWords = ['reject all', 'reject', 'decline', 'deny', 'refuse', 'necessary only', 'only necessary', 'essential only'];
+                const closeWords = ['close', 'dismiss', 'x', '×'];
+                const consentHints = /cookie|consent|gdpr|privacy|tracking/i;
+
+                function isVisible(el) {{
+                    const style = window.getComputedStyle(el);
+                    const rect = el.getBoundingClientRect();
+                    return style.display !== 'none' &&
+                           style.visibility !== 'hidden' &&
+                           rect.width > 0 && rect.height > 0;
+                }}
+
+                function buttonText(el) {{
+                    return (el.getAttribute('aria-label') || el.textContent || el.value || '').trim();
+                }}
+
+                function findButton(container, words) {{
+                    const buttons = container.querySelectorAll('button, [role="button"], a, input[type="button"], input[type="submit"]');
+                    for (const word of words) {{
+                        for (const btn of buttons) {{
+                            const text = buttonText(btn).toLowerCase();
+                            if (isVisible(btn) && (text === word || text.startsWith(word + ' ') || (word.length > 3 && text.includes(word)))) {{
+                                return btn;
+                            }}
+                        }}
+                    }}
+                    return null;
+                }}
+
+                function dismiss(container, vendor) {{
+                    const primary = mode === 'reject' ? rejectWords : acceptWords;
+                    let btn = findButton(container, primary);
+                    if (!btn && mode === 'reject') btn = findButton(container, closeWords);
+                    if (!btn) {{
+                        return {{ vendor, button: null, dismissed: false, note: 'no matching button' }};
+                    }}
+                    const text = buttonText(btn);
+                    btn.click();
+                    return {{ vendor, button: text, dismissed: true, note: null }};
+                }}
+
+                const results = [];
+                const handled = new Set();
+
+                for (const {{ vendor, selector }} of vendors) {{
+                    const container = document.querySelector(selector);
+                    if (container && isVisible(container)) {{
+                        handled.add(container);
+                        results.push(dismiss(container, vendor));
+                    }}
+                }}
+
+                // Generic dialogs mentioning cookies/consent
+                const dialogs = document.querySelectorAll('[role="dialog"], [role="alertdialog"], [aria-modal="true"], [id*="cookie" i], [class*="cookie" i], [id*="consent" i], [class*="consent" i]');
+                for (const dialog of dialogs) {{
+                    if (handled.has(dialog) || !isVisible(dialog)) continue;
+                    if ([...handled].some(h => h.contains(dialog) || dialog.contains(h))) continue;
+                    if (!consentHints.test(dialog.textContent || '')) continue;
+                    handled.add(dialog);
+                    results.push(dismiss(dialog, 'generic'));
+                }}
+
+                // Vendor iframes: same-origin ones can be handled, cross-origin ones are only reported
+                for (const frame of document.querySelectorAll('iframe')) {{
+                    const src = (frame.getAttribute('src') || '') + ' ' + (frame.id || '') + ' ' + (frame.name || '');
+                    if (!vendorFrames.some(v => src.toLowerCase().includes(v)) || !isVisible(frame)) continue;
+                    try {{
+                        const doc = frame.contentDocument;
+                        if (!doc) throw new Error('cross-origin');
+                        results.push(dismiss(doc.body, 'iframe'));
+                    }} catch (e) {{
+                        results.push({{ vendor: 'iframe', button: null, dismissed: false, note: 'cross-origin consent iframe' }});
+                    }}
+                }}
+
+                return results;
+            }})()
+            "#,
+            mode
+        )
+    }
+}
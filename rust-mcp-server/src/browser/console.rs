@@ -0,0 +1,193 @@
+use anyhow::Result;
+use chromiumoxide::cdp::js_protocol::runtime::{EventConsoleApiCalled, EventExceptionThrown, RemoteObject, StackTrace};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::models::{ConsoleEntry, SessionEventKind};
+
+use super::EventLog;
+
+/// Longest console text kept per entry; pages sometimes log whole documents
+const MAX_TEXT_CHARS: usize = 2000;
+
+/// Bounded per-session buffer of console messages and uncaught exceptions
+pub struct ConsoleLog {
+    /// Each with its sequence number in the session, counting dropped entries
+    entries: RwLock<VecDeque<(u64, ConsoleEntry)>>,
+    capacity: usize,
+}
+
+impl ConsoleLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: RwLock::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// Create log sized from CONSOLE_LOG_SIZE (default: 200)
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("CONSOLE_LOG_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(200);
+
+        Self::new(capacity)
+    }
+
+    pub async fn push(&self, entry: ConsoleEntry) {
+        let mut entries = self.entries.write().await;
+        let seq = entries.back().map_or(0, |(seq, _)| seq + 1);
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        if self.capacity > 0 {
+            entries.push_back((seq, entry));
+        }
+    }
+
+    /// Entries currently retained with their sequence numbers, oldest first
+    pub async fn recent(&self) -> Vec<(u64, ConsoleEntry)> {
+        self.entries.read().await.iter().cloned().collect()
+    }
+
+    /// Sequence number the next entry will get
+    pub async fn mark(&self) -> u64 {
+        self.entries.read().await.back().map_or(0, |(seq, _)| seq + 1)
+    }
+
+    /// Errors and exceptions logged from `mark` on
+    pub async fn errors_since(&self, mark: u64) -> Vec<ConsoleEntry> {
+        self.entries
+            .read()
+            .await
+            .iter()
+            .filter(|(seq, entry)| *seq >= mark && entry.is_error())
+            .map(|(_, entry)| entry.clone())
+            .collect()
+    }
+}
+
+/// Buffer the page's `console.*` calls and uncaught exceptions, and send them to live subscribers
+pub async fn forward_console(page: &Page, events: Arc<EventLog>, log: Arc<ConsoleLog>) -> Result<()> {
+    let mut calls = page.event_listener::<EventConsoleApiCalled>().await?;
+    let mut exceptions = page.event_listener::<EventExceptionThrown>().await?;
+
+    tokio::spawn(async move {
+        loop {
+            let entry = tokio::select! {
+                Some(call) = calls.next() => console_entry(&call),
+                Some(thrown) = exceptions.next() => exception_entry(&thrown),
+                else => break,
+            };
+
+            if events.has_subscribers() {
+                events.broadcast(SessionEventKind::ConsoleMessage {
+                    level: entry.level.clone(),
+                    text: entry.text.clone(),
+                });
+            }
+            log.push(entry).await;
+        }
+    });
+
+    Ok(())
+}
+
+fn console_entry(call: &EventConsoleApiCalled) -> ConsoleEntry {
+    let (url, line) = top_frame(call.stack_trace.as_ref());
+    ConsoleEntry {
+        timestamp: chrono::Utc::now(),
+        level: call.r#type.as_ref().to_string(),
+        text: truncate(console_text(&call.args)),
+        url,
+        line,
+    }
+}
+
+fn exception_entry(thrown: &EventExceptionThrown) -> ConsoleEntry {
+    let details = &thrown.exception_details;
+    // `text` is only "Uncaught"; the error's own description carries the message
+    let message = details
+        .exception
+        .as_ref()
+        .and_then(|exception| exception.description.as_deref())
+        .and_then(|description| description.lines().next())
+        .map(|message| format!("{} {}", details.text, message))
+        .unwrap_or_else(|| details.text.clone());
+    let (url, line) = match &details.url {
+        Some(url) if !url.is_empty() => (Some(url.clone()), Some(details.line_number + 1)),
+        _ => top_frame(details.stack_trace.as_ref()),
+    };
+
+    ConsoleEntry {
+        timestamp: chrono::Utc::now(),
+        level: ConsoleEntry::EXCEPTION.to_string(),
+        text: truncate(message),
+        url,
+        line,
+    }
+}
+
+/// Script and 1-based line of the innermost frame that has a URL
+fn top_frame(stack: Option<&StackTrace>) -> (Option<String>, Option<i64>) {
+    stack
+        .and_then(|stack| stack.call_frames.iter().find(|frame| !frame.url.is_empty()))
+        .map_or((None, None), |frame| (Some(frame.url.clone()), Some(frame.line_number + 1)))
+}
+
+fn truncate(text: String) -> String {
+    match text.char_indices().nth(MAX_TEXT_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+/// Arguments joined with spaces, strings unquoted, as DevTools prints them
+fn console_text(args: &[RemoteObject]) -> String {
+    args.iter()
+        .map(|arg| match &arg.value {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            Some(value) => value.to_string(),
+            None => arg
+                .description
+                .clone()
+                .unwrap_or_else(|| arg.r#type.as_ref().to_string()),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: &str, text: &str) -> ConsoleEntry {
+        ConsoleEntry {
+            timestamp: chrono::Utc::now(),
+            level: level.to_string(),
+            text: text.to_string(),
+            url: None,
+            line: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_errors_since() {
+        let log = ConsoleLog::new(3);
+        log.push(entry("error", "before")).await;
+        let mark = log.mark().await;
+        log.push(entry("log", "clicked")).await;
+        log.push(entry(ConsoleEntry::EXCEPTION, "Uncaught TypeError: cart is undefined")).await;
+        log.push(entry("warning", "deprecated")).await;
+
+        let errors: Vec<String> = log.errors_since(mark).await.into_iter().map(|e| e.text).collect();
+        assert_eq!(errors, vec!["Uncaught TypeError: cart is undefined"]);
+        // The oldest entry was dropped, but sequence numbers keep counting
+        assert_eq!(log.recent().await.first().map(|(seq, _)| *seq), Some(1));
+        assert_eq!(log.mark().await, 4);
+    }
+}
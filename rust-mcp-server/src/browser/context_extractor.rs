@@ -1,18 +1,293 @@
 use anyhow::{Context as AnyhowContext, Result};
+use chromiumoxide::cdp::browser_protocol::accessibility::{
+    AxNode, AxPropertyName, AxValue, GetFullAxTreeParams, QueryAxTreeParams,
+};
+use chromiumoxide::cdp::browser_protocol::dom::{
+    BackendNodeId, GetDocumentParams, Node, PushNodesByBackendIdsToFrontendParams, SetAttributeValueParams,
+};
 use chromiumoxide::page::Page;
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use ui_core::simplify::simplify_tree;
 
 use crate::models::{
-    AXElement, ElementRect, SimplifiedElement, UIContext, Viewport,
+    AXElement, AriaStates, DateOrder, ElementNeighborhood, ElementRect, FocusedElement, SemanticSelector,
+    UIContext, Viewport,
 };
 
+/// Stamped with the context ID while collected elements are matched to accessibility nodes
+pub(crate) const AX_ID_ATTRIBUTE: &str = "data-mcp-ax-id";
+
+/// Chrome roles that say nothing beyond what the element's tag already told us
+const UNINFORMATIVE_ROLES: &[&str] = &["", "generic", "none", "presentation"];
+
+/// Element id of the overlay holding Set-of-Mark boxes
+const SET_OF_MARK_LAYER_ID: &str = "__mcp_set_of_mark";
+
+/// JavaScript helpers shared by every script that needs to agree on element IDs.
+///
+/// `collectElements()` returns `[{ el, role, name, frame }]` in the same order
+/// the extractor assigns `AXElement.id`, so an ID from `get_context` can be
+/// resolved back to a DOM node. Elements of same-origin iframes come after the
+/// top document's, with `frame` holding the path of iframe indices to them.
+pub(crate) const COLLECT_ELEMENTS_JS: &str = r#"
+            // Helper to check if element is visible
+            function isVisible(el) {
+                if (!el) return false;
+                const style = el.ownerDocument.defaultView.getComputedStyle(el);
+                return style.display !== 'none' &&
+                       style.visibility !== 'hidden' &&
+                       style.opacity !== '0';
+            }
+
+            // Helper to get accessible name
+            function getAccessibleName(el) {
+                // Try aria-label first
+                if (el.getAttribute('aria-label')) {
+                    return el.getAttribute('aria-label');
+                }
+                // Try aria-labelledby
+                const labelledBy = el.getAttribute('aria-labelledby');
+                if (labelledBy) {
+                    const label = el.getRootNode().getElementById(labelledBy);
+                    if (label) return label.textContent.trim();
+                }
+                // Try associated label
+                if (el.id) {
+                    const label = el.getRootNode().querySelector(`label[for="${el.id}"]`);
+                    if (label) return label.textContent.trim();
+                }
+                // Try placeholder for inputs
+                if (el.placeholder) return el.placeholder;
+                // Try text content for buttons/links
+                if (el.tagName === 'BUTTON' || el.tagName === 'A') {
+                    return el.textContent.trim();
+                }
+                // Try value for inputs
+                if (el.value && el.type !== 'password') return el.value;
+
+                return null;
+            }
+
+            // Helper to get role
+            function getRole(el) {
+                // Explicit ARIA role
+                const ariaRole = el.getAttribute('role');
+                if (ariaRole) return ariaRole;
+
+                // Implicit roles based on tag
+                const tagRoles = {
+                    'BUTTON': 'button',
+                    'A': 'link',
+                    'INPUT': el.type === 'submit' ? 'button' : 'textbox',
+                    'TEXTAREA': 'textbox',
+                    'SELECT': 'combobox',
+                    'H1': 'heading',
+                    'H2': 'heading',
+                    'H3': 'heading',
+                    'IMG': 'img',
+                    'NAV': 'navigation',
+                    'MAIN': 'main',
+                    'HEADER': 'banner',
+                    'FOOTER': 'contentinfo',
+                    'SECTION': 'region',
+                    'FORM': 'form',
+                };
+
+                return tagRoles[el.tagName] || 'generic';
+            }
+
+            // Helper to get WAI-ARIA states
+            function getStates(el) {
+                function boolAttr(name) {
+                    const v = el.getAttribute(name);
+                    if (v === 'true') return true;
+                    if (v === 'false') return false;
+                    return null;
+                }
+                function tokenAttr(name) {
+                    const v = el.getAttribute(name);
+                    return (v === null || v === '' || v === 'undefined') ? null : v;
+                }
+
+                let checked = tokenAttr('aria-checked');
+                if (checked === null && el.tagName === 'INPUT' &&
+                    (el.type === 'checkbox' || el.type === 'radio')) {
+                    checked = el.indeterminate ? 'mixed' : String(el.checked);
+                }
+
+                let selected = boolAttr('aria-selected');
+                if (selected === null && el.tagName === 'OPTION') {
+                    selected = el.selected;
+                }
+
+                let current = tokenAttr('aria-current');
+                if (current === 'false') current = null;
+
+                // aria-invalid also takes "grammar" and "spelling"
+                let invalid = tokenAttr('aria-invalid');
+                invalid = invalid === null ? null : invalid !== 'false';
+                if (invalid === null && el.willValidate) {
+                    try {
+                        if (el.matches(':user-invalid')) invalid = true;
+                    } catch (e) {}
+                }
+
+                return {
+                    expanded: boolAttr('aria-expanded'),
+                    selected,
+                    checked,
+                    pressed: tokenAttr('aria-pressed'),
+                    current,
+                    invalid,
+                };
+            }
+
+            // Current value of fields, selects and sliders; never a password in clear
+            function getValue(el) {
+                if (el.tagName === 'TEXTAREA' || el.tagName === 'SELECT') return el.value || null;
+                if (el.tagName !== 'INPUT') {
+                    return el.getAttribute('aria-valuetext') || el.getAttribute('aria-valuenow');
+                }
+                if (['checkbox', 'radio', 'button', 'submit', 'reset', 'image', 'file', 'hidden'].includes(el.type)) {
+                    return null;
+                }
+                if (el.type === 'password') return el.value ? '********' : null;
+                return el.value || null;
+            }
+
+            // Get bounding rect, relative to the top window even inside iframes
+            function getRect(el) {
+                const rect = el.getBoundingClientRect();
+                let x = rect.x, y = rect.y;
+                for (let view = el.ownerDocument.defaultView; view && view.frameElement; view = view.parent) {
+                    const frame = view.frameElement;
+                    const frameRect = frame.getBoundingClientRect();
+                    x += frameRect.x + frame.clientLeft;
+                    y += frameRect.y + frame.clientTop;
+                }
+                return {
+                    x,
+                    y,
+                    width: rect.width,
+                    height: rect.height
+                };
+            }
+
+            // Elements of `root` matching `selector`, including those in open shadow
+            // roots, in tree order with each shadow tree right after its host
+            function deepQuerySelectorAll(root, selector) {
+                const found = [];
+                root.querySelectorAll('*').forEach(el => {
+                    if (el.matches(selector)) found.push(el);
+                    if (el.shadowRoot) {
+                        for (const inner of deepQuerySelectorAll(el.shadowRoot, selector)) found.push(inner);
+                    }
+                });
+                return found;
+            }
+
+            // Parent element, stepping out of a shadow root to its host
+            function parentOf(el) {
+                if (el.parentElement) return el.parentElement;
+                const root = el.getRootNode();
+                return root instanceof ShadowRoot ? root.host : null;
+            }
+
+            // The top document and every same-origin iframe document, each with
+            // its path of iframe indices; cross-origin frames can't be read
+            function frameDocuments(doc = document, frame = []) {
+                const documents = [{ doc, frame }];
+                deepQuerySelectorAll(doc, 'iframe, frame').forEach((el, i) => {
+                    let inner = null;
+                    try {
+                        inner = el.contentDocument;
+                    } catch (e) {}
+                    if (inner && inner.documentElement) {
+                        documents.push(...frameDocuments(inner, [...frame, i]));
+                    }
+                });
+                return documents;
+            }
+
+            // Document at an iframe path, or null if a frame is missing or cross-origin
+            function frameDocument(frame) {
+                let doc = document;
+                for (const i of frame) {
+                    const el = deepQuerySelectorAll(doc, 'iframe, frame')[i];
+                    try {
+                        doc = el ? el.contentDocument : null;
+                    } catch (e) {
+                        doc = null;
+                    }
+                    if (!doc) return null;
+                }
+                return doc;
+            }
+
+            // Collect interactive and semantic elements
+            const selectors = [
+                'button',
+                'a[href]',
+                'input',
+                'textarea',
+                'select',
+                '[role="button"]',
+                '[role="link"]',
+                '[role="textbox"]',
+                '[role="combobox"]',
+                '[role="checkbox"]',
+                '[role="radio"]',
+                '[role="tab"]',
+                '[role="menuitem"]',
+                '[role="switch"]',
+                '[role="option"]',
+                '[aria-expanded]',
+                '[aria-pressed]',
+                '[aria-current]',
+                '[aria-invalid="true"]',
+                '[role="alert"]',
+                '[role="status"]',
+                'h1', 'h2', 'h3', 'h4', 'h5', 'h6',
+                '[aria-label]',
+            ];
+
+            const LIVE_ROLES = ['alert', 'status'];
+
+            function collectElements() {
+                const collected = [];
+                frameDocuments().forEach(({ doc, frame }) => {
+                    deepQuerySelectorAll(doc, selectors.join(',')).forEach(el => {
+                        const role = getRole(el);
+                        // Messages in live regions are named by their text
+                        const name = getAccessibleName(el) ||
+                            (LIVE_ROLES.includes(role) ? el.textContent.trim().slice(0, 200) || null : null);
+
+                        // Only include if it has a name or is a heading
+                        if (name || role === 'heading') {
+                            collected.push({ el, role, name, frame });
+                        }
+                    });
+                });
+                return collected;
+            }
+"#;
+
 /// Extract UI context from page using Accessibility Tree (Solution A)
 pub struct ContextExtractor;
 
 impl ContextExtractor {
     /// Extract full UI context from page
     pub async fn extract(page: Arc<RwLock<Page>>) -> Result<UIContext> {
+        Ok(Self::extract_with_nodes(page).await?.0)
+    }
+
+    /// Extract the UI context and the DOM node of each element, by element ID
+    ///
+    /// The map is empty where Chrome doesn't report the DOM.
+    pub async fn extract_with_nodes(page: Arc<RwLock<Page>>) -> Result<(UIContext, HashMap<usize, BackendNodeId>)> {
         let page_guard = page.read().await;
 
         // Get basic page info
@@ -28,17 +303,80 @@ impl ContextExtractor {
         let viewport = Self::extract_viewport(&page_guard).await?;
 
         // Get accessibility tree
-        let ax_tree = Self::extract_ax_tree(&page_guard).await?;
+        let (ax_tree, nodes) = Self::extract_ax_tree(&page_guard).await?;
+
+        // Page language decides how ambiguous dates like 03/04/2025 are read
+        let date_order = DateOrder::from_lang(&Self::page_lang(&page_guard).await?);
 
         // Simplify for LLM consumption
-        let elements = Self::simplify_tree(&ax_tree, &viewport);
+        let mut elements = simplify_tree(&ax_tree, &viewport, date_order);
 
-        Ok(UIContext {
+        let focused = Self::focused_element(&page_guard).await?;
+        if let Some(id) = focused.as_ref().and_then(|f| f.element_id) {
+            if let Some(element) = elements.iter_mut().find(|e| e.id == id) {
+                element.display.push_str(" [focused]");
+            }
+        }
+
+        let context = UIContext {
             url,
             title,
             viewport,
             elements,
-        })
+            navigation_history: Vec::new(),
+            closed_overlays: Vec::new(),
+            tabs: Vec::new(),
+            focused,
+        };
+        Ok((context, nodes))
+    }
+
+    /// Element that has keyboard focus, descending into open shadow roots
+    pub async fn focused_element(page: &Page) -> Result<Option<FocusedElement>> {
+        let script = format!(
+            r#"
+            (() => {{
+                {}
+
+                let el = document.activeElement;
+                while (el) {{
+                    let inner = null;
+                    if (el.shadowRoot) {{
+                        inner = el.shadowRoot.activeElement;
+                    }} else if (el.tagName === 'IFRAME' || el.tagName === 'FRAME') {{
+                        try {{
+                            inner = el.contentDocument && el.contentDocument.activeElement;
+                        }} catch (e) {{}}
+                    }}
+                    if (!inner || inner === inner.ownerDocument.body) break;
+                    el = inner;
+                }}
+                if (!el || el === el.ownerDocument.body || el === el.ownerDocument.documentElement) return null;
+
+                const id = collectElements().findIndex(c => c.el === el);
+                const text = (el.innerText || '').replace(/\s+/g, ' ').trim();
+                return {{
+                    element_id: id >= 0 ? id : null,
+                    role: getRole(el),
+                    name: getAccessibleName(el) || (text ? text.slice(0, 80) : null),
+                    in_modal: !!el.closest('[aria-modal="true"]') ||
+                        !!(el.closest('dialog') && el.closest('dialog').matches(':modal')),
+                }};
+            }})()
+            "#,
+            COLLECT_ELEMENTS_JS
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(serde_json::from_value(result.into_value()?)?)
+    }
+
+    /// Language of the page (`<html lang>`, else the browser's), e.g. "en-US"
+    pub async fn page_lang(page: &Page) -> Result<String> {
+        let result = page
+            .evaluate("document.documentElement.lang || navigator.language || ''")
+            .await?;
+        Ok(result.into_value::<String>().unwrap_or_default())
     }
 
     /// Extract viewport information
@@ -86,136 +424,73 @@ impl ContextExtractor {
     }
 
     /// Extract accessibility tree from page
-    async fn extract_ax_tree(page: &Page) -> Result<Vec<AXElement>> {
-        // Use JavaScript-based approach to extract semantic elements
-        let script = r#"
-            (() => {
-                const elements = [];
-                let id = 0;
-
-                // Helper to check if element is visible
-                function isVisible(el) {
-                    if (!el) return false;
-                    const style = window.getComputedStyle(el);
-                    return style.display !== 'none' &&
-                           style.visibility !== 'hidden' &&
-                           style.opacity !== '0';
-                }
-
-                // Helper to get accessible name
-                function getAccessibleName(el) {
-                    // Try aria-label first
-                    if (el.getAttribute('aria-label')) {
-                        return el.getAttribute('aria-label');
-                    }
-                    // Try aria-labelledby
-                    const labelledBy = el.getAttribute('aria-labelledby');
-                    if (labelledBy) {
-                        const label = document.getElementById(labelledBy);
-                        if (label) return label.textContent.trim();
-                    }
-                    // Try associated label
-                    if (el.id) {
-                        const label = document.querySelector(`label[for="${el.id}"]`);
-                        if (label) return label.textContent.trim();
-                    }
-                    // Try placeholder for inputs
-                    if (el.placeholder) return el.placeholder;
-                    // Try text content for buttons/links
-                    if (el.tagName === 'BUTTON' || el.tagName === 'A') {
-                        return el.textContent.trim();
-                    }
-                    // Try value for inputs
-                    if (el.value) return el.value;
+    ///
+    /// Elements are collected by `collectElements()`, so IDs stay the ones every
+    /// other script resolves. Role, name, description, disabled and the ARIA
+    /// states then come from Chrome's accessibility tree (`Accessibility.getFullAXTree`),
+    /// which implements the full name computation. If that fails, the
+    /// JavaScript heuristics are kept.
+    async fn extract_ax_tree(page: &Page) -> Result<(Vec<AXElement>, HashMap<usize, BackendNodeId>)> {
+        let mut elements = Self::extract_js_ax_tree(page).await?;
 
-                    return null;
+        let mut stamped = HashMap::new();
+        match page.execute(GetDocumentParams::builder().depth(-1).pierce(true).build()).await {
+            Ok(document) => {
+                collect_stamped_nodes(&document.result.root, &mut stamped);
+                if let Err(e) = Self::apply_cdp_ax_tree(page, &mut elements, &stamped).await {
+                    tracing::debug!("CDP accessibility tree unavailable, using page heuristics: {}", e);
                 }
+            }
+            Err(e) => tracing::debug!("DOM unavailable, using page heuristics: {}", e),
+        }
 
-                // Helper to get role
-                function getRole(el) {
-                    // Explicit ARIA role
-                    const ariaRole = el.getAttribute('role');
-                    if (ariaRole) return ariaRole;
-
-                    // Implicit roles based on tag
-                    const tagRoles = {
-                        'BUTTON': 'button',
-                        'A': 'link',
-                        'INPUT': el.type === 'submit' ? 'button' : 'textbox',
-                        'TEXTAREA': 'textbox',
-                        'SELECT': 'combobox',
-                        'H1': 'heading',
-                        'H2': 'heading',
-                        'H3': 'heading',
-                        'IMG': 'img',
-                        'NAV': 'navigation',
-                        'MAIN': 'main',
-                        'HEADER': 'banner',
-                        'FOOTER': 'contentinfo',
-                        'SECTION': 'region',
-                        'FORM': 'form',
-                    };
-
-                    return tagRoles[el.tagName] || 'generic';
-                }
+        let cleanup = format!(
+            r#"
+            (() => {{
+                {}
 
-                // Get bounding rect
-                function getRect(el) {
-                    const rect = el.getBoundingClientRect();
-                    return {
-                        x: rect.x,
-                        y: rect.y,
-                        width: rect.width,
-                        height: rect.height
-                    };
-                }
+                frameDocuments().forEach(({{ doc }}) =>
+                    deepQuerySelectorAll(doc, '[{attr}]').forEach(el => el.removeAttribute('{attr}')));
+            }})()
+            "#,
+            COLLECT_ELEMENTS_JS,
+            attr = AX_ID_ATTRIBUTE
+        );
+        if let Err(e) = page.evaluate(cleanup).await {
+            tracing::debug!("Failed to remove accessibility stamps: {}", e);
+        }
 
-                // Collect interactive and semantic elements
-                const selectors = [
-                    'button',
-                    'a[href]',
-                    'input',
-                    'textarea',
-                    'select',
-                    '[role="button"]',
-                    '[role="link"]',
-                    '[role="textbox"]',
-                    '[role="combobox"]',
-                    '[role="checkbox"]',
-                    '[role="radio"]',
-                    '[role="tab"]',
-                    '[role="menuitem"]',
-                    'h1', 'h2', 'h3', 'h4', 'h5', 'h6',
-                    '[aria-label]',
-                ];
-
-                const foundElements = document.querySelectorAll(selectors.join(','));
-
-                foundElements.forEach(el => {
-                    const role = getRole(el);
-                    const name = getAccessibleName(el);
-                    const visible = isVisible(el);
-                    const rect = getRect(el);
+        let nodes = stamped.into_iter().map(|(node, id)| (id, node)).collect();
+        Ok((elements, nodes))
+    }
 
-                    // Only include if it has a name or is a heading
-                    if (name || role === 'heading') {
-                        elements.push({
-                            id: id++,
-                            role,
-                            name,
-                            value: el.value || null,
-                            description: el.getAttribute('aria-description') || el.title || null,
-                            enabled: !el.disabled,
-                            visible,
-                            rect,
-                            children: [] // We'll keep it flat for simplicity
-                        });
-                    }
-                });
+    /// Collected elements described by the JavaScript helpers, each stamped with its ID
+    async fn extract_js_ax_tree(page: &Page) -> Result<Vec<AXElement>> {
+        let script = format!(
+            r#"
+            (() => {{
+                {}
 
-                return elements;
-            })()
-        "#;
+                return collectElements().map(({{ el, role, name, frame }}, id) => {{
+                    el.setAttribute('{}', id);
+                    return {{
+                        id,
+                        role,
+                        name,
+                        frame,
+                        value: getValue(el),
+                        description: el.getAttribute('aria-description') || el.title || null,
+                        enabled: !el.disabled,
+                        visible: isVisible(el),
+                        rect: getRect(el),
+                        states: getStates(el),
+                        children: [] // Nested by extract_tree
+                    }};
+                }});
+            }})()
+            "#,
+            COLLECT_ELEMENTS_JS, AX_ID_ATTRIBUTE
+        );
 
         let result = page.evaluate(script).await?;
 
@@ -226,45 +501,565 @@ impl ContextExtractor {
         Ok(elements)
     }
 
-    /// Simplify AX tree for LLM consumption
-    fn simplify_tree(ax_tree: &[AXElement], viewport: &Viewport) -> Vec<SimplifiedElement> {
-        ax_tree
+    /// Elements nested by DOM containment, under the forms, lists, menus and landmarks holding them
+    ///
+    /// Elements keep their context IDs. Containers that aren't in the flat
+    /// element list get IDs after the last element; actions can't target them.
+    pub async fn extract_tree(page: &Page) -> Result<Vec<AXElement>> {
+        let (elements, _) = Self::extract_ax_tree(page).await?;
+
+        let script = format!(
+            r#"
+            (() => {{
+                {}
+
+                const containerRoles = ['form', 'dialog', 'alertdialog', 'navigation', 'main', 'banner',
+                                        'contentinfo', 'region', 'complementary', 'search', 'list', 'listitem',
+                                        'table', 'row', 'menu', 'menubar', 'tablist', 'toolbar', 'group',
+                                        'radiogroup', 'listbox', 'grid', 'tree'];
+                const containerTags = {{
+                    'FORM': 'form', 'NAV': 'navigation', 'MAIN': 'main', 'HEADER': 'banner',
+                    'FOOTER': 'contentinfo', 'ASIDE': 'complementary', 'SECTION': 'region',
+                    'DIALOG': 'dialog', 'UL': 'list', 'OL': 'list', 'LI': 'listitem',
+                    'TABLE': 'table', 'TR': 'row', 'FIELDSET': 'group',
+                }};
+                function containerRole(el) {{
+                    const role = el.getAttribute('role') || containerTags[el.tagName];
+                    return containerRoles.includes(role) ? role : null;
+                }}
+
+                const collected = collectElements();
+                const ids = new Map(collected.map((c, i) => [c.el, i]));
+                // Member -> iframe path of its document
+                const members = new Map(collected.map(c => [c.el, c.frame]));
+                collected.forEach(({{ el, frame }}) => {{
+                    for (let node = parentOf(el); node && node !== el.ownerDocument.body; node = parentOf(node)) {{
+                        if (containerRole(node)) members.set(node, frame);
+                    }}
+                }});
+
+                // Documents in frame order, then tree order; puts every parent before its descendants
+                const ordered = frameDocuments().flatMap(({{ doc }}) =>
+                    deepQuerySelectorAll(doc, '*').filter(el => members.has(el)));
+                const position = new Map(ordered.map((el, i) => [el, i]));
+
+                return ordered.map(el => {{
+                    let parent = null;
+                    for (let node = parentOf(el); node; node = parentOf(node)) {{
+                        if (position.has(node)) {{
+                            parent = position.get(node);
+                            break;
+                        }}
+                    }}
+                    if (ids.has(el)) return {{ id: ids.get(el), parent }};
+
+                    const legend = el.querySelector(':scope > legend, :scope > caption');
+                    return {{
+                        id: null,
+                        parent,
+                        frame: members.get(el),
+                        role: containerRole(el),
+                        name: getAccessibleName(el) || (legend ? legend.textContent.trim() : null),
+                        visible: isVisible(el),
+                        rect: getRect(el),
+                        states: getStates(el),
+                    }};
+                }});
+            }})()
+            "#,
+            COLLECT_ELEMENTS_JS
+        );
+
+        let result = page.evaluate(script).await?;
+        let nodes: Vec<TreeNode> = serde_json::from_value(result.into_value()?)?;
+        Ok(nest_elements(elements, &nodes))
+    }
+
+    /// Overwrite the heuristics with Chrome's accessibility tree, matching nodes by their stamps
+    async fn apply_cdp_ax_tree(
+        page: &Page,
+        elements: &mut [AXElement],
+        stamped: &HashMap<BackendNodeId, usize>,
+    ) -> Result<()> {
+        let mut nodes = page.execute(GetFullAxTreeParams::default()).await?.result.nodes;
+        // The tree of the main frame stops at iframes; each frame has its own
+        if elements.iter().any(|element| !element.frame.is_empty()) {
+            let main_frame = page.mainframe().await?;
+            for frame_id in page.frames().await? {
+                if main_frame.as_ref() == Some(&frame_id) {
+                    continue;
+                }
+                match page.execute(GetFullAxTreeParams::builder().frame_id(frame_id).build()).await {
+                    Ok(tree) => nodes.extend(tree.result.nodes),
+                    Err(e) => tracing::debug!("No accessibility tree for a child frame: {}", e),
+                }
+            }
+        }
+
+        for node in nodes.iter().filter(|node| !node.ignored) {
+            let id = node.backend_dom_node_id.and_then(|backend_id| stamped.get(&backend_id));
+            if let Some(element) = id.and_then(|id| elements.get_mut(*id)) {
+                apply_ax_node(element, node);
+            }
+        }
+        Ok(())
+    }
+
+    /// Stamp `attribute="token"` on the first element whose computed role and name equal the selector's
+    ///
+    /// Lets actions reach elements named by the accessibility tree where the
+    /// finder script's simpler name computation gives a different name.
+    pub async fn stamp_ax_match(
+        page: &Page,
+        selector: &SemanticSelector,
+        attribute: &str,
+        token: &str,
+    ) -> Result<bool> {
+        let root = page.get_document().await?;
+        let mut query = QueryAxTreeParams::builder()
+            .backend_node_id(root.backend_node_id)
+            .role(selector.role.clone());
+        if let Some(name) = &selector.name {
+            query = query.accessible_name(name.clone());
+        }
+
+        let nodes = page.execute(query.build()).await?.result.nodes;
+        let Some(backend_id) = nodes
             .iter()
-            .map(|el| {
-                // Check if element is in viewport
-                let in_viewport = if let Some(rect) = &el.rect {
-                    Self::is_in_viewport(rect, viewport)
-                } else {
-                    false
-                };
+            .filter(|node| !node.ignored)
+            .find_map(|node| node.backend_dom_node_id)
+        else {
+            return Ok(false);
+        };
 
-                let mut simplified = SimplifiedElement::new(
-                    el.id,
-                    &el.role,
-                    el.name.as_deref(),
-                    in_viewport,
-                );
+        Self::stamp_node(page, backend_id, attribute, token).await
+    }
 
-                // Add description to selector if available
-                simplified.selector.description = el.description.clone();
+    /// Stamp `attribute="token"` on a DOM node; false if the node left the document
+    pub async fn stamp_node(page: &Page, backend_id: BackendNodeId, attribute: &str, token: &str) -> Result<bool> {
+        let pushed = match page
+            .execute(PushNodesByBackendIdsToFrontendParams::new(vec![backend_id]))
+            .await
+        {
+            Ok(pushed) => pushed.result,
+            // Nodes of a replaced document are unknown
+            Err(e) => {
+                tracing::debug!("Could not resolve DOM node: {}", e);
+                return Ok(false);
+            }
+        };
+        let node_id = pushed.node_ids.first().context("Node was not pushed to the frontend")?;
+        // Detached nodes come back as 0
+        if *node_id.inner() == 0 {
+            return Ok(false);
+        }
+        page.execute(SetAttributeValueParams::new(*node_id, attribute, token))
+            .await?;
+        Ok(true)
+    }
 
-                simplified
-            })
+    /// Extract ancestors, headings, siblings and nearby text for a context element ID
+    pub async fn extract_neighborhood(page: &Page, element_id: usize) -> Result<Option<ElementNeighborhood>> {
+        let script = format!(
+            r#"
+            (() => {{
+                {}
+
+                const collected = collectElements();
+                const target = collected[{}];
+                if (!target) return null;
+
+                const idOf = new Map(collected.map((c, i) => [c.el, i]));
+                const landmarkRoles = ['form', 'dialog', 'alertdialog', 'navigation', 'main', 'banner',
+                                       'contentinfo', 'region', 'complementary', 'search', 'list',
+                                       'listitem', 'row', 'table', 'menu', 'tablist', 'group'];
+                const landmarkTags = {{
+                    'FORM': 'form', 'NAV': 'navigation', 'MAIN': 'main', 'HEADER': 'banner',
+                    'FOOTER': 'contentinfo', 'ASIDE': 'complementary', 'SECTION': 'region',
+                    'DIALOG': 'dialog', 'UL': 'list', 'OL': 'list', 'LI': 'listitem',
+                    'TR': 'row', 'TABLE': 'table', 'FIELDSET': 'group',
+                }};
+
+                // `contains` that also looks into shadow trees
+                function within(container, el) {{
+                    for (let node = el; node; node = parentOf(node)) {{
+                        if (node === container) return true;
+                    }}
+                    return false;
+                }}
+
+                function describe(el) {{
+                    return {{
+                        role: getRole(el),
+                        name: getAccessibleName(el),
+                        element_id: idOf.has(el) ? idOf.get(el) : null,
+                    }};
+                }}
+
+                const ancestors = [];
+                for (let node = parentOf(target.el); node && node !== document.body; node = parentOf(node)) {{
+                    const role = node.getAttribute('role') || landmarkTags[node.tagName];
+                    if (role && landmarkRoles.includes(role)) {{
+                        const legend = node.querySelector(':scope > legend, :scope > caption');
+                        ancestors.push({{
+                            role,
+                            name: node.getAttribute('aria-label') || (legend ? legend.textContent.trim() : null),
+                            element_id: idOf.has(node) ? idOf.get(node) : null,
+                        }});
+                    }}
+                    if (ancestors.length >= 5) break;
+                }}
+
+                // Headings preceding the target in document order, nearest first
+                const headings = [];
+                const allHeadings = document.querySelectorAll('h1, h2, h3, h4, h5, h6, [role="heading"]');
+                for (const h of allHeadings) {{
+                    if (h.compareDocumentPosition(target.el) & Node.DOCUMENT_POSITION_FOLLOWING) {{
+                        const text = h.textContent.trim();
+                        if (text) headings.unshift(text.slice(0, 80));
+                    }}
+                }}
+
+                // Siblings: collected elements sharing the closest container that has others
+                let siblings = [];
+                for (let container = parentOf(target.el); container && container !== document.body; container = parentOf(container)) {{
+                    siblings = collected
+                        .filter(c => c.el !== target.el && within(container, c.el))
+                        .slice(0, 10)
+                        .map(c => describe(c.el));
+                    if (siblings.length > 0) break;
+                }}
+
+                let nearby = '';
+                for (let container = parentOf(target.el); container && container !== document.body; container = parentOf(container)) {{
+                    nearby = (container.innerText || '').replace(/\s+/g, ' ').trim();
+                    if (nearby.length > 20) break;
+                }}
+
+                return {{
+                    element_id: {},
+                    role: target.role,
+                    name: target.name,
+                    ancestors,
+                    headings: headings.slice(0, 3),
+                    siblings,
+                    nearby_text: nearby ? nearby.slice(0, 300) : null,
+                }};
+            }})()
+            "#,
+            COLLECT_ELEMENTS_JS, element_id, element_id
+        );
+
+        let result = page.evaluate(script).await?;
+        let neighborhood: Option<ElementNeighborhood> = serde_json::from_value(result.into_value()?)?;
+
+        Ok(neighborhood)
+    }
+
+    /// Context IDs to mark in a Set-of-Mark screenshot: interactive elements in the viewport
+    pub fn set_of_mark_ids(context: &UIContext) -> Vec<usize> {
+        context
+            .elements
+            .iter()
+            .filter(|e| e.in_viewport && e.is_interactive())
+            .map(|e| e.id)
             .collect()
     }
 
-    /// Check if element rect is in viewport
-    fn is_in_viewport(rect: &ElementRect, viewport: &Viewport) -> bool {
-        let viewport_bottom = viewport.scroll_y + viewport.height as f64;
-        let viewport_right = viewport.scroll_x + viewport.width as f64;
+    /// Draw a numbered box over each element in `ids`; returns how many were drawn
+    ///
+    /// The labels are the context IDs, so a model looking at the screenshot can
+    /// answer with "[12]" and mean the same element as the element list.
+    pub async fn draw_marks(page: &Page, ids: &[usize]) -> Result<usize> {
+        let script = format!(
+            r#"
+            (() => {{
+                {}
+
+                document.getElementById('{layer}')?.remove();
+                const ids = new Set({ids});
+                const colors = ['#e6194b', '#3cb44b', '#4363d8', '#f58231', '#911eb4', '#008080', '#9a6324', '#800000'];
+                const layer = document.createElement('div');
+                layer.id = '{layer}';
+                layer.style.cssText = 'position:fixed;inset:0;pointer-events:none;z-index:2147483647;';
+
+                let drawn = 0;
+                collectElements().forEach(({{ el }}, id) => {{
+                    if (!ids.has(id)) return;
+                    const rect = getRect(el);
+                    if (rect.width === 0 || rect.height === 0) return;
+
+                    const color = colors[id % colors.length];
+                    const box = document.createElement('div');
+                    box.style.cssText = `position:fixed;left:${{rect.x}}px;top:${{rect.y}}px;` +
+                        `width:${{rect.width}}px;height:${{rect.height}}px;border:2px solid ${{color}};box-sizing:border-box;`;
+                    const label = document.createElement('span');
+                    label.textContent = id;
+                    // Above the box unless that would leave the viewport
+                    label.style.cssText = `position:absolute;left:-2px;top:${{rect.y >= 14 ? -14 : 0}}px;` +
+                        `background:${{color}};color:#fff;font:bold 11px/14px monospace;padding:0 3px;`;
+                    box.appendChild(label);
+                    layer.appendChild(box);
+                    drawn++;
+                }});
+
+                document.documentElement.appendChild(layer);
+                return drawn;
+            }})()
+            "#,
+            COLLECT_ELEMENTS_JS,
+            layer = SET_OF_MARK_LAYER_ID,
+            ids = serde_json::to_string(ids)?,
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(result.into_value::<usize>().unwrap_or_default())
+    }
+
+    /// Remove the boxes drawn by `draw_marks`
+    pub async fn clear_marks(page: &Page) -> Result<()> {
+        page.evaluate(format!(
+            "document.getElementById('{}')?.remove()",
+            SET_OF_MARK_LAYER_ID
+        ))
+        .await?;
+        Ok(())
+    }
+}
+
+/// A collected element or container in document order, as reported by the tree script
+#[derive(Debug, Deserialize)]
+struct TreeNode {
+    /// Context ID; `None` for a container
+    id: Option<usize>,
+    /// Position of the closest enclosing node in the list
+    parent: Option<usize>,
+    #[serde(default)]
+    role: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    visible: bool,
+    #[serde(default)]
+    rect: Option<ElementRect>,
+    #[serde(default)]
+    states: AriaStates,
+    #[serde(default)]
+    frame: Vec<usize>,
+}
+
+/// Move `elements` into the children of their enclosing nodes; returns the top-level nodes
+fn nest_elements(elements: Vec<AXElement>, nodes: &[TreeNode]) -> Vec<AXElement> {
+    let mut next_container_id = elements.len();
+    let mut elements: Vec<Option<AXElement>> = elements.into_iter().map(Some).collect();
+
+    let mut built: Vec<Option<AXElement>> = nodes
+        .iter()
+        .map(|node| match node.id {
+            // Gone if the page changed between the two scripts
+            Some(id) => elements.get_mut(id).and_then(Option::take),
+            None => {
+                next_container_id += 1;
+                Some(AXElement {
+                    id: next_container_id - 1,
+                    role: node.role.clone(),
+                    name: node.name.clone().filter(|name| !name.is_empty()),
+                    value: None,
+                    description: None,
+                    enabled: true,
+                    visible: node.visible,
+                    rect: node.rect.clone(),
+                    states: node.states.clone(),
+                    frame: node.frame.clone(),
+                    children: Vec::new(),
+                })
+            }
+        })
+        .collect();
+
+    // Parents come first, so walking backwards finishes every child before its parent takes it
+    let mut roots = Vec::new();
+    for (position, node) in nodes.iter().enumerate().rev() {
+        let Some(element) = built[position].take() else {
+            continue;
+        };
+        match node.parent.filter(|parent| *parent < position).and_then(|parent| built[parent].as_mut()) {
+            Some(parent) => parent.children.insert(0, element),
+            None => roots.insert(0, element),
+        }
+    }
+    roots
+}
+
+/// Map backend node IDs of stamped elements to their context IDs
+fn collect_stamped_nodes(node: &Node, stamped: &mut HashMap<BackendNodeId, usize>) {
+    // Attributes come as a flat [name, value, name, value, ...] list
+    let id = node.attributes.as_ref().and_then(|attributes| {
+        attributes
+            .chunks(2)
+            .find(|pair| pair[0] == AX_ID_ATTRIBUTE)
+            .and_then(|pair| pair.get(1)?.parse().ok())
+    });
+    if let Some(id) = id {
+        stamped.insert(node.backend_node_id, id);
+    }
+
+    let children = node.children.iter().flatten().chain(node.shadow_roots.iter().flatten());
+    for child in children.chain(node.content_document.as_deref()) {
+        collect_stamped_nodes(child, stamped);
+    }
+}
+
+/// String form of an accessibility value ("true", "mixed", "Log in", ...)
+fn ax_string(value: &AxValue) -> Option<String> {
+    match value.value.as_ref()? {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// Take role, name, description, disabled and ARIA states from an accessibility node
+fn apply_ax_node(element: &mut AXElement, node: &AxNode) {
+    let text = |value: &Option<AxValue>| {
+        value
+            .as_ref()
+            .and_then(ax_string)
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+
+    if let Some(role) = text(&node.role).filter(|role| !UNINFORMATIVE_ROLES.contains(&role.as_str())) {
+        element.role = role;
+    }
+    if let Some(name) = text(&node.name) {
+        element.name = Some(name);
+    }
+    if let Some(description) = text(&node.description) {
+        element.description = Some(description);
+    }
+
+    for property in node.properties.iter().flatten() {
+        let value = ax_string(&property.value);
+        match property.name {
+            AxPropertyName::Disabled => element.enabled = value.as_deref() != Some("true"),
+            AxPropertyName::Checked => element.states.checked = value,
+            AxPropertyName::Pressed => element.states.pressed = value,
+            AxPropertyName::Expanded => element.states.expanded = value.map(|v| v == "true"),
+            AxPropertyName::Selected => element.states.selected = value.map(|v| v == "true"),
+            AxPropertyName::Invalid => element.states.invalid = value.map(|v| v != "false"),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SimplifiedElement;
+
+    #[test]
+    fn test_set_of_mark_ids() {
+        let context = UIContext {
+            url: "https://shop.example.com".to_string(),
+            title: "Shop".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements: vec![
+                SimplifiedElement::new(0, "heading", Some("Deals"), true),
+                SimplifiedElement::new(1, "button", Some("Add to cart"), true),
+                SimplifiedElement::new(2, "link", Some("Footer link"), false),
+                SimplifiedElement::new(3, "textbox", Some("Search"), true),
+            ],
+            navigation_history: Vec::new(),
+            closed_overlays: Vec::new(),
+            tabs: Vec::new(),
+            focused: None,
+        };
+
+        assert_eq!(ContextExtractor::set_of_mark_ids(&context), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_nest_elements() {
+        let element = |id: usize, role: &str, name: &str| -> AXElement {
+            serde_json::from_value(serde_json::json!({
+                "id": id, "role": role, "name": name, "value": null, "description": null,
+                "enabled": true, "visible": true, "rect": null, "children": []
+            }))
+            .unwrap()
+        };
+        let elements = vec![
+            element(0, "heading", "Sign in"),
+            element(1, "textbox", "Email"),
+            element(2, "button", "Continue"),
+            element(3, "link", "Help"),
+        ];
+        let nodes: Vec<TreeNode> = serde_json::from_value(serde_json::json!([
+            {"id": 0, "parent": null},
+            {"id": null, "parent": null, "role": "form", "name": "Sign in", "visible": true},
+            {"id": 1, "parent": 1},
+            {"id": 2, "parent": 1},
+            {"id": null, "parent": null, "role": "contentinfo", "name": null, "visible": true},
+            {"id": 3, "parent": 4},
+            // Collected after the flat list was taken
+            {"id": 9, "parent": 4}
+        ]))
+        .unwrap();
+
+        let tree = nest_elements(elements, &nodes);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree[0].role, "heading");
+
+        let form = &tree[1];
+        assert_eq!((form.id, form.role.as_str()), (4, "form"));
+        let children: Vec<usize> = form.children.iter().map(|child| child.id).collect();
+        assert_eq!(children, vec![1, 2]);
+
+        assert_eq!(tree[2].id, 5);
+        assert_eq!(tree[2].name, None);
+        assert_eq!(tree[2].children.len(), 1);
+        assert_eq!(tree[2].children[0].name.as_deref(), Some("Help"));
+    }
+
+    #[test]
+    fn test_apply_ax_node() {
+        // Native checkbox the heuristics took for an unnamed textbox
+        let mut element: AXElement = serde_json::from_value(serde_json::json!({
+            "id": 4, "role": "textbox", "name": null, "value": "on", "description": null,
+            "enabled": true, "visible": true, "rect": null, "children": []
+        }))
+        .unwrap();
+        let node: AxNode = serde_json::from_value(serde_json::json!({
+            "nodeId": "12",
+            "ignored": false,
+            "role": {"type": "role", "value": "checkbox"},
+            "name": {"type": "computedString", "value": "Subscribe to the newsletter "},
+            "properties": [
+                {"name": "checked", "value": {"type": "tristate", "value": "mixed"}},
+                {"name": "disabled", "value": {"type": "boolean", "value": true}},
+                {"name": "focusable", "value": {"type": "booleanOrUndefined", "value": true}}
+            ],
+            "backendDOMNodeId": 31
+        }))
+        .unwrap();
 
-        let element_bottom = rect.y + rect.height;
-        let element_right = rect.x + rect.width;
+        apply_ax_node(&mut element, &node);
+        assert_eq!(element.role, "checkbox");
+        assert_eq!(element.name.as_deref(), Some("Subscribe to the newsletter"));
+        assert_eq!(element.states.checked.as_deref(), Some("mixed"));
+        assert!(!element.enabled);
 
-        // Element is in viewport if it overlaps with viewport bounds
-        rect.y < viewport_bottom
-            && element_bottom > viewport.scroll_y
-            && rect.x < viewport_right
-            && element_right > viewport.scroll_x
+        // A generic role keeps the role from the page
+        let node: AxNode = serde_json::from_value(serde_json::json!({
+            "nodeId": "13", "ignored": false, "role": {"type": "role", "value": "generic"}
+        }))
+        .unwrap();
+        apply_ax_node(&mut element, &node);
+        assert_eq!(element.role, "checkbox");
     }
 }
@@ -0,0 +1,218 @@
+use anyhow::Result;
+use chromiumoxide::cdp::browser_protocol::page::{EventFrameNavigated, EventNavigatedWithinDocument};
+use chromiumoxide::cdp::js_protocol::runtime::{AddBindingParams, EventBindingCalled};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::models::{ContextChange, SessionEventKind};
+
+use super::settle::ignore_script;
+use super::{ContextExtractor, EventLog, SettlePolicy};
+
+/// CDP binding the page calls when a burst of DOM mutations happened
+const BINDING_NAME: &str = "__mcpMutationBurst";
+
+/// Mutation records are counted over windows of this length
+const MUTATION_WINDOW_MS: u64 = 1000;
+
+/// Settings for automatic context pushes
+#[derive(Debug, Clone)]
+pub struct ContextPushConfig {
+    pub enabled: bool,
+    /// Quiet period after the last change before a context is extracted
+    pub debounce: Duration,
+    /// Minimum time between two pushes of the same session
+    pub min_interval: Duration,
+    /// Mutation records within one window that count as a significant change
+    pub mutation_threshold: u32,
+}
+
+impl ContextPushConfig {
+    /// Configure from CONTEXT_PUSH_ENABLED (default: true), CONTEXT_PUSH_DEBOUNCE_MS (500),
+    /// CONTEXT_PUSH_MIN_INTERVAL_MS (2000) and CONTEXT_PUSH_MUTATION_THRESHOLD (50)
+    pub fn from_env() -> Self {
+        fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+            std::env::var(key)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            enabled: env_or("CONTEXT_PUSH_ENABLED", true),
+            debounce: Duration::from_millis(env_or("CONTEXT_PUSH_DEBOUNCE_MS", 500)),
+            min_interval: Duration::from_millis(env_or("CONTEXT_PUSH_MIN_INTERVAL_MS", 2000)),
+            mutation_threshold: env_or("CONTEXT_PUSH_MUTATION_THRESHOLD", 50),
+        }
+    }
+}
+
+enum Trigger {
+    Navigated(ContextChange),
+    Binding(Arc<EventBindingCalled>),
+}
+
+/// Push a compact context to live subscribers after navigations and mutation bursts
+///
+/// Changes are debounced and rate-limited per session; nothing is extracted while
+/// no live channel is connected. The settle policy of the page's origin, if any,
+/// filters the mutations, may replace the quiet period and gates the extraction.
+pub async fn start_context_push(
+    page: Arc<RwLock<Page>>,
+    events: Arc<EventLog>,
+    config: ContextPushConfig,
+    settle: Vec<SettlePolicy>,
+) -> Result<()> {
+    if !config.enabled {
+        return Ok(());
+    }
+
+    let mut stream = {
+        let page = page.read().await;
+
+        page.execute(AddBindingParams::new(BINDING_NAME)).await?;
+        let script = build_burst_script(config.mutation_threshold, &settle);
+        page.evaluate(script.as_str()).await?;
+        page.evaluate_on_new_document(script).await?;
+
+        let frame_navigated = page
+            .event_listener::<EventFrameNavigated>()
+            .await?
+            .filter(|event| futures::future::ready(event.frame.parent_id.is_none()))
+            .map(|_| Trigger::Navigated(ContextChange::Navigation));
+        let within_document = page
+            .event_listener::<EventNavigatedWithinDocument>()
+            .await?
+            .map(|_| Trigger::Navigated(ContextChange::Navigation));
+        let bindings = page
+            .event_listener::<EventBindingCalled>()
+            .await?
+            .map(Trigger::Binding);
+
+        futures::stream::select(
+            futures::stream::select(frame_navigated, within_document),
+            bindings,
+        )
+        .filter_map(|trigger| {
+            futures::future::ready(match trigger {
+                Trigger::Navigated(change) => Some(change),
+                Trigger::Binding(call) if call.name == BINDING_NAME => Some(ContextChange::Mutations),
+                Trigger::Binding(_) => None,
+            })
+        })
+        .boxed()
+    };
+
+    tokio::spawn(async move {
+        let mut last_push: Option<Instant> = None;
+
+        while let Some(first) = stream.next().await {
+            let mut reason = first;
+            let started = Instant::now();
+            let policy = current_policy(&page, &settle).await;
+            let settle_time = policy.as_ref().and_then(|policy| policy.settle_ms).map(Duration::from_millis);
+
+            // Wait for the page to go quiet, then for the rate limit; a navigation
+            // anywhere in the burst wins over mutations
+            let mut quiet_for = settle_time.unwrap_or(config.debounce);
+            loop {
+                match tokio::time::timeout(quiet_for, stream.next()).await {
+                    Ok(Some(change)) => {
+                        if change == ContextChange::Navigation {
+                            reason = change;
+                        }
+                        quiet_for = match settle_time {
+                            // Counted from the first change, however busy the page stays
+                            Some(settle_time) => settle_time.saturating_sub(started.elapsed()),
+                            None => config.debounce,
+                        };
+                    }
+                    Ok(None) => return,
+                    Err(_) => match last_push.map(|at| at.elapsed()) {
+                        Some(elapsed) if elapsed < config.min_interval => {
+                            quiet_for = config.min_interval - elapsed;
+                        }
+                        _ => break,
+                    },
+                }
+            }
+
+            if !events.has_subscribers() {
+                continue;
+            }
+
+            if let Some(policy) = &policy {
+                let ready = policy.wait_until_ready(&*page.read().await).await;
+                if !matches!(ready, Ok(true)) {
+                    tracing::debug!("Pushing context before the page's settle policy says ready: {:?}", ready);
+                }
+            }
+
+            match ContextExtractor::extract(Arc::clone(&page)).await {
+                Ok(context) => {
+                    events.broadcast(SessionEventKind::ContextUpdated {
+                        reason,
+                        context: context.compact(),
+                    });
+                    last_push = Some(Instant::now());
+                }
+                Err(e) => tracing::debug!("Skipping context push: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Settle policy for the page's current URL
+async fn current_policy(page: &RwLock<Page>, settle: &[SettlePolicy]) -> Option<SettlePolicy> {
+    if settle.is_empty() {
+        return None;
+    }
+    let url = page.read().await.url().await.ok().flatten()?;
+    SettlePolicy::for_url(settle, &url).cloned()
+}
+
+/// Counts mutation records per window and reports once per window over the threshold
+///
+/// Records inside subtrees the settle policy ignores are not counted.
+fn build_burst_script(threshold: u32, settle: &[SettlePolicy]) -> String {
+    format!(
+        r#"(() => {{
+            if (window.__mcpBurstObserver) return;
+            window.__mcpBurstObserver = true;
+            {ignore}
+
+            let count = 0;
+            let reported = false;
+            const start = () => {{
+                new MutationObserver(records => {{
+                    count += records.filter(record => !settleIgnored(record.target)).length;
+                    if (!reported && count >= {threshold}) {{
+                        reported = true;
+                        try {{ window.{binding}(''); }} catch (e) {{}}
+                    }}
+                }}).observe(document.documentElement, {{
+                    childList: true,
+                    subtree: true,
+                    attributes: true,
+                    characterData: true,
+                }});
+                setInterval(() => {{ count = 0; reported = false; }}, {window});
+            }};
+
+            if (document.documentElement) {{
+                start();
+            }} else {{
+                document.addEventListener('DOMContentLoaded', start);
+            }}
+        }})()"#,
+        threshold = threshold,
+        ignore = ignore_script(settle),
+        binding = BINDING_NAME,
+        window = MUTATION_WINDOW_MS,
+    )
+}
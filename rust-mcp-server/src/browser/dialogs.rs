@@ -0,0 +1,182 @@
+use anyhow::Result;
+use chromiumoxide::cdp::browser_protocol::page::{DialogType, EventJavascriptDialogOpening, HandleJavaScriptDialogParams};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::models::{DialogPolicy, DialogRecord, SessionEventKind};
+
+use super::EventLog;
+
+/// Answered dialogs kept for the responses of the actions that opened them
+const KEPT_DIALOGS: usize = 20;
+
+/// Answers the page's JavaScript dialogs as soon as they open
+///
+/// An open dialog blocks the page, and with it every action and context
+/// read, so nothing waits for a decision: the dialog gets the answer a
+/// `handle_dialog` action set up for it, or else the session's policy.
+#[derive(Clone)]
+pub struct DialogHandler {
+    state: Arc<Mutex<DialogState>>,
+    events: Arc<EventLog>,
+}
+
+#[derive(Default)]
+struct DialogState {
+    policy: DialogPolicy,
+    /// Answer for the next dialog only, from `handle_dialog`
+    next: Option<DialogPolicy>,
+    /// Recent dialogs with their sequence numbers
+    handled: VecDeque<(u64, DialogRecord)>,
+    count: u64,
+}
+
+impl DialogState {
+    /// Take the answer for a dialog that just opened; true if `handle_dialog` chose it
+    fn answer(&mut self) -> (DialogPolicy, bool) {
+        match self.next.take() {
+            Some(answer) => (answer, true),
+            None => (self.policy.clone(), false),
+        }
+    }
+
+    fn record(&mut self, dialog: DialogRecord) {
+        if self.handled.len() >= KEPT_DIALOGS {
+            self.handled.pop_front();
+        }
+        self.handled.push_back((self.count, dialog));
+        self.count += 1;
+    }
+}
+
+impl DialogHandler {
+    pub async fn start(page: &Page, events: Arc<EventLog>, policy: DialogPolicy) -> Result<Self> {
+        let handler = Self {
+            state: Arc::new(Mutex::new(DialogState {
+                policy,
+                ..Default::default()
+            })),
+            events,
+        };
+        handler.attach(page).await?;
+        Ok(handler)
+    }
+
+    /// Answer the dialogs of another tab of the session the same way
+    pub async fn attach(&self, page: &Page) -> Result<()> {
+        let mut opened = page.event_listener::<EventJavascriptDialogOpening>().await?;
+
+        let answer_page = page.clone();
+        let dialogs = Arc::clone(&self.state);
+        let events = Arc::clone(&self.events);
+        tokio::spawn(async move {
+            while let Some(dialog) = opened.next().await {
+                let (answer, by_action) = dialogs.lock().unwrap().answer();
+                let prompt_text = match (&dialog.r#type, answer.accept) {
+                    (DialogType::Prompt, true) => answer.prompt_text.or_else(|| dialog.default_prompt.clone()),
+                    _ => None,
+                };
+
+                let record = DialogRecord {
+                    kind: dialog.r#type.as_ref().to_string(),
+                    message: dialog.message.clone(),
+                    url: dialog.url.clone(),
+                    accepted: answer.accept,
+                    prompt_text: prompt_text.clone(),
+                    by_action,
+                };
+                // Recorded before answering: the action that opened the dialog resumes once it closes
+                dialogs.lock().unwrap().record(record.clone());
+
+                let mut params = HandleJavaScriptDialogParams::new(answer.accept);
+                params.prompt_text = prompt_text;
+                if let Err(e) = answer_page.execute(params).await {
+                    tracing::warn!("Failed to answer {} dialog: {}", record.kind, e);
+                    continue;
+                }
+
+                tracing::info!(
+                    "{} {} dialog: {}",
+                    if record.accepted { "Accepted" } else { "Dismissed" },
+                    record.kind,
+                    record.message
+                );
+                events.push(SessionEventKind::DialogHandled { dialog: record }).await;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// How dialogs are answered from now on
+    pub fn set_policy(&self, policy: DialogPolicy) {
+        self.state.lock().unwrap().policy = policy;
+    }
+
+    /// Answer only the next dialog this way
+    pub fn answer_next(&self, answer: DialogPolicy) {
+        self.state.lock().unwrap().next = Some(answer);
+    }
+
+    /// Sequence number the next dialog will get
+    pub fn mark(&self) -> u64 {
+        self.state.lock().unwrap().count
+    }
+
+    /// Dialogs answered from `mark` on
+    pub fn since(&self, mark: u64) -> Vec<DialogRecord> {
+        self.state
+            .lock()
+            .unwrap()
+            .handled
+            .iter()
+            .filter(|(seq, _)| *seq >= mark)
+            .map(|(_, dialog)| dialog.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(message: &str, accepted: bool) -> DialogRecord {
+        DialogRecord {
+            kind: "confirm".to_string(),
+            message: message.to_string(),
+            url: "https://shop.example.com/cart".to_string(),
+            accepted,
+            prompt_text: None,
+            by_action: false,
+        }
+    }
+
+    #[test]
+    fn test_next_answer_is_used_once() {
+        let mut state = DialogState {
+            policy: DialogPolicy { accept: false, prompt_text: None },
+            ..Default::default()
+        };
+        state.next = Some(DialogPolicy { accept: true, prompt_text: Some("42".to_string()) });
+
+        let (answer, by_action) = state.answer();
+        assert!(answer.accept && by_action);
+        assert_eq!(answer.prompt_text.as_deref(), Some("42"));
+        let (answer, by_action) = state.answer();
+        assert!(!answer.accept && !by_action);
+    }
+
+    #[test]
+    fn test_recent_dialogs_keep_their_numbers() {
+        let mut state = DialogState::default();
+        for n in 0..KEPT_DIALOGS + 2 {
+            state.record(record(&format!("Remove item {}?", n), true));
+        }
+
+        assert_eq!(state.handled.len(), KEPT_DIALOGS);
+        assert_eq!(state.handled.front().map(|(seq, _)| *seq), Some(2));
+        assert_eq!(state.count, KEPT_DIALOGS as u64 + 2);
+    }
+}
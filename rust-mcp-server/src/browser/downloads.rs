@@ -0,0 +1,309 @@
+use anyhow::Result;
+use chromiumoxide::cdp::browser_protocol::browser::{
+    DownloadProgressState, EventDownloadProgress, EventDownloadWillBegin,
+    SetDownloadBehaviorBehavior, SetDownloadBehaviorParams,
+};
+use chromiumoxide::page::Page;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{Notify, RwLock};
+
+use crate::models::{DownloadArtifact, DownloadState, SessionEventKind};
+
+use super::EventLog;
+
+struct TrackedDownload {
+    artifact: DownloadArtifact,
+    /// Already returned by a `wait_for_download`
+    claimed: bool,
+}
+
+/// Outcome of waiting for a download
+pub enum DownloadWait {
+    Completed(DownloadArtifact),
+    Canceled(DownloadArtifact),
+    TimedOut,
+}
+
+/// Saves the session's downloads into its own directory and tracks their progress
+pub struct DownloadTracker {
+    dir: PathBuf,
+    downloads: Arc<RwLock<Vec<TrackedDownload>>>,
+    changed: Arc<Notify>,
+}
+
+enum DownloadEvent {
+    Began(Arc<EventDownloadWillBegin>),
+    Progress(Arc<EventDownloadProgress>),
+}
+
+impl DownloadTracker {
+    /// Route downloads into a fresh directory under DOWNLOAD_DIR (default: system temp dir)
+    pub async fn start(page: &Page, events: Arc<EventLog>) -> Result<Self> {
+        let dir = download_root().join(uuid::Uuid::new_v4().to_string());
+        tokio::fs::create_dir_all(&dir).await?;
+
+        // Files are stored under their GUID, so concurrent downloads never collide
+        page.execute(
+            SetDownloadBehaviorParams::builder()
+                .behavior(SetDownloadBehaviorBehavior::AllowAndName)
+                .download_path(dir.to_string_lossy().to_string())
+                .events_enabled(true)
+                .build()
+                .map_err(|e| anyhow::anyhow!("Failed to build download behavior: {}", e))?,
+        )
+        .await?;
+
+        let began = page
+            .event_listener::<EventDownloadWillBegin>()
+            .await?
+            .map(DownloadEvent::Began);
+        let progress = page
+            .event_listener::<EventDownloadProgress>()
+            .await?
+            .map(DownloadEvent::Progress);
+        let mut stream = futures::stream::select(began, progress);
+
+        let downloads: Arc<RwLock<Vec<TrackedDownload>>> = Arc::new(RwLock::new(Vec::new()));
+        let changed = Arc::new(Notify::new());
+
+        let tracked = Arc::clone(&downloads);
+        let notify = Arc::clone(&changed);
+        tokio::spawn(async move {
+            while let Some(event) = stream.next().await {
+                match event {
+                    DownloadEvent::Began(event) => {
+                        tracked.write().await.push(TrackedDownload {
+                            artifact: DownloadArtifact {
+                                id: event.guid.clone(),
+                                filename: event.suggested_filename.clone(),
+                                url: event.url.clone(),
+                                state: DownloadState::InProgress,
+                                size_bytes: 0,
+                                started_at: Utc::now(),
+                                completed_at: None,
+                            },
+                            claimed: false,
+                        });
+                    }
+                    DownloadEvent::Progress(event) => {
+                        let state = match event.state {
+                            DownloadProgressState::InProgress => continue,
+                            DownloadProgressState::Completed => DownloadState::Completed,
+                            DownloadProgressState::Canceled => DownloadState::Canceled,
+                        };
+
+                        let completed = {
+                            let mut downloads = tracked.write().await;
+                            let Some(download) = downloads
+                                .iter_mut()
+                                .find(|d| d.artifact.id == event.guid)
+                            else {
+                                continue;
+                            };
+                            download.artifact.state = state;
+                            download.artifact.size_bytes = event.received_bytes as u64;
+                            download.artifact.completed_at = Some(Utc::now());
+                            download.artifact.clone()
+                        };
+
+                        if state == DownloadState::Completed {
+                            events
+                                .push(SessionEventKind::DownloadCompleted {
+                                    artifact_id: completed.id,
+                                    filename: completed.filename,
+                                })
+                                .await;
+                        }
+                    }
+                }
+                notify.notify_waiters();
+            }
+        });
+
+        Ok(Self {
+            dir,
+            downloads,
+            changed,
+        })
+    }
+
+    /// Wait for a finished download whose file name matches `pattern`
+    ///
+    /// Each download is returned by at most one wait, so consecutive exports
+    /// each get their own file. Downloads that finished before the call count.
+    pub async fn wait_for_download(&self, pattern: Option<&str>, timeout_ms: u64) -> DownloadWait {
+        let wait = async {
+            loop {
+                // Register interest before checking, so a completion in between is not missed
+                let notified = self.changed.notified();
+
+                {
+                    let mut downloads = self.downloads.write().await;
+                    let finished = downloads.iter_mut().find(|d| {
+                        !d.claimed
+                            && d.artifact.state != DownloadState::InProgress
+                            && pattern.is_none_or(|p| filename_matches(p, &d.artifact.filename))
+                    });
+
+                    if let Some(download) = finished {
+                        download.claimed = true;
+                        return match download.artifact.state {
+                            DownloadState::Canceled => DownloadWait::Canceled(download.artifact.clone()),
+                            _ => DownloadWait::Completed(download.artifact.clone()),
+                        };
+                    }
+                }
+
+                notified.await;
+            }
+        };
+
+        tokio::time::timeout(std::time::Duration::from_millis(timeout_ms), wait)
+            .await
+            .unwrap_or(DownloadWait::TimedOut)
+    }
+
+    pub async fn list(&self) -> Vec<DownloadArtifact> {
+        self.downloads
+            .read()
+            .await
+            .iter()
+            .map(|d| d.artifact.clone())
+            .collect()
+    }
+
+    /// Completed artifact and the path of its file
+    pub async fn artifact(&self, id: &str) -> Option<(DownloadArtifact, PathBuf)> {
+        let downloads = self.downloads.read().await;
+        let download = downloads
+            .iter()
+            .find(|d| d.artifact.id == id && d.artifact.state == DownloadState::Completed)?;
+
+        Some((download.artifact.clone(), self.dir.join(&download.artifact.id)))
+    }
+
+    /// Copy the downloads completed since `since` into `dir`, where they outlive the session
+    pub async fn keep(&self, since: DateTime<Utc>, dir: &Path) -> Result<Vec<DownloadArtifact>> {
+        let kept: Vec<DownloadArtifact> = self
+            .list()
+            .await
+            .into_iter()
+            .filter(|artifact| artifact.state == DownloadState::Completed && artifact.started_at >= since)
+            .collect();
+        if kept.is_empty() {
+            return Ok(kept);
+        }
+
+        tokio::fs::create_dir_all(dir).await?;
+        for artifact in &kept {
+            tokio::fs::copy(self.dir.join(&artifact.id), dir.join(&artifact.id)).await?;
+        }
+        Ok(kept)
+    }
+}
+
+impl Drop for DownloadTracker {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.dir) {
+            tracing::debug!("Failed to remove download dir {}: {}", self.dir.display(), e);
+        }
+    }
+}
+
+/// DOWNLOAD_DIR, or `mcp-downloads` in the system temp dir
+fn download_root() -> PathBuf {
+    std::env::var("DOWNLOAD_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| std::env::temp_dir().join("mcp-downloads"))
+}
+
+/// Where the files a run downloaded are kept after its session is gone
+pub fn run_download_dir(run_id: &str) -> PathBuf {
+    download_root().join("runs").join(run_id)
+}
+
+/// Remove the kept downloads of runs that finished more than `max_age` ago
+///
+/// Runs may be stored by another instance, so the age of the directory is
+/// what counts, not whether this instance still knows the run.
+pub async fn prune_run_downloads(max_age: std::time::Duration) -> Result<usize> {
+    remove_dirs_older_than(&download_root().join("runs"), max_age).await
+}
+
+/// Remove the directories in `root` last modified more than `max_age` ago; how many were removed
+pub(crate) async fn remove_dirs_older_than(root: &Path, max_age: std::time::Duration) -> Result<usize> {
+    let mut entries = match tokio::fs::read_dir(root).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut removed = 0;
+    while let Some(entry) = entries.next_entry().await? {
+        let age = entry
+            .metadata()
+            .await
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok());
+        if age.is_some_and(|age| age > max_age) {
+            tokio::fs::remove_dir_all(entry.path()).await?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Remove the kept downloads of a run, if it has any
+pub async fn remove_run_downloads(run_id: &str) {
+    if let Err(e) = tokio::fs::remove_dir_all(run_download_dir(run_id)).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("Failed to remove the downloads of run {}: {}", run_id, e);
+        }
+    }
+}
+
+/// Glob match on a file name: `*` matches any run of characters, `?` a single one
+pub fn filename_matches(pattern: &str, filename: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = filename.to_lowercase().chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filename_matches() {
+        assert!(filename_matches("*.csv", "export-2024.CSV"));
+        assert!(filename_matches("report_??.pdf", "report_01.pdf"));
+        assert!(filename_matches("*", "anything.bin"));
+        assert!(filename_matches("orders*.xlsx", "orders.xlsx"));
+        assert!(!filename_matches("*.csv", "export.csv.part"));
+        assert!(!filename_matches("report_?.pdf", "report_01.pdf"));
+    }
+}
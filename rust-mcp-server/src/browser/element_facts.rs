@@ -0,0 +1,173 @@
+use anyhow::Result;
+use chromiumoxide::element::Element;
+use chromiumoxide::page::Page;
+use ui_core::suggest::{ElementFacts, NamedElement};
+
+use super::COLLECT_ELEMENTS_JS;
+
+/// Reads what the suggestion engine needs to explain a failed action
+///
+/// Gathered only after an action failed, so the extra element scans don't
+/// cost anything on the happy path.
+pub struct ElementInspector;
+
+impl ElementInspector {
+    /// Visibility, position, enabled state and obstruction of `element`
+    pub async fn facts(element: &Element) -> Result<ElementFacts> {
+        let script = format!(
+            r#"
+            function() {{
+                {}
+
+                const el = this;
+                const doc = el.ownerDocument;
+                const dialogSelector = 'dialog[open], [role="dialog"], [role="alertdialog"], [aria-modal="true"]';
+                let ids = null;
+                const named = node => {{
+                    ids = ids || new Map(collectElements().map(({{ el }}, id) => [el, id]));
+                    return {{ id: ids.has(node) ? ids.get(node) : null, role: getRole(node), name: getAccessibleName(node) || null }};
+                }};
+
+                function hiddenReason() {{
+                    const style = doc.defaultView.getComputedStyle(el);
+                    if (style.visibility === 'hidden') return 'visibility: hidden';
+                    for (let node = el; node && node.nodeType === 1; node = node.parentElement || node.getRootNode().host) {{
+                        const where = node === el ? '' : ' on an ancestor';
+                        const nodeStyle = doc.defaultView.getComputedStyle(node);
+                        if (nodeStyle.display === 'none') return 'display: none' + where;
+                        if (nodeStyle.opacity === '0') return 'opacity: 0' + where;
+                    }}
+                    const details = el.closest('details:not([open])');
+                    if (details && !el.closest('summary')) return 'inside a closed <details>';
+                    return null;
+                }}
+
+                // Collapsed disclosure whose controlled region holds the element
+                function expander() {{
+                    for (let node = el.parentElement; node; node = node.parentElement) {{
+                        if (node.tagName === 'DETAILS' && !node.open) {{
+                            const summary = node.querySelector(':scope > summary');
+                            if (summary) return summary;
+                        }}
+                        if (node.id) {{
+                            const control = node.getRootNode().querySelector(
+                                `[aria-controls~="${{CSS.escape(node.id)}}"][aria-expanded="false"]`);
+                            if (control) return control;
+                        }}
+                    }}
+                    return null;
+                }}
+
+                function isEmpty(field) {{
+                    if (field.type === 'checkbox') return !field.checked;
+                    if (field.type === 'radio') {{
+                        const group = field.form && field.name ? field.form.elements.namedItem(field.name) : null;
+                        return group && group.length ? !Array.from(group).some(radio => radio.checked) : !field.checked;
+                    }}
+                    if ('value' in field) return !String(field.value).trim();
+                    return !(field.textContent || '').trim();
+                }}
+
+                function missingFields() {{
+                    const form = el.form || el.closest('form, [role="form"]');
+                    if (!form) return [];
+                    const seen = new Set();
+                    return deepQuerySelectorAll(form, '[required], [aria-required="true"]')
+                        .filter(field => field !== el && isVisible(field) && !field.disabled && isEmpty(field))
+                        .filter(field => {{
+                            // One entry per radio group
+                            if (field.type !== 'radio' || !field.name) return true;
+                            if (seen.has(field.name)) return false;
+                            seen.add(field.name);
+                            return true;
+                        }})
+                        .map(named);
+                }}
+
+                function closeButton(dialog) {{
+                    const buttons = deepQuerySelectorAll(dialog, 'button, [role="button"], a[href], input[type="button"], input[type="submit"]')
+                        .filter(isVisible)
+                        .map(button => getAccessibleName(button) || (button.textContent || '').trim() || button.value || '')
+                        .filter(Boolean);
+                    return buttons.find(name => /close|dismiss|^[×✕x]$/i.test(name)) ||
+                        buttons.find(name => /no thanks|not now|cancel|got it|accept|agree|^ok$|continue/i.test(name)) ||
+                        null;
+                }}
+
+                // An open dialog on top of the element's center, or one that makes the page inert
+                function blocker() {{
+                    const rect = el.getBoundingClientRect();
+                    const view = doc.defaultView;
+                    const inView = rect.width > 0 && rect.height > 0 && rect.bottom > 0 && rect.right > 0 &&
+                        rect.top < view.innerHeight && rect.left < view.innerWidth;
+                    const root = el.getRootNode().elementFromPoint ? el.getRootNode() : doc;
+                    const hit = inView ? root.elementFromPoint(rect.x + rect.width / 2, rect.y + rect.height / 2) : null;
+                    const covering = hit && !el.contains(hit) && !hit.contains(el) ? hit : null;
+
+                    const open = deepQuerySelectorAll(doc, dialogSelector).filter(dialog => isVisible(dialog) && !dialog.contains(el));
+                    const isModal = dialog => {{
+                        try {{ return dialog.matches(':modal'); }} catch (e) {{ return false; }}
+                    }};
+                    const hitDialog = covering ? covering.closest(dialogSelector) : null;
+                    const dialog = (hitDialog && !hitDialog.contains(el) ? hitDialog : null) ||
+                        open.find(isModal) ||
+                        (covering ? open.find(dialog => dialog.getAttribute('aria-modal') === 'true') : null);
+                    if (!dialog) return null;
+                    return {{
+                        role: dialog.getAttribute('role') || 'dialog',
+                        name: getAccessibleName(dialog) || null,
+                        close_button: closeButton(dialog),
+                    }};
+                }}
+
+                let viewportWidth = window.innerWidth, viewportHeight = window.innerHeight;
+                try {{
+                    viewportWidth = window.top.innerWidth;
+                    viewportHeight = window.top.innerHeight;
+                }} catch (e) {{}}
+
+                const reason = hiddenReason();
+                const disabled = !!el.disabled;
+                const disclosure = reason ? expander() : null;
+                return {{
+                    hidden: reason !== null,
+                    hidden_reason: reason,
+                    expander: disclosure ? named(disclosure) : null,
+                    rect: getRect(el),
+                    viewport_width: viewportWidth,
+                    viewport_height: viewportHeight,
+                    disabled,
+                    busy: !!el.closest('[aria-busy="true"]'),
+                    missing_fields: disabled ? missingFields() : [],
+                    covered_by: reason ? null : blocker(),
+                }};
+            }}
+            "#,
+            COLLECT_ELEMENTS_JS
+        );
+
+        let result = element.call_js_fn(script, false).await?;
+        let value = result.result.value.unwrap_or_default();
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// The visible named elements of the page, under their context element IDs
+    pub async fn named_elements(page: &Page) -> Result<Vec<NamedElement>> {
+        let script = format!(
+            r#"
+            (() => {{
+                {}
+
+                return collectElements()
+                    .map(({{ el, role, name }}, id) => ({{ id, role, name, visible: isVisible(el) }}))
+                    .filter(element => element.visible && element.name)
+                    .map(({{ id, role, name }}) => ({{ id, role, name }}));
+            }})()
+            "#,
+            COLLECT_ELEMENTS_JS
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(serde_json::from_value(result.into_value()?)?)
+    }
+}
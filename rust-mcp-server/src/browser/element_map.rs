@@ -0,0 +1,91 @@
+use chromiumoxide::cdp::browser_protocol::dom::BackendNodeId;
+use std::collections::HashMap;
+
+use crate::models::{ActionRequest, SemanticSelector, SimplifiedElement};
+
+/// The elements of a session's latest context by ID, for actions that target `element_id`
+///
+/// Each ID maps to the DOM node it was read from, so the action reaches that
+/// exact element even when others share its role and name. IDs stay valid
+/// until the next context is read; a node removed since then is not found.
+#[derive(Debug, Default)]
+pub struct ElementMap {
+    entries: HashMap<usize, MappedElement>,
+}
+
+#[derive(Debug)]
+struct MappedElement {
+    node: BackendNodeId,
+    selector: SemanticSelector,
+}
+
+impl ElementMap {
+    pub fn new(elements: &[SimplifiedElement], mut nodes: HashMap<usize, BackendNodeId>) -> Self {
+        let entries = elements
+            .iter()
+            .filter_map(|element| {
+                let node = nodes.remove(&element.id)?;
+                Some((element.id, MappedElement { node, selector: element.selector.clone() }))
+            })
+            .collect();
+        Self { entries }
+    }
+
+    pub fn node(&self, element_id: usize) -> Option<BackendNodeId> {
+        self.entries.get(&element_id).map(|entry| entry.node)
+    }
+
+    /// `action` with role, name and frame of its `element_id` target filled in where it leaves them out
+    ///
+    /// Feedback, the heatmap and recorded steps then name the element as usual.
+    pub fn complete(&self, action: &ActionRequest) -> ActionRequest {
+        let mut action = action.clone();
+        let Some(selector) = action.selector_mut() else {
+            return action;
+        };
+        let Some(entry) = selector.element_id.and_then(|id| self.entries.get(&id)) else {
+            return action;
+        };
+
+        if selector.role.is_empty() {
+            selector.role = entry.selector.role.clone();
+        }
+        if selector.name.is_none() {
+            selector.name = entry.selector.name.clone();
+        }
+        if selector.frame.is_empty() {
+            selector.frame = entry.selector.frame.clone();
+        }
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete() {
+        let elements = vec![
+            SimplifiedElement::new(0, "button", Some("Delete"), true),
+            SimplifiedElement::new(1, "button", Some("Delete"), true).with_frame(vec![0]),
+        ];
+        let map = ElementMap::new(
+            &elements,
+            HashMap::from([(0, BackendNodeId::new(31)), (1, BackendNodeId::new(47))]),
+        );
+        assert_eq!(map.node(1), Some(BackendNodeId::new(47)));
+        assert_eq!(map.node(2), None);
+
+        let click: ActionRequest = serde_json::from_value(serde_json::json!({ "tool": "click", "element_id": 1 })).unwrap();
+        let completed = map.complete(&click);
+        let selector = completed.selector().unwrap();
+        assert_eq!((selector.role.as_str(), selector.name.as_deref()), ("button", Some("Delete")));
+        assert_eq!(selector.frame, vec![0]);
+        assert_eq!(selector.element_id, Some(1));
+
+        // Unknown IDs stay as they are, to fail as not found
+        let stale: ActionRequest = serde_json::from_value(serde_json::json!({ "tool": "click", "element_id": 9 })).unwrap();
+        assert_eq!(map.complete(&stale).selector().unwrap().role, "");
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use tokio::sync::{broadcast, RwLock};
+
+use crate::models::{SessionEvent, SessionEventKind};
+
+/// Bounded per-session event log with live fan-out to subscribers
+pub struct EventLog {
+    /// Each with its sequence number in the session, counting dropped events
+    entries: RwLock<VecDeque<(u64, SessionEvent)>>,
+    capacity: usize,
+    sender: broadcast::Sender<SessionEvent>,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(64);
+
+        Self {
+            entries: RwLock::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            sender,
+        }
+    }
+
+    /// Create log sized from SESSION_EVENT_LOG_SIZE (default: 200)
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("SESSION_EVENT_LOG_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(200);
+
+        Self::new(capacity)
+    }
+
+    /// Append an event and notify live subscribers
+    pub async fn push(&self, kind: SessionEventKind) {
+        let event = SessionEvent::new(kind);
+
+        {
+            let mut entries = self.entries.write().await;
+            let seq = entries.back().map_or(0, |(seq, _)| seq + 1);
+            if entries.len() >= self.capacity {
+                entries.pop_front();
+            }
+            if self.capacity > 0 {
+                entries.push_back((seq, event.clone()));
+            }
+        }
+
+        // No receivers is fine
+        let _ = self.sender.send(event);
+    }
+
+    /// Send an event to live subscribers without retaining it
+    pub fn broadcast(&self, kind: SessionEventKind) {
+        let _ = self.sender.send(SessionEvent::new(kind));
+    }
+
+    /// Whether any live channel is listening
+    pub fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    /// Events currently retained with their sequence numbers, oldest first
+    pub async fn recent(&self) -> Vec<(u64, SessionEvent)> {
+        self.entries.read().await.iter().cloned().collect()
+    }
+
+    /// Receive events as they happen
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_broadcast_is_not_retained() {
+        let log = EventLog::new(10);
+        assert!(!log.has_subscribers());
+
+        let mut receiver = log.subscribe();
+        assert!(log.has_subscribers());
+
+        log.push(SessionEventKind::Navigated { url: "https://example.com".to_string() }).await;
+        log.broadcast(SessionEventKind::OverlayRestored { overlay_id: 1 });
+
+        assert_eq!(log.recent().await.len(), 1);
+        assert!(receiver.recv().await.is_ok());
+        assert!(matches!(
+            receiver.recv().await.unwrap().kind,
+            SessionEventKind::OverlayRestored { overlay_id: 1 }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sequence_counts_dropped_events() {
+        let log = EventLog::new(2);
+        for overlay_id in 0..3 {
+            log.push(SessionEventKind::OverlayRestored { overlay_id }).await;
+        }
+
+        let sequence: Vec<u64> = log.recent().await.iter().map(|(seq, _)| *seq).collect();
+        assert_eq!(sequence, vec![1, 2]);
+    }
+}
@@ -0,0 +1,289 @@
+use anyhow::Result;
+use chromiumoxide::cdp::browser_protocol::emulation::{
+    SetLocaleOverrideParams, SetTimezoneOverrideParams, SetUserAgentOverrideParams, UserAgentBrandVersion,
+    UserAgentMetadata,
+};
+use chromiumoxide::page::Page;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Chrome version the built-in fingerprints claim
+const BUILT_IN_CHROME: &str = "124.0.0.0";
+
+/// Screen size reported through `window.screen`, independent of the viewport
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScreenSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// How a session's browser presents itself to sites: user agent, languages, platform, screen and fonts
+///
+/// Meant for staging environments whose bot detection treats the default
+/// headless identity differently from real users.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub user_agent: String,
+
+    /// Accept-Language header and `navigator.languages`, e.g. "de-DE,de;q=0.9,en;q=0.8"
+    pub accept_language: String,
+
+    /// `navigator.platform`: "Win32", "MacIntel", "Linux x86_64"
+    pub platform: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen: Option<ScreenSize>,
+
+    #[serde(default = "default_device_scale_factor")]
+    pub device_scale_factor: f64,
+
+    /// IANA time zone, e.g. "Europe/Berlin"; the host's when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+
+    /// Font families `document.fonts.check()` reports as available
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fonts: Vec<String>,
+}
+
+fn default_device_scale_factor() -> f64 {
+    1.0
+}
+
+impl Fingerprint {
+    /// Apply the overrides to `page`; call before its first navigation
+    ///
+    /// Screen size and scale factor go with the viewport's device metrics,
+    /// which the caller sets.
+    pub async fn apply(&self, page: &Page) -> Result<()> {
+        page.execute(SetUserAgentOverrideParams {
+            user_agent: self.user_agent.clone(),
+            accept_language: Some(self.accept_language.clone()),
+            platform: Some(self.platform.clone()),
+            user_agent_metadata: self.client_hints(),
+        })
+        .await?;
+
+        if let Some(locale) = self.locale() {
+            page.execute(SetLocaleOverrideParams::builder().locale(locale).build()).await?;
+        }
+        if let Some(timezone) = &self.timezone {
+            page.execute(SetTimezoneOverrideParams::new(timezone.clone())).await?;
+        }
+        if !self.fonts.is_empty() {
+            page.evaluate_on_new_document(self.fonts_script()).await?;
+        }
+        Ok(())
+    }
+
+    /// ICU locale of the first language, e.g. "de_DE" for "de-DE,de;q=0.9"
+    fn locale(&self) -> Option<String> {
+        let first = self.accept_language.split([',', ';']).next()?.trim();
+        (!first.is_empty() && first != "*").then(|| first.replace('-', "_"))
+    }
+
+    /// `navigator.userAgentData` and Sec-CH-UA headers, for Chrome user agents only
+    ///
+    /// Other browsers don't send client hints, so a Firefox user agent
+    /// claiming them would stand out.
+    fn client_hints(&self) -> Option<UserAgentMetadata> {
+        let version = self.user_agent.split("Chrome/").nth(1)?.split_whitespace().next()?;
+        let major = version.split('.').next()?;
+        let (platform, platform_version) = match self.platform.as_str() {
+            "Win32" => ("Windows", "15.0.0"),
+            "MacIntel" => ("macOS", "14.4.0"),
+            other if other.starts_with("Linux") => ("Linux", ""),
+            _ => return None,
+        };
+        let brands = |version: &str| {
+            vec![
+                UserAgentBrandVersion::new("Chromium", version),
+                UserAgentBrandVersion::new("Google Chrome", version),
+                UserAgentBrandVersion::new("Not-A.Brand", "99"),
+            ]
+        };
+
+        Some(UserAgentMetadata {
+            brands: Some(brands(major)),
+            full_version_list: Some(brands(version)),
+            platform: platform.to_string(),
+            platform_version: platform_version.to_string(),
+            architecture: "x86".to_string(),
+            model: String::new(),
+            mobile: false,
+            bitness: Some("64".to_string()),
+            wow64: Some(false),
+        })
+    }
+
+    /// `document.fonts.check()` answers yes for the listed families; other answers stay untouched
+    fn fonts_script(&self) -> String {
+        let fonts: Vec<String> = self.fonts.iter().map(|font| font.to_lowercase()).collect();
+        format!(
+            r#"
+            (() => {{
+                const fonts = new Set({fonts});
+                const check = FontFaceSet.prototype.check;
+                FontFaceSet.prototype.check = function (font, text) {{
+                    // "bold 16px/1.2 'Segoe UI', sans-serif" -> "segoe ui"
+                    const family = String(font)
+                        .replace(/^.*?\d[\d.]*(px|pt|em|rem|%)(\/\S+)?\s+/, '')
+                        .split(',')[0]
+                        .replace(/["']/g, '')
+                        .trim();
+                    return fonts.has(family.toLowerCase()) || check.call(this, font, text);
+                }};
+            }})();
+            "#,
+            fonts = serde_json::to_string(&fonts).unwrap_or_else(|_| "[]".to_string())
+        )
+    }
+}
+
+/// Fingerprints by name: the built-in ones plus those from the environment
+#[derive(Debug)]
+pub struct FingerprintPresets {
+    presets: HashMap<String, Fingerprint>,
+}
+
+impl Default for FingerprintPresets {
+    fn default() -> Self {
+        Self {
+            presets: built_in_presets(),
+        }
+    }
+}
+
+impl FingerprintPresets {
+    /// Built-ins extended by FINGERPRINT_PRESETS_FILE (JSON file) and FINGERPRINT_PRESETS (inline JSON)
+    ///
+    /// Both hold a map of name -> fingerprint. Later sources replace
+    /// fingerprints with the same name, built-ins included.
+    pub fn from_env() -> Self {
+        let mut presets = Self::default();
+
+        if let Ok(path) = std::env::var("FINGERPRINT_PRESETS_FILE") {
+            match std::fs::read_to_string(&path) {
+                Ok(raw) => presets.load(&raw, &path),
+                Err(e) => tracing::warn!("Failed to read fingerprint presets {}: {}", path, e),
+            }
+        }
+
+        if let Ok(raw) = std::env::var("FINGERPRINT_PRESETS") {
+            presets.load(&raw, "FINGERPRINT_PRESETS");
+        }
+
+        presets
+    }
+
+    fn load(&mut self, raw: &str, source: &str) {
+        match serde_json::from_str::<HashMap<String, Fingerprint>>(raw) {
+            Ok(presets) => {
+                tracing::info!("Loaded {} fingerprint preset(s) from {}", presets.len(), source);
+                self.presets.extend(presets);
+            }
+            Err(e) => tracing::warn!("Invalid fingerprint presets in {}, ignoring: {}", source, e),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Fingerprint> {
+        self.presets.get(name)
+    }
+
+    /// Fingerprint names, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.presets.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+fn built_in_presets() -> HashMap<String, Fingerprint> {
+    let chrome = |os: &str| {
+        format!(
+            "Mozilla/5.0 ({}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{} Safari/537.36",
+            os, BUILT_IN_CHROME
+        )
+    };
+    let fonts = |fonts: &[&str]| fonts.iter().map(|font| font.to_string()).collect();
+    let windows = Fingerprint {
+        user_agent: chrome("Windows NT 10.0; Win64; x64"),
+        accept_language: "en-US,en;q=0.9".to_string(),
+        platform: "Win32".to_string(),
+        screen: Some(ScreenSize { width: 1920, height: 1080 }),
+        device_scale_factor: 1.0,
+        timezone: None,
+        fonts: fonts(&["Arial", "Calibri", "Cambria", "Consolas", "Segoe UI", "Times New Roman"]),
+    };
+
+    HashMap::from([
+        (
+            "chrome-windows-de".to_string(),
+            Fingerprint {
+                accept_language: "de-DE,de;q=0.9,en;q=0.8".to_string(),
+                timezone: Some("Europe/Berlin".to_string()),
+                ..windows.clone()
+            },
+        ),
+        ("chrome-windows".to_string(), windows),
+        (
+            "chrome-macos".to_string(),
+            Fingerprint {
+                user_agent: chrome("Macintosh; Intel Mac OS X 10_15_7"),
+                accept_language: "en-US,en;q=0.9".to_string(),
+                platform: "MacIntel".to_string(),
+                screen: Some(ScreenSize { width: 1512, height: 982 }),
+                device_scale_factor: 2.0,
+                timezone: None,
+                fonts: fonts(&["Helvetica Neue", "Menlo", "Monaco", "Arial", "Times New Roman"]),
+            },
+        ),
+        (
+            "chrome-linux".to_string(),
+            Fingerprint {
+                user_agent: chrome("X11; Linux x86_64"),
+                accept_language: "en-US,en;q=0.9".to_string(),
+                platform: "Linux x86_64".to_string(),
+                screen: Some(ScreenSize { width: 1920, height: 1080 }),
+                device_scale_factor: 1.0,
+                timezone: None,
+                fonts: fonts(&["DejaVu Sans", "Liberation Sans", "Liberation Mono", "Ubuntu"]),
+            },
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        let mut presets = FingerprintPresets::default();
+        presets.load(
+            r#"{
+                "kiosk": {"user_agent": "Mozilla/5.0 (X11; Linux x86_64; rv:125.0) Gecko/20100101 Firefox/125.0",
+                          "accept_language": "fr-FR,fr;q=0.9", "platform": "Linux x86_64"}
+            }"#,
+            "test",
+        );
+        presets.load("not json", "test");
+        assert_eq!(
+            presets.names(),
+            vec!["chrome-linux", "chrome-macos", "chrome-windows", "chrome-windows-de", "kiosk"]
+        );
+
+        let german = presets.get("chrome-windows-de").unwrap();
+        assert_eq!(german.locale().as_deref(), Some("de_DE"));
+        let hints = german.client_hints().unwrap();
+        assert_eq!(hints.platform, "Windows");
+        assert_eq!(hints.brands.unwrap()[0].version, "124");
+        assert_eq!(hints.full_version_list.unwrap()[0].version, BUILT_IN_CHROME);
+
+        // Firefox doesn't send client hints
+        let kiosk = presets.get("kiosk").unwrap();
+        assert!(kiosk.client_hints().is_none());
+        assert_eq!(kiosk.device_scale_factor, 1.0);
+        assert_eq!(kiosk.locale().as_deref(), Some("fr_FR"));
+    }
+}
@@ -0,0 +1,81 @@
+use crate::models::FocusedElement;
+
+/// Elements visited by consecutive Tab / Shift+Tab presses, for focus trap detection
+///
+/// Tabbing through a whole page eventually moves focus back to the document
+/// (the trail is cleared there). Returning to an element already on the trail
+/// without passing the document means focus is cycling inside a region.
+#[derive(Debug, Default)]
+pub struct FocusTrail {
+    visited: Vec<FocusedElement>,
+}
+
+impl FocusTrail {
+    /// Record one Tab press; returns the cycle if focus came back to a visited element
+    pub fn record(&mut self, before: Option<FocusedElement>, after: Option<FocusedElement>) -> Option<Vec<FocusedElement>> {
+        // Focus moved some other way since the last press: start over
+        if self.visited.last() != before.as_ref() {
+            self.visited.clear();
+            self.visited.extend(before);
+        }
+
+        let Some(after) = after else {
+            self.visited.clear();
+            return None;
+        };
+
+        match self.visited.iter().position(|visited| *visited == after) {
+            Some(start) => {
+                let cycle = self.visited.split_off(start);
+                self.visited = vec![after];
+                Some(cycle)
+            }
+            None => {
+                self.visited.push(after);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(id: usize) -> Option<FocusedElement> {
+        Some(FocusedElement {
+            element_id: Some(id),
+            role: "link".to_string(),
+            name: Some(format!("Link {}", id)),
+            in_modal: false,
+        })
+    }
+
+    #[test]
+    fn test_focus_trail_detects_cycle() {
+        let mut trail = FocusTrail::default();
+        assert_eq!(trail.record(element(1), element(2)), None);
+        assert_eq!(trail.record(element(2), element(3)), None);
+
+        let cycle = trail.record(element(3), element(2)).unwrap();
+        assert_eq!(cycle, vec![element(2).unwrap(), element(3).unwrap()]);
+    }
+
+    #[test]
+    fn test_focus_trail_resets_on_document_and_jumps() {
+        let mut trail = FocusTrail::default();
+        trail.record(element(1), element(2));
+        // Tabbed past the last element to the document
+        assert_eq!(trail.record(element(2), None), None);
+        assert_eq!(trail.record(None, element(1)), None);
+
+        // Focus was moved by a click in between
+        assert_eq!(trail.record(element(5), element(1)), None);
+    }
+
+    #[test]
+    fn test_focus_trail_detects_stuck_focus() {
+        let mut trail = FocusTrail::default();
+        assert_eq!(trail.record(element(4), element(4)), Some(vec![element(4).unwrap()]));
+    }
+}
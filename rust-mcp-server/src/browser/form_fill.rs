@@ -0,0 +1,257 @@
+use anyhow::Result;
+use chromiumoxide::page::Page;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Attribute tagging the elements matched for a fill, so they can be found again
+pub(crate) const FILL_ATTRIBUTE: &str = "data-mcp-fill-id";
+
+/// How a form field is filled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldKind {
+    /// Typed with real key events (inputs, textareas, contenteditable)
+    Text,
+    Select,
+    Checkbox,
+    Radio,
+}
+
+/// A form field matched to one of the requested keys
+#[derive(Debug, Clone, Deserialize)]
+pub struct MatchedField {
+    pub key: String,
+    /// Value of `FILL_ATTRIBUTE` on the element
+    pub fill_id: u32,
+    pub kind: FieldKind,
+    /// Label the key was matched against
+    pub label: String,
+    pub disabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Resolution {
+    /// In document order
+    matched: Vec<MatchedField>,
+    unmatched: Vec<String>,
+}
+
+/// Outcome for one requested field
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldFillResult {
+    pub field: String,
+    pub success: bool,
+
+    /// Label of the form control the field was matched to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl FieldFillResult {
+    pub fn filled(field: &MatchedField) -> Self {
+        Self {
+            field: field.key.clone(),
+            success: true,
+            matched: Some(field.label.clone()),
+            error: None,
+        }
+    }
+
+    pub fn failed(key: &str, matched: Option<&str>, error: &str) -> Self {
+        Self {
+            field: key.to_string(),
+            success: false,
+            matched: matched.map(str::to_string),
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+/// Matches field keys against form controls and sets non-text values
+pub struct FormFiller;
+
+impl FormFiller {
+    /// Match each key to a form control by label, placeholder, name or id
+    ///
+    /// Exact (case/punctuation-insensitive) label matches win over attribute
+    /// matches, which win over substring matches. Each control is used at most once.
+    pub async fn resolve(
+        page: &Page,
+        fields: &BTreeMap<String, String>,
+    ) -> Result<(Vec<MatchedField>, Vec<String>)> {
+        let keys: Vec<&String> = fields.keys().collect();
+        let script = format!(
+            r#"
+            (() => {{
+                const keys = {keys};
+                const attr = '{attr}';
+                document.querySelectorAll(`[${{attr}}]`).forEach(el => el.removeAttribute(attr));
+
+                const norm = s => (s || '').toLowerCase().replace(/[*:]/g, '').replace(/\s+/g, ' ').trim();
+                const text = el => el ? (el.innerText || el.textContent || '') : '';
+
+                function labelsOf(el) {{
+                    const labels = [];
+                    if (el.labels) el.labels.forEach(l => labels.push(text(l)));
+                    const wrapping = el.closest('label');
+                    if (wrapping) labels.push(text(wrapping));
+                    if (el.getAttribute('aria-label')) labels.push(el.getAttribute('aria-label'));
+                    const labelledBy = el.getAttribute('aria-labelledby');
+                    if (labelledBy) {{
+                        labelledBy.split(/\s+/).forEach(id => labels.push(text(document.getElementById(id))));
+                    }}
+                    return labels.map(norm).filter(Boolean);
+                }}
+
+                function attributesOf(el) {{
+                    return [el.placeholder, el.name, el.id, el.getAttribute('data-testid')]
+                        .map(norm).filter(Boolean);
+                }}
+
+                function kindOf(el) {{
+                    if (el.tagName === 'SELECT') return 'select';
+                    if (el.type === 'checkbox' || el.getAttribute('role') === 'checkbox') return 'checkbox';
+                    if (el.type === 'radio' || el.getAttribute('role') === 'radio') return 'radio';
+                    return 'text';
+                }}
+
+                const skipped = ['hidden', 'submit', 'button', 'reset', 'image', 'file'];
+                const controls = Array.from(document.querySelectorAll(
+                    'input, textarea, select, [contenteditable="true"], [role="textbox"], [role="checkbox"], [role="radio"]'
+                )).filter(el => !skipped.includes(el.type) && el.offsetParent !== null);
+
+                const used = new Set();
+                const matched = [];
+                const unmatched = [];
+
+                for (const key of keys) {{
+                    const wanted = norm(key);
+                    const score = el => {{
+                        const labels = labelsOf(el);
+                        const attrs = attributesOf(el);
+                        if (labels.includes(wanted)) return [3, labels[0]];
+                        if (attrs.includes(wanted)) return [2, labels[0] || attrs[0]];
+                        const partial = labels.find(l => l.includes(wanted)) || attrs.find(a => a.includes(wanted));
+                        if (partial) return [1, labels[0] || partial];
+                        return [0, null];
+                    }};
+
+                    let best = null;
+                    controls.forEach((el, index) => {{
+                        if (used.has(index)) return;
+                        const [points, label] = score(el);
+                        if (points > 0 && (!best || points > best.points)) best = {{ el, index, points, label }};
+                    }});
+
+                    if (!best) {{
+                        unmatched.push(key);
+                        continue;
+                    }}
+
+                    used.add(best.index);
+                    best.el.setAttribute(attr, String(best.index));
+                    matched.push({{
+                        key,
+                        fill_id: best.index,
+                        kind: kindOf(best.el),
+                        label: best.label || key,
+                        disabled: !!best.el.disabled || best.el.getAttribute('aria-disabled') === 'true' ||
+                            !!best.el.readOnly,
+                    }});
+                }}
+
+                matched.sort((a, b) => a.fill_id - b.fill_id);
+                return {{ matched, unmatched }};
+            }})()
+            "#,
+            keys = serde_json::to_string(&keys)?,
+            attr = FILL_ATTRIBUTE,
+        );
+
+        let result = page.evaluate(script).await?;
+        let resolution: Resolution = serde_json::from_value(result.into_value()?)?;
+        Ok((resolution.matched, resolution.unmatched))
+    }
+
+    /// Set a select, checkbox or radio; returns an error message on failure
+    pub async fn set_choice(page: &Page, field: &MatchedField, value: &str) -> Result<Option<String>> {
+        let script = format!(
+            r#"
+            (() => {{
+                const el = document.querySelector('[{attr}="{id}"]');
+                if (!el) return 'field disappeared';
+                const value = {value};
+                const wanted = value.trim().toLowerCase();
+                const fire = () => {{
+                    el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                    el.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                }};
+
+                if (el.tagName === 'SELECT') {{
+                    const option = Array.from(el.options).find(o =>
+                        o.value.toLowerCase() === wanted || o.text.trim().toLowerCase() === wanted
+                    ) || Array.from(el.options).find(o => o.text.trim().toLowerCase().includes(wanted));
+                    if (!option) {{
+                        return `no option '${{value}}' (options: ${{Array.from(el.options).map(o => o.text.trim()).join(', ')}})`;
+                    }}
+                    el.value = option.value;
+                    fire();
+                    return null;
+                }}
+
+                const truthy = ['true', 'yes', 'on', '1', 'checked', 'x'];
+                const falsy = ['false', 'no', 'off', '0', 'unchecked', ''];
+                if (!truthy.includes(wanted) && !falsy.includes(wanted)) {{
+                    return `expected a yes/no value, got '${{value}}'`;
+                }}
+                const want = truthy.includes(wanted);
+                const checked = el.checked !== undefined ? el.checked : el.getAttribute('aria-checked') === 'true';
+                if (checked !== want) {{
+                    if (el.type === 'radio' && !want) return 'a radio button cannot be unchecked directly';
+                    el.click();
+                }}
+                return null;
+            }})()
+            "#,
+            attr = FILL_ATTRIBUTE,
+            id = field.fill_id,
+            value = serde_json::to_string(value)?,
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(result.value().and_then(|v| v.as_str()).map(str::to_string))
+    }
+
+    /// Submit the form containing the filled fields; false if none was found
+    pub async fn submit(page: &Page) -> Result<bool> {
+        let script = format!(
+            r#"
+            (() => {{
+                const field = document.querySelector('[{attr}]');
+                const form = field && (field.form || field.closest('form'));
+                if (!form) return false;
+
+                // Clicking the submit button runs the page's own click handlers
+                const button = form.querySelector(
+                    'button[type="submit"], input[type="submit"], button:not([type])'
+                );
+                if (button) {{
+                    button.click();
+                }} else if (form.requestSubmit) {{
+                    form.requestSubmit();
+                }} else {{
+                    form.submit();
+                }}
+                return true;
+            }})()
+            "#,
+            attr = FILL_ATTRIBUTE,
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(result.into_value::<bool>().unwrap_or(false))
+    }
+}
@@ -0,0 +1,145 @@
+use anyhow::Result;
+use chromiumoxide::page::Page;
+
+use crate::models::PageForm;
+
+use super::COLLECT_ELEMENTS_JS;
+
+/// Reads the forms of a page with labels, values, required flags and validation state
+///
+/// Covers same-origin iframes and open shadow roots like the element list.
+/// Fields outside any form are grouped into one extra entry without a name,
+/// as single-page apps often skip the `<form>` element.
+pub struct FormStateExtractor;
+
+impl FormStateExtractor {
+    pub async fn extract(page: &Page) -> Result<Vec<PageForm>> {
+        let script = format!(
+            r#"
+            (() => {{
+                {}
+
+                const ids = new Map(collectElements().map(({{ el }}, id) => [el, id]));
+                const text = el => (el && (el.innerText || el.textContent) || '').replace(/\s+/g, ' ').trim() || null;
+                const fieldSelector = 'input:not([type="hidden"]):not([type="submit"]):not([type="button"])' +
+                    ':not([type="reset"]):not([type="image"]), textarea, select, ' +
+                    '[role="textbox"], [role="combobox"], [role="checkbox"], [role="radio"], [role="switch"], [role="slider"]';
+                const submitSelector = 'button:not([type="button"]):not([type="reset"]), input[type="submit"], input[type="image"]';
+
+                function formName(form) {{
+                    const legend = form.querySelector('legend');
+                    let heading = null;
+                    for (let node = form; node && !heading; node = node.previousElementSibling) {{
+                        if (/^H[1-6]$/.test(node.tagName)) heading = node;
+                    }}
+                    return getAccessibleName(form) || text(legend) || text(form.querySelector('h1, h2, h3, h4, h5, h6')) ||
+                        text(heading) || form.getAttribute('name') || form.id || null;
+                }}
+
+                function label(el) {{
+                    const own = getAccessibleName(el);
+                    if (own && own !== el.value) return own;
+                    const labels = el.labels ? Array.from(el.labels).map(text).filter(Boolean) : [];
+                    return labels[0] || el.getAttribute('title') || own || null;
+                }}
+
+                // The page's own error text, referenced through aria-errormessage or aria-describedby
+                function errorText(el) {{
+                    const root = el.getRootNode();
+                    for (const attr of ['aria-errormessage', 'aria-describedby']) {{
+                        for (const id of (el.getAttribute(attr) || '').split(/\s+/).filter(Boolean)) {{
+                            const ref = root.getElementById ? root.getElementById(id) : null;
+                            const message = ref && isVisible(ref) ? text(ref) : null;
+                            if (message && (attr === 'aria-errormessage' || ref.getAttribute('role') === 'alert' ||
+                                /error|invalid/i.test(ref.className || ''))) {{
+                                return message;
+                            }}
+                        }}
+                    }}
+                    return null;
+                }}
+
+                function field(el) {{
+                    const tag = el.tagName;
+                    const kind = tag === 'INPUT' ? (el.type || 'text') : tag === 'TEXTAREA' ? 'textarea' :
+                        tag === 'SELECT' ? 'select' : el.getAttribute('role');
+                    const native = tag === 'INPUT' || tag === 'TEXTAREA' || tag === 'SELECT';
+                    const checkable = kind === 'checkbox' || kind === 'radio' || kind === 'switch';
+
+                    let value = null;
+                    if (tag === 'SELECT') {{
+                        value = Array.from(el.selectedOptions).map(option => option.text.trim()).join(', ') || null;
+                    }} else if (native && !checkable) {{
+                        value = kind === 'password' ? (el.value ? '********' : null) : (el.value || null);
+                    }} else if (!native && !checkable) {{
+                        value = el.getAttribute('aria-valuetext') || el.getAttribute('aria-valuenow') || text(el);
+                    }}
+
+                    let checked = null;
+                    if (checkable) {{
+                        checked = native ? el.checked : el.getAttribute('aria-checked') === 'true';
+                    }}
+
+                    const ariaInvalid = (el.getAttribute('aria-invalid') || 'false') !== 'false';
+                    const valid = !ariaInvalid && (!el.validity || el.validity.valid);
+                    const validationMessage = valid ? null : (errorText(el) || el.validationMessage || null);
+
+                    return {{
+                        element_id: ids.has(el) ? ids.get(el) : null,
+                        kind,
+                        label: label(el),
+                        name: el.getAttribute('name'),
+                        value,
+                        checked,
+                        options: tag === 'SELECT' ? Array.from(el.options).map(option => option.text.trim()) : [],
+                        placeholder: el.getAttribute('placeholder'),
+                        autocomplete: el.getAttribute('autocomplete'),
+                        required: el.required === true || el.getAttribute('aria-required') === 'true',
+                        disabled: el.disabled === true || el.getAttribute('aria-disabled') === 'true',
+                        read_only: el.readOnly === true || el.getAttribute('aria-readonly') === 'true',
+                        valid,
+                        validation_message: validationMessage,
+                    }};
+                }}
+
+                function button(el) {{
+                    return {{
+                        element_id: ids.has(el) ? ids.get(el) : null,
+                        label: getAccessibleName(el) || text(el) || el.value || null,
+                        disabled: el.disabled === true || el.getAttribute('aria-disabled') === 'true',
+                    }};
+                }}
+
+                const forms = [];
+                frameDocuments().forEach(({{ doc, frame }}) => {{
+                    const grouped = new Set();
+                    deepQuerySelectorAll(doc, 'form, [role="form"]').forEach(form => {{
+                        const fields = deepQuerySelectorAll(form, fieldSelector).filter(isVisible);
+                        fields.forEach(el => grouped.add(el));
+                        if (!fields.length) return;
+                        forms.push({{
+                            name: formName(form),
+                            action: form.tagName === 'FORM' && form.getAttribute('action') ? form.action : null,
+                            method: form.tagName === 'FORM' ? (form.getAttribute('method') || 'get').toLowerCase() : null,
+                            frame,
+                            fields: fields.map(field),
+                            submit_buttons: deepQuerySelectorAll(form, submitSelector).filter(isVisible).map(button),
+                        }});
+                    }});
+
+                    const loose = deepQuerySelectorAll(doc, fieldSelector)
+                        .filter(el => !grouped.has(el) && !el.form && isVisible(el));
+                    if (loose.length) {{
+                        forms.push({{ name: null, action: null, method: null, frame, fields: loose.map(field), submit_buttons: [] }});
+                    }}
+                }});
+                return forms;
+            }})()
+            "#,
+            COLLECT_ELEMENTS_JS
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(serde_json::from_value(result.into_value()?)?)
+    }
+}
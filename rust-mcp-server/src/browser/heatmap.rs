@@ -0,0 +1,224 @@
+use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::models::SemanticSelector;
+
+/// Path segment standing in for IDs in URL patterns
+const ID_SEGMENT: &str = ":id";
+
+/// Interaction counts of one element on one URL pattern
+#[derive(Debug, Clone, Default)]
+struct ElementCounts {
+    role: String,
+    name: Option<String>,
+    interactions: u64,
+    failures: u64,
+    /// Failures by error code, e.g. "element_not_visible"
+    errors: BTreeMap<String, u64>,
+}
+
+/// How often agents used an element and how often that failed
+#[derive(Debug, Clone, Serialize)]
+pub struct ElementHeat {
+    pub fingerprint: String,
+    pub role: String,
+    pub name: Option<String>,
+    pub interactions: u64,
+    pub failures: u64,
+    /// failures / interactions
+    pub failure_rate: f64,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub errors: BTreeMap<String, u64>,
+}
+
+/// Interaction heatmap of one URL pattern, most used elements first
+#[derive(Debug, Clone, Serialize)]
+pub struct PageHeatmap {
+    pub url_pattern: String,
+    pub interactions: u64,
+    pub failures: u64,
+    pub elements: Vec<ElementHeat>,
+}
+
+/// Totals of one URL pattern, for listing every page
+#[derive(Debug, Clone, Serialize)]
+pub struct PageSummary {
+    pub url_pattern: String,
+    pub interactions: u64,
+    pub failures: u64,
+    pub elements: usize,
+}
+
+/// Counts which elements agents act on and how often that fails, per URL pattern
+///
+/// Shared by all sessions, like `PageHygiene`, so the numbers describe the
+/// whole server. Elements are keyed by `element_fingerprint` and pages by
+/// `url_pattern`, so `/orders/17` and `/orders/18` add up.
+#[derive(Default)]
+pub struct InteractionHeatmap {
+    pages: DashMap<String, BTreeMap<String, ElementCounts>>,
+}
+
+impl InteractionHeatmap {
+    /// Count one action on the element `selector` of the page at `url`
+    ///
+    /// `error` is the failure's error code, `None` if the action succeeded.
+    pub fn record(&self, url: &str, selector: &SemanticSelector, error: Option<&str>) {
+        let mut page = self.pages.entry(url_pattern(url)).or_default();
+        let counts = page
+            .entry(element_fingerprint(selector))
+            .or_insert_with(|| ElementCounts {
+                role: selector.role.clone(),
+                name: selector.name.clone(),
+                ..Default::default()
+            });
+
+        counts.interactions += 1;
+        if let Some(error) = error {
+            counts.failures += 1;
+            *counts.errors.entry(error.to_string()).or_default() += 1;
+        }
+    }
+
+    /// Heatmap of the URL pattern `url` belongs to
+    pub fn page(&self, url: &str) -> Option<PageHeatmap> {
+        let pattern = url_pattern(url);
+        let page = self.pages.get(&pattern)?;
+
+        let mut elements: Vec<ElementHeat> = page
+            .iter()
+            .map(|(fingerprint, counts)| ElementHeat {
+                fingerprint: fingerprint.clone(),
+                role: counts.role.clone(),
+                name: counts.name.clone(),
+                interactions: counts.interactions,
+                failures: counts.failures,
+                failure_rate: counts.failures as f64 / counts.interactions as f64,
+                errors: counts.errors.clone(),
+            })
+            .collect();
+        elements.sort_by(|a, b| b.interactions.cmp(&a.interactions).then(b.failures.cmp(&a.failures)));
+
+        Some(PageHeatmap {
+            url_pattern: pattern,
+            interactions: elements.iter().map(|e| e.interactions).sum(),
+            failures: elements.iter().map(|e| e.failures).sum(),
+            elements,
+        })
+    }
+
+    /// Every URL pattern seen so far, most failures first
+    pub fn pages(&self) -> Vec<PageSummary> {
+        let mut pages: Vec<PageSummary> = self
+            .pages
+            .iter()
+            .map(|page| PageSummary {
+                url_pattern: page.key().clone(),
+                interactions: page.values().map(|c| c.interactions).sum(),
+                failures: page.values().map(|c| c.failures).sum(),
+                elements: page.len(),
+            })
+            .collect();
+        pages.sort_by(|a, b| b.failures.cmp(&a.failures).then(a.url_pattern.cmp(&b.url_pattern)));
+        pages
+    }
+}
+
+/// Stable identity of a targeted element: role and whitespace-normalized, lowercased name
+pub fn element_fingerprint(selector: &SemanticSelector) -> String {
+    let name = selector
+        .name
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    format!("{}:{}", selector.role, name)
+}
+
+/// Page URL without query and fragment, with ID-like path segments replaced by ":id"
+///
+/// "https://shop.example.com/orders/1042?tab=items" becomes
+/// "https://shop.example.com/orders/:id". Anything without a host (about:blank,
+/// strings that aren't URLs) is kept as it is.
+pub fn url_pattern(url: &str) -> String {
+    let parsed = match reqwest::Url::parse(url) {
+        Ok(parsed) if parsed.has_host() => parsed,
+        _ => return url.to_string(),
+    };
+
+    let path = parsed
+        .path()
+        .split('/')
+        .map(|segment| if is_id_segment(segment) { ID_SEGMENT } else { segment })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    format!("{}{}", parsed.origin().ascii_serialization(), path)
+}
+
+/// Numbers, UUIDs and long hex strings such as hashes
+fn is_id_segment(segment: &str) -> bool {
+    let hex_digits = segment.chars().filter(|c| *c != '-').count();
+    !segment.is_empty()
+        && (segment.chars().all(|c| c.is_ascii_digit())
+            || (hex_digits >= 16 && segment.chars().all(|c| c.is_ascii_hexdigit() || c == '-')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selector(role: &str, name: &str) -> SemanticSelector {
+        SemanticSelector {
+            role: role.to_string(),
+            name: Some(name.to_string()),
+            description: None,
+            css_fallback: None,
+            frame: Vec::new(),
+            element_id: None,
+        }
+    }
+
+    #[test]
+    fn test_url_pattern() {
+        assert_eq!(
+            url_pattern("https://Shop.example.com/orders/1042?tab=items#top"),
+            "https://shop.example.com/orders/:id"
+        );
+        assert_eq!(
+            url_pattern("https://shop.example.com/users/0f8fad5b-d9cb-469f-a165-70867728950e/edit"),
+            "https://shop.example.com/users/:id/edit"
+        );
+        assert_eq!(url_pattern("https://shop.example.com/"), "https://shop.example.com/");
+        assert_eq!(url_pattern("https://shop.example.com/v2/cafe"), "https://shop.example.com/v2/cafe");
+        assert_eq!(url_pattern("about:blank"), "about:blank");
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let heatmap = InteractionHeatmap::default();
+        heatmap.record("https://shop.example.com/orders/1", &selector("button", "Pay  now"), None);
+        heatmap.record(
+            "https://shop.example.com/orders/2",
+            &selector("button", "Pay now"),
+            Some("element_not_visible"),
+        );
+        heatmap.record("https://shop.example.com/orders/3", &selector("link", "Invoice"), None);
+        heatmap.record("https://shop.example.com/cart", &selector("button", "Checkout"), None);
+
+        let page = heatmap.page("https://shop.example.com/orders/99?ref=mail").unwrap();
+        assert_eq!(page.url_pattern, "https://shop.example.com/orders/:id");
+        assert_eq!((page.interactions, page.failures), (3, 1));
+        assert_eq!(page.elements[0].fingerprint, "button:pay now");
+        assert_eq!(page.elements[0].failure_rate, 0.5);
+        assert_eq!(page.elements[0].errors["element_not_visible"], 1);
+
+        assert!(heatmap.page("https://shop.example.com/account").is_none());
+        let pages = heatmap.pages();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].url_pattern, "https://shop.example.com/orders/:id");
+    }
+}
@@ -0,0 +1,522 @@
+use anyhow::Result;
+use base64::Engine;
+use chromiumoxide::cdp::browser_protocol::fetch::{
+    AuthChallengeResponse, AuthChallengeResponseResponse, AuthChallengeSource, ContinueRequestParams,
+    ContinueWithAuthParams, EnableParams, EventAuthRequired, EventRequestPaused, FailRequestParams, FulfillRequestParams,
+    HeaderEntry, RequestPattern, RequestStage,
+};
+use chromiumoxide::cdp::browser_protocol::network::{ErrorReason, ResourceType};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+use crate::credentials::{Credential, HttpAuthCredentials};
+use crate::models::{ActionResponse, ErrorCode, NetworkRuleAction};
+use crate::tunnel::{Tunnel, TunnelRegistry, TunnelRequest};
+
+use super::policy::SessionPolicy;
+use super::NetworkRules;
+
+/// An HTTP authentication challenge of the main frame that the server could not answer
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthChallenge {
+    /// Site (or proxy) asking, e.g. "https://staging.example.com"
+    pub origin: String,
+    pub scheme: String,
+    pub realm: String,
+    /// Asked by a proxy rather than the site
+    pub proxy: bool,
+    /// Configured credentials were sent and refused
+    pub rejected: bool,
+}
+
+impl AuthChallenge {
+    /// `auth_required` feedback naming the origin
+    pub fn feedback(&self) -> ActionResponse {
+        let asker = if self.proxy { "The proxy" } else { "The site" };
+        let realm = match self.realm.is_empty() {
+            true => String::new(),
+            false => format!(" (realm \"{}\")", self.realm),
+        };
+
+        let (reason, suggestion) = match self.rejected {
+            true => (
+                format!("{} {}{} rejected the configured HTTP credentials", asker, self.origin, realm),
+                format!("check the username and password for {} in HTTP_AUTH_CREDENTIALS", self.origin),
+            ),
+            false => (
+                format!("{} {}{} asks for HTTP {} authentication", asker, self.origin, realm, self.scheme),
+                format!("add credentials for {} to HTTP_AUTH_CREDENTIALS", self.origin),
+            ),
+        };
+
+        let mut response = ActionResponse::error_with_suggestion(ErrorCode::AuthRequired, &reason, &suggestion);
+        response.details = Some(serde_json::json!({
+            "origin": self.origin,
+            "scheme": self.scheme,
+            "realm": self.realm,
+            "proxy": self.proxy,
+        }));
+        response
+    }
+}
+
+/// Server-wide answers for paused requests: HTTP credentials by origin and open tunnels
+#[derive(Clone)]
+pub struct RequestRouting {
+    pub http_auth: Arc<HttpAuthCredentials>,
+    pub tunnels: Arc<TunnelRegistry>,
+}
+
+/// Answers HTTP authentication for configured origins and reports the challenges it can't answer
+///
+/// Once something needs it (HTTP_AUTH_CREDENTIALS, an open tunnel, a session
+/// policy that filters requests or a network rule), every request is paused
+/// through the Fetch domain; until then requests go out untouched. Requests
+/// to origins in HTTP_AUTH_CREDENTIALS get a Basic `Authorization` header
+/// before they are sent. Challenges that still come up are cancelled, so the
+/// page shows the 401 response instead of waiting on a login prompt nobody
+/// can fill in. Requests the session's policy forbids fail as blocked by the
+/// client, the session's network rules may block, rewrite or stub the rest,
+/// and requests to a tunnel host are answered through that tunnel. Each
+/// paused request is answered on its own task, so a slow rule or tunnel
+/// doesn't hold up the page's other requests.
+#[derive(Clone)]
+pub struct HttpAuthHandler {
+    challenge: watch::Sender<Option<AuthChallenge>>,
+    routing: RequestRouting,
+    policy: Arc<SessionPolicy>,
+    rules: Arc<NetworkRules>,
+    /// Tabs whose requests this handler answers
+    pages: Arc<Mutex<Vec<Page>>>,
+    /// Whether the tabs' requests are paused; never turned off again
+    intercepting: Arc<tokio::sync::Mutex<bool>>,
+}
+
+impl HttpAuthHandler {
+    pub async fn start(
+        page: &Page,
+        routing: RequestRouting,
+        policy: Arc<SessionPolicy>,
+        rules: Arc<NetworkRules>,
+    ) -> Result<Self> {
+        let (challenge, _) = watch::channel(None);
+        let handler = Self {
+            challenge,
+            routing,
+            policy,
+            rules,
+            pages: Arc::new(Mutex::new(Vec::new())),
+            intercepting: Arc::new(tokio::sync::Mutex::new(false)),
+        };
+        handler.attach(page).await?;
+        Ok(handler)
+    }
+
+    /// Route the requests of another tab of the session the same way
+    pub async fn attach(&self, page: &Page) -> Result<()> {
+        let mut paused = page.event_listener::<EventRequestPaused>().await?;
+        let mut challenges = page.event_listener::<EventAuthRequired>().await?;
+
+        let handler = self.clone();
+        let routed_page = page.clone();
+        let mut tunnels = self.routing.tunnels.subscribe();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    request = paused.next() => match request {
+                        Some(request) => {
+                            tokio::spawn(handler.clone().route(routed_page.clone(), request));
+                        }
+                        None => break,
+                    },
+                    Ok(()) = tunnels.changed() => {
+                        if let Err(e) = handler.intercept().await {
+                            tracing::warn!("Failed to pause requests for tunnels: {}", e);
+                        }
+                    }
+                }
+            }
+            // The tab is gone
+            handler.forget(&routed_page);
+        });
+
+        let recorder = self.challenge.clone();
+        let credentials = Arc::clone(&self.routing.http_auth);
+        let auth_page = page.clone();
+        tokio::spawn(async move {
+            while let Some(event) = challenges.next().await {
+                let main_frame = auth_page.mainframe().await.ok().flatten();
+                if event.resource_type == ResourceType::Document && main_frame.as_ref() == Some(&event.frame_id) {
+                    let challenge = &event.auth_challenge;
+                    recorder.send_replace(Some(AuthChallenge {
+                        origin: challenge.origin.clone(),
+                        scheme: challenge.scheme.clone(),
+                        realm: challenge.realm.clone(),
+                        proxy: challenge.source == Some(AuthChallengeSource::Proxy),
+                        rejected: credentials.for_origin(&challenge.origin).is_some(),
+                    }));
+                }
+
+                let cancel = AuthChallengeResponse::new(AuthChallengeResponseResponse::CancelAuth);
+                if let Err(e) = auth_page
+                    .execute(ContinueWithAuthParams::new(event.request_id.clone(), cancel))
+                    .await
+                {
+                    tracing::debug!("Failed to cancel HTTP authentication: {}", e);
+                }
+            }
+        });
+
+        let intercepting = self.intercepting.lock().await;
+        self.pages.lock().unwrap().push(page.clone());
+        if *intercepting {
+            return enable_interception(page).await;
+        }
+        drop(intercepting);
+        self.intercept().await
+    }
+
+    /// Start pausing the requests of every tab, if anything needs them paused
+    ///
+    /// Called again whenever something that may need it is added, such as a
+    /// network rule; does nothing once requests are paused.
+    pub async fn intercept(&self) -> Result<()> {
+        let mut intercepting = self.intercepting.lock().await;
+        if *intercepting || !self.needed().await {
+            return Ok(());
+        }
+
+        // A tab that closed meanwhile fails; the others are paused all the same
+        let pages = self.pages.lock().unwrap().clone();
+        let mut enabled = Ok(());
+        for page in &pages {
+            if let Err(e) = enable_interception(page).await {
+                enabled = Err(e);
+            }
+        }
+        *intercepting = true;
+        tracing::debug!("Pausing the requests of {} tab(s)", pages.len());
+        enabled
+    }
+
+    /// Whether anything answers or filters paused requests
+    async fn needed(&self) -> bool {
+        !self.routing.http_auth.is_empty()
+            || !self.routing.tunnels.is_empty()
+            || self.policy.filters_requests()
+            || !self.rules.is_empty().await
+    }
+
+    fn forget(&self, page: &Page) {
+        self.pages
+            .lock()
+            .unwrap()
+            .retain(|tracked| tracked.target_id() != page.target_id());
+    }
+
+    /// Block, rewrite, stub, tunnel or authenticate one paused request, then let it go
+    async fn route(self, page: Page, request: Arc<EventRequestPaused>) {
+        if self.policy.blocks_request(&request.request.method, &request.request.url, &request.resource_type) {
+            tracing::debug!("Blocked {} {} by session policy", request.request.method, request.request.url);
+            let fail = FailRequestParams::new(request.request_id.clone(), ErrorReason::BlockedByClient);
+            if let Err(e) = page.execute(fail).await {
+                tracing::debug!("Failed to block paused request: {}", e);
+            }
+            return;
+        }
+
+        let mut params = ContinueRequestParams::new(request.request_id.clone());
+        let mut headers = None;
+        match self.rules.decide(&request.request.method, &request.request.url).await {
+            Some(NetworkRuleAction::Block) => {
+                let fail = FailRequestParams::new(request.request_id.clone(), ErrorReason::BlockedByClient);
+                if let Err(e) = page.execute(fail).await {
+                    tracing::debug!("Failed to block paused request: {}", e);
+                }
+                return;
+            }
+            Some(stub @ NetworkRuleAction::Stub { .. }) => {
+                answer_with_stub(page, request, stub).await;
+                return;
+            }
+            Some(NetworkRuleAction::Rewrite { url, headers: set, body }) => {
+                params.url = url;
+                params.post_data = body.map(|body| base64::engine::general_purpose::STANDARD.encode(body).into());
+                if !set.is_empty() {
+                    let mut entries = header_entries(request.request.headers.inner());
+                    entries.retain(|entry| !set.keys().any(|name| name.eq_ignore_ascii_case(&entry.name)));
+                    entries.extend(set.into_iter().map(|(name, value)| HeaderEntry { name, value }));
+                    headers = Some(entries);
+                }
+            }
+            None => {}
+        }
+        let url = params.url.clone().unwrap_or_else(|| request.request.url.clone());
+
+        // A rewrite rule must not lead to what the session's policy forbids
+        if params.url.is_some()
+            && self.policy.blocks_request(&request.request.method, &url, &request.resource_type)
+        {
+            tracing::debug!("Blocked rewrite of {} to {} by session policy", request.request.url, url);
+            let fail = FailRequestParams::new(request.request_id.clone(), ErrorReason::BlockedByClient);
+            if let Err(e) = page.execute(fail).await {
+                tracing::debug!("Failed to block paused request: {}", e);
+            }
+            return;
+        }
+
+        if let Some(tunnel) = self.routing.tunnels.for_url(&url) {
+            answer_through_tunnel(page, tunnel, request).await;
+            return;
+        }
+
+        params.headers = match origin_of(&url).and_then(|origin| self.routing.http_auth.for_origin(&origin)) {
+            Some(credential) => {
+                let current = headers.map(entries_object).unwrap_or_else(|| request.request.headers.inner().clone());
+                Some(with_authorization(&current, credential))
+            }
+            None => headers,
+        };
+
+        if let Err(e) = page.execute(params).await {
+            tracing::debug!("Failed to continue paused request: {}", e);
+        }
+    }
+
+    /// Forget earlier challenges, before starting an action
+    pub fn clear(&self) {
+        self.challenge.send_replace(None);
+    }
+
+    /// Challenge seen since the last `clear`
+    pub fn challenge(&self) -> Option<AuthChallenge> {
+        self.challenge.borrow().clone()
+    }
+
+    /// Wait for the next unanswered challenge
+    pub async fn challenged(&self) -> AuthChallenge {
+        let mut challenge = self.challenge.subscribe();
+        loop {
+            if let Some(challenge) = challenge.borrow_and_update().clone() {
+                return challenge;
+            }
+            if challenge.changed().await.is_err() {
+                // The handler lives as long as the session; never resolve without a challenge
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+}
+
+/// Pause every request of `page` and answer its HTTP authentication challenges
+async fn enable_interception(page: &Page) -> Result<()> {
+    page.execute(
+        EnableParams::builder()
+            .pattern(
+                RequestPattern::builder()
+                    .url_pattern("*")
+                    .request_stage(RequestStage::Request)
+                    .build(),
+            )
+            .handle_auth_requests(true)
+            .build(),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Fulfill a paused request with the response of the client's app, or fail it as unreachable
+async fn answer_through_tunnel(page: Page, tunnel: Arc<Tunnel>, paused: Arc<EventRequestPaused>) {
+    let request = &paused.request;
+    let path = reqwest::Url::parse(&request.url)
+        .map(|url| match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        })
+        .unwrap_or_else(|_| "/".to_string());
+
+    let engine = base64::engine::general_purpose::STANDARD;
+    let body = request.post_data_entries.as_ref().map(|entries| {
+        let bytes: Vec<u8> = entries
+            .iter()
+            .filter_map(|entry| entry.bytes.as_ref())
+            .flat_map(|bytes| engine.decode(AsRef::<str>::as_ref(bytes)).unwrap_or_default())
+            .collect();
+        engine.encode(bytes)
+    });
+    let headers = header_entries(request.headers.inner())
+        .into_iter()
+        .map(|entry| (entry.name, entry.value))
+        .collect();
+
+    let forwarded = tunnel
+        .forward(TunnelRequest {
+            id: String::new(),
+            method: request.method.clone(),
+            path,
+            headers,
+            body,
+        })
+        .await;
+
+    let result = match forwarded {
+        Ok(response) => {
+            let mut params = FulfillRequestParams::new(paused.request_id.clone(), response.status);
+            params.response_headers = Some(
+                response
+                    .headers
+                    .into_iter()
+                    .map(|(name, value)| HeaderEntry { name, value })
+                    .collect(),
+            );
+            params.body = response.body.map(Into::into);
+            page.execute(params).await.map(|_| ())
+        }
+        Err(e) => {
+            tracing::warn!("{} {} through tunnel failed: {}", request.method, request.url, e);
+            let fail = FailRequestParams::new(paused.request_id.clone(), ErrorReason::ConnectionRefused);
+            page.execute(fail).await.map(|_| ())
+        }
+    };
+    if let Err(e) = result {
+        tracing::debug!("Failed to answer tunneled request: {}", e);
+    }
+}
+
+/// Fulfill a paused request with a network rule's canned response, after its delay
+async fn answer_with_stub(page: Page, paused: Arc<EventRequestPaused>, stub: NetworkRuleAction) {
+    let NetworkRuleAction::Stub { status, headers, body, delay_ms } = stub else {
+        return;
+    };
+    if let Some(delay) = delay_ms {
+        tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+    }
+
+    let (body, content_type) = NetworkRuleAction::stub_body(&body);
+    let mut entries: Vec<HeaderEntry> = headers.into_iter().map(|(name, value)| HeaderEntry { name, value }).collect();
+    if let Some(content_type) = content_type {
+        if !entries.iter().any(|entry| entry.name.eq_ignore_ascii_case("content-type")) {
+            entries.push(HeaderEntry { name: "Content-Type".to_string(), value: content_type.to_string() });
+        }
+    }
+    // Pages on other origins can only read the stub with CORS allowed
+    if !entries.iter().any(|entry| entry.name.eq_ignore_ascii_case("access-control-allow-origin")) {
+        entries.push(HeaderEntry { name: "Access-Control-Allow-Origin".to_string(), value: "*".to_string() });
+    }
+
+    let mut params = FulfillRequestParams::new(paused.request_id.clone(), status);
+    params.response_headers = Some(entries);
+    params.body = Some(base64::engine::general_purpose::STANDARD.encode(body).into());
+    if let Err(e) = page.execute(params).await {
+        tracing::debug!("Failed to answer stubbed request: {}", e);
+    }
+}
+
+/// Header entries as CDP's header object
+fn entries_object(entries: Vec<HeaderEntry>) -> serde_json::Value {
+    serde_json::Value::Object(
+        entries
+            .into_iter()
+            .map(|entry| (entry.name, serde_json::Value::String(entry.value)))
+            .collect(),
+    )
+}
+
+/// "https://staging.example.com" for a request URL
+pub(crate) fn origin_of(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    url.has_host().then(|| url.origin().ascii_serialization())
+}
+
+/// CDP's header object as entries
+fn header_entries(headers: &serde_json::Value) -> Vec<HeaderEntry> {
+    headers
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, value)| HeaderEntry {
+            name: name.clone(),
+            value: value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string()),
+        })
+        .collect()
+}
+
+/// The request's headers plus Basic credentials, unless it already carries an Authorization header
+fn with_authorization(headers: &serde_json::Value, credential: &Credential) -> Vec<HeaderEntry> {
+    let mut entries = header_entries(headers);
+
+    if !entries.iter().any(|entry| entry.name.eq_ignore_ascii_case("authorization")) {
+        let token = base64::engine::general_purpose::STANDARD
+            .encode(format!("{}:{}", credential.username, credential.password));
+        entries.push(HeaderEntry {
+            name: "Authorization".to_string(),
+            value: format!("Basic {}", token),
+        });
+    }
+    entries
+}
+
+/// `client_certificate_required` feedback for a navigation to `url`
+pub fn client_certificate_feedback(url: &str) -> ActionResponse {
+    let origin = origin_of(url).unwrap_or_else(|| url.to_string());
+    ActionResponse::error_with_suggestion(
+        ErrorCode::ClientCertificateRequired,
+        &format!("{} requires a TLS client certificate and the browser has none for it", origin),
+        "use an environment without mutual TLS, or install the certificate in the browser's certificate store",
+    )
+}
+
+/// Navigation failed because the site wants a TLS client certificate
+pub fn is_client_certificate_error(error: &str) -> bool {
+    ["ERR_SSL_CLIENT_AUTH_", "ERR_BAD_SSL_CLIENT_AUTH_CERT"]
+        .iter()
+        .any(|code| error.contains(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_authorization() {
+        let credential = Credential {
+            username: "qa".to_string(),
+            password: "secret".to_string(),
+        };
+
+        let headers = with_authorization(&serde_json::json!({"Accept": "text/html"}), &credential);
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[1].value, "Basic cWE6c2VjcmV0");
+
+        // The page's own Authorization header is kept
+        let headers = with_authorization(&serde_json::json!({"authorization": "Bearer abc"}), &credential);
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].value, "Bearer abc");
+
+        assert_eq!(
+            origin_of("https://staging.example.com/login?next=/").as_deref(),
+            Some("https://staging.example.com")
+        );
+        assert_eq!(origin_of("data:text/html,hi"), None);
+    }
+
+    #[test]
+    fn test_feedback() {
+        let challenge = AuthChallenge {
+            origin: "https://staging.example.com".to_string(),
+            scheme: "basic".to_string(),
+            realm: "Staging".to_string(),
+            proxy: false,
+            rejected: false,
+        };
+
+        let response = challenge.feedback();
+        assert_eq!(response.error, Some(ErrorCode::AuthRequired));
+        assert!(response.reason.unwrap().contains("https://staging.example.com (realm \"Staging\")"));
+
+        assert!(is_client_certificate_error("net::ERR_BAD_SSL_CLIENT_AUTH_CERT"));
+        assert!(!is_client_certificate_error("net::ERR_NAME_NOT_RESOLVED"));
+    }
+}
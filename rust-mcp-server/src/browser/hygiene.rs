@@ -0,0 +1,111 @@
+use anyhow::Result;
+use chromiumoxide::page::Page;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use ui_core::selector::ELEMENT_ID_ATTRIBUTE;
+
+use super::context_extractor::{AX_ID_ATTRIBUTE, COLLECT_ELEMENTS_JS};
+use super::form_fill::FILL_ATTRIBUTE;
+use super::login::LOGIN_ATTRIBUTE;
+
+/// Attributes that only have to live for the action that set them
+///
+/// Overlay markers are not listed: `restore_overlay` needs them later.
+const TRANSIENT_ATTRIBUTES: &[&str] = &[ELEMENT_ID_ATTRIBUTE, FILL_ATTRIBUTE, LOGIN_ATTRIBUTE, AX_ID_ATTRIBUTE];
+
+/// Snapshot of the page-hygiene counters
+#[derive(Debug, Clone, Serialize)]
+pub struct HygieneStats {
+    /// Stamped attributes removed by the cleanup after each action
+    pub removed_after_actions: u64,
+    /// Hygiene passes run on context extraction
+    pub passes: u64,
+    /// Passes that still found stamped attributes
+    pub dirty_passes: u64,
+    /// Stamped attributes those passes removed, i.e. missed by the action cleanup
+    pub leftovers_removed: u64,
+    /// Cleanups that failed, e.g. because the page navigated away mid-script
+    pub failures: u64,
+}
+
+/// Removes attributes stamped on page elements and counts what was left behind
+///
+/// Shared by all sessions, so the counters describe the whole server.
+#[derive(Default)]
+pub struct PageHygiene {
+    removed_after_actions: AtomicU64,
+    passes: AtomicU64,
+    dirty_passes: AtomicU64,
+    leftovers_removed: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl PageHygiene {
+    /// Clean up once an action has finished with its elements
+    pub async fn after_action(&self, page: &Page) {
+        match remove_stamps(page).await {
+            Ok(removed) => {
+                self.removed_after_actions.fetch_add(removed, Ordering::Relaxed);
+            }
+            Err(e) => self.failed(e),
+        }
+    }
+
+    /// Hygiene pass before reading the page; anything found here escaped the action cleanup
+    pub async fn sweep(&self, page: &Page) {
+        self.passes.fetch_add(1, Ordering::Relaxed);
+
+        match remove_stamps(page).await {
+            Ok(0) => {}
+            Ok(leftovers) => {
+                tracing::debug!("Page hygiene removed {} leftover stamped attributes", leftovers);
+                self.dirty_passes.fetch_add(1, Ordering::Relaxed);
+                self.leftovers_removed.fetch_add(leftovers, Ordering::Relaxed);
+            }
+            Err(e) => self.failed(e),
+        }
+    }
+
+    pub fn stats(&self) -> HygieneStats {
+        HygieneStats {
+            removed_after_actions: self.removed_after_actions.load(Ordering::Relaxed),
+            passes: self.passes.load(Ordering::Relaxed),
+            dirty_passes: self.dirty_passes.load(Ordering::Relaxed),
+            leftovers_removed: self.leftovers_removed.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+
+    fn failed(&self, error: anyhow::Error) {
+        tracing::debug!("Page hygiene failed: {}", error);
+        self.failures.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Remove every transient attribute from the document and its same-origin frames, returning how many were removed
+async fn remove_stamps(page: &Page) -> Result<u64> {
+    let script = format!(
+        r#"
+        (() => {{
+            {helpers}
+
+            const attrs = {attrs};
+            let removed = 0;
+            frameDocuments().forEach(({{ doc }}) => {{
+                const elements = deepQuerySelectorAll(doc, attrs.map(attr => `[${{attr}}]`).join(','));
+                elements.forEach(el => attrs.forEach(attr => {{
+                    if (el.hasAttribute(attr)) {{
+                        el.removeAttribute(attr);
+                        removed += 1;
+                    }}
+                }}));
+            }});
+            return removed;
+        }})()
+        "#,
+        helpers = COLLECT_ELEMENTS_JS,
+        attrs = serde_json::to_string(TRANSIENT_ATTRIBUTES)?,
+    );
+
+    Ok(page.evaluate(script).await?.into_value()?)
+}
@@ -0,0 +1,184 @@
+use anyhow::Result;
+use chromiumoxide::cdp::browser_protocol::input::{DispatchKeyEventParams, DispatchKeyEventType};
+use chromiumoxide::element::Element;
+use chromiumoxide::keys;
+use chromiumoxide::page::Page;
+
+use crate::models::KeyModifier;
+
+/// Named keys accepted by `press_key` (case-insensitive); single characters are accepted too
+pub const NAMED_KEYS: &[&str] = &[
+    "Enter", "Tab", "Escape", "Space", "Backspace", "Delete", "Insert", "Home", "End", "PageUp", "PageDown",
+    "ArrowUp", "ArrowDown", "ArrowLeft", "ArrowRight", "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9",
+    "F10", "F11", "F12",
+];
+
+/// A key press and the modifiers held during it, e.g. "Shift+Tab" or "Control+Enter"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChord {
+    /// Key name in the US keyboard layout ("Tab", " ", "a", "A", ...)
+    pub key: &'static str,
+    pub modifiers: Vec<KeyModifier>,
+}
+
+impl KeyChord {
+    /// Parse "Tab", "shift+tab", "Ctrl+Enter", "a", ...; `modifiers` are held as well
+    ///
+    /// `None` for unknown keys or modifiers.
+    pub fn parse(input: &str, modifiers: &[KeyModifier]) -> Option<Self> {
+        let input = input.trim();
+        let (prefix, name) = if input == "+" {
+            ("", "+")
+        } else if let Some(prefix) = input.strip_suffix("++") {
+            (prefix, "+")
+        } else {
+            input.rsplit_once('+').unwrap_or(("", input))
+        };
+
+        let mut held: Vec<KeyModifier> = Vec::new();
+        let written = prefix
+            .split('+')
+            .filter(|part| !part.trim().is_empty())
+            .map(KeyModifier::from_name);
+        for modifier in written.chain(modifiers.iter().copied().map(Some)) {
+            let modifier = modifier?;
+            if !held.contains(&modifier) {
+                held.push(modifier);
+            }
+        }
+
+        let key = Self::resolve(name.trim(), held.contains(&KeyModifier::Shift))?;
+        Some(Self { key, modifiers: held })
+    }
+
+    /// Key name as listed in the US layout table
+    fn resolve(name: &str, shifted: bool) -> Option<&'static str> {
+        let name = match name.to_ascii_lowercase().as_str() {
+            "esc" => "Escape",
+            "return" => "Enter",
+            _ => name,
+        };
+
+        if let Some(named) = NAMED_KEYS.iter().find(|key| key.eq_ignore_ascii_case(name)) {
+            // The table names the space bar " "
+            let name = if *named == "Space" { " " } else { named };
+            return keys::get_key_definition(name).map(|definition| definition.key);
+        }
+
+        let mut chars = name.chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return None;
+        };
+        // Letters follow Shift, like on a physical keyboard
+        let c = match shifted {
+            true => c.to_ascii_uppercase(),
+            false => c.to_ascii_lowercase(),
+        };
+        keys::get_key_definition(c.to_string()).map(|definition| definition.key)
+    }
+
+    /// Tab or Shift+Tab, which move focus through the page
+    pub fn moves_focus(&self) -> bool {
+        self.key == "Tab" && self.modifiers.iter().all(|m| *m == KeyModifier::Shift)
+    }
+
+    /// Dispatch the key to whatever element has focus, like a physical keyboard would
+    pub async fn press(&self, page: &Page) -> Result<()> {
+        let definition = keys::get_key_definition(self.key)
+            .ok_or_else(|| anyhow::anyhow!("No key definition for '{}'", self.key))?;
+
+        // `Input.dispatchKeyEvent` modifier bits
+        let modifiers = self
+            .modifiers
+            .iter()
+            .map(|modifier| match modifier {
+                KeyModifier::Alt => 1,
+                KeyModifier::Control => 2,
+                KeyModifier::Meta => 4,
+                KeyModifier::Shift => 8,
+            })
+            .sum::<i64>();
+
+        let mut event = DispatchKeyEventParams::builder()
+            .key(definition.key)
+            .code(definition.code)
+            .windows_virtual_key_code(definition.key_code)
+            .native_virtual_key_code(definition.key_code)
+            .modifiers(modifiers);
+
+        // Shortcuts don't insert text; keys producing text need a real keyDown
+        // for their default action (e.g. Enter submits)
+        let shortcut = self.modifiers.iter().any(|m| *m != KeyModifier::Shift);
+        let text = definition.text.or((definition.key.len() == 1).then_some(definition.key));
+        let key_down = match text {
+            Some(text) if !shortcut => {
+                event = event.text(text);
+                DispatchKeyEventType::KeyDown
+            }
+            _ => DispatchKeyEventType::RawKeyDown,
+        };
+
+        page.execute(event.clone().r#type(key_down).build().map_err(anyhow::Error::msg)?)
+            .await?;
+        page.execute(
+            event
+                .r#type(DispatchKeyEventType::KeyUp)
+                .build()
+                .map_err(anyhow::Error::msg)?,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Whether the element, or something inside it, has keyboard focus
+pub async fn has_focus(element: &Element) -> Result<bool> {
+    let result = element
+        .call_js_fn(
+            "function() { return this === document.activeElement || this.contains(document.activeElement); }",
+            false,
+        )
+        .await?;
+
+    Ok(result.result.value.and_then(|value| value.as_bool()).unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_chord() {
+        let chord = |key: &'static str, modifiers: &[KeyModifier]| {
+            Some(KeyChord {
+                key,
+                modifiers: modifiers.to_vec(),
+            })
+        };
+
+        assert_eq!(KeyChord::parse("Tab", &[]), chord("Tab", &[]));
+        assert_eq!(KeyChord::parse("shift+tab", &[]), chord("Tab", &[KeyModifier::Shift]));
+        assert_eq!(KeyChord::parse(" Ctrl + Enter ", &[]), chord("Enter", &[KeyModifier::Control]));
+        assert_eq!(KeyChord::parse("space", &[]), chord(" ", &[]));
+        assert_eq!(KeyChord::parse("esc", &[]), chord("Escape", &[]));
+        assert_eq!(KeyChord::parse("Control+A", &[]), chord("a", &[KeyModifier::Control]));
+        assert_eq!(KeyChord::parse("a", &[KeyModifier::Shift]), chord("A", &[KeyModifier::Shift]));
+        assert_eq!(
+            KeyChord::parse("Shift+Tab", &[KeyModifier::Shift, KeyModifier::Alt]),
+            chord("Tab", &[KeyModifier::Shift, KeyModifier::Alt])
+        );
+        assert_eq!(KeyChord::parse("Control++", &[]), chord("+", &[KeyModifier::Control]));
+
+        assert_eq!(KeyChord::parse("Hyper+a", &[]), None);
+        assert_eq!(KeyChord::parse("NotAKey", &[]), None);
+        assert_eq!(KeyChord::parse("", &[]), None);
+    }
+
+    #[test]
+    fn test_moves_focus() {
+        assert!(KeyChord::parse("Shift+Tab", &[]).unwrap().moves_focus());
+        assert!(!KeyChord::parse("Control+Tab", &[]).unwrap().moves_focus());
+        assert!(!KeyChord::parse("Enter", &[]).unwrap().moves_focus());
+    }
+}
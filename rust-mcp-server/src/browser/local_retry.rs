@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use crate::models::{ActionRequest, ActionResponse, ErrorCode};
+
+const TRANSIENT_ERRORS: &[&str] = &[
+    "detached",
+    "no node with given id",
+    "could not compute box model",
+    "node does not have a layout object",
+    "cannot find context with specified id",
+    "execution context was destroyed",
+];
+
+/// Re-running element actions that failed for a passing reason, before the failure reaches the agent
+///
+/// Re-renders replace the element between lookup and click, and fade-ins keep
+/// it hidden for a moment. Both usually clear up within a few hundred
+/// milliseconds, so the same action is tried again instead of costing an LLM call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalRetryPolicy {
+    /// Attempts after the first one; 0 turns local retries off
+    pub max_retries: u32,
+    /// Pause before each retry
+    pub delay: Duration,
+}
+
+impl LocalRetryPolicy {
+    /// Reads ACTION_LOCAL_RETRIES (default: 2) and ACTION_LOCAL_RETRY_DELAY_MS (default: 250)
+    pub fn from_env() -> Self {
+        let max_retries = std::env::var("ACTION_LOCAL_RETRIES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2);
+        let delay_ms = std::env::var("ACTION_LOCAL_RETRY_DELAY_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(250);
+
+        Self {
+            max_retries,
+            delay: Duration::from_millis(delay_ms),
+        }
+    }
+
+    /// Whether a failed `action` is worth another local attempt
+    ///
+    /// Only actions on one element qualify; they find their target anew on
+    /// every attempt. Waits already wait, and page-level actions may have had
+    /// effects a second run would repeat.
+    pub fn should_retry(action: &ActionRequest, outcome: &anyhow::Result<ActionResponse>) -> bool {
+        let targets_element = action.selector().is_some() && !matches!(action, ActionRequest::WaitForElement { .. });
+        targets_element
+            && match outcome {
+                Ok(response) => match response.error {
+                    Some(ErrorCode::ElementNotVisible) => true,
+                    Some(ErrorCode::ExecutionError) => response.reason.as_deref().is_some_and(is_transient_error),
+                    _ => false,
+                },
+                Err(e) => is_transient_error(&e.to_string()),
+            }
+    }
+}
+
+fn is_transient_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    TRANSIENT_ERRORS.iter().any(|error| message.contains(error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SemanticSelector;
+
+    #[test]
+    fn test_should_retry() {
+        let selector = SemanticSelector {
+            role: "button".to_string(),
+            name: Some("Save".to_string()),
+            description: None,
+            css_fallback: None,
+            frame: vec![],
+            element_id: None,
+        };
+        let click = ActionRequest::Click { selector: selector.clone() };
+        let failed = |error: ErrorCode, reason: &str| Ok(ActionResponse::error_with_suggestion(error, reason, ""));
+
+        assert!(LocalRetryPolicy::should_retry(&click, &Err(anyhow::anyhow!("Node is detached from document"))));
+        assert!(LocalRetryPolicy::should_retry(
+            &click,
+            &failed(ErrorCode::ExecutionError, "Failed to click: No node with given id found")
+        ));
+        assert!(LocalRetryPolicy::should_retry(&click, &failed(ErrorCode::ElementNotVisible, "below viewport")));
+
+        // Wrong targets and real errors go straight back to the agent
+        assert!(!LocalRetryPolicy::should_retry(&click, &failed(ErrorCode::ElementNotFound, "no Save button")));
+        assert!(!LocalRetryPolicy::should_retry(&click, &Err(anyhow::anyhow!("Browser closed"))));
+        assert!(!LocalRetryPolicy::should_retry(&click, &Ok(ActionResponse::success())));
+
+        let wait = ActionRequest::WaitForElement { selector, timeout_ms: None };
+        assert!(!LocalRetryPolicy::should_retry(&wait, &Err(anyhow::anyhow!("Node is detached from document"))));
+        assert!(!LocalRetryPolicy::should_retry(&ActionRequest::Reload, &Err(anyhow::anyhow!("detached"))));
+    }
+}
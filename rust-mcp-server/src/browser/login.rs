@@ -0,0 +1,180 @@
+use anyhow::Result;
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+/// Attribute tagging the located login fields ("username" / "password")
+pub(crate) const LOGIN_ATTRIBUTE: &str = "data-mcp-login";
+
+/// Login fields found on the current page
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct LoginFields {
+    pub username: bool,
+    pub password: bool,
+}
+
+/// What the page looks like after submitting
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoginState {
+    pub url: String,
+    pub password_visible: bool,
+    /// Text of a visible error banner or invalid-field message
+    pub error: Option<String>,
+}
+
+/// Heuristics for username/password login forms
+pub struct LoginHelper;
+
+impl LoginHelper {
+    /// Find and tag the username and password fields
+    ///
+    /// The username is the input before the password field that looks like a
+    /// user name or email (autocomplete, name, id, placeholder), else the input
+    /// directly before it. Identifier-first forms have no password field yet.
+    pub async fn locate(page: &Page) -> Result<LoginFields> {
+        let script = format!(
+            r#"
+            (() => {{
+                const attr = '{attr}';
+                document.querySelectorAll(`[${{attr}}]`).forEach(el => el.removeAttribute(attr));
+
+                const usable = el => el.offsetParent !== null && !el.disabled && !el.readOnly;
+                const passwords = Array.from(document.querySelectorAll('input[type="password"]')).filter(usable);
+                // Skip "new password" fields of sign-up forms when a current-password field exists
+                const password = passwords.find(el => !/new-password/.test(el.autocomplete || '')) || passwords[0] || null;
+
+                const scope = (password && (password.form || password.closest('form'))) || document;
+                let candidates = Array.from(scope.querySelectorAll('input'))
+                    .filter(el => usable(el) && ['text', 'email', 'tel'].includes(el.type));
+                if (password) {{
+                    candidates = candidates.filter(el =>
+                        el.compareDocumentPosition(password) & Node.DOCUMENT_POSITION_FOLLOWING);
+                }}
+
+                const hint = el => [el.autocomplete, el.name, el.id, el.placeholder, el.getAttribute('aria-label')]
+                    .join(' ').toLowerCase();
+                const username =
+                    candidates.find(el => /username|email/.test(el.autocomplete || '')) ||
+                    candidates.find(el => /user|email|login|account|phone/.test(hint(el))) ||
+                    (password ? candidates[candidates.length - 1] : null) ||
+                    null;
+
+                if (username) username.setAttribute(attr, 'username');
+                if (password) password.setAttribute(attr, 'password');
+                return {{ username: !!username, password: !!password }};
+            }})()
+            "#,
+            attr = LOGIN_ATTRIBUTE,
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(serde_json::from_value(result.into_value()?)?)
+    }
+
+    /// Click the submit button of the tagged field's form; false if there is none
+    pub async fn submit(page: &Page, field: &str) -> Result<bool> {
+        let script = format!(
+            r#"
+            (() => {{
+                const field = document.querySelector('[{attr}="{field}"]');
+                if (!field) return false;
+                const scope = field.form || field.closest('form') || document;
+
+                const button = scope.querySelector('button[type="submit"], input[type="submit"]') ||
+                    Array.from(scope.querySelectorAll('button, [role="button"], input[type="button"]'))
+                        .find(el => el.offsetParent !== null &&
+                            /log ?in|sign ?in|continue|next|submit/i.test(el.textContent || el.value || ''));
+                if (!button || button.disabled) return false;
+
+                button.click();
+                return true;
+            }})()
+            "#,
+            attr = LOGIN_ATTRIBUTE,
+            field = field,
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(result.into_value::<bool>().unwrap_or(false))
+    }
+
+    /// Enter a one-time code into the page's code field(s); false if there are none
+    ///
+    /// Handles a single field (autocomplete="one-time-code" or a name/label
+    /// hinting at a code) and rows of one-character boxes.
+    pub async fn fill_otp(page: &Page, code: &str) -> Result<bool> {
+        let script = format!(
+            r#"
+            (() => {{
+                const code = {code};
+                const usable = el => el.offsetParent !== null && !el.disabled && !el.readOnly;
+                const inputs = Array.from(document.querySelectorAll('input')).filter(el =>
+                    usable(el) && ['text', 'tel', 'number', 'password', ''].includes(el.getAttribute('type') || ''));
+
+                // Framework-controlled inputs only notice values set through the native setter
+                const setter = Object.getOwnPropertyDescriptor(HTMLInputElement.prototype, 'value').set;
+                const set = (el, value) => {{
+                    el.focus();
+                    setter.call(el, value);
+                    el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                    el.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                }};
+
+                const boxes = inputs.filter(el => el.maxLength === 1);
+                if (boxes.length >= code.length && boxes.length <= code.length + 2) {{
+                    Array.from(code).forEach((digit, i) => set(boxes[i], digit));
+                    return true;
+                }}
+
+                const hint = el => [el.autocomplete, el.name, el.id, el.placeholder, el.getAttribute('aria-label')]
+                    .join(' ').toLowerCase();
+                const field =
+                    inputs.find(el => el.autocomplete === 'one-time-code') ||
+                    inputs.find(el => /otp|one.?time|2fa|mfa|totp|verification|security.?code|code|token/.test(hint(el))) ||
+                    null;
+                if (!field) return false;
+
+                set(field, code);
+                return true;
+            }})()
+            "#,
+            code = serde_json::to_string(code)?,
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(result.into_value::<bool>().unwrap_or(false))
+    }
+
+    /// Current URL, whether a password field is still shown, and any error banner
+    pub async fn state(page: &Page) -> Result<LoginState> {
+        let script = r#"
+            (() => {
+                const shown = el => el.offsetParent !== null;
+                const text = el => (el.innerText || el.textContent || '').replace(/\s+/g, ' ').trim();
+
+                const banners = document.querySelectorAll(
+                    '[role="alert"], [aria-live="assertive"], .error, .errors, .alert-danger, .alert-error, ' +
+                    '.error-message, .invalid-feedback, .form-error, .flash-error'
+                );
+                let error = Array.from(banners).map(el => shown(el) ? text(el) : '').find(Boolean) || null;
+
+                if (!error) {
+                    const invalid = Array.from(document.querySelectorAll('input[aria-invalid="true"]')).find(shown);
+                    if (invalid) {
+                        const described = invalid.getAttribute('aria-describedby');
+                        const message = described && document.getElementById(described);
+                        error = (message && text(message)) || invalid.validationMessage || 'A field was marked invalid';
+                    }
+                }
+
+                return {
+                    url: location.href,
+                    password_visible: Array.from(document.querySelectorAll('input[type="password"]')).some(shown),
+                    error: error ? error.slice(0, 200) : null,
+                };
+            })()
+        "#;
+
+        let result = page.evaluate(script).await?;
+        Ok(serde_json::from_value(result.into_value()?)?)
+    }
+}
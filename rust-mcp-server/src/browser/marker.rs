@@ -0,0 +1,130 @@
+use anyhow::Result;
+use chromiumoxide::cdp::browser_protocol::network::{EnableParams, Headers, SetExtraHttpHeadersParams};
+use chromiumoxide::page::Page;
+
+/// Attribute on the banner's host element
+pub const BANNER_ATTRIBUTE: &str = "data-mcp-automation-banner";
+
+/// Request header announcing automated traffic
+pub const AUTOMATED_BY_HEADER: &str = "X-Automated-By";
+
+/// How controlled pages are marked as automated, for staging environments and observers
+#[derive(Debug, Clone)]
+pub struct AutomationMarker {
+    /// Show a small banner on every page of the session
+    pub banner: bool,
+    pub banner_text: String,
+    /// Keep the banner in screenshots taken by the server
+    pub banner_in_screenshots: bool,
+    /// `X-Automated-By` value sent with every request; no header when `None`
+    pub header: Option<String>,
+}
+
+impl Default for AutomationMarker {
+    fn default() -> Self {
+        Self {
+            banner: false,
+            banner_text: "Automated session – Interact UI Agent".to_string(),
+            banner_in_screenshots: false,
+            header: None,
+        }
+    }
+}
+
+impl AutomationMarker {
+    /// AUTOMATION_BANNER / AUTOMATION_BANNER_TEXT / AUTOMATION_BANNER_IN_SCREENSHOTS /
+    /// AUTOMATED_BY_HEADER (banner and header are off by default)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            banner: std::env::var("AUTOMATION_BANNER")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.banner),
+            banner_text: std::env::var("AUTOMATION_BANNER_TEXT")
+                .ok()
+                .filter(|text| !text.trim().is_empty())
+                .unwrap_or(defaults.banner_text),
+            banner_in_screenshots: std::env::var("AUTOMATION_BANNER_IN_SCREENSHOTS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.banner_in_screenshots),
+            header: std::env::var("AUTOMATED_BY_HEADER")
+                .ok()
+                .filter(|value| !value.trim().is_empty()),
+        }
+    }
+
+    /// Builder method to override the banner for one session
+    pub fn with_banner(mut self, enabled: bool) -> Self {
+        self.banner = enabled;
+        self
+    }
+
+    /// Mark the page before its first navigation, so every document and request is covered
+    pub async fn install(&self, page: &Page) -> Result<()> {
+        if let Some(value) = &self.header {
+            page.execute(EnableParams::default()).await?;
+            page.execute(SetExtraHttpHeadersParams::new(Headers::new(serde_json::json!({
+                AUTOMATED_BY_HEADER: value,
+            }))))
+            .await?;
+        }
+
+        if self.banner {
+            page.evaluate_on_new_document(self.banner_script()?).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether the banner has to be hidden while a screenshot is taken
+    pub fn hides_banner_in_screenshots(&self) -> bool {
+        self.banner && !self.banner_in_screenshots
+    }
+
+    /// Show or hide the banner on the current document
+    pub async fn set_banner_visible(&self, page: &Page, visible: bool) -> Result<()> {
+        let script = format!(
+            "document.querySelectorAll('[{}]').forEach(host => host.style.visibility = '{}')",
+            BANNER_ATTRIBUTE,
+            if visible { "" } else { "hidden" }
+        );
+        page.evaluate(script).await?;
+        Ok(())
+    }
+
+    /// Fixed badge in a closed shadow root: hidden from the accessibility tree,
+    /// unaffected by page styles and transparent to clicks
+    fn banner_script(&self) -> Result<String> {
+        Ok(format!(
+            r#"
+            (() => {{
+                if (window.top !== window) return;
+                const mount = () => {{
+                    const root = document.documentElement;
+                    if (!root || root.querySelector('[{attr}]')) return;
+
+                    const host = document.createElement('div');
+                    host.setAttribute('{attr}', '');
+                    host.setAttribute('aria-hidden', 'true');
+                    host.style.cssText = 'position:fixed;right:8px;bottom:8px;z-index:2147483647;pointer-events:none;';
+
+                    const badge = document.createElement('div');
+                    badge.textContent = {text};
+                    badge.style.cssText = 'font:600 12px/1.4 system-ui,sans-serif;color:#fff;background:rgba(190,18,60,.85);' +
+                        'padding:4px 10px;border-radius:4px;box-shadow:0 1px 4px rgba(0,0,0,.3);';
+                    host.attachShadow({{ mode: 'closed' }}).appendChild(badge);
+                    root.appendChild(host);
+                }};
+                mount();
+                document.addEventListener('DOMContentLoaded', mount);
+                window.addEventListener('load', mount);
+            }})()
+            "#,
+            attr = BANNER_ATTRIBUTE,
+            text = serde_json::to_string(&self.banner_text)?,
+        ))
+    }
+}
@@ -1,5 +1,74 @@
+pub mod assistive;
 pub mod automation;
+pub mod checkbox;
+pub mod console;
+pub mod consent;
+pub mod dialogs;
 pub mod context_extractor;
+pub mod context_push;
+pub mod downloads;
+pub mod element_facts;
+pub mod element_map;
+pub mod events;
+pub mod fingerprint;
+pub mod focus;
+pub mod form_state;
+pub mod form_fill;
+pub mod heatmap;
+pub mod http_auth;
+pub mod hygiene;
+pub mod keyboard;
+pub mod local_retry;
+pub mod login;
+pub mod marker;
+pub mod navigation;
+pub mod network_rules;
+pub mod overlay;
+pub mod policy;
+pub mod readability;
+pub mod scraper;
+pub mod select;
+pub mod settle;
+pub mod storage;
+pub mod table;
+pub mod tabs;
+pub mod verify;
+pub mod waits;
+pub mod watcher;
 
+pub use assistive::*;
 pub use automation::*;
+pub use checkbox::*;
+pub use console::*;
+pub use consent::*;
+pub use dialogs::*;
 pub use context_extractor::*;
+pub use context_push::*;
+pub use downloads::*;
+pub use element_facts::*;
+pub use element_map::*;
+pub use events::*;
+pub use fingerprint::*;
+pub use focus::*;
+pub use form_state::*;
+pub use form_fill::*;
+pub use heatmap::*;
+pub use http_auth::*;
+pub use hygiene::*;
+pub use keyboard::*;
+pub use local_retry::*;
+pub use login::*;
+pub use marker::*;
+pub use navigation::*;
+pub use network_rules::*;
+pub use overlay::*;
+pub use policy::*;
+pub use readability::*;
+pub use scraper::*;
+pub use select::*;
+pub use settle::*;
+pub use storage::*;
+pub use table::*;
+pub use tabs::*;
+pub use verify::*;
+pub use watcher::*;
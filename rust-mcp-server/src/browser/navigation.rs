@@ -0,0 +1,153 @@
+use chromiumoxide::cdp::browser_protocol::page::{
+    EventFrameNavigated, EventLoadEventFired, EventNavigatedWithinDocument,
+};
+use chromiumoxide::page::Page;
+use chrono::Utc;
+use futures::StreamExt;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::models::{NavigationEntry, SessionEventKind};
+
+use super::EventLog;
+
+/// Bounded breadcrumb of recently visited pages in a session
+pub struct NavigationHistory {
+    entries: VecDeque<NavigationEntry>,
+    capacity: usize,
+}
+
+impl NavigationHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Create history sized from NAVIGATION_HISTORY_SIZE (default: 5)
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("NAVIGATION_HISTORY_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(5);
+
+        Self::new(capacity)
+    }
+
+    /// Record a visit, ignoring blank pages and repeated URLs
+    pub fn record(&mut self, url: &str, title: Option<String>) {
+        if self.capacity == 0 || url.is_empty() || url == "about:blank" {
+            return;
+        }
+
+        if let Some(last) = self.entries.back_mut() {
+            if last.url == url {
+                if title.is_some() {
+                    last.title = title;
+                }
+                return;
+            }
+        }
+
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(NavigationEntry {
+            url: url.to_string(),
+            title,
+            visited_at: Utc::now(),
+        });
+    }
+
+    /// Fill in the title of the current page once it is known
+    pub fn set_current_title(&mut self, title: String) {
+        if let Some(last) = self.entries.back_mut() {
+            if last.title.is_none() && !title.is_empty() {
+                last.title = Some(title);
+            }
+        }
+    }
+
+    /// Oldest first
+    pub fn entries(&self) -> Vec<NavigationEntry> {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+enum NavigationEvent {
+    FrameNavigated(Arc<EventFrameNavigated>),
+    WithinDocument(Arc<EventNavigatedWithinDocument>),
+    Loaded,
+}
+
+/// Listen to CDP navigation events and record main-frame visits into `history`
+pub async fn track_navigation(
+    page: Page,
+    history: Arc<RwLock<NavigationHistory>>,
+    events: Arc<EventLog>,
+) -> anyhow::Result<()> {
+    let frame_navigated = page
+        .event_listener::<EventFrameNavigated>()
+        .await?
+        .map(NavigationEvent::FrameNavigated);
+    let within_document = page
+        .event_listener::<EventNavigatedWithinDocument>()
+        .await?
+        .map(NavigationEvent::WithinDocument);
+    let loaded = page
+        .event_listener::<EventLoadEventFired>()
+        .await?
+        .map(|_| NavigationEvent::Loaded);
+
+    let mut stream = futures::stream::select(
+        futures::stream::select(frame_navigated, within_document),
+        loaded,
+    );
+
+    tokio::spawn(async move {
+        while let Some(event) = stream.next().await {
+            match event {
+                NavigationEvent::FrameNavigated(event) => {
+                    // Only the main frame counts as a page visit
+                    if event.frame.parent_id.is_none() && event.frame.url != "about:blank" {
+                        history.write().await.record(&event.frame.url, None);
+                        events
+                            .push(SessionEventKind::Navigated {
+                                url: event.frame.url.clone(),
+                            })
+                            .await;
+                    }
+                }
+                NavigationEvent::WithinDocument(event) => {
+                    let is_main_frame = page
+                        .mainframe()
+                        .await
+                        .ok()
+                        .flatten()
+                        .map(|id| id == event.frame_id)
+                        .unwrap_or(false);
+
+                    if is_main_frame {
+                        let title = page.get_title().await.ok().flatten();
+                        history.write().await.record(&event.url, title);
+                        events
+                            .push(SessionEventKind::Navigated {
+                                url: event.url.clone(),
+                            })
+                            .await;
+                    }
+                }
+                NavigationEvent::Loaded => {
+                    if let Ok(Some(title)) = page.get_title().await {
+                        history.write().await.set_current_title(title);
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use tokio::sync::RwLock;
+
+use crate::models::{NetworkRule, NetworkRuleAction};
+
+use super::filename_matches;
+
+/// A session's request interception rules: block, rewrite or stub by URL pattern
+///
+/// Consulted for every request the page makes, after the session policy and
+/// before tunnels and HTTP credentials. The first matching rule wins, in the
+/// order the rules were added.
+#[derive(Default)]
+pub struct NetworkRules {
+    rules: RwLock<Vec<NetworkRule>>,
+}
+
+impl NetworkRules {
+    /// Add rules after the existing ones, giving each an ID
+    pub async fn add(&self, rules: Vec<NetworkRule>) -> Vec<NetworkRule> {
+        let added: Vec<NetworkRule> = rules
+            .into_iter()
+            .map(|rule| NetworkRule {
+                id: uuid::Uuid::new_v4().to_string(),
+                hits: 0,
+                ..rule
+            })
+            .collect();
+        self.rules.write().await.extend(added.iter().cloned());
+        added
+    }
+
+    pub async fn list(&self) -> Vec<NetworkRule> {
+        self.rules.read().await.clone()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.rules.read().await.is_empty()
+    }
+
+    /// Remove one rule; false if there is none with that ID
+    pub async fn remove(&self, id: &str) -> bool {
+        let mut rules = self.rules.write().await;
+        let before = rules.len();
+        rules.retain(|rule| rule.id != id);
+        rules.len() != before
+    }
+
+    /// Remove every rule, returning how many there were
+    pub async fn clear(&self) -> usize {
+        std::mem::take(&mut *self.rules.write().await).len()
+    }
+
+    /// What to do with a request, counting the hit on the rule that decides it
+    pub async fn decide(&self, method: &str, url: &str) -> Option<NetworkRuleAction> {
+        let mut rules = self.rules.write().await;
+        let rule = rules.iter_mut().find(|rule| matches(rule, method, url))?;
+        rule.hits += 1;
+        tracing::debug!("Network rule {} ({}) handles {} {}", rule.id, rule.url_pattern, method, url);
+        Some(rule.action.clone())
+    }
+}
+
+/// Same glob as download filename patterns, over the whole URL
+fn matches(rule: &NetworkRule, method: &str, url: &str) -> bool {
+    rule.method.as_ref().is_none_or(|wanted| wanted.eq_ignore_ascii_case(method)) && filename_matches(&rule.url_pattern, url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(url_pattern: &str, method: Option<&str>, action: NetworkRuleAction) -> NetworkRule {
+        NetworkRule {
+            id: String::new(),
+            url_pattern: url_pattern.to_string(),
+            method: method.map(str::to_string),
+            action,
+            hits: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decide() {
+        let rules = NetworkRules::default();
+        let added = rules
+            .add(vec![
+                rule("https://api.example.com/orders*", Some("POST"), NetworkRuleAction::Block),
+                rule("https://*.analytics.example.net/*", None, NetworkRuleAction::Block),
+                rule(
+                    "https://api.example.com/*",
+                    None,
+                    NetworkRuleAction::Stub {
+                        status: 503,
+                        headers: Default::default(),
+                        body: serde_json::Value::Null,
+                        delay_ms: None,
+                    },
+                ),
+            ])
+            .await;
+
+        assert_eq!(
+            rules.decide("POST", "https://api.example.com/orders/12").await,
+            Some(NetworkRuleAction::Block)
+        );
+        // A GET of the same URL falls through to the catch-all stub
+        assert!(matches!(
+            rules.decide("GET", "https://api.example.com/orders/12").await,
+            Some(NetworkRuleAction::Stub { status: 503, .. })
+        ));
+        assert!(rules.decide("GET", "https://example.com/").await.is_none());
+        assert_eq!(rules.list().await[0].hits, 1);
+
+        assert!(rules.remove(&added[0].id).await);
+        assert!(!rules.remove(&added[0].id).await);
+        assert!(matches!(
+            rules.decide("POST", "https://api.example.com/orders/12").await,
+            Some(NetworkRuleAction::Stub { .. })
+        ));
+        assert_eq!(rules.clear().await, 2);
+    }
+}
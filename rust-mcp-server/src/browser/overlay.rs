@@ -0,0 +1,178 @@
+use anyhow::Result;
+use chromiumoxide::cdp::browser_protocol::page::EventFrameNavigated;
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::models::{ClosedOverlay, OverlayKind};
+
+/// Whether obstructing overlays should be closed automatically.
+/// Reads OVERLAY_AUTO_CLOSE (default: true).
+pub fn overlay_auto_close_from_env() -> bool {
+    std::env::var("OVERLAY_AUTO_CLOSE")
+        .map(|v| !matches!(v.to_lowercase().as_str(), "false" | "0" | "off"))
+        .unwrap_or(true)
+}
+
+/// Forget the overlays closed in `page` whenever its main frame loads another document
+///
+/// Overlay IDs are tags on elements of the old document; a new page may tag
+/// its own overlays with the same IDs.
+pub async fn forget_overlays_on_navigation(page: &Page, closed: Arc<RwLock<Vec<ClosedOverlay>>>) -> Result<()> {
+    let mut navigated = page.event_listener::<EventFrameNavigated>().await?;
+    tokio::spawn(async move {
+        while let Some(event) = navigated.next().await {
+            if event.frame.parent_id.is_none() {
+                closed.write().await.clear();
+            }
+        }
+    });
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct DetectedOverlay {
+    kind: OverlayKind,
+    label: String,
+}
+
+/// Heuristics for newsletter modals and chat widgets that obstruct the viewport.
+///
+/// Overlays are hidden rather than removed, so they can be restored later
+/// if the agent turns out to need them.
+pub struct OverlayCloser;
+
+impl OverlayCloser {
+    /// Hide detected overlays, tagging each with an ID starting at `next_id`
+    pub async fn close(page: &Page, next_id: u32) -> Result<Vec<ClosedOverlay>> {
+        let script = Self::build_close_script(next_id);
+        let result = page.evaluate(script).await?;
+        let detected: Vec<DetectedOverlay> = serde_json::from_value(result.into_value()?)?;
+
+        Ok(detected
+            .into_iter()
+            .enumerate()
+            .map(|(i, overlay)| ClosedOverlay {
+                id: next_id + i as u32,
+                kind: overlay.kind,
+                label: overlay.label,
+            })
+            .collect())
+    }
+
+    /// Un-hide an overlay previously closed by `close`. Returns false if it no longer exists.
+    pub async fn restore(page: &Page, overlay_id: u32) -> Result<bool> {
+        let script = format!(
+            r#"
+            (() => {{
+                const el = document.querySelector('[data-mcp-overlay-id="{}"]');
+                if (!el) return false;
+                el.style.display = el.getAttribute('data-mcp-prev-display') || '';
+                el.removeAttribute('data-mcp-prev-display');
+                el.removeAttribute('data-mcp-overlay-id');
+                const prevOverflow = el.getAttribute('data-mcp-prev-body-overflow');
+                if (prevOverflow !== null) {{
+                    document.body.style.overflow = prevOverflow;
+                    el.removeAttribute('data-mcp-prev-body-overflow');
+                }}
+                return true;
+            }})()
+            "#,
+            overlay_id
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(result.into_value::<bool>().unwrap_or(false))
+    }
+
+    fn build_close_script(next_id: u32) -> String {
+        format!(
+            r#"
+            (() => {{
+                let nextId = {};
+                const closed = [];
+
+                const chatVendors = [
+                    {{ label: 'Intercom', selector: '#intercom-container, .intercom-lightweight-app, iframe[name^="intercom"]' }},
+                    {{ label: 'Drift', selector: '#drift-widget-container, #drift-frame-controller' }},
+                    {{ label: 'Zendesk', selector: '#launcher, iframe#webWidget' }},
+                    {{ label: 'HubSpot', selector: '#hubspot-messages-iframe-container' }},
+                    {{ label: 'Crisp', selector: '.crisp-client' }},
+                    {{ label: 'Tawk.to', selector: 'iframe[title*="chat widget" i]' }},
+                    {{ label: 'LiveChat', selector: '#chat-widget-container' }},
+                ];
+                const newsletterWords = /newsletter|subscribe|sign up for|join our|% off|discount|exclusive offer/i;
+
+                function isVisible(el) {{
+                    const style = window.getComputedStyle(el);
+                    const rect = el.getBoundingClientRect();
+                    return style.display !== 'none' &&
+                           style.visibility !== 'hidden' &&
+                           rect.width > 0 && rect.height > 0;
+                }}
+
+                function isFloating(el) {{
+                    for (let node = el; node && node !== document.body; node = node.parentElement) {{
+                        const position = window.getComputedStyle(node).position;
+                        if (position === 'fixed' || position === 'sticky') return true;
+                    }}
+                    return false;
+                }}
+
+                // Never hide what the user is interacting with
+                function containsFocus(el) {{
+                    return document.activeElement && document.activeElement !== document.body &&
+                           el.contains(document.activeElement);
+                }}
+
+                function hide(el, kind, label) {{
+                    if (el.hasAttribute('data-mcp-overlay-id') || containsFocus(el)) return;
+                    el.setAttribute('data-mcp-overlay-id', String(nextId));
+                    el.setAttribute('data-mcp-prev-display', el.style.display || '');
+                    if (kind === 'newsletter' && window.getComputedStyle(document.body).overflow === 'hidden') {{
+                        el.setAttribute('data-mcp-prev-body-overflow', document.body.style.overflow || '');
+                        document.body.style.overflow = '';
+                    }}
+                    el.style.setProperty('display', 'none', 'important');
+                    closed.push({{ kind, label }});
+                    nextId++;
+                }}
+
+                for (const {{ label, selector }} of chatVendors) {{
+                    const el = document.querySelector(selector);
+                    if (el && isVisible(el)) hide(el, 'chat_widget', label);
+                }}
+
+                // Newsletter modals: floating dialogs with an email field and marketing copy
+                const viewportArea = window.innerWidth * window.innerHeight;
+                const candidates = document.querySelectorAll('[role="dialog"], [aria-modal="true"], [class*="modal" i], [class*="popup" i], [id*="modal" i], [id*="popup" i], [class*="newsletter" i]');
+                for (const el of candidates) {{
+                    if (!isVisible(el) || !isFloating(el)) continue;
+                    if (!el.querySelector('input[type="email"], input[name*="email" i]')) continue;
+                    if (!newsletterWords.test(el.textContent || '')) continue;
+                    if (el.querySelector('input[type="password"]')) continue;
+                    const rect = el.getBoundingClientRect();
+                    if (rect.width * rect.height < viewportArea * 0.05) continue;
+
+                    // Hide the outermost floating container (backdrop included)
+                    let target = el;
+                    while (target.parentElement && target.parentElement !== document.body &&
+                           isFloating(target.parentElement) &&
+                           !target.parentElement.querySelector('main, nav')) {{
+                        target = target.parentElement;
+                    }}
+
+                    const heading = el.querySelector('h1, h2, h3, [class*="title" i]');
+                    const label = heading ? heading.textContent.trim().slice(0, 60) : 'Newsletter popup';
+                    hide(target, 'newsletter', label);
+                }}
+
+                return closed;
+            }})()
+            "#,
+            next_id
+        )
+    }
+}
@@ -0,0 +1,145 @@
+use chromiumoxide::cdp::browser_protocol::network::ResourceType;
+use serde::{Deserialize, Serialize};
+
+use crate::hooks::origin_matches;
+use crate::models::{ActionRequest, ActionResponse, DialogPolicy, ErrorCode};
+
+use super::http_auth::origin_of;
+use super::SettlePolicy;
+
+/// Resource types a session can refuse to load
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockedResource {
+    Image,
+    Media,
+    Font,
+    Stylesheet,
+}
+
+impl BlockedResource {
+    fn matches(self, resource_type: &ResourceType) -> bool {
+        matches!(
+            (self, resource_type),
+            (Self::Image, ResourceType::Image)
+                | (Self::Media, ResourceType::Media)
+                | (Self::Font, ResourceType::Font)
+                | (Self::Stylesheet, ResourceType::Stylesheet)
+        )
+    }
+}
+
+/// Rules on what a session's browser may load and do, set by its preset
+///
+/// The default allows everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionPolicy {
+    /// Origins pages and frames may be loaded from ("*." host wildcards allowed); empty allows any
+    pub allowed_origins: Vec<String>,
+    /// Fail requests that may change data (any method but GET, HEAD and OPTIONS)
+    pub read_only: bool,
+    /// `tool` values rejected by the action endpoint
+    pub blocked_actions: Vec<String>,
+    pub blocked_resources: Vec<BlockedResource>,
+    /// When pages of each site count as settled, for context pushes
+    pub settle: Vec<SettlePolicy>,
+    /// How JavaScript dialogs are answered
+    pub dialogs: DialogPolicy,
+}
+
+impl SessionPolicy {
+    /// Whether a page at `url` may be opened
+    pub fn allows_url(&self, url: &str) -> bool {
+        if self.allowed_origins.is_empty() || url == "about:blank" {
+            return true;
+        }
+        origin_of(url).is_some_and(|origin| {
+            self.allowed_origins
+                .iter()
+                .any(|pattern| origin_matches(pattern, &origin))
+        })
+    }
+
+    /// Whether any request the page makes may have to fail
+    pub fn filters_requests(&self) -> bool {
+        self.read_only || !self.allowed_origins.is_empty() || !self.blocked_resources.is_empty()
+    }
+
+    /// Whether a request the page makes has to fail
+    pub fn blocks_request(&self, method: &str, url: &str, resource_type: &ResourceType) -> bool {
+        (self.read_only && !matches!(method, "GET" | "HEAD" | "OPTIONS"))
+            || (*resource_type == ResourceType::Document && !self.allows_url(url))
+            || self.blocked_resources.iter().any(|blocked| blocked.matches(resource_type))
+    }
+
+    /// Feedback for an action the policy forbids; `None` if it may run
+    pub fn check_action(&self, action: &ActionRequest) -> Option<ActionResponse> {
+        let tool = action.tool();
+        if self.blocked_actions.iter().any(|blocked| blocked == tool) {
+            return Some(ActionResponse::error_with_suggestion(
+                ErrorCode::ActionBlocked,
+                &format!("The '{}' action is disabled for this session", tool),
+                "reach the goal with other actions, or use a session with a different preset",
+            ));
+        }
+
+        match action {
+            ActionRequest::Navigate { url } | ActionRequest::NewTab { url: Some(url) } if !self.allows_url(url) => Some(ActionResponse::error_with_suggestion(
+                ErrorCode::UrlNotAllowed,
+                &format!("{} is outside the origins this session may open", url),
+                &format!("stay on {}", self.allowed_origins.join(", ")),
+            )),
+            // Requests would be blocked anyway; say so before the action gets halfway
+            ActionRequest::Login { .. } | ActionRequest::InvokeHook { .. } | ActionRequest::FillForm { submit: true, .. }
+                if self.read_only =>
+            {
+                Some(ActionResponse::error_with_suggestion(
+                    ErrorCode::ReadOnly,
+                    "This session is read-only and can't submit data",
+                    "only read the page; fill_form without submit still works",
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ScreenshotFormat;
+
+    #[test]
+    fn test_session_policy() {
+        let policy = SessionPolicy {
+            allowed_origins: vec!["https://*.example.com".to_string()],
+            read_only: true,
+            blocked_actions: vec!["screenshot".to_string()],
+            blocked_resources: vec![BlockedResource::Image],
+            settle: Vec::new(),
+            dialogs: DialogPolicy::default(),
+        };
+
+        assert!(policy.allows_url("https://app.example.com/orders"));
+        assert!(!policy.allows_url("https://example.org/"));
+        assert!(SessionPolicy::default().allows_url("https://example.org/"));
+
+        let document = ResourceType::Document;
+        assert!(!policy.blocks_request("GET", "https://app.example.com/", &document));
+        assert!(policy.blocks_request("POST", "https://app.example.com/orders", &ResourceType::Xhr));
+        assert!(policy.blocks_request("GET", "https://example.org/", &document));
+        // Subresources may come from any origin
+        assert!(!policy.blocks_request("GET", "https://cdn.example.net/app.js", &ResourceType::Script));
+        assert!(policy.blocks_request("GET", "https://app.example.com/logo.png", &ResourceType::Image));
+
+        let navigate = ActionRequest::Navigate { url: "https://example.org/".to_string() };
+        assert_eq!(policy.check_action(&navigate).unwrap().error, Some(ErrorCode::UrlNotAllowed));
+        let new_tab = ActionRequest::NewTab { url: Some("https://example.org/".to_string()) };
+        assert_eq!(policy.check_action(&new_tab).unwrap().error, Some(ErrorCode::UrlNotAllowed));
+        let login = ActionRequest::Login { credential_name: "qa".to_string() };
+        assert_eq!(policy.check_action(&login).unwrap().error, Some(ErrorCode::ReadOnly));
+        let screenshot = ActionRequest::Screenshot { full_page: false, format: ScreenshotFormat::Png, quality: None };
+        assert_eq!(policy.check_action(&screenshot).unwrap().error, Some(ErrorCode::ActionBlocked));
+        assert!(policy.check_action(&ActionRequest::Reload).is_none());
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::Result;
+use chromiumoxide::page::Page;
+
+use crate::models::PageContent;
+
+/// Finds a page's main content and reads it as headings, paragraphs, lists, quotes, code and images
+///
+/// Readability-style: every paragraph of 25 characters or more scores its
+/// parent, and half as much its grandparent, by length and commas. Class
+/// and id names like "article" or "content" raise the score, and names like
+/// "comment", "sidebar" or "share" lower it. The container with the best
+/// score, discounted by how much of its text is links, becomes the root, or
+/// the `<article>` around it. Navigation, asides, footers and forms inside
+/// the root are dropped; an article's own `<header>` is kept for its title.
+pub struct ReadabilityExtractor;
+
+impl ReadabilityExtractor {
+    pub async fn extract(page: &Page) -> Result<PageContent> {
+        let script = r#"
+            (() => {
+                const NEGATIVE = /comment|footer|nav|menu|sidebar|side-bar|share|social|related|promo|sponsor|advert|\bads?\b|banner|cookie|subscribe|newsletter|breadcrumb|pagination|popup|modal/i;
+                const POSITIVE = /article|content|post|entry|story|body|main|text|blog/i;
+                const SKIP = 'nav, aside, footer, form, script, style, noscript, template, button, svg, iframe, dialog, ' +
+                    '[role="navigation"], [role="banner"], [role="contentinfo"], [role="complementary"], [aria-hidden="true"]';
+                const BLOCK = 'p, div, section, article, main, ul, ol, pre, blockquote, table, figure, img, h1, h2, h3, h4, h5, h6';
+                const clean = text => (text || '').replace(/\s+/g, ' ');
+                const visible = el => {
+                    const style = getComputedStyle(el);
+                    return style.display !== 'none' && style.visibility !== 'hidden';
+                };
+                const names = el => (typeof el.className === 'string' ? el.className : '') + ' ' + el.id;
+                const classWeight = el => (POSITIVE.test(names(el)) ? 25 : 0) - (NEGATIVE.test(names(el)) ? 25 : 0);
+                const linkDensity = el => {
+                    const total = clean(el.textContent).trim().length || 1;
+                    let links = 0;
+                    el.querySelectorAll('a').forEach(a => links += clean(a.textContent).trim().length);
+                    return links / total;
+                };
+
+                const scores = new Map();
+                document.body.querySelectorAll('p, pre, td, blockquote').forEach(paragraph => {
+                    if (paragraph.closest(SKIP)) return;
+                    const text = clean(paragraph.textContent).trim();
+                    if (text.length < 25) return;
+                    const score = 1 + text.split(',').length + Math.min(3, Math.floor(text.length / 100));
+                    const parent = paragraph.parentElement;
+                    [[parent, 1], [parent && parent.parentElement, 0.5]].forEach(([node, share]) => {
+                        if (!node || node === document.documentElement) return;
+                        if (!scores.has(node)) scores.set(node, classWeight(node));
+                        scores.set(node, scores.get(node) + score * share);
+                    });
+                });
+
+                let root = null;
+                let best = 0;
+                scores.forEach((score, node) => {
+                    const discounted = score * (1 - linkDensity(node));
+                    if (discounted > best) {
+                        best = discounted;
+                        root = node;
+                    }
+                });
+                root = (root && root.closest('article')) || root ||
+                    document.querySelector('article, main, [role="main"]') || document.body;
+
+                function spans(el) {
+                    const out = [];
+                    (function walk(node, format) {
+                        if (node.nodeType === Node.TEXT_NODE) {
+                            const text = clean(node.textContent);
+                            if (text) out.push({ text, ...format });
+                            return;
+                        }
+                        if (node.nodeType !== Node.ELEMENT_NODE || node.matches(SKIP) || !visible(node)) return;
+                        if (node !== el && (node.tagName === 'UL' || node.tagName === 'OL')) return;
+                        if (node.tagName === 'BR') {
+                            out.push({ text: ' ', ...format });
+                            return;
+                        }
+                        const next = { ...format };
+                        if (node.tagName === 'STRONG' || node.tagName === 'B') next.strong = true;
+                        if (node.tagName === 'EM' || node.tagName === 'I') next.emphasis = true;
+                        if (node.tagName === 'CODE' || node.tagName === 'KBD') next.code = true;
+                        const href = node.tagName === 'A' ? node.getAttribute('href') : null;
+                        if (href && !href.startsWith('#') && !/^javascript:/i.test(href)) next.href = node.href;
+                        node.childNodes.forEach(child => walk(child, next));
+                    })(el, {});
+
+                    // Merge neighbours with the same formatting
+                    const merged = [];
+                    for (const span of out) {
+                        const last = merged[merged.length - 1];
+                        if (last && last.href === span.href && !!last.strong === !!span.strong &&
+                            !!last.emphasis === !!span.emphasis && !!last.code === !!span.code) {
+                            last.text = clean(last.text + span.text);
+                        } else {
+                            merged.push(span);
+                        }
+                    }
+                    return merged;
+                }
+
+                const blocks = [];
+                const push = block => {
+                    if (block.spans && !block.spans.some(span => span.text.trim())) return;
+                    blocks.push(block);
+                };
+
+                function list(el, depth) {
+                    for (const item of el.children) {
+                        if (item.tagName !== 'LI' || !visible(item)) continue;
+                        push({ kind: 'list_item', ordered: el.tagName === 'OL', depth, spans: spans(item) });
+                        item.querySelectorAll(':scope > ul, :scope > ol').forEach(nested => list(nested, depth + 1));
+                    }
+                }
+
+                function walk(el) {
+                    for (const node of el.children) {
+                        if (node.matches(SKIP) || !visible(node)) continue;
+                        const tag = node.tagName;
+                        if (/^H[1-6]$/.test(tag)) {
+                            push({ kind: 'heading', level: Number(tag[1]), spans: spans(node) });
+                        } else if (tag === 'P') {
+                            push({ kind: 'paragraph', spans: spans(node) });
+                        } else if (tag === 'UL' || tag === 'OL') {
+                            list(node, 0);
+                        } else if (tag === 'PRE') {
+                            const code = node.querySelector('code') || node;
+                            const language = (names(code).match(/(?:lang|language)-(\w+)/) || [])[1];
+                            blocks.push({ kind: 'code', text: node.innerText, language });
+                        } else if (tag === 'BLOCKQUOTE') {
+                            push({ kind: 'quote', spans: spans(node) });
+                        } else if (tag === 'IMG') {
+                            if (node.alt && (node.currentSrc || node.src)) {
+                                blocks.push({ kind: 'image', alt: clean(node.alt).trim(), src: node.currentSrc || node.src });
+                            }
+                        } else if (tag === 'TABLE') {
+                            node.querySelectorAll('tr').forEach(row => {
+                                const cells = Array.from(row.cells).map(cell => clean(cell.innerText).trim()).filter(Boolean);
+                                if (cells.length) push({ kind: 'paragraph', spans: [{ text: cells.join(' | ') }] });
+                            });
+                        } else if (Array.from(node.children).some(child => child.matches(BLOCK))) {
+                            walk(node);
+                        } else if (!(linkDensity(node) > 0.8 && clean(node.textContent).trim().length < 80)) {
+                            // A div used as a paragraph, unless it is a short run of links like "Share · Tweet"
+                            push({ kind: 'paragraph', spans: spans(node) });
+                        }
+                    }
+                }
+                walk(root);
+
+                const meta = selector => {
+                    const tag = document.querySelector(selector);
+                    return tag ? clean(tag.getAttribute('content')).trim() : '';
+                };
+                const bylineElement = document.querySelector('[rel="author"], [itemprop="author"], .byline, .author');
+                const byline = meta('meta[name="author"]') ||
+                    (bylineElement ? clean(bylineElement.textContent).trim().slice(0, 100) : '');
+
+                return {
+                    url: location.href,
+                    title: meta('meta[property="og:title"]') || document.title,
+                    byline: byline || null,
+                    blocks,
+                };
+            })()
+        "#;
+
+        let result = page.evaluate(script).await?;
+        Ok(serde_json::from_value(result.into_value()?)?)
+    }
+}
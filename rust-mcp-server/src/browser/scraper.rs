@@ -0,0 +1,107 @@
+use anyhow::Result;
+use chromiumoxide::page::Page;
+
+use crate::models::PageCandidates;
+
+/// Characters of main-content text handed to the LLM
+const MAX_TEXT_CHARS: usize = 8000;
+
+/// Record groups kept per page, largest first
+const MAX_GROUPS: usize = 3;
+
+/// Records kept per group
+const MAX_GROUP_ITEMS: usize = 50;
+
+/// Gathers the parts of a page that usually hold its data, for schema extraction
+///
+/// Structured markup (JSON-LD, OpenGraph and product meta tags) comes first
+/// since it is exact. Listings are found as runs of at least three siblings
+/// with the same tag and classes outside navigation, header and footer, and
+/// the main content's text covers the rest.
+pub struct Scraper;
+
+impl Scraper {
+    pub async fn candidates(page: &Page) -> Result<PageCandidates> {
+        let script = format!(
+            r#"
+            (() => {{
+                const maxText = {max_text};
+                const clean = text => (text || '').replace(/\s+/g, ' ').trim();
+
+                const jsonLd = [];
+                document.querySelectorAll('script[type="application/ld+json"]').forEach(script => {{
+                    try {{ jsonLd.push(JSON.parse(script.textContent)); }} catch (e) {{}}
+                }});
+
+                const meta = {{}};
+                document.querySelectorAll('meta[property^="og:"], meta[property^="product:"], meta[name="description"], meta[itemprop]')
+                    .forEach(tag => {{
+                        const key = tag.getAttribute('property') || tag.getAttribute('name') || tag.getAttribute('itemprop');
+                        const content = clean(tag.getAttribute('content'));
+                        if (key && content && !(key in meta)) meta[key] = content;
+                    }});
+
+                function path(el) {{
+                    const parts = [];
+                    for (let node = el; node && node !== document.body && parts.length < 3; node = node.parentElement) {{
+                        const classes = Array.from(node.classList).slice(0, 2).map(c => '.' + CSS.escape(c)).join('');
+                        parts.unshift(node.id ? '#' + CSS.escape(node.id) : node.tagName.toLowerCase() + classes);
+                        if (node.id) break;
+                    }}
+                    return parts.join(' > ');
+                }}
+
+                const signature = el => el.tagName + '.' + Array.from(el.classList).sort().join('.');
+                const groups = [];
+                document.body.querySelectorAll('*').forEach(parent => {{
+                    if (parent.children.length < 3 || parent.closest('nav, header, footer, [role="navigation"]')) return;
+                    const bySignature = new Map();
+                    for (const child of parent.children) {{
+                        const key = signature(child);
+                        bySignature.set(key, (bySignature.get(key) || []).concat(child));
+                    }}
+                    const items = Array.from(bySignature.values())
+                        .sort((a, b) => b.length - a.length)[0]
+                        .filter(child => child.offsetParent !== null || child.getClientRects().length);
+                    const texts = items.map(child => clean(child.innerText));
+                    const filled = texts.filter(text => text.length >= 3);
+                    if (filled.length < 3) return;
+                    const score = filled.length * Math.min(200, filled.reduce((sum, text) => sum + text.length, 0) / filled.length);
+                    groups.push({{ parent, items, texts, score }});
+                }});
+
+                const chosen = [];
+                for (const group of groups.sort((a, b) => b.score - a.score)) {{
+                    if (chosen.length >= {max_groups}) break;
+                    if (chosen.some(other => other.parent.contains(group.parent) || group.parent.contains(other.parent))) continue;
+                    chosen.push(group);
+                }}
+
+                const main = document.querySelector('main, [role="main"], article') || document.body;
+                return {{
+                    url: location.href,
+                    title: document.title,
+                    json_ld: jsonLd,
+                    meta,
+                    groups: chosen.map(({{ parent, items, texts }}) => ({{
+                        container: path(parent),
+                        items: items.slice(0, {max_items}).map((child, i) => ({{
+                            text: texts[i].slice(0, 300),
+                            links: Array.from(child.querySelectorAll('a[href]')).map(a => a.href).slice(0, 3),
+                            images: Array.from(child.querySelectorAll('img'))
+                                .map(img => img.currentSrc || img.src).filter(Boolean).slice(0, 2),
+                        }})),
+                    }})),
+                    text: clean(main.innerText).slice(0, maxText),
+                }};
+            }})()
+            "#,
+            max_text = MAX_TEXT_CHARS,
+            max_groups = MAX_GROUPS,
+            max_items = MAX_GROUP_ITEMS,
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(serde_json::from_value(result.into_value()?)?)
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use chromiumoxide::element::Element;
+use serde::Deserialize;
+
+/// Result of choosing an option in a `<select>` or ARIA listbox
+#[derive(Debug, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SelectOutcome {
+    Selected { value: Option<String>, label: String },
+    /// No option matched; `options` lists the available labels
+    NotFound { options: Vec<String> },
+    Disabled { label: String },
+    /// The element is neither a `<select>` nor a listbox or combobox with a popup
+    NotAList,
+}
+
+/// Choose an option by value (exact) or label (case-insensitive, then substring)
+///
+/// Native selects get their value set followed by `input` and `change` events.
+/// ARIA listboxes have no value to set, so the option is clicked; a collapsed
+/// combobox is opened first and its popup located through `aria-controls`.
+pub async fn choose_option(element: &Element, value: Option<&str>, label: Option<&str>) -> Result<SelectOutcome> {
+    let script = format!(
+        r#"async function() {{
+            const wantedValue = {value};
+            const wantedLabel = {label};
+            const norm = s => (s || '').replace(/\s+/g, ' ').trim().toLowerCase();
+            const done = outcome => JSON.stringify(outcome);
+
+            function pick(options, valueOf, labelOf) {{
+                if (wantedValue !== null) {{
+                    const byValue = options.find(o => valueOf(o) === wantedValue);
+                    if (byValue || wantedLabel === null) return byValue || null;
+                }}
+                const wanted = norm(wantedLabel);
+                return options.find(o => norm(labelOf(o)) === wanted) ||
+                    options.find(o => norm(labelOf(o)).includes(wanted)) || null;
+            }}
+
+            if (this.tagName === 'SELECT') {{
+                const options = Array.from(this.options);
+                const option = pick(options, o => o.value, o => o.text);
+                if (!option) return done({{ status: 'not_found', options: options.map(o => o.text.trim()) }});
+                if (option.disabled) return done({{ status: 'disabled', label: option.text.trim() }});
+
+                this.value = option.value;
+                option.selected = true;
+                this.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                this.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                return done({{ status: 'selected', value: option.value, label: option.text.trim() }});
+            }}
+
+            let listbox = this.getAttribute('role') === 'listbox' ? this : null;
+            if (!listbox) {{
+                const popupId = this.getAttribute('aria-controls') || this.getAttribute('aria-owns');
+                if (!popupId) return done({{ status: 'not_a_list' }});
+                if (this.getAttribute('aria-expanded') !== 'true') this.click();
+
+                // Popups are often rendered only once opened
+                for (let i = 0; i < 10 && !listbox; i++) {{
+                    listbox = document.getElementById(popupId);
+                    if (!listbox) await new Promise(resolve => setTimeout(resolve, 100));
+                }}
+                if (!listbox) return done({{ status: 'not_a_list' }});
+            }}
+
+            const options = Array.from(listbox.querySelectorAll('[role="option"]'));
+            const valueOf = o => o.getAttribute('data-value') || o.getAttribute('value') || o.id || null;
+            const option = pick(options, valueOf, o => o.textContent);
+            if (!option) return done({{ status: 'not_found', options: options.map(o => o.textContent.trim()) }});
+            if (option.getAttribute('aria-disabled') === 'true') {{
+                return done({{ status: 'disabled', label: option.textContent.trim() }});
+            }}
+
+            option.scrollIntoView({{ block: 'nearest' }});
+            option.click();
+            return done({{ status: 'selected', value: valueOf(option), label: option.textContent.trim() }});
+        }}"#,
+        value = serde_json::to_string(&value)?,
+        label = serde_json::to_string(&label)?,
+    );
+
+    let result = element.call_js_fn(script, true).await?;
+    let json = result
+        .result
+        .value
+        .and_then(|value| value.as_str().map(str::to_string))
+        .ok_or_else(|| anyhow::anyhow!("option selection returned no result"))?;
+    Ok(serde_json::from_str(&json)?)
+}
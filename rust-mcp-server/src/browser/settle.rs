@@ -0,0 +1,139 @@
+use anyhow::Result;
+use chromiumoxide::page::Page;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::hooks::origin_matches;
+
+use super::http_auth::origin_of;
+
+/// Longest wait for a policy's readiness selectors before the page is read anyway
+const READY_TIMEOUT_MS: u64 = 5000;
+
+const READY_POLL_MS: u64 = 100;
+
+/// When a site's pages count as settled after a change, for apps that never stop mutating the DOM
+///
+/// Carousels, tickers and clocks keep the DOM busy, so waiting for mutations
+/// to stop never ends. A policy names the subtrees to ignore, or replaces the
+/// quiet period with a fixed settle time, and can add selectors that have to
+/// (or must not) match before the page is read.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SettlePolicy {
+    /// Origins the policy applies to ("*." host wildcards allowed)
+    pub origins: Vec<String>,
+
+    /// Subtrees whose mutations are not changes, by CSS selector (e.g. ".carousel")
+    #[serde(default)]
+    pub ignore: Vec<String>,
+
+    /// Settle this long after the first change instead of waiting for the DOM to go quiet
+    #[serde(default)]
+    pub settle_ms: Option<u64>,
+
+    /// Selectors that must each match a visible element before the page is ready
+    #[serde(default)]
+    pub ready: Vec<String>,
+
+    /// Selectors that must match no visible element (spinners, skeleton screens)
+    #[serde(default)]
+    pub busy: Vec<String>,
+}
+
+impl SettlePolicy {
+    /// The first of `policies` covering the origin of `url`
+    pub fn for_url<'a>(policies: &'a [SettlePolicy], url: &str) -> Option<&'a SettlePolicy> {
+        let origin = origin_of(url)?;
+        policies
+            .iter()
+            .find(|policy| policy.origins.iter().any(|pattern| origin_matches(pattern, &origin)))
+    }
+
+    /// Wait until the readiness selectors hold, for at most READY_TIMEOUT_MS
+    ///
+    /// Returns whether they did; a page that never gets ready is read as it is.
+    pub async fn wait_until_ready(&self, page: &Page) -> Result<bool> {
+        if self.ready.is_empty() && self.busy.is_empty() {
+            return Ok(true);
+        }
+
+        let script = format!(
+            r#"(() => {{
+                const visible = el => {{
+                    const style = el.ownerDocument.defaultView.getComputedStyle(el);
+                    return style.display !== 'none' && style.visibility !== 'hidden' && el.getClientRects().length > 0;
+                }};
+                const matches = selector => {{
+                    try {{ return Array.from(document.querySelectorAll(selector)).some(visible); }} catch (e) {{ return false; }}
+                }};
+                return {ready}.every(matches) && !{busy}.some(matches);
+            }})()"#,
+            ready = serde_json::to_string(&self.ready)?,
+            busy = serde_json::to_string(&self.busy)?,
+        );
+
+        let deadline = Instant::now() + Duration::from_millis(READY_TIMEOUT_MS);
+        loop {
+            let ready: bool = page.evaluate(script.as_str()).await?.into_value().unwrap_or(false);
+            if ready || Instant::now() >= deadline {
+                return Ok(ready);
+            }
+            tokio::time::sleep(Duration::from_millis(READY_POLL_MS)).await;
+        }
+    }
+}
+
+/// Page-side filter for mutation records: `settleIgnored(node)` is true inside an ignored subtree
+///
+/// The policy is chosen in the page by each document's own origin, with the
+/// same rules as `origin_matches`, so it already holds for the first mutations.
+pub(crate) fn ignore_script(policies: &[SettlePolicy]) -> String {
+    let rules: Vec<serde_json::Value> = policies
+        .iter()
+        .map(|policy| serde_json::json!({ "origins": policy.origins, "ignore": policy.ignore }))
+        .collect();
+
+    format!(
+        r#"
+            const settleRules = {rules};
+            const originMatches = (pattern, origin) => {{
+                const [patternScheme, patternHost] = pattern.toLowerCase().replace(/\/+$/, '').split('://');
+                const [scheme, host] = origin.toLowerCase().split('://');
+                if (!patternHost || patternScheme !== scheme) return false;
+                return patternHost.startsWith('*.') ? host.endsWith(patternHost.slice(1)) : host === patternHost;
+            }};
+            const settleRule = settleRules.find(rule => rule.origins.some(pattern => originMatches(pattern, location.origin)));
+            const settleIgnore = (settleRule ? settleRule.ignore : []).filter(selector => {{
+                try {{ document.createDocumentFragment().querySelector(selector); return true; }} catch (e) {{ return false; }}
+            }}).join(', ');
+            const settleIgnored = node => {{
+                const el = node && node.nodeType === 1 ? node : node && node.parentElement;
+                return !!(settleIgnore && el && el.closest(settleIgnore));
+            }};
+"#,
+        rules = serde_json::Value::Array(rules)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_url() {
+        let policies: Vec<SettlePolicy> = serde_json::from_str(
+            r#"[
+                {"origins": ["https://news.example.com"], "ignore": [".ticker"], "settle_ms": 800},
+                {"origins": ["https://*.example.com"], "busy": [".spinner"]}
+            ]"#,
+        )
+        .unwrap();
+
+        let news = SettlePolicy::for_url(&policies, "https://news.example.com/today").unwrap();
+        assert_eq!(news.ignore, vec![".ticker"]);
+        assert_eq!(news.settle_ms, Some(800));
+        assert_eq!(SettlePolicy::for_url(&policies, "https://shop.example.com/").unwrap().busy, vec![".spinner"]);
+        assert!(SettlePolicy::for_url(&policies, "https://example.org/").is_none());
+        assert!(SettlePolicy::for_url(&policies, "about:blank").is_none());
+    }
+}
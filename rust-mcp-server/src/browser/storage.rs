@@ -0,0 +1,275 @@
+use anyhow::Result;
+use chromiumoxide::browser::Browser;
+use chromiumoxide::cdp::browser_protocol::network::{Cookie, CookieParam, CookieSameSite, SetCookiesParams, TimeSinceEpoch};
+use chromiumoxide::cdp::browser_protocol::page::RemoveScriptToEvaluateOnNewDocumentParams;
+use chromiumoxide::cdp::browser_protocol::storage::GetCookiesParams as GetAllCookiesParams;
+use chromiumoxide::page::Page;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::{
+    PlaywrightCookie, PlaywrightOrigin, PlaywrightStorageItem, PlaywrightStorageState, StorageState, StoredCookie,
+    WebStorageArea, WebStorageContents,
+};
+
+/// Reads the origin and its `localStorage`/`sessionStorage` as objects; opaque origins yield empty maps
+const READ_STORAGE_JS: &str = r#"
+    (() => {
+        function dump(storage) {
+            const out = {};
+            try {
+                for (let i = 0; i < storage.length; i++) {
+                    const key = storage.key(i);
+                    out[key] = storage.getItem(key);
+                }
+            } catch (e) {}
+            return out;
+        }
+        let local = {}, session = {};
+        try { local = dump(window.localStorage); } catch (e) {}
+        try { session = dump(window.sessionStorage); } catch (e) {}
+        return { origin: location.origin, local_storage: local, session_storage: session };
+    })()
+"#;
+
+/// Captures and re-applies cookies and web storage
+pub struct StorageStateManager;
+
+impl StorageStateManager {
+    /// Snapshot cookies and web storage of the current page
+    pub async fn capture(page: &Page) -> Result<StorageState> {
+        let url = page.url().await?.unwrap_or_default();
+
+        let cookies = page
+            .get_cookies()
+            .await?
+            .into_iter()
+            .map(|cookie| StoredCookie {
+                name: cookie.name,
+                value: cookie.value,
+                domain: cookie.domain,
+                path: cookie.path,
+                expires: (!cookie.session).then_some(cookie.expires),
+                http_only: cookie.http_only,
+                secure: cookie.secure,
+                same_site: cookie.same_site.map(|s| s.as_ref().to_string()),
+            })
+            .collect();
+
+        #[derive(serde::Deserialize)]
+        struct WebStorage {
+            local_storage: HashMap<String, String>,
+            session_storage: HashMap<String, String>,
+        }
+
+        let storage: WebStorage = page.evaluate(READ_STORAGE_JS).await?.into_value()?;
+
+        Ok(StorageState {
+            url,
+            cookies,
+            local_storage: storage.local_storage,
+            session_storage: storage.session_storage,
+        })
+    }
+
+    /// Every cookie of the browser and the current origin's localStorage, in Playwright's format
+    pub async fn export_playwright(browser: &Browser, page: &Page) -> Result<PlaywrightStorageState> {
+        let cookies = browser
+            .execute(GetAllCookiesParams::default())
+            .await?
+            .result
+            .cookies
+            .into_iter()
+            .map(playwright_cookie)
+            .collect();
+
+        // Other origins' storage is not reachable from this page
+        let storage = Self::web_storage(page).await?;
+        let origins = match storage.origin.as_str() {
+            "null" => Vec::new(),
+            _ if storage.local_storage.is_empty() => Vec::new(),
+            _ => vec![PlaywrightOrigin {
+                origin: storage.origin,
+                local_storage: storage
+                    .local_storage
+                    .into_iter()
+                    .map(|(name, value)| PlaywrightStorageItem { name, value })
+                    .collect(),
+            }],
+        };
+
+        Ok(PlaywrightStorageState { cookies, origins })
+    }
+
+    /// Set the cookies of a Playwright state, then open `url` with its localStorage in place
+    ///
+    /// The localStorage is written before the page's own scripts run, on
+    /// every origin the load passes through, so apps that read their token at
+    /// startup find it.
+    pub async fn import_playwright(page: &Page, state: &PlaywrightStorageState, url: &str) -> Result<()> {
+        let cookies: Vec<CookieParam> = state
+            .cookies
+            .iter()
+            .map(|cookie| CookieParam {
+                domain: Some(cookie.domain.clone()),
+                path: Some(cookie.path.clone()),
+                secure: Some(cookie.secure),
+                http_only: Some(cookie.http_only),
+                same_site: cookie.same_site.parse::<CookieSameSite>().ok(),
+                expires: (cookie.expires >= 0.0).then(|| TimeSinceEpoch::new(cookie.expires)),
+                ..CookieParam::new(cookie.name.clone(), cookie.value.clone())
+            })
+            .collect();
+        // Sent as they are: `Page::set_cookies` would tie every cookie to the current page's URL
+        if !cookies.is_empty() {
+            page.execute(SetCookiesParams::new(cookies)).await?;
+        }
+
+        let origins: HashMap<&str, HashMap<&str, &str>> = state
+            .origins
+            .iter()
+            .map(|origin| {
+                let items = origin
+                    .local_storage
+                    .iter()
+                    .map(|item| (item.name.as_str(), item.value.as_str()))
+                    .collect();
+                (origin.origin.as_str(), items)
+            })
+            .collect();
+        let seed = match origins.is_empty() {
+            true => None,
+            false => {
+                let script = format!(
+                    r#"
+                    (() => {{
+                        const items = {}[location.origin];
+                        if (!items) return;
+                        try {{ for (const [k, v] of Object.entries(items)) localStorage.setItem(k, v); }} catch (e) {{}}
+                    }})()
+                    "#,
+                    serde_json::to_string(&origins)?
+                );
+                Some(page.evaluate_on_new_document(script).await?)
+            }
+        };
+
+        let opened = async {
+            page.goto(url).await?;
+            page.wait_for_navigation().await?;
+            anyhow::Ok(())
+        }
+        .await;
+
+        // Seeding later loads would undo what the app removes, e.g. on logout
+        if let Some(identifier) = seed {
+            page.execute(RemoveScriptToEvaluateOnNewDocumentParams::new(identifier)).await?;
+        }
+        opened
+    }
+
+    /// Web storage of the current page's origin
+    pub async fn web_storage(page: &Page) -> Result<WebStorageContents> {
+        Ok(page.evaluate(READ_STORAGE_JS).await?.into_value()?)
+    }
+
+    /// Set keys of one storage area of the current origin, removing those mapped to `None`
+    ///
+    /// With `clear`, the area is emptied first. The inner error is the page's
+    /// exception, e.g. a SecurityError on about:blank or a QuotaExceededError.
+    pub async fn write_web_storage(
+        page: &Page,
+        area: WebStorageArea,
+        items: &BTreeMap<String, Option<String>>,
+        clear: bool,
+    ) -> Result<std::result::Result<(), String>> {
+        let script = format!(
+            r#"
+            (() => {{
+                try {{
+                    const storage = window.{};
+                    if ({}) storage.clear();
+                    for (const [key, value] of Object.entries({})) {{
+                        if (value === null) storage.removeItem(key);
+                        else storage.setItem(key, value);
+                    }}
+                    return {{ error: null }};
+                }} catch (e) {{
+                    return {{ error: String(e) }};
+                }}
+            }})()
+            "#,
+            area.global(),
+            clear,
+            serde_json::to_string(items)?
+        );
+
+        #[derive(serde::Deserialize)]
+        struct Outcome {
+            error: Option<String>,
+        }
+
+        let outcome: Outcome = page.evaluate(script).await?.into_value()?;
+        Ok(outcome.error.map_or(Ok(()), Err))
+    }
+
+    /// Apply cookies and web storage, then reload so the page picks them up
+    ///
+    /// The page should already be on the origin the state was captured from.
+    pub async fn apply(page: &Page, state: &StorageState) -> Result<()> {
+        let cookies: Vec<CookieParam> = state
+            .cookies
+            .iter()
+            .map(|cookie| CookieParam {
+                domain: Some(cookie.domain.clone()),
+                path: Some(cookie.path.clone()),
+                secure: Some(cookie.secure),
+                http_only: Some(cookie.http_only),
+                same_site: cookie
+                    .same_site
+                    .as_deref()
+                    .and_then(|s| s.parse::<CookieSameSite>().ok()),
+                expires: cookie.expires.map(TimeSinceEpoch::new),
+                ..CookieParam::new(cookie.name.clone(), cookie.value.clone())
+            })
+            .collect();
+
+        if !cookies.is_empty() {
+            page.set_cookies(cookies).await?;
+        }
+
+        let script = format!(
+            r#"
+            (() => {{
+                const local = {};
+                const session = {};
+                try {{ for (const [k, v] of Object.entries(local)) localStorage.setItem(k, v); }} catch (e) {{}}
+                try {{ for (const [k, v] of Object.entries(session)) sessionStorage.setItem(k, v); }} catch (e) {{}}
+                return true;
+            }})()
+            "#,
+            serde_json::to_string(&state.local_storage)?,
+            serde_json::to_string(&state.session_storage)?
+        );
+        page.evaluate(script).await?;
+
+        page.reload().await?;
+        page.wait_for_navigation().await?;
+
+        Ok(())
+    }
+}
+
+fn playwright_cookie(cookie: Cookie) -> PlaywrightCookie {
+    PlaywrightCookie {
+        expires: if cookie.session { -1.0 } else { cookie.expires },
+        same_site: cookie
+            .same_site
+            .map_or_else(|| "Lax".to_string(), |same_site| same_site.as_ref().to_string()),
+        name: cookie.name,
+        value: cookie.value,
+        domain: cookie.domain,
+        path: cookie.path,
+        http_only: cookie.http_only,
+        secure: cookie.secure,
+    }
+}
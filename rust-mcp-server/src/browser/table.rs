@@ -0,0 +1,293 @@
+use anyhow::Result;
+use chromiumoxide::page::Page;
+use serde::Deserialize;
+
+use crate::models::{DateOrder, ExtractedTable, SemanticSelector};
+
+use super::{ContextExtractor, COLLECT_ELEMENTS_JS};
+
+/// How long to wait for a table to change after clicking "next"
+const PAGE_CHANGE_TIMEOUT_MS: u64 = 5000;
+
+/// Which table to read
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TableTarget {
+    /// CSS selector of the table (or an element containing it)
+    #[serde(default)]
+    pub selector: Option<String>,
+
+    /// Text of the table's caption/label or of a heading before it
+    #[serde(default)]
+    pub heading: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TablePage {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl TablePage {
+    /// Cheap fingerprint to notice that pagination replaced the rows
+    fn signature(&self) -> String {
+        format!(
+            "{}|{:?}|{:?}",
+            self.rows.len(),
+            self.rows.first(),
+            self.rows.last()
+        )
+    }
+}
+
+/// Reads HTML tables and ARIA tables/grids into normalized rows, optionally following pagination
+pub struct TableExtractor;
+
+impl TableExtractor {
+    /// Extract the target table; without a selector or heading the largest table is used
+    ///
+    /// Up to `max_pages` pages are read by clicking `next` (or a detected "Next"
+    /// control). Returns `None` if no table matches.
+    pub async fn extract(
+        page: &Page,
+        target: &TableTarget,
+        max_pages: u32,
+        next: Option<&SemanticSelector>,
+    ) -> Result<Option<ExtractedTable>> {
+        let Some(first) = Self::read_page(page, target).await? else {
+            return Ok(None);
+        };
+
+        let mut table = ExtractedTable {
+            headers: first.headers.clone(),
+            rows: first.rows.clone(),
+            pages: 1,
+            parsed: Vec::new(),
+        };
+        let mut signature = first.signature();
+
+        while table.pages < max_pages.max(1) {
+            if !Self::click_next(page, next).await? {
+                break;
+            }
+
+            let Some(next_page) = Self::wait_for_new_page(page, target, &signature).await? else {
+                break;
+            };
+
+            signature = next_page.signature();
+            let width = table.headers.len();
+            table
+                .rows
+                .extend(next_page.rows.into_iter().filter(|row| row.len() == width));
+            table.pages += 1;
+        }
+
+        table.parse_dates(DateOrder::from_lang(&ContextExtractor::page_lang(page).await?));
+        Ok(Some(table))
+    }
+
+    /// Poll until the table shows different rows than `previous`
+    async fn wait_for_new_page(
+        page: &Page,
+        target: &TableTarget,
+        previous: &str,
+    ) -> Result<Option<TablePage>> {
+        let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_millis(PAGE_CHANGE_TIMEOUT_MS);
+
+        while tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
+
+            if let Some(current) = Self::read_page(page, target).await? {
+                if current.signature() != previous {
+                    return Ok(Some(current));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn read_page(page: &Page, target: &TableTarget) -> Result<Option<TablePage>> {
+        let script = Self::build_read_script(target);
+        let result = page.evaluate(script).await?;
+        // A missing table comes back as null, which CDP reports without a value
+        let value = result.value().cloned().unwrap_or_default();
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Click the next-page control; false when there is none (or it is disabled)
+    async fn click_next(page: &Page, next: Option<&SemanticSelector>) -> Result<bool> {
+        let spec = next.map(|selector| {
+            serde_json::json!({
+                "role": selector.role,
+                "name": selector.name,
+                "css": selector.css_fallback,
+            })
+        });
+
+        let script = format!(
+            r#"
+            (() => {{
+                {}
+
+                const spec = {};
+                const disabled = el => el.disabled ||
+                    el.getAttribute('aria-disabled') === 'true' ||
+                    /\bdisabled\b/.test(el.className || '') ||
+                    /\bdisabled\b/.test((el.parentElement && el.parentElement.className) || '');
+
+                let next = null;
+                if (spec) {{
+                    const role = (spec.role || '').toLowerCase();
+                    const match = collectElements().find(c =>
+                        c.role.toLowerCase() === role && (!spec.name || (c.name || '').includes(spec.name)));
+                    next = match ? match.el : (spec.css ? document.querySelector(spec.css) : null);
+                }} else {{
+                    const labels = ['next', 'next page', '›', '»', '>', '→'];
+                    next = document.querySelector('a[rel="next"]');
+                    if (!next) {{
+                        next = Array.from(document.querySelectorAll('a, button, [role="button"], [role="link"]'))
+                            .find(el => {{
+                                const label = (el.getAttribute('aria-label') || el.title || el.textContent || '')
+                                    .replace(/\s+/g, ' ').trim().toLowerCase();
+                                return isVisible(el) && (labels.includes(label) || label.startsWith('next '));
+                            }}) || null;
+                    }}
+                }}
+
+                if (!next || disabled(next)) return false;
+                next.scrollIntoView({{ block: 'center' }});
+                next.click();
+                return true;
+            }})()
+            "#,
+            COLLECT_ELEMENTS_JS,
+            spec.unwrap_or(serde_json::Value::Null)
+        );
+
+        let result = page.evaluate(script).await?;
+        Ok(result.into_value::<bool>().unwrap_or(false))
+    }
+
+    fn build_read_script(target: &TableTarget) -> String {
+        let args = serde_json::json!({
+            "css": target.selector,
+            "heading": target.heading,
+        });
+
+        format!(
+            r#"
+            (() => {{
+                const args = {};
+                const text = el => (el.innerText || el.textContent || '').replace(/\s+/g, ' ').trim();
+                const tableSelector = 'table:not([role="presentation"]):not([role="none"]), [role="table"], [role="grid"], [role="treegrid"]';
+                const cellSelector = '[role="cell"], [role="gridcell"], [role="columnheader"], [role="rowheader"]';
+                const isNative = table => table.tagName === 'TABLE';
+
+                // Rows and cells of an ARIA table, without those of tables nested in it
+                const ownedBy = (el, owner, selector) => el.parentElement && el.parentElement.closest(selector) === owner;
+                const ariaRows = table => Array.from(table.querySelectorAll('[role="row"]'))
+                    .filter(row => ownedBy(row, table, tableSelector));
+                const ariaCells = row => Array.from(row.querySelectorAll(cellSelector))
+                    .filter(cell => ownedBy(cell, row, '[role="row"]'));
+                const rowsOf = table => isNative(table) ? Array.from(table.rows) : ariaRows(table);
+                const cellsOf = row => row.cells ? Array.from(row.cells) : ariaCells(row);
+
+                function findTable() {{
+                    const tables = Array.from(document.querySelectorAll(tableSelector));
+
+                    if (args.css) {{
+                        const el = document.querySelector(args.css);
+                        if (!el) return null;
+                        return el.matches(tableSelector) ? el : el.querySelector(tableSelector);
+                    }}
+
+                    if (args.heading) {{
+                        const needle = args.heading.toLowerCase();
+
+                        // Caption or accessible label of the table itself
+                        for (const table of tables) {{
+                            const labelledBy = table.getAttribute('aria-labelledby');
+                            const label = [
+                                table.caption ? text(table.caption) : '',
+                                table.getAttribute('aria-label') || '',
+                                labelledBy && document.getElementById(labelledBy)
+                                    ? text(document.getElementById(labelledBy)) : '',
+                            ].join(' ').toLowerCase();
+                            if (label.includes(needle)) return table;
+                        }}
+
+                        // First table after a matching heading
+                        const headings = document.querySelectorAll('h1, h2, h3, h4, h5, h6, [role="heading"], legend');
+                        for (const heading of headings) {{
+                            if (!text(heading).toLowerCase().includes(needle)) continue;
+                            const table = tables.find(t =>
+                                heading.compareDocumentPosition(t) & Node.DOCUMENT_POSITION_FOLLOWING);
+                            if (table) return table;
+                        }}
+                        return null;
+                    }}
+
+                    return tables.sort((a, b) => rowsOf(b).length - rowsOf(a).length)[0] || null;
+                }}
+
+                const table = findTable();
+                if (!table) return null;
+
+                // Expand colspan/rowspan (or aria-colspan/aria-rowspan) into a rectangular grid
+                const span = (cell, native, aria) => Math.max(1, cell[native] || parseInt(cell.getAttribute(aria), 10) || 1);
+                const isHeader = cell => cell.tagName === 'TH' || cell.getAttribute('role') === 'columnheader';
+                const tableRows = rowsOf(table);
+                const rowCount = tableRows.length;
+                const grid = Array.from({{ length: rowCount }}, () => []);
+                const headerRow = [];
+                tableRows.forEach((tr, r) => {{
+                    const cells = cellsOf(tr);
+                    let c = 0;
+                    for (const cell of cells) {{
+                        // Virtualized grids place their rendered cells by aria-colindex
+                        const index = parseInt(cell.getAttribute('aria-colindex'), 10);
+                        if (index > 0) c = index - 1;
+                        while (grid[r][c] !== undefined) c++;
+                        const colspan = span(cell, 'colSpan', 'aria-colspan');
+                        const rowspan = span(cell, 'rowSpan', 'aria-rowspan');
+                        const value = text(cell);
+                        for (let dr = 0; dr < rowspan && r + dr < rowCount; dr++) {{
+                            for (let dc = 0; dc < colspan; dc++) grid[r + dr][c + dc] = value;
+                        }}
+                        c += colspan;
+                    }}
+                    headerRow[r] = (tr.parentElement && tr.parentElement.tagName === 'THEAD') ||
+                        (cells.length > 0 && cells.every(isHeader));
+                }});
+
+                const width = Math.max(0, ...grid.map(row => row.length));
+                const rows = grid.map(row => Array.from({{ length: width }}, (_, i) => row[i] || ''));
+
+                // Leading header rows are merged per column, e.g. "Q1 / Revenue"
+                let headerCount = 0;
+                while (headerCount < rows.length && headerRow[headerCount]) headerCount++;
+
+                const seen = {{}};
+                const headers = Array.from({{ length: width }}, (_, i) => {{
+                    const parts = [];
+                    for (let r = 0; r < headerCount; r++) {{
+                        const part = rows[r][i];
+                        if (part && parts[parts.length - 1] !== part) parts.push(part);
+                    }}
+                    let name = parts.join(' / ') || `column_${{i + 1}}`;
+                    if (seen[name]) name = `${{name}}_${{++seen[name]}}`;
+                    else seen[name] = 1;
+                    return name;
+                }});
+
+                return {{
+                    headers,
+                    rows: rows.slice(headerCount).filter(row => row.some(cell => cell !== '')),
+                }};
+            }})()
+            "#,
+            args
+        )
+    }
+}
@@ -0,0 +1,406 @@
+use anyhow::Result;
+use chromiumoxide::browser::Browser;
+use chromiumoxide::cdp::browser_protocol::emulation::SetDeviceMetricsOverrideParams;
+use chromiumoxide::cdp::browser_protocol::target::{EventAttachedToTarget, EventTargetDestroyed, TargetId, TargetInfo};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+
+use crate::models::{ClosedOverlay, SessionEventKind, TabInfo};
+
+use super::{
+    forget_overlays_on_navigation, forward_console, track_navigation, AutomationMarker, ConsoleLog, DialogHandler, EventLog, Fingerprint,
+    HttpAuthHandler, NavigationHistory,
+};
+
+/// What the first tab was set up with, applied to every tab opened after it
+#[derive(Clone)]
+pub struct TabSetup {
+    pub device_metrics: SetDeviceMetricsOverrideParams,
+    pub fingerprint: Option<Fingerprint>,
+    pub marker: AutomationMarker,
+    pub events: Arc<EventLog>,
+    pub console: Arc<ConsoleLog>,
+    pub history: Arc<RwLock<NavigationHistory>>,
+    pub closed_overlays: Arc<RwLock<Vec<ClosedOverlay>>>,
+    pub dialogs: Option<DialogHandler>,
+    pub http_auth: Option<HttpAuthHandler>,
+}
+
+impl TabSetup {
+    /// Failures are logged; the tab stays usable without the part that failed
+    async fn apply(&self, page: &Page) {
+        if let Some(fingerprint) = &self.fingerprint {
+            if let Err(e) = fingerprint.apply(page).await {
+                tracing::warn!("Failed to apply the browser fingerprint to a new tab: {}", e);
+            }
+        }
+        if let Err(e) = page.execute(self.device_metrics.clone()).await {
+            tracing::warn!("Failed to set the viewport of a new tab: {}", e);
+        }
+        if let Some(http_auth) = &self.http_auth {
+            if let Err(e) = http_auth.attach(page).await {
+                tracing::warn!("Failed to route the requests of a new tab: {}", e);
+            }
+        }
+        if let Some(dialogs) = &self.dialogs {
+            if let Err(e) = dialogs.attach(page).await {
+                tracing::warn!("Failed to set up dialog handling for a new tab: {}", e);
+            }
+        }
+        if let Err(e) = self.marker.install(page).await {
+            tracing::warn!("Failed to mark a new tab as automated: {}", e);
+        }
+        if let Err(e) = track_navigation(page.clone(), Arc::clone(&self.history), Arc::clone(&self.events)).await {
+            tracing::warn!("Failed to subscribe to the navigation events of a new tab: {}", e);
+        }
+        if let Err(e) = forget_overlays_on_navigation(page, Arc::clone(&self.closed_overlays)).await {
+            tracing::warn!("Failed to subscribe to the navigation events of a new tab for closed overlays: {}", e);
+        }
+        if let Err(e) = forward_console(page, Arc::clone(&self.events), Arc::clone(&self.console)).await {
+            tracing::warn!("Failed to subscribe to the console of a new tab: {}", e);
+        }
+    }
+}
+
+/// The session's tabs, and the one actions go to
+///
+/// Tabs the page opens (`target="_blank"` links, `window.open`) are picked up
+/// as the browser attaches to them and set up like the first tab. Popups
+/// become the active tab right away, and closing them returns to their
+/// opener, so flows like OAuth sign-in need no switching. Switching
+/// swaps the tab's page into the session's shared page handle, so actions and
+/// context reads follow the active tab. Element subscriptions, context pushes
+/// and downloads stay with the first tab.
+pub struct TabTracker {
+    inner: Arc<Tabs>,
+}
+
+struct Tabs {
+    browser: Arc<Browser>,
+    /// The session's page handle, holding the active tab's page
+    active_page: Arc<RwLock<Page>>,
+    state: Mutex<TabState>,
+    setup: TabSetup,
+}
+
+struct Tab {
+    id: u32,
+    opener: Option<u32>,
+    popup: bool,
+    page: Page,
+}
+
+#[derive(Default)]
+struct TabState {
+    tabs: Vec<Tab>,
+    active: u32,
+    /// Highest ID given out; IDs are not reused
+    last_id: u32,
+}
+
+impl TabState {
+    /// Track `page` unless it already is; its new ID
+    fn add(&mut self, page: Page, popup: bool) -> Option<u32> {
+        if self.tabs.iter().any(|tab| tab.page.target_id() == page.target_id()) {
+            return None;
+        }
+
+        let opener = page.opener_id().as_ref().and_then(|opener| self.id_of(opener));
+        self.last_id += 1;
+        self.tabs.push(Tab {
+            id: self.last_id,
+            opener,
+            popup,
+            page,
+        });
+        Some(self.last_id)
+    }
+
+    fn id_of(&self, target: &TargetId) -> Option<u32> {
+        self.tabs.iter().find(|tab| tab.page.target_id() == target).map(|tab| tab.id)
+    }
+
+    fn get(&self, id: u32) -> Option<&Tab> {
+        self.tabs.iter().find(|tab| tab.id == id)
+    }
+
+    fn page(&self, id: u32) -> Option<Page> {
+        self.get(id).map(|tab| tab.page.clone())
+    }
+
+    /// Stop tracking a tab; the page to switch to if it was the active one
+    fn remove(&mut self, id: u32) -> Option<Option<Page>> {
+        let index = self.tabs.iter().position(|tab| tab.id == id)?;
+        let closed = self.tabs.remove(index);
+        if closed.id != self.active {
+            return Some(None);
+        }
+
+        let remaining: Vec<u32> = self.tabs.iter().map(|tab| tab.id).collect();
+        let next = next_active(closed.opener, &remaining)?;
+        self.active = next;
+        Some(self.page(next))
+    }
+}
+
+/// Whether a new target is a popup: a window still able to reach the page that opened it
+///
+/// `window.open` keeps `window.opener`, which sign-in and payment popups use to
+/// report back. Links with `target="_blank"` are `noopener` by default, so
+/// they open as ordinary tabs.
+fn is_popup(info: &TargetInfo) -> bool {
+    info.opener_id.is_some() && info.can_access_opener
+}
+
+/// Tab to continue in after the active one closed: the tab that opened it, or else the newest
+fn next_active(opener: Option<u32>, remaining: &[u32]) -> Option<u32> {
+    opener
+        .filter(|opener| remaining.contains(opener))
+        .or_else(|| remaining.last().copied())
+}
+
+impl TabTracker {
+    /// Track the session's first tab, whose page `active_page` holds, and every tab opened later
+    pub async fn start(browser: Arc<Browser>, active_page: Arc<RwLock<Page>>, setup: TabSetup) -> Result<Self> {
+        let mut attached = browser.event_listener::<EventAttachedToTarget>().await?;
+        let mut destroyed = browser.event_listener::<EventTargetDestroyed>().await?;
+
+        let mut state = TabState::default();
+        let first = active_page.read().await.clone();
+        state.active = state.add(first, false).unwrap_or(1);
+
+        let inner = Arc::new(Tabs {
+            browser,
+            active_page,
+            state: Mutex::new(state),
+            setup,
+        });
+
+        let tabs = Arc::clone(&inner);
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(event) = attached.next() => {
+                        if event.target_info.r#type != "page" {
+                            continue;
+                        }
+                        match tabs.browser.get_page(event.target_info.target_id.clone()).await {
+                            Ok(page) => {
+                                tabs.add(page, is_popup(&event.target_info)).await;
+                            }
+                            Err(e) => tracing::warn!("Failed to pick up a new tab: {}", e),
+                        }
+                    }
+                    Some(event) = destroyed.next() => {
+                        let id = tabs.state.lock().unwrap().id_of(&event.target_id);
+                        if let Some(id) = id {
+                            tabs.remove(id).await;
+                        }
+                    }
+                    else => break,
+                }
+            }
+        });
+
+        Ok(Self { inner })
+    }
+
+    /// Every open tab, in the order they opened
+    pub async fn list(&self) -> Vec<TabInfo> {
+        let (tabs, active): (Vec<TabEntry>, u32) = {
+            let state = self.inner.state.lock().unwrap();
+            (state.tabs.iter().map(TabEntry::from).collect(), state.active)
+        };
+
+        let mut infos = Vec::with_capacity(tabs.len());
+        for tab in tabs {
+            let is_active = tab.id == active;
+            infos.push(tab.info(is_active).await);
+        }
+        infos
+    }
+
+    pub fn active(&self) -> u32 {
+        self.inner.state.lock().unwrap().active
+    }
+
+    /// ID the next tab will get
+    pub fn mark(&self) -> u32 {
+        self.inner.state.lock().unwrap().last_id + 1
+    }
+
+    /// Tabs opened from `mark` on that are still open
+    pub async fn since(&self, mark: u32) -> Vec<TabInfo> {
+        let mut tabs = self.list().await;
+        tabs.retain(|tab| tab.tab_id >= mark);
+        tabs
+    }
+
+    /// Open a tab, make it active and load `url` in it
+    pub async fn open(&self, url: Option<&str>) -> Result<TabInfo> {
+        // Blank first, so the tab is set up before the page's first request
+        let page = self.inner.browser.new_page("about:blank").await?;
+        let id = match self.inner.add(page.clone(), false).await {
+            Some(id) => id,
+            // The attach listener got to it first
+            None => self.inner.state.lock().unwrap().id_of(page.target_id()).unwrap_or_default(),
+        };
+        self.switch(id).await;
+
+        if let Some(url) = url {
+            page.goto(url).await?;
+        }
+        Ok(TabEntry { id, opener: None, popup: false, page }.info(true).await)
+    }
+
+    /// Make a tab active; `None` if no open tab has that ID
+    pub async fn switch(&self, id: u32) -> Option<TabInfo> {
+        let tab = self.inner.activate(id).await?;
+        Some(tab.info(true).await)
+    }
+
+    /// Close a tab; the tab now active, or `None` if no open tab has that ID
+    pub async fn close(&self, id: u32) -> Result<Option<u32>> {
+        let Some(page) = self.inner.state.lock().unwrap().page(id) else {
+            return Ok(None);
+        };
+        self.inner.remove(id).await;
+        page.close().await?;
+        Ok(Some(self.active()))
+    }
+
+    /// IDs of the open tabs
+    pub fn ids(&self) -> Vec<u32> {
+        self.inner.state.lock().unwrap().tabs.iter().map(|tab| tab.id).collect()
+    }
+
+    pub fn count(&self) -> usize {
+        self.inner.state.lock().unwrap().tabs.len()
+    }
+}
+
+impl Tabs {
+    /// Set up and announce a tab the tracker doesn't know yet; its ID
+    async fn add(&self, page: Page, popup: bool) -> Option<u32> {
+        let id = self.state.lock().unwrap().add(page.clone(), popup)?;
+        self.setup.apply(&page).await;
+
+        let entry = self.state.lock().unwrap().get(id).map(TabEntry::from)?;
+        // Only follow popups of tabs the session knows, so there is an opener to return to
+        let follow = entry.popup && entry.opener.is_some();
+        if follow {
+            self.activate(id).await;
+        }
+
+        let tab = entry.info(follow).await;
+        tracing::info!("Tab {} opened: {}{}", id, tab.url, if follow { " (popup, now active)" } else { "" });
+        self.setup.events.push(SessionEventKind::TabOpened { tab }).await;
+        Some(id)
+    }
+
+    /// Make a tab the one actions go to
+    async fn activate(&self, id: u32) -> Option<TabEntry> {
+        let tab = {
+            let mut state = self.state.lock().unwrap();
+            let tab = state.get(id).map(TabEntry::from)?;
+            state.active = id;
+            tab
+        };
+
+        if let Err(e) = tab.page.bring_to_front().await {
+            tracing::debug!("Failed to bring tab {} to the front: {}", id, e);
+        }
+        *self.active_page.write().await = tab.page.clone();
+        Some(tab)
+    }
+
+    /// Forget a closed tab, moving to another one if it was active
+    async fn remove(&self, id: u32) {
+        let Some(next) = self.state.lock().unwrap().remove(id) else {
+            return;
+        };
+        if let Some(page) = &next {
+            if let Err(e) = page.bring_to_front().await {
+                tracing::debug!("Failed to bring the next tab to the front: {}", e);
+            }
+            *self.active_page.write().await = page.clone();
+        }
+
+        let active_tab_id = self.state.lock().unwrap().active;
+        tracing::info!("Tab {} closed; tab {} is active", id, active_tab_id);
+        self.setup
+            .events
+            .push(SessionEventKind::TabClosed { tab_id: id, active_tab_id })
+            .await;
+    }
+}
+
+/// A tab copied out of the state, to read its page without holding the lock
+struct TabEntry {
+    id: u32,
+    opener: Option<u32>,
+    popup: bool,
+    page: Page,
+}
+
+impl From<&Tab> for TabEntry {
+    fn from(tab: &Tab) -> Self {
+        Self {
+            id: tab.id,
+            opener: tab.opener,
+            popup: tab.popup,
+            page: tab.page.clone(),
+        }
+    }
+}
+
+impl TabEntry {
+    async fn info(&self, active: bool) -> TabInfo {
+        TabInfo {
+            tab_id: self.id,
+            url: self.page.url().await.ok().flatten().unwrap_or_default(),
+            title: self.page.get_title().await.ok().flatten().unwrap_or_default(),
+            active,
+            opener_tab_id: self.opener,
+            popup: self.popup,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_active_prefers_the_opener() {
+        assert_eq!(next_active(Some(1), &[1, 3]), Some(1));
+        // The opener closed first: continue in the newest tab
+        assert_eq!(next_active(Some(2), &[1, 3]), Some(3));
+        assert_eq!(next_active(None, &[1, 3]), Some(3));
+        assert_eq!(next_active(Some(1), &[]), None);
+    }
+
+    #[test]
+    fn test_only_windows_that_keep_their_opener_are_popups() {
+        let target = |opener: Option<&str>, can_access_opener: bool| {
+            let mut info = TargetInfo::builder()
+                .target_id("tab-2".to_string())
+                .r#type("page")
+                .title("Sign in")
+                .url("https://accounts.example.com/")
+                .attached(true)
+                .can_access_opener(can_access_opener)
+                .build()
+                .unwrap();
+            info.opener_id = opener.map(|id| TargetId::from(id.to_string()));
+            info
+        };
+
+        assert!(is_popup(&target(Some("tab-1"), true)));
+        // A target="_blank" link: noopener
+        assert!(!is_popup(&target(Some("tab-1"), false)));
+        assert!(!is_popup(&target(None, false)));
+    }
+}
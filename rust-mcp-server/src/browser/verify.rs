@@ -0,0 +1,76 @@
+use anyhow::Result;
+use chromiumoxide::element::Element;
+use serde::Deserialize;
+
+/// What an input holds after typing into it
+#[derive(Debug, Deserialize)]
+pub struct FieldValue {
+    /// `None` for elements without a readable value
+    pub value: Option<String>,
+    /// Password fields: the value must not be echoed back
+    pub secret: bool,
+}
+
+impl FieldValue {
+    /// Read the value of an input, textarea or contenteditable element
+    pub async fn read(element: &Element) -> Result<Self> {
+        let result = element
+            .call_js_fn(
+                r#"function() {
+                    const value = this.isContentEditable ? this.innerText
+                        : (typeof this.value === 'string' ? this.value : null);
+                    return JSON.stringify({ value, secret: this.type === 'password' });
+                }"#,
+                false,
+            )
+            .await?;
+
+        let json = result
+            .result
+            .value
+            .and_then(|value| value.as_str().map(str::to_string))
+            .ok_or_else(|| anyhow::anyhow!("element value is not readable"))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// Whether the field shows what was typed
+///
+/// Input masks may reformat the text ("5551234567" becomes "(555) 123-4567",
+/// "ab12cd" becomes "AB12 CD"), so when the exact text differs, the letters and
+/// digits are compared case-insensitively.
+pub fn value_matches(intended: &str, actual: &str) -> bool {
+    if intended == actual || intended.trim() == actual.trim() {
+        return true;
+    }
+
+    let significant = |s: &str| {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    let intended = significant(intended);
+    !intended.is_empty() && intended == significant(actual)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_matches() {
+        assert!(value_matches("john@example.com", "john@example.com"));
+        assert!(value_matches("5551234567", "(555) 123-4567"));
+        assert!(value_matches("4111111111111111", "4111 1111 1111 1111"));
+        assert!(value_matches("ab12cd", "AB12 CD"));
+        assert!(value_matches("", ""));
+
+        // Swallowed or truncated keystrokes
+        assert!(!value_matches("john@example.com", ""));
+        assert!(!value_matches("john@example.com", "j"));
+        assert!(!value_matches("5551234567", "(555) 123-456"));
+        assert!(!value_matches("hello world", "hello"));
+        assert!(!value_matches("---", ""));
+    }
+}
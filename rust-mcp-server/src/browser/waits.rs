@@ -0,0 +1,186 @@
+use anyhow::Result;
+use chromiumoxide::cdp::browser_protocol::network::{
+    EventLoadingFailed, EventLoadingFinished, EventRequestWillBeSent, RequestId, ResourceType,
+};
+use chromiumoxide::cdp::browser_protocol::page::{
+    EventFrameNavigated, EventLoadEventFired, EventNavigatedWithinDocument,
+};
+use chromiumoxide::page::Page;
+use futures::StreamExt;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use super::filename_matches;
+
+const POLL_MS: u64 = 100;
+
+/// How long no request may be in flight for the network to count as idle
+pub const NETWORK_IDLE_MS: u64 = 500;
+
+/// Wait for the main frame's navigation to finish; the URL it ended on, `None` on timeout
+///
+/// A page still loading (e.g. after a click on a link) counts as the
+/// navigation to wait for. Otherwise the next navigation is awaited: a new
+/// document once it has loaded, or an SPA history change right away.
+pub async fn wait_for_navigation(page: &Page, timeout: Duration) -> Result<Option<String>> {
+    enum Event {
+        Document,
+        WithinDocument(String),
+        Loaded,
+    }
+
+    let main_frame = page.mainframe().await?;
+    let navigated = page.event_listener::<EventFrameNavigated>().await?.filter_map(|event| {
+        let main = event.frame.parent_id.is_none();
+        futures::future::ready(main.then_some(Event::Document))
+    });
+    let within_document = page.event_listener::<EventNavigatedWithinDocument>().await?.filter_map(move |event| {
+        let main = main_frame.as_ref().is_none_or(|frame| *frame == event.frame_id);
+        futures::future::ready(main.then(|| Event::WithinDocument(event.url.clone())))
+    });
+    let loaded = page.event_listener::<EventLoadEventFired>().await?.map(|_| Event::Loaded);
+    let mut events = futures::stream::select(futures::stream::select(navigated, within_document), loaded);
+
+    // Subscribed first, so a load finishing right now is not missed
+    let state: String = page.evaluate("document.readyState").await?.into_value().unwrap_or_default();
+    let mut loading = state != "complete";
+
+    let wait = async {
+        while let Some(event) = events.next().await {
+            match event {
+                Event::Document => loading = true,
+                Event::WithinDocument(url) if !loading => return Some(url),
+                Event::Loaded if loading => return page.url().await.ok().flatten(),
+                _ => {}
+            }
+        }
+        None
+    };
+
+    Ok(tokio::time::timeout(timeout, wait).await.ok().flatten())
+}
+
+/// Requests the page has in flight, as seen from network events
+#[derive(Debug, Default)]
+struct InFlight {
+    requests: HashSet<RequestId>,
+}
+
+impl InFlight {
+    /// Connections that stay open on purpose never finish, so they don't count
+    fn started(&mut self, id: RequestId, kind: Option<&ResourceType>) {
+        if !matches!(kind, Some(ResourceType::WebSocket | ResourceType::EventSource)) {
+            self.requests.insert(id);
+        }
+    }
+
+    fn finished(&mut self, id: &RequestId) {
+        self.requests.remove(id);
+    }
+
+    fn is_idle(&self) -> bool {
+        self.requests.is_empty()
+    }
+}
+
+/// Wait until no request has been in flight for `idle`; whether it happened before `timeout`
+///
+/// Requests already running when the wait starts are not seen, so their end
+/// is not awaited.
+pub async fn wait_for_network_idle(page: &Page, idle: Duration, timeout: Duration) -> Result<bool> {
+    enum Event {
+        Started(RequestId, Option<ResourceType>),
+        Finished(RequestId),
+    }
+
+    let started = page
+        .event_listener::<EventRequestWillBeSent>()
+        .await?
+        .map(|event| Event::Started(event.request_id.clone(), event.r#type.clone()));
+    let finished = page
+        .event_listener::<EventLoadingFinished>()
+        .await?
+        .map(|event| Event::Finished(event.request_id.clone()));
+    let failed = page
+        .event_listener::<EventLoadingFailed>()
+        .await?
+        .map(|event| Event::Finished(event.request_id.clone()));
+    let mut events = futures::stream::select(futures::stream::select(started, finished), failed);
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut in_flight = InFlight::default();
+    loop {
+        let quiet_until = tokio::time::Instant::now() + idle;
+        if in_flight.is_idle() && quiet_until > deadline {
+            return Ok(false);
+        }
+
+        let wake = if in_flight.is_idle() { quiet_until } else { deadline };
+        tokio::select! {
+            event = events.next() => match event {
+                Some(Event::Started(id, kind)) => in_flight.started(id, kind.as_ref()),
+                Some(Event::Finished(id)) => in_flight.finished(&id),
+                None => return Ok(false),
+            },
+            _ = tokio::time::sleep_until(wake) => return Ok(in_flight.is_idle()),
+        }
+    }
+}
+
+/// Wait until the page's URL matches `pattern`; the URL, `None` on timeout
+///
+/// The pattern is a glob over the whole URL (`*` for any characters, `?` for
+/// one, ignoring case), e.g. `*/orders/*`.
+pub async fn wait_for_url(page: &Page, pattern: &str, timeout: Duration) -> Result<Option<String>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let url = page.url().await?.unwrap_or_default();
+        if filename_matches(pattern, &url) {
+            return Ok(Some(url));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        tokio::time::sleep(Duration::from_millis(POLL_MS)).await;
+    }
+}
+
+/// Wait until the page's visible text contains `text`, ignoring case; whether it did before `timeout`
+pub async fn wait_for_text(page: &Page, text: &str, timeout: Duration) -> Result<bool> {
+    let script = format!(
+        "(() => !!document.body && document.body.innerText.toLowerCase().includes({}))()",
+        serde_json::to_string(&text.to_lowercase())?
+    );
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        let found: bool = page.evaluate(script.as_str()).await?.into_value().unwrap_or(false);
+        if found || Instant::now() >= deadline {
+            return Ok(found);
+        }
+        tokio::time::sleep(Duration::from_millis(POLL_MS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_flight_ignores_long_lived_connections() {
+        let mut in_flight = InFlight::default();
+        in_flight.started(RequestId::from("1".to_string()), Some(&ResourceType::Fetch));
+        in_flight.started(RequestId::from("2".to_string()), Some(&ResourceType::EventSource));
+        in_flight.started(RequestId::from("3".to_string()), Some(&ResourceType::WebSocket));
+        assert!(!in_flight.is_idle());
+
+        // A redirect reuses the request's ID
+        in_flight.started(RequestId::from("1".to_string()), Some(&ResourceType::Fetch));
+        in_flight.finished(&RequestId::from("1".to_string()));
+        assert!(in_flight.is_idle());
+
+        // Requests from before the wait finish unseen
+        in_flight.finished(&RequestId::from("0".to_string()));
+        assert!(in_flight.is_idle());
+    }
+}
@@ -0,0 +1,294 @@
+use anyhow::Result;
+use chromiumoxide::cdp::browser_protocol::page::{
+    RemoveScriptToEvaluateOnNewDocumentParams, ScriptIdentifier,
+};
+use chromiumoxide::cdp::js_protocol::runtime::{AddBindingParams, EventBindingCalled};
+use chromiumoxide::page::Page;
+use chrono::Utc;
+use futures::StreamExt;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::models::{ElementSubscription, SemanticSelector, SessionEventKind};
+
+use super::{EventLog, COLLECT_ELEMENTS_JS};
+
+/// CDP binding the page script reports presence changes through
+const BINDING_NAME: &str = "__mcpElementWatch";
+
+/// Milliseconds of DOM quiet before subscriptions are re-checked
+const CHECK_DEBOUNCE_MS: u64 = 200;
+
+/// Payload sent by the page script through the binding
+#[derive(Debug, Deserialize)]
+struct PresenceReport {
+    id: String,
+    present: bool,
+}
+
+/// Watches the page for elements matching subscribed selectors
+///
+/// A MutationObserver in the page re-evaluates every subscription after DOM
+/// changes and reports transitions through a CDP binding. The script is
+/// installed on every new document, so subscriptions survive navigations.
+pub struct ElementWatcher {
+    page: Page,
+    subscriptions: Arc<RwLock<Vec<ElementSubscription>>>,
+    script: RwLock<Option<ScriptIdentifier>>,
+}
+
+impl ElementWatcher {
+    /// Register the binding and start forwarding presence changes to `events`
+    pub async fn start(page: Page, events: Arc<EventLog>) -> Result<Self> {
+        page.execute(AddBindingParams::new(BINDING_NAME)).await?;
+
+        let subscriptions: Arc<RwLock<Vec<ElementSubscription>>> = Arc::new(RwLock::new(Vec::new()));
+        let mut calls = page.event_listener::<EventBindingCalled>().await?;
+
+        let tracked = Arc::clone(&subscriptions);
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+
+            while let Some(call) = calls.next().await {
+                if call.name != BINDING_NAME {
+                    continue;
+                }
+
+                let report: PresenceReport = match serde_json::from_str(&call.payload) {
+                    Ok(report) => report,
+                    Err(e) => {
+                        tracing::debug!("Ignoring malformed element watch payload: {}", e);
+                        continue;
+                    }
+                };
+
+                // Every new document reports its initial state; only real transitions count
+                let changed = {
+                    let mut subscriptions = tracked.write().await;
+                    subscriptions
+                        .iter_mut()
+                        .find(|s| s.id == report.id && s.present != report.present)
+                        .map(|subscription| {
+                            subscription.present = report.present;
+                            subscription.clone()
+                        })
+                };
+
+                if let Some(subscription) = changed {
+                    notify(&client, &events, &subscription).await;
+                }
+            }
+        });
+
+        Ok(Self {
+            page,
+            subscriptions,
+            script: RwLock::new(None),
+        })
+    }
+
+    /// Start watching for `selector`; returns the subscription with its current presence
+    pub async fn subscribe(
+        &self,
+        selector: SemanticSelector,
+        webhook_url: Option<String>,
+    ) -> Result<ElementSubscription> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let present = self.is_present(&id, &selector).await?;
+
+        let subscription = ElementSubscription {
+            id,
+            selector,
+            webhook_url,
+            present,
+            created_at: Utc::now(),
+        };
+
+        self.subscriptions.write().await.push(subscription.clone());
+        self.install().await?;
+
+        Ok(subscription)
+    }
+
+    /// Stop watching; returns false if the subscription does not exist
+    pub async fn unsubscribe(&self, id: &str) -> Result<bool> {
+        let removed = {
+            let mut subscriptions = self.subscriptions.write().await;
+            let before = subscriptions.len();
+            subscriptions.retain(|s| s.id != id);
+            subscriptions.len() != before
+        };
+
+        if removed {
+            self.install().await?;
+        }
+
+        Ok(removed)
+    }
+
+    pub async fn list(&self) -> Vec<ElementSubscription> {
+        self.subscriptions.read().await.clone()
+    }
+
+    /// Evaluate one selector right now, without reporting through the binding
+    async fn is_present(&self, id: &str, selector: &SemanticSelector) -> Result<bool> {
+        let script = format!(
+            r#"(() => {{ {} {} return matchesSpec({}); }})()"#,
+            COLLECT_ELEMENTS_JS,
+            MATCH_SPEC_JS,
+            spec_json(id, selector)
+        );
+
+        let result = self.page.evaluate(script).await?;
+        Ok(result.into_value::<bool>().unwrap_or(false))
+    }
+
+    /// Replace the per-document script with one watching the current subscriptions
+    async fn install(&self) -> Result<()> {
+        let specs: Vec<serde_json::Value> = self
+            .subscriptions
+            .read()
+            .await
+            .iter()
+            .map(|s| spec_json(&s.id, &s.selector))
+            .collect();
+
+        let mut script_id = self.script.write().await;
+        if let Some(identifier) = script_id.take() {
+            self.page
+                .execute(RemoveScriptToEvaluateOnNewDocumentParams::new(identifier))
+                .await?;
+        }
+
+        let watching = !specs.is_empty();
+        let script = build_watch_script(&serde_json::Value::Array(specs));
+
+        // Current document picks up the new specs; future documents get the script
+        self.page.evaluate(script.as_str()).await?;
+        if watching {
+            *script_id = Some(self.page.evaluate_on_new_document(script).await?);
+        }
+
+        Ok(())
+    }
+}
+
+/// Record a presence change and deliver it to the subscription's webhook
+async fn notify(client: &reqwest::Client, events: &EventLog, subscription: &ElementSubscription) {
+    let kind = if subscription.present {
+        SessionEventKind::ElementAppeared {
+            subscription_id: subscription.id.clone(),
+            selector: subscription.selector.clone(),
+        }
+    } else {
+        SessionEventKind::ElementDisappeared {
+            subscription_id: subscription.id.clone(),
+            selector: subscription.selector.clone(),
+        }
+    };
+
+    if let Some(url) = &subscription.webhook_url {
+        let payload = serde_json::json!({
+            "event": &kind,
+            "timestamp": Utc::now(),
+        });
+
+        if let Err(e) = client.post(url).json(&payload).send().await {
+            tracing::warn!("Element subscription webhook to {} failed: {}", url, e);
+        }
+    }
+
+    events.push(kind).await;
+}
+
+fn spec_json(id: &str, selector: &SemanticSelector) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "role": selector.role,
+        "name": selector.name,
+        "css": selector.css_fallback,
+    })
+}
+
+/// `matchesSpec(spec)`: same matching rules as action targeting (role, name substring,
+/// then CSS fallback), restricted to visible elements
+const MATCH_SPEC_JS: &str = r#"
+            function matchesSpec(spec) {
+                try {
+                    const role = (spec.role || '').toLowerCase();
+                    const found = collectElements().some(c =>
+                        c.role.toLowerCase() === role &&
+                        (!spec.name || (c.name || '').includes(spec.name)) &&
+                        isVisible(c.el)
+                    );
+                    if (found) return true;
+                    if (spec.css) {
+                        const el = document.querySelector(spec.css);
+                        return !!el && isVisible(el);
+                    }
+                } catch (e) {}
+                return false;
+            }
+"#;
+
+fn build_watch_script(specs: &serde_json::Value) -> String {
+    format!(
+        r#"(() => {{
+            const specs = {specs};
+            if (window.__mcpWatcher) {{
+                window.__mcpWatcher.setSpecs(specs);
+                return;
+            }}
+
+            {collect}
+            {matches}
+
+            const watcher = {{ specs, last: {{}}, timer: null }};
+
+            function check() {{
+                watcher.timer = null;
+                for (const spec of watcher.specs) {{
+                    const present = matchesSpec(spec);
+                    if (watcher.last[spec.id] !== present) {{
+                        watcher.last[spec.id] = present;
+                        try {{
+                            window.{binding}(JSON.stringify({{ id: spec.id, present }}));
+                        }} catch (e) {{}}
+                    }}
+                }}
+            }}
+
+            function schedule() {{
+                if (!watcher.timer) watcher.timer = setTimeout(check, {debounce});
+            }}
+
+            watcher.setSpecs = (next) => {{
+                watcher.specs = next;
+                check();
+            }};
+            window.__mcpWatcher = watcher;
+
+            function start() {{
+                new MutationObserver(schedule).observe(document.documentElement, {{
+                    childList: true,
+                    subtree: true,
+                    attributes: true,
+                    characterData: true,
+                }});
+                check();
+            }}
+
+            if (document.readyState === 'loading') {{
+                document.addEventListener('DOMContentLoaded', start);
+            }} else {{
+                start();
+            }}
+        }})()"#,
+        specs = specs,
+        collect = COLLECT_ELEMENTS_JS,
+        matches = MATCH_SPEC_JS,
+        binding = BINDING_NAME,
+        debounce = CHECK_DEBOUNCE_MS,
+    )
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use crate::hooks::origin_matches;
+
+use super::Credential;
+
+/// Logins for sites behind HTTP authentication (basic auth), by origin
+///
+/// Origins may start their host with "*." to cover every subdomain; an exact
+/// origin wins over a wildcard.
+#[derive(Debug, Default)]
+pub struct HttpAuthCredentials {
+    origins: Vec<(String, Credential)>,
+}
+
+impl HttpAuthCredentials {
+    /// Load from HTTP_AUTH_CREDENTIALS (inline JSON)
+    ///
+    /// A map of origin -> {"username": "...", "password": "..."}, e.g.
+    /// `{"https://staging.example.com": {"username": "qa", "password": "..."}}`.
+    pub fn from_env() -> Self {
+        let mut credentials = Self::default();
+
+        if let Ok(raw) = std::env::var("HTTP_AUTH_CREDENTIALS") {
+            credentials.load(&raw, "HTTP_AUTH_CREDENTIALS");
+        }
+
+        if !credentials.origins.is_empty() {
+            tracing::info!("Loaded HTTP authentication for {} origin(s)", credentials.origins.len());
+        }
+        credentials
+    }
+
+    fn load(&mut self, raw: &str, source: &str) {
+        // The error may quote the input, so it is not logged
+        match serde_json::from_str::<HashMap<String, Credential>>(raw) {
+            Ok(origins) => {
+                self.origins.extend(origins);
+                // Exact origins first, so they win over wildcards
+                self.origins.sort_by_key(|(origin, _)| origin.contains("://*."));
+            }
+            Err(_) => tracing::warn!("Invalid HTTP authentication JSON in {}, ignoring", source),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.origins.is_empty()
+    }
+
+    /// Login for an origin such as "https://staging.example.com"
+    pub fn for_origin(&self, origin: &str) -> Option<&Credential> {
+        self.origins
+            .iter()
+            .find(|(pattern, _)| origin_matches(pattern, origin))
+            .map(|(_, credential)| credential)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_origin() {
+        let mut credentials = HttpAuthCredentials::default();
+        credentials.load(
+            r#"{
+                "https://*.example.com": {"username": "team", "password": "a"},
+                "https://staging.example.com/": {"username": "qa", "password": "b"}
+            }"#,
+            "test",
+        );
+        credentials.load("not json", "test");
+
+        assert_eq!(credentials.for_origin("https://staging.example.com").unwrap().username, "qa");
+        assert_eq!(credentials.for_origin("https://preview.example.com").unwrap().username, "team");
+        assert!(credentials.for_origin("http://staging.example.com").is_none());
+        assert!(credentials.for_origin("https://example.org").is_none());
+    }
+}
@@ -0,0 +1,5 @@
+pub mod http_auth;
+pub mod vault;
+
+pub use http_auth::*;
+pub use vault::*;
@@ -0,0 +1,100 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A named login for a site under test
+#[derive(Clone, Deserialize)]
+pub struct Credential {
+    pub username: String,
+    pub password: String,
+}
+
+// Never print the password, even in debug logs
+impl fmt::Debug for Credential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credential")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Credentials the agent can use by name without ever seeing the secrets
+#[derive(Debug, Default)]
+pub struct CredentialVault {
+    credentials: HashMap<String, Credential>,
+}
+
+impl CredentialVault {
+    /// Load from CREDENTIAL_VAULT_FILE (JSON file) and/or CREDENTIAL_VAULT (inline JSON)
+    ///
+    /// Both hold a map of name -> {"username": "...", "password": "..."}; inline
+    /// entries override file entries with the same name.
+    pub fn from_env() -> Self {
+        let mut vault = Self::default();
+
+        if let Ok(path) = std::env::var("CREDENTIAL_VAULT_FILE") {
+            match std::fs::read_to_string(&path) {
+                Ok(raw) => vault.load(&raw, &path),
+                Err(e) => tracing::warn!("Failed to read credential vault {}: {}", path, e),
+            }
+        }
+
+        if let Ok(raw) = std::env::var("CREDENTIAL_VAULT") {
+            vault.load(&raw, "CREDENTIAL_VAULT");
+        }
+
+        if !vault.credentials.is_empty() {
+            tracing::info!("Loaded {} credential(s) into the vault", vault.credentials.len());
+        }
+        vault
+    }
+
+    fn load(&mut self, raw: &str, source: &str) {
+        // The error may quote the input, so it is not logged
+        match serde_json::from_str::<HashMap<String, Credential>>(raw) {
+            Ok(credentials) => self.credentials.extend(credentials),
+            Err(_) => tracing::warn!("Invalid credential vault JSON in {}, ignoring", source),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Credential> {
+        self.credentials.get(name)
+    }
+
+    /// Credential names, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.credentials.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// A vault holding only the named credentials, for sessions limited to a few logins
+    pub fn only(&self, names: &[String]) -> CredentialVault {
+        let credentials = self
+            .credentials
+            .iter()
+            .filter(|(name, _)| names.contains(name))
+            .map(|(name, credential)| (name.clone(), credential.clone()))
+            .collect();
+        CredentialVault { credentials }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_and_redact() {
+        let mut vault = CredentialVault::default();
+        vault.load(r#"{"staging": {"username": "qa@example.com", "password": "hunter2"}}"#, "test");
+        vault.load("not json", "test");
+
+        let credential = vault.get("staging").unwrap();
+        assert_eq!(credential.username, "qa@example.com");
+        assert!(!format!("{:?}", credential).contains("hunter2"));
+        assert_eq!(vault.names(), vec!["staging".to_string()]);
+        assert!(vault.only(&["prod".to_string()]).get("staging").is_none());
+    }
+}
@@ -0,0 +1,345 @@
+//! Browser end-to-end tests against the fixture pages in `fixtures/`
+//!
+//! Each test starts a local fixture server and drives a real session against
+//! it, so changes to the browser layer are checked against realistic DOM
+//! patterns. They need Chrome and only build with the `e2e` feature:
+//!
+//! ```text
+//! cargo test --features e2e e2e::
+//! ```
+
+use axum::{
+    response::Html,
+    routing::get,
+    Form, Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::browser::{BrowserAutomation, TableTarget};
+use crate::models::{ActionRequest, ErrorCode, ScrollDirection, SemanticSelector, SimplifiedElement, UIContext};
+use crate::session::{SessionManager, SessionOptions};
+
+/// Local web server hosting the fixture pages
+struct FixtureServer {
+    base_url: String,
+}
+
+impl FixtureServer {
+    async fn start() -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind fixture server");
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            axum::serve(listener, fixture_routes()).await.unwrap();
+        });
+
+        Self { base_url }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
+
+fn fixture_routes() -> Router {
+    Router::new()
+        .route("/login", get(|| async { Html(include_str!("fixtures/login.html")) }).post(sign_in))
+        .route("/modal", get(|| async { Html(include_str!("fixtures/modal.html")) }))
+        .route("/infinite_scroll", get(|| async { Html(include_str!("fixtures/infinite_scroll.html")) }))
+        .route("/iframe", get(|| async { Html(include_str!("fixtures/iframe.html")) }))
+        .route("/iframe/payment", get(|| async { Html(include_str!("fixtures/iframe_payment.html")) }))
+        .route("/shadow_dom", get(|| async { Html(include_str!("fixtures/shadow_dom.html")) }))
+        .route("/product_list", get(|| async { Html(include_str!("fixtures/product_list.html")) }))
+        .route("/signup", get(|| async { Html(include_str!("fixtures/signup.html")) }))
+        .route("/article", get(|| async { Html(include_str!("fixtures/article.html")) }))
+        .route("/pricing", get(|| async { Html(include_str!("fixtures/pricing.html")) }))
+}
+
+#[derive(Deserialize)]
+struct SignIn {
+    username: String,
+    password: String,
+}
+
+/// Accepts any username with the password "correct horse"
+async fn sign_in(Form(form): Form<SignIn>) -> Html<String> {
+    let body = match form.password == "correct horse" {
+        true => format!(r#"<h1>Dashboard</h1><a href="/login">Log out {}</a>"#, form.username),
+        false => r#"<p role="alert" aria-label="Wrong username or password">Wrong username or password</p>
+            <a href="/login">Try again</a>"#
+            .to_string(),
+    };
+    Html(format!("<!DOCTYPE html><html><head><title>Account</title></head><body>{}</body></html>", body))
+}
+
+/// A fresh session opened on a fixture page
+struct FixtureSession {
+    // Owns the browser; dropping it closes the session
+    _sessions: SessionManager,
+    browser: Arc<BrowserAutomation>,
+}
+
+impl FixtureSession {
+    async fn open(server: &FixtureServer, path: &str) -> Self {
+        let sessions = SessionManager::new();
+        let session_id = sessions
+            .create_session(server.url(path), 1280, 720, None, SessionOptions::default())
+            .await
+            .expect("Failed to open session (is Chrome installed?)");
+        let browser = sessions.get_browser(&session_id).unwrap();
+
+        Self {
+            _sessions: sessions,
+            browser,
+        }
+    }
+
+    /// Execute an action and require it to succeed
+    async fn run(&self, action: ActionRequest) {
+        let response = self.browser.execute_action(&action).await.unwrap();
+        assert!(response.success, "{:?} failed: {:?}", action, response);
+    }
+
+    async fn context(&self) -> UIContext {
+        self.browser.extract_context().await.unwrap()
+    }
+}
+
+fn selector(role: &str, name: &str) -> SemanticSelector {
+    SemanticSelector {
+        role: role.to_string(),
+        name: Some(name.to_string()),
+        description: None,
+        css_fallback: None,
+        frame: Vec::new(),
+        element_id: None,
+    }
+}
+
+fn wait_for(role: &str, name: &str) -> ActionRequest {
+    ActionRequest::WaitForElement {
+        selector: selector(role, name),
+        timeout_ms: Some(5000),
+    }
+}
+
+fn find<'a>(context: &'a UIContext, role: &str, name: &str) -> Option<&'a SimplifiedElement> {
+    context
+        .elements
+        .iter()
+        .find(|element| element.selector.role == role && element.selector.name.as_deref() == Some(name))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_login_form() {
+    let server = FixtureServer::start().await;
+    let session = FixtureSession::open(&server, "/login").await;
+
+    let context = session.context().await;
+    assert!(find(&context, "textbox", "Username").is_some());
+    assert!(find(&context, "textbox", "Password").is_some());
+
+    session
+        .run(ActionRequest::Type {
+            selector: selector("textbox", "Username"),
+            text: "ann".to_string(),
+        })
+        .await;
+    session
+        .run(ActionRequest::Type {
+            selector: selector("textbox", "Password"),
+            text: "correct horse".to_string(),
+        })
+        .await;
+    session.run(ActionRequest::Click { selector: selector("button", "Log in") }).await;
+    session.run(wait_for("link", "Log out ann")).await;
+
+    let context = session.context().await;
+    assert!(context.url.ends_with("/login"));
+    assert!(find(&context, "button", "Log in").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_modal_dialog() {
+    let server = FixtureServer::start().await;
+    let session = FixtureSession::open(&server, "/modal").await;
+
+    session.run(ActionRequest::Click { selector: selector("button", "Delete account") }).await;
+    session.run(wait_for("dialog", "Confirm deletion")).await;
+    assert!(find(&session.context().await, "button", "Confirm").is_some());
+
+    session.run(ActionRequest::Click { selector: selector("button", "Confirm") }).await;
+
+    let context = session.context().await;
+    assert!(find(&context, "dialog", "Confirm deletion").is_none());
+    assert!(find(&context, "button", "Account deleted").is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_element_id_target() {
+    let server = FixtureServer::start().await;
+    let session = FixtureSession::open(&server, "/product_list").await;
+
+    // Three buttons share role and name; the ID picks the second
+    let context = session.context().await;
+    let buttons: Vec<usize> = context
+        .elements
+        .iter()
+        .filter(|element| element.selector.name.as_deref() == Some("Add to cart"))
+        .map(|element| element.id)
+        .collect();
+    assert_eq!(buttons.len(), 3);
+
+    let click: ActionRequest =
+        serde_json::from_value(serde_json::json!({ "tool": "click", "element_id": buttons[1] })).unwrap();
+    session.run(click).await;
+    session.run(wait_for("link", "Cart: Blue mug")).await;
+
+    // IDs refer to the latest context only
+    let stale: ActionRequest = serde_json::from_value(serde_json::json!({ "tool": "click", "element_id": 99 })).unwrap();
+    let response = session.browser.execute_action(&stale).await.unwrap();
+    assert_eq!(response.error, Some(ErrorCode::ElementNotFound));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_infinite_scroll() {
+    let server = FixtureServer::start().await;
+    let session = FixtureSession::open(&server, "/infinite_scroll").await;
+
+    let context = session.context().await;
+    assert!(find(&context, "link", "Post 10").is_some());
+    assert!(find(&context, "link", "Post 11").is_none());
+
+    session
+        .run(ActionRequest::Scroll {
+            direction: ScrollDirection::Down,
+            amount: Some(5000),
+            selector: None,
+        })
+        .await;
+    session.run(wait_for("link", "Post 11")).await;
+
+    let posts = session
+        .context()
+        .await
+        .elements
+        .iter()
+        .filter(|element| element.selector.role == "link")
+        .count();
+    assert!(posts >= 20, "expected a second page of posts, found {}", posts);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_iframe_page() {
+    let server = FixtureServer::start().await;
+    let session = FixtureSession::open(&server, "/iframe").await;
+
+    session.run(wait_for("button", "Back to cart")).await;
+    let context = session.context().await;
+    let pay = find(&context, "button", "Pay now").expect("button inside the iframe");
+    assert_eq!(pay.selector.frame, vec![0]);
+    assert!(pay.display.ends_with("[frame=0]"));
+
+    let mut card = selector("textbox", "Card number");
+    card.frame = vec![0];
+    session
+        .run(ActionRequest::Type {
+            selector: card,
+            text: "4242 4242 4242 4242".to_string(),
+        })
+        .await;
+    session.run(ActionRequest::Click { selector: pay.selector.clone() }).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_shadow_dom_page() {
+    let server = FixtureServer::start().await;
+    let session = FixtureSession::open(&server, "/shadow_dom").await;
+
+    session.run(wait_for("link", "Archive")).await;
+    let context = session.context().await;
+    assert!(find(&context, "button", "Subscribe").is_some());
+    // Labels are looked up in the shadow root
+    assert!(find(&context, "textbox", "Email").is_some());
+
+    session
+        .run(ActionRequest::Type {
+            selector: selector("textbox", "Email"),
+            text: "ann@example.com".to_string(),
+        })
+        .await;
+    session.run(ActionRequest::Click { selector: selector("button", "Subscribe") }).await;
+    session.run(wait_for("button", "Subscribed")).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_aria_grid_table() {
+    let server = FixtureServer::start().await;
+    let session = FixtureSession::open(&server, "/pricing").await;
+
+    let target = TableTarget { selector: None, heading: Some("Pricing".to_string()) };
+    let table = session.browser.extract_table(&target, 1, None).await.unwrap().unwrap();
+    assert_eq!(table.headers, vec!["Plan", "Price", "Seats"]);
+    assert_eq!(table.rows.len(), 3);
+    assert_eq!(table.rows[1], vec!["Team", "$12 / user", "Up to 50"]);
+    assert_eq!(table.rows[2], vec!["Enterprise", "Contact sales", "Contact sales"]);
+
+    // The largest table wins without a target, whether it is HTML or ARIA
+    let largest = session.browser.extract_table(&TableTarget::default(), 1, None).await.unwrap().unwrap();
+    assert_eq!(largest.headers, table.headers);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_form_state() {
+    let server = FixtureServer::start().await;
+    let session = FixtureSession::open(&server, "/signup").await;
+
+    let forms = session.browser.forms().await.unwrap();
+    assert_eq!(forms.len(), 2);
+    let signup = &forms[0];
+    assert_eq!(signup.name.as_deref(), Some("Create account"));
+    assert_eq!(signup.method.as_deref(), Some("post"));
+    assert_eq!(signup.submit_buttons[0].label.as_deref(), Some("Create account"));
+
+    let field = |label: &str| signup.fields.iter().find(|f| f.label.as_deref() == Some(label)).unwrap();
+    let email = field("Email");
+    assert!(email.required && !email.valid);
+    assert_eq!(email.value.as_deref(), Some("not-an-email"));
+    assert_eq!(email.validation_message.as_deref(), Some("Enter a valid email address"));
+    assert_eq!(field("Password").value.as_deref(), Some("********"));
+    assert_eq!(field("Plan").value.as_deref(), Some("Team"));
+    assert_eq!(field("Plan").options, vec!["Free", "Team"]);
+    assert_eq!(field("I accept the terms").checked, Some(false));
+
+    // The ID is the one the element has in the context
+    let context = session.context().await;
+    assert_eq!(email.element_id, find(&context, "textbox", "Email").map(|e| e.id));
+
+    // Fields outside any form come as a nameless group
+    assert_eq!(forms[1].name, None);
+    assert_eq!(forms[1].fields[0].placeholder.as_deref(), Some("Search the docs"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_readable_content() {
+    let server = FixtureServer::start().await;
+    let session = FixtureSession::open(&server, "/article").await;
+
+    let content = session.browser.readable_content().await.unwrap();
+    assert_eq!(content.byline.as_deref(), Some("Ann Lee"));
+
+    let markdown = content.to_markdown();
+    assert!(markdown.starts_with("# Version 2.0 is out\n\n"), "{}", markdown);
+    assert!(markdown.contains("a new **query planner**, faster startup"));
+    assert!(markdown.contains(&format!("[upgrade guide]({})", server.url("/docs/upgrade"))));
+    assert!(markdown.contains("- `max_conn` is now `max_connections`\n- `timeout`"));
+    assert!(markdown.contains("```toml\nmax_connections = 20\n```"));
+
+    // Navigation, sidebar, share links and footer stay out
+    for noise in ["Home", "Popular posts", "Share", "rights reserved"] {
+        assert!(!markdown.contains(noise), "{} in {}", noise, markdown);
+    }
+}
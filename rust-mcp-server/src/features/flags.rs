@@ -0,0 +1,262 @@
+use anyhow::Result;
+use dashmap::DashMap;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+use crate::models::{ActionRequest, ActionResponse, ErrorCode};
+
+/// Redis hash holding runtime overrides (field = feature name, value = "true"/"false")
+const REDIS_KEY: &str = "feature_flags";
+
+/// Risky subsystems that operators can switch on per environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// Running arbitrary JavaScript in the page
+    Evaluate,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 1] = [Feature::Evaluate];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Evaluate => "evaluate",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.name() == name)
+    }
+
+    /// Feature an action needs, if it is gated
+    pub fn for_action(action: &ActionRequest) -> Option<Self> {
+        match action {
+            ActionRequest::Evaluate { .. } => Some(Self::Evaluate),
+            _ => None,
+        }
+    }
+
+    /// Feedback for an action turned away because this feature is off
+    pub fn disabled_feedback(self) -> ActionResponse {
+        ActionResponse::error_with_suggestion(
+            ErrorCode::FeatureDisabled,
+            &format!("The '{}' feature is disabled on this server", self.name()),
+            "reach the goal with the other actions",
+        )
+    }
+}
+
+/// Current state of a flag
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureState {
+    pub feature: Feature,
+    pub enabled: bool,
+    /// Value from FEATURE_FLAGS (everything is off unless listed)
+    pub default: bool,
+    /// Runtime override set through the admin API, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overridden: Option<bool>,
+}
+
+/// Feature flags: defaults from the environment plus runtime overrides,
+/// optionally shared between instances through Redis
+pub struct FeatureFlags {
+    defaults: HashMap<Feature, bool>,
+    overrides: Arc<DashMap<Feature, bool>>,
+    redis: OnceCell<ConnectionManager>,
+}
+
+impl FeatureFlags {
+    /// Parse FEATURE_FLAGS, e.g. "evaluate=true" (a bare name means enabled)
+    pub fn from_env() -> Self {
+        let raw = std::env::var("FEATURE_FLAGS").unwrap_or_default();
+        Self::new(parse_flags(&raw))
+    }
+
+    pub fn new(defaults: HashMap<Feature, bool>) -> Self {
+        Self {
+            defaults,
+            overrides: Arc::new(DashMap::new()),
+            redis: OnceCell::new(),
+        }
+    }
+
+    /// Share overrides through Redis when USE_REDIS=true
+    ///
+    /// Loads existing overrides and refreshes them periodically so flips made on
+    /// another instance take effect here too.
+    pub async fn connect_redis_from_env(&self) -> Result<()> {
+        let use_redis = std::env::var("USE_REDIS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false);
+        if !use_redis {
+            return Ok(());
+        }
+
+        let redis_url =
+            std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379".to_string());
+        let client = redis::Client::open(redis_url)?;
+        let mut connection = ConnectionManager::new(client).await?;
+
+        load_overrides(&mut connection, &self.overrides).await?;
+
+        let overrides = Arc::clone(&self.overrides);
+        let mut refresh_connection = connection.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(10));
+            loop {
+                ticker.tick().await;
+                if let Err(e) = load_overrides(&mut refresh_connection, &overrides).await {
+                    tracing::warn!("Failed to refresh feature flags from Redis: {}", e);
+                }
+            }
+        });
+
+        let _ = self.redis.set(connection);
+        tracing::info!("Feature flag overrides shared through Redis");
+        Ok(())
+    }
+
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        self.overrides
+            .get(&feature)
+            .map(|v| *v)
+            .unwrap_or_else(|| self.default_for(feature))
+    }
+
+    /// Features currently on
+    pub fn enabled(&self) -> Vec<Feature> {
+        Feature::ALL.into_iter().filter(|f| self.is_enabled(*f)).collect()
+    }
+
+    /// Feedback for `action` if it needs a feature that is off
+    pub fn check_action(&self, action: &ActionRequest) -> Option<ActionResponse> {
+        Feature::for_action(action)
+            .filter(|feature| !self.is_enabled(*feature))
+            .map(Feature::disabled_feedback)
+    }
+
+    fn default_for(&self, feature: Feature) -> bool {
+        self.defaults.get(&feature).copied().unwrap_or(false)
+    }
+
+    pub fn state(&self, feature: Feature) -> FeatureState {
+        FeatureState {
+            feature,
+            enabled: self.is_enabled(feature),
+            default: self.default_for(feature),
+            overridden: self.overrides.get(&feature).map(|v| *v),
+        }
+    }
+
+    pub fn list(&self) -> Vec<FeatureState> {
+        Feature::ALL.into_iter().map(|f| self.state(f)).collect()
+    }
+
+    /// Set (`Some`) or clear (`None`) a runtime override
+    pub async fn set_override(&self, feature: Feature, enabled: Option<bool>) -> Result<()> {
+        if let Some(connection) = self.redis.get() {
+            let mut connection = connection.clone();
+            match enabled {
+                Some(value) => {
+                    connection
+                        .hset::<_, _, _, ()>(REDIS_KEY, feature.name(), value.to_string())
+                        .await?
+                }
+                None => connection.hdel::<_, _, ()>(REDIS_KEY, feature.name()).await?,
+            }
+        }
+
+        match enabled {
+            Some(value) => {
+                self.overrides.insert(feature, value);
+            }
+            None => {
+                self.overrides.remove(&feature);
+            }
+        }
+
+        tracing::info!("Feature '{}' override set to {:?}", feature.name(), enabled);
+        Ok(())
+    }
+}
+
+fn parse_flags(raw: &str) -> HashMap<Feature, bool> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let (name, value) = entry.split_once('=').unwrap_or((entry, "true"));
+            let feature = Feature::parse(name.trim());
+            if feature.is_none() {
+                tracing::warn!("Unknown feature flag '{}' in FEATURE_FLAGS", name);
+            }
+            Some((feature?, value.trim().parse().unwrap_or(false)))
+        })
+        .collect()
+}
+
+async fn load_overrides(
+    connection: &mut ConnectionManager,
+    overrides: &DashMap<Feature, bool>,
+) -> Result<()> {
+    let stored: HashMap<String, String> = connection.hgetall(REDIS_KEY).await?;
+
+    overrides.clear();
+    for (name, value) in stored {
+        if let (Some(feature), Ok(enabled)) = (Feature::parse(&name), value.parse()) {
+            overrides.insert(feature, enabled);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_flags() {
+        let flags = parse_flags(" evaluate=true ,unknown=true");
+        assert_eq!(flags.get(&Feature::Evaluate), Some(&true));
+        assert_eq!(flags.len(), 1);
+
+        let flags = parse_flags("evaluate=false");
+        assert_eq!(flags.get(&Feature::Evaluate), Some(&false));
+        assert_eq!(parse_flags("evaluate").get(&Feature::Evaluate), Some(&true));
+    }
+
+    #[tokio::test]
+    async fn test_overrides_take_precedence() {
+        let flags = FeatureFlags::new(parse_flags("evaluate"));
+        assert!(flags.is_enabled(Feature::Evaluate));
+        assert!(!FeatureFlags::new(HashMap::new()).is_enabled(Feature::Evaluate));
+
+        flags.set_override(Feature::Evaluate, Some(false)).await.unwrap();
+        assert!(!flags.is_enabled(Feature::Evaluate));
+        assert_eq!(flags.state(Feature::Evaluate).overridden, Some(false));
+
+        flags.set_override(Feature::Evaluate, None).await.unwrap();
+        assert!(flags.is_enabled(Feature::Evaluate));
+    }
+
+    #[tokio::test]
+    async fn test_gated_actions() {
+        let flags = FeatureFlags::new(HashMap::new());
+        let evaluate = ActionRequest::Evaluate { script: "document.title".to_string() };
+        let feedback = flags.check_action(&evaluate).expect("evaluate is off by default");
+        assert_eq!(feedback.error, Some(ErrorCode::FeatureDisabled));
+        assert!(flags.check_action(&ActionRequest::Reload).is_none());
+
+        flags.set_override(Feature::Evaluate, Some(true)).await.unwrap();
+        assert!(flags.check_action(&evaluate).is_none());
+        assert_eq!(flags.enabled(), vec![Feature::Evaluate]);
+    }
+}
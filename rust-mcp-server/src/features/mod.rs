@@ -0,0 +1,3 @@
+pub mod flags;
+
+pub use flags::*;
@@ -0,0 +1,169 @@
+use anyhow::Result;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// An app-provided test function the agent may call on matching origins
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestHook {
+    /// Dotted path of the page function, e.g. "window.__test.resetDb"
+    pub function: String,
+
+    /// Origins the hook may run on, e.g. "https://staging.example.com" or
+    /// "https://*.staging.example.com" (any subdomain)
+    pub origins: Vec<String>,
+
+    /// Shown to the agent and in listings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl TestHook {
+    /// Reject function paths that are not plain property chains
+    pub fn validate(&self) -> Result<()> {
+        let valid_identifier = |part: &str| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        };
+
+        anyhow::ensure!(
+            self.function.split('.').all(valid_identifier),
+            "function must be a dotted path such as window.__test.resetDb"
+        );
+        anyhow::ensure!(!self.origins.is_empty(), "at least one origin is required");
+        for origin in &self.origins {
+            anyhow::ensure!(
+                origin.starts_with("http://") || origin.starts_with("https://"),
+                "origin '{}' must start with http:// or https://",
+                origin
+            );
+        }
+        Ok(())
+    }
+
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        self.origins.iter().any(|pattern| origin_matches(pattern, origin))
+    }
+}
+
+/// Named test hooks, from TEST_HOOKS plus runtime registrations through the admin API
+#[derive(Default)]
+pub struct HookRegistry {
+    hooks: DashMap<String, TestHook>,
+}
+
+impl HookRegistry {
+    /// Load TEST_HOOKS: JSON map of name -> {"function": "...", "origins": [...], "description": "..."}
+    pub fn from_env() -> Self {
+        let registry = Self::default();
+
+        if let Ok(raw) = std::env::var("TEST_HOOKS") {
+            match serde_json::from_str::<HashMap<String, TestHook>>(&raw) {
+                Ok(hooks) => {
+                    for (name, hook) in hooks {
+                        if let Err(e) = registry.set(&name, hook) {
+                            tracing::warn!("Invalid test hook '{}', ignoring: {}", name, e);
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!("Invalid TEST_HOOKS, ignoring: {}", e),
+            }
+        }
+
+        registry
+    }
+
+    pub fn get(&self, name: &str) -> Option<TestHook> {
+        self.hooks.get(name).map(|hook| hook.clone())
+    }
+
+    /// Register or replace a hook
+    pub fn set(&self, name: &str, hook: TestHook) -> Result<()> {
+        hook.validate()?;
+        self.hooks.insert(name.to_string(), hook);
+        Ok(())
+    }
+
+    pub fn remove(&self, name: &str) -> Option<TestHook> {
+        self.hooks.remove(name).map(|(_, hook)| hook)
+    }
+
+    /// All hooks, sorted by name
+    pub fn list(&self) -> Vec<(String, TestHook)> {
+        let mut hooks: Vec<(String, TestHook)> = self
+            .hooks
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        hooks.sort_by(|a, b| a.0.cmp(&b.0));
+        hooks
+    }
+
+    /// Hooks that may run on `origin`, sorted by name
+    pub fn available_on(&self, origin: &str) -> Vec<(String, TestHook)> {
+        self.list()
+            .into_iter()
+            .filter(|(_, hook)| hook.allows_origin(origin))
+            .collect()
+    }
+}
+
+/// Match an origin ("https://app.example.com:8443") against a pattern
+///
+/// Scheme and port must be equal. A leading "*." in the pattern's host matches
+/// any subdomain, but not the bare domain.
+pub fn origin_matches(pattern: &str, origin: &str) -> bool {
+    fn split(origin: &str) -> Option<(&str, &str)> {
+        let (scheme, rest) = origin.split_once("://")?;
+        Some((scheme, rest.trim_end_matches('/')))
+    }
+
+    let (Some((pattern_scheme, pattern_host)), Some((scheme, host))) = (split(pattern), split(origin)) else {
+        return false;
+    };
+    if !pattern_scheme.eq_ignore_ascii_case(scheme) {
+        return false;
+    }
+
+    let pattern_host = pattern_host.to_ascii_lowercase();
+    let host = host.to_ascii_lowercase();
+    match pattern_host.strip_prefix("*.") {
+        Some(domain) => host.ends_with(&format!(".{}", domain)),
+        None => host == pattern_host,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin_matches() {
+        assert!(origin_matches("https://staging.example.com", "https://staging.example.com"));
+        assert!(origin_matches("https://staging.example.com/", "https://STAGING.example.com"));
+        assert!(origin_matches("https://*.example.com", "https://app.staging.example.com"));
+        assert!(origin_matches("http://localhost:3000", "http://localhost:3000"));
+
+        assert!(!origin_matches("https://*.example.com", "https://example.com"));
+        assert!(!origin_matches("https://staging.example.com", "http://staging.example.com"));
+        assert!(!origin_matches("http://localhost:3000", "http://localhost:3001"));
+        assert!(!origin_matches("https://*.example.com", "https://example.com.evil.io"));
+    }
+
+    #[test]
+    fn test_hook_validation() {
+        let hook = |function: &str| TestHook {
+            function: function.to_string(),
+            origins: vec!["https://staging.example.com".to_string()],
+            description: None,
+        };
+
+        assert!(hook("window.__test.resetDb").validate().is_ok());
+        assert!(hook("resetDb").validate().is_ok());
+        assert!(hook("fetch('/x')").validate().is_err());
+        assert!(hook("window..x").validate().is_err());
+    }
+}
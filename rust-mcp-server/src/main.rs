@@ -2,30 +2,51 @@ mod agent;
 mod api;
 mod auth;
 mod browser;
+mod credentials;
+#[cfg(all(test, feature = "e2e"))]
+mod e2e;
+mod features;
+mod hooks;
+mod mcp;
 mod models;
+mod notifications;
+mod otp;
+mod privacy;
+mod recorder;
 mod session;
+mod tunnel;
 
 use anyhow::Result;
 use axum::Router;
 use std::net::SocketAddr;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt};
 
 use api::{create_router, AppState};
+use mcp::McpServer;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
+    // Load environment variables
+    dotenv::dotenv().ok();
+
+    // `--stdio` (or MCP_TRANSPORT=stdio) speaks MCP on stdin/stdout instead of serving HTTP
+    let stdio = std::env::args().any(|arg| arg == "--stdio")
+        || std::env::var("MCP_TRANSPORT").is_ok_and(|transport| transport == "stdio");
+
+    // Initialize tracing; stdout belongs to the protocol in stdio mode
+    let log_writer = if stdio {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "mcp_server=debug,tower_http=debug".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(log_writer).with_ansi(!stdio))
         .init();
 
-    // Load environment variables
-    dotenv::dotenv().ok();
-
     tracing::info!("Starting MCP Server for UI Automation");
 
     // Initialize Playwright (install browsers if needed)
@@ -35,6 +56,43 @@ async fn main() -> Result<()> {
     // Create application state
     let state = AppState::new();
 
+    // Share feature flag overrides between instances (USE_REDIS=true)
+    if let Err(e) = state.features.connect_redis_from_env().await {
+        tracing::warn!("Feature flags: Redis unavailable, using local overrides only: {}", e);
+    }
+
+    // Keep the run history across restarts (USE_REDIS=true)
+    if let Err(e) = state.run_store.connect_redis_from_env().await {
+        tracing::warn!("Agent runs: Redis unavailable, keeping runs in memory only: {}", e);
+    }
+
+    // Forget Idempotency-Keys past IDEMPOTENCY_TTL_SECONDS
+    state.idempotency.start_reaper(std::time::Duration::from_secs(60));
+
+    // Drop agent runs past RUN_RETENTION_HOURS
+    state.run_store.start_reaper(std::time::Duration::from_secs(600));
+
+    // Drop agent tasks past TASK_RETENTION_SECONDS and those of closed sessions
+    let sessions = std::sync::Arc::clone(&state.session_manager);
+    state.task_store.start_reaper(std::time::Duration::from_secs(60), move |session_id| {
+        sessions.get_session(session_id).is_ok()
+    });
+
+    // Close sessions whose lease ran out
+    state
+        .session_manager
+        .start_reaper(std::time::Duration::from_secs(30));
+
+    // Pre-launch browsers for the WARM_POOL start URLs
+    state
+        .session_manager
+        .start_warm_pool(std::time::Duration::from_secs(10));
+
+    if stdio {
+        tracing::info!("Serving MCP over stdio");
+        return McpServer::new(state).serve_stdio().await;
+    }
+
     // Build router
     let app: Router = create_router(state);
 
@@ -48,24 +106,99 @@ async fn main() -> Result<()> {
 
     tracing::info!("MCP Server listening on {}", addr);
     tracing::info!("API Documentation:");
+    tracing::info!("  Routes are served under /v1 and /v2; unprefixed paths use the API-Version header (default 1)");
+    tracing::info!("  GET  /capabilities - Supported actions, emulation, LLM providers and limits");
     tracing::info!("");
     tracing::info!("Authentication (Step 4):");
-    tracing::info!("  POST /auth/login - Login and get JWT token");
+    tracing::info!("  POST /auth/login - Login and get JWT token (or HttpOnly cookie + CSRF token)");
+    tracing::info!("  POST /auth/logout - Clear auth cookies");
     tracing::info!("  GET  /auth/me - Get current user info");
     tracing::info!("");
     tracing::info!("Session Management:");
     tracing::info!("  POST /sessions - Create new session");
-    tracing::info!("  GET  /sessions - List all sessions");
-    tracing::info!("  DELETE /sessions/:session_id - Delete session");
+    tracing::info!("  GET  /sessions - List all sessions (v2: with metadata)");
+    tracing::info!("  GET  /sessions/:session_id - Get session details (incl. expires_at)");
+    tracing::info!("  POST /sessions/:session_id/keepalive - Extend the session lease");
+    tracing::info!("  POST /sessions/:session_id/share - Mint a scoped, expiring capability token");
+    tracing::info!("  DELETE /sessions/:session_id - Delete session (restorable; ?permanent=true to skip)");
+    tracing::info!("  POST /sessions/:session_id/restore - Restore a deleted session");
     tracing::info!("");
     tracing::info!("Browser Control (Step 1):");
-    tracing::info!("  GET  /:session_id/get_context - Get UI context (AXTree)");
+    tracing::info!("  GET  /:session_id/get_context - Get UI context (AXTree, ?mode=set_of_mark for a marked screenshot)");
     tracing::info!("  POST /:session_id/execute - Execute action");
+    tracing::info!("  POST /:session_id/validate_action - Dry-run an action against the current page");
+    tracing::info!("  PUT  /:session_id/vars - Set variables for {{{{name}}}} placeholders in tasks and actions");
     tracing::info!("  POST /:session_id/trigger - Handle client trigger");
+    tracing::info!("  GET  /:session_id/events - Session event log");
+    tracing::info!("  GET  /:session_id/console - Console messages and page errors");
+    tracing::info!("  GET  /:session_id/tabs - Open tabs and which one is active");
+    tracing::info!("  GET  /:session_id/ws - Live session events over WebSocket");
+    tracing::info!("  POST /:session_id/extract_table - Table rows as JSON or CSV (with pagination)");
+    tracing::info!("  GET  /:session_id/forms - Forms with field labels, values, required flags and validation state");
+    tracing::info!("  GET  /:session_id/content - Main content as text or Markdown (?format=text|markdown)");
+    tracing::info!("  POST /:session_id/extract - Page data mapped into a JSON Schema by the LLM");
+    tracing::info!("  GET  /:session_id/screenshot - PNG/JPEG of the viewport or full page");
+    tracing::info!("  POST /:session_id/a11y/assistive_check - Elements lost under high contrast / 200% text");
+    tracing::info!("  GET  /:session_id/downloads - List downloaded files (also /artifacts)");
+    tracing::info!("  GET  /:session_id/downloads/:artifact_id - Fetch a downloaded file");
+    tracing::info!("  POST /:session_id/subscriptions - Watch for an element to appear/disappear");
+    tracing::info!("  GET  /:session_id/subscriptions - List element subscriptions");
+    tracing::info!("  DELETE /:session_id/subscriptions/:id - Stop watching an element");
+    tracing::info!("  POST /:session_id/network/rules - Block, rewrite or stub matching requests");
+    tracing::info!("  GET  /:session_id/network/rules - List network rules and their hits");
+    tracing::info!("  DELETE /:session_id/network/rules - Remove all network rules");
+    tracing::info!("  DELETE /:session_id/network/rules/:id - Remove one network rule");
+    tracing::info!("  GET  /:session_id/storage - localStorage and sessionStorage of the current origin");
+    tracing::info!("  PUT  /:session_id/storage - Set or remove web storage keys");
+    tracing::info!("  GET  /:session_id/state - Cookies and localStorage as a Playwright storage state");
+    tracing::info!("  POST /:session_id/evaluate - Run JavaScript in the page (evaluate feature)");
+    tracing::info!("  GET  /:session_id/elements/:id/neighborhood - Element surroundings");
+    tracing::info!("  GET  /:session_id/hooks - Test hooks callable on the current page");
+    tracing::info!("  POST /:session_id/recordings/start - Record executed actions into a named macro");
+    tracing::info!("  POST /:session_id/recordings/stop - Save the recording, turning given values into parameters");
+    tracing::info!("  POST /:session_id/macros/:name/replay - Replay a macro with parameter values");
+    tracing::info!("  GET  /macros - List recorded macros");
+    tracing::info!("  GET  /macros/:name - A macro's actions and parameters");
+    tracing::info!("  DELETE /macros/:name - Delete a macro");
+    tracing::info!("  GET  /recordings/:id/export?format=playwright - A macro or run as a Playwright test");
     tracing::info!("");
     tracing::info!("AI Agent (Step 2 & 3):");
     tracing::info!("  POST /:session_id/agent/execute - Single-step agent execution");
     tracing::info!("  POST /:session_id/agent/execute_multi_step - Multi-step with retry & feedback loop");
+    tracing::info!("  POST /:session_id/agent/execute_multi_step/stream - Multi-step streamed as Server-Sent Events");
+    tracing::info!("  POST /:session_id/agent/tasks - Queue a multi-step run in the background");
+    tracing::info!("  POST /:session_id/agent/pause - Pause the running multi-step run");
+    tracing::info!("  POST /:session_id/agent/resume - Resume a paused multi-step run");
+    tracing::info!("  POST /:session_id/agent/cancel - Stop the running multi-step run");
+    tracing::info!("  GET  /:session_id/agent/runs - Stored multi-step runs of the session");
+    tracing::info!("  GET  /agent/runs/:run_id - A stored multi-step run with its full result");
+    tracing::info!("  GET  /:session_id/agent/trace/:run_id - Steps of a traced run with screenshots and DOM snapshots");
+    tracing::info!("  GET  /tasks/:task_id - Agent task status and result");
+    tracing::info!("  DELETE /tasks/:task_id - Cancel an agent task");
+    tracing::info!("  POST /run - One-shot task: open a session, run the agent, tear down");
+    tracing::info!("  GET  /runs/:run_id/report?format=markdown|html - Shareable run report");
+    tracing::info!("  GET  /runs/:run_id/downloads/:download_id - Fetch a file a run downloaded");
+    tracing::info!("  GET  /runs/compare?a=&b= - Where two runs of a task diverge");
+    tracing::info!("");
+    tracing::info!("Notifications:");
+    tracing::info!("  GET  /notifications/webhooks - List your Slack/Teams webhooks");
+    tracing::info!("  PUT  /notifications/webhooks - Replace your Slack/Teams webhooks");
+    tracing::info!("");
+    tracing::info!("Tunnels:");
+    tracing::info!("  GET  /tunnels/connect - WebSocket tunnel to an app on your machine");
+    tracing::info!("");
+    tracing::info!("One-time codes:");
+    tracing::info!("  POST /otp/twilio/sms - Twilio incoming-SMS webhook (signed)");
+
+    tracing::info!("");
+    tracing::info!("Admin (X-Admin-Token):");
+    tracing::info!("  GET  /admin/features - List feature flags");
+    tracing::info!("  PUT  /admin/features/:feature - Override a feature flag");
+    tracing::info!("  GET  /admin/metrics - Session count and page-hygiene counters");
+    tracing::info!("  GET  /analytics/pages?url= - Element interaction heatmap per URL pattern");
+    tracing::info!("  GET  /admin/hooks - List test hooks");
+    tracing::info!("  PUT  /admin/hooks/:name - Register a test hook");
+    tracing::info!("  DELETE /admin/hooks/:name - Remove a test hook");
 
     // Start server
     let listener = tokio::net::TcpListener::bind(addr).await?;
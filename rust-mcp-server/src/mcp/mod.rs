@@ -0,0 +1,7 @@
+pub mod protocol;
+pub mod server;
+pub mod tools;
+
+pub use protocol::*;
+pub use server::*;
+pub use tools::*;
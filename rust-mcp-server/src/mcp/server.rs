@@ -0,0 +1,210 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+use crate::api::AppState;
+
+use super::{
+    call_tool, negotiate_protocol_version, tool_definitions, JsonRpcRequest, JsonRpcResponse,
+    ToolCallError, INTERNAL_ERROR, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR,
+};
+
+#[derive(Debug, Deserialize)]
+struct CallToolParams {
+    name: String,
+    #[serde(default)]
+    arguments: Option<Value>,
+}
+
+/// Model Context Protocol server exposing browser sessions as tools
+pub struct McpServer {
+    state: AppState,
+}
+
+impl McpServer {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// Serve newline-delimited JSON-RPC on stdin/stdout until stdin closes
+    ///
+    /// Requests run concurrently, so a long `agent_execute` does not hold up
+    /// `ping` or other calls; responses are written as they complete.
+    pub async fn serve_stdio(self) -> Result<()> {
+        let server = Arc::new(self);
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+
+        let writer = tokio::spawn(async move {
+            let mut stdout = tokio::io::stdout();
+            while let Some(line) = rx.recv().await {
+                stdout.write_all(line.as_bytes()).await?;
+                stdout.write_all(b"\n").await?;
+                stdout.flush().await?;
+            }
+            anyhow::Ok(())
+        });
+
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let server = Arc::clone(&server);
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                if let Some(response) = server.handle_message(&line).await {
+                    match serde_json::to_string(&response) {
+                        Ok(json) => {
+                            let _ = tx.send(json);
+                        }
+                        Err(e) => tracing::error!("Failed to serialize MCP response: {}", e),
+                    }
+                }
+            });
+        }
+
+        // The writer stops once in-flight requests have answered
+        drop(tx);
+        writer.await??;
+        tracing::info!("MCP client disconnected");
+        Ok(())
+    }
+
+    /// Handle one JSON-RPC message; notifications get no response
+    pub async fn handle_message(&self, message: &str) -> Option<JsonRpcResponse> {
+        let request: JsonRpcRequest = match serde_json::from_str::<Value>(message) {
+            Err(e) => return Some(JsonRpcResponse::error(Value::Null, PARSE_ERROR, e.to_string())),
+            Ok(value) => {
+                let id = value.get("id").cloned().unwrap_or(Value::Null);
+                match serde_json::from_value(value) {
+                    Ok(request) => request,
+                    Err(e) => return Some(JsonRpcResponse::error(id, INVALID_REQUEST, e.to_string())),
+                }
+            }
+        };
+
+        let Some(id) = request.id.clone() else {
+            tracing::debug!("MCP notification: {}", request.method);
+            return None;
+        };
+
+        if request.jsonrpc != "2.0" {
+            return Some(JsonRpcResponse::error(id, INVALID_REQUEST, "jsonrpc must be \"2.0\""));
+        }
+
+        Some(match self.handle_request(request).await {
+            Ok(result) => JsonRpcResponse::success(id, result),
+            Err((code, message)) => JsonRpcResponse::error(id, code, message),
+        })
+    }
+
+    async fn handle_request(&self, request: JsonRpcRequest) -> Result<Value, (i64, String)> {
+        let params = request.params.unwrap_or(Value::Null);
+
+        match request.method.as_str() {
+            "initialize" => {
+                let requested = params.get("protocolVersion").and_then(Value::as_str);
+                if let Some(client) = params.get("clientInfo") {
+                    tracing::info!("MCP client connected: {}", client);
+                }
+
+                Ok(json!({
+                    "protocolVersion": negotiate_protocol_version(requested),
+                    "capabilities": { "tools": { "listChanged": false } },
+                    "serverInfo": {
+                        "name": "interact-ui-agent",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                    "instructions": "Drive a real browser: create_session on a URL, read the page with get_context, \
+                        then execute_action with role/name selectors, or hand a whole task to agent_execute.",
+                }))
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => {
+                let params: CallToolParams = serde_json::from_value(params)
+                    .map_err(|e| (INVALID_PARAMS, format!("Invalid tools/call params: {}", e)))?;
+                tracing::info!("MCP tool call: {}", params.name);
+
+                let arguments = params.arguments.unwrap_or_else(|| json!({}));
+                match call_tool(&self.state, &params.name, arguments).await {
+                    Ok(result) => serde_json::to_value(result).map_err(|e| (INTERNAL_ERROR, e.to_string())),
+                    Err(ToolCallError::UnknownTool(name)) => Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+                    Err(ToolCallError::InvalidArguments(e)) => {
+                        Err((INVALID_PARAMS, format!("Invalid arguments for {}: {}", params.name, e)))
+                    }
+                }
+            }
+            method => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn call(server: &McpServer, message: Value) -> Value {
+        let response = server.handle_message(&message.to_string()).await.unwrap();
+        serde_json::to_value(response).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_handshake_and_tool_listing() {
+        let server = McpServer::new(AppState::new());
+
+        let init = call(
+            &server,
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"protocolVersion": "2024-11-05"}}),
+        )
+        .await;
+        assert_eq!(init["id"], 1);
+        assert_eq!(init["result"]["protocolVersion"], "2024-11-05");
+        assert!(init["result"]["capabilities"]["tools"].is_object());
+
+        let notification = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        assert!(server.handle_message(&notification.to_string()).await.is_none());
+
+        let tools = call(&server, json!({"jsonrpc": "2.0", "id": "t", "method": "tools/list"})).await;
+        let names: Vec<&str> = tools["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        for expected in ["create_session", "get_context", "execute_action", "agent_execute"] {
+            assert!(names.contains(&expected), "missing tool {}", expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let server = McpServer::new(AppState::new());
+
+        let parse = server.handle_message("{not json").await.unwrap();
+        assert_eq!(parse.error.unwrap().code, PARSE_ERROR);
+
+        let unknown_method = call(&server, json!({"jsonrpc": "2.0", "id": 2, "method": "resources/list"})).await;
+        assert_eq!(unknown_method["error"]["code"], METHOD_NOT_FOUND);
+
+        let unknown_tool = call(
+            &server,
+            json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call", "params": {"name": "nope"}}),
+        )
+        .await;
+        assert_eq!(unknown_tool["error"]["code"], INVALID_PARAMS);
+
+        // Missing session: the tool runs and reports the failure to the model
+        let missing = call(
+            &server,
+            json!({"jsonrpc": "2.0", "id": 4, "method": "tools/call",
+                   "params": {"name": "get_context", "arguments": {"session_id": "missing"}}}),
+        )
+        .await;
+        assert_eq!(missing["result"]["isError"], true);
+    }
+}
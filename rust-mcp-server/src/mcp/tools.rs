@@ -0,0 +1,249 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::api::error::ApiError;
+use crate::api::handlers::{self, ContentQuery, ContextQuery, CreateSessionRequest, DeleteSessionQuery, MultiStepTaskRequest};
+use crate::api::AppState;
+use crate::models::ActionRequest;
+
+use super::ToolResult;
+
+/// Why a `tools/call` could not run at all (reported as a JSON-RPC error)
+#[derive(Debug)]
+pub enum ToolCallError {
+    UnknownTool(String),
+    InvalidArguments(String),
+}
+
+/// Tools advertised in `tools/list`, with JSON schemas for their arguments
+pub fn tool_definitions() -> Vec<Value> {
+    let session_id = json!({ "type": "string", "description": "Id returned by create_session" });
+
+    vec![
+        json!({
+            "name": "create_session",
+            "description": "Open a browser session on a URL. Returns the session_id used by the other tools.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "initial_url": { "type": "string", "description": "Page to open" },
+                    "viewport_width": { "type": "integer", "default": 1280 },
+                    "viewport_height": { "type": "integer", "default": 720 },
+                    "automation_banner": {
+                        "type": "boolean",
+                        "description": "Show an \"automated session\" banner on the pages (default: server setting)"
+                    },
+                    "keyboard_only": {
+                        "type": "boolean",
+                        "default": false,
+                        "description": "Reject mouse actions; the page has to be operated with press_key and type"
+                    },
+                    "preset": {
+                        "type": "string",
+                        "description": "Session preset bundling allowed origins, read-only mode, blocked actions and credentials (listed by GET /capabilities)"
+                    },
+                    "fingerprint": {
+                        "type": "string",
+                        "description": "Browser identity to present: user agent, languages, platform, screen and fonts, e.g. \"chrome-windows\" (listed by GET /capabilities)"
+                    }
+                },
+                "required": ["initial_url"]
+            }
+        }),
+        json!({
+            "name": "list_sessions",
+            "description": "List open browser sessions with their current URL and expiry.",
+            "inputSchema": { "type": "object", "properties": {} }
+        }),
+        json!({
+            "name": "delete_session",
+            "description": "Close a browser session.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session_id": session_id,
+                    "permanent": {
+                        "type": "boolean",
+                        "default": false,
+                        "description": "Skip the trash; the session cannot be restored"
+                    }
+                },
+                "required": ["session_id"]
+            }
+        }),
+        json!({
+            "name": "get_context",
+            "description": "Read the current page as a list of interactive elements (role, name, state, in_viewport) from the accessibility tree.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session_id": session_id,
+                    "format": {
+                        "type": "string",
+                        "enum": ["flat", "tree"],
+                        "default": "flat",
+                        "description": "\"tree\" also returns the elements nested under their forms, lists, menus and landmarks"
+                    }
+                },
+                "required": ["session_id"]
+            }
+        }),
+        json!({
+            "name": "read_content",
+            "description": "Read the main content of the current page (article body, documentation, results) \
+                without navigation, sidebars and footers.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session_id": session_id,
+                    "format": {
+                        "type": "string",
+                        "enum": ["text", "markdown"],
+                        "default": "text",
+                        "description": "\"markdown\" keeps headings, lists, links and code blocks"
+                    }
+                },
+                "required": ["session_id"]
+            }
+        }),
+        json!({
+            "name": "execute_action",
+            "description": "Run one browser action, addressing elements by role and name from get_context, \
+                or by their element_id from the latest get_context. Failures come with a reason and a suggestion for recovery.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session_id": session_id,
+                    "action": {
+                        "type": "object",
+                        "description": "Action with a \"tool\" field, e.g. {\"tool\": \"click\", \"role\": \"button\", \"name\": \"Login\"}, \
+                            {\"tool\": \"click\", \"element_id\": 12}, \
+                            {\"tool\": \"type\", \"role\": \"textbox\", \"name\": \"Email\", \"text\": \"...\"}, \
+                            {\"tool\": \"navigate\", \"url\": \"...\"} or {\"tool\": \"scroll\", \"direction\": \"down\"}",
+                        "properties": { "tool": { "type": "string" } },
+                        "required": ["tool"]
+                    }
+                },
+                "required": ["session_id", "action"]
+            }
+        }),
+        json!({
+            "name": "agent_execute",
+            "description": "Let the server's own agent carry out a task in natural language, step by step. \
+                Returns every step taken and whether the task was completed.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session_id": session_id,
+                    "task": { "type": "string", "description": "What to accomplish, e.g. \"log in and open the billing page\"" },
+                    "max_steps": { "type": "integer", "default": 20 },
+                    "max_retries_per_step": { "type": "integer", "default": 3 },
+                    "use_vision": {
+                        "type": "boolean",
+                        "default": false,
+                        "description": "Also show the agent a screenshot of the viewport (multimodal models only)"
+                    }
+                },
+                "required": ["session_id", "task"]
+            }
+        }),
+    ]
+}
+
+#[derive(Debug, Deserialize)]
+struct GetContextArgs {
+    session_id: String,
+    #[serde(flatten)]
+    query: ContextQuery,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadContentArgs {
+    session_id: String,
+    #[serde(flatten)]
+    query: ContentQuery,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeleteSessionArgs {
+    session_id: String,
+    #[serde(flatten)]
+    query: DeleteSessionQuery,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExecuteActionArgs {
+    session_id: String,
+    action: ActionRequest,
+}
+
+#[derive(Debug, Deserialize)]
+struct AgentExecuteArgs {
+    session_id: String,
+    #[serde(flatten)]
+    task: MultiStepTaskRequest,
+}
+
+fn parse_args<T: DeserializeOwned>(arguments: Value) -> Result<T, ToolCallError> {
+    serde_json::from_value(arguments).map_err(|e| ToolCallError::InvalidArguments(e.to_string()))
+}
+
+/// Tool result from a REST handler's response; HTTP errors become tool errors
+fn reply<T: Serialize>(response: Result<Json<T>, ApiError>) -> ToolResult {
+    match response {
+        Ok(Json(body)) => match serde_json::to_string_pretty(&body) {
+            Ok(text) => ToolResult::text(text),
+            Err(e) => ToolResult::error(format!("Failed to serialize result: {}", e)),
+        },
+        Err(error) => ToolResult::error(error.to_string()),
+    }
+}
+
+/// Run a tool by calling the REST handler behind it
+///
+/// Calls go through the same handlers as the HTTP API without an authenticated
+/// user: whoever starts the stdio server owns its sessions.
+pub async fn call_tool(state: &AppState, name: &str, arguments: Value) -> Result<ToolResult, ToolCallError> {
+    let state = State(state.clone());
+
+    let result = match name {
+        "create_session" => {
+            let req: CreateSessionRequest = parse_args(arguments)?;
+            reply(handlers::create_session(state, None, Json(req)).await)
+        }
+        "list_sessions" => reply(Ok(handlers::list_sessions_v2(state).await)),
+        "delete_session" => {
+            let args: DeleteSessionArgs = parse_args(arguments)?;
+            reply(handlers::delete_session(state, Path(args.session_id), Query(args.query)).await)
+        }
+        "get_context" => {
+            let args: GetContextArgs = parse_args(arguments)?;
+            reply(handlers::get_context(state, Path(args.session_id), Query(args.query)).await)
+        }
+        "read_content" => {
+            let args: ReadContentArgs = parse_args(arguments)?;
+            reply(handlers::get_content(state, Path(args.session_id), Query(args.query)).await)
+        }
+        "execute_action" => {
+            let args: ExecuteActionArgs = parse_args(arguments)?;
+            let response = handlers::execute_action(state, Path(args.session_id), Json(args.action)).await;
+            // A failed action is a tool error, so clients show it as one
+            let failed = matches!(&response, Ok(Json(action)) if !action.success);
+            ToolResult {
+                is_error: failed,
+                ..reply(response)
+            }
+        }
+        "agent_execute" => {
+            let args: AgentExecuteArgs = parse_args(arguments)?;
+            reply(handlers::agent_execute_multi_step(state, Path(args.session_id), None, Json(args.task)).await)
+        }
+        _ => return Err(ToolCallError::UnknownTool(name.to_string())),
+    };
+
+    Ok(result)
+}
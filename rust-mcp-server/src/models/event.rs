@@ -0,0 +1,197 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ActionRequest, ActionResponse, ClosedOverlay, CompactContext, DialogRecord, FocusedElement, SemanticSelector, TabInfo,
+};
+
+/// Entry in a session's event log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionEvent {
+    pub timestamp: DateTime<Utc>,
+
+    #[serde(flatten)]
+    pub kind: SessionEventKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionEventKind {
+    /// Main frame navigated (including SPA history changes)
+    Navigated { url: String },
+
+    /// A consent banner was dismissed
+    ConsentDismissed { vendor: String, button: Option<String> },
+
+    /// An obstructing overlay was closed automatically
+    OverlayClosed { overlay: ClosedOverlay },
+
+    /// A previously closed overlay was re-opened
+    OverlayRestored { overlay_id: u32 },
+
+    /// An element matching a subscription appeared on the page
+    ElementAppeared {
+        subscription_id: String,
+        selector: SemanticSelector,
+    },
+
+    /// An element matching a subscription is no longer on the page
+    ElementDisappeared {
+        subscription_id: String,
+        selector: SemanticSelector,
+    },
+
+    /// A download finished and is available as an artifact
+    DownloadCompleted { artifact_id: String, filename: String },
+
+    /// Tabbing kept cycling through these elements without reaching the rest of the page
+    FocusTrapped { elements: Vec<FocusedElement> },
+
+    /// An app test hook was called through `invoke_hook`
+    HookInvoked { name: String, success: bool },
+
+    /// The page opened a JavaScript dialog, which was answered right away
+    DialogHandled { dialog: DialogRecord },
+
+    /// A new tab opened, by the page or through `new_tab`
+    TabOpened { tab: TabInfo },
+
+    /// A tab closed; actions now go to `active_tab_id`
+    TabClosed { tab_id: u32, active_tab_id: u32 },
+
+    /// Fresh context after a significant page change (live subscribers only, not retained)
+    ContextUpdated {
+        reason: ContextChange,
+        context: CompactContext,
+    },
+
+    /// A `console.*` call on the page (live subscribers only, not retained)
+    ConsoleMessage { level: String, text: String },
+
+    /// An action finished, whoever sent it (live subscribers only, not retained)
+    ActionExecuted {
+        action: ActionRequest,
+        result: ActionResponse,
+    },
+
+    /// A multi-step agent run completed a step (live subscribers only, not retained)
+    AgentStep {
+        step_number: usize,
+        action: ActionRequest,
+        context: CompactContext,
+    },
+}
+
+/// What triggered an automatic context push
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextChange {
+    Navigation,
+    Mutations,
+}
+
+impl SessionEvent {
+    pub fn new(kind: SessionEventKind) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            kind,
+        }
+    }
+}
+
+/// Placeholder for values live subscribers don't get to see
+const REDACTED: &str = "[redacted]";
+
+impl SessionEventKind {
+    /// `ActionExecuted` without the values that only the session's owner may see
+    ///
+    /// Observers watch `/ws` with an `observe` token, which can't read variables,
+    /// web storage or cookies, so the same values are left out of action events:
+    /// text typed into password fields, form and storage values, and whatever
+    /// `get_storage`, `get_otp`, `evaluate` and `invoke_hook` returned.
+    pub fn action_executed(action: &ActionRequest, result: &ActionResponse) -> Self {
+        let mut result = result.clone();
+        if matches!(
+            action,
+            ActionRequest::GetStorage { .. }
+                | ActionRequest::GetOtp { .. }
+                | ActionRequest::Evaluate { .. }
+                | ActionRequest::InvokeHook { .. }
+        ) {
+            result.details = None;
+        }
+
+        Self::ActionExecuted {
+            action: observed_action(action, result.is_secret()),
+            result,
+        }
+    }
+}
+
+/// The action as live subscribers see it; `secret` when it typed into a password field
+pub fn observed_action(action: &ActionRequest, secret: bool) -> ActionRequest {
+    let mut action = action.clone();
+    match &mut action {
+        ActionRequest::Type { text, .. } if secret => *text = REDACTED.to_string(),
+        ActionRequest::FillForm { fields, .. } => {
+            for value in fields.values_mut() {
+                *value = REDACTED.to_string();
+            }
+        }
+        ActionRequest::SetStorage { value: Some(value), .. } => *value = REDACTED.to_string(),
+        _ => {}
+    }
+    action
+}
+
+/// A client's request to be told when an element appears or disappears
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementSubscription {
+    pub id: String,
+
+    pub selector: SemanticSelector,
+
+    /// Also POST each change to this URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+
+    /// Whether a matching element is currently on the page
+    pub present: bool,
+
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sent(kind: SessionEventKind) -> serde_json::Value {
+        serde_json::to_value(kind).unwrap()
+    }
+
+    #[test]
+    fn test_action_events_leave_out_secrets() {
+        let password: ActionRequest = serde_json::from_value(serde_json::json!({
+            "tool": "type", "role": "textbox", "name": "Password", "text": "hunter2"
+        }))
+        .unwrap();
+        let typed = ActionResponse::success_with_details(serde_json::json!({"verified": true, "secret": true}));
+        let event = sent(SessionEventKind::action_executed(&password, &typed));
+        assert_eq!(event["action"]["text"], REDACTED);
+
+        let storage: ActionRequest = serde_json::from_value(serde_json::json!({"tool": "get_storage"})).unwrap();
+        let read = ActionResponse::success_with_details(serde_json::json!({"entries": {"token": "abc"}}));
+        let event = sent(SessionEventKind::action_executed(&storage, &read));
+        assert!(event["result"].get("details").is_none());
+
+        // Other text and results are sent as they are
+        let search: ActionRequest = serde_json::from_value(serde_json::json!({
+            "tool": "type", "role": "searchbox", "name": "Search", "text": "lamps"
+        }))
+        .unwrap();
+        let verified = ActionResponse::success_with_details(serde_json::json!({"verified": true}));
+        let event = sent(SessionEventKind::action_executed(&search, &verified));
+        assert_eq!(event["action"]["text"], "lamps");
+        assert_eq!(event["result"]["details"]["verified"], true);
+    }
+}
@@ -1,7 +1,8 @@
-pub mod action;
-pub mod context;
+//! Server-only models; the ones clients share are in `ui_core::models`
+
+pub mod event;
 pub mod session;
 
-pub use action::*;
-pub use context::*;
+pub use event::*;
 pub use session::*;
+pub use ui_core::models::*;
@@ -1,7 +1,11 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// Lease length used when SESSION_LEASE_SECONDS is not set
+pub const DEFAULT_LEASE_SECONDS: i64 = 1800;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     pub id: String,
@@ -12,6 +16,10 @@ pub struct Session {
     pub created_at: DateTime<Utc>,
     pub last_activity: DateTime<Utc>,
 
+    /// End of the session lease; the session is reaped after this time
+    #[serde(default = "Utc::now")]
+    pub expires_at: DateTime<Utc>,
+
     /// Browser context info
     pub browser_info: BrowserInfo,
 }
@@ -27,6 +35,21 @@ pub struct BrowserInfo {
     /// Viewport dimensions
     pub viewport_width: u32,
     pub viewport_height: u32,
+
+    /// Pages show the "automated session" banner
+    #[serde(default)]
+    pub automation_banner: bool,
+
+    /// Mouse actions are disabled (keyboard-only accessibility testing)
+    #[serde(default)]
+    pub keyboard_only: bool,
+
+    /// Session preset whose rules the browser enforces
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// Fingerprint preset the browser presents to sites
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
 }
 
 impl Session {
@@ -38,25 +61,183 @@ impl Session {
             user_id: None, // Can be set later for multi-user scenarios
             created_at: now,
             last_activity: now,
+            expires_at: now + Duration::seconds(DEFAULT_LEASE_SECONDS),
             browser_info: BrowserInfo {
                 current_url: initial_url.clone(),
                 initial_url,
                 viewport_width,
                 viewport_height,
+                automation_banner: false,
+                keyboard_only: false,
+                preset: None,
+                fingerprint: None,
             },
         }
     }
 
-    /// Builder method to set user_id (for future multi-user session creation)
-    #[allow(dead_code)]
+    /// Builder method to set the owning user
     pub fn with_user_id(mut self, user_id: String) -> Self {
         self.user_id = Some(user_id);
         self
     }
 
+    /// Builder method to record whether pages show the automation banner
+    pub fn with_automation_banner(mut self, enabled: bool) -> Self {
+        self.browser_info.automation_banner = enabled;
+        self
+    }
+
+    /// Builder method to record that the session only accepts keyboard interaction
+    pub fn with_keyboard_only(mut self, enabled: bool) -> Self {
+        self.browser_info.keyboard_only = enabled;
+        self
+    }
+
+    /// Builder method to record the preset the session was created with
+    pub fn with_preset(mut self, name: String) -> Self {
+        self.browser_info.preset = Some(name);
+        self
+    }
+
+    /// Builder method to record the session's fingerprint preset
+    pub fn with_fingerprint(mut self, name: String) -> Self {
+        self.browser_info.fingerprint = Some(name);
+        self
+    }
+
+    /// Builder method to set the initial lease length
+    pub fn with_lease(mut self, seconds: i64) -> Self {
+        self.expires_at = self.created_at + Duration::seconds(seconds);
+        self
+    }
+
     pub fn update_activity(&mut self) {
         self.last_activity = Utc::now();
     }
+
+    /// Hold the session for at least `seconds` from now (never shortens the lease)
+    pub fn extend_lease(&mut self, seconds: i64) {
+        let until = Utc::now() + Duration::seconds(seconds);
+        if until > self.expires_at {
+            self.expires_at = until;
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Utc::now() >= self.expires_at
+    }
+
+    /// Make `user_id` the owner, unless the session already has one
+    pub fn claim(&mut self, user_id: &str) {
+        self.user_id.get_or_insert_with(|| user_id.to_string());
+    }
+
+    /// Whether the user may use the session without a capability token; a
+    /// session without an owner is open to every caller
+    pub fn usable_by(&self, user_id: Option<&str>) -> bool {
+        self.user_id.as_deref().is_none_or(|owner| user_id == Some(owner))
+    }
+}
+
+/// Cookies and web storage of a session, enough to recreate it in a new browser
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageState {
+    /// Page the state was captured on (web storage belongs to its origin)
+    pub url: String,
+
+    pub cookies: Vec<StoredCookie>,
+
+    #[serde(default)]
+    pub local_storage: HashMap<String, String>,
+
+    #[serde(default)]
+    pub session_storage: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+
+    /// Expiry in seconds since the epoch; `None` for session cookies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<f64>,
+
+    pub http_only: bool,
+    pub secure: bool,
+
+    /// "Strict", "Lax" or "None"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub same_site: Option<String>,
+}
+
+/// Cookies and localStorage in Playwright's `storageState` format
+///
+/// Exported by `GET /:session_id/state` and accepted as `storage_state` when
+/// creating a session, so a login can be reused across sessions, and with
+/// Playwright tests.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlaywrightStorageState {
+    #[serde(default)]
+    pub cookies: Vec<PlaywrightCookie>,
+
+    #[serde(default)]
+    pub origins: Vec<PlaywrightOrigin>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaywrightCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+
+    #[serde(default = "default_cookie_path")]
+    pub path: String,
+
+    /// Seconds since the epoch; -1 for session cookies
+    #[serde(default = "session_cookie_expiry")]
+    pub expires: f64,
+
+    #[serde(default)]
+    pub http_only: bool,
+
+    #[serde(default)]
+    pub secure: bool,
+
+    /// "Strict", "Lax" or "None"
+    #[serde(default = "default_same_site")]
+    pub same_site: String,
+}
+
+fn default_cookie_path() -> String {
+    "/".to_string()
+}
+
+fn session_cookie_expiry() -> f64 {
+    -1.0
+}
+
+fn default_same_site() -> String {
+    "Lax".to_string()
+}
+
+/// localStorage of one origin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaywrightOrigin {
+    /// e.g. "https://app.example.com"
+    pub origin: String,
+
+    #[serde(default, rename = "localStorage")]
+    pub local_storage: Vec<PlaywrightStorageItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaywrightStorageItem {
+    pub name: String,
+    pub value: String,
 }
 
 /// Trigger event from client (Step 1.5)
@@ -76,3 +257,32 @@ pub enum TriggerEventType {
     UserAction,
     StateUpdate,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playwright_storage_state_format() {
+        // As written by Playwright's `context.storageState({ path })`
+        let state: PlaywrightStorageState = serde_json::from_str(
+            r#"{
+                "cookies": [
+                    {"name": "sid", "value": "abc", "domain": ".example.com", "path": "/", "expires": -1, "httpOnly": true, "secure": true, "sameSite": "Strict"},
+                    {"name": "theme", "value": "dark", "domain": "app.example.com"}
+                ],
+                "origins": [{"origin": "https://app.example.com", "localStorage": [{"name": "token", "value": "t0k"}]}]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(state.cookies[0].http_only && state.cookies[0].same_site == "Strict");
+        let minimal = &state.cookies[1];
+        assert_eq!((minimal.path.as_str(), minimal.expires, minimal.same_site.as_str()), ("/", -1.0, "Lax"));
+        assert_eq!(state.origins[0].local_storage[0].value, "t0k");
+
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["cookies"][0]["httpOnly"], true);
+        assert_eq!(json["origins"][0]["localStorage"][0]["name"], "token");
+    }
+}
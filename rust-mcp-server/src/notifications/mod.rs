@@ -0,0 +1,3 @@
+pub mod webhook;
+
+pub use webhook::*;
@@ -0,0 +1,296 @@
+use anyhow::{bail, Context, Result};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+
+use crate::agent::AgentRun;
+
+/// Tenant used for unauthenticated requests and as fallback for every tenant
+pub const DEFAULT_TENANT: &str = "*";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookKind {
+    Slack,
+    Teams,
+}
+
+/// Reject webhook URLs that are not https or resolve to a non-public address
+///
+/// Webhooks are posted from the server, so a URL pointing at loopback, a
+/// private network or link-local metadata endpoints would let a caller reach
+/// services that are only meant to be reachable from inside.
+pub async fn validate_webhook_url(url: &str) -> Result<()> {
+    let parsed = reqwest::Url::parse(url).context("invalid URL")?;
+    if parsed.scheme() != "https" {
+        bail!("webhook URL must use https");
+    }
+    let host = parsed.host_str().context("webhook URL has no host")?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = parsed.port_or_known_default().unwrap_or(443);
+
+    let addresses: Vec<_> = tokio::net::lookup_host((host, port))
+        .await
+        .with_context(|| format!("cannot resolve {}", host))?
+        .collect();
+    if addresses.is_empty() {
+        bail!("{} does not resolve to any address", host);
+    }
+    if let Some(address) = addresses.iter().find(|address| !is_public_address(address.ip())) {
+        bail!("{} resolves to the non-public address {}", host, address.ip());
+    }
+    Ok(())
+}
+
+/// Resolves webhook hosts for the notifier's client, keeping only public addresses
+///
+/// The client connects to the addresses checked here, so a host that resolves
+/// to a public address for `validate_webhook_url` and to an internal one when
+/// the post is sent (DNS rebinding) still can't reach internal services.
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|address| is_public_address(address.ip()))
+                .collect();
+            if addresses.is_empty() {
+                return Err(format!("{} does not resolve to any public address", host).into());
+            }
+            Ok(Box::new(addresses.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Whether `ip` is reachable on the public internet (not loopback, private, link-local and the like)
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_v4(mapped),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    let shared = a == 100 && (64..128).contains(&b);
+    !(ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || shared
+        || a == 0)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    let unique_local = first & 0xfe00 == 0xfc00;
+    let link_local = first & 0xffc0 == 0xfe80;
+    !(ip.is_loopback() || ip.is_unspecified() || ip.is_multicast() || unique_local || link_local)
+}
+
+/// An incoming-webhook destination for run notifications
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookTarget {
+    pub kind: WebhookKind,
+    pub url: String,
+}
+
+/// Posts concise run summaries to Slack/Teams incoming webhooks, per tenant
+pub struct Notifier {
+    client: reqwest::Client,
+    webhooks: DashMap<String, Vec<WebhookTarget>>,
+    /// Successful runs shorter than this are not announced (failures always are)
+    min_run_seconds: i64,
+    /// Base URL used to link to run reports
+    public_base_url: Option<String>,
+}
+
+impl Notifier {
+    /// Configure from environment:
+    /// - NOTIFICATION_WEBHOOKS: JSON map of tenant -> [{"kind": "slack", "url": "..."}], "*" for all tenants
+    /// - NOTIFY_MIN_RUN_SECONDS: minimum duration for announcing successful runs (default: 30)
+    /// - PUBLIC_BASE_URL: server URL used in report links
+    pub fn from_env() -> Self {
+        let webhooks = DashMap::new();
+
+        if let Ok(raw) = std::env::var("NOTIFICATION_WEBHOOKS") {
+            match serde_json::from_str::<HashMap<String, Vec<WebhookTarget>>>(&raw) {
+                Ok(config) => {
+                    for (tenant, targets) in config {
+                        webhooks.insert(tenant, targets);
+                    }
+                }
+                Err(e) => tracing::warn!("Invalid NOTIFICATION_WEBHOOKS, ignoring: {}", e),
+            }
+        }
+
+        let min_run_seconds = std::env::var("NOTIFY_MIN_RUN_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(30);
+
+        // A redirect could lead a checked URL to an address the check would reject
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            webhooks,
+            min_run_seconds,
+            public_base_url: std::env::var("PUBLIC_BASE_URL").ok(),
+        }
+    }
+
+    /// Webhooks configured for a tenant (without the "*" fallback)
+    pub fn webhooks_for(&self, tenant: &str) -> Vec<WebhookTarget> {
+        self.webhooks
+            .get(tenant)
+            .map(|entry| entry.clone())
+            .unwrap_or_default()
+    }
+
+    /// Replace a tenant's webhooks
+    pub fn set_webhooks(&self, tenant: &str, targets: Vec<WebhookTarget>) {
+        if targets.is_empty() {
+            self.webhooks.remove(tenant);
+        } else {
+            self.webhooks.insert(tenant.to_string(), targets);
+        }
+    }
+
+    /// Announce a finished run if it failed or ran long enough; errors are logged, not returned
+    pub async fn notify_run(&self, tenant: Option<&str>, run: &AgentRun) {
+        let duration = (run.finished_at - run.started_at).num_seconds();
+        if run.result.task_completed && duration < self.min_run_seconds {
+            return;
+        }
+
+        let mut targets = self.webhooks_for(DEFAULT_TENANT);
+        if let Some(tenant) = tenant.filter(|t| *t != DEFAULT_TENANT) {
+            targets.extend(self.webhooks_for(tenant));
+        }
+
+        if targets.is_empty() {
+            return;
+        }
+
+        let text = self.summary(run, duration);
+        for target in &targets {
+            if let Err(e) = self.post(target, &text).await {
+                tracing::warn!("Failed to send {:?} notification for run {}: {}", target.kind, run.run_id, e);
+            }
+        }
+    }
+
+    fn summary(&self, run: &AgentRun, duration: i64) -> String {
+        let (icon, outcome) = if run.result.task_completed {
+            ("✅", "completed")
+        } else {
+            ("❌", "failed")
+        };
+
+        let mut text = format!(
+            "{} Agent run {} in {}s ({} steps, {} retries)\nTask: {}",
+            icon, outcome, duration, run.result.steps_taken, run.result.retries_count, run.task
+        );
+
+        if let Some(summary) = &run.result.summary {
+            text.push_str(&format!("\nSummary: {}", summary.summary));
+        }
+
+        if let Some(error) = &run.result.error {
+            text.push_str(&format!("\nError: {}", error));
+        }
+
+        if let Some(base) = &self.public_base_url {
+            text.push_str(&format!(
+                "\nReport: {}/runs/{}/report?format=html",
+                base.trim_end_matches('/'),
+                run.run_id
+            ));
+        }
+
+        text
+    }
+
+    async fn post(&self, target: &WebhookTarget, text: &str) -> Result<()> {
+        // Slack and Teams incoming webhooks both accept a plain {"text": ...} payload
+        // Catches plain http and internal IP literals, which never reach the resolver;
+        // host names are kept to public addresses by the client's resolver when connecting
+        validate_webhook_url(&target.url).await?;
+
+        let payload = match target.kind {
+            WebhookKind::Slack => serde_json::json!({ "text": text }),
+            WebhookKind::Teams => serde_json::json!({ "text": text.replace('\n', "\n\n") }),
+        };
+
+        self.client
+            .post(&target.url)
+            .json(&payload)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public_address() {
+        let internal = [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fe80::1",
+            "fd00::1",
+            "::ffff:127.0.0.1",
+        ];
+        for internal in internal {
+            assert!(!is_public_address(internal.parse().unwrap()), "{}", internal);
+        }
+        for public in ["93.184.216.34", "2606:2800:220:1::248"] {
+            assert!(is_public_address(public.parse().unwrap()), "{}", public);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_webhook_url() {
+        assert!(validate_webhook_url("http://93.184.216.34/hook").await.is_err());
+        assert!(validate_webhook_url("https://169.254.169.254/latest/meta-data").await.is_err());
+        assert!(validate_webhook_url("https://10.0.0.5/hook").await.is_err());
+        assert!(validate_webhook_url("https://[::1]:8443/hook").await.is_err());
+        assert!(validate_webhook_url("https://localhost/hook").await.is_err());
+        assert!(validate_webhook_url("https://93.184.216.34/hook").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_resolver_drops_internal_addresses() {
+        use reqwest::dns::Resolve;
+
+        let name = "localhost".parse().unwrap();
+        assert!(PublicResolver.resolve(name).await.is_err());
+    }
+}
@@ -0,0 +1,319 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_native_tls::{native_tls, TlsConnector, TlsStream};
+
+use super::{extract_code, OtpProvider};
+
+/// How often the mailbox is checked for new mail
+const POLL_INTERVAL_MS: u64 = 3000;
+
+/// Newest messages inspected per poll
+const MAX_MESSAGES_PER_POLL: usize = 5;
+
+fn default_imap_port() -> u16 {
+    993
+}
+
+fn default_mailbox() -> String {
+    "INBOX".to_string()
+}
+
+/// Mailbox that receives the one-time codes
+#[derive(Clone, Deserialize)]
+pub struct ImapConfig {
+    pub host: String,
+    #[serde(default = "default_imap_port")]
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    #[serde(default = "default_mailbox")]
+    pub mailbox: String,
+    /// Only consider mail from this sender (IMAP `FROM` substring match)
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Code length; any run of 4-8 digits when absent
+    #[serde(default)]
+    pub digits: Option<usize>,
+}
+
+/// Codes from emails, polled over IMAP with implicit TLS
+///
+/// The mailbox is opened read-only, so messages are not marked as seen.
+pub struct ImapProvider {
+    config: ImapConfig,
+}
+
+impl ImapProvider {
+    pub fn new(config: ImapConfig) -> Self {
+        Self { config }
+    }
+
+    /// Newest code in mail received at or after `since`
+    async fn find_code(&self, session: &mut ImapSession, since: DateTime<Utc>) -> Result<Option<String>> {
+        // SINCE has day granularity; INTERNALDATE is checked per message below
+        let mut search = format!("UID SEARCH SINCE {}", since.format("%d-%b-%Y"));
+        if let Some(from) = &self.config.from {
+            search.push_str(&format!(" FROM {}", quote(from)));
+        }
+
+        let response = session.command(&search).await?;
+        let mut uids: Vec<u32> = response
+            .lines
+            .iter()
+            .filter_map(|line| line.strip_prefix("* SEARCH"))
+            .flat_map(|rest| rest.split_whitespace().filter_map(|uid| uid.parse().ok()))
+            .collect();
+        uids.sort_unstable_by(|a, b| b.cmp(a));
+
+        for uid in uids.into_iter().take(MAX_MESSAGES_PER_POLL) {
+            let response = session
+                .command(&format!(
+                    "UID FETCH {} (INTERNALDATE BODY.PEEK[HEADER.FIELDS (SUBJECT)] BODY.PEEK[TEXT])",
+                    uid
+                ))
+                .await?;
+
+            let received = response.lines.iter().find_map(|line| internal_date(line));
+            if received.is_some_and(|received| received < since) {
+                continue;
+            }
+
+            if let Some(code) = extract_code(&email_text(&response.literals), self.config.digits) {
+                return Ok(Some(code));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl OtpProvider for ImapProvider {
+    fn kind(&self) -> &'static str {
+        "imap"
+    }
+
+    async fn fetch(&self, since: DateTime<Utc>, timeout: Duration) -> Result<Option<String>> {
+        let mut session = ImapSession::connect(&self.config.host, self.config.port).await?;
+        session
+            .command(&format!(
+                "LOGIN {} {}",
+                quote(&self.config.username),
+                quote(&self.config.password)
+            ))
+            .await?;
+        session.command(&format!("EXAMINE {}", quote(&self.config.mailbox))).await?;
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let code = loop {
+            if let Some(code) = self.find_code(&mut session, since).await? {
+                break Some(code);
+            }
+
+            if tokio::time::Instant::now() + Duration::from_millis(POLL_INTERVAL_MS) > deadline {
+                break None;
+            }
+            tokio::time::sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+            // Lets the server report mail that arrived in the meantime
+            session.command("NOOP").await?;
+        };
+
+        if let Err(e) = session.command("LOGOUT").await {
+            tracing::debug!("IMAP logout failed: {}", e);
+        }
+        Ok(code)
+    }
+}
+
+/// Untagged response lines, with literal data collected separately
+struct ImapResponse {
+    lines: Vec<String>,
+    literals: Vec<Vec<u8>>,
+}
+
+/// Just enough IMAP4rev1 for LOGIN / EXAMINE / SEARCH / FETCH
+struct ImapSession {
+    stream: BufReader<TlsStream<TcpStream>>,
+    next_tag: u32,
+}
+
+impl ImapSession {
+    async fn connect(host: &str, port: u16) -> Result<Self> {
+        let tcp = TcpStream::connect((host, port)).await?;
+        let connector = TlsConnector::from(native_tls::TlsConnector::new()?);
+        let tls = connector.connect(host, tcp).await?;
+
+        let mut session = Self {
+            stream: BufReader::new(tls),
+            next_tag: 1,
+        };
+
+        let greeting = session.read_line().await?;
+        anyhow::ensure!(greeting.starts_with("* OK"), "Unexpected IMAP greeting: {}", greeting.trim());
+        Ok(session)
+    }
+
+    async fn read_line(&mut self) -> Result<String> {
+        let mut line = Vec::new();
+        if self.stream.read_until(b'\n', &mut line).await? == 0 {
+            anyhow::bail!("IMAP server closed the connection");
+        }
+        Ok(String::from_utf8_lossy(&line).into_owned())
+    }
+
+    /// Send a command and read everything up to its tagged completion
+    async fn command(&mut self, command: &str) -> Result<ImapResponse> {
+        let tag = format!("a{}", self.next_tag);
+        self.next_tag += 1;
+
+        let stream = self.stream.get_mut();
+        stream.write_all(format!("{} {}\r\n", tag, command).as_bytes()).await?;
+        stream.flush().await?;
+
+        let mut response = ImapResponse {
+            lines: Vec::new(),
+            literals: Vec::new(),
+        };
+
+        loop {
+            let line = self.read_line().await?;
+
+            if let Some(status) = line.strip_prefix(&format!("{} ", tag)) {
+                if status.starts_with("OK") {
+                    return Ok(response);
+                }
+                // Only the command verb is reported: LOGIN carries the password
+                let verb = command.split_whitespace().next().unwrap_or_default();
+                anyhow::bail!("IMAP {} failed: {}", verb, status.trim());
+            }
+
+            if let Some(length) = literal_length(&line) {
+                let mut literal = vec![0; length];
+                self.stream.read_exact(&mut literal).await?;
+                response.literals.push(literal);
+            }
+            response.lines.push(line);
+        }
+    }
+}
+
+/// IMAP quoted string
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Length of the literal announced at the end of a response line, e.g. `{42}`
+fn literal_length(line: &str) -> Option<usize> {
+    let line = line.trim_end();
+    let start = line.strip_suffix('}')?.rfind('{')?;
+    line[start + 1..line.len() - 1].parse().ok()
+}
+
+/// INTERNALDATE of a FETCH response line, e.g. `INTERNALDATE " 7-Oct-2026 09:15:02 +0000"`
+fn internal_date(line: &str) -> Option<DateTime<Utc>> {
+    let rest = &line[line.find("INTERNALDATE \"")? + "INTERNALDATE \"".len()..];
+    let value = &rest[..rest.find('"')?];
+    DateTime::parse_from_str(value.trim(), "%d-%b-%Y %H:%M:%S %z")
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// Readable text of a message's subject header and body
+///
+/// Undoes quoted-printable encoding and drops HTML markup, style blocks and
+/// MIME part headers, whose hex colors and boundaries would look like codes.
+fn email_text(literals: &[Vec<u8>]) -> String {
+    let raw = literals
+        .iter()
+        .map(|literal| String::from_utf8_lossy(literal).into_owned())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // Quoted-printable: soft line breaks and =XX escapes
+    let raw = raw.replace("=\r\n", "").replace("=\n", "");
+    let mut decoded = Vec::with_capacity(raw.len());
+    let bytes = raw.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = (bytes[i] == b'=')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    let text = String::from_utf8_lossy(&decoded).into_owned();
+
+    let mut visible = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find('<') {
+        visible.push_str(&rest[..start]);
+        let tag = &rest[start..];
+        let skip_to = if tag.to_ascii_lowercase().starts_with("<style") {
+            tag.to_ascii_lowercase().find("</style>").map(|end| end + "</style>".len())
+        } else {
+            tag.find('>').map(|end| end + 1)
+        };
+        match skip_to {
+            Some(end) => {
+                visible.push(' ');
+                rest = &tag[end..];
+            }
+            None => {
+                rest = "";
+            }
+        }
+    }
+    visible.push_str(rest);
+
+    visible
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            let lower = line.to_ascii_lowercase();
+            let base64_like = line.len() >= 60 && !line.contains(' ');
+            !line.starts_with("--") && !lower.starts_with("content-") && !base64_like
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_parsing() {
+        assert_eq!(literal_length("* 3 FETCH (UID 4821 BODY[TEXT] {342}\r\n"), Some(342));
+        assert_eq!(literal_length("* OK [UIDVALIDITY 3857529045] UIDs valid\r\n"), None);
+
+        let date = internal_date("* 3 FETCH (UID 4821 INTERNALDATE \" 7-Oct-2026 09:15:02 +0200\" BODY[TEXT] {1}")
+            .unwrap();
+        assert_eq!(date.to_rfc3339(), "2026-10-07T07:15:02+00:00");
+    }
+
+    #[test]
+    fn test_email_text_skips_markup() {
+        let subject = b"Subject: Sign-in request\r\n\r\n".to_vec();
+        let body = b"------=_Part_123456_7890\r\nContent-Type: text/html; charset=3Dutf-8\r\n\r\n\
+            <style>.x { color: #333333 }</style><p style=3D\"color:#000000\">Your code is <b>4829=\r\n13</b></p>"
+            .to_vec();
+
+        let text = email_text(&[subject, body]);
+        assert!(!text.contains("Content-Type"));
+        assert_eq!(extract_code(&text, None).as_deref(), Some("482913"));
+    }
+}
@@ -0,0 +1,9 @@
+pub mod imap;
+pub mod provider;
+pub mod sms;
+pub mod totp;
+
+pub use imap::*;
+pub use provider::*;
+pub use sms::*;
+pub use totp::*;
@@ -0,0 +1,203 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{ImapConfig, ImapProvider, SmsInbox, TotpProvider, TwilioSmsProvider};
+
+/// A source of one-time codes for two-factor login flows
+#[async_trait]
+pub trait OtpProvider: Send + Sync {
+    /// Short provider type name, e.g. "totp"
+    fn kind(&self) -> &'static str;
+
+    /// A code issued at or after `since`, or `None` if none arrived within `timeout`
+    async fn fetch(&self, since: DateTime<Utc>, timeout: Duration) -> Result<Option<String>>;
+}
+
+fn default_totp_digits() -> u32 {
+    6
+}
+
+fn default_totp_period() -> u64 {
+    30
+}
+
+/// One entry of OTP_PROVIDERS
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum ProviderConfig {
+    Totp {
+        /// Base32 secret from the enrollment QR code
+        secret: String,
+        #[serde(default = "default_totp_digits")]
+        digits: u32,
+        #[serde(default = "default_totp_period")]
+        period: u64,
+    },
+    Imap(ImapConfig),
+    Twilio {
+        /// Number the codes are sent to; any number when absent
+        #[serde(default)]
+        to: Option<String>,
+        /// Code length; any run of 4-8 digits when absent
+        #[serde(default)]
+        digits: Option<usize>,
+    },
+}
+
+/// Named OTP providers, configured by the operator
+///
+/// Remembers when each provider last returned a code, so the next request
+/// only accepts codes that arrived after it.
+pub struct OtpProviders {
+    providers: HashMap<String, Arc<dyn OtpProvider>>,
+    sms_inbox: Arc<SmsInbox>,
+    last_fetch: DashMap<String, DateTime<Utc>>,
+    /// How far back a first request looks for a code (OTP_LOOKBACK_SECONDS)
+    lookback_seconds: i64,
+}
+
+impl Default for OtpProviders {
+    fn default() -> Self {
+        Self {
+            providers: HashMap::new(),
+            sms_inbox: Arc::new(SmsInbox::default()),
+            last_fetch: DashMap::new(),
+            lookback_seconds: 120,
+        }
+    }
+}
+
+impl OtpProviders {
+    /// Configure from environment:
+    /// - OTP_PROVIDERS: JSON map of name -> {"kind": "totp" | "imap" | "twilio", ...}
+    /// - OTP_LOOKBACK_SECONDS: how old a code may be on the first request (default: 120)
+    pub fn from_env() -> Self {
+        let mut otp = Self::default();
+
+        if let Ok(raw) = std::env::var("OTP_PROVIDERS") {
+            otp.load(&raw);
+        }
+
+        if let Some(seconds) = std::env::var("OTP_LOOKBACK_SECONDS").ok().and_then(|s| s.parse().ok()) {
+            otp.lookback_seconds = seconds;
+        }
+
+        if !otp.providers.is_empty() {
+            tracing::info!("Configured {} OTP provider(s)", otp.providers.len());
+        }
+        otp
+    }
+
+    fn load(&mut self, raw: &str) {
+        // Secrets may be quoted in serde errors, so only the entry name is logged
+        let Ok(entries) = serde_json::from_str::<HashMap<String, serde_json::Value>>(raw) else {
+            tracing::warn!("Invalid OTP_PROVIDERS JSON, ignoring");
+            return;
+        };
+
+        for (name, value) in entries {
+            let provider: Result<Arc<dyn OtpProvider>> = match serde_json::from_value(value) {
+                Ok(ProviderConfig::Totp { secret, digits, period }) => {
+                    TotpProvider::new(&secret, digits, period).map(|p| Arc::new(p) as Arc<dyn OtpProvider>)
+                }
+                Ok(ProviderConfig::Imap(config)) => Ok(Arc::new(ImapProvider::new(config))),
+                Ok(ProviderConfig::Twilio { to, digits }) => Ok(Arc::new(TwilioSmsProvider::new(
+                    to,
+                    digits,
+                    Arc::clone(&self.sms_inbox),
+                ))),
+                Err(_) => Err(anyhow::anyhow!("unknown kind or missing fields")),
+            };
+
+            match provider {
+                Ok(provider) => {
+                    self.providers.insert(name, provider);
+                }
+                Err(e) => tracing::warn!("Invalid OTP provider '{}', ignoring: {}", name, e),
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn OtpProvider>> {
+        self.providers.get(name)
+    }
+
+    /// Provider names, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.providers.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Inbox fed by the Twilio SMS webhook
+    pub fn sms_inbox(&self) -> Arc<SmsInbox> {
+        Arc::clone(&self.sms_inbox)
+    }
+
+    /// Next unused code from a provider; `Ok(None)` on timeout
+    pub async fn fetch(&self, name: &str, timeout: Duration) -> Result<Option<String>> {
+        let provider = self
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown OTP provider '{}'", name))?;
+
+        let requested_at = Utc::now();
+        let lookback = requested_at - chrono::Duration::seconds(self.lookback_seconds);
+        let since = self
+            .last_fetch
+            .get(name)
+            .map(|last| (*last).max(lookback))
+            .unwrap_or(lookback);
+
+        let code = provider.fetch(since, timeout).await?;
+        if code.is_some() {
+            self.last_fetch.insert(name.to_string(), requested_at);
+        }
+        Ok(code)
+    }
+}
+
+/// First standalone run of digits that looks like a one-time code
+///
+/// With `digits` only runs of exactly that length count, otherwise runs of
+/// 4 to 8 digits. Digits inside longer numbers (phone numbers, dates) are skipped.
+pub fn extract_code(text: &str, digits: Option<usize>) -> Option<String> {
+    let accepts = |len: usize| digits.map_or((4..=8).contains(&len), |d| len == d);
+
+    text.split(|c: char| !c.is_ascii_digit())
+        .find(|run| !run.is_empty() && accepts(run.len()))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_code() {
+        assert_eq!(extract_code("Your code is 482913.", None).as_deref(), Some("482913"));
+        assert_eq!(extract_code("Call +15550001111, code 1234", None).as_deref(), Some("1234"));
+        assert_eq!(extract_code("Order 12345678 code 482913", Some(6)).as_deref(), Some("482913"));
+        assert_eq!(extract_code("No code here", None), None);
+    }
+
+    #[test]
+    fn test_load_providers() {
+        let mut otp = OtpProviders::default();
+        otp.load(
+            r#"{
+                "authenticator": {"kind": "totp", "secret": "GEZDGNBVGY3TQOJQ"},
+                "sms": {"kind": "twilio", "to": "+15552223333"},
+                "broken": {"kind": "carrier_pigeon"}
+            }"#,
+        );
+
+        assert_eq!(otp.names(), vec!["authenticator", "sms"]);
+        assert_eq!(otp.get("sms").unwrap().kind(), "twilio");
+    }
+}
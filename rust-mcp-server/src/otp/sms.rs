@@ -0,0 +1,172 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Notify, RwLock};
+
+use super::{extract_code, OtpProvider};
+
+/// Messages kept in the inbox; older ones are dropped
+const MAX_MESSAGES: usize = 50;
+
+/// An SMS delivered by the Twilio webhook
+#[derive(Debug, Clone)]
+pub struct SmsMessage {
+    pub from: String,
+    pub to: String,
+    pub body: String,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Recent SMS messages pushed by Twilio's incoming-message webhook
+#[derive(Default)]
+pub struct SmsInbox {
+    messages: RwLock<VecDeque<SmsMessage>>,
+    arrived: Notify,
+}
+
+impl SmsInbox {
+    pub async fn push(&self, message: SmsMessage) {
+        tracing::debug!("SMS received from {} for {}", message.from, message.to);
+
+        let mut messages = self.messages.write().await;
+        if messages.len() >= MAX_MESSAGES {
+            messages.pop_front();
+        }
+        messages.push_back(message);
+        drop(messages);
+
+        self.arrived.notify_waiters();
+    }
+
+    /// Wait for a message to `to` (any number when `None`) received at or after `since`
+    pub async fn wait_for(
+        &self,
+        to: Option<&str>,
+        since: DateTime<Utc>,
+        timeout: Duration,
+    ) -> Option<SmsMessage> {
+        let wait = async {
+            loop {
+                // Register interest before checking, so a message in between is not missed
+                let arrived = self.arrived.notified();
+
+                {
+                    let messages = self.messages.read().await;
+                    let found = messages
+                        .iter()
+                        .find(|m| m.received_at >= since && to.is_none_or(|to| same_number(to, &m.to)));
+                    if let Some(message) = found {
+                        return message.clone();
+                    }
+                }
+
+                arrived.await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait).await.ok()
+    }
+}
+
+fn same_number(a: &str, b: &str) -> bool {
+    let digits = |s: &str| s.chars().filter(char::is_ascii_digit).collect::<String>();
+    digits(a) == digits(b)
+}
+
+/// Codes from SMS sent to a Twilio number
+pub struct TwilioSmsProvider {
+    /// Twilio number the codes are sent to; any number when `None`
+    to: Option<String>,
+    digits: Option<usize>,
+    inbox: Arc<SmsInbox>,
+}
+
+impl TwilioSmsProvider {
+    pub fn new(to: Option<String>, digits: Option<usize>, inbox: Arc<SmsInbox>) -> Self {
+        Self { to, digits, inbox }
+    }
+}
+
+#[async_trait]
+impl OtpProvider for TwilioSmsProvider {
+    fn kind(&self) -> &'static str {
+        "twilio"
+    }
+
+    async fn fetch(&self, since: DateTime<Utc>, timeout: Duration) -> Result<Option<String>> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut since = since;
+
+        // Oldest first, skipping messages without a code (e.g. marketing texts)
+        while let Some(message) = self
+            .inbox
+            .wait_for(
+                self.to.as_deref(),
+                since,
+                deadline.saturating_duration_since(tokio::time::Instant::now()),
+            )
+            .await
+        {
+            if let Some(code) = extract_code(&message.body, self.digits) {
+                return Ok(Some(code));
+            }
+            since = message.received_at + chrono::Duration::microseconds(1);
+        }
+
+        Ok(None)
+    }
+}
+
+/// Check Twilio's X-Twilio-Signature for a form-encoded webhook request
+///
+/// The signature is base64(HMAC-SHA1(auth token, URL + each POST parameter
+/// name and value, sorted by name)), where URL is exactly what Twilio called.
+pub fn verify_twilio_signature(
+    auth_token: &str,
+    url: &str,
+    params: &BTreeMap<String, String>,
+    signature: &str,
+) -> bool {
+    let mut payload = url.to_string();
+    for (key, value) in params {
+        payload.push_str(key);
+        payload.push_str(value);
+    }
+
+    let mut mac = Hmac::<Sha1>::new_from_slice(auth_token.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(payload.as_bytes());
+
+    match base64::engine::general_purpose::STANDARD.decode(signature) {
+        Ok(expected) => mac.verify_slice(&expected).is_ok(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_twilio_signature() {
+        let params: BTreeMap<String, String> = [
+            ("AccountSid", "AC123"),
+            ("Body", "Your code is 482913"),
+            ("From", "+15550001111"),
+            ("To", "+15552223333"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let url = "https://mcp.example.com/v1/otp/twilio/sms";
+        assert!(verify_twilio_signature("12345", url, &params, "91PkwvHX67wW18xKzjk/4AfRafI="));
+        assert!(!verify_twilio_signature("wrong", url, &params, "91PkwvHX67wW18xKzjk/4AfRafI="));
+        assert!(!verify_twilio_signature("12345", url, &params, "not base64"));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod scrubber;
+
+pub use scrubber::*;
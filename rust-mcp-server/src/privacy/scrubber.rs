@@ -0,0 +1,252 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use crate::models::{ActionResponse, ElementNeighborhood, UIContext};
+use crate::notifications::DEFAULT_TENANT;
+
+static EMAIL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}").unwrap());
+
+/// 13-19 digits, optionally grouped by spaces or dashes
+static CARD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b\d(?:[ -]?\d){12,18}\b").unwrap());
+
+/// Digit runs with separators, an optional "+" country code and optional parentheses
+static PHONE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?:\+|\(|\b)\d[\d ().-]{5,}\d\b").unwrap());
+
+/// Personal data the scrubber can remove
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    Email,
+    Phone,
+    Card,
+}
+
+impl PiiKind {
+    /// Text put in place of a match
+    pub fn placeholder(&self) -> &'static str {
+        match self {
+            Self::Email => "[email]",
+            Self::Phone => "[phone]",
+            Self::Card => "[card]",
+        }
+    }
+}
+
+/// Replaces email addresses, phone numbers and card numbers in page text with placeholders
+///
+/// Card numbers must pass the Luhn check. Phone numbers need 7-15 digits and
+/// either a "+", parentheses or separators with at least 10 digits, so dates,
+/// prices and order numbers are left alone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiiScrubber {
+    kinds: Vec<PiiKind>,
+}
+
+impl PiiScrubber {
+    pub fn new(kinds: Vec<PiiKind>) -> Self {
+        Self { kinds }
+    }
+
+    pub fn scrub(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        // Cards first: their digit groups would otherwise pass for phone numbers
+        for kind in [PiiKind::Card, PiiKind::Email, PiiKind::Phone] {
+            if !self.kinds.contains(&kind) {
+                continue;
+            }
+            text = match kind {
+                PiiKind::Email => EMAIL.replace_all(&text, kind.placeholder()).into_owned(),
+                PiiKind::Card => replace_checked(&CARD, &text, kind.placeholder(), is_card_number),
+                PiiKind::Phone => replace_checked(&PHONE, &text, kind.placeholder(), is_phone_number),
+            };
+        }
+        text
+    }
+
+    fn scrub_option(&self, text: &mut Option<String>) {
+        if let Some(text) = text {
+            *text = self.scrub(text);
+        }
+    }
+
+    /// Scrub everything of the context that shows page content
+    pub fn scrub_context(&self, context: &mut UIContext) {
+        context.url = self.scrub(&context.url);
+        context.title = self.scrub(&context.title);
+
+        for element in &mut context.elements {
+            element.display = self.scrub(&element.display);
+            self.scrub_option(&mut element.selector.name);
+            self.scrub_option(&mut element.value);
+            self.scrub_option(&mut element.selector.description);
+            if let Some(parsed) = &mut element.parsed {
+                parsed.text = self.scrub(&parsed.text);
+            }
+        }
+        for entry in &mut context.navigation_history {
+            entry.url = self.scrub(&entry.url);
+            self.scrub_option(&mut entry.title);
+        }
+        for overlay in &mut context.closed_overlays {
+            overlay.label = self.scrub(&overlay.label);
+        }
+        if let Some(focused) = &mut context.focused {
+            self.scrub_option(&mut focused.name);
+        }
+    }
+
+    /// Scrub the feedback of an action, including every string in its details
+    pub fn scrub_response(&self, response: &mut ActionResponse) {
+        self.scrub_option(&mut response.reason);
+        self.scrub_option(&mut response.suggestion);
+        if let Some(details) = &mut response.details {
+            self.scrub_json(details);
+        }
+    }
+
+    pub fn scrub_neighborhood(&self, neighborhood: &mut ElementNeighborhood) {
+        self.scrub_option(&mut neighborhood.name);
+        self.scrub_option(&mut neighborhood.nearby_text);
+        for heading in &mut neighborhood.headings {
+            *heading = self.scrub(heading);
+        }
+        for neighbor in neighborhood.ancestors.iter_mut().chain(&mut neighborhood.siblings) {
+            self.scrub_option(&mut neighbor.name);
+        }
+    }
+
+    fn scrub_json(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(text) => *text = self.scrub(text),
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|item| self.scrub_json(item)),
+            serde_json::Value::Object(fields) => fields.values_mut().for_each(|field| self.scrub_json(field)),
+            _ => {}
+        }
+    }
+}
+
+/// Replace the matches of `pattern` that `check` accepts
+fn replace_checked(pattern: &Regex, text: &str, placeholder: &str, check: fn(&str) -> bool) -> String {
+    pattern
+        .replace_all(text, |captures: &regex::Captures| {
+            let found = &captures[0];
+            if check(found) {
+                placeholder.to_string()
+            } else {
+                found.to_string()
+            }
+        })
+        .into_owned()
+}
+
+fn digits(text: &str) -> Vec<u32> {
+    text.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+/// Luhn checksum, as used by every major card network
+fn is_card_number(text: &str) -> bool {
+    let digits = digits(text);
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match i % 2 {
+            0 => d,
+            _ if d * 2 > 9 => d * 2 - 9,
+            _ => d * 2,
+        })
+        .sum();
+    (13..=19).contains(&digits.len()) && sum.is_multiple_of(10)
+}
+
+fn is_phone_number(text: &str) -> bool {
+    let count = digits(text).len();
+    let marked = text.starts_with('+') || text.contains('(');
+    let separated = text.contains([' ', '-', '.']);
+    (7..=15).contains(&count) && (marked || (separated && count >= 10))
+}
+
+/// PII scrubbing per tenant, for organizations that must keep personal data away from the LLM
+pub struct PiiScrubbing {
+    tenants: HashMap<String, Vec<PiiKind>>,
+}
+
+impl PiiScrubbing {
+    /// PII_SCRUBBING: JSON map of tenant -> kinds to scrub, "*" for every tenant without its own entry
+    ///
+    /// e.g. `{"*": ["email", "phone", "card"], "internal-qa": []}`. Scrubbing is off when unset.
+    pub fn from_env() -> Self {
+        let tenants = match std::env::var("PII_SCRUBBING") {
+            Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                tracing::warn!("Invalid PII_SCRUBBING, ignoring: {}", e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self { tenants }
+    }
+
+    /// Scrubber for a tenant (`None` for unauthenticated requests); `None` if nothing is scrubbed
+    pub fn for_tenant(&self, tenant: Option<&str>) -> Option<PiiScrubber> {
+        let kinds = tenant
+            .and_then(|tenant| self.tenants.get(tenant))
+            .or_else(|| self.tenants.get(DEFAULT_TENANT))?;
+        (!kinds.is_empty()).then(|| PiiScrubber::new(kinds.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all() -> PiiScrubber {
+        PiiScrubber::new(vec![PiiKind::Email, PiiKind::Phone, PiiKind::Card])
+    }
+
+    #[test]
+    fn test_scrub() {
+        let scrubber = all();
+        assert_eq!(
+            scrubber.scrub("Signed in as ann.lee+shop@mail.example.co.uk"),
+            "Signed in as [email]"
+        );
+        assert_eq!(scrubber.scrub("Card 4242 4242 4242 4242 expires 04/27"), "Card [card] expires 04/27");
+        assert_eq!(scrubber.scrub("Call +49 30 1234567 or (555) 123-4567"), "Call [phone] or [phone]");
+        assert_eq!(scrubber.scrub("Support: 555-123-4567."), "Support: [phone].");
+
+        // Look-alikes stay
+        assert_eq!(scrubber.scrub("Order 4242 4242 4242 4241"), "Order 4242 4242 4242 4241");
+        assert_eq!(scrubber.scrub("Placed 2025-04-03, order 1042"), "Placed 2025-04-03, order 1042");
+        assert_eq!(scrubber.scrub("Total: 1 299.00 EUR"), "Total: 1 299.00 EUR");
+
+        let emails_only = PiiScrubber::new(vec![PiiKind::Email]);
+        assert_eq!(emails_only.scrub("ann@example.com, +1 555 123 4567"), "[email], +1 555 123 4567");
+    }
+
+    #[test]
+    fn test_for_tenant() {
+        let scrubbing = PiiScrubbing {
+            tenants: HashMap::from([
+                (DEFAULT_TENANT.to_string(), vec![PiiKind::Email]),
+                ("acme".to_string(), vec![PiiKind::Email, PiiKind::Card]),
+                ("internal-qa".to_string(), vec![]),
+            ]),
+        };
+
+        assert_eq!(
+            scrubbing.for_tenant(Some("acme")),
+            Some(PiiScrubber::new(vec![PiiKind::Email, PiiKind::Card]))
+        );
+        assert_eq!(scrubbing.for_tenant(None), Some(PiiScrubber::new(vec![PiiKind::Email])));
+        assert_eq!(scrubbing.for_tenant(Some("internal-qa")), None);
+
+        let mut response = ActionResponse::success_with_details(serde_json::json!({
+            "matches": [{"text": "Contact ann@example.com"}]
+        }));
+        all().scrub_response(&mut response);
+        assert_eq!(response.details.unwrap()["matches"][0]["text"], "Contact [email]");
+    }
+}
@@ -0,0 +1,374 @@
+use serde::Deserialize;
+
+use crate::agent::report::describe_action;
+use crate::agent::trace::replayable_action;
+use crate::agent::MultiStepExecutionResult;
+use crate::models::{ActionRequest, Assertion, ScrollDirection, SemanticSelector, WebStorageArea};
+use crate::session::{placeholders, substitute};
+
+/// Language of an exported test script
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// Playwright Test in TypeScript
+    #[default]
+    Playwright,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Playwright => "application/typescript; charset=utf-8",
+        }
+    }
+
+    /// File name suffix, after the script's name
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Playwright => "spec.ts",
+        }
+    }
+}
+
+/// A runnable test script that performs `actions`
+pub fn export_script(format: ExportFormat, title: &str, actions: &[ActionRequest]) -> String {
+    match format {
+        ExportFormat::Playwright => playwright_script(title, actions),
+    }
+}
+
+/// The actions of a run that succeeded, in order, with element IDs resolved (see `replayable_action`)
+pub fn run_actions(result: &MultiStepExecutionResult) -> Vec<ActionRequest> {
+    (0..result.steps.len())
+        .filter(|&index| result.steps[index].action_result.success)
+        .map(|index| replayable_action(&result.steps, index))
+        .collect()
+}
+
+fn playwright_script(title: &str, actions: &[ActionRequest]) -> String {
+    let mut parameters: Vec<String> = Vec::new();
+    for action in actions {
+        let json = serde_json::to_string(action).unwrap_or_default();
+        for name in placeholders(&json) {
+            if !parameters.contains(&name) {
+                parameters.push(name);
+            }
+        }
+    }
+
+    let mut script = String::new();
+    script.push_str("// Exported by mcp-server. Steps a script can't reproduce are left as comments.\n");
+    script.push_str("import { test, expect } from '@playwright/test';\n\n");
+
+    if !parameters.is_empty() {
+        script.push_str("// Macro parameters, read from the environment\n");
+        script.push_str("const params: Record<string, string> = {\n");
+        for name in &parameters {
+            script.push_str(&format!(
+                "  {}: process.env.{} ?? '',\n",
+                json_string(name),
+                env_var_name(name)
+            ));
+        }
+        script.push_str("};\n\n");
+    }
+
+    script.push_str(&format!("test({}, async ({{ page }}) => {{\n", json_string(title)));
+    for action in actions {
+        let statement = playwright_statement(action)
+            .unwrap_or_else(|| format!("// Not exported: {}", describe_action(action)));
+        for line in statement.lines() {
+            script.push_str("  ");
+            script.push_str(line);
+            script.push('\n');
+        }
+    }
+    script.push_str("});\n");
+    script
+}
+
+/// Playwright code for one action; `None` for actions that depend on the server
+/// (vault credentials, one-time codes, test hooks) or on its tab and overlay bookkeeping
+fn playwright_statement(action: &ActionRequest) -> Option<String> {
+    let timeout = |timeout_ms: &Option<u64>| match timeout_ms {
+        Some(ms) => format!("{{ timeout: {} }}", ms),
+        None => String::new(),
+    };
+
+    let statement = match action {
+        ActionRequest::Click { selector } => format!("await {}.click();", locator(selector)?),
+        ActionRequest::Type { selector, text } => {
+            format!("await {}.fill({});", locator(selector)?, js_string(text))
+        }
+        ActionRequest::SelectOption { selector, value, label } => match (label, value) {
+            (Some(label), _) => format!(
+                "await {}.selectOption({{ label: {} }});",
+                locator(selector)?,
+                js_string(label)
+            ),
+            (None, Some(value)) => format!("await {}.selectOption({});", locator(selector)?, js_string(value)),
+            (None, None) => return None,
+        },
+        ActionRequest::SetChecked { selector, checked } => {
+            format!("await {}.setChecked({});", locator(selector)?, checked)
+        }
+        ActionRequest::Focus { selector } => format!("await {}.focus();", locator(selector)?),
+        ActionRequest::PressKey { key, modifiers } => {
+            let mut combination: Vec<&str> = modifiers.iter().map(|m| m.name()).collect();
+            combination.push(key);
+            format!("await page.keyboard.press({});", js_string(&combination.join("+")))
+        }
+        ActionRequest::Scroll { direction, amount, selector } => {
+            let amount = amount.unwrap_or(300) as i64;
+            let (x, y) = match direction {
+                ScrollDirection::Down => (0, amount),
+                ScrollDirection::Up => (0, -amount),
+                ScrollDirection::Right => (amount, 0),
+                ScrollDirection::Left => (-amount, 0),
+            };
+            match selector {
+                Some(selector) => format!(
+                    "await {}.evaluate((element) => element.scrollBy({}, {}));",
+                    locator(selector)?,
+                    x,
+                    y
+                ),
+                None => format!("await page.evaluate(() => window.scrollBy({}, {}));", x, y),
+            }
+        }
+        ActionRequest::WaitForElement { selector, timeout_ms } => {
+            format!("await {}.waitFor({});", locator(selector)?, timeout(timeout_ms))
+        }
+        ActionRequest::WaitForNavigation { timeout_ms } => match timeout_ms {
+            Some(_) => format!("await page.waitForLoadState('load', {});", timeout(timeout_ms)),
+            None => "await page.waitForLoadState('load');".to_string(),
+        },
+        ActionRequest::WaitForNetworkIdle { timeout_ms } => match timeout_ms {
+            Some(_) => format!("await page.waitForLoadState('networkidle', {});", timeout(timeout_ms)),
+            None => "await page.waitForLoadState('networkidle');".to_string(),
+        },
+        ActionRequest::WaitForUrl { pattern, timeout_ms } => match timeout_ms {
+            Some(_) => format!("await page.waitForURL({}, {});", glob_regex(pattern), timeout(timeout_ms)),
+            None => format!("await page.waitForURL({});", glob_regex(pattern)),
+        },
+        ActionRequest::WaitForText { text, timeout_ms } => format!(
+            "await expect(page.locator('body')).toContainText({}, {{ ignoreCase: true{} }});",
+            js_string(text),
+            timeout_ms.map(|ms| format!(", timeout: {}", ms)).unwrap_or_default()
+        ),
+        ActionRequest::Navigate { url } => format!("await page.goto({});", js_string(url)),
+        ActionRequest::GoBack => "await page.goBack();".to_string(),
+        ActionRequest::GoForward => "await page.goForward();".to_string(),
+        ActionRequest::Reload => "await page.reload();".to_string(),
+        ActionRequest::NewTab { url } => {
+            let mut statement = "page = await page.context().newPage();".to_string();
+            if let Some(url) = url {
+                statement.push_str(&format!("\nawait page.goto({});", js_string(url)));
+            }
+            statement
+        }
+        ActionRequest::FindText { query, scroll_to_first } => {
+            if *scroll_to_first == Some(false) {
+                return None;
+            }
+            format!(
+                "await page.getByText({}).first().scrollIntoViewIfNeeded();",
+                js_string(query)
+            )
+        }
+        ActionRequest::FillForm { fields, submit } => {
+            let mut lines: Vec<String> = fields
+                .iter()
+                .map(|(field, value)| {
+                    format!("await page.getByLabel({}).fill({});", js_string(field), js_string(value))
+                })
+                .collect();
+            if let (true, Some(field)) = (*submit, fields.keys().last()) {
+                lines.push(format!(
+                    "await page.getByLabel({}).evaluate((field) => field.form?.requestSubmit());",
+                    js_string(field)
+                ));
+            }
+            lines.join("\n")
+        }
+        ActionRequest::HandleDialog { accept, prompt_text } => match (accept, prompt_text) {
+            (true, Some(text)) => format!("page.once('dialog', (dialog) => dialog.accept({}));", js_string(text)),
+            (true, None) => "page.once('dialog', (dialog) => dialog.accept());".to_string(),
+            (false, _) => "page.once('dialog', (dialog) => dialog.dismiss());".to_string(),
+        },
+        ActionRequest::SetStorage { area, key, value } => {
+            let storage = match area {
+                WebStorageArea::Local => "localStorage",
+                WebStorageArea::Session => "sessionStorage",
+            };
+            match value {
+                Some(value) => format!(
+                    "await page.evaluate(([key, value]) => {}.setItem(key, value), [{}, {}]);",
+                    storage,
+                    js_string(key),
+                    js_string(value)
+                ),
+                None => format!(
+                    "await page.evaluate((key) => {}.removeItem(key), {});",
+                    storage,
+                    js_string(key)
+                ),
+            }
+        }
+        ActionRequest::Evaluate { script } => format!("await page.evaluate({});", js_string(script)),
+        ActionRequest::Assert { assertion } => assertion_statement(assertion)?,
+        ActionRequest::Screenshot { full_page, .. } => {
+            format!("await page.screenshot({{ fullPage: {} }});", full_page)
+        }
+        ActionRequest::SwitchTab { .. }
+        | ActionRequest::CloseTab { .. }
+        | ActionRequest::SetZoom { .. }
+        | ActionRequest::RestoreOverlay { .. }
+        | ActionRequest::WaitForDownload { .. }
+        | ActionRequest::Login { .. }
+        | ActionRequest::GetOtp { .. }
+        | ActionRequest::InvokeHook { .. }
+        | ActionRequest::GetStorage { .. } => return None,
+    };
+    Some(statement)
+}
+
+fn assertion_statement(assertion: &Assertion) -> Option<String> {
+    let statement = match assertion {
+        Assertion::ElementExists { selector } => {
+            format!("await expect({}.first()).toBeAttached();", locator(selector)?)
+        }
+        Assertion::ElementTextEquals { selector, text } => {
+            format!("await expect({}).toHaveText({});", locator(selector)?, js_string(text))
+        }
+        Assertion::ElementTextContains { selector, text } => {
+            format!("await expect({}).toContainText({});", locator(selector)?, js_string(text))
+        }
+        Assertion::UrlMatches { pattern } => format!("await expect(page).toHaveURL({});", glob_regex(pattern)),
+        Assertion::TitleContains { text } => format!(
+            "await expect(page).toHaveTitle(new RegExp({}));",
+            js_string(&escape_regex(text))
+        ),
+    };
+    Some(statement)
+}
+
+/// Playwright locator for a selector, inside its iframes; `None` when it only gives an element ID
+fn locator(selector: &SemanticSelector) -> Option<String> {
+    let mut scope = "page".to_string();
+    for index in &selector.frame {
+        scope.push_str(&format!(".frameLocator(':is(iframe, frame) >> nth={}')", index));
+    }
+
+    if !selector.role.is_empty() {
+        return Some(match &selector.name {
+            Some(name) => format!(
+                "{}.getByRole({}, {{ name: {}, exact: true }})",
+                scope,
+                js_string(&selector.role),
+                js_string(name)
+            ),
+            None => format!("{}.getByRole({})", scope, js_string(&selector.role)),
+        });
+    }
+    selector
+        .css_fallback
+        .as_ref()
+        .map(|css| format!("{}.locator({})", scope, js_string(css)))
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// A JavaScript string for `value`; a template literal reading `params` when it has `{{name}}` placeholders
+fn js_string(value: &str) -> String {
+    if placeholders(value).is_empty() {
+        return json_string(value);
+    }
+
+    let escaped = value.replace('\\', "\\\\").replace('`', "\\`").replace('$', "\\$");
+    let filled = substitute(&escaped, |name| Ok(format!("${{params[{}]}}", json_string(name))))
+        .unwrap_or(escaped);
+    format!("`{}`", filled)
+}
+
+/// `order.id` -> `ORDER_ID`
+fn env_var_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+/// Regex metacharacters escaped; braces are left alone so placeholders survive, and
+/// stay literal in a regex without the `u` flag
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]/".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// The server's URL globs (`*` for any characters, `?` for one, ignoring case) as a RegExp
+///
+/// Playwright's own globs don't let `*` cross a `/`, so they can't be used as they are.
+fn glob_regex(pattern: &str) -> String {
+    let source: String = pattern
+        .split('*')
+        .map(|part| part.split('?').map(escape_regex).collect::<Vec<_>>().join("."))
+        .collect::<Vec<_>>()
+        .join(".*");
+    format!("new RegExp({}, 'i')", js_string(&format!("^{}$", source)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actions(json: serde_json::Value) -> Vec<ActionRequest> {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_playwright_script() {
+        let actions = actions(serde_json::json!([
+            {"tool": "navigate", "url": "https://shop.example.com/orders/{{order_id}}"},
+            {"tool": "type", "role": "textbox", "name": "Note", "text": "Refund `now` for $5"},
+            {"tool": "click", "role": "button", "name": "Refund", "frame": [1]},
+            {"tool": "wait_for_url", "pattern": "*/orders/*?done=1"},
+            {"tool": "press_key", "key": "a", "modifiers": ["control"]},
+            {"tool": "login", "credential_name": "admin"},
+            {"tool": "assert", "check": "title_contains", "text": "Order (refunded)"}
+        ]));
+        let script = export_script(ExportFormat::Playwright, "refund_order", &actions);
+
+        assert!(script.contains("  \"order_id\": process.env.ORDER_ID ?? '',\n"));
+        assert!(script.contains("test(\"refund_order\", async ({ page }) => {\n"));
+        assert!(script.contains("  await page.goto(`https://shop.example.com/orders/${params[\"order_id\"]}`);\n"));
+        assert!(script.contains(
+            "  await page.getByRole(\"textbox\", { name: \"Note\", exact: true }).fill(\"Refund `now` for $5\");\n"
+        ));
+        assert!(script.contains(
+            "  await page.frameLocator(':is(iframe, frame) >> nth=1').getByRole(\"button\", { name: \"Refund\", exact: true }).click();\n"
+        ));
+        assert!(script.contains("  await page.waitForURL(new RegExp(\"^.*\\\\/orders\\\\/.*.done=1$\", 'i'));\n"));
+        assert!(script.contains("  await page.keyboard.press(\"Control+a\");\n"));
+        assert!(script.contains("  // Not exported: Log in as 'admin'\n"));
+        assert!(script.contains("  await expect(page).toHaveTitle(new RegExp(\"Order \\\\(refunded\\\\)\"));\n"));
+        assert!(script.ends_with("});\n"));
+    }
+
+    #[test]
+    fn test_js_string_escapes_template_literals() {
+        assert_eq!(js_string("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(
+            js_string("${not} `{{user}}` \\"),
+            "`\\${not} \\`${params[\"user\"]}\\` \\\\`"
+        );
+        assert_eq!(env_var_name("order.id"), "ORDER_ID");
+    }
+}
@@ -0,0 +1,7 @@
+pub mod export;
+pub mod recording;
+pub mod store;
+
+pub use export::*;
+pub use recording::*;
+pub use store::*;
@@ -0,0 +1,180 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::models::{ActionRequest, ActionResponse};
+use crate::session::{action_text_mut, placeholders};
+
+/// A sequence of actions recorded from a session, to be replayed against another one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Macro {
+    pub name: String,
+
+    /// Session the actions were recorded in
+    pub recorded_from: String,
+
+    /// User who recorded the macro; `None` when it was recorded without authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+
+    pub created_at: DateTime<Utc>,
+
+    /// Names of the `{{name}}` placeholders a replay has to fill
+    pub parameters: Vec<String>,
+
+    pub actions: Vec<ActionRequest>,
+}
+
+/// Overview of a stored macro, for listings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroOverview {
+    pub name: String,
+    pub recorded_from: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub parameters: Vec<String>,
+    pub action_count: usize,
+}
+
+impl Macro {
+    pub fn overview(&self) -> MacroOverview {
+        MacroOverview {
+            name: self.name.clone(),
+            recorded_from: self.recorded_from.clone(),
+            owner: self.owner.clone(),
+            created_at: self.created_at,
+            parameters: self.parameters.clone(),
+            action_count: self.actions.len(),
+        }
+    }
+
+    /// Whether `user` (`None` without authentication) may see the macro: only its owner may
+    pub fn visible_to(&self, user: Option<&str>) -> bool {
+        self.owner.as_deref() == user
+    }
+}
+
+/// A recording in progress: the actions a session executed successfully since it started
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub name: String,
+    actions: Vec<ActionRequest>,
+    /// Secret fields typed into so far; each becomes a `{{secret_N}}` parameter
+    secrets: usize,
+}
+
+impl Recording {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            actions: Vec::new(),
+            secrets: 0,
+        }
+    }
+
+    /// Keep an executed action if it succeeded
+    ///
+    /// Failed attempts are left out, so an agent run's retries don't end up in
+    /// the macro. Element IDs only mean something in the session's latest
+    /// context, so targets are kept by role and name. Text typed into password
+    /// fields is replaced by a placeholder rather than stored.
+    pub fn record(&mut self, action: &ActionRequest, response: &ActionResponse) {
+        if !response.success {
+            return;
+        }
+
+        let mut action = action.clone();
+        if let Some(selector) = action.selector_mut() {
+            if !selector.role.is_empty() {
+                selector.element_id = None;
+            }
+        }
+        if let (ActionRequest::Type { text, .. }, true) = (&mut action, response.is_secret()) {
+            self.secrets += 1;
+            *text = format!("{{{{secret_{}}}}}", self.secrets);
+        }
+        self.actions.push(action);
+    }
+
+    /// Turn the recording into a macro, replacing each value of `parameters` by its `{{name}}` placeholder
+    ///
+    /// Longer values are replaced first, so a value that contains another one
+    /// keeps its own placeholder.
+    pub fn finish(self, session_id: &str, parameters: &BTreeMap<String, String>) -> Macro {
+        let mut values: Vec<(&String, &String)> = parameters.iter().filter(|(_, value)| !value.is_empty()).collect();
+        values.sort_by_key(|(_, value)| std::cmp::Reverse(value.len()));
+
+        let mut actions = self.actions;
+        let mut names: Vec<String> = Vec::new();
+        for action in &mut actions {
+            let Some(text) = action_text_mut(action) else {
+                continue;
+            };
+            for (name, value) in &values {
+                *text = text.replace(value.as_str(), &format!("{{{{{}}}}}", name));
+            }
+            for name in placeholders(text) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+
+        Macro {
+            name: self.name,
+            recorded_from: session_id.to_string(),
+            owner: None,
+            created_at: Utc::now(),
+            parameters: names,
+            actions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(json: serde_json::Value) -> ActionRequest {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_recording_becomes_a_parameterized_macro() {
+        let mut recording = Recording::new("checkout");
+        recording.record(
+            &action(serde_json::json!({"tool": "navigate", "url": "https://shop.example.com/orders/1042"})),
+            &ActionResponse::success(),
+        );
+        // A failed attempt the agent retried
+        recording.record(
+            &action(serde_json::json!({"tool": "click", "role": "button", "name": "Pay"})),
+            &ActionResponse::error_with_suggestion(crate::models::ErrorCode::ElementNotFound, "", ""),
+        );
+        recording.record(
+            &action(serde_json::json!({"tool": "click", "role": "button", "name": "Pay now", "element_id": 7})),
+            &ActionResponse::success(),
+        );
+        recording.record(
+            &action(serde_json::json!({"tool": "type", "role": "textbox", "name": "Password", "text": "hunter2"})),
+            &ActionResponse::success_with_details(serde_json::json!({"verified": true, "secret": true})),
+        );
+        recording.record(
+            &action(serde_json::json!({"tool": "wait_for_text", "text": "Order 1042 paid"})),
+            &ActionResponse::success(),
+        );
+
+        let parameters = BTreeMap::from([("order_id".to_string(), "1042".to_string())]);
+        let recorded = recording.finish("session-1", &parameters);
+        assert_eq!(recorded.actions.len(), 4);
+        assert_eq!(recorded.parameters, vec!["order_id", "secret_1"]);
+
+        let json = serde_json::to_value(&recorded.actions).unwrap();
+        assert_eq!(json[0]["url"], "https://shop.example.com/orders/{{order_id}}");
+        assert!(json[1].get("element_id").is_none());
+        assert_eq!(json[1]["name"], "Pay now");
+        assert_eq!(json[2]["text"], "{{secret_1}}");
+        assert_eq!(json[3]["text"], "Order {{order_id}} paid");
+    }
+}
@@ -0,0 +1,89 @@
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+
+use super::{Macro, MacroOverview};
+
+/// In-memory store of recorded macros, keyed by name
+///
+/// Names are shared by all users; a name belongs to whoever stored a macro under it first.
+pub struct MacroStore {
+    macros: DashMap<String, Macro>,
+}
+
+impl MacroStore {
+    pub fn new() -> Self {
+        Self {
+            macros: DashMap::new(),
+        }
+    }
+
+    /// Store a macro, replacing an earlier one of the same name and owner
+    ///
+    /// Returns `false`, and keeps the earlier macro, when the name belongs to someone else.
+    pub fn insert(&self, recorded: Macro) -> bool {
+        match self.macros.entry(recorded.name.clone()) {
+            Entry::Occupied(existing) if existing.get().owner != recorded.owner => false,
+            Entry::Occupied(mut existing) => {
+                existing.insert(recorded);
+                true
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(recorded);
+                true
+            }
+        }
+    }
+
+    /// Whether the name is taken by a macro of someone other than `user`
+    pub fn taken_by_other(&self, name: &str, user: Option<&str>) -> bool {
+        self.macros.get(name).is_some_and(|existing| !existing.visible_to(user))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Macro> {
+        self.macros.get(name).map(|entry| entry.clone())
+    }
+
+    pub fn remove(&self, name: &str) -> Option<Macro> {
+        self.macros.remove(name).map(|(_, recorded)| recorded)
+    }
+
+    /// Overviews of the stored macros, by name
+    pub fn overviews(&self) -> Vec<MacroOverview> {
+        let mut overviews: Vec<MacroOverview> = self.macros.iter().map(|entry| entry.overview()).collect();
+        overviews.sort_by(|a, b| a.name.cmp(&b.name));
+        overviews
+    }
+}
+
+impl Default for MacroStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recorder::Recording;
+    use std::collections::BTreeMap;
+
+    fn recorded(owner: Option<&str>) -> Macro {
+        let mut recorded = Recording::new("checkout").finish("session-1", &BTreeMap::new());
+        recorded.owner = owner.map(str::to_string);
+        recorded
+    }
+
+    #[test]
+    fn test_names_belong_to_their_first_owner() {
+        let store = MacroStore::new();
+        assert!(store.insert(recorded(Some("alice"))));
+        assert!(store.taken_by_other("checkout", Some("bob")));
+        assert!(!store.taken_by_other("checkout", Some("alice")));
+
+        // Someone else can't replace it; its owner can
+        assert!(!store.insert(recorded(Some("bob"))));
+        assert!(!store.insert(recorded(None)));
+        assert_eq!(store.get("checkout").unwrap().owner.as_deref(), Some("alice"));
+        assert!(store.insert(recorded(Some("alice"))));
+    }
+}
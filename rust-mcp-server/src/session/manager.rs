@@ -1,24 +1,188 @@
 use anyhow::{Context as AnyhowContext, Result};
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use std::sync::Arc;
 
-use crate::browser::BrowserAutomation;
-use crate::models::Session;
+use crate::browser::{
+    AutomationMarker, BrowserAutomation, ConsentPolicy, Fingerprint, FingerprintPresets, HygieneStats,
+    InteractionHeatmap, PageHygiene, RequestRouting,
+};
+use crate::credentials::{CredentialVault, HttpAuthCredentials};
+use crate::hooks::HookRegistry;
+use crate::otp::OtpProviders;
+use crate::tunnel::TunnelRegistry;
+use crate::models::{DialogPolicy, PlaywrightStorageState, Session, StorageState, DEFAULT_LEASE_SECONDS};
+
+use super::presets::{SessionPreset, SessionPresets};
+use super::vars::SessionVars;
+use super::warm_pool::{WarmPool, WarmPoolStatus};
 
 /// Session manager - manages browser sessions
 pub struct SessionManager {
     sessions: Arc<DashMap<String, SessionData>>,
+
+    /// Lease granted on creation and renewed by activity (SESSION_LEASE_SECONDS)
+    lease_seconds: i64,
+
+    /// Upper bound for explicit keepalive extensions (SESSION_MAX_LEASE_SECONDS)
+    max_lease_seconds: i64,
+
+    /// Soft-deleted sessions that can still be restored
+    trash: Arc<DashMap<String, DeletedSession>>,
+
+    /// How long deleted sessions stay restorable (SESSION_TRASH_SECONDS)
+    trash_seconds: i64,
+
+    /// Services attached to every session's browser
+    services: BrowserServices,
+
+    /// Default banner/header marking pages as automated
+    marker: AutomationMarker,
+
+    /// Pre-launched browsers new sessions can claim (WARM_POOL)
+    warm_pool: Arc<WarmPool>,
+
+    /// Named bundles of session rules (SESSION_PRESETS)
+    presets: Arc<SessionPresets>,
+
+    /// Named browser identities (built-ins and FINGERPRINT_PRESETS)
+    fingerprints: Arc<FingerprintPresets>,
+}
+
+/// Choices made when creating a session, beyond URL and viewport
+#[derive(Debug, Clone, Default)]
+pub struct SessionOptions {
+    /// Show the automation banner; the preset's or server default when `None`
+    pub automation_banner: Option<bool>,
+    pub keyboard_only: bool,
+    /// Name of a preset from SESSION_PRESETS
+    pub preset: Option<String>,
+    /// Name of a fingerprint preset; the session preset's when `None`
+    pub fingerprint: Option<String>,
+    /// How to answer JavaScript dialogs; the preset's when `None`
+    pub dialogs: Option<DialogPolicy>,
+    /// Cookies and localStorage to start with, e.g. exported from a logged-in session
+    pub storage_state: Option<PlaywrightStorageState>,
+}
+
+/// Shared services handed to every browser the manager launches
+#[derive(Clone)]
+struct BrowserServices {
+    /// Site credentials for the `login` action
+    credentials: Arc<CredentialVault>,
+
+    /// Answers to HTTP authentication challenges by origin, and the open tunnels
+    routing: RequestRouting,
+
+    /// One-time code sources for the `get_otp` action
+    otp: Arc<OtpProviders>,
+
+    /// App test hooks for the `invoke_hook` action
+    hooks: Arc<HookRegistry>,
+
+    /// Stamped-attribute cleanup counters of all sessions
+    hygiene: Arc<PageHygiene>,
+
+    /// Element interaction counts of all sessions
+    heatmap: Arc<InteractionHeatmap>,
+}
+
+/// Per-session choices a browser is launched with
+struct LaunchSettings<'a> {
+    marker: &'a AutomationMarker,
+    keyboard_only: bool,
+    preset: Option<&'a SessionPreset>,
+    fingerprint: Option<&'a Fingerprint>,
+}
+
+impl BrowserServices {
+    async fn launch(
+        &self,
+        url: &str,
+        viewport_width: u32,
+        viewport_height: u32,
+        settings: LaunchSettings<'_>,
+    ) -> Result<BrowserAutomation> {
+        let LaunchSettings { marker, keyboard_only, preset, fingerprint } = settings;
+        let policy = preset.map(SessionPreset::policy).unwrap_or_default();
+        let credentials = match preset.and_then(|preset| preset.credentials.as_deref()) {
+            Some(names) => Arc::new(self.credentials.only(names)),
+            None => Arc::clone(&self.credentials),
+        };
+
+        let routing = self.routing.clone();
+        Ok(BrowserAutomation::new(url, viewport_width, viewport_height, marker, routing, policy, fingerprint)
+            .await?
+            .with_credentials(credentials)
+            .with_otp_providers(Arc::clone(&self.otp))
+            .with_hooks(Arc::clone(&self.hooks))
+            .with_page_hygiene(Arc::clone(&self.hygiene))
+            .with_interaction_heatmap(Arc::clone(&self.heatmap))
+            .with_keyboard_only(keyboard_only))
+    }
+}
+
+/// A soft-deleted session: browser closed, metadata and storage state kept
+#[derive(Debug, Clone)]
+pub struct DeletedSession {
+    pub session: Session,
+    pub storage_state: Option<StorageState>,
+    pub vars: SessionVars,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl DeletedSession {
+    fn restorable_until(&self, trash_seconds: i64) -> DateTime<Utc> {
+        self.deleted_at + chrono::Duration::seconds(trash_seconds)
+    }
 }
 
 pub struct SessionData {
     pub session: Session,
     pub browser: Arc<BrowserAutomation>,
+    /// Values for `{{name}}` placeholders in tasks and actions
+    pub vars: SessionVars,
 }
 
 impl SessionManager {
     pub fn new() -> Self {
+        let lease_seconds = std::env::var("SESSION_LEASE_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_LEASE_SECONDS);
+
+        let max_lease_seconds = std::env::var("SESSION_MAX_LEASE_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(4 * 3600)
+            .max(lease_seconds);
+
+        let trash_seconds = std::env::var("SESSION_TRASH_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(3600);
+
         Self {
             sessions: Arc::new(DashMap::new()),
+            lease_seconds,
+            max_lease_seconds,
+            trash: Arc::new(DashMap::new()),
+            trash_seconds,
+            services: BrowserServices {
+                credentials: Arc::new(CredentialVault::from_env()),
+                routing: RequestRouting {
+                    http_auth: Arc::new(HttpAuthCredentials::from_env()),
+                    tunnels: Arc::new(TunnelRegistry::from_env()),
+                },
+                otp: Arc::new(OtpProviders::from_env()),
+                hooks: Arc::new(HookRegistry::from_env()),
+                hygiene: Arc::new(PageHygiene::default()),
+                heatmap: Arc::new(InteractionHeatmap::default()),
+            },
+            marker: AutomationMarker::from_env(),
+            warm_pool: Arc::new(WarmPool::from_env()),
+            presets: Arc::new(SessionPresets::from_env()),
+            fingerprints: Arc::new(FingerprintPresets::from_env()),
         }
     }
 
@@ -28,18 +192,100 @@ impl SessionManager {
         initial_url: String,
         viewport_width: u32,
         viewport_height: u32,
+        user_id: Option<String>,
+        options: SessionOptions,
     ) -> Result<String> {
+        let preset = options
+            .preset
+            .as_deref()
+            .map(|name| self.preset(name).with_context(|| format!("Unknown session preset '{}'", name)))
+            .transpose()?;
+        if let Some(preset) = &preset {
+            if !preset.policy().allows_url(&initial_url) {
+                anyhow::bail!("{} is outside the origins the preset allows", initial_url);
+            }
+        }
+        let fingerprint_name = options
+            .fingerprint
+            .or_else(|| preset.as_ref().and_then(|preset| preset.fingerprint.clone()));
+        let fingerprint = fingerprint_name
+            .as_deref()
+            .map(|name| self.fingerprint(name).with_context(|| format!("Unknown fingerprint '{}'", name)))
+            .transpose()?;
+
+        let banner = options
+            .automation_banner
+            .or_else(|| preset.as_ref().and_then(|preset| preset.automation_banner))
+            .unwrap_or(self.marker.banner);
+        let marker = self.marker.clone().with_banner(banner);
+        let keyboard_only = options.keyboard_only;
+
         // Create session metadata
-        let session = Session::new(
+        let mut session = Session::new(
             initial_url.clone(),
             viewport_width,
             viewport_height,
-        );
+        )
+        .with_lease(self.lease_seconds)
+        .with_automation_banner(marker.banner)
+        .with_keyboard_only(keyboard_only);
+        if let Some(user_id) = user_id {
+            session = session.with_user_id(user_id);
+        }
+        if let Some(name) = options.preset {
+            session = session.with_preset(name);
+        }
+        if let Some(name) = fingerprint_name {
+            session = session.with_fingerprint(name);
+        }
 
         let session_id = session.id.clone();
 
+        // Warm browsers carry the default banner, identity and no policy; keyboard-only mode can still be switched on
+        let warm = match marker.banner == self.marker.banner && preset.is_none() && fingerprint.is_none() {
+            true => self.warm_pool.claim(&initial_url, viewport_width, viewport_height),
+            false => None,
+        };
+
         // Create browser automation
-        let browser = BrowserAutomation::new(&initial_url, viewport_width, viewport_height).await?;
+        let browser = match warm {
+            Some(browser) => {
+                tracing::info!("Session {} claimed a warm browser for {}", session_id, initial_url);
+                browser.with_keyboard_only(keyboard_only)
+            }
+            None => {
+                self.services
+                    .launch(
+                        &initial_url,
+                        viewport_width,
+                        viewport_height,
+                        LaunchSettings {
+                            marker: &marker,
+                            keyboard_only,
+                            preset: preset.as_ref(),
+                            fingerprint: fingerprint.as_ref(),
+                        },
+                    )
+                    .await?
+            }
+        };
+
+        let browser = match options.dialogs {
+            Some(dialogs) => browser.with_dialog_policy(dialogs),
+            None => browser,
+        };
+        if let Some(state) = &options.storage_state {
+            browser
+                .import_storage_state(state, &initial_url)
+                .await
+                .context("Failed to apply the storage state")?;
+            tracing::info!(
+                "Session {} started with {} cookie(s) and storage of {} origin(s)",
+                session_id,
+                state.cookies.len(),
+                state.origins.len()
+            );
+        }
 
         // Store session
         self.sessions.insert(
@@ -47,6 +293,7 @@ impl SessionManager {
             SessionData {
                 session,
                 browser: Arc::new(browser),
+                vars: SessionVars::new(),
             },
         );
 
@@ -64,7 +311,6 @@ impl SessionManager {
     }
 
     /// Get session metadata
-    #[allow(dead_code)] // Utility method for future session inspection
     pub fn get_session(&self, session_id: &str) -> Result<Session> {
         let entry = self
             .sessions
@@ -74,6 +320,40 @@ impl SessionManager {
         Ok(entry.session.clone())
     }
 
+    /// Make the user the owner of a session that has none; returns the session
+    pub fn claim(&self, session_id: &str, user_id: &str) -> Result<Session> {
+        let mut entry = self
+            .sessions
+            .get_mut(session_id)
+            .context("Session not found")?;
+
+        entry.session.claim(user_id);
+
+        Ok(entry.session.clone())
+    }
+
+    /// Variables of the session
+    pub fn vars(&self, session_id: &str) -> Result<SessionVars> {
+        let entry = self
+            .sessions
+            .get(session_id)
+            .context("Session not found")?;
+
+        Ok(entry.vars.clone())
+    }
+
+    /// Replace all variables of the session
+    pub fn set_vars(&self, session_id: &str, vars: SessionVars) -> Result<()> {
+        let mut entry = self
+            .sessions
+            .get_mut(session_id)
+            .context("Session not found")?;
+
+        entry.vars = vars;
+
+        Ok(())
+    }
+
     /// Update session activity
     pub fn update_activity(&self, session_id: &str) -> Result<()> {
         let mut entry = self
@@ -82,11 +362,212 @@ impl SessionManager {
             .context("Session not found")?;
 
         entry.session.update_activity();
+        entry.session.extend_lease(self.lease_seconds);
 
         Ok(())
     }
 
-    /// Remove session
+    /// Explicitly extend a session's lease, e.g. while a human is in the loop
+    ///
+    /// Extends by `extend_seconds` (default: the standard lease), capped at the maximum lease.
+    pub fn keepalive(&self, session_id: &str, extend_seconds: Option<i64>) -> Result<Session> {
+        let mut entry = self
+            .sessions
+            .get_mut(session_id)
+            .context("Session not found")?;
+
+        let seconds = extend_seconds
+            .unwrap_or(self.lease_seconds)
+            .clamp(0, self.max_lease_seconds);
+
+        entry.session.update_activity();
+        entry.session.extend_lease(seconds);
+
+        Ok(entry.session.clone())
+    }
+
+    /// Periodically remove sessions whose lease has expired and purge old trash
+    pub fn start_reaper(&self, interval: std::time::Duration) {
+        let sessions = Arc::clone(&self.sessions);
+        let trash = Arc::clone(&self.trash);
+        let trash_seconds = self.trash_seconds;
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                sessions.retain(|session_id, data| {
+                    if data.session.is_expired() {
+                        tracing::info!("Session {} lease expired, closing it", session_id);
+                        false
+                    } else {
+                        true
+                    }
+                });
+                trash.retain(|_, deleted| Utc::now() <= deleted.restorable_until(trash_seconds));
+            }
+        });
+    }
+
+    /// Keep the warm pool topped up, launching missing browsers every `interval`
+    pub fn start_warm_pool(&self, interval: std::time::Duration) {
+        if self.warm_pool.entries().is_empty() {
+            return;
+        }
+
+        let pool = Arc::clone(&self.warm_pool);
+        let services = self.services.clone();
+        let marker = self.marker.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                for (index, entry) in pool.entries().iter().enumerate() {
+                    for _ in 0..pool.missing(index) {
+                        let browser = match services
+                            .launch(
+                                &entry.url,
+                                entry.viewport_width,
+                                entry.viewport_height,
+                                LaunchSettings { marker: &marker, keyboard_only: false, preset: None, fingerprint: None },
+                            )
+                            .await
+                        {
+                            Ok(browser) => browser,
+                            Err(e) => {
+                                tracing::warn!("Could not warm up a browser for {}: {}", entry.url, e);
+                                break;
+                            }
+                        };
+
+                        let policy = entry.consent_policy.unwrap_or_else(ConsentPolicy::from_env);
+                        if let Err(e) = browser.dismiss_consent_banners(policy).await {
+                            tracing::warn!("Could not dismiss consent banner on warm {}: {}", entry.url, e);
+                        }
+                        pool.add(index, browser);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Soft-delete a session: close its browser but keep metadata and storage
+    /// state so it can be restored within the trash window
+    pub async fn soft_delete_session(&self, session_id: &str) -> Result<DateTime<Utc>> {
+        let (_, data) = self
+            .sessions
+            .remove(session_id)
+            .context("Session not found")?;
+
+        let storage_state = match data.browser.storage_state().await {
+            Ok(state) => Some(state),
+            Err(e) => {
+                tracing::warn!("Could not capture storage state of session {}: {}", session_id, e);
+                None
+            }
+        };
+
+        let deleted = DeletedSession {
+            session: data.session,
+            storage_state,
+            vars: data.vars,
+            deleted_at: Utc::now(),
+        };
+        let restorable_until = deleted.restorable_until(self.trash_seconds);
+        self.trash.insert(session_id.to_string(), deleted);
+
+        // Dropping the last browser handle closes it
+        Ok(restorable_until)
+    }
+
+    /// Recreate a soft-deleted session under its original ID
+    ///
+    /// Only callers who could use the session (see `Session::usable_by`) can
+    /// restore it; to anyone else it doesn't exist. The session stays in the
+    /// trash until its new browser runs, so a failed restore can be retried.
+    pub async fn restore_session(&self, session_id: &str, caller: Option<&str>) -> Result<Session> {
+        let deleted = self
+            .trash
+            .get(session_id)
+            .filter(|deleted| deleted.session.usable_by(caller))
+            .map(|deleted| deleted.clone())
+            .context("No deleted session with this ID")?;
+
+        if Utc::now() > deleted.restorable_until(self.trash_seconds) {
+            anyhow::bail!("Restore window for session {} has passed", session_id);
+        }
+
+        let mut session = deleted.session;
+        let url = deleted
+            .storage_state
+            .as_ref()
+            .map(|state| state.url.clone())
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| session.browser_info.current_url.clone());
+
+        let marker = self
+            .marker
+            .clone()
+            .with_banner(session.browser_info.automation_banner);
+        // Presets may have been edited since; the session gets the current rules
+        let preset = match &session.browser_info.preset {
+            Some(name) => Some(
+                self.preset(name)
+                    .with_context(|| format!("Session preset '{}' no longer exists", name))?,
+            ),
+            None => None,
+        };
+        let fingerprint = match &session.browser_info.fingerprint {
+            Some(name) => Some(
+                self.fingerprint(name)
+                    .with_context(|| format!("Fingerprint '{}' no longer exists", name))?,
+            ),
+            None => None,
+        };
+        let browser = self
+            .services
+            .launch(
+                &url,
+                session.browser_info.viewport_width,
+                session.browser_info.viewport_height,
+                LaunchSettings {
+                    marker: &marker,
+                    keyboard_only: session.browser_info.keyboard_only,
+                    preset: preset.as_ref(),
+                    fingerprint: fingerprint.as_ref(),
+                },
+            )
+            .await?;
+
+        // Another restore of the session may have finished meanwhile; its browser wins
+        if self.trash.remove(session_id).is_none() {
+            anyhow::bail!("Session {} was restored meanwhile", session_id);
+        }
+
+        if let Some(state) = &deleted.storage_state {
+            if let Err(e) = browser.restore_storage_state(state).await {
+                tracing::warn!("Could not restore storage state of session {}: {}", session_id, e);
+            }
+        }
+
+        session.browser_info.current_url = url;
+        session.update_activity();
+        session.extend_lease(self.lease_seconds);
+
+        self.sessions.insert(
+            session_id.to_string(),
+            SessionData {
+                session: session.clone(),
+                browser: Arc::new(browser),
+                vars: deleted.vars,
+            },
+        );
+
+        Ok(session)
+    }
+
+    /// Remove session permanently
     pub fn remove_session(&self, session_id: &str) -> Result<()> {
         self.sessions
             .remove(session_id)
@@ -103,8 +584,78 @@ impl SessionManager {
             .collect()
     }
 
+    /// Metadata of all active sessions, oldest first
+    pub fn list_session_details(&self) -> Vec<Session> {
+        let mut sessions: Vec<Session> = self
+            .sessions
+            .iter()
+            .map(|entry| entry.session.clone())
+            .collect();
+        sessions.sort_by_key(|session| session.created_at);
+        sessions
+    }
+
+    /// OTP providers shared by all sessions (the SMS webhook feeds their inbox)
+    pub fn otp_providers(&self) -> Arc<OtpProviders> {
+        Arc::clone(&self.services.otp)
+    }
+
+    /// Test hooks shared by all sessions (registered through the admin API)
+    pub fn hooks(&self) -> Arc<HookRegistry> {
+        Arc::clone(&self.services.hooks)
+    }
+
+    /// Element interaction counts across all sessions
+    pub fn interaction_heatmap(&self) -> &InteractionHeatmap {
+        &self.services.heatmap
+    }
+
+    /// Page-hygiene counters across all sessions
+    pub fn hygiene_stats(&self) -> HygieneStats {
+        self.services.hygiene.stats()
+    }
+
+    /// Ready browsers per warm pool URL
+    pub fn warm_pool_status(&self) -> Vec<WarmPoolStatus> {
+        self.warm_pool.status()
+    }
+
+    /// A session preset by name
+    pub fn preset(&self, name: &str) -> Option<SessionPreset> {
+        self.presets.get(name).cloned()
+    }
+
+    /// Names of the configured session presets, sorted
+    pub fn preset_names(&self) -> Vec<String> {
+        self.presets.names()
+    }
+
+    /// A browser fingerprint by name
+    pub fn fingerprint(&self, name: &str) -> Option<Fingerprint> {
+        self.fingerprints.get(name).cloned()
+    }
+
+    /// Names of the available fingerprints, built-in and configured, sorted
+    pub fn fingerprint_names(&self) -> Vec<String> {
+        self.fingerprints.names()
+    }
+
+    /// Tunnels to apps on callers' machines, reachable from every session
+    pub fn tunnels(&self) -> Arc<TunnelRegistry> {
+        Arc::clone(&self.services.routing.tunnels)
+    }
+
+    /// Lease granted on creation and renewed by activity
+    pub fn lease_seconds(&self) -> i64 {
+        self.lease_seconds
+    }
+
+    /// Longest lease a keepalive may grant
+    pub fn max_lease_seconds(&self) -> i64 {
+        self.max_lease_seconds
+    }
+
     /// Get session count
-    #[allow(dead_code)] // Utility method for monitoring/stats
     pub fn session_count(&self) -> usize {
         self.sessions.len()
     }
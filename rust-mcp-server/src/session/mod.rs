@@ -1,4 +1,9 @@
 pub mod manager;
+pub mod presets;
 pub mod redis_store;
+pub mod vars;
+pub mod warm_pool;
 
-pub use manager::SessionManager;
+pub use manager::{SessionManager, SessionOptions};
+pub use vars::{action_text_mut, interpolate, interpolate_action, is_valid_var_name, placeholders, substitute, SessionVars};
+pub use warm_pool::WarmPoolStatus;
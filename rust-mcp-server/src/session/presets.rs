@@ -0,0 +1,145 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::browser::{BlockedResource, SessionPolicy, SettlePolicy};
+use crate::models::{ActionRequest, DialogPolicy};
+
+/// Organizational rules for a kind of session, chosen by name at creation (e.g. "staging", "prod-readonly")
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SessionPreset {
+    /// Origins pages may be loaded from, e.g. "https://*.staging.example.com"; any when empty
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+
+    /// Block form posts and every other request that may change data
+    #[serde(default)]
+    pub read_only: bool,
+
+    /// Actions the session rejects, by `tool` value (e.g. "invoke_hook")
+    #[serde(default)]
+    pub blocked_actions: Vec<String>,
+
+    /// Resource types never loaded: "image", "media", "font", "stylesheet"
+    #[serde(default)]
+    pub block_resources: Vec<BlockedResource>,
+
+    /// Vault credentials the `login` action may use; every credential when absent
+    #[serde(default)]
+    pub credentials: Option<Vec<String>>,
+
+    /// Automation banner when the request doesn't choose (default: AUTOMATION_BANNER)
+    #[serde(default)]
+    pub automation_banner: Option<bool>,
+    /// Fingerprint preset when the request doesn't choose one
+    #[serde(default)]
+    pub fingerprint: Option<String>,
+
+    /// Settle policies for sites whose DOM never goes quiet, first match by origin wins
+    #[serde(default)]
+    pub settle: Vec<SettlePolicy>,
+
+    /// How JavaScript dialogs are answered when the request doesn't choose (default: accept)
+    #[serde(default)]
+    pub dialogs: Option<DialogPolicy>,
+}
+
+impl SessionPreset {
+    pub fn policy(&self) -> SessionPolicy {
+        SessionPolicy {
+            allowed_origins: self.allowed_origins.clone(),
+            read_only: self.read_only,
+            blocked_actions: self.blocked_actions.clone(),
+            blocked_resources: self.block_resources.clone(),
+            settle: self.settle.clone(),
+            dialogs: self.dialogs.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Session presets by name
+#[derive(Debug, Default)]
+pub struct SessionPresets {
+    presets: HashMap<String, SessionPreset>,
+}
+
+impl SessionPresets {
+    /// Load from SESSION_PRESETS_FILE (JSON file) and/or SESSION_PRESETS (inline JSON)
+    ///
+    /// Both hold a map of name -> preset; inline presets override file presets
+    /// with the same name.
+    pub fn from_env() -> Self {
+        let mut presets = Self::default();
+
+        if let Ok(path) = std::env::var("SESSION_PRESETS_FILE") {
+            match std::fs::read_to_string(&path) {
+                Ok(raw) => presets.load(&raw, &path),
+                Err(e) => tracing::warn!("Failed to read session presets {}: {}", path, e),
+            }
+        }
+
+        if let Ok(raw) = std::env::var("SESSION_PRESETS") {
+            presets.load(&raw, "SESSION_PRESETS");
+        }
+
+        if !presets.presets.is_empty() {
+            tracing::info!("Loaded {} session preset(s)", presets.presets.len());
+        }
+        presets
+    }
+
+    fn load(&mut self, raw: &str, source: &str) {
+        match serde_json::from_str::<HashMap<String, SessionPreset>>(raw) {
+            Ok(presets) => {
+                for (name, preset) in &presets {
+                    for tool in &preset.blocked_actions {
+                        if !ActionRequest::TOOLS.contains(&tool.as_str()) {
+                            tracing::warn!("Preset '{}' blocks unknown action '{}'", name, tool);
+                        }
+                    }
+                }
+                self.presets.extend(presets);
+            }
+            Err(e) => tracing::warn!("Invalid session presets in {}, ignoring: {}", source, e),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SessionPreset> {
+        self.presets.get(name)
+    }
+
+    /// Preset names, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.presets.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let mut presets = SessionPresets::default();
+        presets.load(
+            r#"{
+                "staging": {"allowed_origins": ["https://*.staging.example.com"], "credentials": ["staging-qa"]},
+                "prod-readonly": {"read_only": true, "blocked_actions": ["invoke_hook"], "block_resources": ["image"]}
+            }"#,
+            "test",
+        );
+        presets.load("not json", "test");
+
+        assert_eq!(presets.names(), vec!["prod-readonly", "staging"]);
+
+        let staging = presets.get("staging").unwrap();
+        assert!(staging.policy().allows_url("https://app.staging.example.com/"));
+        assert!(!staging.policy().allows_url("https://app.example.com/"));
+        assert_eq!(staging.credentials.as_deref(), Some(&["staging-qa".to_string()][..]));
+
+        let readonly = presets.get("prod-readonly").unwrap().policy();
+        assert!(readonly.read_only);
+        assert_eq!(readonly.blocked_resources, vec![BlockedResource::Image]);
+    }
+}
@@ -177,10 +177,16 @@ mod tests {
             user_id: Some("user456".to_string()),
             created_at: Utc::now(),
             last_activity: Utc::now(),
+            expires_at: Utc::now(),
             browser_info: BrowserInfo {
                 initial_url: "http://localhost".to_string(),
+                current_url: "http://localhost".to_string(),
                 viewport_width: 1280,
                 viewport_height: 720,
+                automation_banner: false,
+                keyboard_only: false,
+                preset: None,
+                fingerprint: None,
             },
         };
 
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+use crate::models::ActionRequest;
+
+/// Named values of one session, filled into `{{name}}` placeholders
+pub type SessionVars = BTreeMap<String, String>;
+
+/// Letters, digits, `_`, `-` and `.`, e.g. "user.email"
+pub fn is_valid_var_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Replace `{{name}}` placeholders (spaces inside the braces allowed) with session variables
+///
+/// Braces around anything that isn't a variable name are kept as they are;
+/// a valid name without a value is an error, so typos don't end up on the page.
+pub fn interpolate(template: &str, vars: &SessionVars) -> Result<String> {
+    substitute(template, |name| {
+        vars.get(name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Unknown session variable '{}'", name))
+    })
+}
+
+/// Names of the `{{name}}` placeholders in `template`, in order of appearance
+pub fn placeholders(template: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let _ = substitute(template, |name| {
+        names.push(name.to_string());
+        Ok(String::new())
+    });
+    names
+}
+
+/// Replace each `{{name}}` placeholder of `template` with `value_of(name)`
+pub fn substitute(template: &str, mut value_of: impl FnMut(&str) -> Result<String>) -> Result<String> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find("}}") else {
+            output.push_str(&rest[start..]);
+            return Ok(output);
+        };
+
+        let name = after_open[..end].trim();
+        if is_valid_var_name(name) {
+            output.push_str(&value_of(name)?);
+        } else {
+            output.push_str(&rest[start..start + 2 + end + 2]);
+        }
+        rest = &after_open[end + 2..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Fill placeholders in the free text of an action (see `action_text_mut`)
+pub fn interpolate_action(action: &ActionRequest, vars: &SessionVars) -> Result<ActionRequest> {
+    let mut action = action.clone();
+    if let Some(text) = action_text_mut(&mut action) {
+        *text = interpolate(text, vars)?;
+    }
+    Ok(action)
+}
+
+/// The free text of an action, where placeholders may appear: typed and awaited text, URLs, URL patterns and stored values
+pub fn action_text_mut(action: &mut ActionRequest) -> Option<&mut String> {
+    match action {
+        ActionRequest::Type { text, .. } | ActionRequest::WaitForText { text, .. } => Some(text),
+        ActionRequest::WaitForUrl { pattern, .. } => Some(pattern),
+        ActionRequest::Navigate { url } | ActionRequest::NewTab { url: Some(url) } => Some(url),
+        ActionRequest::SetStorage { value: Some(value), .. } => Some(value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> SessionVars {
+        SessionVars::from([
+            ("user".to_string(), "ann".to_string()),
+            ("order.id".to_string(), "1042".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_interpolate() {
+        assert_eq!(
+            interpolate("Log in as {{user}} and open order {{ order.id }}", &vars()).unwrap(),
+            "Log in as ann and open order 1042"
+        );
+        // Not placeholders
+        assert_eq!(interpolate("{{ not a name }} {{user", &vars()).unwrap(), "{{ not a name }} {{user");
+        assert_eq!(interpolate("{}", &vars()).unwrap(), "{}");
+
+        let error = interpolate("Hello {{usr}}", &vars()).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown session variable 'usr'");
+
+        assert_eq!(placeholders("{{ user }} bought {{order.id}} {{ not a name }}"), vec!["user", "order.id"]);
+    }
+
+    #[test]
+    fn test_interpolate_action() {
+        let action: ActionRequest =
+            serde_json::from_str(r#"{"tool": "navigate", "url": "https://shop.example.com/orders/{{order.id}}"}"#).unwrap();
+        match interpolate_action(&action, &vars()).unwrap() {
+            ActionRequest::Navigate { url } => assert_eq!(url, "https://shop.example.com/orders/1042"),
+            other => panic!("unexpected action {:?}", other),
+        }
+
+        let action: ActionRequest =
+            serde_json::from_str(r#"{"tool": "set_storage", "key": "last_order", "value": "{{order.id}}"}"#).unwrap();
+        match interpolate_action(&action, &vars()).unwrap() {
+            ActionRequest::SetStorage { area, value, .. } => {
+                assert_eq!(area, crate::models::WebStorageArea::Local);
+                assert_eq!(value.as_deref(), Some("1042"));
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+
+        assert!(is_valid_var_name("user_email"));
+        assert!(!is_valid_var_name("user email"));
+        assert!(!is_valid_var_name(""));
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use crate::browser::{BrowserAutomation, ConsentPolicy};
+
+/// A start URL kept open in pre-launched browsers, ready to be claimed by new sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmPoolEntry {
+    pub url: String,
+
+    /// Browsers kept ready for this URL
+    #[serde(default = "default_size")]
+    pub size: usize,
+
+    #[serde(default = "default_viewport_width")]
+    pub viewport_width: u32,
+
+    #[serde(default = "default_viewport_height")]
+    pub viewport_height: u32,
+
+    /// Answer to consent banners while warming up (default: CONSENT_POLICY)
+    #[serde(default)]
+    pub consent_policy: Option<ConsentPolicy>,
+}
+
+fn default_size() -> usize {
+    1
+}
+
+fn default_viewport_width() -> u32 {
+    1280
+}
+
+fn default_viewport_height() -> u32 {
+    720
+}
+
+impl WarmPoolEntry {
+    /// Whether a session asking for this URL and viewport can take a browser from this entry
+    pub fn serves(&self, url: &str, viewport_width: u32, viewport_height: u32) -> bool {
+        // "https://app.example.com" and "https://app.example.com/" are the same page
+        self.url.trim_end_matches('/') == url.trim_end_matches('/')
+            && self.viewport_width == viewport_width
+            && self.viewport_height == viewport_height
+    }
+}
+
+/// How full one entry of the pool is
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmPoolStatus {
+    pub url: String,
+    pub size: usize,
+    pub ready: usize,
+}
+
+/// Pre-launched browsers per configured start URL (WARM_POOL)
+///
+/// Browsers are launched with the default automation banner and without
+/// keyboard-only mode; sessions asking for another banner setting are never
+/// served from the pool.
+#[derive(Default)]
+pub struct WarmPool {
+    entries: Vec<WarmPoolEntry>,
+
+    /// Ready browsers, one list per entry
+    ready: Mutex<Vec<Vec<BrowserAutomation>>>,
+}
+
+impl WarmPool {
+    /// Load WARM_POOL: JSON list of {"url": "...", "size": 1, "viewport_width": 1280,
+    /// "viewport_height": 720, "consent_policy": "accept"}
+    pub fn from_env() -> Self {
+        let entries = match std::env::var("WARM_POOL") {
+            Ok(raw) if !raw.trim().is_empty() => match serde_json::from_str::<Vec<WarmPoolEntry>>(&raw) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    tracing::warn!("Invalid WARM_POOL, ignoring: {}", e);
+                    Vec::new()
+                }
+            },
+            _ => Vec::new(),
+        };
+
+        Self::new(entries)
+    }
+
+    pub fn new(entries: Vec<WarmPoolEntry>) -> Self {
+        let ready = entries.iter().map(|_| Vec::new()).collect();
+        Self {
+            entries,
+            ready: Mutex::new(ready),
+        }
+    }
+
+    pub fn entries(&self) -> &[WarmPoolEntry] {
+        &self.entries
+    }
+
+    /// Take a ready browser for the URL and viewport, if the pool has one
+    pub fn claim(&self, url: &str, viewport_width: u32, viewport_height: u32) -> Option<BrowserAutomation> {
+        let mut ready = self.ready.lock().unwrap();
+        self.entries
+            .iter()
+            .zip(ready.iter_mut())
+            .filter(|(entry, _)| entry.serves(url, viewport_width, viewport_height))
+            .find_map(|(_, browsers)| browsers.pop())
+    }
+
+    /// Browsers still missing for the entry at `index`
+    pub fn missing(&self, index: usize) -> usize {
+        let ready = self.ready.lock().unwrap();
+        self.entries[index].size.saturating_sub(ready[index].len())
+    }
+
+    /// Add a warmed-up browser to the entry at `index`
+    pub fn add(&self, index: usize, browser: BrowserAutomation) {
+        self.ready.lock().unwrap()[index].push(browser);
+    }
+
+    pub fn status(&self) -> Vec<WarmPoolStatus> {
+        let ready = self.ready.lock().unwrap();
+        self.entries
+            .iter()
+            .zip(ready.iter())
+            .map(|(entry, browsers)| WarmPoolStatus {
+                url: entry.url.clone(),
+                size: entry.size,
+                ready: browsers.len(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_defaults_and_matching() {
+        let entries: Vec<WarmPoolEntry> =
+            serde_json::from_str(r#"[{"url": "https://app.example.com/login/", "consent_policy": "accept"}]"#).unwrap();
+        let entry = &entries[0];
+        assert_eq!(entry.size, 1);
+        assert_eq!(entry.consent_policy, Some(ConsentPolicy::Accept));
+
+        assert!(entry.serves("https://app.example.com/login", 1280, 720));
+        assert!(!entry.serves("https://app.example.com/login", 800, 600));
+        assert!(!entry.serves("https://app.example.com/", 1280, 720));
+    }
+
+    #[test]
+    fn test_empty_pool_has_nothing_to_claim() {
+        let pool = WarmPool::new(vec![WarmPoolEntry {
+            url: "https://app.example.com".to_string(),
+            size: 2,
+            viewport_width: 1280,
+            viewport_height: 720,
+            consent_policy: None,
+        }]);
+        assert!(pool.claim("https://app.example.com", 1280, 720).is_none());
+        assert_eq!(pool.missing(0), 2);
+        assert_eq!(pool.status()[0].ready, 0);
+    }
+}
@@ -0,0 +1,234 @@
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, watch};
+
+/// Host suffix of tunnel URLs; `*.localhost` pages count as secure contexts in Chrome
+const DEFAULT_HOST_SUFFIX: &str = "tunnel.localhost";
+
+/// How long a request waits for the client's answer (seconds)
+const DEFAULT_REQUEST_TIMEOUT_SECONDS: u64 = 30;
+
+/// Frames the server sends over a tunnel's WebSocket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TunnelFrame {
+    /// First frame: the URL sessions open to reach the client's app
+    Registered { tunnel_id: String, url: String },
+    /// A browser request for the client to send to its local app
+    Request(TunnelRequest),
+}
+
+/// A browser request going through a tunnel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TunnelRequest {
+    /// Echoed back in the response
+    pub id: String,
+    pub method: String,
+    /// Path and query, e.g. "/search?q=shoes"
+    pub path: String,
+    /// Name-value pairs, in the browser's order
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// Base64 request body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+/// The client's answer to a [`TunnelRequest`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TunnelResponse {
+    pub id: String,
+    pub status: u16,
+    /// Name-value pairs; repeat a name for several values, e.g. Set-Cookie
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// Base64 response body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+/// One client's open tunnel: forwards requests over its WebSocket and matches up the answers
+pub struct Tunnel {
+    pub id: String,
+    pub host: String,
+    outbound: mpsc::UnboundedSender<TunnelFrame>,
+    pending: DashMap<String, oneshot::Sender<TunnelResponse>>,
+    next_request: AtomicU64,
+    timeout: Duration,
+}
+
+impl Tunnel {
+    pub fn url(&self) -> String {
+        format!("http://{}", self.host)
+    }
+
+    /// Send `request` (its `id` is assigned here) to the client and wait for the response
+    pub async fn forward(&self, mut request: TunnelRequest) -> Result<TunnelResponse> {
+        request.id = self.next_request.fetch_add(1, Ordering::Relaxed).to_string();
+        let id = request.id.clone();
+        let (answer, response) = oneshot::channel();
+        self.pending.insert(id.clone(), answer);
+
+        if self.outbound.send(TunnelFrame::Request(request)).is_err() {
+            self.pending.remove(&id);
+            anyhow::bail!("tunnel {} is closed", self.id);
+        }
+
+        let result = tokio::time::timeout(self.timeout, response).await;
+        self.pending.remove(&id);
+        match result {
+            Ok(response) => response.with_context(|| format!("tunnel {} closed before answering", self.id)),
+            Err(_) => anyhow::bail!("tunnel {} did not answer within {}s", self.id, self.timeout.as_secs()),
+        }
+    }
+
+    /// Hand a response from the client to the request waiting for it; false if none is
+    pub fn resolve(&self, response: TunnelResponse) -> bool {
+        match self.pending.remove(&response.id) {
+            Some((_, answer)) => answer.send(response).is_ok(),
+            None => false,
+        }
+    }
+}
+
+/// Open tunnels by ID, reachable from every session as `http://<id>.<suffix>`
+///
+/// Requests to these hosts never leave the server's browser: the request
+/// interceptor hands them to the tunnel, whose client answers them from an
+/// app on its own machine, e.g. one on `localhost:3000`.
+pub struct TunnelRegistry {
+    tunnels: DashMap<String, Arc<Tunnel>>,
+    /// Marked changed whenever a tunnel opens
+    opened: watch::Sender<()>,
+    host_suffix: String,
+    timeout: Duration,
+}
+
+impl Default for TunnelRegistry {
+    fn default() -> Self {
+        Self {
+            tunnels: DashMap::new(),
+            opened: watch::Sender::new(()),
+            host_suffix: DEFAULT_HOST_SUFFIX.to_string(),
+            timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECONDS),
+        }
+    }
+}
+
+impl TunnelRegistry {
+    /// TUNNEL_HOST_SUFFIX (default: tunnel.localhost) and TUNNEL_REQUEST_TIMEOUT_SECONDS (default: 30)
+    pub fn from_env() -> Self {
+        let mut registry = Self::default();
+        if let Ok(suffix) = std::env::var("TUNNEL_HOST_SUFFIX") {
+            let suffix = suffix.trim().trim_matches('.').to_lowercase();
+            if !suffix.is_empty() {
+                registry.host_suffix = suffix;
+            }
+        }
+        if let Some(seconds) = std::env::var("TUNNEL_REQUEST_TIMEOUT_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+        {
+            registry.timeout = Duration::from_secs(seconds);
+        }
+        registry
+    }
+
+    /// Register a tunnel; its frames, starting with [`TunnelFrame::Registered`], come out of the receiver
+    pub fn open(&self) -> (Arc<Tunnel>, mpsc::UnboundedReceiver<TunnelFrame>) {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let (outbound, frames) = mpsc::unbounded_channel();
+        let tunnel = Arc::new(Tunnel {
+            host: format!("{}.{}", id, self.host_suffix),
+            id: id.clone(),
+            outbound,
+            pending: DashMap::new(),
+            next_request: AtomicU64::new(1),
+            timeout: self.timeout,
+        });
+
+        let _ = tunnel.outbound.send(TunnelFrame::Registered {
+            tunnel_id: id.clone(),
+            url: tunnel.url(),
+        });
+        self.tunnels.insert(id, Arc::clone(&tunnel));
+        self.opened.send_replace(());
+        (tunnel, frames)
+    }
+
+    /// Changes whenever a tunnel opens
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.opened.subscribe()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tunnels.is_empty()
+    }
+
+    /// Remove a tunnel; requests still waiting on it fail
+    pub fn close(&self, tunnel_id: &str) {
+        if let Some((_, tunnel)) = self.tunnels.remove(tunnel_id) {
+            tunnel.pending.clear();
+        }
+    }
+
+    /// The tunnel serving `url`, if its host is a tunnel host
+    pub fn for_url(&self, url: &str) -> Option<Arc<Tunnel>> {
+        let url = reqwest::Url::parse(url).ok()?;
+        let host = url.host_str()?.to_lowercase();
+        let id = host.strip_suffix(&self.host_suffix)?.strip_suffix('.')?;
+        self.tunnels.get(id).map(|tunnel| Arc::clone(&tunnel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_forward() {
+        let registry = TunnelRegistry::default();
+        let (tunnel, mut frames) = registry.open();
+
+        let Some(TunnelFrame::Registered { tunnel_id, url }) = frames.recv().await else {
+            panic!("expected the registration frame");
+        };
+        assert_eq!(url, format!("http://{}.tunnel.localhost", tunnel_id));
+        assert!(registry.for_url(&format!("{}/cart?id=7", url)).is_some());
+        assert!(registry.for_url("http://other.tunnel.localhost/").is_none());
+        assert!(registry.for_url("http://localhost:3000/").is_none());
+
+        // Play the client: answer the request that comes through
+        let client = Arc::clone(&tunnel);
+        tokio::spawn(async move {
+            let Some(TunnelFrame::Request(request)) = frames.recv().await else {
+                return;
+            };
+            assert_eq!(request.path, "/cart?id=7");
+            client.resolve(TunnelResponse {
+                id: request.id,
+                status: 200,
+                headers: vec![("content-type".to_string(), "text/html".to_string())],
+                body: None,
+            });
+        });
+
+        let request = TunnelRequest {
+            id: String::new(),
+            method: "GET".to_string(),
+            path: "/cart?id=7".to_string(),
+            headers: vec![],
+            body: None,
+        };
+        assert_eq!(tunnel.forward(request.clone()).await.unwrap().status, 200);
+
+        // The client is gone and nobody answers any more
+        registry.close(&tunnel_id);
+        assert!(registry.for_url(&url).is_none());
+        assert!(tunnel.forward(request).await.is_err());
+    }
+}
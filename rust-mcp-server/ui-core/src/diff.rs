@@ -0,0 +1,76 @@
+use std::collections::HashSet;
+
+use crate::models::UIContext;
+
+use super::prompt::element_line;
+
+/// Element lines that differ between two contexts of the same page
+///
+/// Lines include the element ID, so an element whose ID shifted counts as
+/// removed and added; the model never works with stale IDs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContextDiff {
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+impl ContextDiff {
+    pub fn between(previous: &UIContext, current: &UIContext) -> Self {
+        let before: Vec<String> = previous.elements.iter().map(element_line).collect();
+        let after: Vec<String> = current.elements.iter().map(element_line).collect();
+
+        let before_set: HashSet<&String> = before.iter().collect();
+        let after_set: HashSet<&String> = after.iter().collect();
+
+        Self {
+            removed: before.iter().filter(|l| !after_set.contains(l)).cloned().collect(),
+            added: after.iter().filter(|l| !before_set.contains(l)).cloned().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.removed.len() + self.added.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{SimplifiedElement, Viewport};
+
+    fn context(names: &[&str]) -> UIContext {
+        UIContext {
+            url: "http://shop".to_string(),
+            title: "Shop".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements: names
+                .iter()
+                .enumerate()
+                .map(|(id, name)| SimplifiedElement::new(id, "button", Some(*name), true))
+                .collect(),
+            navigation_history: vec![],
+            closed_overlays: vec![],
+            tabs: vec![],
+            focused: None,
+        }
+    }
+
+    #[test]
+    fn test_context_diff_lists_changed_lines() {
+        let before = context(&["Home", "Cart", "Checkout", "Help"]);
+        let after = context(&["Home", "Cart (1)", "Checkout", "Help"]);
+
+        let diff = ContextDiff::between(&before, &after);
+        assert_eq!(diff.removed, vec!["[1] Button('Cart') - in_viewport: true"]);
+        assert_eq!(diff.added, vec!["[1] Button('Cart (1)') - in_viewport: true"]);
+    }
+}
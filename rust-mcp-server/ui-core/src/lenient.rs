@@ -0,0 +1,477 @@
+use serde_json::{Map, Value};
+
+use crate::models::ActionRequest;
+
+/// Keys that may hold the selector as a nested object instead of flattened fields
+const SELECTOR_KEYS: &[&str] = &["selector", "target", "element", "locator"];
+
+/// Keys that may wrap all arguments, function-call style
+const ARGUMENT_KEYS: &[&str] = &["arguments", "parameters", "params", "input", "args"];
+
+/// Keys that may hold the tool name instead of `tool`
+const TOOL_KEYS: &[&str] = &["action", "tool_name", "function", "command"];
+
+/// Tool names LLMs use for the canonical ones
+const TOOL_ALIASES: &[(&str, &str)] = &[
+    ("tap", "click"),
+    ("click_element", "click"),
+    ("press_button", "click"),
+    ("type_text", "type"),
+    ("fill", "type"),
+    ("input", "type"),
+    ("input_text", "type"),
+    ("enter_text", "type"),
+    ("select", "select_option"),
+    ("choose", "select_option"),
+    ("check", "set_checked"),
+    ("uncheck", "set_checked"),
+    ("press", "press_key"),
+    ("key", "press_key"),
+    ("keypress", "press_key"),
+    ("goto", "navigate"),
+    ("go_to", "navigate"),
+    ("open", "navigate"),
+    ("open_url", "navigate"),
+    ("visit", "navigate"),
+    ("back", "go_back"),
+    ("forward", "go_forward"),
+    ("refresh", "reload"),
+    ("wait", "wait_for_element"),
+    ("wait_for", "wait_for_element"),
+    ("wait_for_load", "wait_for_navigation"),
+    ("wait_for_page_load", "wait_for_navigation"),
+    ("wait_for_idle", "wait_for_network_idle"),
+    ("wait_for_network", "wait_for_network_idle"),
+    ("find", "find_text"),
+    ("search_text", "find_text"),
+    ("zoom", "set_zoom"),
+    ("scroll_down", "scroll"),
+    ("scroll_up", "scroll"),
+    ("scroll_left", "scroll"),
+    ("scroll_right", "scroll"),
+    ("accept_dialog", "handle_dialog"),
+    ("dismiss_dialog", "handle_dialog"),
+    ("read_storage", "get_storage"),
+    ("write_storage", "set_storage"),
+    ("execute_script", "evaluate"),
+    ("run_script", "evaluate"),
+    ("open_tab", "new_tab"),
+    ("new_window", "new_tab"),
+    ("switch_to_tab", "switch_tab"),
+    ("focus_tab", "switch_tab"),
+    ("execute_js", "evaluate"),
+];
+
+/// Field names LLMs use for the canonical ones, per tool (`*` for every tool)
+const FIELD_ALIASES: &[(&str, &str, &str)] = &[
+    ("*", "id", "element_id"),
+    ("*", "index", "element_id"),
+    ("*", "timeout", "timeout_ms"),
+    ("click", "label", "name"),
+    ("click", "text", "name"),
+    ("focus", "label", "name"),
+    ("focus", "text", "name"),
+    ("wait_for_element", "label", "name"),
+    ("wait_for_element", "text", "name"),
+    ("wait_for_url", "url", "pattern"),
+    ("wait_for_text", "query", "text"),
+    ("set_checked", "label", "name"),
+    ("set_checked", "value", "checked"),
+    ("set_checked", "state", "checked"),
+    ("type", "value", "text"),
+    ("type", "input", "text"),
+    ("type", "content", "text"),
+    ("type", "label", "name"),
+    ("select_option", "option", "label"),
+    ("select_option", "choice", "label"),
+    ("press_key", "keys", "key"),
+    ("press_key", "value", "key"),
+    ("press_key", "text", "key"),
+    ("navigate", "href", "url"),
+    ("navigate", "link", "url"),
+    ("navigate", "target", "url"),
+    ("find_text", "text", "query"),
+    ("find_text", "search", "query"),
+    ("find_text", "q", "query"),
+    ("handle_dialog", "text", "prompt_text"),
+    ("handle_dialog", "value", "prompt_text"),
+    ("scroll", "pixels", "amount"),
+    ("scroll", "by", "amount"),
+    ("scroll", "distance", "amount"),
+    ("scroll", "dir", "direction"),
+    ("set_zoom", "zoom", "factor"),
+    ("set_zoom", "scale", "factor"),
+    ("set_zoom", "level", "factor"),
+    ("fill_form", "values", "fields"),
+    ("fill_form", "data", "fields"),
+    ("login", "credential", "credential_name"),
+    ("get_otp", "source", "provider"),
+    ("evaluate", "code", "script"),
+    ("evaluate", "expression", "script"),
+    ("evaluate", "js", "script"),
+    ("new_tab", "href", "url"),
+    ("switch_tab", "tab", "tab_id"),
+    ("close_tab", "tab", "tab_id"),
+];
+
+const NUMBER_FIELDS: &[&str] = &["element_id", "amount", "timeout_ms", "overlay_id", "tab_id", "factor", "quality"];
+
+const BOOL_FIELDS: &[&str] = &["checked", "submit", "full_page", "scroll_to_first", "fill"];
+
+/// An action read from LLM output, with the rewrites it took to get there
+#[derive(Debug, Clone)]
+pub struct LenientAction {
+    pub action: ActionRequest,
+
+    /// What was rewritten to reach the canonical schema, e.g. "renamed `value` to `text`";
+    /// empty when the output was canonical
+    pub normalized: Vec<String>,
+}
+
+/// Parse an action, accepting the near misses LLMs commonly produce
+///
+/// Canonical JSON is parsed as it is. Anything else is rewritten into the
+/// canonical form, and parsed with the same strict schema:
+/// - the object is cut out of surrounding prose or code fences;
+/// - the tool name may come as `action`, `function` or `command`, in any case,
+///   or under an alias (`fill` → `type`, `goto` → `navigate`, `check` → `set_checked`);
+/// - arguments wrapped in `arguments`/`parameters` (also as a JSON string) are unwrapped;
+/// - a nested `selector`/`target`/`element` object is flattened. A string
+///   selector is read as Playwright's `role=button[name="Save"]` or as a CSS fallback;
+/// - camelCase keys become snake_case, and per-tool aliases are renamed (`value` → `text` for `type`);
+/// - numbers and booleans sent as strings are converted.
+///
+/// On failure the error of the strict parse of the original output is returned.
+pub fn parse_action(raw: &str) -> Result<LenientAction, serde_json::Error> {
+    let strict = match serde_json::from_str::<ActionRequest>(raw) {
+        Ok(action) => return Ok(LenientAction { action, normalized: Vec::new() }),
+        Err(e) => e,
+    };
+
+    let mut normalized = Vec::new();
+    let value = match serde_json::from_str::<Value>(raw) {
+        Ok(value) => value,
+        Err(_) => match embedded_object(raw) {
+            Some(value) => {
+                normalized.push("took the JSON object out of the surrounding text".to_string());
+                value
+            }
+            None => return Err(strict),
+        },
+    };
+
+    let mut object = match value {
+        Value::Object(object) => object,
+        Value::Array(mut items) if items.len() == 1 && items[0].is_object() => {
+            normalized.push("used the only action of a one-element array".to_string());
+            match items.remove(0) {
+                Value::Object(object) => object,
+                _ => return Err(strict),
+            }
+        }
+        _ => return Err(strict),
+    };
+
+    normalize(&mut object, &mut normalized);
+    match serde_json::from_value::<ActionRequest>(Value::Object(object)) {
+        Ok(action) => Ok(LenientAction { action, normalized }),
+        Err(_) => Err(strict),
+    }
+}
+
+fn normalize(object: &mut Map<String, Value>, report: &mut Vec<String>) {
+    snake_case_keys(object, report);
+    unwrap_arguments(object, report);
+    normalize_tool(object, report);
+    flatten_selector(object, report);
+
+    let tool = object.get("tool").and_then(Value::as_str).unwrap_or_default().to_string();
+    for (alias_tool, alias, field) in FIELD_ALIASES {
+        if *alias_tool == "*" || *alias_tool == tool {
+            rename(object, alias, field, report);
+        }
+    }
+
+    if let Some(Value::String(direction)) = object.get_mut("direction") {
+        let lower = direction.to_lowercase();
+        if *direction != lower {
+            *direction = lower;
+            report.push("lowercased `direction`".to_string());
+        }
+    }
+    convert_scalars(object, report);
+}
+
+fn snake_case_keys(object: &mut Map<String, Value>, report: &mut Vec<String>) {
+    let camel: Vec<String> = object.keys().filter(|key| key.chars().any(|c| c.is_ascii_uppercase())).cloned().collect();
+    for key in camel {
+        let snake = to_snake_case(&key);
+        rename(object, &key, &snake, report);
+    }
+}
+
+/// `{"tool": "click", "arguments": {...}}` and `{"name": "click", "arguments": "{...}"}`
+fn unwrap_arguments(object: &mut Map<String, Value>, report: &mut Vec<String>) {
+    for key in ARGUMENT_KEYS {
+        let arguments = match object.get(*key) {
+            Some(Value::Object(arguments)) => arguments.clone(),
+            // OpenAI-style function calls carry the arguments as a JSON string
+            Some(Value::String(raw)) if *key != "input" => match serde_json::from_str::<Map<String, Value>>(raw) {
+                Ok(arguments) => arguments,
+                Err(_) => continue,
+            },
+            _ => continue,
+        };
+
+        object.remove(*key);
+        if !object.contains_key("tool") {
+            if let Some(Value::String(name)) = object.remove("name") {
+                object.insert("tool".to_string(), Value::String(name));
+                report.push("read `name` as `tool`".to_string());
+            }
+        }
+        for (field, value) in arguments {
+            object.entry(to_snake_case(&field)).or_insert(value);
+        }
+        report.push(format!("unwrapped `{}` into the action", key));
+        snake_case_keys(object, report);
+        return;
+    }
+}
+
+fn normalize_tool(object: &mut Map<String, Value>, report: &mut Vec<String>) {
+    if !object.contains_key("tool") {
+        for key in TOOL_KEYS {
+            if object.get(*key).is_some_and(Value::is_string) {
+                rename(object, key, "tool", report);
+                break;
+            }
+        }
+    }
+
+    let Some(Value::String(tool)) = object.get("tool") else {
+        return;
+    };
+    let given = tool.clone();
+    let mut canonical = to_snake_case(&given).replace(['-', ' '], "_");
+    if let Some((_, target)) = TOOL_ALIASES.iter().find(|(alias, _)| *alias == canonical) {
+        match canonical.as_str() {
+            "check" | "uncheck" => {
+                object.entry("checked").or_insert(Value::Bool(canonical == "check"));
+            }
+            "accept_dialog" | "dismiss_dialog" => {
+                object.entry("accept").or_insert(Value::Bool(canonical == "accept_dialog"));
+            }
+            scroll if scroll.starts_with("scroll_") => {
+                let direction = scroll.trim_start_matches("scroll_").to_string();
+                object.entry("direction").or_insert(Value::String(direction));
+            }
+            _ => {}
+        }
+        canonical = target.to_string();
+    }
+
+    if canonical != given {
+        report.push(format!("read tool `{}` as `{}`", given, canonical));
+        object.insert("tool".to_string(), Value::String(canonical));
+    }
+}
+
+fn flatten_selector(object: &mut Map<String, Value>, report: &mut Vec<String>) {
+    let navigates = object.get("tool").and_then(Value::as_str) == Some("navigate");
+    for key in SELECTOR_KEYS {
+        match object.get(*key) {
+            Some(Value::Object(selector)) => {
+                let selector = selector.clone();
+                object.remove(*key);
+                for (field, value) in selector {
+                    object.entry(to_snake_case(&field)).or_insert(value);
+                }
+                report.push(format!("flattened the `{}` object into the action", key));
+            }
+            Some(Value::Number(id)) if *key == "element" => {
+                let id = Value::Number(id.clone());
+                object.remove(*key);
+                object.entry("element_id").or_insert(id);
+                report.push("read `element` as `element_id`".to_string());
+            }
+            // `target` is the URL of a navigation
+            Some(Value::String(selector)) if !(navigates && *key == "target") => {
+                let selector = selector.clone();
+                object.remove(*key);
+                match playwright_role(&selector) {
+                    Some((role, name)) => {
+                        object.entry("role").or_insert(Value::String(role));
+                        if let Some(name) = name {
+                            object.entry("name").or_insert(Value::String(name));
+                        }
+                        report.push(format!("read `{}` as a role selector", key));
+                    }
+                    // A bare `target` or `element` string is more often a name than CSS
+                    None if matches!(*key, "selector" | "locator") => {
+                        object.entry("css_fallback").or_insert(Value::String(selector));
+                        report.push(format!("read `{}` as `css_fallback`", key));
+                    }
+                    None => {
+                        object.insert(key.to_string(), Value::String(selector));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `role=button[name="Save"]` → ("button", Some("Save"))
+fn playwright_role(selector: &str) -> Option<(String, Option<String>)> {
+    let rest = selector.trim().strip_prefix("role=")?;
+    let (role, attributes) = match rest.split_once('[') {
+        Some((role, attributes)) => (role, Some(attributes.trim_end_matches(']'))),
+        None => (rest, None),
+    };
+    if role.is_empty() || !role.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let name = attributes.and_then(|attributes| attributes.strip_prefix("name=")).map(|name| {
+        // Drop Playwright's case flag, as in name="save" i
+        let name = name.trim();
+        let name = name.strip_suffix(" i").or_else(|| name.strip_suffix(" s")).unwrap_or(name);
+        name.trim().trim_matches(['"', '\'']).to_string()
+    });
+    Some((role.to_string(), name))
+}
+
+fn convert_scalars(object: &mut Map<String, Value>, report: &mut Vec<String>) {
+    for field in NUMBER_FIELDS {
+        if let Some(Value::String(text)) = object.get(*field) {
+            if let Ok(number) = serde_json::from_str::<serde_json::Number>(text.trim()) {
+                object.insert(field.to_string(), Value::Number(number));
+                report.push(format!("read `{}` as a number", field));
+            }
+        }
+    }
+    for field in BOOL_FIELDS {
+        if let Some(Value::String(text)) = object.get(*field) {
+            let flag = match text.trim().to_lowercase().as_str() {
+                "true" | "yes" | "on" | "checked" => true,
+                "false" | "no" | "off" | "unchecked" => false,
+                _ => continue,
+            };
+            object.insert(field.to_string(), Value::Bool(flag));
+            report.push(format!("read `{}` as a boolean", field));
+        }
+    }
+}
+
+fn rename(object: &mut Map<String, Value>, from: &str, to: &str, report: &mut Vec<String>) {
+    if from == to || object.contains_key(to) {
+        return;
+    }
+    if let Some(value) = object.remove(from) {
+        object.insert(to.to_string(), value);
+        report.push(format!("renamed `{}` to `{}`", from, to));
+    }
+}
+
+fn to_snake_case(key: &str) -> String {
+    if !key.chars().any(|c| c.is_ascii_lowercase()) {
+        return key.to_lowercase();
+    }
+    let mut snake = String::with_capacity(key.len() + 4);
+    for (i, c) in key.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/// The outermost `{...}` of `raw`, e.g. inside a ```json fence
+fn embedded_object(raw: &str) -> Option<Value> {
+    let start = raw.find('{')?;
+    let end = raw.rfind('}')?;
+    if end <= start {
+        return None;
+    }
+    serde_json::from_str(&raw[start..=end]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_is_untouched() {
+        let parsed = parse_action(r#"{"tool": "type", "role": "textbox", "name": "Email", "text": "ann@example.com"}"#).unwrap();
+        assert!(parsed.normalized.is_empty());
+        assert!(matches!(parsed.action, ActionRequest::Type { .. }));
+
+        // Whatever isn't an action stays an error
+        assert!(parse_action(r#"{"tool": "type", "role": "textbox"}"#).is_err());
+        assert!(parse_action("I would click the button").is_err());
+    }
+
+    #[test]
+    fn test_near_misses() {
+        let parsed = parse_action(
+            "```json\n{\"action\": \"fill\", \"selector\": {\"role\": \"textbox\", \"name\": \"Email\"}, \"value\": \"ann@example.com\"}\n```",
+        )
+        .unwrap();
+        match parsed.action {
+            ActionRequest::Type { selector, text } => {
+                assert_eq!(selector.role, "textbox");
+                assert_eq!(selector.name.as_deref(), Some("Email"));
+                assert_eq!(text, "ann@example.com");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(
+            parsed.normalized,
+            vec![
+                "took the JSON object out of the surrounding text",
+                "renamed `action` to `tool`",
+                "read tool `fill` as `type`",
+                "flattened the `selector` object into the action",
+                "renamed `value` to `text`",
+            ]
+        );
+
+        let call = parse_action(r#"{"name": "click", "arguments": "{\"elementId\": \"12\"}"}"#).unwrap();
+        match call.action {
+            ActionRequest::Click { selector } => assert_eq!(selector.element_id, Some(12)),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let role = parse_action(r#"{"tool": "Click", "selector": "role=button[name=\"Save\"]"}"#).unwrap();
+        match role.action {
+            ActionRequest::Click { selector } => {
+                assert_eq!(selector.role, "button");
+                assert_eq!(selector.name.as_deref(), Some("Save"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let uncheck = parse_action(r#"{"tool": "uncheck", "role": "checkbox", "label": "Newsletter"}"#).unwrap();
+        assert!(matches!(uncheck.action, ActionRequest::SetChecked { checked: false, .. }));
+
+        let dialog = parse_action(r#"{"tool": "acceptDialog", "text": "3"}"#).unwrap();
+        assert!(matches!(
+            dialog.action,
+            ActionRequest::HandleDialog { accept: true, prompt_text: Some(text) } if text == "3"
+        ));
+
+        let scroll = parse_action(r#"{"tool": "scroll_down", "pixels": "600"}"#).unwrap();
+        assert!(matches!(
+            scroll.action,
+            ActionRequest::Scroll { direction: crate::models::ScrollDirection::Down, amount: Some(600), selector: None }
+        ));
+
+        let navigate = parse_action(r#"{"command": "goto", "target": "https://example.com"}"#).unwrap();
+        assert!(matches!(navigate.action, ActionRequest::Navigate { url } if url == "https://example.com"));
+    }
+}
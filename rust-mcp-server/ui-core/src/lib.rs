@@ -0,0 +1,19 @@
+//! Pure element logic shared with clients: the models of contexts and actions,
+//! tree simplification, prompt building, context diffs, page transitions,
+//! selector matching and scoring (and the equivalent finder script), lenient
+//! action parsing and the suggestions attached to failed actions
+//!
+//! Nothing here touches the browser, the network, the clock or the environment,
+//! and the only dependencies are serde, serde_json and chrono (without its
+//! clock). Keep it that way so the crate builds for `wasm32-unknown-unknown`
+//! (browser extension, SolidJS client) and gives the exact same element lists
+//! and prompts as the server.
+
+pub mod diff;
+pub mod lenient;
+pub mod models;
+pub mod prompt;
+pub mod selector;
+pub mod simplify;
+pub mod suggest;
+pub mod transition;
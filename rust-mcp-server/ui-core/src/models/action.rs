@@ -0,0 +1,444 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use super::{Assertion, ErrorCode, SemanticSelector, WebStorageArea};
+
+/// Action request from agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "tool", rename_all = "snake_case")]
+pub enum ActionRequest {
+    Click {
+        #[serde(flatten)]
+        selector: SemanticSelector,
+    },
+    Type {
+        #[serde(flatten)]
+        selector: SemanticSelector,
+        text: String,
+    },
+    /// Choose an option of a `<select>` or ARIA listbox/combobox by value or visible label
+    SelectOption {
+        #[serde(flatten)]
+        selector: SemanticSelector,
+        value: Option<String>,
+        label: Option<String>,
+    },
+    /// Make sure a checkbox, radio button or switch is checked (or unchecked); clicks only if needed
+    SetChecked {
+        #[serde(flatten)]
+        selector: SemanticSelector,
+        checked: bool,
+    },
+    /// Move keyboard focus to an element without clicking it
+    Focus {
+        #[serde(flatten)]
+        selector: SemanticSelector,
+    },
+    /// Press a key on the focused element, e.g. "Enter", "Tab", "ArrowDown" or "Control+a"
+    PressKey {
+        key: String,
+        /// Held while the key is pressed (in addition to any written in `key`)
+        #[serde(default)]
+        modifiers: Vec<KeyModifier>,
+    },
+    /// Scroll the window, or the scrollable container given by the selector (chat panes, long lists)
+    Scroll {
+        direction: ScrollDirection,
+        amount: Option<u32>,
+        #[serde(flatten)]
+        selector: Option<SemanticSelector>,
+    },
+    WaitForElement {
+        #[serde(flatten)]
+        selector: SemanticSelector,
+        timeout_ms: Option<u64>,
+    },
+    /// Wait for the page to finish loading, or for the next navigation (SPA route changes included)
+    WaitForNavigation {
+        timeout_ms: Option<u64>,
+    },
+    /// Wait until no request has been in flight for half a second
+    WaitForNetworkIdle {
+        timeout_ms: Option<u64>,
+    },
+    /// Wait until the URL matches a glob such as "*/orders/*"
+    WaitForUrl {
+        pattern: String,
+        timeout_ms: Option<u64>,
+    },
+    /// Wait until the page's visible text contains `text` (ignoring case)
+    WaitForText {
+        text: String,
+        timeout_ms: Option<u64>,
+    },
+    Navigate {
+        url: String,
+    },
+    /// Previous entry of the tab's history, like the browser's back button
+    GoBack,
+    /// Next entry of the tab's history
+    GoForward,
+    /// Reload the current page
+    Reload,
+    /// Open a tab, optionally at a URL, and continue in it
+    NewTab {
+        url: Option<String>,
+    },
+    /// Continue in another open tab; later actions and context reads go to it
+    SwitchTab {
+        tab_id: u32,
+    },
+    /// Close a tab (the active one by default); the tab that opened it becomes active
+    CloseTab {
+        tab_id: Option<u32>,
+    },
+    /// Search visible text on the page
+    FindText {
+        query: String,
+        /// Scroll the first match into view (default: true)
+        scroll_to_first: Option<bool>,
+    },
+    /// Zoom the page (e.g. 0.5 to fit wide dashboards into the viewport, 1.0 to reset)
+    SetZoom {
+        factor: f64,
+    },
+    /// Re-open an overlay that was closed automatically
+    RestoreOverlay {
+        overlay_id: u32,
+    },
+    /// Block until a download matching the pattern (e.g. "*.csv") has finished
+    WaitForDownload {
+        filename_pattern: Option<String>,
+        timeout_ms: Option<u64>,
+    },
+    /// Fill several fields at once, keyed by label, placeholder or name
+    FillForm {
+        fields: BTreeMap<String, String>,
+        /// Submit the form once every field was filled
+        #[serde(default)]
+        submit: bool,
+    },
+    /// Log in with a credential from the server's vault (the agent never sees the secret)
+    Login {
+        credential_name: String,
+    },
+    /// Fetch a one-time code (TOTP, email or SMS) from a configured provider
+    GetOtp {
+        provider: String,
+        timeout_ms: Option<u64>,
+        /// Type the code into the page's one-time-code field, if one is found (default: true)
+        fill: Option<bool>,
+    },
+    /// Call an operator-registered test hook of the app (e.g. reset the database)
+    InvokeHook {
+        name: String,
+        #[serde(default)]
+        args: Vec<serde_json::Value>,
+    },
+    /// Answer the next JavaScript dialog (alert, confirm, prompt) this way instead of by the session's policy
+    HandleDialog {
+        accept: bool,
+        /// Text for a `prompt()` dialog
+        prompt_text: Option<String>,
+    },
+    /// Read the current origin's localStorage (default) or sessionStorage: one key, or all of them
+    GetStorage {
+        #[serde(default)]
+        area: WebStorageArea,
+        key: Option<String>,
+    },
+    /// Set a key of the current origin's localStorage (default) or sessionStorage; a null value removes it
+    SetStorage {
+        #[serde(default)]
+        area: WebStorageArea,
+        key: String,
+        value: Option<String>,
+    },
+    /// Run JavaScript in the page and return its result; only when the `evaluate` feature is enabled
+    Evaluate {
+        /// An expression, or statements wrapped in a function; a returned promise is awaited
+        script: String,
+    },
+    /// Check the page without changing it; fails with `assertion_failed` when the check does not hold
+    Assert {
+        #[serde(flatten)]
+        assertion: Assertion,
+    },
+    /// Capture the viewport or the whole page; the image is returned base64-encoded in `details`
+    Screenshot {
+        #[serde(default)]
+        full_page: bool,
+        #[serde(default)]
+        format: ScreenshotFormat,
+        /// JPEG quality, 0-100
+        quality: Option<u8>,
+    },
+}
+
+impl ActionRequest {
+    /// Every `tool` value, for capability discovery
+    pub const TOOLS: &'static [&'static str] = &[
+        "click", "type", "select_option", "set_checked", "focus", "press_key", "scroll", "wait_for_element",
+        "wait_for_navigation", "wait_for_network_idle", "wait_for_url", "wait_for_text", "navigate", "go_back", "go_forward", "reload", "new_tab", "switch_tab", "close_tab", "find_text", "set_zoom", "restore_overlay",
+        "wait_for_download", "fill_form", "login", "get_otp", "invoke_hook", "handle_dialog", "get_storage",
+        "set_storage", "evaluate", "assert", "screenshot",
+    ];
+
+    /// The action's `tool` value
+    pub fn tool(&self) -> &'static str {
+        match self {
+            ActionRequest::Click { .. } => "click",
+            ActionRequest::Type { .. } => "type",
+            ActionRequest::SelectOption { .. } => "select_option",
+            ActionRequest::SetChecked { .. } => "set_checked",
+            ActionRequest::Focus { .. } => "focus",
+            ActionRequest::PressKey { .. } => "press_key",
+            ActionRequest::Scroll { .. } => "scroll",
+            ActionRequest::WaitForElement { .. } => "wait_for_element",
+            ActionRequest::WaitForNavigation { .. } => "wait_for_navigation",
+            ActionRequest::WaitForNetworkIdle { .. } => "wait_for_network_idle",
+            ActionRequest::WaitForUrl { .. } => "wait_for_url",
+            ActionRequest::WaitForText { .. } => "wait_for_text",
+            ActionRequest::Navigate { .. } => "navigate",
+            ActionRequest::GoBack => "go_back",
+            ActionRequest::GoForward => "go_forward",
+            ActionRequest::Reload => "reload",
+            ActionRequest::NewTab { .. } => "new_tab",
+            ActionRequest::SwitchTab { .. } => "switch_tab",
+            ActionRequest::CloseTab { .. } => "close_tab",
+            ActionRequest::FindText { .. } => "find_text",
+            ActionRequest::SetZoom { .. } => "set_zoom",
+            ActionRequest::RestoreOverlay { .. } => "restore_overlay",
+            ActionRequest::WaitForDownload { .. } => "wait_for_download",
+            ActionRequest::FillForm { .. } => "fill_form",
+            ActionRequest::Login { .. } => "login",
+            ActionRequest::GetOtp { .. } => "get_otp",
+            ActionRequest::InvokeHook { .. } => "invoke_hook",
+            ActionRequest::HandleDialog { .. } => "handle_dialog",
+            ActionRequest::GetStorage { .. } => "get_storage",
+            ActionRequest::SetStorage { .. } => "set_storage",
+            ActionRequest::Evaluate { .. } => "evaluate",
+            ActionRequest::Assert { .. } => "assert",
+            ActionRequest::Screenshot { .. } => "screenshot",
+        }
+    }
+
+    /// Semantic selector targeted by this action, if any
+    pub fn selector(&self) -> Option<&SemanticSelector> {
+        match self {
+            ActionRequest::Click { selector }
+            | ActionRequest::Type { selector, .. }
+            | ActionRequest::SelectOption { selector, .. }
+            | ActionRequest::SetChecked { selector, .. }
+            | ActionRequest::Focus { selector }
+            | ActionRequest::Scroll { selector: Some(selector), .. }
+            | ActionRequest::WaitForElement { selector, .. } => Some(selector),
+            ActionRequest::Assert { assertion } => assertion.selector(),
+            _ => None,
+        }
+    }
+
+    pub fn selector_mut(&mut self) -> Option<&mut SemanticSelector> {
+        match self {
+            ActionRequest::Click { selector }
+            | ActionRequest::Type { selector, .. }
+            | ActionRequest::SelectOption { selector, .. }
+            | ActionRequest::SetChecked { selector, .. }
+            | ActionRequest::Focus { selector }
+            | ActionRequest::Scroll { selector: Some(selector), .. }
+            | ActionRequest::WaitForElement { selector, .. } => Some(selector),
+            ActionRequest::Assert { assertion } => assertion.selector_mut(),
+            _ => None,
+        }
+    }
+
+    /// Whether the action needs the mouse; such actions fail in keyboard-only sessions
+    pub fn uses_pointer(&self) -> bool {
+        match self {
+            ActionRequest::Click { .. }
+            | ActionRequest::Scroll { .. }
+            | ActionRequest::FillForm { .. }
+            | ActionRequest::Login { .. }
+            // Listbox options are clicked
+            | ActionRequest::SelectOption { .. }
+            | ActionRequest::SetChecked { .. } => true,
+            // Filling the code clicks into the field
+            ActionRequest::GetOtp { fill, .. } => fill.unwrap_or(true),
+            _ => false,
+        }
+    }
+}
+
+/// Modifier held while a key is pressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyModifier {
+    Alt,
+    #[serde(alias = "ctrl")]
+    Control,
+    #[serde(alias = "cmd")]
+    Meta,
+    Shift,
+}
+
+impl KeyModifier {
+    /// Parse a modifier as written in key combinations ("Ctrl", "shift", "Cmd", ...)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "alt" | "option" => Some(KeyModifier::Alt),
+            "control" | "ctrl" => Some(KeyModifier::Control),
+            "meta" | "cmd" | "command" => Some(KeyModifier::Meta),
+            "shift" => Some(KeyModifier::Shift),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyModifier::Alt => "Alt",
+            KeyModifier::Control => "Control",
+            KeyModifier::Meta => "Meta",
+            KeyModifier::Shift => "Shift",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenshotFormat {
+    #[default]
+    Png,
+    Jpeg,
+}
+
+impl ScreenshotFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Png => "image/png",
+            ScreenshotFormat::Jpeg => "image/jpeg",
+        }
+    }
+}
+
+/// Smart feedback response (Solution C)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionResponse {
+    pub success: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorCode>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
+    /// Smart suggestion for recovery
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+
+    /// Additional context
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+
+    /// Times the server re-ran the action after a transient failure (detached or briefly hidden element)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_retries: Option<u32>,
+}
+
+impl ActionResponse {
+    pub fn success() -> Self {
+        Self {
+            success: true,
+            error: None,
+            reason: None,
+            suggestion: None,
+            details: None,
+            local_retries: None,
+        }
+    }
+
+    pub fn success_with_details(details: serde_json::Value) -> Self {
+        Self {
+            details: Some(details),
+            ..Self::success()
+        }
+    }
+
+    pub fn error_with_suggestion(error: ErrorCode, reason: &str, suggestion: &str) -> Self {
+        Self {
+            success: false,
+            error: Some(error),
+            reason: Some(reason.to_string()),
+            suggestion: Some(suggestion.to_string()),
+            details: None,
+            local_retries: None,
+        }
+    }
+
+    /// The action typed into a password field (its details say `"secret": true`)
+    pub fn is_secret(&self) -> bool {
+        self.details
+            .as_ref()
+            .and_then(|details| details.get("secret"))
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scroll_container_is_optional() {
+        let window: ActionRequest = serde_json::from_value(serde_json::json!({
+            "tool": "scroll",
+            "direction": "down"
+        }))
+        .unwrap();
+        assert!(window.selector().is_none());
+
+        let container: ActionRequest = serde_json::from_value(serde_json::json!({
+            "tool": "scroll",
+            "direction": "down",
+            "role": "log",
+            "name": "Messages"
+        }))
+        .unwrap();
+        assert_eq!(container.selector().and_then(|s| s.name.as_deref()), Some("Messages"));
+    }
+
+    #[test]
+    fn test_assert_takes_the_selector_of_its_check() {
+        let action: ActionRequest = serde_json::from_value(serde_json::json!({
+            "tool": "assert",
+            "check": "element_text_equals",
+            "element_id": 12,
+            "text": "Paid"
+        }))
+        .unwrap();
+        assert_eq!(action.selector().and_then(|s| s.element_id), Some(12));
+
+        let url: ActionRequest =
+            serde_json::from_value(serde_json::json!({ "tool": "assert", "check": "url_matches", "pattern": "*/orders/*" }))
+                .unwrap();
+        assert!(url.selector().is_none());
+    }
+
+    #[test]
+    fn test_tools_are_action_variants() {
+        for tool in ActionRequest::TOOLS {
+            // Fields may be missing, but the tag has to be known
+            if let Err(e) = serde_json::from_value::<ActionRequest>(serde_json::json!({ "tool": tool })) {
+                assert!(!e.to_string().contains("unknown variant"), "{}: {}", tool, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+use super::SemanticSelector;
+
+/// A deterministic check of the page, for `assert` actions and run success criteria
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "check", rename_all = "snake_case")]
+pub enum Assertion {
+    ElementExists {
+        #[serde(flatten)]
+        selector: SemanticSelector,
+    },
+    /// The element's visible text is `text`, with runs of whitespace counting as one space
+    ElementTextEquals {
+        #[serde(flatten)]
+        selector: SemanticSelector,
+        text: String,
+    },
+    ElementTextContains {
+        #[serde(flatten)]
+        selector: SemanticSelector,
+        text: String,
+    },
+    /// The URL matches a glob such as "*/orders/*"
+    UrlMatches { pattern: String },
+    TitleContains { text: String },
+}
+
+impl Assertion {
+    /// The assertion's `check` value
+    pub fn name(&self) -> &'static str {
+        match self {
+            Assertion::ElementExists { .. } => "element_exists",
+            Assertion::ElementTextEquals { .. } => "element_text_equals",
+            Assertion::ElementTextContains { .. } => "element_text_contains",
+            Assertion::UrlMatches { .. } => "url_matches",
+            Assertion::TitleContains { .. } => "title_contains",
+        }
+    }
+
+    pub fn selector(&self) -> Option<&SemanticSelector> {
+        match self {
+            Assertion::ElementExists { selector }
+            | Assertion::ElementTextEquals { selector, .. }
+            | Assertion::ElementTextContains { selector, .. } => Some(selector),
+            _ => None,
+        }
+    }
+
+    pub fn selector_mut(&mut self) -> Option<&mut SemanticSelector> {
+        match self {
+            Assertion::ElementExists { selector }
+            | Assertion::ElementTextEquals { selector, .. }
+            | Assertion::ElementTextContains { selector, .. } => Some(selector),
+            _ => None,
+        }
+    }
+
+    /// What the assertion expects, e.g. "title contains 'Orders'"
+    pub fn describe(&self) -> String {
+        let target = |selector: &SemanticSelector| match &selector.name {
+            Some(name) => format!("{} '{}'", selector.role, name),
+            None => selector.role.clone(),
+        };
+        match self {
+            Assertion::ElementExists { selector } => format!("{} exists", target(selector)),
+            Assertion::ElementTextEquals { selector, text } => format!("{} reads '{}'", target(selector), text),
+            Assertion::ElementTextContains { selector, text } => format!("{} contains '{}'", target(selector), text),
+            Assertion::UrlMatches { pattern } => format!("URL matches '{}'", pattern),
+            Assertion::TitleContains { text } => format!("title contains '{}'", text),
+        }
+    }
+}
+
+/// Whether an element's text passes a text assertion; `None` for the other assertions
+///
+/// Both sides are compared with whitespace collapsed, since the visible text
+/// of an element depends on how its children are laid out.
+pub fn text_assertion_holds(assertion: &Assertion, actual: &str) -> Option<bool> {
+    let collapse = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+    match assertion {
+        Assertion::ElementTextEquals { text, .. } => Some(collapse(actual) == collapse(text)),
+        Assertion::ElementTextContains { text, .. } => Some(collapse(actual).contains(&collapse(text))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selector(role: &str) -> SemanticSelector {
+        SemanticSelector {
+            role: role.to_string(),
+            name: None,
+            description: None,
+            css_fallback: None,
+            frame: vec![],
+            element_id: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_assertion() {
+        let assertion: Assertion = serde_json::from_value(serde_json::json!({
+            "check": "element_text_contains",
+            "role": "status",
+            "text": "Order placed"
+        }))
+        .unwrap();
+        assert_eq!(assertion.name(), "element_text_contains");
+        assert_eq!(assertion.selector().map(|s| s.role.as_str()), Some("status"));
+        assert_eq!(assertion.describe(), "status contains 'Order placed'");
+    }
+
+    #[test]
+    fn test_text_assertion_holds() {
+        let equals = |text: &str| Assertion::ElementTextEquals {
+            selector: selector("heading"),
+            text: text.to_string(),
+        };
+        assert_eq!(text_assertion_holds(&equals("Total: $120.00"), "Total:\n  $120.00 "), Some(true));
+        assert_eq!(text_assertion_holds(&equals("Total"), "Total: $120.00"), Some(false));
+        // Case matters
+        assert_eq!(text_assertion_holds(&equals("total: $120.00"), "Total: $120.00"), Some(false));
+
+        let contains = Assertion::ElementTextContains {
+            selector: selector("status"),
+            text: "3 items".to_string(),
+        };
+        assert_eq!(text_assertion_holds(&contains, "Cart:  3\titems"), Some(true));
+        assert_eq!(text_assertion_holds(&contains, "Cart: 13 items"), Some(true));
+        assert_eq!(text_assertion_holds(&contains, "Cart: 2 items"), Some(false));
+
+        let title = Assertion::TitleContains { text: "Orders".to_string() };
+        assert_eq!(text_assertion_holds(&title, "Orders"), None);
+    }
+}
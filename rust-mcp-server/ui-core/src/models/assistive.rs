@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+use super::ElementRect;
+
+/// Display settings used by people with low vision, emulated for accessibility checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssistiveSetting {
+    /// Forced colors (Windows high contrast) with `prefers-contrast: more`
+    HighContrast,
+    /// All text at 200%, as required by WCAG 1.4.4 (Resize Text)
+    TextZoom,
+}
+
+/// Where an interactive element is drawn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementLayout {
+    /// ID in the context extracted from the same page
+    pub id: usize,
+    pub role: String,
+    pub name: Option<String>,
+    pub visible: bool,
+    pub rect: ElementRect,
+}
+
+impl ElementLayout {
+    /// Drawn with a non-empty box
+    fn shown(&self) -> bool {
+        self.visible && self.rect.width > 0.0 && self.rect.height > 0.0
+    }
+
+    /// Boxes share more than a hairline
+    fn overlaps(&self, other: &ElementLayout) -> bool {
+        // Adjacent elements commonly touch or overlap by a border's width
+        const TOLERANCE: f64 = 2.0;
+
+        let width = (self.rect.x + self.rect.width).min(other.rect.x + other.rect.width)
+            - self.rect.x.max(other.rect.x);
+        let height = (self.rect.y + self.rect.height).min(other.rect.y + other.rect.height)
+            - self.rect.y.max(other.rect.y);
+        width > TOLERANCE && height > TOLERANCE
+    }
+}
+
+/// Two interactive elements drawn on top of each other
+#[derive(Debug, Clone, Serialize)]
+pub struct LayoutOverlap {
+    pub first: ElementLayout,
+    pub second: ElementLayout,
+}
+
+/// Interactive elements that stop working visually under one setting
+#[derive(Debug, Clone, Serialize)]
+pub struct AssistiveCheck {
+    pub setting: AssistiveSetting,
+
+    /// Shown normally, but hidden, collapsed or removed under the setting (normal layout)
+    pub disappeared: Vec<ElementLayout>,
+
+    /// Pairs that only overlap under the setting (layout under the setting)
+    pub overlapping: Vec<LayoutOverlap>,
+}
+
+impl AssistiveCheck {
+    /// Compare the interactive elements of the normal page with the page under `setting`
+    pub fn compare(setting: AssistiveSetting, baseline: &[ElementLayout], emulated: &[ElementLayout]) -> Self {
+        let find = |layouts: &[ElementLayout], id: usize| layouts.iter().find(|l| l.id == id).cloned();
+
+        let disappeared = baseline
+            .iter()
+            .filter(|before| before.shown())
+            .filter(|before| !find(emulated, before.id).is_some_and(|after| after.shown()))
+            .cloned()
+            .collect();
+
+        let shown: Vec<&ElementLayout> = emulated.iter().filter(|l| l.shown()).collect();
+        let mut overlapping = Vec::new();
+        for (i, first) in shown.iter().enumerate() {
+            for second in &shown[i + 1..] {
+                if !first.overlaps(second) {
+                    continue;
+                }
+                // Nested or stacked by design, e.g. a clear button inside a search box
+                let overlapped_before = match (find(baseline, first.id), find(baseline, second.id)) {
+                    (Some(a), Some(b)) => a.overlaps(&b),
+                    _ => false,
+                };
+                if !overlapped_before {
+                    overlapping.push(LayoutOverlap {
+                        first: (*first).clone(),
+                        second: (*second).clone(),
+                    });
+                }
+            }
+        }
+
+        Self {
+            setting,
+            disappeared,
+            overlapping,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(id: usize, visible: bool, x: f64, y: f64, width: f64, height: f64) -> ElementLayout {
+        ElementLayout {
+            id,
+            role: "button".to_string(),
+            name: Some(format!("Button {}", id)),
+            visible,
+            rect: ElementRect { x, y, width, height },
+        }
+    }
+
+    #[test]
+    fn test_compare_reports_disappeared_elements() {
+        let baseline = vec![
+            layout(0, true, 0.0, 0.0, 80.0, 30.0),
+            layout(1, true, 100.0, 0.0, 80.0, 30.0),
+            layout(2, true, 200.0, 0.0, 80.0, 30.0),
+            layout(3, false, 300.0, 0.0, 80.0, 30.0),
+        ];
+        let emulated = vec![
+            layout(0, true, 0.0, 0.0, 80.0, 30.0),
+            layout(1, false, 100.0, 0.0, 80.0, 30.0),
+            layout(3, false, 300.0, 0.0, 80.0, 30.0),
+        ];
+
+        let check = AssistiveCheck::compare(AssistiveSetting::HighContrast, &baseline, &emulated);
+        let ids: Vec<usize> = check.disappeared.iter().map(|l| l.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert!(check.overlapping.is_empty());
+    }
+
+    #[test]
+    fn test_compare_reports_new_overlaps_only() {
+        let baseline = vec![
+            layout(0, true, 0.0, 0.0, 80.0, 30.0),
+            layout(1, true, 90.0, 0.0, 80.0, 30.0),
+            // Stacked on purpose before and after
+            layout(2, true, 200.0, 0.0, 80.0, 30.0),
+            layout(3, true, 250.0, 5.0, 20.0, 20.0),
+        ];
+        let emulated = vec![
+            layout(0, true, 0.0, 0.0, 160.0, 60.0),
+            layout(1, true, 90.0, 0.0, 160.0, 60.0),
+            layout(2, true, 300.0, 0.0, 160.0, 60.0),
+            layout(3, true, 400.0, 10.0, 40.0, 40.0),
+        ];
+
+        let check = AssistiveCheck::compare(AssistiveSetting::TextZoom, &baseline, &emulated);
+        assert_eq!(check.overlapping.len(), 1);
+        assert_eq!((check.overlapping[0].first.id, check.overlapping[0].second.id), (0, 1));
+    }
+}
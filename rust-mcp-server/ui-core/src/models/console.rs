@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A `console.*` call or an uncaught exception on the page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsoleEntry {
+    pub timestamp: DateTime<Utc>,
+
+    /// The console method ("log", "warning", "error", ...), or "exception" for uncaught errors
+    pub level: String,
+
+    pub text: String,
+
+    /// Script that logged or threw, when the browser knows it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// 1-based line in that script
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<i64>,
+}
+
+impl ConsoleEntry {
+    pub const EXCEPTION: &'static str = "exception";
+
+    /// Errors, failed assertions and uncaught exceptions
+    pub fn is_error(&self) -> bool {
+        matches!(self.level.as_str(), "error" | "assert" | Self::EXCEPTION)
+    }
+
+    /// One line for prompts and logs, e.g. "TypeError: cart is undefined (https://shop.example.com/app.js:42)"
+    pub fn summary(&self) -> String {
+        match (&self.url, self.line) {
+            (Some(url), Some(line)) => format!("{} ({}:{})", self.text, url, line),
+            (Some(url), None) => format!("{} ({})", self.text, url),
+            _ => self.text.clone(),
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+
+/// The main content of a page, without navigation, sidebars and footers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageContent {
+    pub url: String,
+    pub title: String,
+
+    /// Author line, from `<meta name="author">`, `rel="author"` or a byline element
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub byline: Option<String>,
+
+    pub blocks: Vec<ContentBlock>,
+}
+
+/// One block of readable content, in document order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Heading { level: u8, spans: Vec<TextSpan> },
+    Paragraph { spans: Vec<TextSpan> },
+    /// `depth` counts enclosing lists, 0 for a top-level item
+    ListItem { ordered: bool, depth: u8, spans: Vec<TextSpan> },
+    Quote { spans: Vec<TextSpan> },
+    Code {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+    },
+    Image { alt: String, src: String },
+}
+
+/// A run of text with the same inline formatting
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextSpan {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub href: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strong: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub emphasis: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub code: bool,
+}
+
+impl PageContent {
+    /// Plain text: one block per paragraph, list items with "- ", no images
+    pub fn to_text(&self) -> String {
+        let blocks = self.blocks.iter().filter_map(|block| match block {
+            ContentBlock::Heading { spans, .. } | ContentBlock::Paragraph { spans } | ContentBlock::Quote { spans } => {
+                Some(plain(spans))
+            }
+            ContentBlock::ListItem { depth, spans, .. } => {
+                Some(format!("{}- {}", "  ".repeat(*depth as usize), plain(spans)))
+            }
+            ContentBlock::Code { text, .. } => Some(text.trim_end().to_string()),
+            ContentBlock::Image { .. } => None,
+        });
+        join_blocks(blocks)
+    }
+
+    /// CommonMark with the title as a top-level heading unless the content starts with one
+    pub fn to_markdown(&self) -> String {
+        let starts_with_title = matches!(self.blocks.first(), Some(ContentBlock::Heading { level: 1, .. }));
+        let title = (!starts_with_title && !self.title.is_empty()).then(|| format!("# {}", self.title));
+
+        let blocks = self.blocks.iter().map(|block| match block {
+            ContentBlock::Heading { level, spans } => {
+                format!("{} {}", "#".repeat((*level).clamp(1, 6) as usize), markdown(spans))
+            }
+            ContentBlock::Paragraph { spans } => markdown(spans),
+            ContentBlock::ListItem { ordered, depth, spans } => {
+                let marker = if *ordered { "1." } else { "-" };
+                format!("{}{} {}", "   ".repeat(*depth as usize), marker, markdown(spans))
+            }
+            ContentBlock::Quote { spans } => format!("> {}", markdown(spans)),
+            ContentBlock::Code { text, language } => {
+                format!("```{}\n{}\n```", language.as_deref().unwrap_or(""), text.trim_end())
+            }
+            ContentBlock::Image { alt, src } => format!("![{}]({})", alt, src),
+        });
+        join_blocks(title.into_iter().chain(blocks))
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| match block {
+                ContentBlock::Heading { spans, .. }
+                | ContentBlock::Paragraph { spans }
+                | ContentBlock::ListItem { spans, .. }
+                | ContentBlock::Quote { spans } => plain(spans).split_whitespace().count(),
+                ContentBlock::Code { text, .. } => text.split_whitespace().count(),
+                ContentBlock::Image { .. } => 0,
+            })
+            .sum()
+    }
+}
+
+fn plain(spans: &[TextSpan]) -> String {
+    spans.iter().map(|span| span.text.as_str()).collect::<String>().trim().to_string()
+}
+
+fn markdown(spans: &[TextSpan]) -> String {
+    let rendered: String = spans
+        .iter()
+        .map(|span| {
+            let text = span.text.trim();
+            if text.is_empty() {
+                return span.text.clone();
+            }
+            let mut inline = if span.code { format!("`{}`", text) } else { escape(text) };
+            if span.emphasis {
+                inline = format!("*{}*", inline);
+            }
+            if span.strong {
+                inline = format!("**{}**", inline);
+            }
+            if let Some(href) = &span.href {
+                inline = format!("[{}]({})", inline, href);
+            }
+            // Keep the spacing around the span outside its markers
+            let leading = &span.text[..span.text.len() - span.text.trim_start().len()];
+            let trailing = &span.text[span.text.trim_end().len()..];
+            format!("{}{}{}", leading, inline, trailing)
+        })
+        .collect();
+    rendered.trim().to_string()
+}
+
+/// Backslash-escape characters that would start inline formatting
+fn escape(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| match c {
+            '*' | '_' | '`' | '[' | ']' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+/// Blocks separated by blank lines, except list items following each other
+fn join_blocks(blocks: impl Iterator<Item = String>) -> String {
+    let mut out = String::new();
+    let mut previous_item = false;
+    for block in blocks.filter(|block| !block.trim().is_empty()) {
+        let item = is_list_item(&block);
+        if !out.is_empty() {
+            out.push_str(if item && previous_item { "\n" } else { "\n\n" });
+        }
+        out.push_str(&block);
+        previous_item = item;
+    }
+    out
+}
+
+fn is_list_item(block: &str) -> bool {
+    let block = block.trim_start();
+    block.starts_with("- ") || block.starts_with("1. ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(text: &str) -> TextSpan {
+        TextSpan { text: text.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_render() {
+        let content = PageContent {
+            url: "https://blog.example.com/rust-1-80".to_string(),
+            title: "Rust 1.80 released".to_string(),
+            byline: Some("Ann Lee".to_string()),
+            blocks: vec![
+                ContentBlock::Paragraph {
+                    spans: vec![
+                        span("The release adds "),
+                        TextSpan { code: true, ..span("LazyLock") },
+                        span(" and "),
+                        TextSpan { href: Some("https://doc.rust-lang.org".to_string()), strong: true, ..span("exclusive ranges ") },
+                        span("in patterns_1."),
+                    ],
+                },
+                ContentBlock::Heading { level: 2, spans: vec![span("Upgrading")] },
+                ContentBlock::ListItem { ordered: true, depth: 0, spans: vec![span("Run rustup update")] },
+                ContentBlock::ListItem { ordered: false, depth: 1, spans: vec![span("stable only")] },
+                ContentBlock::Code { text: "rustup update stable\n".to_string(), language: Some("bash".to_string()) },
+                ContentBlock::Image { alt: "Ferris".to_string(), src: "/ferris.png".to_string() },
+            ],
+        };
+
+        assert_eq!(
+            content.to_markdown(),
+            "# Rust 1.80 released\n\n\
+             The release adds `LazyLock` and [**exclusive ranges**](https://doc.rust-lang.org) in patterns\\_1.\n\n\
+             ## Upgrading\n\n\
+             1. Run rustup update\n   - stable only\n\n\
+             ```bash\nrustup update stable\n```\n\n\
+             ![Ferris](/ferris.png)"
+        );
+        assert_eq!(
+            content.to_text(),
+            "The release adds LazyLock and exclusive ranges in patterns_1.\n\n\
+             Upgrading\n\n\
+             - Run rustup update\n  - stable only\n\n\
+             rustup update stable"
+        );
+        assert_eq!(content.word_count(), 18);
+    }
+}
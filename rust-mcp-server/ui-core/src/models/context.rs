@@ -0,0 +1,435 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{ParsedDate, TabInfo};
+
+/// Accessibility Tree Element - simplified representation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AXElement {
+    /// Simple numeric ID for referencing in LLM prompts
+    pub id: usize,
+
+    /// ARIA role (button, link, textbox, etc.)
+    pub role: String,
+
+    /// Accessible name/label
+    pub name: Option<String>,
+
+    /// Element value (for inputs)
+    pub value: Option<String>,
+
+    /// Additional description
+    pub description: Option<String>,
+
+    /// Is the element visible/enabled
+    pub enabled: bool,
+    pub visible: bool,
+
+    /// Position information (for scroll/viewport checks)
+    pub rect: Option<ElementRect>,
+
+    /// WAI-ARIA states (expanded, selected, checked, pressed, current)
+    #[serde(default)]
+    pub states: AriaStates,
+
+    /// Iframe path to the element's document; empty in the top document
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frame: Vec<usize>,
+
+    /// Child elements; only filled in by the tree view (`?format=tree`)
+    pub children: Vec<AXElement>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// WAI-ARIA widget states
+///
+/// `checked` and `pressed` are tri-state in ARIA ("true" | "false" | "mixed"),
+/// so they are kept as strings. `current` holds the aria-current token
+/// ("page", "step", "location", ...).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AriaStates {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expanded: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checked: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressed: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+
+    /// Field rejected by validation (aria-invalid, or a constraint the user broke)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invalid: Option<bool>,
+}
+
+impl AriaStates {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Short flags for prompts, e.g. "expanded, selected"
+    pub fn summary(&self) -> String {
+        let mut flags = Vec::new();
+
+        if let Some(expanded) = self.expanded {
+            flags.push(if expanded { "expanded".to_string() } else { "collapsed".to_string() });
+        }
+        if self.selected == Some(true) {
+            flags.push("selected".to_string());
+        }
+        match self.checked.as_deref() {
+            Some("true") => flags.push("checked".to_string()),
+            Some("false") => flags.push("unchecked".to_string()),
+            Some("mixed") => flags.push("partially checked".to_string()),
+            _ => {}
+        }
+        match self.pressed.as_deref() {
+            Some("true") => flags.push("pressed".to_string()),
+            Some("mixed") => flags.push("partially pressed".to_string()),
+            _ => {}
+        }
+        if let Some(current) = &self.current {
+            flags.push(format!("current={}", current));
+        }
+        if self.invalid == Some(true) {
+            flags.push("invalid".to_string());
+        }
+
+        flags.join(", ")
+    }
+}
+
+/// Simplified context for LLM
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UIContext {
+    /// Current page URL
+    pub url: String,
+
+    /// Current page title
+    pub title: String,
+
+    /// Viewport dimensions
+    pub viewport: Viewport,
+
+    /// Simplified element list (flattened AXTree)
+    pub elements: Vec<SimplifiedElement>,
+
+    /// Recently visited pages in this session, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub navigation_history: Vec<NavigationEntry>,
+
+    /// Overlays (newsletter modals, chat widgets) closed automatically; can be restored
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub closed_overlays: Vec<ClosedOverlay>,
+
+    /// The session's tabs, when more than one is open
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tabs: Vec<TabInfo>,
+
+    /// Element that currently has keyboard focus; `None` when focus is on the page itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focused: Option<FocusedElement>,
+}
+
+impl UIContext {
+    /// Page identity and element lines only, for live updates
+    pub fn compact(&self) -> CompactContext {
+        CompactContext {
+            url: self.url.clone(),
+            title: self.title.clone(),
+            elements: self.elements.iter().map(|e| e.display.clone()).collect(),
+        }
+    }
+}
+
+/// Lightweight UI context pushed to live clients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactContext {
+    pub url: String,
+    pub title: String,
+
+    /// Element display lines, e.g. "[1] Button('Login')"
+    pub elements: Vec<String>,
+}
+
+/// The element keyboard input currently goes to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusedElement {
+    /// ID in the current context, if the element is part of it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element_id: Option<usize>,
+
+    pub role: String,
+    pub name: Option<String>,
+
+    /// Inside an open modal dialog, where keeping focus contained is intended
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub in_modal: bool,
+}
+
+/// An obstructing overlay that was hidden by the auto-close heuristics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosedOverlay {
+    /// ID used by the `restore_overlay` action
+    pub id: u32,
+
+    pub kind: OverlayKind,
+
+    /// Short human-readable label (vendor name or heading text)
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayKind {
+    Newsletter,
+    ChatWidget,
+}
+
+/// One entry of the session's navigation breadcrumb
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NavigationEntry {
+    pub url: String,
+    pub title: Option<String>,
+    pub visited_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Viewport {
+    pub width: u32,
+    pub height: u32,
+    pub scroll_x: f64,
+    pub scroll_y: f64,
+}
+
+/// Simplified element for LLM context (easier to parse)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimplifiedElement {
+    /// Unique ID in this context
+    pub id: usize,
+
+    /// Format: "[1] Button('Login')" or "[2] Textbox('Username')"
+    pub display: String,
+
+    /// Full semantic selector for execution
+    pub selector: SemanticSelector,
+
+    /// Is this element in viewport?
+    pub in_viewport: bool,
+
+    /// WAI-ARIA states, so the agent knows e.g. a menu is already open
+    #[serde(default, skip_serializing_if = "AriaStates::is_empty")]
+    pub states: AriaStates,
+
+    /// Date or time found in the element's name, normalized to ISO-8601
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parsed: Option<ParsedDate>,
+
+    /// Bounding box in CSS pixels, relative to the top window's viewport
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rect: Option<ElementRect>,
+
+    /// Current value of text fields, selects and sliders; password values are masked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+
+    /// Disabled, natively or through aria-disabled
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+}
+
+/// Surroundings of an element, used to disambiguate similar targets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementNeighborhood {
+    pub element_id: usize,
+    pub role: String,
+    pub name: Option<String>,
+
+    /// Enclosing landmarks/containers, innermost first (form, dialog, navigation, ...)
+    pub ancestors: Vec<NeighborElement>,
+
+    /// Closest preceding headings, nearest first
+    pub headings: Vec<String>,
+
+    /// Other elements sharing the same container
+    pub siblings: Vec<NeighborElement>,
+
+    /// Visible text around the element (trimmed)
+    pub nearby_text: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeighborElement {
+    pub role: String,
+    pub name: Option<String>,
+
+    /// ID in the current context, if the element is part of it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element_id: Option<usize>,
+}
+
+/// Semantic selector - describes how to find an element
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SelectorFields")]
+pub struct SemanticSelector {
+    /// Empty when the selector only gives `element_id`
+    pub role: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+
+    /// Fallback CSS selector if semantic search fails
+    pub css_fallback: Option<String>,
+
+    /// Iframe path to the element: index among the `iframe`/`frame` elements of
+    /// the top document, then of that frame's document, and so on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frame: Vec<usize>,
+
+    /// ID of the element in the session's latest context; picks exactly that
+    /// element instead of matching role and name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element_id: Option<usize>,
+}
+
+/// A selector as sent by clients, which may leave out `role` when it gives `element_id`
+#[derive(Deserialize)]
+struct SelectorFields {
+    role: Option<String>,
+    name: Option<String>,
+    description: Option<String>,
+    css_fallback: Option<String>,
+    #[serde(default)]
+    frame: Vec<usize>,
+    element_id: Option<usize>,
+}
+
+impl TryFrom<SelectorFields> for SemanticSelector {
+    type Error = &'static str;
+
+    fn try_from(fields: SelectorFields) -> Result<Self, Self::Error> {
+        if fields.role.is_none() && fields.element_id.is_none() {
+            return Err("missing field `role` (or `element_id`)");
+        }
+        Ok(Self {
+            role: fields.role.unwrap_or_default(),
+            name: fields.name,
+            description: fields.description,
+            css_fallback: fields.css_fallback,
+            frame: fields.frame,
+            element_id: fields.element_id,
+        })
+    }
+}
+
+/// Longer values are cut in display strings; `value` keeps them whole
+const MAX_DISPLAY_VALUE_CHARS: usize = 60;
+
+/// Roles of the controls a user operates: ranked first in trimmed prompts, measured by the
+/// assistive checks and boxed in Set-of-Mark screenshots
+pub const INTERACTIVE_ROLES: &[&str] = &[
+    "button", "link", "textbox", "searchbox", "combobox", "listbox", "checkbox", "radio", "switch",
+    "slider", "tab", "menuitem", "option",
+];
+
+impl SimplifiedElement {
+    pub fn new(id: usize, role: &str, name: Option<&str>, in_viewport: bool) -> Self {
+        let mut element = Self {
+            id,
+            display: String::new(),
+            selector: SemanticSelector {
+                role: role.to_string(),
+                name: name.map(|s| s.to_string()),
+                description: None,
+                css_fallback: None,
+                frame: Vec::new(),
+                element_id: None,
+            },
+            in_viewport,
+            states: AriaStates::default(),
+            parsed: None,
+            rect: None,
+            value: None,
+            disabled: false,
+        };
+        element.display = element.render_display();
+        element
+    }
+
+    /// Attach ARIA states and refresh the display string
+    pub fn with_states(mut self, states: AriaStates) -> Self {
+        self.states = states;
+        self.display = self.render_display();
+        self
+    }
+
+    /// Attach the form state (current value, disabled) and refresh the display string
+    pub fn with_form_state(mut self, value: Option<String>, disabled: bool) -> Self {
+        self.value = value;
+        self.disabled = disabled;
+        self.display = self.render_display();
+        self
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        INTERACTIVE_ROLES.contains(&self.selector.role.as_str())
+    }
+
+    /// Place the element inside an iframe and refresh the display string
+    pub fn with_frame(mut self, frame: Vec<usize>) -> Self {
+        self.selector.frame = frame;
+        self.display = self.render_display();
+        self
+    }
+
+    /// Format: "[1] Button('Login')", "[4] Tab('Billing') [selected]", "[2] Textbox('Email') = 'a@b.co'"
+    /// or "[9] Button('Pay') [disabled] [frame=0]"
+    fn render_display(&self) -> String {
+        let mut chars = self.selector.role.chars();
+        let role = match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+            None => String::new(),
+        };
+
+        let mut display = if let Some(n) = &self.selector.name {
+            format!("[{}] {}('{}')", self.id, role, n)
+        } else {
+            format!("[{}] {}", self.id, role)
+        };
+
+        // Fields without a label are named by their value already
+        if let Some(value) = self.value.as_ref().filter(|v| Some(*v) != self.selector.name.as_ref()) {
+            let shown: String = value.chars().take(MAX_DISPLAY_VALUE_CHARS).collect();
+            let ellipsis = if shown.len() < value.len() { "..." } else { "" };
+            display.push_str(&format!(" = '{}{}'", shown, ellipsis));
+        }
+
+        let mut summary = self.states.summary();
+        if self.disabled {
+            summary = if summary.is_empty() { "disabled".to_string() } else { format!("disabled, {}", summary) };
+        }
+        if !summary.is_empty() {
+            display.push_str(&format!(" [{}]", summary));
+        }
+
+        let frame = &self.selector.frame;
+        if !frame.is_empty() {
+            let path: Vec<String> = frame.iter().map(|i| i.to_string()).collect();
+            display.push_str(&format!(" [frame={}]", path.join(".")));
+        }
+
+        display
+    }
+}
@@ -0,0 +1,474 @@
+use chrono::{FixedOffset, NaiveDate, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+/// How to read ambiguous numeric dates such as 03/04/2025
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    /// 03/04/2025 is 3 April
+    Dmy,
+    /// 03/04/2025 is March 4 (US English, and the default for pages without a language)
+    Mdy,
+    /// 2025/03/04
+    Ymd,
+}
+
+impl DateOrder {
+    /// Order implied by a BCP 47 language tag, e.g. the page's `<html lang>`
+    pub fn from_lang(lang: &str) -> Self {
+        let lang = lang.trim().to_ascii_lowercase().replace('_', "-");
+        let primary = lang.split('-').next().unwrap_or_default();
+
+        match primary {
+            "" => Self::Mdy,
+            "en" if lang == "en" || lang == "en-us" || lang == "en-ph" => Self::Mdy,
+            "ja" | "zh" | "ko" | "hu" | "lt" | "mn" => Self::Ymd,
+            _ => Self::Dmy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateKind {
+    Date,
+    DateTime,
+    Time,
+}
+
+/// A date or time recognized in free text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedDate {
+    /// The matched text, as it appears on the page
+    pub text: String,
+
+    /// ISO-8601: `2025-03-04`, `2025-03-04T15:45:00` (with an offset when the text has one) or `15:45:00`
+    pub iso: String,
+
+    pub kind: DateKind,
+}
+
+/// Month names and abbreviations (English, German, French, Spanish)
+const MONTHS: &[(&str, u32)] = &[
+    ("january", 1), ("jan", 1), ("januar", 1), ("janvier", 1), ("janv", 1), ("enero", 1), ("ene", 1),
+    ("february", 2), ("feb", 2), ("februar", 2), ("février", 2), ("fevrier", 2), ("févr", 2), ("fevr", 2),
+    ("febrero", 2),
+    ("march", 3), ("mar", 3), ("märz", 3), ("maerz", 3), ("mär", 3), ("mrz", 3), ("mars", 3), ("marzo", 3),
+    ("april", 4), ("apr", 4), ("avril", 4), ("avr", 4), ("abril", 4), ("abr", 4),
+    ("may", 5), ("mai", 5), ("mayo", 5),
+    ("june", 6), ("jun", 6), ("juni", 6), ("juin", 6), ("junio", 6),
+    ("july", 7), ("jul", 7), ("juli", 7), ("juillet", 7), ("juil", 7), ("julio", 7),
+    ("august", 8), ("aug", 8), ("août", 8), ("aout", 8), ("agosto", 8), ("ago", 8),
+    ("september", 9), ("sep", 9), ("sept", 9), ("septembre", 9), ("septiembre", 9), ("setiembre", 9),
+    ("october", 10), ("oct", 10), ("oktober", 10), ("okt", 10), ("octobre", 10), ("octubre", 10),
+    ("november", 11), ("nov", 11), ("novembre", 11), ("noviembre", 11),
+    ("december", 12), ("dec", 12), ("dezember", 12), ("dez", 12), ("décembre", 12), ("decembre", 12),
+    ("déc", 12), ("diciembre", 12), ("dic", 12),
+];
+
+/// Words allowed between the parts of a written date ("4 de marzo de 2025", "the 4th of March")
+const FILLERS: &[&str] = &["de", "of", "del"];
+
+/// Words allowed between a date and its time ("on March 4 at 3pm", "am 4. März um 15:00")
+const TIME_JOINERS: &[&str] = &["at", "um", "à", "a", "las", "t"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Number { value: u32, digits: usize },
+    Word(String),
+    Punct(char),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+    /// Directly follows the previous token, without whitespace
+    joined: bool,
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    let mut joined = false;
+
+    while let Some((start, c)) = chars.next() {
+        if c.is_whitespace() {
+            joined = false;
+            continue;
+        }
+
+        let mut end = start + c.len_utf8();
+        let kind = if c.is_ascii_digit() {
+            while let Some(&(i, next)) = chars.peek() {
+                if !next.is_ascii_digit() {
+                    break;
+                }
+                end = i + next.len_utf8();
+                chars.next();
+            }
+            let digits = &text[start..end];
+            // Long digit runs are IDs or amounts, never date parts
+            let value = digits.parse().unwrap_or(u32::MAX);
+            TokenKind::Number { value, digits: digits.len() }
+        } else if c.is_alphabetic() {
+            while let Some(&(i, next)) = chars.peek() {
+                if !next.is_alphabetic() {
+                    break;
+                }
+                end = i + next.len_utf8();
+                chars.next();
+            }
+            TokenKind::Word(text[start..end].to_lowercase())
+        } else {
+            TokenKind::Punct(c)
+        };
+
+        tokens.push(Token { kind, start, end, joined });
+        joined = true;
+    }
+
+    tokens
+}
+
+/// Cursor over tokens with small matching helpers
+struct Cursor<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn number(&mut self, digits: std::ops::RangeInclusive<usize>) -> Option<u32> {
+        match self.peek()?.kind {
+            TokenKind::Number { value, digits: n } if digits.contains(&n) => {
+                self.pos += 1;
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// A number that must touch the previous token, e.g. the "04" in "03/04"
+    fn joined_number(&mut self, digits: std::ops::RangeInclusive<usize>) -> Option<u32> {
+        if !self.peek()?.joined {
+            return None;
+        }
+        self.number(digits)
+    }
+
+    fn punct(&mut self, accepted: &[char]) -> Option<char> {
+        match self.peek()?.kind {
+            TokenKind::Punct(c) if accepted.contains(&c) => {
+                self.pos += 1;
+                Some(c)
+            }
+            _ => None,
+        }
+    }
+
+    fn joined_punct(&mut self, accepted: &[char]) -> Option<char> {
+        if !self.peek()?.joined {
+            return None;
+        }
+        self.punct(accepted)
+    }
+
+    fn word_in(&mut self, words: &[&str]) -> bool {
+        match &self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Word(w)) if words.contains(&w.as_str()) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn month(&mut self) -> Option<u32> {
+        let TokenKind::Word(word) = &self.peek()?.kind else {
+            return None;
+        };
+        let month = MONTHS.iter().find(|(name, _)| name == word)?.1;
+        self.pos += 1;
+        // Abbreviations may end with a dot: "Mar. 4"
+        if self.peek().is_some_and(|t| t.joined) {
+            self.punct(&['.']);
+        }
+        Some(month)
+    }
+
+    /// Day number with an optional ordinal suffix or dot: "4", "4th", "4."
+    fn day(&mut self) -> Option<u32> {
+        let day = self.number(1..=2)?;
+        if self.peek().is_some_and(|t| t.joined) && !self.word_in(&["st", "nd", "rd", "th", "er", "e"]) {
+            self.punct(&['.']);
+        }
+        Some(day)
+    }
+
+    /// Skip optional punctuation/filler words between written date parts
+    fn skip_fillers(&mut self) {
+        loop {
+            if self.punct(&[',']).is_none() && !self.word_in(FILLERS) {
+                return;
+            }
+        }
+    }
+}
+
+fn expand_year(year: u32, digits: usize) -> i32 {
+    match digits {
+        2 if year < 70 => 2000 + year as i32,
+        2 => 1900 + year as i32,
+        _ => year as i32,
+    }
+}
+
+/// Numeric date: 2025-03-04, 04.03.2025, 3/4/25
+fn numeric_date(cursor: &mut Cursor, order: DateOrder) -> Option<NaiveDate> {
+    let start = cursor.pos;
+
+    // Year first is unambiguous with a four-digit year
+    if let Some(year) = cursor.number(4..=4) {
+        let sep = cursor.joined_punct(&['-', '/', '.'])?;
+        let month = cursor.joined_number(1..=2)?;
+        cursor.joined_punct(&[sep])?;
+        let day = cursor.joined_number(1..=2)?;
+        return NaiveDate::from_ymd_opt(year as i32, month, day);
+    }
+
+    cursor.pos = start;
+    let first = cursor.number(1..=2)?;
+    let sep = cursor.joined_punct(&['-', '/', '.'])?;
+    let second = cursor.joined_number(1..=2)?;
+    cursor.joined_punct(&[sep])?;
+    let year_token = cursor.peek()?.kind.clone();
+    let year = cursor.joined_number(2..=4)?;
+    let TokenKind::Number { digits, .. } = year_token else {
+        return None;
+    };
+    if digits == 3 {
+        return None;
+    }
+    let year = expand_year(year, digits);
+
+    let (month, day) = match order {
+        DateOrder::Mdy => (first, second),
+        DateOrder::Dmy | DateOrder::Ymd => (second, first),
+    };
+
+    // When only the other reading is a valid date, it is not ambiguous
+    NaiveDate::from_ymd_opt(year, month, day).or_else(|| NaiveDate::from_ymd_opt(year, day, month))
+}
+
+/// Written date: "March 4, 2025", "4 March 2025", "4. März 2025", "4 de marzo de 2025"
+fn written_date(cursor: &mut Cursor) -> Option<NaiveDate> {
+    let start = cursor.pos;
+
+    if let Some(month) = cursor.month() {
+        let day = cursor.day()?;
+        cursor.skip_fillers();
+        let year = cursor.number(4..=4)?;
+        return NaiveDate::from_ymd_opt(year as i32, month, day);
+    }
+
+    cursor.pos = start;
+    let day = cursor.day()?;
+    cursor.skip_fillers();
+    let month = cursor.month()?;
+    cursor.skip_fillers();
+    let year = cursor.number(4..=4)?;
+    NaiveDate::from_ymd_opt(year as i32, month, day)
+}
+
+/// Clock time: "15:45", "3:45:10 PM", "3pm", "15h30"
+fn time(cursor: &mut Cursor) -> Option<NaiveTime> {
+    let start = cursor.pos;
+    let hour = cursor.number(1..=2)?;
+
+    let (minute, second) = if cursor.joined_punct(&[':']).is_some() || cursor.peek().is_some_and(|t| {
+        t.joined && t.kind == TokenKind::Word("h".to_string())
+    }) && cursor.word_in(&["h"])
+    {
+        let minute = cursor.joined_number(2..=2)?;
+        let second = match cursor.joined_punct(&[':']) {
+            Some(_) => Some(cursor.joined_number(2..=2)?),
+            None => None,
+        };
+        (minute, second.unwrap_or(0))
+    } else {
+        (0, 0)
+    };
+    let has_minutes = cursor.pos > start + 1;
+
+    let meridiem = if cursor.word_in(&["am"]) {
+        Some(false)
+    } else if cursor.word_in(&["pm"]) {
+        Some(true)
+    } else {
+        None
+    };
+
+    let hour = match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        // A bare number is not a time
+        None if !has_minutes => return None,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+/// UTC offset after a time: "Z", "UTC", "+02:00", "-0500"
+fn offset(cursor: &mut Cursor) -> Option<FixedOffset> {
+    let start = cursor.pos;
+    if cursor.word_in(&["z", "utc", "gmt"]) {
+        // "GMT+2" style offsets follow the zone name
+        if let Some(offset) = offset(cursor) {
+            return Some(offset);
+        }
+        return FixedOffset::east_opt(0);
+    }
+
+    // Must touch the time: "3:45 PM - 5:00 PM" is a range, not an offset
+    let sign = match cursor.joined_punct(&['+', '-', '−'])? {
+        '+' => 1,
+        _ => -1,
+    };
+    let offset = match cursor.peek()?.kind {
+        TokenKind::Number { value, digits: 4 } => {
+            cursor.pos += 1;
+            FixedOffset::east_opt(sign * ((value / 100) * 3600 + (value % 100) * 60) as i32)
+        }
+        TokenKind::Number { value, digits: 1..=2 } => {
+            cursor.pos += 1;
+            let minutes = match cursor.joined_punct(&[':']) {
+                Some(_) => cursor.joined_number(2..=2),
+                None => Some(0),
+            };
+            minutes.and_then(|m| FixedOffset::east_opt(sign * (value * 3600 + m * 60) as i32))
+        }
+        _ => None,
+    };
+
+    if offset.is_none() {
+        cursor.pos = start;
+    }
+    offset
+}
+
+/// Date, date-time or time starting at the cursor
+fn parse_at(cursor: &mut Cursor, order: DateOrder) -> Option<(String, DateKind)> {
+    let start = cursor.pos;
+
+    let date = numeric_date(cursor, order).or_else(|| {
+        cursor.pos = start;
+        written_date(cursor)
+    });
+
+    let Some(date) = date else {
+        cursor.pos = start;
+        return time(cursor).map(|t| (t.format("%H:%M:%S").to_string(), DateKind::Time));
+    };
+
+    // Optional time after the date
+    let date_end = cursor.pos;
+    cursor.punct(&[',']);
+    cursor.word_in(TIME_JOINERS);
+    let Some(time) = time(cursor) else {
+        cursor.pos = date_end;
+        return Some((date.format("%Y-%m-%d").to_string(), DateKind::Date));
+    };
+
+    let naive = date.and_time(time);
+    let iso = match offset(cursor) {
+        Some(offset) => match offset.from_local_datetime(&naive).single() {
+            Some(datetime) => datetime.to_rfc3339(),
+            None => naive.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        },
+        None => naive.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    };
+    Some((iso, DateKind::DateTime))
+}
+
+/// First date or time in `text`, normalized to ISO-8601
+///
+/// Recognizes ISO and numeric dates, written dates in English, German, French
+/// and Spanish, and clock times with optional AM/PM and UTC offset. `order`
+/// decides how ambiguous numeric dates such as 03/04/2025 are read.
+pub fn parse_date(text: &str, order: DateOrder) -> Option<ParsedDate> {
+    let tokens = tokenize(text);
+
+    for start in 0..tokens.len() {
+        // Don't start inside a number run like "12345/6/7" or a word
+        if start > 0 && tokens[start].joined && !matches!(tokens[start - 1].kind, TokenKind::Punct(_)) {
+            continue;
+        }
+
+        let mut cursor = Cursor { tokens: &tokens, pos: start };
+        if let Some((iso, kind)) = parse_at(&mut cursor, order) {
+            let end = tokens[cursor.pos - 1].end;
+            return Some(ParsedDate {
+                text: text[tokens[start].start..end].to_string(),
+                iso,
+                kind,
+            });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iso(text: &str, order: DateOrder) -> Option<String> {
+        parse_date(text, order).map(|parsed| parsed.iso)
+    }
+
+    #[test]
+    fn test_parse_dates() {
+        use DateOrder::*;
+
+        assert_eq!(iso("Due 2025-03-04", Dmy).as_deref(), Some("2025-03-04"));
+        assert_eq!(iso("03/04/2025", Mdy).as_deref(), Some("2025-03-04"));
+        assert_eq!(iso("03/04/2025", Dmy).as_deref(), Some("2025-04-03"));
+        assert_eq!(iso("13/04/2025", Mdy).as_deref(), Some("2025-04-13"));
+        assert_eq!(iso("04.03.25", Dmy).as_deref(), Some("2025-03-04"));
+        assert_eq!(iso("Tuesday, March 4th, 2025", Dmy).as_deref(), Some("2025-03-04"));
+        assert_eq!(iso("Mar. 4 2025", Dmy).as_deref(), Some("2025-03-04"));
+        assert_eq!(iso("am 4. März 2025", Dmy).as_deref(), Some("2025-03-04"));
+        assert_eq!(iso("4 de marzo de 2025", Dmy).as_deref(), Some("2025-03-04"));
+        assert_eq!(iso("le 4 mars 2025 à 15h30", Dmy).as_deref(), Some("2025-03-04T15:30:00"));
+        assert_eq!(iso("Mar 4, 2025 at 3:45 PM", Dmy).as_deref(), Some("2025-03-04T15:45:00"));
+        assert_eq!(
+            iso("2025-03-04T15:45:00+02:00", Dmy).as_deref(),
+            Some("2025-03-04T15:45:00+02:00")
+        );
+        assert_eq!(iso("Opens at 9am", Dmy).as_deref(), Some("09:00:00"));
+        assert_eq!(iso("Updated 10:05", Dmy).as_deref(), Some("10:05:00"));
+
+        assert_eq!(iso("Add 3 items", Dmy), None);
+        assert_eq!(iso("Order 12345/6/7", Dmy), None);
+        assert_eq!(iso("Version 2.1.0", Dmy), None);
+        assert_eq!(iso("31/02/2025", Dmy), None);
+    }
+
+    #[test]
+    fn test_matched_text_and_order() {
+        let parsed = parse_date("Invoice from Mar 4, 2025 (paid)", DateOrder::Mdy).unwrap();
+        assert_eq!(parsed.text, "Mar 4, 2025");
+        assert_eq!(parsed.kind, DateKind::Date);
+
+        assert_eq!(DateOrder::from_lang("en-US"), DateOrder::Mdy);
+        assert_eq!(DateOrder::from_lang("en-GB"), DateOrder::Dmy);
+        assert_eq!(DateOrder::from_lang("de"), DateOrder::Dmy);
+        assert_eq!(DateOrder::from_lang("ja-JP"), DateOrder::Ymd);
+        assert_eq!(DateOrder::from_lang(""), DateOrder::Mdy);
+    }
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// How a session answers the page's `alert`, `confirm`, `prompt` and `beforeunload` dialogs
+///
+/// Dialogs block the page until they are answered, so every dialog gets an
+/// answer right away; the default accepts them all.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DialogPolicy {
+    /// OK (true) or Cancel (false)
+    #[serde(default = "default_accept")]
+    pub accept: bool,
+
+    /// Text entered into `prompt()` dialogs when accepting; the dialog's own default when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_text: Option<String>,
+}
+
+fn default_accept() -> bool {
+    true
+}
+
+impl Default for DialogPolicy {
+    fn default() -> Self {
+        Self {
+            accept: true,
+            prompt_text: None,
+        }
+    }
+}
+
+/// A dialog the page opened, and how it was answered
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DialogRecord {
+    /// "alert", "confirm", "prompt" or "beforeunload"
+    pub kind: String,
+
+    pub message: String,
+
+    /// Page that opened the dialog
+    pub url: String,
+
+    pub accepted: bool,
+
+    /// Text entered into a `prompt()` dialog
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_text: Option<String>,
+
+    /// Answered as a `handle_dialog` action asked, rather than by the session's policy
+    #[serde(default)]
+    pub by_action: bool,
+}
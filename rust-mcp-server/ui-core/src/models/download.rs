@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadState {
+    InProgress,
+    Completed,
+    Canceled,
+}
+
+/// A file downloaded by the session's browser, retrievable as an artifact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadArtifact {
+    /// Artifact ID (the browser's download GUID)
+    pub id: String,
+
+    /// File name suggested by the server
+    pub filename: String,
+
+    /// URL the file was downloaded from
+    pub url: String,
+
+    pub state: DownloadState,
+
+    pub size_bytes: u64,
+
+    pub started_at: DateTime<Utc>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
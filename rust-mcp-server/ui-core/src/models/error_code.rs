@@ -0,0 +1,361 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Machine-readable cause of a failed action (`ActionResponse.error`) or a turned-away API request
+///
+/// Serialized in snake_case. The codes are stable across releases, unlike the
+/// wording of `reason` and `message`, so clients should switch on them.
+/// `GET /capabilities` lists every code with its description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    // Finding and using elements
+    ElementNotFound,
+    ElementNotVisible,
+    ElementNotEnabled,
+    ElementObstructed,
+    NotCheckable,
+    CannotUncheckRadio,
+    NotFocusable,
+    NotFocused,
+    NotScrollable,
+    NotAList,
+    OptionNotFound,
+    OptionDisabled,
+    InvalidOption,
+    ValueMismatch,
+    StateUnchanged,
+    TextNotFound,
+    OverlayNotFound,
+    FocusTrap,
+    UnsupportedKey,
+
+    // Forms and login
+    FormNotFound,
+    FormFieldsFailed,
+    InvalidFields,
+    CredentialNotFound,
+    LoginFieldsNotFound,
+    PasswordFieldNotFound,
+    LoginFailed,
+    LoginUnverified,
+    OtpProviderNotFound,
+    OtpProviderError,
+
+    // Navigation and page
+    InvalidUrl,
+    NoHistory,
+    InvalidZoom,
+    InvalidQuality,
+    InvalidQuery,
+    AuthRequired,
+    ClientCertificateRequired,
+    Timeout,
+
+    // Tabs
+    TabNotFound,
+    LastTab,
+
+    // Downloads
+    DownloadsUnavailable,
+    DownloadCanceled,
+
+    // Dialogs
+    DialogsUnavailable,
+
+    // Web storage
+    StorageUnavailable,
+
+    // Scripts
+    ScriptFailed,
+
+    // Assertions
+    AssertionFailed,
+
+    // Test hooks
+    HookNotFound,
+    HookNotAllowed,
+    HookUnavailable,
+    HookFailed,
+
+    // Session rules
+    KeyboardOnly,
+    UrlNotAllowed,
+    ReadOnly,
+    ActionBlocked,
+    FeatureDisabled,
+
+    ExecutionError,
+
+    // API requests
+    RateLimited,
+    InvalidRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    PayloadTooLarge,
+    IdempotencyKeyReused,
+    Unavailable,
+    InternalError,
+
+    /// A code this build does not know, e.g. in a run stored by a newer server
+    #[serde(other)]
+    Unknown,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 66] = [
+        Self::ElementNotFound,
+        Self::ElementNotVisible,
+        Self::ElementNotEnabled,
+        Self::ElementObstructed,
+        Self::NotCheckable,
+        Self::CannotUncheckRadio,
+        Self::NotFocusable,
+        Self::NotFocused,
+        Self::NotScrollable,
+        Self::NotAList,
+        Self::OptionNotFound,
+        Self::OptionDisabled,
+        Self::InvalidOption,
+        Self::ValueMismatch,
+        Self::StateUnchanged,
+        Self::TextNotFound,
+        Self::OverlayNotFound,
+        Self::FocusTrap,
+        Self::UnsupportedKey,
+        Self::FormNotFound,
+        Self::FormFieldsFailed,
+        Self::InvalidFields,
+        Self::CredentialNotFound,
+        Self::LoginFieldsNotFound,
+        Self::PasswordFieldNotFound,
+        Self::LoginFailed,
+        Self::LoginUnverified,
+        Self::OtpProviderNotFound,
+        Self::OtpProviderError,
+        Self::InvalidUrl,
+        Self::NoHistory,
+        Self::InvalidZoom,
+        Self::InvalidQuality,
+        Self::InvalidQuery,
+        Self::AuthRequired,
+        Self::ClientCertificateRequired,
+        Self::Timeout,
+        Self::TabNotFound,
+        Self::LastTab,
+        Self::DownloadsUnavailable,
+        Self::DownloadCanceled,
+        Self::DialogsUnavailable,
+        Self::StorageUnavailable,
+        Self::ScriptFailed,
+        Self::AssertionFailed,
+        Self::HookNotFound,
+        Self::HookNotAllowed,
+        Self::HookUnavailable,
+        Self::HookFailed,
+        Self::KeyboardOnly,
+        Self::UrlNotAllowed,
+        Self::ReadOnly,
+        Self::ActionBlocked,
+        Self::FeatureDisabled,
+        Self::ExecutionError,
+        Self::RateLimited,
+        Self::InvalidRequest,
+        Self::Unauthorized,
+        Self::Forbidden,
+        Self::NotFound,
+        Self::Conflict,
+        Self::PayloadTooLarge,
+        Self::IdempotencyKeyReused,
+        Self::Unavailable,
+        Self::InternalError,
+        Self::Unknown,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ElementNotFound => "element_not_found",
+            Self::ElementNotVisible => "element_not_visible",
+            Self::ElementNotEnabled => "element_not_enabled",
+            Self::ElementObstructed => "element_obstructed",
+            Self::NotCheckable => "not_checkable",
+            Self::CannotUncheckRadio => "cannot_uncheck_radio",
+            Self::NotFocusable => "not_focusable",
+            Self::NotFocused => "not_focused",
+            Self::NotScrollable => "not_scrollable",
+            Self::NotAList => "not_a_list",
+            Self::OptionNotFound => "option_not_found",
+            Self::OptionDisabled => "option_disabled",
+            Self::InvalidOption => "invalid_option",
+            Self::ValueMismatch => "value_mismatch",
+            Self::StateUnchanged => "state_unchanged",
+            Self::TextNotFound => "text_not_found",
+            Self::OverlayNotFound => "overlay_not_found",
+            Self::FocusTrap => "focus_trap",
+            Self::UnsupportedKey => "unsupported_key",
+            Self::FormNotFound => "form_not_found",
+            Self::FormFieldsFailed => "form_fields_failed",
+            Self::InvalidFields => "invalid_fields",
+            Self::CredentialNotFound => "credential_not_found",
+            Self::LoginFieldsNotFound => "login_fields_not_found",
+            Self::PasswordFieldNotFound => "password_field_not_found",
+            Self::LoginFailed => "login_failed",
+            Self::LoginUnverified => "login_unverified",
+            Self::OtpProviderNotFound => "otp_provider_not_found",
+            Self::OtpProviderError => "otp_provider_error",
+            Self::InvalidUrl => "invalid_url",
+            Self::NoHistory => "no_history",
+            Self::InvalidZoom => "invalid_zoom",
+            Self::InvalidQuality => "invalid_quality",
+            Self::InvalidQuery => "invalid_query",
+            Self::AuthRequired => "auth_required",
+            Self::ClientCertificateRequired => "client_certificate_required",
+            Self::Timeout => "timeout",
+            Self::TabNotFound => "tab_not_found",
+            Self::LastTab => "last_tab",
+            Self::DownloadsUnavailable => "downloads_unavailable",
+            Self::DownloadCanceled => "download_canceled",
+            Self::DialogsUnavailable => "dialogs_unavailable",
+            Self::StorageUnavailable => "storage_unavailable",
+            Self::ScriptFailed => "script_failed",
+            Self::AssertionFailed => "assertion_failed",
+            Self::HookNotFound => "hook_not_found",
+            Self::HookNotAllowed => "hook_not_allowed",
+            Self::HookUnavailable => "hook_unavailable",
+            Self::HookFailed => "hook_failed",
+            Self::KeyboardOnly => "keyboard_only",
+            Self::UrlNotAllowed => "url_not_allowed",
+            Self::ReadOnly => "read_only",
+            Self::ActionBlocked => "action_blocked",
+            Self::FeatureDisabled => "feature_disabled",
+            Self::ExecutionError => "execution_error",
+            Self::RateLimited => "rate_limited",
+            Self::InvalidRequest => "invalid_request",
+            Self::Unauthorized => "unauthorized",
+            Self::Forbidden => "forbidden",
+            Self::NotFound => "not_found",
+            Self::Conflict => "conflict",
+            Self::PayloadTooLarge => "payload_too_large",
+            Self::IdempotencyKeyReused => "idempotency_key_reused",
+            Self::Unavailable => "unavailable",
+            Self::InternalError => "internal_error",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// What the code means, for client SDKs and docs
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::ElementNotFound => "No element matches the selector",
+            Self::ElementNotVisible => "The element exists but is hidden or outside the viewport",
+            Self::ElementNotEnabled => "The element is disabled",
+            Self::ElementObstructed => "A dialog or overlay covers the element",
+            Self::NotCheckable => "The element is not a checkbox, radio button or switch",
+            Self::CannotUncheckRadio => "A radio button can only be unchecked by checking another one",
+            Self::NotFocusable => "The element cannot take keyboard focus",
+            Self::NotFocused => "Focus did not land on the element",
+            Self::NotScrollable => "The element does not scroll",
+            Self::NotAList => "The element is not a select or listbox",
+            Self::OptionNotFound => "The list has no option with that label or value",
+            Self::OptionDisabled => "The option is disabled",
+            Self::InvalidOption => "The action names no option",
+            Self::ValueMismatch => "The field holds a different value than the one typed",
+            Self::StateUnchanged => "The action ran but the element kept its state",
+            Self::TextNotFound => "The text does not occur on the page",
+            Self::OverlayNotFound => "No closable overlay is open",
+            Self::FocusTrap => "Tabbing keeps cycling without reaching the target",
+            Self::UnsupportedKey => "The key name is not recognized",
+            Self::FormNotFound => "The filled fields are not inside a form that can be submitted",
+            Self::FormFieldsFailed => "Some form fields could not be filled",
+            Self::InvalidFields => "The action gives no fields to fill",
+            Self::CredentialNotFound => "The session has no credential with that name",
+            Self::LoginFieldsNotFound => "No username and password fields were found",
+            Self::PasswordFieldNotFound => "No password field appeared after the username step",
+            Self::LoginFailed => "The site reported an error after logging in",
+            Self::LoginUnverified => "The login form is still showing after submitting",
+            Self::OtpProviderNotFound => "The session has no one-time code source with that name",
+            Self::OtpProviderError => "The one-time code source failed",
+            Self::InvalidUrl => "The URL is malformed or uses an unsupported scheme",
+            Self::NoHistory => "There is no page to go back or forward to",
+            Self::InvalidZoom => "The zoom level is out of range",
+            Self::InvalidQuality => "The screenshot quality is out of range or does not fit the format",
+            Self::InvalidQuery => "The search query is empty",
+            Self::AuthRequired => "The page asked for HTTP credentials the server does not have",
+            Self::ClientCertificateRequired => "The site requires a TLS client certificate",
+            Self::Timeout => "An element, one-time code or download did not arrive in time",
+            Self::TabNotFound => "No open tab has that ID",
+            Self::LastTab => "The session's only tab cannot be closed",
+            Self::DownloadsUnavailable => "Download tracking is not running in this session",
+            Self::DownloadCanceled => "The download was canceled",
+            Self::DialogsUnavailable => "Dialog handling is not running in this session",
+            Self::StorageUnavailable => "The page has no web storage (e.g. about:blank) or the storage is full",
+            Self::ScriptFailed => "The script threw an error or its promise was rejected",
+            Self::AssertionFailed => "The page did not meet the assertion",
+            Self::HookNotFound => "No test hook with that name is registered",
+            Self::HookNotAllowed => "The test hook may not run for this page's origin",
+            Self::HookUnavailable => "The page does not define the function the test hook calls",
+            Self::HookFailed => "The test hook threw an error",
+            Self::KeyboardOnly => "The session only accepts keyboard actions",
+            Self::UrlNotAllowed => "The session policy does not allow that URL",
+            Self::ReadOnly => "The session policy forbids actions that change data",
+            Self::ActionBlocked => "The session policy forbids the action",
+            Self::FeatureDisabled => "The action needs a feature the operator has not enabled",
+            Self::ExecutionError => "The browser failed to carry out the action",
+            Self::RateLimited => "Too many requests; retry after the given delay",
+            Self::InvalidRequest => "The request has missing or invalid parameters",
+            Self::Unauthorized => "The request carries no valid token",
+            Self::Forbidden => "The caller may not do this",
+            Self::NotFound => "No such session, run or other resource, or it belongs to another user",
+            Self::Conflict => "The resource's current state does not allow the request, or the name is taken",
+            Self::PayloadTooLarge => "The request body is too large",
+            Self::IdempotencyKeyReused => "The Idempotency-Key was already used with a different request body",
+            Self::Unavailable => "A part of the server the request needs is not configured or not running",
+            Self::InternalError => "The server failed to handle the request",
+            Self::Unknown => "A code this server version does not know",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An error code with its meaning, as listed by `GET /capabilities`
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorCodeInfo {
+    pub code: ErrorCode,
+    pub description: &'static str,
+}
+
+impl ErrorCodeInfo {
+    pub fn all() -> Vec<ErrorCodeInfo> {
+        ErrorCode::ALL
+            .into_iter()
+            .map(|code| ErrorCodeInfo {
+                code,
+                description: code.description(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_match_serde() {
+        for code in ErrorCode::ALL {
+            assert_eq!(serde_json::to_value(code).unwrap(), code.name(), "{:?}", code);
+            assert_eq!(serde_json::from_value::<ErrorCode>(code.name().into()).unwrap(), code);
+        }
+        assert_eq!(
+            serde_json::from_value::<ErrorCode>("added_in_a_later_release".into()).unwrap(),
+            ErrorCode::Unknown
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+/// A form on the page and the state of its fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageForm {
+    /// Accessible name, legend or heading of the form; absent for fields outside any form
+    pub name: Option<String>,
+
+    /// Where and how the form submits; absent for script-handled and loose fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+
+    /// Iframe path of the form's document; empty in the top document
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frame: Vec<usize>,
+
+    pub fields: Vec<FormField>,
+
+    /// Buttons that submit the form
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub submit_buttons: Vec<FormButton>,
+}
+
+/// One input, textarea, select or custom field of a form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormField {
+    /// ID in the current context, for actions that target `element_id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element_id: Option<usize>,
+
+    /// Input type ("text", "email", "password", "checkbox", ...), "textarea", "select" or the ARIA role
+    pub kind: String,
+
+    /// Visible label or accessible name
+    pub label: Option<String>,
+
+    /// `name` attribute, the key the form submits the value under
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Current value; password values are masked, selects give the chosen option's text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+
+    /// State of checkboxes and radio buttons
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checked: Option<bool>,
+
+    /// Option texts of selects
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autocomplete: Option<String>,
+
+    pub required: bool,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+
+    /// Passes the browser's constraint validation and isn't marked aria-invalid
+    ///
+    /// A required field that is still empty is not valid, even before the
+    /// user has touched it.
+    pub valid: bool,
+
+    /// Why the field is invalid: the page's own error message, or the browser's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation_message: Option<String>,
+}
+
+/// A button that submits its form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormButton {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element_id: Option<usize>,
+
+    pub label: Option<String>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+}
@@ -0,0 +1,33 @@
+pub mod action;
+pub mod assertion;
+pub mod assistive;
+pub mod console;
+pub mod content;
+pub mod context;
+pub mod dates;
+pub mod dialog;
+pub mod download;
+pub mod error_code;
+pub mod form;
+pub mod network;
+pub mod scrape;
+pub mod tab;
+pub mod table;
+pub mod web_storage;
+
+pub use action::*;
+pub use assertion::*;
+pub use assistive::*;
+pub use console::*;
+pub use content::*;
+pub use context::*;
+pub use dates::*;
+pub use dialog::*;
+pub use download::*;
+pub use error_code::*;
+pub use form::*;
+pub use network::*;
+pub use scrape::*;
+pub use tab::*;
+pub use table::*;
+pub use web_storage::*;
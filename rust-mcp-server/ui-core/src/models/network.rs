@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How a session handles requests whose URL matches `url_pattern`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkRule {
+    /// Assigned when the rule is added
+    #[serde(default)]
+    pub id: String,
+
+    /// Glob over the full URL: `*` matches any run of characters, `?` one character
+    pub url_pattern: String,
+
+    /// Only requests with this method (e.g. "POST"); any method when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+
+    #[serde(flatten)]
+    pub action: NetworkRuleAction,
+
+    /// Requests the rule has handled so far
+    #[serde(default)]
+    pub hits: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum NetworkRuleAction {
+    /// Fail the request as blocked by the client
+    Block,
+
+    /// Send the request changed: to another URL, with headers set, or with another body
+    Rewrite {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        headers: BTreeMap<String, String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        body: Option<String>,
+    },
+
+    /// Answer with a canned response, without contacting the server
+    Stub {
+        #[serde(default = "default_status")]
+        status: u16,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        headers: BTreeMap<String, String>,
+        /// A string is sent as it is, any other value as JSON
+        #[serde(default)]
+        body: serde_json::Value,
+        /// Wait this long before answering, e.g. to test loading states
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        delay_ms: Option<u64>,
+    },
+}
+
+fn default_status() -> u16 {
+    200
+}
+
+impl NetworkRule {
+    /// Why the rule can't be used, if it can't
+    pub fn validate(&self) -> Result<(), String> {
+        if self.url_pattern.trim().is_empty() {
+            return Err("url_pattern must not be empty".to_string());
+        }
+        match &self.action {
+            NetworkRuleAction::Stub { status, .. } if !(100..=599).contains(status) => {
+                Err(format!("status {} is not an HTTP status", status))
+            }
+            NetworkRuleAction::Rewrite { url: Some(url), .. }
+                if !url.starts_with("http://") && !url.starts_with("https://") =>
+            {
+                Err(format!("rewrite url must be an absolute http(s) URL: {}", url))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl NetworkRuleAction {
+    /// Body bytes and default content type of a stubbed response
+    pub fn stub_body(body: &serde_json::Value) -> (Vec<u8>, Option<&'static str>) {
+        match body {
+            serde_json::Value::Null => (Vec::new(), None),
+            serde_json::Value::String(text) => (text.clone().into_bytes(), Some("text/plain; charset=utf-8")),
+            value => (value.to_string().into_bytes(), Some("application/json")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_rule() {
+        let rule: NetworkRule = serde_json::from_value(serde_json::json!({
+            "url_pattern": "https://api.example.com/prices*",
+            "method": "get",
+            "action": "stub",
+            "body": { "price": 12 }
+        }))
+        .unwrap();
+
+        assert_eq!(rule.method.as_deref(), Some("get"));
+        assert!(rule.validate().is_ok());
+
+        let NetworkRuleAction::Stub { status, body, .. } = &rule.action else {
+            panic!("expected a stub");
+        };
+        assert_eq!(*status, 200);
+        assert_eq!(
+            NetworkRuleAction::stub_body(body),
+            (br#"{"price":12}"#.to_vec(), Some("application/json"))
+        );
+
+        let block: NetworkRule =
+            serde_json::from_value(serde_json::json!({ "url_pattern": "", "action": "block" })).unwrap();
+        assert_eq!(block.action, NetworkRuleAction::Block);
+        assert!(block.validate().is_err());
+    }
+}
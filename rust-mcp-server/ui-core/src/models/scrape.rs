@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Page content likely to hold structured data, gathered for schema extraction
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageCandidates {
+    pub url: String,
+    pub title: String,
+
+    /// Parsed `<script type="application/ld+json">` blocks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub json_ld: Vec<serde_json::Value>,
+
+    /// OpenGraph, product and description `<meta>` tags by property
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub meta: BTreeMap<String, String>,
+
+    /// Runs of similar sibling elements, e.g. product cards or search results
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<CandidateGroup>,
+
+    /// Visible text of the main content, cut to a budget
+    pub text: String,
+}
+
+/// Similar siblings under one parent, each a likely record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidateGroup {
+    /// Short CSS path of the parent, e.g. "main > ul.results"
+    pub container: String,
+    pub items: Vec<CandidateItem>,
+}
+
+/// One record-like element: its text plus the links and images it holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidateItem {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<String>,
+}
+
+impl PageCandidates {
+    /// Apply `scrub` to every text that came from the page
+    pub fn map_text(&mut self, scrub: impl Fn(&str) -> String) {
+        self.title = scrub(&self.title);
+        self.text = scrub(&self.text);
+        for value in self.meta.values_mut() {
+            *value = scrub(value);
+        }
+        for item in self.groups.iter_mut().flat_map(|group| group.items.iter_mut()) {
+            item.text = scrub(&item.text);
+        }
+        for block in &mut self.json_ld {
+            map_strings(block, &scrub);
+        }
+    }
+}
+
+fn map_strings(value: &mut serde_json::Value, scrub: &impl Fn(&str) -> String) {
+    match value {
+        serde_json::Value::String(text) => *text = scrub(text),
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|item| map_strings(item, scrub)),
+        serde_json::Value::Object(fields) => fields.values_mut().for_each(|field| map_strings(field, scrub)),
+        _ => {}
+    }
+}
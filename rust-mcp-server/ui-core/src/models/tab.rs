@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// A browser tab of a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabInfo {
+    /// Number for `switch_tab` and `close_tab`; tabs are numbered from 1 in the order they opened
+    pub tab_id: u32,
+
+    pub url: String,
+
+    pub title: String,
+
+    /// Actions and context reads go to this tab
+    #[serde(default)]
+    pub active: bool,
+
+    /// Tab whose page opened this one, e.g. through a `target="_blank"` link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opener_tab_id: Option<u32>,
+
+    /// A window the page opened with a handle back to itself, like an OAuth sign-in window;
+    /// it becomes the active tab when it opens and hands back to its opener when it closes
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub popup: bool,
+}
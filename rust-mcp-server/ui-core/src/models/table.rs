@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{parse_date, DateOrder, ParsedDate};
+
+/// Rows extracted from an HTML table, across all visited pages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractedTable {
+    /// Column names (generated as "column_N" when the table has no header)
+    pub headers: Vec<String>,
+
+    /// Cell text, one entry per header; spanned cells are repeated
+    pub rows: Vec<Vec<String>>,
+
+    /// Number of pages read (more than 1 when pagination was followed)
+    pub pages: u32,
+
+    /// Dates/times found in each row, keyed by header; aligned with `rows`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsed: Vec<BTreeMap<String, ParsedDate>>,
+}
+
+impl ExtractedTable {
+    /// Rows as objects keyed by header
+    pub fn records(&self) -> Vec<serde_json::Map<String, serde_json::Value>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                self.headers
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(header, cell)| (header.clone(), serde_json::Value::String(cell.clone())))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Fill `parsed` from the cell text; left empty when no cell holds a date
+    pub fn parse_dates(&mut self, order: DateOrder) {
+        let parsed: Vec<BTreeMap<String, ParsedDate>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                self.headers
+                    .iter()
+                    .zip(row.iter())
+                    .filter_map(|(header, cell)| Some((header.clone(), parse_date(cell, order)?)))
+                    .collect()
+            })
+            .collect();
+
+        self.parsed = if parsed.iter().all(BTreeMap::is_empty) {
+            Vec::new()
+        } else {
+            parsed
+        };
+    }
+
+    /// RFC 4180 CSV with a header line
+    pub fn to_csv(&self) -> String {
+        fn field(value: &str) -> String {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.to_string()
+            }
+        }
+
+        let mut csv = String::new();
+        for line in std::iter::once(&self.headers).chain(self.rows.iter()) {
+            let fields: Vec<String> = line.iter().map(|v| field(v)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_quoting() {
+        let table = ExtractedTable {
+            headers: vec!["Name".to_string(), "Note".to_string()],
+            rows: vec![vec!["Ann".to_string(), "says \"hi\", twice".to_string()]],
+            pages: 1,
+            parsed: Vec::new(),
+        };
+
+        assert_eq!(table.to_csv(), "Name,Note\r\nAnn,\"says \"\"hi\"\", twice\"\r\n");
+        assert_eq!(table.records()[0]["Note"], "says \"hi\", twice");
+    }
+
+    #[test]
+    fn test_parse_dates() {
+        let mut table = ExtractedTable {
+            headers: vec!["Order".to_string(), "Placed".to_string()],
+            rows: vec![
+                vec!["A-1".to_string(), "04.03.2025 14:05".to_string()],
+                vec!["A-2".to_string(), "pending".to_string()],
+            ],
+            pages: 1,
+            parsed: Vec::new(),
+        };
+
+        table.parse_dates(DateOrder::Dmy);
+        assert_eq!(table.parsed.len(), 2);
+        assert_eq!(table.parsed[0]["Placed"].iso, "2025-03-04T14:05:00");
+        assert!(table.parsed[1].is_empty());
+    }
+}
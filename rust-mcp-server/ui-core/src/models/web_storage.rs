@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// `localStorage` or `sessionStorage`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebStorageArea {
+    #[default]
+    Local,
+    Session,
+}
+
+impl WebStorageArea {
+    /// The page's global for the area
+    pub fn global(self) -> &'static str {
+        match self {
+            Self::Local => "localStorage",
+            Self::Session => "sessionStorage",
+        }
+    }
+}
+
+/// Web storage of the current page's origin
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebStorageContents {
+    /// e.g. "https://app.example.com"; "null" for pages without storage such as about:blank
+    pub origin: String,
+
+    pub local_storage: BTreeMap<String, String>,
+
+    pub session_storage: BTreeMap<String, String>,
+}
+
+impl WebStorageContents {
+    pub fn area(&self, area: WebStorageArea) -> &BTreeMap<String, String> {
+        match area {
+            WebStorageArea::Local => &self.local_storage,
+            WebStorageArea::Session => &self.session_storage,
+        }
+    }
+}
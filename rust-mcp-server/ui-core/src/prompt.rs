@@ -0,0 +1,856 @@
+use crate::models::{Assertion, ElementNeighborhood, SimplifiedElement, UIContext};
+
+use super::diff::ContextDiff;
+
+/// JavaScript errors quoted in a retry prompt; the latest ones are the likeliest causes
+const MAX_PAGE_ERRORS: usize = 5;
+
+/// Build system prompt for the UI automation agent
+pub fn build_system_prompt() -> String {
+    r#"You are a UI automation agent that controls a web browser to accomplish user tasks.
+
+Your capabilities:
+1. You can see the current page context as an Accessibility Tree (AXTree)
+2. You can execute actions: click, type, select_option, set_checked, focus, press_key, scroll, wait_for_element, wait_for_navigation, wait_for_network_idle, wait_for_url, wait_for_text, navigate, go_back, go_forward, reload, find_text, set_zoom, restore_overlay, wait_for_download, fill_form, login, get_otp, invoke_hook, handle_dialog, get_storage, set_storage, new_tab, switch_tab, close_tab
+3. You receive smart feedback when actions fail with suggestions for recovery
+
+Action Format (respond in JSON):
+{
+  "tool": "click" | "type" | "select_option" | "set_checked" | "focus" | "press_key" | "scroll" | "wait_for_element" | "wait_for_navigation" | "wait_for_network_idle" | "wait_for_url" | "wait_for_text" | "navigate" | "go_back" | "go_forward" | "reload" | "find_text" | "set_zoom" | "restore_overlay" | "wait_for_download" | "fill_form" | "login" | "get_otp" | "invoke_hook" | "handle_dialog" | "get_storage" | "set_storage" | "new_tab" | "switch_tab" | "close_tab",
+  "element_id": number (the [N] of an element in the list; targets exactly that element, role and name may then be left out),
+  "role": "button" | "link" | "textbox" | "combobox" | etc (for scroll: a container such as a chat pane or list, optional),
+  "name": "element name from AXTree (for invoke_hook: the hook name)",
+  "text": "text to type (for type action; for wait_for_text: the text to wait for)",
+  "label": "visible text of the option to choose (for select_option; or give its \"value\")",
+  "checked": true | false (for set_checked; clicks only if the checkbox/radio/switch is not already in that state),
+  "key": "Enter" | "Tab" | "Escape" | "ArrowDown" | "Control+a" | a single character | etc (for press_key; goes to the focused element; for get_storage / set_storage: the storage key, all keys when get_storage leaves it out),
+  "modifiers": ["control" | "shift" | "alt" | "meta"] (for press_key, optional),
+  "direction": "up" | "down" | "left" | "right" (for scroll),
+  "amount": number (for scroll, optional),
+  "url": "URL to navigate to (for navigate; for new_tab: the URL to open, optional)",
+  "query": "text to search for (for find_text; scrolls to the first match and lists nearby elements)",
+  "factor": number (for set_zoom; e.g. 0.5 to fit wide tables, 1.0 to reset),
+  "overlay_id": number (for restore_overlay),
+  "filename_pattern": "glob such as *.csv (for wait_for_download, optional)",
+  "pattern": "URL glob such as */orders/* (for wait_for_url)",
+  "timeout_ms": number (for wait_for_element / wait_for_download / get_otp and the other waits, optional),
+  "fields": {"field label": "value"} (for fill_form; checkboxes take "yes"/"no"),
+  "submit": boolean (for fill_form, optional),
+  "credential_name": "name of a stored credential (for login; never type passwords yourself)",
+  "provider": "name of an OTP provider (for get_otp; the code is typed into the page's code field)",
+  "args": [JSON values] (for invoke_hook, optional; only hooks the operator registered for this site exist),
+  "accept": true | false (for handle_dialog; OK or Cancel on the next alert/confirm/prompt),
+  "prompt_text": "text to enter into the next prompt dialog (for handle_dialog, optional)",
+  "area": "local" | "session" (for get_storage / set_storage: localStorage or sessionStorage, default local),
+  "value": "text to store (for set_storage; null removes the key)",
+  "tab_id": number (for switch_tab; for close_tab: the tab to close, the active one if left out)
+}
+
+Guidelines:
+1. Target elements by role + name from the AXTree context; when several elements share them, use "element_id" instead
+2. Prefer elements that are in_viewport: true
+3. If an element is not in viewport, scroll to it first
+4. If an action fails, read the suggestion in the error response
+5. Be precise with element names - match exactly as shown in the AXTree
+6. ARIA states are shown in brackets after the element (e.g. [expanded], [selected], [checked]).
+   Do not re-open a menu that is already expanded or click a tab that is already selected
+7. Newsletter popups and chat widgets may have been closed for you (listed under Closed Overlays).
+   Use restore_overlay only if the task needs one of them
+8. Elements inside iframes end in [frame=0] (or [frame=0.1] for nested frames). Pass the path
+   back with the action as "frame": [0] (or [0, 1]); without it only the top page is searched
+9. JavaScript dialogs are answered automatically and listed under "dialogs" in the action's result.
+   To answer one differently (e.g. cancel a "Discard changes?" confirm), send handle_dialog before the action that opens it
+10. Links that open a new tab are listed under "opened_tabs" in the action's result; the page you see stays the same
+   until you switch_tab to it. Popups such as "Sign in with Google" windows are switched to for you, and the page
+   that opened them comes back once they close. Once more than one tab is open, all of them are listed under Tabs
+
+Example AXTree format:
+[1] Button('Login') - in_viewport: true
+[2] Textbox('Username') - in_viewport: true
+[3] Textbox('Password') - in_viewport: false
+[4] Tab('Billing') [selected] - in_viewport: true
+
+Example actions:
+- Click login button: {"tool": "click", "role": "button", "name": "Login"}
+- Type username: {"tool": "type", "role": "textbox", "name": "Username", "text": "john@example.com"}
+- Choose a country: {"tool": "select_option", "role": "combobox", "name": "Country", "label": "Germany"}
+- Click the second of two "Delete" buttons: {"tool": "click", "element_id": 7}
+- Accept the terms: {"tool": "set_checked", "role": "checkbox", "name": "I agree to the terms", "checked": true}
+- Pay inside an embedded payment form listed as [frame=0]: {"tool": "click", "role": "button", "name": "Pay now", "frame": [0]}
+- Submit the focused search box: {"tool": "press_key", "key": "Enter"}
+- Send the focused message: {"tool": "press_key", "key": "Enter", "modifiers": ["control"]}
+- Scroll to see password field: {"tool": "scroll", "direction": "down", "amount": 300}
+- Load older chat messages: {"tool": "scroll", "direction": "up", "amount": 500, "role": "log", "name": "Messages"}
+- Find the refunds section: {"tool": "find_text", "query": "refund"}
+- Return to the search results: {"tool": "go_back"}
+- Fill a whole form at once: {"tool": "fill_form", "fields": {"Email": "john@example.com", "Password": "secret"}, "submit": true}
+- Log in with a stored credential: {"tool": "login", "credential_name": "staging-admin"}
+- Get the 2FA code after logging in: {"tool": "get_otp", "provider": "staging-sms"}
+- Reset test data before starting: {"tool": "invoke_hook", "name": "reset-db"}
+- After clicking "Export CSV": {"tool": "wait_for_download", "filename_pattern": "*.csv"}
+- Wait for the app to route to the order page after saving: {"tool": "wait_for_url", "pattern": "*/orders/*"}
+- Wait for search results to arrive: {"tool": "wait_for_text", "text": "results for"}
+- Keep the item when "Delete" asks for confirmation: {"tool": "handle_dialog", "accept": false}
+- Check which theme the app saved: {"tool": "get_storage", "key": "theme"}
+- Continue in the invoice a link opened as tab 2: {"tool": "switch_tab", "tab_id": 2}
+
+IMPORTANT: Respond ONLY with a single valid JSON action object. No explanations, no markdown, just JSON."#.to_string()
+}
+
+/// Build system prompt for runs restricted to the keyboard (accessibility testing)
+///
+/// The agent cannot click or scroll; it has to reach every control with Tab and operate it
+/// with Enter, Space and the arrow keys, the way a keyboard-only user would.
+pub fn build_keyboard_only_system_prompt() -> String {
+    r#"You are a UI automation agent testing whether a web page can be used with the keyboard alone.
+
+Your capabilities:
+1. You can see the current page context as an Accessibility Tree (AXTree); the element that has
+   keyboard focus is marked [focused] and named in the "Focused Element" line
+2. You can execute actions: press_key, focus, type, wait_for_element, navigate, go_back, go_forward, reload, find_text, wait_for_download
+3. Mouse actions (click, scroll, fill_form, login) are disabled and fail with error "keyboard_only"
+
+Action Format (respond in JSON):
+{
+  "tool": "press_key" | "focus" | "type" | "wait_for_element" | "navigate" | "go_back" | "go_forward" | "reload" | "find_text" | "wait_for_download",
+  "key": "Tab" | "Shift+Tab" | "Enter" | "Space" | "Escape" | "ArrowUp" | "ArrowDown" | "ArrowLeft" | "ArrowRight" | "Home" | "End" | "PageUp" | "PageDown" | "Control+a" | etc (for press_key),
+  "modifiers": ["control" | "shift" | "alt" | "meta"] (for press_key, optional),
+  "role": "role of the element (for focus; for type: of the focused element)",
+  "name": "name of the element (for focus; for type: of the focused element)",
+  "text": "text to type (for type; only into the element that has focus)",
+  "url": "URL to navigate to (for navigate)",
+  "query": "text to search for (for find_text)",
+  "filename_pattern": "glob such as *.csv (for wait_for_download, optional)",
+  "timeout_ms": number (for wait_for_element / wait_for_download, optional)
+}
+
+Guidelines:
+1. Move focus with Tab (forward) and Shift+Tab (backward); check the Focused Element after each step
+2. Activate links and buttons with Enter, toggle checkboxes with Space, and move within menus,
+   tabs, radio groups and lists with the arrow keys
+3. Type only after the target field has focus
+4. Close dialogs and menus with Escape
+5. Use focus only where a real page offers a shortcut to it (e.g. a skip link target); reach everything else with Tab
+6. If focus does not reach a control, or an action fails with "focus_trap", say so instead of working around it
+
+Example actions:
+- Move to the next control: {"tool": "press_key", "key": "Tab"}
+- Go back one control: {"tool": "press_key", "key": "Shift+Tab"}
+- Type into the focused email field: {"tool": "type", "role": "textbox", "name": "Email", "text": "john@example.com"}
+- Press the focused button: {"tool": "press_key", "key": "Enter"}
+
+IMPORTANT: Respond ONLY with a single valid JSON action object. No explanations, no markdown, just JSON."#.to_string()
+}
+
+/// Build user prompt with current UI context and task
+///
+/// With `max_tokens`, the element list is trimmed so the prompt stays within
+/// about that many tokens (see `element_list`).
+pub fn build_user_prompt(context: &UIContext, task: &str, max_tokens: Option<usize>) -> String {
+    let render = |elements: &str| {
+        format!(
+            r#"Current Page State:
+URL: {}
+Title: {}
+Viewport: {}x{} (scroll: {}, {}){}{}
+
+Available Elements (Accessibility Tree):
+{}{}{}
+
+Your Task: {}
+
+Please provide the NEXT SINGLE ACTION to accomplish this task as a JSON object."#,
+            context.url,
+            context.title,
+            context.viewport.width,
+            context.viewport.height,
+            context.viewport.scroll_x,
+            context.viewport.scroll_y,
+            format_navigation_history(context),
+            format_focus(context),
+            elements,
+            format_closed_overlays(context),
+            format_tabs(context),
+            task
+        )
+    };
+
+    let budget = max_tokens.map(|max| max.saturating_sub(estimate_tokens(&render(""))));
+    render(&element_list(context, budget))
+}
+
+/// Build a follow-up prompt listing only the elements that changed since the previous prompt
+///
+/// Only valid right after a prompt for the same page in the same conversation.
+pub fn build_diff_prompt(context: &UIContext, diff: &ContextDiff, task: &str) -> String {
+    let changes = if diff.is_empty() {
+        "No elements changed since your last action.".to_string()
+    } else {
+        let mut changes = String::new();
+        if !diff.removed.is_empty() {
+            changes.push_str(&format!("Removed Elements:\n{}\n\n", diff.removed.join("\n")));
+        }
+        if !diff.added.is_empty() {
+            changes.push_str(&format!("New or Changed Elements:\n{}\n\n", diff.added.join("\n")));
+        }
+        changes.push_str("All other elements are unchanged from the previous page state.");
+        changes
+    };
+
+    format!(
+        r#"Page Update Since Your Last Action:
+URL: {} (unchanged)
+Title: {}
+Viewport: {}x{} (scroll: {}, {}){}
+
+{}{}{}
+
+Your Task: {}
+
+Please provide the NEXT SINGLE ACTION to accomplish this task as a JSON object."#,
+        context.url,
+        context.title,
+        context.viewport.width,
+        context.viewport.height,
+        context.viewport.scroll_x,
+        context.viewport.scroll_y,
+        format_focus(context),
+        changes,
+        format_closed_overlays(context),
+        format_tabs(context),
+        task
+    )
+}
+
+/// System prompt addition explaining the follow-up prompts of `build_diff_prompt`
+pub fn diff_prompt_instructions() -> &'static str {
+    r#"
+
+Page Updates:
+After your first action you may receive a "Page Update Since Your Last Action" instead of the full page state. It lists only removed and new or changed elements; every other element from the most recent full page state is still present with the same ID. A changed element appears under Removed with its old line and under New with its new line."#
+}
+
+/// System prompt addition for prompts that come with a screenshot of the viewport
+pub fn vision_prompt_instructions() -> &'static str {
+    r#"
+
+Screenshot:
+Each page state comes with a screenshot of the visible part of the page. Interactive elements are outlined, with their element ID in the box's corner label. Use it to understand what the element list leaves out: canvas drawings, icon-only buttons, badly labeled controls and where elements sit relative to each other. Still target elements from the element list: the label [12] is the element listed as [12]."#
+}
+
+/// System prompt addition for servers that let the agent run JavaScript in the page
+pub fn evaluate_prompt_instructions() -> &'static str {
+    r#"
+
+Scripts:
+One more tool is available: {"tool": "evaluate", "script": "..."} runs JavaScript in the page and returns its JSON value under "result" (a returned promise is awaited). Use it only for what the other tools cannot do, such as reading data the element list leaves out. Click and type with the regular tools, so the page reacts as it would to a user."#
+}
+
+/// System prompt addition listing the checks that end a run
+pub fn success_criteria_instructions(assertions: &[Assertion]) -> String {
+    let criteria: Vec<String> = assertions.iter().map(|assertion| format!("- {}", assertion.describe())).collect();
+    format!(
+        "\n\nSuccess Criteria:\nThe task is done as soon as all of these hold; they are checked after every action:\n{}",
+        criteria.join("\n")
+    )
+}
+
+/// One element as listed in prompts
+pub fn element_line(element: &SimplifiedElement) -> String {
+    format!("{} - in_viewport: {}", element.display, element.in_viewport)
+}
+
+/// Rough token count (about four characters per token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// The elements of a context, one per line, within about `budget` tokens if given
+///
+/// A list over budget keeps the elements that help most: the focused element,
+/// then interactive elements, headings, other elements in the viewport and the
+/// rest, with in-viewport and page order breaking ties. They are listed in page
+/// order, followed by a line counting the elements left out. The same context
+/// and budget always give the same list.
+fn element_list(context: &UIContext, budget: Option<usize>) -> String {
+    let lines: Vec<String> = context.elements.iter().map(element_line).collect();
+    let full = lines.join("\n");
+    let Some(budget) = budget.filter(|budget| estimate_tokens(&full) > *budget) else {
+        return full;
+    };
+
+    let focused = context.focused.as_ref().and_then(|focused| focused.element_id);
+    let tier = |element: &SimplifiedElement| match element {
+        e if Some(e.id) == focused => 0,
+        e if e.is_interactive() => 1,
+        e if e.selector.role == "heading" => 2,
+        e if e.in_viewport => 3,
+        _ => 4,
+    };
+    let mut ranked: Vec<usize> = (0..context.elements.len()).collect();
+    ranked.sort_by_key(|&index| {
+        let element = &context.elements[index];
+        (tier(element), !element.in_viewport, index)
+    });
+
+    // Room for the marker line, sized for the largest count it can show
+    let mut remaining = budget.saturating_sub(estimate_tokens(&omitted_marker(lines.len())) + 1);
+    let mut keep = vec![false; lines.len()];
+    for index in ranked {
+        // +1 for the line break
+        let cost = estimate_tokens(&lines[index]) + 1;
+        if cost > remaining {
+            break;
+        }
+        remaining -= cost;
+        keep[index] = true;
+    }
+
+    let omitted = keep.iter().filter(|kept| !**kept).count();
+    let mut list: Vec<&str> = lines
+        .iter()
+        .zip(&keep)
+        .filter(|(_, kept)| **kept)
+        .map(|(line, _)| line.as_str())
+        .collect();
+    let marker = omitted_marker(omitted);
+    list.push(&marker);
+    list.join("\n")
+}
+
+fn omitted_marker(count: usize) -> String {
+    let noun = if count == 1 { "element" } else { "elements" };
+    format!("({} {} omitted, scroll or filter to see more)", count, noun)
+}
+
+/// Render the focused element as an extra prompt line (empty if focus is on the page)
+fn format_focus(context: &UIContext) -> String {
+    let Some(focused) = &context.focused else {
+        return String::new();
+    };
+
+    let label = format!("{}('{}')", focused.role, focused.name.as_deref().unwrap_or(""));
+    match focused.element_id.filter(|id| context.elements.iter().any(|e| e.id == *id)) {
+        Some(id) => format!("\nFocused Element: [{}] {}", id, label),
+        None => format!("\nFocused Element: {} (not in the element list)", label),
+    }
+}
+
+/// Render automatically closed overlays as an extra prompt section (empty if none)
+fn format_closed_overlays(context: &UIContext) -> String {
+    if context.closed_overlays.is_empty() {
+        return String::new();
+    }
+
+    let overlays: Vec<String> = context
+        .closed_overlays
+        .iter()
+        .map(|o| format!("[{}] {:?} '{}'", o.id, o.kind, o.label))
+        .collect();
+
+    format!(
+        "\n\nClosed Overlays (restore with {{\"tool\": \"restore_overlay\", \"overlay_id\": N}}):\n{}",
+        overlays.join("\n")
+    )
+}
+
+/// Render the session's tabs as an extra prompt section (empty if only one is open)
+fn format_tabs(context: &UIContext) -> String {
+    if context.tabs.len() < 2 {
+        return String::new();
+    }
+
+    let tabs: Vec<String> = context
+        .tabs
+        .iter()
+        .map(|tab| {
+            let popup = if tab.popup { " (popup)" } else { "" };
+            let active = if tab.active { " (active)" } else { "" };
+            format!("[{}] '{}' {}{}{}", tab.tab_id, tab.title, tab.url, popup, active)
+        })
+        .collect();
+
+    format!(
+        "\n\nTabs (switch with {{\"tool\": \"switch_tab\", \"tab_id\": N}}):\n{}",
+        tabs.join("\n")
+    )
+}
+
+/// Render the navigation breadcrumb as an extra prompt line (empty if no history)
+fn format_navigation_history(context: &UIContext) -> String {
+    // The last entry is the current page, which is already shown above
+    let previous: Vec<String> = context
+        .navigation_history
+        .iter()
+        .filter(|entry| entry.url != context.url)
+        .map(|entry| match &entry.title {
+            Some(title) if !title.is_empty() => format!("{} ({})", title, entry.url),
+            _ => entry.url.clone(),
+        })
+        .collect();
+
+    if previous.is_empty() {
+        return String::new();
+    }
+
+    format!("\nRecently Visited: {} -> (current page)", previous.join(" -> "))
+}
+
+/// Render the page's JavaScript errors as an extra prompt section (empty if none)
+fn format_page_errors(errors: &[String]) -> String {
+    if errors.is_empty() {
+        return String::new();
+    }
+
+    let skipped = errors.len().saturating_sub(MAX_PAGE_ERRORS);
+    let mut lines: Vec<String> = errors[skipped..].iter().map(|error| format!("- {}", error)).collect();
+    if skipped > 0 {
+        lines.insert(0, format!("- ({} earlier errors omitted)", skipped));
+    }
+
+    format!("\n\nJavaScript errors on the page while it ran:\n{}", lines.join("\n"))
+}
+
+/// Build prompt after action failure to enable self-correction
+///
+/// `page_errors` are the JavaScript errors the page logged while the action
+/// ran, oldest first. `max_tokens` trims the element list as in `build_user_prompt`.
+pub fn build_retry_prompt(
+    context: &UIContext,
+    task: &str,
+    failed_action: &str,
+    error_message: &str,
+    suggestion: &str,
+    page_errors: &[String],
+    max_tokens: Option<usize>,
+) -> String {
+    let page_errors = format_page_errors(page_errors);
+    let render = |elements: &str| {
+        format!(
+            r#"Current Page State:
+URL: {}
+Title: {}
+
+Available Elements (Accessibility Tree):
+{}
+
+Your Task: {}
+
+Previous Action Attempted:
+{}
+
+This action failed with error: {}{}
+
+Suggestion: {}
+
+Based on this feedback, please provide the CORRECTED NEXT ACTION as a JSON object."#,
+            context.url,
+            context.title,
+            elements,
+            task,
+            failed_action,
+            error_message,
+            page_errors,
+            suggestion
+        )
+    };
+
+    let budget = max_tokens.map(|max| max.saturating_sub(estimate_tokens(&render(""))));
+    render(&element_list(context, budget))
+}
+
+/// Ask the LLM why its attempts at a step keep failing, before it picks the next one
+///
+/// `attempts` are the failed actions (as JSON) with their errors, oldest first;
+/// `feedback` is the server's suggestion after the last one. The answer is a
+/// JSON object with `wrong_assumption` and `alternative`.
+pub fn build_reflection_prompt(
+    context: &UIContext,
+    task: &str,
+    attempts: &[(String, String)],
+    feedback: &str,
+    max_tokens: Option<usize>,
+) -> String {
+    let attempts: Vec<String> = attempts
+        .iter()
+        .enumerate()
+        .map(|(i, (action, error))| format!("{}. {} -> failed: {}", i + 1, action, error))
+        .collect();
+    let render = |elements: &str| {
+        format!(
+            r#"Your Task: {}
+
+Current Page State:
+URL: {}
+Title: {}
+
+Available Elements (Accessibility Tree):
+{}
+
+Your attempts at the current step, all of which failed:
+{}
+
+Server feedback on the last attempt: {}
+
+Before trying again, reflect: what did you assume about this page that turned out to be wrong, and what different approach could work? Do not repeat an attempt that already failed.
+
+Respond with a JSON object:
+{{
+  "wrong_assumption": "one sentence: the belief about the page the attempts relied on",
+  "alternative": "one or two sentences: a concrete different approach, naming elements by ID where possible"
+}}
+
+IMPORTANT: Respond ONLY with valid JSON."#,
+            task,
+            context.url,
+            context.title,
+            elements,
+            attempts.join("\n"),
+            feedback
+        )
+    };
+
+    let budget = max_tokens.map(|max| max.saturating_sub(estimate_tokens(&render(""))));
+    render(&element_list(context, budget))
+}
+
+/// Describe the surroundings of candidate elements so the agent can tell similar targets apart
+pub fn build_neighborhood_hint(neighborhoods: &[ElementNeighborhood]) -> String {
+    if neighborhoods.is_empty() {
+        return String::new();
+    }
+
+    let mut hint = String::from(
+        "\n\nThe target is ambiguous or keeps failing. Candidate elements and their surroundings:\n",
+    );
+
+    for n in neighborhoods {
+        hint.push_str(&format!(
+            "\n[{}] {}('{}')\n",
+            n.element_id,
+            n.role,
+            n.name.as_deref().unwrap_or("")
+        ));
+
+        if !n.ancestors.is_empty() {
+            let ancestors: Vec<String> = n
+                .ancestors
+                .iter()
+                .map(|a| match &a.name {
+                    Some(name) => format!("{}('{}')", a.role, name),
+                    None => a.role.clone(),
+                })
+                .collect();
+            hint.push_str(&format!("  inside: {}\n", ancestors.join(" < ")));
+        }
+        if let Some(heading) = n.headings.first() {
+            hint.push_str(&format!("  under heading: {}\n", heading));
+        }
+        if !n.siblings.is_empty() {
+            let siblings: Vec<String> = n
+                .siblings
+                .iter()
+                .map(|s| format!("{}('{}')", s.role, s.name.as_deref().unwrap_or("")))
+                .collect();
+            hint.push_str(&format!("  next to: {}\n", siblings.join(", ")));
+        }
+        if let Some(text) = &n.nearby_text {
+            hint.push_str(&format!("  nearby text: \"{}\"\n", text));
+        }
+    }
+
+    hint.push_str("\nUse a more specific name (or the surrounding context) to target the right element.");
+    hint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AriaStates, FocusedElement, NavigationEntry, SimplifiedElement, TabInfo, Viewport};
+
+    #[test]
+    fn test_build_system_prompt() {
+        let prompt = build_system_prompt();
+        assert!(prompt.contains("UI automation agent"));
+        assert!(prompt.contains("JSON"));
+    }
+
+    #[test]
+    fn test_keyboard_only_prompt_disables_mouse_actions() {
+        let prompt = build_keyboard_only_system_prompt();
+        assert!(prompt.contains("\"press_key\""));
+        assert!(prompt.contains("Shift+Tab"));
+        assert!(!prompt.contains("\"click\""));
+    }
+
+    #[test]
+    fn test_build_user_prompt() {
+        let context = UIContext {
+            url: "http://localhost:3000".to_string(),
+            title: "Test Page".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements: vec![
+                SimplifiedElement::new(1, "button", Some("Login"), true),
+            ],
+            navigation_history: vec![],
+            closed_overlays: vec![],
+            tabs: vec![],
+            focused: None,
+        };
+
+        let prompt = build_user_prompt(&context, "Click the login button", None);
+        assert!(prompt.contains("Test Page"));
+        assert!(prompt.contains("[1] Button('Login')"));
+        assert!(prompt.contains("Click the login button"));
+    }
+
+    #[test]
+    fn test_user_prompt_includes_aria_states() {
+        let context = UIContext {
+            url: "http://localhost:3000".to_string(),
+            title: "Settings".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements: vec![
+                SimplifiedElement::new(1, "tab", Some("Billing"), true).with_states(AriaStates {
+                    selected: Some(true),
+                    ..Default::default()
+                }),
+                SimplifiedElement::new(2, "button", Some("Menu"), true).with_states(AriaStates {
+                    expanded: Some(false),
+                    ..Default::default()
+                }),
+            ],
+            navigation_history: vec![],
+            closed_overlays: vec![],
+            tabs: vec![],
+            focused: None,
+        };
+
+        let prompt = build_user_prompt(&context, "Open the billing tab", None);
+        assert!(prompt.contains("[1] Tab('Billing') [selected]"));
+        assert!(prompt.contains("[2] Button('Menu') [collapsed]"));
+    }
+
+    #[test]
+    fn test_user_prompt_names_focused_element() {
+        let mut context = UIContext {
+            url: "http://localhost:3000/login".to_string(),
+            title: "Login".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements: vec![SimplifiedElement::new(1, "textbox", Some("Email"), true)],
+            navigation_history: vec![],
+            closed_overlays: vec![],
+            tabs: vec![],
+            focused: Some(FocusedElement {
+                element_id: Some(1),
+                role: "textbox".to_string(),
+                name: Some("Email".to_string()),
+                in_modal: false,
+            }),
+        };
+
+        let prompt = build_user_prompt(&context, "Log in", None);
+        assert!(prompt.contains("Focused Element: [1] textbox('Email')"));
+
+        context.focused = Some(FocusedElement {
+            element_id: None,
+            role: "generic".to_string(),
+            name: Some("Skip to content".to_string()),
+            in_modal: false,
+        });
+        let prompt = build_user_prompt(&context, "Log in", None);
+        assert!(prompt.contains("Focused Element: generic('Skip to content') (not in the element list)"));
+    }
+
+    #[test]
+    fn test_user_prompt_includes_navigation_breadcrumb() {
+        let visit = |url: &str, title: &str| NavigationEntry {
+            url: url.to_string(),
+            title: Some(title.to_string()),
+            visited_at: chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        };
+
+        let context = UIContext {
+            url: "http://localhost:3000/checkout".to_string(),
+            title: "Checkout".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements: vec![],
+            navigation_history: vec![
+                visit("http://localhost:3000/products", "Products"),
+                visit("http://localhost:3000/cart", "Cart"),
+                visit("http://localhost:3000/checkout", "Checkout"),
+            ],
+            closed_overlays: vec![],
+            tabs: vec![],
+            focused: None,
+        };
+
+        let prompt = build_user_prompt(&context, "Pay for the order", None);
+        assert!(prompt.contains(
+            "Recently Visited: Products (http://localhost:3000/products) -> Cart (http://localhost:3000/cart) -> (current page)"
+        ));
+    }
+
+    #[test]
+    fn test_user_prompt_lists_tabs() {
+        let tab = |tab_id: u32, title: &str, url: &str, active: bool| TabInfo {
+            tab_id,
+            url: url.to_string(),
+            title: title.to_string(),
+            active,
+            opener_tab_id: None,
+            popup: false,
+        };
+
+        let mut context = UIContext {
+            url: "http://localhost:3000/orders".to_string(),
+            title: "Orders".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements: vec![],
+            navigation_history: vec![],
+            closed_overlays: vec![],
+            tabs: vec![tab(1, "Orders", "http://localhost:3000/orders", true)],
+            focused: None,
+        };
+
+        // A single tab is not worth a section
+        assert!(!build_user_prompt(&context, "Open the invoice", None).contains("Tabs"));
+
+        context.tabs.push(tab(2, "Invoice 42", "http://localhost:3000/invoices/42", false));
+        let prompt = build_user_prompt(&context, "Open the invoice", None);
+        assert!(prompt.contains("[1] 'Orders' http://localhost:3000/orders (active)"));
+        assert!(prompt.contains("[2] 'Invoice 42' http://localhost:3000/invoices/42\n"));
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("Click"), 2);
+        assert_eq!(estimate_tokens(&"a".repeat(400)), 100);
+    }
+
+    #[test]
+    fn test_user_prompt_within_token_budget() {
+        // A long article below the fold, then the controls and a heading
+        let mut elements: Vec<SimplifiedElement> = (0..200)
+            .map(|id| SimplifiedElement::new(id, "paragraph", Some("Lorem ipsum dolor sit amet"), false))
+            .collect();
+        elements.push(SimplifiedElement::new(200, "heading", Some("Checkout"), false));
+        elements.push(SimplifiedElement::new(201, "button", Some("Pay now"), false));
+        elements.push(SimplifiedElement::new(202, "textbox", Some("Coupon"), true));
+        let context = UIContext {
+            url: "http://localhost:3000/checkout".to_string(),
+            title: "Checkout".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements,
+            navigation_history: vec![],
+            closed_overlays: vec![],
+            tabs: vec![],
+            focused: None,
+        };
+
+        let full = build_user_prompt(&context, "Pay", None);
+        assert_eq!(build_user_prompt(&context, "Pay", Some(100_000)), full);
+
+        let prompt = build_user_prompt(&context, "Pay", Some(300));
+        assert!(estimate_tokens(&prompt) <= 300);
+        assert_eq!(prompt, build_user_prompt(&context, "Pay", Some(300)));
+        assert!(prompt.contains("[200] Heading('Checkout') - in_viewport: false\n[201] Button('Pay now')"));
+        assert!(prompt.contains("[202] Textbox('Coupon')"));
+        assert!(prompt.contains("[0] Paragraph"));
+        assert!(!prompt.contains("[199] Paragraph"));
+
+        let kept = prompt.matches(" - in_viewport: ").count();
+        assert!(prompt.contains(&format!("({} elements omitted, scroll or filter to see more)", 203 - kept)));
+    }
+
+    #[test]
+    fn test_retry_prompt_quotes_page_errors() {
+        let context = UIContext {
+            url: "http://localhost:3000/cart".to_string(),
+            title: "Cart".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements: vec![SimplifiedElement::new(1, "button", Some("Checkout"), true)],
+            navigation_history: vec![],
+            closed_overlays: vec![],
+            tabs: vec![],
+            focused: None,
+        };
+        let action = r#"{"tool":"click","role":"button","name":"Checkout"}"#;
+
+        let quiet = build_retry_prompt(&context, "Check out", action, "state_unchanged", "retry", &[], None);
+        assert!(!quiet.contains("JavaScript errors"));
+
+        let errors: Vec<String> = (1..=7).map(|n| format!("Uncaught TypeError: error {}", n)).collect();
+        let prompt = build_retry_prompt(&context, "Check out", action, "state_unchanged", "retry", &errors, None);
+        assert!(prompt.contains("JavaScript errors on the page while it ran:\n- (2 earlier errors omitted)\n- Uncaught TypeError: error 3"));
+        assert!(prompt.contains("- Uncaught TypeError: error 7\n\nSuggestion: retry"));
+        assert!(!prompt.contains("error 2\n"));
+    }
+
+    #[test]
+    fn test_reflection_prompt_lists_failed_attempts() {
+        let context = UIContext {
+            url: "http://localhost:3000/cart".to_string(),
+            title: "Cart".to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements: vec![SimplifiedElement::new(1, "button", Some("Proceed to payment"), true)],
+            navigation_history: vec![],
+            closed_overlays: vec![],
+            tabs: vec![],
+            focused: None,
+        };
+        let attempts = vec![
+            (r#"{"tool":"click","role":"button","name":"Checkout"}"#.to_string(), "element_not_found".to_string()),
+            (r#"{"tool":"click","role":"link","name":"Checkout"}"#.to_string(), "element_not_found".to_string()),
+        ];
+
+        let prompt = build_reflection_prompt(&context, "Check out", &attempts, "Use an element from the list", None);
+        assert!(prompt.contains(
+            "1. {\"tool\":\"click\",\"role\":\"button\",\"name\":\"Checkout\"} -> failed: element_not_found\n2. {\"tool\":\"click\",\"role\":\"link\""
+        ));
+        assert!(prompt.contains("Proceed to payment"));
+        assert!(prompt.contains("Server feedback on the last attempt: Use an element from the list"));
+        assert!(prompt.contains("\"wrong_assumption\""));
+    }
+}
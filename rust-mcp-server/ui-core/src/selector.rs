@@ -0,0 +1,272 @@
+use crate::models::{SemanticSelector, SimplifiedElement};
+
+/// Stamped by the element finder so the element can be looked up by CSS
+pub const ELEMENT_ID_ATTRIBUTE: &str = "data-element-id";
+
+/// Build JavaScript to find element by semantic selector
+///
+/// Searches the document at the selector's iframe path, open shadow roots
+/// included; returns null if that frame is gone or cross-origin.
+pub fn find_element_script(selector: &SemanticSelector) -> String {
+    let role = &selector.role;
+    let name = selector.name.as_deref().unwrap_or("");
+    let frame = serde_json::to_string(&selector.frame).unwrap_or_else(|_| "[]".to_string());
+
+    // JavaScript to find element based on role and accessible name
+    format!(
+        r#"
+        (function() {{
+            function getAccessibleName(el) {{
+                if (el.getAttribute('aria-label')) return el.getAttribute('aria-label');
+                const labelledBy = el.getAttribute('aria-labelledby');
+                if (labelledBy) {{
+                    const label = el.getRootNode().getElementById(labelledBy);
+                    if (label) return label.textContent.trim();
+                }}
+                if (el.id) {{
+                    const label = el.getRootNode().querySelector(`label[for="${{el.id}}"]`);
+                    if (label) return label.textContent.trim();
+                }}
+                if (el.placeholder) return el.placeholder;
+                if (el.tagName === 'BUTTON' || el.tagName === 'A') {{
+                    return el.textContent.trim();
+                }}
+                return '';
+            }}
+
+            function getRole(el) {{
+                const ariaRole = el.getAttribute('role');
+                if (ariaRole) return ariaRole;
+
+                const tagRoles = {{
+                    'BUTTON': 'button',
+                    'A': 'link',
+                    'INPUT': el.type === 'submit' ? 'button' : 'textbox',
+                    'TEXTAREA': 'textbox',
+                    'SELECT': 'combobox',
+                }};
+                return tagRoles[el.tagName] || '';
+            }}
+
+            // Every element below `root`, each shadow tree right after its host
+            function deepElements(root) {{
+                const found = [];
+                root.querySelectorAll('*').forEach(el => {{
+                    found.push(el);
+                    if (el.shadowRoot) {{
+                        for (const inner of deepElements(el.shadowRoot)) found.push(inner);
+                    }}
+                }});
+                return found;
+            }}
+
+            const targetRole = '{}';
+            const targetName = '{}';
+            const attr = '{}';
+
+            let doc = document;
+            for (const i of {}) {{
+                const frame = deepElements(doc).filter(el => el.matches('iframe, frame'))[i];
+                try {{
+                    doc = frame ? frame.contentDocument : null;
+                }} catch (e) {{
+                    doc = null;
+                }}
+                if (!doc) return null;
+            }}
+
+            const allElements = deepElements(doc);
+            for (let el of allElements) {{
+                const role = getRole(el);
+                const name = getAccessibleName(el);
+
+                if (role === targetRole && (!targetName || name.includes(targetName))) {{
+                    // Mark element for retrieval
+                    el.setAttribute(attr, Math.random().toString(36));
+                    return el.getAttribute(attr);
+                }}
+            }}
+
+            return null;
+        }})()
+        "#,
+        role, name, ELEMENT_ID_ATTRIBUTE, frame
+    )
+}
+
+/// How well a listed element answers the selector (0-1), or `None` if it can't be the target
+///
+/// Follows the finder script: the frame and role must be the same and the
+/// element's name must contain the selector's name. Exact names score highest,
+/// then names that only differ in case and punctuation, then longer names
+/// containing it; elements in the viewport win ties.
+pub fn match_score(selector: &SemanticSelector, element: &SimplifiedElement) -> Option<f64> {
+    if let Some(element_id) = selector.element_id {
+        return (element.id == element_id).then_some(1.0);
+    }
+    let target = &element.selector;
+    if target.frame != selector.frame || target.role != selector.role {
+        return None;
+    }
+
+    let score = match (selector.name.as_deref(), target.name.as_deref()) {
+        (None | Some(""), _) => 0.5,
+        (Some(wanted), Some(name)) if name == wanted => 1.0,
+        (Some(wanted), Some(name)) if normalize(name) == normalize(wanted) => 0.9,
+        (Some(wanted), Some(name)) if name.contains(wanted) => {
+            0.5 + 0.3 * wanted.chars().count() as f64 / name.chars().count() as f64
+        }
+        _ => return None,
+    };
+    Some(if element.in_viewport { score } else { score - 0.05 })
+}
+
+/// The element of a context the selector targets: the best match, the first
+/// listed among equals
+pub fn find_in_context<'a>(
+    selector: &SemanticSelector,
+    elements: &'a [SimplifiedElement],
+) -> Option<&'a SimplifiedElement> {
+    let mut best: Option<(f64, &SimplifiedElement)> = None;
+    for element in elements {
+        if let Some(score) = match_score(selector, element) {
+            if best.is_none_or(|(top, _)| score > top) {
+                best = Some((score, element));
+            }
+        }
+    }
+    best.map(|(_, element)| element)
+}
+
+/// Lowercase words without punctuation, e.g. "Sign-in →" -> "sign in"
+pub fn normalize(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The better of word overlap and edit-distance likeness, with containment counting high
+pub fn similarity(a: &str, b: &str) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a.contains(b) || b.contains(a) {
+        return 0.8;
+    }
+
+    let words_a: Vec<&str> = a.split(' ').collect();
+    let words_b: Vec<&str> = b.split(' ').collect();
+    let shared = words_a.iter().filter(|word| words_b.contains(word)).count();
+    let overlap = shared as f64 / words_a.len().max(words_b.len()) as f64;
+
+    let longest = a.chars().count().max(b.chars().count());
+    let edits = 1.0 - levenshtein(a, b) as f64 / longest as f64;
+
+    overlap.max(edits)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substituted = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Build CSS selector from semantic selector (fallback)
+pub fn css_selector(selector: &SemanticSelector) -> String {
+    // Try to build a reasonable CSS selector based on role
+    match selector.role.as_str() {
+        "button" => {
+            if let Some(name) = &selector.name {
+                format!("button:contains('{}'), [role='button']:contains('{}')", name, name)
+            } else {
+                "button, [role='button']".to_string()
+            }
+        }
+        "link" => {
+            if let Some(name) = &selector.name {
+                format!("a:contains('{}')", name)
+            } else {
+                "a".to_string()
+            }
+        }
+        "textbox" => "input[type='text'], input:not([type]), textarea, [role='textbox']".to_string(),
+        _ => {
+            if let Some(name) = &selector.name {
+                format!("[role='{}']:contains('{}')", selector.role, name)
+            } else {
+                format!("[role='{}']", selector.role)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selector(role: &str, name: Option<&str>) -> SemanticSelector {
+        SemanticSelector {
+            role: role.to_string(),
+            name: name.map(str::to_string),
+            description: None,
+            css_fallback: None,
+            frame: vec![],
+            element_id: None,
+        }
+    }
+
+    fn element(id: usize, role: &str, name: &str, in_viewport: bool) -> SimplifiedElement {
+        SimplifiedElement {
+            id,
+            display: format!("[{}] {}('{}')", id, role, name),
+            selector: selector(role, Some(name)),
+            in_viewport,
+            states: Default::default(),
+            parsed: None,
+            rect: None,
+            value: None,
+            disabled: false,
+        }
+    }
+
+    #[test]
+    fn test_find_in_context_prefers_exact_visible_names() {
+        let elements = vec![
+            element(1, "button", "Sign in with Google", true),
+            element(2, "link", "Sign in", true),
+            element(3, "button", "Sign in", false),
+            element(4, "button", "Sign in", true),
+            element(5, "button", "sign-in", true),
+        ];
+
+        let found = |role: &str, name: Option<&str>| find_in_context(&selector(role, name), &elements).map(|e| e.id);
+        assert_eq!(found("button", Some("Sign in")), Some(4));
+        assert_eq!(found("button", Some("Google")), Some(1));
+        assert_eq!(found("button", Some("Sign In")), Some(4));
+        assert_eq!(found("button", None), Some(1));
+        assert_eq!(found("textbox", Some("Sign in")), None);
+
+        let mut by_id = selector("", None);
+        by_id.element_id = Some(3);
+        assert_eq!(find_in_context(&by_id, &elements).map(|e| e.id), Some(3));
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(normalize("Sign-in →"), "sign in");
+        assert_eq!(similarity("sign in", "sign in now"), 0.8);
+        assert!(similarity("checkout", "chekout") > 0.8);
+        assert!(similarity("checkout", "newsletter") < 0.5);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::models::{parse_date, AXElement, DateOrder, ElementRect, SimplifiedElement, Viewport};
+
+/// Simplify AX tree for LLM consumption
+pub fn simplify_tree(
+    ax_tree: &[AXElement],
+    viewport: &Viewport,
+    date_order: DateOrder,
+) -> Vec<SimplifiedElement> {
+    ax_tree
+        .iter()
+        .map(|el| {
+            // Check if element is in viewport
+            let in_viewport = if let Some(rect) = &el.rect {
+                is_in_viewport(rect, viewport)
+            } else {
+                false
+            };
+
+            let mut simplified = SimplifiedElement::new(
+                el.id,
+                &el.role,
+                el.name.as_deref(),
+                in_viewport,
+            );
+
+            // Add description to selector if available
+            simplified.selector.description = el.description.clone();
+            simplified.parsed = el.name.as_deref().and_then(|name| parse_date(name, date_order));
+            simplified.rect = el.rect.clone();
+
+            simplified
+                .with_states(el.states.clone())
+                .with_form_state(el.value.clone(), !el.enabled)
+                .with_frame(el.frame.clone())
+        })
+        .collect()
+}
+
+/// Check if element rect is in viewport
+pub fn is_in_viewport(rect: &ElementRect, viewport: &Viewport) -> bool {
+    let viewport_bottom = viewport.scroll_y + viewport.height as f64;
+    let viewport_right = viewport.scroll_x + viewport.width as f64;
+
+    let element_bottom = rect.y + rect.height;
+    let element_right = rect.x + rect.width;
+
+    // Element is in viewport if it overlaps with viewport bounds
+    rect.y < viewport_bottom
+        && element_bottom > viewport.scroll_y
+        && rect.x < viewport_right
+        && element_right > viewport.scroll_x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_in_viewport_accounts_for_scroll() {
+        let viewport = Viewport {
+            width: 1280,
+            height: 720,
+            scroll_x: 0.0,
+            scroll_y: 1000.0,
+        };
+        let rect = |y: f64| ElementRect {
+            x: 10.0,
+            y,
+            width: 100.0,
+            height: 30.0,
+        };
+
+        assert!(is_in_viewport(&rect(1200.0), &viewport));
+        assert!(is_in_viewport(&rect(980.0), &viewport));
+        assert!(!is_in_viewport(&rect(100.0), &viewport));
+        assert!(!is_in_viewport(&rect(1720.0), &viewport));
+    }
+
+    #[test]
+    fn test_geometry_and_form_state() {
+        let viewport = Viewport {
+            width: 1280,
+            height: 720,
+            scroll_x: 0.0,
+            scroll_y: 0.0,
+        };
+        let element = |id, role: &str, name: Option<&str>, value: Option<&str>, enabled| AXElement {
+            id,
+            role: role.to_string(),
+            name: name.map(str::to_string),
+            value: value.map(str::to_string),
+            description: None,
+            enabled,
+            visible: true,
+            rect: Some(ElementRect { x: 10.0, y: 40.0 * id as f64, width: 200.0, height: 30.0 }),
+            states: Default::default(),
+            frame: vec![],
+            children: vec![],
+        };
+        let tree = vec![
+            element(0, "textbox", Some("Email"), Some("ada@example.com"), true),
+            element(1, "button", Some("Submit"), None, false),
+            element(2, "textbox", Some("ada@example.com"), Some("ada@example.com"), true),
+        ];
+
+        let simplified = simplify_tree(&tree, &viewport, DateOrder::Mdy);
+        assert_eq!(simplified[0].display, "[0] Textbox('Email') = 'ada@example.com'");
+        assert_eq!(simplified[0].value.as_deref(), Some("ada@example.com"));
+        assert_eq!(simplified[0].rect.as_ref().map(|r| r.width), Some(200.0));
+        assert_eq!(simplified[1].display, "[1] Button('Submit') [disabled]");
+        assert!(simplified[1].disabled);
+        // A field named by its value doesn't repeat it
+        assert_eq!(simplified[2].display, "[2] Textbox('ada@example.com')");
+
+        let json = serde_json::to_value(&simplified[0]).unwrap();
+        assert!(json.get("disabled").is_none());
+        assert_eq!(json["rect"]["y"], 0.0);
+    }
+
+    #[test]
+    fn test_frame_path_in_display() {
+        let element = SimplifiedElement::new(9, "button", Some("Pay now"), true).with_frame(vec![0, 2]);
+        assert_eq!(element.display, "[9] Button('Pay now') [frame=0.2]");
+        assert_eq!(element.selector.frame, vec![0, 2]);
+
+        let selector = serde_json::to_value(&element.selector).unwrap();
+        assert_eq!(selector["frame"], serde_json::json!([0, 2]));
+        let top = SimplifiedElement::new(1, "button", Some("Back"), true);
+        assert!(serde_json::to_value(&top.selector).unwrap().get("frame").is_none());
+    }
+}
@@ -0,0 +1,370 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::{ActionResponse, ElementRect, ErrorCode, SemanticSelector};
+use crate::selector::{normalize, similarity};
+
+/// Similar elements offered when the target is not found
+const MAX_CANDIDATES: usize = 3;
+
+/// How alike two names must be to count as a likely typo or rewording (0-1)
+const MIN_SIMILARITY: f64 = 0.5;
+
+/// A named element of the page, as listed by `collectElements()`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamedElement {
+    /// Context element ID, where the element is one `get_context()` lists
+    #[serde(default)]
+    pub id: Option<usize>,
+    pub role: String,
+    pub name: Option<String>,
+}
+
+/// A dialog or overlay lying over the target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Blocker {
+    pub role: String,
+    pub name: Option<String>,
+    /// Button of the blocker that likely closes it ("Close", "×", "Accept", ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub close_button: Option<String>,
+}
+
+/// What the page says about an element an action can't use
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ElementFacts {
+    /// Not rendered: display none, visibility hidden or opacity 0
+    pub hidden: bool,
+
+    /// Which of these and where, e.g. "display: none on an ancestor"
+    #[serde(default)]
+    pub hidden_reason: Option<String>,
+
+    /// Collapsed control (aria-expanded="false") whose content holds the element
+    #[serde(default)]
+    pub expander: Option<NamedElement>,
+
+    /// Position relative to the top-left of the viewport
+    #[serde(default)]
+    pub rect: Option<ElementRect>,
+    pub viewport_width: f64,
+    pub viewport_height: f64,
+
+    pub disabled: bool,
+
+    /// The element or its form is marked aria-busy, so it is likely still loading
+    #[serde(default)]
+    pub busy: bool,
+
+    /// Required fields of the element's form that are still empty, by label
+    #[serde(default)]
+    pub missing_fields: Vec<NamedElement>,
+
+    /// Open dialog covering the element's center, or holding the page modal
+    #[serde(default)]
+    pub covered_by: Option<Blocker>,
+}
+
+impl ElementFacts {
+    /// Vertical distance from the visible area: positive below the fold, negative above it, 0 inside
+    pub fn offscreen_by(&self) -> f64 {
+        let Some(rect) = &self.rect else {
+            return 0.0;
+        };
+        if rect.y >= self.viewport_height {
+            rect.y + rect.height - self.viewport_height
+        } else if rect.y + rect.height <= 0.0 {
+            rect.y
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Feedback for a target that isn't on the page, offering the closest matches there are
+///
+/// A candidate is an element with the same name under another role, or one
+/// of any role whose name is close to the asked one (typo, extra word,
+/// different case).
+pub fn not_found(tool: &str, selector: &SemanticSelector, elements: &[NamedElement]) -> ActionResponse {
+    if let Some(id) = selector.element_id {
+        return ActionResponse::error_with_suggestion(
+            ErrorCode::ElementNotFound,
+            &format!("Element [{}] is not on the page anymore", id),
+            "call get_context() for the current element IDs, or target the element by role and name",
+        );
+    }
+    if selector.role.is_empty() {
+        return ActionResponse::error_with_suggestion(
+            ErrorCode::ElementNotFound,
+            "The action names no element",
+            "give the element's role and name, or its element_id from get_context()",
+        );
+    }
+
+    let role = &selector.role;
+    let name = selector.name.as_deref().unwrap_or("unknown");
+    let reason = format!("Could not find {} with name '{}'", role, name);
+
+    let candidates = similar_elements(selector, elements);
+    let Some(best) = candidates.first() else {
+        let same_role = elements.iter().filter(|e| e.role.eq_ignore_ascii_case(role)).count();
+        let suggestion = match same_role {
+            0 => format!("there is no {} on this page; call get_context() to see available elements", role),
+            n => format!("none of the {} {}(s) on this page is named like that; call get_context() to see their names", n, role),
+        };
+        return ActionResponse::error_with_suggestion(ErrorCode::ElementNotFound, &reason, &suggestion);
+    };
+
+    let mut suggestion = format!("did you mean {}", call(tool, best));
+    if candidates.len() > 1 {
+        let others: Vec<String> = candidates[1..].iter().map(|e| call(tool, e)).collect();
+        suggestion.push_str(&format!(" (or {})", others.join(", ")));
+    }
+    suggestion.push('?');
+
+    let mut response = ActionResponse::error_with_suggestion(ErrorCode::ElementNotFound, &reason, &suggestion);
+    response.details = Some(serde_json::json!({ "candidates": candidates }));
+    response
+}
+
+/// Feedback for a target that is found but not rendered
+pub fn not_visible(selector: &SemanticSelector, facts: &ElementFacts) -> ActionResponse {
+    let target = describe(selector);
+    let offscreen = facts.offscreen_by();
+
+    let mut reason = format!(
+        "{} was found but is hidden ({})",
+        target,
+        facts.hidden_reason.as_deref().unwrap_or("not rendered")
+    );
+    if offscreen != 0.0 {
+        reason.push_str(&format!(", {}", distance(offscreen)));
+    }
+
+    let suggestion = match &facts.expander {
+        Some(expander) => format!(
+            "it is inside a collapsed section; call click({}) to expand it, then retry",
+            arguments(&expander.role, expander.name.as_deref())
+        ),
+        // Content that fades in as it scrolls into view
+        None if offscreen != 0.0 => format!(
+            "call scroll(direction=\"{}\", amount={:.0}) to bring it into view, then retry",
+            if offscreen > 0.0 { "down" } else { "up" },
+            offscreen.abs()
+        ),
+        None => format!(
+            "call wait_for_element({}, timeout_ms=5000) in case it is still appearing, or get_context() to find what reveals it",
+            arguments(&selector.role, selector.name.as_deref())
+        ),
+    };
+
+    let mut response = ActionResponse::error_with_suggestion(ErrorCode::ElementNotVisible, &reason, &suggestion);
+    response.details = Some(serde_json::json!({
+        "hidden_reason": facts.hidden_reason,
+        "offscreen_px": offscreen.round(),
+    }));
+    response
+}
+
+/// Feedback for a target under an open dialog, which would take the click instead
+pub fn obstructed(selector: &SemanticSelector, facts: &ElementFacts) -> Option<ActionResponse> {
+    let blocker = facts.covered_by.as_ref()?;
+    let dialog = match &blocker.name {
+        Some(name) => format!("{} '{}'", blocker.role, name),
+        None => blocker.role.clone(),
+    };
+    let reason = format!("{} is covered by {}", describe(selector), dialog);
+    let suggestion = match &blocker.close_button {
+        Some(button) => format!("close the {} first: click({})", blocker.role, arguments("button", Some(button))),
+        None => format!("close the {} first, e.g. press_key(key=\"Escape\"), or act inside it", blocker.role),
+    };
+
+    let mut response = ActionResponse::error_with_suggestion(ErrorCode::ElementObstructed, &reason, &suggestion);
+    response.details = Some(serde_json::json!({ "covered_by": blocker }));
+    Some(response)
+}
+
+/// Feedback for a disabled target, naming what likely enables it
+pub fn not_enabled(selector: &SemanticSelector, facts: &ElementFacts) -> ActionResponse {
+    let reason = format!("{} is disabled", describe(selector));
+
+    let suggestion = if let Some(first) = facts.missing_fields.first() {
+        let labels: Vec<String> = facts
+            .missing_fields
+            .iter()
+            .map(|field| format!("'{}'", field.name.as_deref().unwrap_or(&field.role)))
+            .collect();
+        format!(
+            "the form still has empty required fields ({}); fill them first, starting with type({}, text=...)",
+            labels.join(", "),
+            arguments(&first.role, first.name.as_deref())
+        )
+    } else if facts.busy {
+        format!(
+            "the page is still loading; call wait_for_element({}) and retry",
+            arguments(&selector.role, selector.name.as_deref())
+        )
+    } else {
+        "wait for element to become enabled or check if preconditions are met".to_string()
+    };
+
+    let mut response = ActionResponse::error_with_suggestion(ErrorCode::ElementNotEnabled, &reason, &suggestion);
+    if !facts.missing_fields.is_empty() {
+        response.details = Some(serde_json::json!({ "missing_fields": facts.missing_fields }));
+    }
+    response
+}
+
+fn similar_elements(selector: &SemanticSelector, elements: &[NamedElement]) -> Vec<NamedElement> {
+    let wanted = normalize(selector.name.as_deref().unwrap_or(""));
+    let mut scored: Vec<(f64, &NamedElement)> = elements
+        .iter()
+        .filter_map(|element| {
+            let name = normalize(element.name.as_deref()?);
+            let same_role = element.role.eq_ignore_ascii_case(&selector.role);
+            let score = match (name == wanted, same_role) {
+                // Exact role and name would have been found; only frames or stale IDs get here
+                (true, true) => return None,
+                (true, false) => 0.9,
+                (false, same_role) if !wanted.is_empty() => {
+                    let similarity = similarity(&wanted, &name);
+                    if same_role { similarity } else { similarity * 0.8 }
+                }
+                _ => return None,
+            };
+            (score >= MIN_SIMILARITY).then_some((score, element))
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.id.cmp(&b.1.id)));
+    scored.into_iter().take(MAX_CANDIDATES).map(|(_, e)| e.clone()).collect()
+}
+
+fn describe(selector: &SemanticSelector) -> String {
+    match &selector.name {
+        Some(name) => format!("Element '{}' ({})", name, selector.role),
+        None => format!("The {}", selector.role),
+    }
+}
+
+fn arguments(role: &str, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("role=\"{}\", name=\"{}\"", role, name),
+        None => format!("role=\"{}\"", role),
+    }
+}
+
+fn call(tool: &str, element: &NamedElement) -> String {
+    format!("{}({})", tool, arguments(&element.role, element.name.as_deref()))
+}
+
+fn distance(offscreen: f64) -> String {
+    if offscreen > 0.0 {
+        format!("{:.0}px below the visible area", offscreen)
+    } else {
+        format!("{:.0}px above the visible area", -offscreen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(id: usize, role: &str, name: &str) -> NamedElement {
+        NamedElement { id: Some(id), role: role.to_string(), name: Some(name.to_string()) }
+    }
+
+    fn selector(role: &str, name: &str) -> SemanticSelector {
+        SemanticSelector {
+            role: role.to_string(),
+            name: Some(name.to_string()),
+            description: None,
+            css_fallback: None,
+            frame: Vec::new(),
+            element_id: None,
+        }
+    }
+
+    #[test]
+    fn test_not_found() {
+        let elements = vec![
+            element(0, "link", "Sign in"),
+            element(1, "button", "Sign in with Google"),
+            element(2, "button", "Search"),
+        ];
+
+        let response = not_found("click", &selector("button", "Sign in"), &elements);
+        assert_eq!(response.error, Some(ErrorCode::ElementNotFound));
+        assert_eq!(
+            response.suggestion.as_deref(),
+            Some("did you mean click(role=\"link\", name=\"Sign in\") (or click(role=\"button\", name=\"Sign in with Google\"))?")
+        );
+        assert_eq!(response.details.unwrap()["candidates"][0]["id"], 0);
+
+        let typo = not_found("click", &selector("button", "Serch"), &elements);
+        assert!(typo.suggestion.unwrap().starts_with("did you mean click(role=\"button\", name=\"Search\")"));
+
+        let nothing = not_found("type", &selector("textbox", "Coupon"), &elements);
+        assert_eq!(
+            nothing.suggestion.as_deref(),
+            Some("there is no textbox on this page; call get_context() to see available elements")
+        );
+    }
+
+    #[test]
+    fn test_element_facts() {
+        let below = ElementFacts {
+            hidden: true,
+            hidden_reason: Some("opacity: 0".to_string()),
+            rect: Some(ElementRect { x: 10.0, y: 1500.0, width: 100.0, height: 40.0 }),
+            viewport_width: 1280.0,
+            viewport_height: 720.0,
+            ..Default::default()
+        };
+        assert_eq!(below.offscreen_by(), 820.0);
+        let response = not_visible(&selector("button", "Load more"), &below);
+        assert_eq!(
+            response.reason.as_deref(),
+            Some("Element 'Load more' (button) was found but is hidden (opacity: 0), 820px below the visible area")
+        );
+        assert_eq!(
+            response.suggestion.as_deref(),
+            Some("call scroll(direction=\"down\", amount=820) to bring it into view, then retry")
+        );
+
+        let collapsed = ElementFacts {
+            expander: Some(element(4, "button", "Advanced options")),
+            ..below.clone()
+        };
+        assert!(not_visible(&selector("checkbox", "Debug"), &collapsed)
+            .suggestion
+            .unwrap()
+            .contains("click(role=\"button\", name=\"Advanced options\")"));
+
+        let covered = ElementFacts {
+            covered_by: Some(Blocker {
+                role: "dialog".to_string(),
+                name: Some("Cookie settings".to_string()),
+                close_button: Some("Accept all".to_string()),
+            }),
+            ..Default::default()
+        };
+        let response = obstructed(&selector("button", "Save"), &covered).unwrap();
+        assert_eq!(response.reason.as_deref(), Some("Element 'Save' (button) is covered by dialog 'Cookie settings'"));
+        assert_eq!(
+            response.suggestion.as_deref(),
+            Some("close the dialog first: click(role=\"button\", name=\"Accept all\")")
+        );
+        assert!(obstructed(&selector("button", "Save"), &below).is_none());
+
+        let incomplete = ElementFacts {
+            disabled: true,
+            missing_fields: vec![element(1, "textbox", "Email"), element(2, "textbox", "Password")],
+            ..Default::default()
+        };
+        assert_eq!(
+            not_enabled(&selector("button", "Create account"), &incomplete).suggestion.as_deref(),
+            Some("the form still has empty required fields ('Email', 'Password'); fill them first, starting with type(role=\"textbox\", name=\"Email\", text=...)")
+        );
+    }
+}
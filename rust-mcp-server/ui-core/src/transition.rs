@@ -0,0 +1,287 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::models::{SimplifiedElement, UIContext};
+
+/// Words in new messages that point at rejected input
+const ERROR_WORDS: &[&str] = &[
+    "required",
+    "invalid",
+    "error",
+    "incorrect",
+    "must be",
+    "must contain",
+    "please enter",
+    "please fill",
+    "please select",
+    "not valid",
+    "failed",
+    "try again",
+];
+
+/// Words in new messages or titles that confirm a submission went through
+const SUCCESS_WORDS: &[&str] = &[
+    "thank you",
+    "thanks for",
+    "success",
+    "confirmed",
+    "confirmation",
+    "order placed",
+    "order number",
+    "has been submitted",
+    "has been sent",
+    "has been saved",
+    "has been created",
+    "welcome back",
+    "you're all set",
+];
+
+/// URL path parts of sign-in pages
+const LOGIN_PATHS: &[&str] = &["login", "signin", "sign-in", "sign_in", "log-in", "sso"];
+
+/// Evidence lines kept per transition
+const MAX_EVIDENCE: usize = 5;
+
+/// What an action did to the page, as far as "did my submit work?" goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionKind {
+    /// Ended up on a sign-in page coming from another page
+    LoginRedirect,
+    /// Fields are marked invalid or error messages appeared
+    ValidationErrors,
+    /// A confirmation appeared: "Thank you", "Order placed", a success status
+    Success,
+    /// A different page with no sign of success or failure
+    Navigated,
+    /// Same page, some elements changed
+    Updated,
+    /// Nothing in the element list changed
+    Unchanged,
+}
+
+/// Classification of the change between the page before and after an action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageTransition {
+    pub kind: TransitionKind,
+
+    /// The URL changed, ignoring the fragment
+    pub url_changed: bool,
+
+    /// Elements or URL the classification rests on, e.g. "[3] Textbox('Email') [invalid]"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evidence: Vec<String>,
+}
+
+impl PageTransition {
+    /// Compare the contexts read before and after an action
+    ///
+    /// Checks run from the strongest signal down: a sign-in page after
+    /// leaving another page, then invalid fields and error messages, then
+    /// confirmation messages. Only elements that weren't on the page before
+    /// count as messages, so a static "Required fields are marked *" or a
+    /// "Welcome back" banner seen on every page doesn't decide the outcome.
+    pub fn between(before: &UIContext, after: &UIContext) -> Self {
+        let url_changed = without_fragment(&before.url) != without_fragment(&after.url);
+        let classify = |kind, evidence: Vec<String>| Self {
+            kind,
+            url_changed,
+            evidence: evidence.into_iter().take(MAX_EVIDENCE).collect(),
+        };
+
+        if url_changed && !is_login_page(before) {
+            if let Some(evidence) = login_evidence(after) {
+                return classify(TransitionKind::LoginRedirect, evidence);
+            }
+        }
+
+        let seen: HashSet<(&str, Option<&str>)> = before.elements.iter().map(element_key).collect();
+        let new_messages: Vec<&SimplifiedElement> = after
+            .elements
+            .iter()
+            .filter(|e| !e.is_interactive() && !seen.contains(&element_key(e)))
+            .collect();
+
+        let errors: Vec<String> = after
+            .elements
+            .iter()
+            .filter(|e| e.states.invalid == Some(true))
+            .chain(new_messages.iter().copied().filter(|e| mentions(e, ERROR_WORDS)))
+            .map(|e| e.display.clone())
+            .collect();
+        if !errors.is_empty() {
+            return classify(TransitionKind::ValidationErrors, errors);
+        }
+
+        let mut confirmations: Vec<String> = new_messages
+            .iter()
+            .filter(|e| mentions(e, SUCCESS_WORDS))
+            .map(|e| e.display.clone())
+            .collect();
+        if before.title != after.title && contains_any(&after.title, SUCCESS_WORDS) {
+            confirmations.insert(0, format!("Title '{}'", after.title));
+        }
+        if !confirmations.is_empty() {
+            return classify(TransitionKind::Success, confirmations);
+        }
+
+        if url_changed {
+            return classify(TransitionKind::Navigated, vec![format!("URL {}", after.url)]);
+        }
+
+        let before_displays: HashSet<&str> = before.elements.iter().map(|e| e.display.as_str()).collect();
+        let changed: Vec<String> = after
+            .elements
+            .iter()
+            .filter(|e| !before_displays.contains(e.display.as_str()))
+            .map(|e| e.display.clone())
+            .collect();
+        if changed.is_empty() && before.elements.len() == after.elements.len() {
+            classify(TransitionKind::Unchanged, vec![])
+        } else {
+            classify(TransitionKind::Updated, changed)
+        }
+    }
+
+    /// One line for the agent, e.g. "The page shows validation errors: [3] Textbox('Email') [invalid]"
+    pub fn describe(&self) -> String {
+        let summary = match self.kind {
+            TransitionKind::LoginRedirect => "Redirected to a sign-in page; the session may have expired",
+            TransitionKind::ValidationErrors => "The page shows validation errors; the form was not accepted",
+            TransitionKind::Success => "The page confirms success",
+            TransitionKind::Navigated => "A different page opened, with no sign of success or failure",
+            TransitionKind::Updated => "The page changed",
+            TransitionKind::Unchanged => "Nothing on the page changed",
+        };
+        if self.evidence.is_empty() {
+            summary.to_string()
+        } else {
+            format!("{}: {}", summary, self.evidence.join(", "))
+        }
+    }
+}
+
+fn without_fragment(url: &str) -> &str {
+    url.split('#').next().unwrap_or(url)
+}
+
+fn element_key(element: &SimplifiedElement) -> (&str, Option<&str>) {
+    (element.selector.role.as_str(), element.selector.name.as_deref())
+}
+
+fn mentions(element: &SimplifiedElement, words: &[&str]) -> bool {
+    element.selector.name.as_deref().is_some_and(|name| contains_any(name, words))
+}
+
+fn contains_any(text: &str, words: &[&str]) -> bool {
+    let text = text.to_lowercase();
+    words.iter().any(|word| text.contains(word))
+}
+
+fn is_password_field(element: &SimplifiedElement) -> bool {
+    element.selector.role == "textbox" && mentions(element, &["password"])
+}
+
+fn is_login_page(context: &UIContext) -> bool {
+    login_evidence(context).is_some()
+}
+
+/// Why `context` looks like a sign-in page, if it does
+fn login_evidence(context: &UIContext) -> Option<Vec<String>> {
+    let path = without_fragment(&context.url)
+        .split_once("://")
+        .map_or(context.url.as_str(), |(_, rest)| rest)
+        .to_lowercase();
+    let path = path.split_once('/').map_or("", |(_, path)| path);
+    let login_url = path
+        .split(['/', '?', '&', '='])
+        .any(|part| LOGIN_PATHS.contains(&part));
+    let password = context.elements.iter().find(|e| is_password_field(e));
+
+    if !login_url && password.is_none() {
+        return None;
+    }
+    let mut evidence = vec![format!("URL {}", context.url)];
+    evidence.extend(password.map(|e| e.display.clone()));
+    Some(evidence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AriaStates, Viewport};
+
+    fn context(url: &str, title: &str, elements: Vec<SimplifiedElement>) -> UIContext {
+        UIContext {
+            url: url.to_string(),
+            title: title.to_string(),
+            viewport: Viewport {
+                width: 1280,
+                height: 720,
+                scroll_x: 0.0,
+                scroll_y: 0.0,
+            },
+            elements,
+            navigation_history: vec![],
+            closed_overlays: vec![],
+            tabs: vec![],
+            focused: None,
+        }
+    }
+
+    fn form(email: AriaStates, extra: Option<SimplifiedElement>) -> UIContext {
+        let mut elements = vec![
+            SimplifiedElement::new(0, "heading", Some("Sign up"), true),
+            SimplifiedElement::new(1, "textbox", Some("Email"), true).with_states(email),
+            SimplifiedElement::new(2, "button", Some("Create account"), true),
+        ];
+        elements.extend(extra);
+        context("https://app.example.com/signup", "Sign up", elements)
+    }
+
+    #[test]
+    fn test_between() {
+        let before = form(AriaStates::default(), None);
+
+        let invalid = AriaStates { invalid: Some(true), ..Default::default() };
+        let rejected = PageTransition::between(&before, &form(invalid, None));
+        assert_eq!(rejected.kind, TransitionKind::ValidationErrors);
+        assert!(!rejected.url_changed);
+        assert_eq!(rejected.evidence, vec!["[1] Textbox('Email') [invalid]"]);
+
+        let message = SimplifiedElement::new(3, "alert", Some("Email is required"), true);
+        let rejected = PageTransition::between(&before, &form(AriaStates::default(), Some(message)));
+        assert_eq!(rejected.kind, TransitionKind::ValidationErrors);
+        assert!(rejected.describe().contains("Alert('Email is required')"));
+
+        let welcome = context(
+            "https://app.example.com/welcome",
+            "Thank you for signing up",
+            vec![SimplifiedElement::new(0, "heading", Some("Check your inbox"), true)],
+        );
+        let accepted = PageTransition::between(&before, &welcome);
+        assert_eq!(accepted.kind, TransitionKind::Success);
+        assert_eq!(accepted.evidence, vec!["Title 'Thank you for signing up'"]);
+
+        let login = context(
+            "https://app.example.com/login?next=/signup",
+            "Sign in",
+            vec![SimplifiedElement::new(0, "textbox", Some("Password"), true)],
+        );
+        let redirected = PageTransition::between(&before, &login);
+        assert_eq!(redirected.kind, TransitionKind::LoginRedirect);
+        assert!(redirected.url_changed);
+        // Submitting the sign-in form itself and staying there is no redirect
+        assert_eq!(PageTransition::between(&login, &login).kind, TransitionKind::Unchanged);
+
+        let pricing = context("https://app.example.com/pricing", "Pricing", vec![]);
+        assert_eq!(PageTransition::between(&before, &pricing).kind, TransitionKind::Navigated);
+
+        let anchored = context("https://app.example.com/signup#terms", "Sign up", before.elements.clone());
+        assert_eq!(PageTransition::between(&before, &anchored).kind, TransitionKind::Unchanged);
+
+        let hint = SimplifiedElement::new(3, "generic", Some("Use at least 8 characters"), true);
+        let updated = PageTransition::between(&before, &form(AriaStates::default(), Some(hint)));
+        assert_eq!(updated.kind, TransitionKind::Updated);
+    }
+}